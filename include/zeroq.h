@@ -0,0 +1,28 @@
+/* C entry points of the zeroq shared library. */
+#ifndef ZEROQ_H
+#define ZEROQ_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/*
+ * Returns the shared-memory layout of this build as a NUL-terminated flat
+ * JSON object mapping entry names to integers. The string is owned by the
+ * library and must not be freed.
+ */
+const char *zeroq_layout_descriptor(void);
+
+/*
+ * Compares a golden layout, in the format returned by
+ * zeroq_layout_descriptor(), against this build. Returns the number of
+ * mismatching entries, 0 when the layouts match, or -1 if golden is NULL
+ * or not valid UTF-8.
+ */
+int zeroq_verify_layout(const char *golden);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* ZEROQ_H */
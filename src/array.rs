@@ -0,0 +1,142 @@
+//! Framing of numpy arrays in queue slots for `put_array()` and `get_array()`.
+//!
+//! An array travels as a small header naming its dtype and shape, followed by
+//! its data. The data starts at a multiple of [`ALIGN`] bytes into the payload,
+//! so that arrays read back into a fresh buffer are aligned for any dtype.
+
+use pyo3::buffer::PyBuffer;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyByteArray, PyDict};
+
+/// Marks payloads written by `put_array()`.
+const MAGIC: &[u8; 2] = b"ZA";
+
+/// Alignment of the array data within the payload.
+const ALIGN: usize = 16;
+
+/// Error message for payloads that do not hold an array.
+const NOT_AN_ARRAY: &str = "The message was not put by put_array()";
+
+/// Dtype and shape of an array carried by a message.
+pub struct ArrayHeader {
+    /// The `dtype.str` of the array, e.g. `<f4`.
+    dtype: String,
+    shape: Vec<usize>,
+}
+
+impl ArrayHeader {
+    /// Returns the offset of the array data within the payload.
+    pub fn data_offset(&self) -> usize {
+        let len = MAGIC.len() + 2 + self.dtype.len() + 8 * self.shape.len();
+        len.next_multiple_of(ALIGN)
+    }
+
+    /// Writes the header into the front of `payload`, zero-padded up to
+    /// [`data_offset`](Self::data_offset).
+    pub fn write(&self, payload: &mut [u8]) {
+        let header = &mut payload[..self.data_offset()];
+        header.fill(0);
+        header[..2].copy_from_slice(MAGIC);
+        header[2] = self.shape.len() as u8;
+        header[3] = self.dtype.len() as u8;
+        let mut offset = 4;
+        header[offset..offset + self.dtype.len()].copy_from_slice(self.dtype.as_bytes());
+        offset += self.dtype.len();
+        for dim in &self.shape {
+            header[offset..offset + 8].copy_from_slice(&(*dim as u64).to_le_bytes());
+            offset += 8;
+        }
+    }
+
+    /// Reads the header at the front of `payload`.
+    ///
+    /// # Errors
+    /// Raises `ValueError` if the payload was not written by `put_array()`.
+    pub fn read(payload: &[u8]) -> PyResult<Self> {
+        let invalid = || PyValueError::new_err(NOT_AN_ARRAY);
+        if payload.len() < 4 || &payload[..2] != MAGIC {
+            return Err(invalid());
+        }
+        let (ndim, dtype_len) = (payload[2] as usize, payload[3] as usize);
+        let dims = payload
+            .get(4 + dtype_len..4 + dtype_len + 8 * ndim)
+            .ok_or_else(invalid)?;
+        let dtype = std::str::from_utf8(&payload[4..4 + dtype_len]).map_err(|_| invalid())?;
+        let shape = dims
+            .chunks_exact(8)
+            .map(|dim| u64::from_le_bytes(dim.try_into().unwrap()) as usize)
+            .collect();
+        Ok(Self {
+            dtype: dtype.to_owned(),
+            shape,
+        })
+    }
+}
+
+/// Returns the header of `array` and a byte buffer over its data, in C order.
+///
+/// Arrays that are not C-contiguous are copied once into a contiguous array.
+///
+/// # Errors
+/// Raises `ImportError` if numpy is not installed, or `ValueError` if the dtype holds
+/// Python objects or named fields, which cannot be rebuilt from their `dtype.str`.
+pub fn export(array: &Bound<'_, PyAny>) -> PyResult<(ArrayHeader, PyBuffer<u8>)> {
+    let numpy = array.py().import("numpy")?;
+    let shape: Vec<usize> = numpy.call_method1("shape", (array,))?.extract()?;
+    let contiguous = numpy.call_method1("ascontiguousarray", (array,))?;
+    let dtype = contiguous.getattr("dtype")?;
+    if dtype.getattr("hasobject")?.is_truthy()? {
+        return Err(PyValueError::new_err(
+            "Arrays of Python objects cannot be put in shared memory",
+        ));
+    }
+    if !dtype.getattr("names")?.is_none() {
+        return Err(PyValueError::new_err(
+            "Arrays of structured dtypes are not supported",
+        ));
+    }
+    let bytes = contiguous
+        .call_method1("reshape", (-1,))?
+        .call_method1("view", (numpy.getattr("uint8")?,))?;
+    let header = ArrayHeader {
+        dtype: dtype.getattr("str")?.extract()?,
+        shape,
+    };
+    if header.dtype.len() > u8::MAX as usize || header.shape.len() > u8::MAX as usize {
+        return Err(PyValueError::new_err(
+            "The dtype or shape of the array is too long",
+        ));
+    }
+    Ok((header, PyBuffer::get(&bytes)?))
+}
+
+/// Returns the array carried by `payload`, a buffer that the array views instead
+/// of copying.
+///
+/// # Errors
+/// Raises `ValueError` if the payload was not written by `put_array()`, or
+/// `ImportError` if numpy is not installed.
+pub fn import<'py>(payload: &Bound<'py, PyByteArray>) -> PyResult<Bound<'py, PyAny>> {
+    // No Python code runs while the contents are borrowed.
+    let header = ArrayHeader::read(unsafe { payload.as_bytes() })?;
+    let numpy = payload.py().import("numpy")?;
+    let dtype = numpy.call_method1("dtype", (&header.dtype,))?;
+    let itemsize: usize = dtype.getattr("itemsize")?.extract()?;
+    let count = header
+        .shape
+        .iter()
+        .try_fold(1usize, |n, dim| n.checked_mul(*dim));
+    let offset = header.data_offset();
+    let end = count.and_then(|count| count.checked_mul(itemsize)?.checked_add(offset));
+    if end.is_none_or(|end| end > payload.len()) {
+        return Err(PyValueError::new_err(NOT_AN_ARRAY));
+    }
+    let kwargs = PyDict::new(payload.py());
+    kwargs.set_item("dtype", dtype)?;
+    kwargs.set_item("count", count.unwrap_or_default())?;
+    kwargs.set_item("offset", offset)?;
+    numpy
+        .call_method("frombuffer", (payload,), Some(&kwargs))?
+        .call_method1("reshape", (header.shape,))
+}
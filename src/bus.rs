@@ -0,0 +1,602 @@
+//! A broadcast ring in shared memory: every attached handle receives every message,
+//! e.g. for fan-out telemetry.
+//!
+//! The segment starts with a [`BusHeader`], followed by `capacity` slots of a
+//! sequence word, a 4-byte length, padding and `element_size` bytes each. Publishers
+//! take the spinlock of the header and never wait for readers: message `n` goes to
+//! slot `n % capacity`, overwriting message `n - capacity`. The sequence word of a
+//! slot is `2n + 1` while message `n` is written and `2n + 2` once it is complete, so
+//! readers detect both torn copies and messages overwritten before they got to them.
+//!
+//! Each handle keeps its own cursor, unless it joins a consumer group: the header is
+//! followed by a table of `max_groups` named groups, whose shared offset the members
+//! advance with a compare-and-swap, so that within a group each message goes to one
+//! member while every group receives all of them. A reader that falls more than
+//! `capacity` messages behind skips ahead to the oldest message still retained and
+//! counts the ones it missed. Readers may also seek back to replay what is retained.
+
+use crate::errors::{Empty, InvalidParameters};
+use crate::futex::WaitSignal;
+use crate::region::{self, Abandoned, SpinLock};
+use crate::shmem_wrapper::ShmemWrapper;
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict};
+use std::borrow::Cow;
+use std::cell::UnsafeCell;
+use std::sync::atomic::{fence, AtomicU32, AtomicU64, Ordering};
+
+/// Marks segments holding a bus.
+const MAGIC: u64 = u64::from_le_bytes(*b"ZQBUS001");
+
+/// Size of the sequence word, the length and the padding in front of each message.
+const SLOT_HEADER: usize = 16;
+
+/// Number of consumer groups a bus has room for unless `max_groups` is given.
+const DEFAULT_GROUPS: usize = 16;
+
+/// Longest name of a consumer group in bytes.
+const GROUP_NAME: usize = 48;
+
+/// Header of a bus segment.
+#[repr(C, align(64))]
+struct BusHeader {
+    magic: AtomicU64,
+    element_size: u64,
+    capacity: u64,
+    max_groups: u64,
+    lock: SpinLock,
+    /// Number of messages published.
+    tail: AtomicU64,
+    published: WaitSignal,
+}
+
+/// A consumer group, free while its name is empty.
+#[repr(C)]
+struct GroupEntry {
+    /// Sequence of the next message the group receives.
+    offset: AtomicU64,
+    name_len: AtomicU32,
+    name: UnsafeCell<[u8; GROUP_NAME]>,
+}
+
+/// Returns the offset of the first slot in a bus with room for `max_groups` groups.
+fn slots_offset(max_groups: usize) -> usize {
+    size_of::<BusHeader>() + max_groups * size_of::<GroupEntry>()
+}
+
+/// Returns the distance between consecutive slots holding `element_size` bytes.
+fn stride(element_size: usize) -> usize {
+    (SLOT_HEADER + element_size).next_multiple_of(8)
+}
+
+/// Outcome of an attempt to read message `seq`.
+enum Read {
+    /// The message.
+    Message(Vec<u8>),
+    /// The message is not published yet.
+    Pending,
+    /// The message was overwritten; the oldest retained one has the given sequence.
+    Overwritten(u64),
+}
+
+/// A broadcast ring of byte strings in shared memory, where every handle, or every
+/// consumer group, receives every message published after it attached.
+///
+/// The handle that creates the bus removes its segment when garbage-collected.
+#[pyclass(frozen, module = "zeroq")]
+pub struct Bus {
+    name: String,
+    shmem: ShmemWrapper,
+    element_size: usize,
+    capacity: usize,
+    max_groups: usize,
+    /// Index of the consumer group of this handle, if it joined one.
+    group: Option<usize>,
+    /// Sequence of the next message this handle receives, unless it is in a group.
+    cursor: AtomicU64,
+    /// Messages this handle skipped because they were overwritten first.
+    missed: AtomicU64,
+}
+
+impl Bus {
+    fn header(&self) -> &BusHeader {
+        unsafe { &*(self.shmem.as_ptr() as *const BusHeader) }
+    }
+
+    fn group_entry(&self, index: usize) -> &GroupEntry {
+        unsafe {
+            &*(self
+                .shmem
+                .as_ptr()
+                .add(size_of::<BusHeader>() + index * size_of::<GroupEntry>())
+                as *const GroupEntry)
+        }
+    }
+
+    /// Returns the cursor this handle advances: its own or that of its group.
+    fn cursor_word(&self) -> &AtomicU64 {
+        match self.group {
+            Some(index) => &self.group_entry(index).offset,
+            None => &self.cursor,
+        }
+    }
+
+    /// Returns the name of group `index`, empty while the entry is free.
+    ///
+    /// # Safety
+    /// The caller holds the lock, or the entry was claimed before it was read.
+    unsafe fn group_name(&self, index: usize) -> &[u8] {
+        let entry = self.group_entry(index);
+        let len = (entry.name_len.load(Ordering::Acquire) as usize).min(GROUP_NAME);
+        std::slice::from_raw_parts(entry.name.get() as *const u8, len)
+    }
+
+    /// Returns the index of the group `name`, claiming a free entry starting at the
+    /// newest message if the group does not exist yet.
+    ///
+    /// # Errors
+    /// Raises `ValueError` if the name is empty or too long, or if every entry is
+    /// taken by another group.
+    fn join(&self, py: Python<'_>, name: &str) -> PyResult<usize> {
+        if name.is_empty() || name.len() > GROUP_NAME {
+            return Err(PyValueError::new_err(format!(
+                "Group names take 1 to {} bytes, got {}",
+                GROUP_NAME,
+                name.len()
+            )));
+        }
+        py.allow_threads(|| {
+            let header = self.header();
+            let _guard = header.lock.lock(Abandoned::TakeOver)?;
+            let mut free = None;
+            for index in 0..self.max_groups {
+                let found = unsafe { self.group_name(index) };
+                if found == name.as_bytes() {
+                    return Ok(index);
+                }
+                if found.is_empty() && free.is_none() {
+                    free = Some(index);
+                }
+            }
+            let index = free.ok_or_else(|| {
+                PyValueError::new_err(format!(
+                    "Bus '{}' has no room for another group than its {}",
+                    self.name, self.max_groups
+                ))
+            })?;
+            let entry = self.group_entry(index);
+            entry
+                .offset
+                .store(header.tail.load(Ordering::Relaxed), Ordering::Relaxed);
+            unsafe {
+                std::ptr::copy_nonoverlapping(
+                    name.as_ptr(),
+                    entry.name.get() as *mut u8,
+                    name.len(),
+                );
+            }
+            entry.name_len.store(name.len() as u32, Ordering::Release);
+            Ok(index)
+        })
+    }
+
+    fn slot_ptr(&self, seq: u64) -> *mut u8 {
+        unsafe {
+            self.shmem.as_ptr().add(
+                slots_offset(self.max_groups)
+                    + (seq % self.capacity as u64) as usize * stride(self.element_size),
+            ) as *mut u8
+        }
+    }
+
+    fn slot_seq(&self, seq: u64) -> &AtomicU64 {
+        unsafe { &*(self.slot_ptr(seq) as *const AtomicU64) }
+    }
+
+    /// Returns the sequence of the oldest message still retained.
+    fn oldest(&self) -> u64 {
+        self.header()
+            .tail
+            .load(Ordering::Acquire)
+            .saturating_sub(self.capacity as u64)
+    }
+
+    /// Copies out message `seq`.
+    fn read(&self, seq: u64) -> Read {
+        if seq >= self.header().tail.load(Ordering::Acquire) {
+            return Read::Pending;
+        }
+        let word = self.slot_seq(seq);
+        let complete = 2 * seq + 2;
+        let before = word.load(Ordering::Acquire);
+        if before < complete {
+            return Read::Pending;
+        }
+        if before == complete {
+            let ptr = self.slot_ptr(seq);
+            let data = unsafe {
+                let size =
+                    u32::from_le_bytes(std::ptr::read_volatile(ptr.add(8) as *const [u8; 4]));
+                let mut data = vec![0u8; (size as usize).min(self.element_size)];
+                std::ptr::copy_nonoverlapping(ptr.add(SLOT_HEADER), data.as_mut_ptr(), data.len());
+                data
+            };
+            fence(Ordering::Acquire);
+            if word.load(Ordering::Relaxed) == complete {
+                return Read::Message(data);
+            }
+        }
+        Read::Overwritten(self.oldest().max(seq + 1))
+    }
+
+    /// Receives the next message for this handle, skipping overwritten ones, unless
+    /// none is published yet. Members of a group race for each message.
+    fn try_receive(&self) -> Option<Vec<u8>> {
+        let word = self.cursor_word();
+        loop {
+            let cursor = word.load(Ordering::Acquire);
+            match self.read(cursor) {
+                Read::Pending => return None,
+                Read::Message(data) => {
+                    if word
+                        .compare_exchange(cursor, cursor + 1, Ordering::AcqRel, Ordering::Acquire)
+                        .is_ok()
+                    {
+                        return Some(data);
+                    }
+                }
+                Read::Overwritten(oldest) => {
+                    if word
+                        .compare_exchange(cursor, oldest, Ordering::AcqRel, Ordering::Acquire)
+                        .is_ok()
+                    {
+                        self.missed.fetch_add(oldest - cursor, Ordering::Relaxed);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Creates the segment of a bus; see `new()`.
+    fn create(
+        name: String,
+        element_size: Option<usize>,
+        capacity: Option<usize>,
+        max_groups: Option<usize>,
+    ) -> PyResult<Self> {
+        let element_size = element_size
+            .filter(|size| (1..=u32::MAX as usize).contains(size))
+            .ok_or_else(|| {
+                PyValueError::new_err(format!(
+                    "element_size between 1 and {} required when create=true",
+                    u32::MAX
+                ))
+            })?;
+        let capacity = capacity
+            .filter(|capacity| *capacity > 0)
+            .ok_or_else(|| PyValueError::new_err("capacity > 0 required when create=true"))?;
+        let max_groups = max_groups.unwrap_or(DEFAULT_GROUPS);
+        let size = capacity
+            .checked_mul(stride(element_size))
+            .zip(max_groups.checked_mul(size_of::<GroupEntry>()))
+            .and_then(|(slots, groups)| slots.checked_add(groups))
+            .and_then(|size| size.checked_add(size_of::<BusHeader>()))
+            .ok_or_else(|| PyValueError::new_err("The bus is too large"))?;
+        let shmem = crate::py_queue::create_shmem(&name, size)?;
+        let bus = Self {
+            name,
+            shmem: ShmemWrapper::new(shmem),
+            element_size,
+            capacity,
+            max_groups,
+            group: None,
+            cursor: AtomicU64::new(0),
+            missed: AtomicU64::new(0),
+        };
+        let header = unsafe { &mut *(bus.shmem.as_ptr() as *mut BusHeader) };
+        header.element_size = element_size as u64;
+        header.capacity = capacity as u64;
+        header.max_groups = max_groups as u64;
+        header.magic.store(MAGIC, Ordering::Release);
+        Ok(bus)
+    }
+
+    /// Attaches to the bus `name`; see `new()`.
+    fn open(
+        py: Python<'_>,
+        name: String,
+        element_size: Option<usize>,
+        capacity: Option<usize>,
+        max_groups: Option<usize>,
+    ) -> PyResult<Self> {
+        let shmem = region::attach(py, &name, MAGIC, size_of::<BusHeader>(), "bus")?;
+        let header = unsafe { &*(shmem.as_ptr() as *const BusHeader) };
+        let found = (
+            header.element_size as usize,
+            header.capacity as usize,
+            header.max_groups as usize,
+        );
+        let size = found
+            .2
+            .checked_mul(size_of::<GroupEntry>())
+            .and_then(|groups| found.1.checked_mul(stride(found.0))?.checked_add(groups));
+        if found.1 == 0
+            || size.is_none_or(|size| shmem.len() < size_of::<BusHeader>().saturating_add(size))
+        {
+            return Err(region::holds_no(&name, "bus"));
+        }
+        if element_size.is_some_and(|size| size != found.0)
+            || capacity.is_some_and(|capacity| capacity != found.1)
+            || max_groups.is_some_and(|groups| groups != found.2)
+        {
+            return Err(InvalidParameters::new_err(format!(
+                "Bus '{}' exists with element_size {}, capacity {} and max_groups {}",
+                name, found.0, found.1, found.2
+            )));
+        }
+        let tail = header.tail.load(Ordering::Acquire);
+        Ok(Self {
+            name,
+            shmem,
+            element_size: found.0,
+            capacity: found.1,
+            max_groups: found.2,
+            group: None,
+            cursor: AtomicU64::new(tail),
+            missed: AtomicU64::new(0),
+        })
+    }
+}
+
+#[pymethods]
+impl Bus {
+    /// Creates or attaches to a bus in shared memory. The handle receives the
+    /// messages published from now on, or those its consumer group has yet to
+    /// receive.
+    ///
+    /// # Arguments
+    /// - `name` (str): Shared memory segment name.
+    /// - `element_size` (int): Maximum size of a message in bytes (required if
+    ///   creating).
+    /// - `capacity` (int): Number of most recent messages retained (required if
+    ///   creating).
+    /// - `create` (bool, default=True): Whether to create a new bus.
+    /// - `group` (str, optional): Consumer group to join, created at the newest
+    ///   message if it does not exist; its members share the messages.
+    /// - `max_groups` (int, default=16): Number of consumer groups the bus has room
+    ///   for.
+    ///
+    /// # Errors
+    /// Raises `ValueError` for invalid parameters or if there is no room for the
+    /// group, `AlreadyExists` if a bus is to be created under a name that is taken, or
+    /// `OSError` if the segment cannot be created or opened or holds no bus.
+    #[new]
+    #[pyo3(signature = (
+        name,
+        element_size=None,
+        capacity=None,
+        create=true,
+        group=None,
+        max_groups=None,
+    ))]
+    fn new(
+        py: Python<'_>,
+        name: String,
+        element_size: Option<usize>,
+        capacity: Option<usize>,
+        create: bool,
+        group: Option<&str>,
+        max_groups: Option<usize>,
+    ) -> PyResult<Self> {
+        let mut bus = if create {
+            Self::create(name, element_size, capacity, max_groups)?
+        } else {
+            Self::open(py, name, element_size, capacity, max_groups)?
+        };
+        if let Some(group) = group {
+            bus.group = Some(bus.join(py, group)?);
+        }
+        Ok(bus)
+    }
+
+    /// Name of the shared memory segment.
+    #[getter]
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Maximum size of a message in bytes.
+    #[getter]
+    fn element_size(&self) -> usize {
+        self.element_size
+    }
+
+    /// Number of most recent messages retained.
+    #[getter]
+    fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of messages published so far, across all handles; the sequence of the
+    /// next message.
+    #[getter]
+    fn published(&self) -> u64 {
+        self.header().tail.load(Ordering::Acquire)
+    }
+
+    /// Sequence of the next message this handle, or its group, receives.
+    #[getter]
+    fn cursor(&self) -> u64 {
+        self.cursor_word().load(Ordering::Acquire)
+    }
+
+    /// Name of the consumer group of this handle, or `None`.
+    #[getter]
+    fn group(&self) -> Option<String> {
+        self.group
+            .map(|index| String::from_utf8_lossy(unsafe { self.group_name(index) }).into_owned())
+    }
+
+    /// Number of consumer groups the bus has room for.
+    #[getter]
+    fn max_groups(&self) -> usize {
+        self.max_groups
+    }
+
+    /// Returns the consumer groups of the bus.
+    ///
+    /// # Returns
+    /// - (dict[str, int]): The offset of each group, the sequence of the next message
+    ///   it receives.
+    fn groups<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let groups = PyDict::new(py);
+        for index in 0..self.max_groups {
+            let name = unsafe { self.group_name(index) };
+            if !name.is_empty() {
+                groups.set_item(
+                    String::from_utf8_lossy(name),
+                    self.group_entry(index).offset.load(Ordering::Acquire),
+                )?;
+            }
+        }
+        Ok(groups)
+    }
+
+    /// Sequence of the oldest message still retained, a snapshot that publishers
+    /// advance once the ring is full.
+    #[getter(oldest)]
+    fn py_oldest(&self) -> u64 {
+        self.oldest()
+    }
+
+    /// Moves the cursor of this handle, or of its group, e.g. to replay the retained
+    /// history.
+    ///
+    /// # Arguments
+    /// - `position` (str | int): `'oldest'` for the oldest retained message, `'newest'`
+    ///   for the next message to be published, or the sequence of a retained message.
+    ///
+    /// # Returns
+    /// - (int): The new cursor.
+    ///
+    /// # Errors
+    /// Raises `ValueError` if the sequence is no longer retained or not published yet,
+    /// or `TypeError` if `position` is neither a sequence nor a known position.
+    fn seek(&self, position: &Bound<'_, PyAny>) -> PyResult<u64> {
+        let seq = if let Ok(seq) = position.extract::<u64>() {
+            let (oldest, published) = (self.oldest(), self.published());
+            if seq < oldest || seq > published {
+                return Err(PyValueError::new_err(format!(
+                    "Cannot seek to message {}: bus '{}' retains messages {} to {}",
+                    seq,
+                    self.name,
+                    oldest,
+                    published.saturating_sub(1)
+                )));
+            }
+            seq
+        } else {
+            match position.extract::<&str>() {
+                Ok("oldest") => self.oldest(),
+                Ok("newest") => self.published(),
+                _ => {
+                    return Err(PyTypeError::new_err(format!(
+                        "position must be 'oldest', 'newest' or a sequence, not {}",
+                        position.repr()?
+                    )))
+                }
+            }
+        };
+        self.cursor_word().store(seq, Ordering::Release);
+        Ok(seq)
+    }
+
+    /// Number of messages this handle skipped because they were overwritten before it
+    /// received them.
+    #[getter]
+    fn missed(&self) -> u64 {
+        self.missed.load(Ordering::Relaxed)
+    }
+
+    /// Number of published messages this handle has yet to receive, a snapshot that
+    /// may include messages already overwritten.
+    fn __len__(&self) -> usize {
+        self.published().saturating_sub(self.cursor()) as usize
+    }
+
+    /// Publishes a message to every handle, overwriting the oldest retained one once
+    /// the ring is full; never waits for readers.
+    ///
+    /// # Arguments
+    /// - `item` (bytes): The message, at most `element_size` bytes.
+    ///
+    /// # Returns
+    /// - (int): Sequence of the message.
+    ///
+    /// # Errors
+    /// Raises `ValueError` if `item` is larger than `element_size`.
+    fn publish(&self, py: Python<'_>, item: Cow<[u8]>) -> PyResult<u64> {
+        if item.len() > self.element_size {
+            return Err(PyValueError::new_err(format!(
+                "Item of {} bytes does not fit in elements of {} bytes",
+                item.len(),
+                self.element_size
+            )));
+        }
+        py.allow_threads(|| {
+            let header = self.header();
+            let guard = header.lock.lock(Abandoned::TakeOver)?;
+            let seq = header.tail.load(Ordering::Relaxed);
+            let word = self.slot_seq(seq);
+            word.store(2 * seq + 1, Ordering::Relaxed);
+            fence(Ordering::Release);
+            let ptr = self.slot_ptr(seq);
+            unsafe {
+                std::ptr::copy_nonoverlapping(
+                    (item.len() as u32).to_le_bytes().as_ptr(),
+                    ptr.add(8),
+                    4,
+                );
+                std::ptr::copy_nonoverlapping(item.as_ptr(), ptr.add(SLOT_HEADER), item.len());
+            }
+            word.store(2 * seq + 2, Ordering::Release);
+            header.tail.store(seq + 1, Ordering::Release);
+            drop(guard);
+            header.published.notify();
+            Ok(seq)
+        })
+    }
+
+    /// Receives the next message for this handle, or the next one its group has yet
+    /// to receive.
+    ///
+    /// # Arguments
+    /// - `timeout` (float, optional): Maximum time to wait for a message in seconds;
+    ///   waits indefinitely if omitted, and does not wait if 0.
+    ///
+    /// # Errors
+    /// Raises `Empty` if no message is published before the timeout.
+    #[pyo3(signature = (timeout=None))]
+    fn receive<'py>(&self, py: Python<'py>, timeout: Option<f64>) -> PyResult<Bound<'py, PyBytes>> {
+        let data = region::wait_for(
+            py,
+            &self.header().published,
+            timeout,
+            || Ok(self.try_receive()),
+            || Empty::new_err(format!("Bus '{}' has no new message", self.name)),
+        )?;
+        Ok(PyBytes::new(py, &data))
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Bus(name='{}', element_size={}, capacity={}, published={}, cursor={})",
+            self.name,
+            self.element_size,
+            self.capacity,
+            self.published(),
+            self.cursor()
+        )
+    }
+}
@@ -0,0 +1,62 @@
+use pyo3::ffi;
+use pyo3::prelude::*;
+use std::pin::Pin;
+
+/// The memory of a C-contiguous buffer-protocol object, viewed as bytes.
+///
+/// Unlike `pyo3::buffer::PyBuffer<u8>`, the element format of the exporter is
+/// ignored, so arrays of any dtype can be read or written byte-wise. The exporter
+/// cannot resize or free the memory while the buffer is held, which makes it safe
+/// to access with the GIL released.
+pub struct ByteBuffer {
+    view: Pin<Box<ffi::Py_buffer>>,
+}
+
+// The exported memory is not tied to the thread that requested it, and
+// `Drop` takes the GIL to release it.
+unsafe impl Send for ByteBuffer {}
+unsafe impl Sync for ByteBuffer {}
+
+impl ByteBuffer {
+    /// Requests the memory of `obj`, writable if `writable` is set.
+    ///
+    /// # Errors
+    /// Raises the error of the exporter, typically `TypeError` if `obj` does not
+    /// support the buffer protocol, or `BufferError` if it is read-only while
+    /// `writable` is set or is not C-contiguous.
+    pub fn get(obj: &Bound<'_, PyAny>, writable: bool) -> PyResult<Self> {
+        let mut view = Box::pin(unsafe { std::mem::zeroed::<ffi::Py_buffer>() });
+        let flags = if writable {
+            ffi::PyBUF_C_CONTIGUOUS | ffi::PyBUF_WRITABLE
+        } else {
+            ffi::PyBUF_C_CONTIGUOUS
+        };
+        if unsafe { ffi::PyObject_GetBuffer(obj.as_ptr(), &mut *view, flags) } == -1 {
+            return Err(PyErr::fetch(obj.py()));
+        }
+        Ok(Self { view })
+    }
+
+    /// Returns the memory of the buffer.
+    pub fn as_slice(&self) -> &[u8] {
+        if self.view.len == 0 {
+            return &[];
+        }
+        unsafe { std::slice::from_raw_parts(self.view.buf as *const u8, self.view.len as usize) }
+    }
+
+    /// Returns the memory of a buffer requested as writable.
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        debug_assert_eq!(self.view.readonly, 0);
+        if self.view.len == 0 {
+            return &mut [];
+        }
+        unsafe { std::slice::from_raw_parts_mut(self.view.buf as *mut u8, self.view.len as usize) }
+    }
+}
+
+impl Drop for ByteBuffer {
+    fn drop(&mut self) {
+        Python::with_gil(|_| unsafe { ffi::PyBuffer_Release(&mut *self.view) });
+    }
+}
@@ -0,0 +1,113 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+/// Time source used by blocking operations to measure timeouts and wait.
+///
+/// Readings are monotonic durations since an arbitrary, clock-specific origin,
+/// so they are only meaningful when compared with readings of the same clock.
+pub enum Clock {
+    /// Real monotonic time; waiting sleeps the calling thread.
+    System,
+    /// Virtual time controlled by a [`ManualClock`]; waiting advances it.
+    Manual(Py<ManualClock>),
+}
+
+impl Clock {
+    /// Returns the current reading of the clock.
+    pub fn now(&self) -> Duration {
+        match self {
+            Clock::System => {
+                static ORIGIN: OnceLock<Instant> = OnceLock::new();
+                ORIGIN.get_or_init(Instant::now).elapsed()
+            }
+            Clock::Manual(clock) => clock.get().now(),
+        }
+    }
+
+    /// Waits for `duration` as measured by the clock.
+    ///
+    /// A manual clock does not block: it advances its virtual time by
+    /// `duration` and yields to the scheduler.
+    pub fn sleep(&self, duration: Duration) {
+        match self {
+            Clock::System => std::thread::sleep(duration),
+            Clock::Manual(clock) => {
+                clock.get().advance_by(duration);
+                std::thread::yield_now();
+            }
+        }
+    }
+}
+
+/// A manually driven clock for deterministic timeout testing.
+///
+/// Passed as `clock=` to `Queue`, it replaces real time for timeouts and
+/// wait-time statistics: waiting advances the clock instead of sleeping, and
+/// `advance()` moves it forward explicitly. Busy-spinning waits do not
+/// advance it.
+#[pyclass(frozen)]
+#[derive(Default)]
+pub struct ManualClock {
+    nanos: AtomicU64,
+}
+
+impl ManualClock {
+    /// Returns the virtual time elapsed since the clock was created.
+    fn now(&self) -> Duration {
+        Duration::from_nanos(self.nanos.load(Ordering::Acquire))
+    }
+
+    /// Moves the clock forward by `duration`.
+    fn advance_by(&self, duration: Duration) {
+        let nanos = duration.as_nanos().min(u64::MAX as u128) as u64;
+        self.nanos.fetch_add(nanos, Ordering::AcqRel);
+    }
+}
+
+#[pymethods]
+impl ManualClock {
+    /// Creates a clock reading `start` seconds.
+    ///
+    /// # Arguments
+    ///
+    /// - `start` (float): Initial reading in seconds.
+    ///
+    /// # Errors
+    ///
+    /// - `ValueError`: If `start` is negative or not finite.
+    #[new]
+    #[pyo3(signature = (start=0.0))]
+    fn new(start: f64) -> PyResult<Self> {
+        let clock = Self::default();
+        clock.advance(start)?;
+        Ok(clock)
+    }
+
+    /// Returns the current reading in seconds.
+    fn time(&self) -> f64 {
+        self.now().as_secs_f64()
+    }
+
+    /// Moves the clock forward.
+    ///
+    /// # Arguments
+    ///
+    /// - `seconds` (float): Amount of virtual time to add.
+    ///
+    /// # Errors
+    ///
+    /// - `ValueError`: If `seconds` is negative or not finite.
+    fn advance(&self, seconds: f64) -> PyResult<()> {
+        let duration = Duration::try_from_secs_f64(seconds)
+            .map_err(|_| PyValueError::new_err("seconds must be a finite, non-negative number"))?;
+        self.advance_by(duration);
+        Ok(())
+    }
+
+    fn __repr__(&self) -> String {
+        format!("ManualClock(time={:.6})", self.time())
+    }
+}
@@ -0,0 +1,276 @@
+use crate::mpmc_queue::CONFIG_WORDS;
+use crate::wait::{Backpressure, BackpressureCurve, WaitStrategy};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::sync::atomic::{fence, AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Bits of the flags word of the shared configuration.
+const FLAG_BUSY_SPIN: u64 = 1 << 0;
+const FLAG_ADAPTIVE: u64 = 1 << 1;
+const FLAG_DROP_EXPIRED: u64 = 1 << 2;
+const FLAG_SLOW_OP: u64 = 1 << 3;
+const CURVE_SHIFT: u32 = 8;
+
+/// Runtime options of a queue handle, as given to the constructor or `configure()`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ConfigOptions {
+    pub busy_spin: bool,
+    pub adaptive: bool,
+    pub spin_limit: u32,
+    pub yield_limit: u32,
+    pub park_interval: f64,
+    pub backpressure: Option<BackpressureCurve>,
+    pub backpressure_base: f64,
+    pub backpressure_max: f64,
+    pub slow_op_threshold: Option<f64>,
+    pub drop_expired: bool,
+}
+
+/// Python-facing names accepted by `Queue.configure()`.
+const OPTION_NAMES: [&str; 11] = [
+    "wait",
+    "busy_spin",
+    "adaptive",
+    "spin_limit",
+    "yield_limit",
+    "park_interval",
+    "backpressure",
+    "backpressure_base",
+    "backpressure_max",
+    "slow_op_threshold",
+    "drop_expired",
+];
+
+impl ConfigOptions {
+    /// Returns a copy with the options present in `overrides` replaced.
+    ///
+    /// # Errors
+    /// Raises `ValueError` for unknown option names and `TypeError` for values
+    /// of the wrong type.
+    pub fn with_overrides(mut self, overrides: &Bound<'_, PyDict>) -> PyResult<Self> {
+        if overrides.contains("wait")?
+            && (overrides.contains("busy_spin")? || overrides.contains("adaptive")?)
+        {
+            return Err(PyValueError::new_err(
+                "wait cannot be combined with busy_spin or adaptive",
+            ));
+        }
+        for (key, value) in overrides.iter() {
+            let key: String = key.extract()?;
+            match key.as_str() {
+                "wait" => {
+                    let name: String = value.extract()?;
+                    (self.busy_spin, self.adaptive) = WaitStrategy::flags_from_name(&name)?;
+                }
+                "busy_spin" => self.busy_spin = value.extract()?,
+                "adaptive" => self.adaptive = value.extract()?,
+                "spin_limit" => self.spin_limit = value.extract()?,
+                "yield_limit" => self.yield_limit = value.extract()?,
+                "park_interval" => self.park_interval = value.extract()?,
+                "backpressure" => {
+                    let curve: Option<String> = value.extract()?;
+                    self.backpressure = curve
+                        .as_deref()
+                        .map(BackpressureCurve::from_name)
+                        .transpose()?;
+                }
+                "backpressure_base" => self.backpressure_base = value.extract()?,
+                "backpressure_max" => self.backpressure_max = value.extract()?,
+                "slow_op_threshold" => self.slow_op_threshold = value.extract()?,
+                "drop_expired" => self.drop_expired = value.extract()?,
+                other => {
+                    return Err(PyValueError::new_err(format!(
+                        "Unknown option '{}': expected one of {}",
+                        other,
+                        OPTION_NAMES.join(", ")
+                    )))
+                }
+            }
+        }
+        Ok(self)
+    }
+
+    /// Returns the options as a Python dictionary keyed by their names.
+    pub fn to_dict<'py>(self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        dict.set_item("wait", WaitStrategy::name_of(self.busy_spin, self.adaptive))?;
+        dict.set_item("busy_spin", self.busy_spin)?;
+        dict.set_item("adaptive", self.adaptive)?;
+        dict.set_item("spin_limit", self.spin_limit)?;
+        dict.set_item("yield_limit", self.yield_limit)?;
+        dict.set_item("park_interval", self.park_interval)?;
+        dict.set_item(
+            "backpressure",
+            self.backpressure.map(BackpressureCurve::name),
+        )?;
+        dict.set_item("backpressure_base", self.backpressure_base)?;
+        dict.set_item("backpressure_max", self.backpressure_max)?;
+        dict.set_item("slow_op_threshold", self.slow_op_threshold)?;
+        dict.set_item("drop_expired", self.drop_expired)?;
+        Ok(dict)
+    }
+
+    /// Encodes the options into the words of the shared configuration,
+    /// excluding the generation word.
+    fn encode(&self) -> [u64; CONFIG_WORDS - 1] {
+        let mut flags = 0;
+        if self.busy_spin {
+            flags |= FLAG_BUSY_SPIN;
+        }
+        if self.adaptive {
+            flags |= FLAG_ADAPTIVE;
+        }
+        if self.drop_expired {
+            flags |= FLAG_DROP_EXPIRED;
+        }
+        if self.slow_op_threshold.is_some() {
+            flags |= FLAG_SLOW_OP;
+        }
+        flags |= (BackpressureCurve::code(self.backpressure) as u64) << CURVE_SHIFT;
+        [
+            flags,
+            self.spin_limit as u64,
+            self.yield_limit as u64,
+            self.park_interval.to_bits(),
+            self.backpressure_base.to_bits(),
+            self.backpressure_max.to_bits(),
+            self.slow_op_threshold.unwrap_or_default().to_bits(),
+        ]
+    }
+
+    /// Decodes options written by [`ConfigOptions::encode`].
+    fn decode(words: &[u64; CONFIG_WORDS - 1]) -> Self {
+        let [flags, spin_limit, yield_limit, park, base, max, slow_op] = *words;
+        Self {
+            busy_spin: flags & FLAG_BUSY_SPIN != 0,
+            adaptive: flags & FLAG_ADAPTIVE != 0,
+            spin_limit: spin_limit as u32,
+            yield_limit: yield_limit as u32,
+            park_interval: f64::from_bits(park),
+            backpressure: BackpressureCurve::from_code((flags >> CURVE_SHIFT) as u8),
+            backpressure_base: f64::from_bits(base),
+            backpressure_max: f64::from_bits(max),
+            slow_op_threshold: (flags & FLAG_SLOW_OP != 0).then(|| f64::from_bits(slow_op)),
+            drop_expired: flags & FLAG_DROP_EXPIRED != 0,
+        }
+    }
+}
+
+/// Runtime behavior of a queue handle built from validated [`ConfigOptions`].
+#[derive(Debug)]
+pub struct HandleConfig {
+    pub options: ConfigOptions,
+    pub wait: WaitStrategy,
+    pub backpressure: Option<Backpressure>,
+    pub slow_op_threshold: Option<Duration>,
+    pub drop_expired: bool,
+}
+
+impl HandleConfig {
+    /// Validates `options` and builds the behavior they describe.
+    ///
+    /// # Errors
+    /// Raises `ValueError` if the options are inconsistent or out of range.
+    pub fn new(options: ConfigOptions) -> PyResult<Self> {
+        let wait = WaitStrategy::from_options(
+            options.busy_spin,
+            options.adaptive,
+            options.spin_limit,
+            options.yield_limit,
+            options.park_interval,
+        )?;
+        let backpressure = options
+            .backpressure
+            .map(|curve| {
+                Backpressure::new(curve, options.backpressure_base, options.backpressure_max)
+            })
+            .transpose()?;
+        let slow_op_threshold = options
+            .slow_op_threshold
+            .map(|t| {
+                Duration::try_from_secs_f64(t)
+                    .map_err(|_| PyValueError::new_err("slow_op_threshold must be non-negative"))
+            })
+            .transpose()?;
+        Ok(Self {
+            options,
+            wait,
+            backpressure,
+            slow_op_threshold,
+            drop_expired: options.drop_expired,
+        })
+    }
+}
+
+/// Configuration words in a queue header shared by every attached process.
+///
+/// Word 0 is a generation counter used as a seqlock: it is odd while a
+/// process is writing and zero until `configure()` is first called.
+pub struct SharedConfig<'a> {
+    words: &'a [AtomicU64; CONFIG_WORDS],
+}
+
+impl<'a> SharedConfig<'a> {
+    pub fn new(words: &'a [AtomicU64; CONFIG_WORDS]) -> Self {
+        Self { words }
+    }
+
+    /// Returns the current generation, zero if the queue was never configured.
+    #[inline]
+    pub fn generation(&self) -> u64 {
+        self.words[0].load(Ordering::Acquire)
+    }
+
+    /// Publishes `options` to every attached process and returns the new generation.
+    pub fn store(&self, options: &ConfigOptions) -> u64 {
+        let generation = &self.words[0];
+        let mut current = generation.load(Ordering::Relaxed);
+        loop {
+            if current % 2 == 1 {
+                std::hint::spin_loop();
+                current = generation.load(Ordering::Relaxed);
+                continue;
+            }
+            match generation.compare_exchange_weak(
+                current,
+                current + 1,
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => break,
+                Err(actual) => current = actual,
+            }
+        }
+        fence(Ordering::Release);
+        for (word, value) in self.words[1..].iter().zip(options.encode()) {
+            word.store(value, Ordering::Relaxed);
+        }
+        generation.store(current + 2, Ordering::Release);
+        current + 2
+    }
+
+    /// Reads a consistent snapshot of the shared options, if any were published,
+    /// together with their generation.
+    pub fn load(&self) -> Option<(u64, ConfigOptions)> {
+        loop {
+            let before = self.generation();
+            if before == 0 {
+                return None;
+            }
+            if before % 2 == 1 {
+                std::hint::spin_loop();
+                continue;
+            }
+            let mut words = [0u64; CONFIG_WORDS - 1];
+            for (value, word) in words.iter_mut().zip(&self.words[1..]) {
+                *value = word.load(Ordering::Relaxed);
+            }
+            fence(Ordering::Acquire);
+            if self.words[0].load(Ordering::Relaxed) == before {
+                return Some((before, ConfigOptions::decode(&words)));
+            }
+        }
+    }
+}
@@ -0,0 +1,467 @@
+//! A FIFO queue in shared memory that keeps at most one pending value per key, for
+//! feeds with last-value semantics per instrument, sensor or entity.
+//!
+//! A put for a key that is still pending replaces its value in place, keeping its
+//! position in the queue; otherwise it appends a new entry. The segment starts with a
+//! [`ConflatingHeader`], followed by an index of the pending keys, open addressing with
+//! linear probing over `table_size` entries, the ring of pending entries in FIFO order,
+//! the stack of free entries and finally the entries. All of it changes under the
+//! spinlock of the header, held for one lookup and one copy. A process that exits
+//! while holding it poisons the queue, which it may have left half-updated.
+
+use crate::errors::{Empty, Full, InvalidParameters};
+use crate::futex::WaitSignal;
+use crate::region::{self, Abandoned, SpinLock, NIL};
+use crate::shmem_wrapper::ShmemWrapper;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyTuple};
+use std::borrow::Cow;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Marks segments holding a conflating queue.
+const MAGIC: u64 = u64::from_le_bytes(*b"ZQCONF01");
+
+/// Size of the key length and the value length in front of each entry.
+const ENTRY_HEADER: usize = 2 * size_of::<u32>();
+
+/// Header of a conflating queue segment.
+#[repr(C, align(64))]
+struct ConflatingHeader {
+    magic: AtomicU64,
+    key_size: u64,
+    element_size: u64,
+    capacity: u64,
+    lock: SpinLock,
+    /// Ring index of the oldest pending entry.
+    head: AtomicU64,
+    /// Number of pending entries.
+    len: AtomicU64,
+    not_empty: WaitSignal,
+    not_full: WaitSignal,
+}
+
+/// Returns the number of index entries for `capacity` pending keys, a power of two
+/// keeping the index at most half full.
+fn table_size(capacity: usize) -> usize {
+    (capacity * 2).next_power_of_two()
+}
+
+/// Returns the distance between consecutive entries.
+fn stride(key_size: usize, element_size: usize) -> usize {
+    (ENTRY_HEADER + key_size + element_size).next_multiple_of(8)
+}
+
+/// Returns the offset of the first entry.
+fn entries_offset(capacity: usize) -> usize {
+    (size_of::<ConflatingHeader>() + (table_size(capacity) + 2 * capacity) * size_of::<u32>())
+        .next_multiple_of(8)
+}
+
+/// FNV-1a hash of `key`.
+fn hash(key: &[u8]) -> usize {
+    key.iter().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    }) as usize
+}
+
+/// A bounded FIFO queue of keyed byte strings in shared memory that replaces the
+/// pending value of a key instead of appending a second one.
+///
+/// The handle that creates the queue removes its segment when garbage-collected.
+#[pyclass(frozen, module = "zeroq")]
+pub struct ConflatingQueue {
+    name: String,
+    shmem: ShmemWrapper,
+    key_size: usize,
+    element_size: usize,
+    capacity: usize,
+}
+
+impl ConflatingQueue {
+    fn header(&self) -> &ConflatingHeader {
+        unsafe { &*(self.shmem.as_ptr() as *const ConflatingHeader) }
+    }
+
+    /// Returns a pointer to the `u32` at `index` of the arrays after the header: the
+    /// key index, then the ring, then the free stack.
+    fn word(&self, index: usize) -> *mut u32 {
+        unsafe { (self.shmem.as_ptr().add(size_of::<ConflatingHeader>()) as *mut u32).add(index) }
+    }
+
+    fn table(&self, index: usize) -> *mut u32 {
+        self.word(index)
+    }
+
+    fn ring(&self, index: usize) -> *mut u32 {
+        self.word(table_size(self.capacity) + index)
+    }
+
+    fn free(&self, index: usize) -> *mut u32 {
+        self.word(table_size(self.capacity) + self.capacity + index)
+    }
+
+    fn entry_ptr(&self, entry: u32) -> *mut u8 {
+        unsafe {
+            self.shmem.as_ptr().add(
+                entries_offset(self.capacity)
+                    + entry as usize * stride(self.key_size, self.element_size),
+            ) as *mut u8
+        }
+    }
+
+    /// Returns the key of `entry`.
+    ///
+    /// # Safety
+    /// The caller holds the lock.
+    unsafe fn key(&self, entry: u32) -> &[u8] {
+        let ptr = self.entry_ptr(entry);
+        let len = *(ptr as *const u32) as usize;
+        std::slice::from_raw_parts(ptr.add(ENTRY_HEADER), len.min(self.key_size))
+    }
+
+    /// Returns the position of `key` in the index, or of the free index entry where it
+    /// belongs, and whether it was found.
+    ///
+    /// # Safety
+    /// The caller holds the lock.
+    unsafe fn lookup(&self, key: &[u8]) -> (usize, bool) {
+        let mask = table_size(self.capacity) - 1;
+        let mut position = hash(key) & mask;
+        loop {
+            let entry = *self.table(position);
+            if entry == NIL {
+                return (position, false);
+            }
+            if self.key(entry) == key {
+                return (position, true);
+            }
+            position = (position + 1) & mask;
+        }
+    }
+
+    /// Empties `position` of the index, shifting back the entries probed past it.
+    ///
+    /// # Safety
+    /// The caller holds the lock.
+    unsafe fn remove_at(&self, mut position: usize) {
+        let mask = table_size(self.capacity) - 1;
+        let mut next = position;
+        loop {
+            next = (next + 1) & mask;
+            let entry = *self.table(next);
+            if entry == NIL {
+                break;
+            }
+            let home = hash(self.key(entry)) & mask;
+            // Move the entry back unless its home lies cyclically in (position, next].
+            let stays = if position <= next {
+                position < home && home <= next
+            } else {
+                position < home || home <= next
+            };
+            if !stays {
+                *self.table(position) = entry;
+                position = next;
+            }
+        }
+        *self.table(position) = NIL;
+    }
+
+    /// Writes `value` into `entry`.
+    ///
+    /// # Safety
+    /// The caller holds the lock.
+    unsafe fn write_value(&self, entry: u32, value: &[u8]) {
+        let ptr = self.entry_ptr(entry);
+        *(ptr.add(size_of::<u32>()) as *mut u32) = value.len() as u32;
+        std::ptr::copy_nonoverlapping(
+            value.as_ptr(),
+            ptr.add(ENTRY_HEADER + self.key_size),
+            value.len(),
+        );
+    }
+
+    /// Replaces the pending value of `key` or appends a new entry, unless the queue is
+    /// full; returns whether a pending value was replaced.
+    fn try_put(&self, key: &[u8], value: &[u8]) -> PyResult<Option<bool>> {
+        let header = self.header();
+        let guard = header.lock.lock(Abandoned::Poison)?;
+        unsafe {
+            let (position, found) = self.lookup(key);
+            if found {
+                self.write_value(*self.table(position), value);
+                return Ok(Some(true));
+            }
+            let len = header.len.load(Ordering::Relaxed) as usize;
+            if len == self.capacity {
+                return Ok(None);
+            }
+            // The free stack holds the entries not pending, so its top is at
+            // capacity - len - 1.
+            let entry = *self.free(self.capacity - len - 1);
+            let ptr = self.entry_ptr(entry);
+            *(ptr as *mut u32) = key.len() as u32;
+            std::ptr::copy_nonoverlapping(key.as_ptr(), ptr.add(ENTRY_HEADER), key.len());
+            self.write_value(entry, value);
+            *self.table(position) = entry;
+            let head = header.head.load(Ordering::Relaxed) as usize;
+            *self.ring((head + len) % self.capacity) = entry;
+            header.len.store(len as u64 + 1, Ordering::Relaxed);
+        }
+        drop(guard);
+        header.not_empty.notify();
+        Ok(Some(false))
+    }
+
+    /// Removes the oldest pending entry and copies out its key and value, unless the
+    /// queue is empty.
+    fn try_get(&self) -> PyResult<Option<(Vec<u8>, Vec<u8>)>> {
+        let header = self.header();
+        let guard = header.lock.lock(Abandoned::Poison)?;
+        let item = unsafe {
+            let len = header.len.load(Ordering::Relaxed) as usize;
+            if len == 0 {
+                return Ok(None);
+            }
+            let head = header.head.load(Ordering::Relaxed) as usize;
+            let entry = *self.ring(head);
+            let key = self.key(entry).to_vec();
+            let ptr = self.entry_ptr(entry);
+            let size = (*(ptr.add(size_of::<u32>()) as *const u32) as usize).min(self.element_size);
+            let value =
+                std::slice::from_raw_parts(ptr.add(ENTRY_HEADER + self.key_size), size).to_vec();
+            let (position, _) = self.lookup(&key);
+            self.remove_at(position);
+            *self.free(self.capacity - len) = entry;
+            header
+                .head
+                .store(((head + 1) % self.capacity) as u64, Ordering::Relaxed);
+            header.len.store(len as u64 - 1, Ordering::Relaxed);
+            (key, value)
+        };
+        drop(guard);
+        header.not_full.notify();
+        Ok(Some(item))
+    }
+}
+
+#[pymethods]
+impl ConflatingQueue {
+    /// Creates or attaches to a conflating queue in shared memory.
+    ///
+    /// # Arguments
+    /// - `name` (str): Shared memory segment name.
+    /// - `key_size` (int): Maximum size of a key in bytes (required if creating).
+    /// - `element_size` (int): Maximum size of a value in bytes (required if
+    ///   creating).
+    /// - `capacity` (int): Maximum number of pending keys (required if creating).
+    /// - `create` (bool, default=True): Whether to create a new queue.
+    ///
+    /// # Errors
+    /// Raises `ValueError` for invalid parameters, `AlreadyExists` if a queue is to be
+    /// created under a name that is taken, or `OSError` if the segment cannot be
+    /// created or opened or holds no conflating queue.
+    #[new]
+    #[pyo3(signature = (name, key_size=None, element_size=None, capacity=None, create=true))]
+    fn new(
+        py: Python<'_>,
+        name: String,
+        key_size: Option<usize>,
+        element_size: Option<usize>,
+        capacity: Option<usize>,
+        create: bool,
+    ) -> PyResult<Self> {
+        const WHAT: &str = "conflating queue";
+        if !create {
+            let shmem = region::attach(py, &name, MAGIC, size_of::<ConflatingHeader>(), WHAT)?;
+            let header = unsafe { &*(shmem.as_ptr() as *const ConflatingHeader) };
+            let found = (
+                header.key_size as usize,
+                header.element_size as usize,
+                header.capacity as usize,
+            );
+            if found.2 >= NIL as usize
+                || shmem.len() < entries_offset(found.2) + found.2 * stride(found.0, found.1)
+            {
+                return Err(region::holds_no(&name, WHAT));
+            }
+            if key_size.is_some_and(|size| size != found.0)
+                || element_size.is_some_and(|size| size != found.1)
+                || capacity.is_some_and(|capacity| capacity != found.2)
+            {
+                return Err(InvalidParameters::new_err(format!(
+                    "Conflating queue '{}' exists with key_size {}, element_size {} and \
+                     capacity {}",
+                    name, found.0, found.1, found.2
+                )));
+            }
+            return Ok(Self {
+                name,
+                shmem,
+                key_size: found.0,
+                element_size: found.1,
+                capacity: found.2,
+            });
+        }
+        let size_between = |size: Option<usize>, what: &str| {
+            size.filter(|size| (1..=u32::MAX as usize).contains(size))
+                .ok_or_else(|| {
+                    PyValueError::new_err(format!(
+                        "{} between 1 and {} required when create=true",
+                        what,
+                        u32::MAX
+                    ))
+                })
+        };
+        let key_size = size_between(key_size, "key_size")?;
+        let element_size = size_between(element_size, "element_size")?;
+        // The key index has twice as many entries, which must stay below NIL.
+        let max_capacity = NIL as usize / 4;
+        let capacity = capacity
+            .filter(|capacity| (1..=max_capacity).contains(capacity))
+            .ok_or_else(|| {
+                PyValueError::new_err(format!(
+                    "capacity between 1 and {} required when create=true",
+                    max_capacity
+                ))
+            })?;
+        let size = capacity
+            .checked_mul(stride(key_size, element_size))
+            .and_then(|size| size.checked_add(entries_offset(capacity)))
+            .ok_or_else(|| PyValueError::new_err("The conflating queue is too large"))?;
+        let shmem = crate::py_queue::create_shmem(&name, size)?;
+        let queue = Self {
+            name,
+            shmem: ShmemWrapper::new(shmem),
+            key_size,
+            element_size,
+            capacity,
+        };
+        let header = unsafe { &mut *(queue.shmem.as_ptr() as *mut ConflatingHeader) };
+        header.key_size = key_size as u64;
+        header.element_size = element_size as u64;
+        header.capacity = capacity as u64;
+        unsafe {
+            for position in 0..table_size(capacity) {
+                *queue.table(position) = NIL;
+            }
+            for entry in 0..capacity {
+                *queue.free(entry) = entry as u32;
+            }
+        }
+        header.magic.store(MAGIC, Ordering::Release);
+        Ok(queue)
+    }
+
+    /// Name of the shared memory segment.
+    #[getter]
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Maximum size of a key in bytes.
+    #[getter]
+    fn key_size(&self) -> usize {
+        self.key_size
+    }
+
+    /// Maximum size of a value in bytes.
+    #[getter]
+    fn element_size(&self) -> usize {
+        self.element_size
+    }
+
+    /// Maximum number of pending keys.
+    #[getter]
+    fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of pending keys, a snapshot that concurrent handles may change.
+    fn __len__(&self) -> usize {
+        self.header().len.load(Ordering::Relaxed) as usize
+    }
+
+    /// Enqueues `value` for `key`, replacing the value still pending for the key, which
+    /// then keeps its position in the queue.
+    ///
+    /// # Arguments
+    /// - `key` (bytes): The key, at most `key_size` bytes.
+    /// - `value` (bytes): The value, at most `element_size` bytes.
+    /// - `timeout` (float, optional): Maximum time to wait for room for a new key in
+    ///   seconds; waits indefinitely if omitted, and does not wait if 0.
+    ///
+    /// # Returns
+    /// - (bool): Whether a pending value was replaced.
+    ///
+    /// # Errors
+    /// Raises `ValueError` if the key or value is too large, `Full` if the queue stays
+    /// full of other keys beyond the timeout, or `RuntimeError` if the queue is
+    /// poisoned.
+    #[pyo3(signature = (key, value, timeout=None))]
+    fn put(
+        &self,
+        py: Python<'_>,
+        key: Cow<[u8]>,
+        value: Cow<[u8]>,
+        timeout: Option<f64>,
+    ) -> PyResult<bool> {
+        if key.len() > self.key_size {
+            return Err(PyValueError::new_err(format!(
+                "Key of {} bytes is longer than key_size {}",
+                key.len(),
+                self.key_size
+            )));
+        }
+        if value.len() > self.element_size {
+            return Err(PyValueError::new_err(format!(
+                "Item of {} bytes does not fit in elements of {} bytes",
+                value.len(),
+                self.element_size
+            )));
+        }
+        region::wait_for(
+            py,
+            &self.header().not_full,
+            timeout,
+            || self.try_put(&key, &value),
+            || Full::new_err(format!("Conflating queue '{}' is full", self.name)),
+        )
+    }
+
+    /// Dequeues the oldest pending key with its latest value.
+    ///
+    /// # Arguments
+    /// - `timeout` (float, optional): Maximum time to wait for a key in seconds; waits
+    ///   indefinitely if omitted, and does not wait if 0.
+    ///
+    /// # Returns
+    /// - (tuple[bytes, bytes]): The key and its value.
+    ///
+    /// # Errors
+    /// Raises `Empty` if the queue stays empty beyond the timeout, or `RuntimeError` if
+    /// the queue is poisoned.
+    #[pyo3(signature = (timeout=None))]
+    fn get<'py>(&self, py: Python<'py>, timeout: Option<f64>) -> PyResult<Bound<'py, PyTuple>> {
+        let (key, value) = region::wait_for(
+            py,
+            &self.header().not_empty,
+            timeout,
+            || self.try_get(),
+            || Empty::new_err(format!("Conflating queue '{}' is empty", self.name)),
+        )?;
+        PyTuple::new(py, [PyBytes::new(py, &key), PyBytes::new(py, &value)])
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ConflatingQueue(name='{}', key_size={}, element_size={}, capacity={}, len={})",
+            self.name,
+            self.key_size,
+            self.element_size,
+            self.capacity,
+            self.__len__()
+        )
+    }
+}
@@ -0,0 +1,255 @@
+use crate::crypto::{CIPHER_AES_256_GCM, CIPHER_CHACHA20_POLY1305, CIPHER_MASK, ENVELOPE_SIZE};
+use crate::futex::WaitSignal;
+use crate::message::{
+    CHECKSUM_SIZE, META_CHECKSUM, META_DEADLINE, META_HEADERS, META_PRODUCER_ID, META_SEQUENCE,
+    META_TIMESTAMP,
+};
+use crate::mpmc_queue::{
+    compute_buffer_layout, compute_lane_offset, CellWidth, MpmcQueueHeader, SlotLayout, CACHE_LINE,
+    CONFIG_WORDS, OWNER_WORDS, PROCESS_SLOTS, QUEUE_MAGIC, SLOT_OWNERS, SLOT_PADDED,
+};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::mem::{align_of, offset_of, size_of};
+
+/// Version of the shared-memory layout described by [`layout_entries`].
+///
+/// Bump it whenever an entry changes, and check in a golden file for the
+/// new version so other implementations can follow.
+pub const LAYOUT_VERSION: usize = 12;
+
+/// Sample slot layouts whose computed regions are pinned by the golden files.
+const SAMPLES: [(&str, SlotLayout, usize); 5] = [
+    (
+        "plain",
+        SlotLayout {
+            element_size: 24,
+            meta_size: 0,
+            meta_flags: 0,
+            cell_width: CellWidth::Wide,
+            padded: false,
+            owners: false,
+        },
+        16,
+    ),
+    (
+        "narrow",
+        SlotLayout {
+            element_size: 24,
+            meta_size: 0,
+            meta_flags: 0,
+            cell_width: CellWidth::Narrow,
+            padded: false,
+            owners: false,
+        },
+        16,
+    ),
+    (
+        "padded",
+        SlotLayout {
+            element_size: 24,
+            meta_size: 0,
+            meta_flags: 0,
+            cell_width: CellWidth::Wide,
+            padded: true,
+            owners: false,
+        },
+        16,
+    ),
+    (
+        "metadata",
+        SlotLayout {
+            element_size: 100,
+            meta_size: 16,
+            meta_flags: META_SEQUENCE | META_TIMESTAMP,
+            cell_width: CellWidth::Narrow,
+            padded: false,
+            owners: false,
+        },
+        8,
+    ),
+    (
+        "owners",
+        SlotLayout {
+            element_size: 24,
+            meta_size: 0,
+            meta_flags: 0,
+            cell_width: CellWidth::Narrow,
+            padded: false,
+            owners: true,
+        },
+        16,
+    ),
+];
+
+/// Returns every byte offset, size and constant an implementation must agree
+/// on to share queues with this one, in a stable order.
+pub fn layout_entries() -> Vec<(String, usize)> {
+    let mut entries: Vec<(String, usize)> = vec![
+        ("pointer_width".into(), usize::BITS as usize),
+        ("layout_version".into(), LAYOUT_VERSION),
+        ("header.size".into(), size_of::<MpmcQueueHeader>()),
+        ("header.align".into(), align_of::<MpmcQueueHeader>()),
+    ];
+    let fields = [
+        ("magic", offset_of!(MpmcQueueHeader, magic)),
+        (
+            "layout_version",
+            offset_of!(MpmcQueueHeader, layout_version),
+        ),
+        ("element_size", offset_of!(MpmcQueueHeader, element_size)),
+        ("buffer_mask", offset_of!(MpmcQueueHeader, buffer_mask)),
+        ("meta_size", offset_of!(MpmcQueueHeader, meta_size)),
+        ("meta_flags", offset_of!(MpmcQueueHeader, meta_flags)),
+        ("cell_size", offset_of!(MpmcQueueHeader, cell_size)),
+        ("checksum", offset_of!(MpmcQueueHeader, checksum)),
+        ("enqueue_pos", offset_of!(MpmcQueueHeader, enqueue_pos)),
+        ("dequeue_pos", offset_of!(MpmcQueueHeader, dequeue_pos)),
+        ("lane_offset", offset_of!(MpmcQueueHeader, lane_offset)),
+        ("config", offset_of!(MpmcQueueHeader, config)),
+        ("not_full", offset_of!(MpmcQueueHeader, not_full)),
+        ("not_empty", offset_of!(MpmcQueueHeader, not_empty)),
+        ("ready", offset_of!(MpmcQueueHeader, ready)),
+        ("closed", offset_of!(MpmcQueueHeader, closed)),
+        ("unfinished", offset_of!(MpmcQueueHeader, unfinished)),
+        ("all_done", offset_of!(MpmcQueueHeader, all_done)),
+        ("processes", offset_of!(MpmcQueueHeader, processes)),
+    ];
+    entries.extend(
+        fields
+            .iter()
+            .map(|(name, offset)| (format!("header.{}.offset", name), *offset)),
+    );
+    entries.extend([
+        ("header.magic".into(), QUEUE_MAGIC as usize),
+        ("header.config.words".into(), CONFIG_WORDS),
+        ("header.processes.slots".into(), PROCESS_SLOTS),
+        ("header.signal.size".into(), size_of::<WaitSignal>()),
+        ("cell.wide.size".into(), CellWidth::Wide.size()),
+        ("cell.narrow.size".into(), CellWidth::Narrow.size()),
+        ("slot.cache_line".into(), CACHE_LINE),
+        ("slot.owner_words".into(), OWNER_WORDS),
+        ("flags.sequence".into(), META_SEQUENCE as usize),
+        ("flags.timestamp".into(), META_TIMESTAMP as usize),
+        ("flags.producer_id".into(), META_PRODUCER_ID as usize),
+        ("flags.headers".into(), META_HEADERS as usize),
+        ("flags.deadline".into(), META_DEADLINE as usize),
+        ("flags.checksum".into(), META_CHECKSUM as usize),
+        ("flags.cipher_mask".into(), CIPHER_MASK as usize),
+        (
+            "flags.cipher.chacha20_poly1305".into(),
+            CIPHER_CHACHA20_POLY1305 as usize,
+        ),
+        (
+            "flags.cipher.aes_256_gcm".into(),
+            CIPHER_AES_256_GCM as usize,
+        ),
+        ("flags.slot_padded".into(), SLOT_PADDED as usize),
+        ("flags.slot_owners".into(), SLOT_OWNERS as usize),
+        ("envelope.size".into(), ENVELOPE_SIZE),
+        ("checksum.size".into(), CHECKSUM_SIZE),
+    ]);
+    for (name, layout, capacity) in SAMPLES {
+        let regions = compute_buffer_layout(&layout, capacity);
+        entries.extend([
+            (format!("sample.{}.stride", name), layout.stride()),
+            (
+                format!("sample.{}.cells_offset", name),
+                regions.cells_offset,
+            ),
+            (format!("sample.{}.cells_size", name), regions.cells_size),
+            (
+                format!("sample.{}.owners_offset", name),
+                regions.owners_offset,
+            ),
+            (format!("sample.{}.owners_size", name), regions.owners_size),
+            (format!("sample.{}.data_offset", name), regions.data_offset),
+            (format!("sample.{}.data_size", name), regions.data_size),
+            (
+                format!("sample.{}.lane_offset", name),
+                compute_lane_offset(&layout, capacity),
+            ),
+        ]);
+    }
+    entries
+}
+
+/// Serializes the layout entries as a flat JSON object, one entry per line.
+pub fn layout_json() -> String {
+    let body = layout_entries()
+        .iter()
+        .map(|(key, value)| format!("  \"{}\": {}", key, value))
+        .collect::<Vec<_>>()
+        .join(",\n");
+    format!("{{\n{}\n}}\n", body)
+}
+
+/// Compares a golden layout, as produced by [`layout_json`], against this build.
+///
+/// # Returns
+/// One human-readable line per missing, differing or unexpected entry;
+/// empty when the layouts match.
+pub fn verify_layout(golden: &str) -> Vec<String> {
+    let expected = match parse_flat_json(golden) {
+        Some(expected) => expected,
+        None => return vec!["golden layout is not a flat JSON object of integers".into()],
+    };
+    let actual = layout_entries();
+    let mut mismatches = Vec::new();
+    for (key, value) in &expected {
+        match actual.iter().find(|(k, _)| k == key) {
+            None => mismatches.push(format!("{}: unknown entry", key)),
+            Some((_, v)) if v != value => {
+                mismatches.push(format!("{}: expected {}, got {}", key, value, v))
+            }
+            Some(_) => {}
+        }
+    }
+    for (key, _) in &actual {
+        if !expected.iter().any(|(k, _)| k == key) {
+            mismatches.push(format!("{}: missing from golden layout", key));
+        }
+    }
+    mismatches
+}
+
+/// Parses a JSON object whose values are all non-negative integers.
+fn parse_flat_json(text: &str) -> Option<Vec<(String, usize)>> {
+    let body = text.trim().strip_prefix('{')?.strip_suffix('}')?.trim();
+    if body.is_empty() {
+        return Some(Vec::new());
+    }
+    body.split(',')
+        .map(|entry| {
+            let (key, value) = entry.split_once(':')?;
+            let key = key.trim().strip_prefix('"')?.strip_suffix('"')?;
+            Some((key.to_string(), value.trim().parse().ok()?))
+        })
+        .collect()
+}
+
+/// Returns the layout of this build as a flat JSON object.
+///
+/// Golden copies are checked in per pointer width and layout version, so
+/// implementations in other languages can prove byte-level compatibility.
+#[pyfunction]
+pub fn layout_descriptor() -> String {
+    layout_json()
+}
+
+/// Checks a golden layout, as returned by `layout_descriptor()`, against this build.
+///
+/// # Errors
+/// Raises `ValueError` listing every mismatching entry.
+#[pyfunction(name = "verify_layout")]
+pub fn py_verify_layout(golden: &str) -> PyResult<()> {
+    let mismatches = verify_layout(golden);
+    if mismatches.is_empty() {
+        Ok(())
+    } else {
+        Err(PyValueError::new_err(format!(
+            "Layout mismatch: {}",
+            mismatches.join("; ")
+        )))
+    }
+}
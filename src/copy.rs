@@ -0,0 +1,74 @@
+//! Copies of payloads into and out of queue slots.
+//!
+//! Payloads of at least [`STREAMING_THRESHOLD`] bytes are copied with non-temporal
+//! stores where the CPU has them, so that moving a large element does not evict the
+//! working set of the copying thread from its caches: the destination goes straight
+//! to memory instead of being read in first and written back later. Smaller payloads,
+//! and every payload on other architectures, use the vectorized `memcpy` of the
+//! standard library.
+//!
+//! Non-temporal stores are weakly ordered even on x86_64, so a streaming copy ends
+//! with a store fence; the release that publishes or frees the slot then orders it
+//! like any other write.
+
+/// Size in bytes from which payloads are copied with non-temporal stores.
+pub const STREAMING_THRESHOLD: usize = 32 * 1024;
+
+/// Copies `src` into the slot payload `dst`.
+///
+/// # Panics
+/// Panics if the lengths differ, like `copy_from_slice`.
+#[inline]
+pub fn write_payload(dst: &mut [u8], src: &[u8]) {
+    copy(dst, src);
+}
+
+/// Copies the slot payload `src` out into `dst`.
+///
+/// # Panics
+/// Panics if the lengths differ, like `copy_from_slice`.
+#[inline]
+pub fn read_payload(dst: &mut [u8], src: &[u8]) {
+    copy(dst, src);
+}
+
+#[inline]
+fn copy(dst: &mut [u8], src: &[u8]) {
+    assert_eq!(
+        dst.len(),
+        src.len(),
+        "source and destination lengths differ"
+    );
+    if src.len() < STREAMING_THRESHOLD {
+        dst.copy_from_slice(src);
+        return;
+    }
+    // Both slices are valid for `len` bytes and cannot overlap, as `dst` is
+    // borrowed mutably.
+    unsafe { stream(dst.as_mut_ptr(), src.as_ptr(), src.len()) }
+}
+
+/// Copies `len` bytes with non-temporal stores of 16 bytes, aligning `dst` first.
+#[cfg(target_arch = "x86_64")]
+unsafe fn stream(dst: *mut u8, src: *const u8, len: usize) {
+    use std::arch::x86_64::{__m128i, _mm_loadu_si128, _mm_sfence, _mm_stream_si128};
+
+    // SSE2 is part of the x86_64 baseline, so no runtime detection is needed.
+    let head = dst.align_offset(16).min(len);
+    std::ptr::copy_nonoverlapping(src, dst, head);
+    let mut offset = head;
+    while offset + 64 <= len {
+        for lane in (0..64).step_by(16) {
+            let value = _mm_loadu_si128(src.add(offset + lane) as *const __m128i);
+            _mm_stream_si128(dst.add(offset + lane) as *mut __m128i, value);
+        }
+        offset += 64;
+    }
+    std::ptr::copy_nonoverlapping(src.add(offset), dst.add(offset), len - offset);
+    _mm_sfence();
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+unsafe fn stream(dst: *mut u8, src: *const u8, len: usize) {
+    std::ptr::copy_nonoverlapping(src, dst, len);
+}
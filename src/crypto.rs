@@ -0,0 +1,169 @@
+use aes_gcm::Aes256Gcm;
+use chacha20poly1305::aead::{AeadCore, AeadInPlace, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Nonce, Tag};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::collections::HashMap;
+
+/// Cipher selector stored in bits 8..16 of the header `meta_flags`.
+pub const CIPHER_MASK: u32 = 0xff << 8;
+pub const CIPHER_CHACHA20_POLY1305: u32 = 1 << 8;
+pub const CIPHER_AES_256_GCM: u32 = 2 << 8;
+
+const KEY_SIZE: usize = 32;
+const NONCE_SIZE: usize = 12;
+const TAG_SIZE: usize = 16;
+
+/// Bytes added in front of every encrypted payload: key id, nonce and tag.
+pub const ENVELOPE_SIZE: usize = 1 + NONCE_SIZE + TAG_SIZE;
+
+/// Authenticated ciphers available for payload encryption.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CipherKind {
+    ChaCha20Poly1305,
+    Aes256Gcm,
+}
+
+impl CipherKind {
+    /// Parses the Python-facing cipher name.
+    pub fn from_name(name: &str) -> PyResult<Self> {
+        match name {
+            "chacha20-poly1305" => Ok(CipherKind::ChaCha20Poly1305),
+            "aes-256-gcm" => Ok(CipherKind::Aes256Gcm),
+            other => Err(PyValueError::new_err(format!(
+                "Unknown encryption '{}': expected 'chacha20-poly1305' or 'aes-256-gcm'",
+                other
+            ))),
+        }
+    }
+
+    /// Reads the cipher recorded in the header flags, if any.
+    pub fn from_flags(flags: u32) -> Option<Self> {
+        match flags & CIPHER_MASK {
+            CIPHER_CHACHA20_POLY1305 => Some(CipherKind::ChaCha20Poly1305),
+            CIPHER_AES_256_GCM => Some(CipherKind::Aes256Gcm),
+            _ => None,
+        }
+    }
+
+    /// Returns the header flag for this cipher.
+    pub fn flag(self) -> u32 {
+        match self {
+            CipherKind::ChaCha20Poly1305 => CIPHER_CHACHA20_POLY1305,
+            CipherKind::Aes256Gcm => CIPHER_AES_256_GCM,
+        }
+    }
+}
+
+/// Errors raised while opening an encrypted payload.
+#[derive(Debug)]
+pub enum CryptoError {
+    UnknownKey { key_id: u8 },
+    AuthenticationFailed { key_id: u8 },
+}
+
+enum CipherKey {
+    ChaCha20Poly1305(ChaCha20Poly1305),
+    Aes256Gcm(Box<Aes256Gcm>),
+}
+
+impl CipherKey {
+    fn new(kind: CipherKind, key: &[u8]) -> PyResult<Self> {
+        if key.len() != KEY_SIZE {
+            return Err(PyValueError::new_err(format!(
+                "Invalid key length: expected {}, got {}",
+                KEY_SIZE,
+                key.len()
+            )));
+        }
+        Ok(match kind {
+            CipherKind::ChaCha20Poly1305 => {
+                CipherKey::ChaCha20Poly1305(ChaCha20Poly1305::new_from_slice(key).unwrap())
+            }
+            CipherKind::Aes256Gcm => {
+                CipherKey::Aes256Gcm(Box::new(Aes256Gcm::new_from_slice(key).unwrap()))
+            }
+        })
+    }
+}
+
+/// Keys known to a queue handle, indexed by the key id stored with each message.
+///
+/// Keys never enter shared memory: every process supplies them when it
+/// attaches, and producers seal payloads with the active key. Rotation adds a
+/// new key id while consumers keep the old one until the backlog drains.
+pub struct Keyring {
+    kind: CipherKind,
+    keys: HashMap<u8, CipherKey>,
+    active: u8,
+}
+
+impl Keyring {
+    /// Builds a keyring; the active key defaults to the highest key id.
+    pub fn new(kind: CipherKind, keys: HashMap<u8, Vec<u8>>, active: Option<u8>) -> PyResult<Self> {
+        let active = match active.or_else(|| keys.keys().max().copied()) {
+            Some(active) => active,
+            None => {
+                return Err(PyValueError::new_err(
+                    "keys required for an encrypted queue",
+                ))
+            }
+        };
+        let mut keyring = Self {
+            kind,
+            keys: HashMap::with_capacity(keys.len()),
+            active,
+        };
+        for (key_id, key) in keys {
+            keyring.keys.insert(key_id, CipherKey::new(kind, &key)?);
+        }
+        if !keyring.keys.contains_key(&active) {
+            return Err(PyValueError::new_err(format!("Unknown key id {}", active)));
+        }
+        Ok(keyring)
+    }
+
+    /// Adds (or replaces) `key_id` when `key` is given and makes it the active key.
+    pub fn rotate(&mut self, key_id: u8, key: Option<&[u8]>) -> PyResult<()> {
+        if let Some(key) = key {
+            self.keys.insert(key_id, CipherKey::new(self.kind, key)?);
+        } else if !self.keys.contains_key(&key_id) {
+            return Err(PyValueError::new_err(format!("Unknown key id {}", key_id)));
+        }
+        self.active = key_id;
+        Ok(())
+    }
+
+    /// Encrypts `payload` in place with the active key, authenticating `aad`,
+    /// and writes the key id, nonce and tag into `envelope`.
+    pub fn seal(&self, envelope: &mut [u8], aad: &[u8], payload: &mut [u8]) {
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let tag = match &self.keys[&self.active] {
+            CipherKey::ChaCha20Poly1305(cipher) => {
+                cipher.encrypt_in_place_detached(&nonce, aad, payload)
+            }
+            CipherKey::Aes256Gcm(cipher) => cipher.encrypt_in_place_detached(&nonce, aad, payload),
+        }
+        .expect("payload within AEAD limits");
+        envelope[0] = self.active;
+        envelope[1..1 + NONCE_SIZE].copy_from_slice(&nonce);
+        envelope[1 + NONCE_SIZE..].copy_from_slice(&tag);
+    }
+
+    /// Decrypts `payload` in place using the key id, nonce and tag from `envelope`.
+    pub fn open(&self, envelope: &[u8], aad: &[u8], payload: &mut [u8]) -> Result<(), CryptoError> {
+        let key_id = envelope[0];
+        let nonce = Nonce::from_slice(&envelope[1..1 + NONCE_SIZE]);
+        let tag = Tag::from_slice(&envelope[1 + NONCE_SIZE..ENVELOPE_SIZE]);
+        let result = match self.keys.get(&key_id) {
+            Some(CipherKey::ChaCha20Poly1305(cipher)) => {
+                cipher.decrypt_in_place_detached(nonce, aad, payload, tag)
+            }
+            Some(CipherKey::Aes256Gcm(cipher)) => {
+                cipher.decrypt_in_place_detached(nonce, aad, payload, tag)
+            }
+            None => return Err(CryptoError::UnknownKey { key_id }),
+        };
+        result.map_err(|_| CryptoError::AuthenticationFailed { key_id })
+    }
+}
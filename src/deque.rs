@@ -0,0 +1,344 @@
+//! A double-ended queue in shared memory, for work-stealing schedulers whose workers
+//! push and pop at one end while idle peers steal from the other.
+//!
+//! The segment starts with a [`DequeHeader`], followed by `capacity` slots of a
+//! 4-byte length and `element_size` bytes each. `head` and `len` describe the ring
+//! and change only under the spinlock of the header, which is held just long enough
+//! to copy one element; both ends share it, since either may reach the other's
+//! element when one is left. A process that exits while holding it poisons the
+//! deque, which it may have left half-updated.
+
+use crate::errors::{Empty, Full, InvalidParameters};
+use crate::futex::WaitSignal;
+use crate::region::{self, Abandoned, SpinLock};
+use crate::shmem_wrapper::ShmemWrapper;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use std::borrow::Cow;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Marks segments holding a deque.
+const MAGIC: u64 = u64::from_le_bytes(*b"ZQDEQU01");
+
+/// Size of the length in front of each element.
+const LEN: usize = size_of::<u32>();
+
+/// Header of a deque segment.
+#[repr(C, align(64))]
+struct DequeHeader {
+    magic: AtomicU64,
+    element_size: u64,
+    capacity: u64,
+    lock: SpinLock,
+    /// Ring index of the front element.
+    head: AtomicU64,
+    /// Number of elements.
+    len: AtomicU64,
+    not_empty: WaitSignal,
+    not_full: WaitSignal,
+}
+
+/// Returns the distance between consecutive slots holding `element_size` bytes.
+fn stride(element_size: usize) -> usize {
+    (LEN + element_size).next_multiple_of(8)
+}
+
+/// The end of a deque an operation works on.
+#[derive(Clone, Copy)]
+enum End {
+    Front,
+    Back,
+}
+
+/// A bounded double-ended queue of byte strings in shared memory.
+///
+/// The handle that creates the deque removes its segment when garbage-collected.
+#[pyclass(frozen, module = "zeroq")]
+pub struct Deque {
+    name: String,
+    shmem: ShmemWrapper,
+    element_size: usize,
+    capacity: usize,
+}
+
+impl Deque {
+    fn header(&self) -> &DequeHeader {
+        unsafe { &*(self.shmem.as_ptr() as *const DequeHeader) }
+    }
+
+    /// Returns a pointer to the slot at ring index `index`.
+    fn slot_ptr(&self, index: usize) -> *mut u8 {
+        unsafe {
+            self.shmem
+                .as_ptr()
+                .add(size_of::<DequeHeader>() + index * stride(self.element_size))
+                as *mut u8
+        }
+    }
+
+    /// Copies `data` into a new element at `end`, unless the deque is full.
+    fn try_push(&self, end: End, data: &[u8]) -> PyResult<Option<()>> {
+        let header = self.header();
+        let guard = header.lock.lock(Abandoned::Poison)?;
+        let len = header.len.load(Ordering::Relaxed) as usize;
+        if len == self.capacity {
+            return Ok(None);
+        }
+        let head = header.head.load(Ordering::Relaxed) as usize;
+        let index = match end {
+            End::Front => {
+                let index = (head + self.capacity - 1) % self.capacity;
+                header.head.store(index as u64, Ordering::Relaxed);
+                index
+            }
+            End::Back => (head + len) % self.capacity,
+        };
+        let slot = self.slot_ptr(index);
+        unsafe {
+            std::ptr::copy_nonoverlapping((data.len() as u32).to_le_bytes().as_ptr(), slot, LEN);
+            std::ptr::copy_nonoverlapping(data.as_ptr(), slot.add(LEN), data.len());
+        }
+        header.len.store(len as u64 + 1, Ordering::Relaxed);
+        drop(guard);
+        header.not_empty.notify();
+        Ok(Some(()))
+    }
+
+    /// Copies out and removes the element at `end`, unless the deque is empty.
+    fn try_pop(&self, end: End) -> PyResult<Option<Vec<u8>>> {
+        let header = self.header();
+        let guard = header.lock.lock(Abandoned::Poison)?;
+        let len = header.len.load(Ordering::Relaxed) as usize;
+        if len == 0 {
+            return Ok(None);
+        }
+        let head = header.head.load(Ordering::Relaxed) as usize;
+        let index = match end {
+            End::Front => {
+                header
+                    .head
+                    .store(((head + 1) % self.capacity) as u64, Ordering::Relaxed);
+                head
+            }
+            End::Back => (head + len - 1) % self.capacity,
+        };
+        let slot = self.slot_ptr(index);
+        let data = unsafe {
+            let size = u32::from_le_bytes(*(slot as *const [u8; LEN])) as usize;
+            std::slice::from_raw_parts(slot.add(LEN), size.min(self.element_size)).to_vec()
+        };
+        header.len.store(len as u64 - 1, Ordering::Relaxed);
+        drop(guard);
+        header.not_full.notify();
+        Ok(Some(data))
+    }
+
+    fn push(&self, py: Python<'_>, end: End, item: &[u8], timeout: Option<f64>) -> PyResult<()> {
+        if item.len() > self.element_size {
+            return Err(PyValueError::new_err(format!(
+                "Item of {} bytes does not fit in elements of {} bytes",
+                item.len(),
+                self.element_size
+            )));
+        }
+        region::wait_for(
+            py,
+            &self.header().not_full,
+            timeout,
+            || self.try_push(end, item),
+            || Full::new_err(format!("Deque '{}' is full", self.name)),
+        )
+    }
+
+    fn pop<'py>(
+        &self,
+        py: Python<'py>,
+        end: End,
+        timeout: Option<f64>,
+    ) -> PyResult<Bound<'py, PyBytes>> {
+        let data = region::wait_for(
+            py,
+            &self.header().not_empty,
+            timeout,
+            || self.try_pop(end),
+            || Empty::new_err(format!("Deque '{}' is empty", self.name)),
+        )?;
+        Ok(PyBytes::new(py, &data))
+    }
+}
+
+#[pymethods]
+impl Deque {
+    /// Creates or attaches to a deque in shared memory.
+    ///
+    /// # Arguments
+    /// - `name` (str): Shared memory segment name.
+    /// - `element_size` (int): Maximum size of an element in bytes (required if
+    ///   creating).
+    /// - `capacity` (int): Maximum number of elements (required if creating).
+    /// - `create` (bool, default=True): Whether to create a new deque.
+    ///
+    /// # Errors
+    /// Raises `ValueError` for invalid parameters, `AlreadyExists` if a deque is to be
+    /// created under a name that is taken, or `OSError` if the segment cannot be
+    /// created or opened or holds no deque.
+    #[new]
+    #[pyo3(signature = (name, element_size=None, capacity=None, create=true))]
+    fn new(
+        py: Python<'_>,
+        name: String,
+        element_size: Option<usize>,
+        capacity: Option<usize>,
+        create: bool,
+    ) -> PyResult<Self> {
+        if !create {
+            let shmem = region::attach(py, &name, MAGIC, size_of::<DequeHeader>(), "deque")?;
+            let header = unsafe { &*(shmem.as_ptr() as *const DequeHeader) };
+            let found = (header.element_size as usize, header.capacity as usize);
+            if shmem.len() < size_of::<DequeHeader>() + found.1 * stride(found.0) {
+                return Err(region::holds_no(&name, "deque"));
+            }
+            if element_size.is_some_and(|size| size != found.0)
+                || capacity.is_some_and(|capacity| capacity != found.1)
+            {
+                return Err(InvalidParameters::new_err(format!(
+                    "Deque '{}' exists with element_size {} and capacity {}",
+                    name, found.0, found.1
+                )));
+            }
+            return Ok(Self {
+                name,
+                shmem,
+                element_size: found.0,
+                capacity: found.1,
+            });
+        }
+        let element_size = element_size
+            .filter(|size| (1..=u32::MAX as usize).contains(size))
+            .ok_or_else(|| {
+                PyValueError::new_err(format!(
+                    "element_size between 1 and {} required when create=true",
+                    u32::MAX
+                ))
+            })?;
+        let capacity = capacity
+            .filter(|capacity| *capacity > 0)
+            .ok_or_else(|| PyValueError::new_err("capacity > 0 required when create=true"))?;
+        let size = capacity
+            .checked_mul(stride(element_size))
+            .and_then(|size| size.checked_add(size_of::<DequeHeader>()))
+            .ok_or_else(|| PyValueError::new_err("The deque is too large"))?;
+        let shmem = crate::py_queue::create_shmem(&name, size)?;
+        let deque = Self {
+            name,
+            shmem: ShmemWrapper::new(shmem),
+            element_size,
+            capacity,
+        };
+        let header = unsafe { &mut *(deque.shmem.as_ptr() as *mut DequeHeader) };
+        header.element_size = element_size as u64;
+        header.capacity = capacity as u64;
+        header.magic.store(MAGIC, Ordering::Release);
+        Ok(deque)
+    }
+
+    /// Name of the shared memory segment.
+    #[getter]
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Maximum size of an element in bytes.
+    #[getter]
+    fn element_size(&self) -> usize {
+        self.element_size
+    }
+
+    /// Maximum number of elements.
+    #[getter]
+    fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of elements, a snapshot that concurrent handles may change.
+    fn __len__(&self) -> usize {
+        self.header().len.load(Ordering::Relaxed) as usize
+    }
+
+    /// Adds an element at the front.
+    ///
+    /// # Arguments
+    /// - `item` (bytes): The element, at most `element_size` bytes.
+    /// - `timeout` (float, optional): Maximum time to wait for room in seconds; waits
+    ///   indefinitely if omitted, and does not wait if 0.
+    ///
+    /// # Errors
+    /// Raises `ValueError` if `item` is larger than an element, `Full` if the deque
+    /// stays full beyond the timeout, or `RuntimeError` if the deque is poisoned.
+    #[pyo3(signature = (item, timeout=None))]
+    fn push_front(&self, py: Python<'_>, item: Cow<[u8]>, timeout: Option<f64>) -> PyResult<()> {
+        self.push(py, End::Front, &item, timeout)
+    }
+
+    /// Adds an element at the back.
+    ///
+    /// # Arguments
+    /// - `item` (bytes): The element, at most `element_size` bytes.
+    /// - `timeout` (float, optional): Maximum time to wait for room in seconds; waits
+    ///   indefinitely if omitted, and does not wait if 0.
+    ///
+    /// # Errors
+    /// Raises `ValueError` if `item` is larger than an element, `Full` if the deque
+    /// stays full beyond the timeout, or `RuntimeError` if the deque is poisoned.
+    #[pyo3(signature = (item, timeout=None))]
+    fn push_back(&self, py: Python<'_>, item: Cow<[u8]>, timeout: Option<f64>) -> PyResult<()> {
+        self.push(py, End::Back, &item, timeout)
+    }
+
+    /// Removes and returns the element at the front.
+    ///
+    /// # Arguments
+    /// - `timeout` (float, optional): Maximum time to wait for an element in seconds;
+    ///   waits indefinitely if omitted, and does not wait if 0.
+    ///
+    /// # Errors
+    /// Raises `Empty` if the deque stays empty beyond the timeout, or `RuntimeError`
+    /// if the deque is poisoned.
+    #[pyo3(signature = (timeout=None))]
+    fn pop_front<'py>(
+        &self,
+        py: Python<'py>,
+        timeout: Option<f64>,
+    ) -> PyResult<Bound<'py, PyBytes>> {
+        self.pop(py, End::Front, timeout)
+    }
+
+    /// Removes and returns the element at the back.
+    ///
+    /// # Arguments
+    /// - `timeout` (float, optional): Maximum time to wait for an element in seconds;
+    ///   waits indefinitely if omitted, and does not wait if 0.
+    ///
+    /// # Errors
+    /// Raises `Empty` if the deque stays empty beyond the timeout, or `RuntimeError`
+    /// if the deque is poisoned.
+    #[pyo3(signature = (timeout=None))]
+    fn pop_back<'py>(
+        &self,
+        py: Python<'py>,
+        timeout: Option<f64>,
+    ) -> PyResult<Bound<'py, PyBytes>> {
+        self.pop(py, End::Back, timeout)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Deque(name='{}', element_size={}, capacity={}, len={})",
+            self.name,
+            self.element_size,
+            self.capacity,
+            self.__len__()
+        )
+    }
+}
@@ -1,52 +1,78 @@
-use crate::mpmc_queue::MpmcQueueError;
-use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use crate::crypto::CryptoError;
+use crate::mpmc_queue::{CapacityError, LayoutError, MpmcQueueError, ValidationError};
+use pyo3::exceptions::{PyFileExistsError, PyOSError, PyRuntimeError, PyValueError};
 use pyo3::prelude::*;
 
+/// Exceptions of the standard `queue` module, so that `except queue.Empty:` handlers
+/// written for `queue.Queue` or `multiprocessing.Queue` also catch ours.
+mod stdlib {
+    pyo3::import_exception!(queue, Empty);
+    pyo3::import_exception!(queue, Full);
+}
+
 // Define custom Python exceptions that map Rust errors to Python-friendly errors.
 // These exceptions allow the Rust library to raise meaningful errors in Python.
-pyo3::create_exception!(zeroq, Empty, PyRuntimeError);
-pyo3::create_exception!(zeroq, Full, PyRuntimeError);
+pyo3::create_exception!(zeroq, Empty, stdlib::Empty);
+pyo3::create_exception!(zeroq, Full, stdlib::Full);
+pyo3::create_exception!(zeroq, DecryptionError, PyRuntimeError);
+pyo3::create_exception!(zeroq, AlreadyExists, PyFileExistsError);
+pyo3::create_exception!(zeroq, QueueClosed, PyOSError);
+pyo3::create_exception!(zeroq, Cancelled, PyRuntimeError);
+pyo3::create_exception!(zeroq, InvalidParameters, PyValueError);
+pyo3::create_exception!(zeroq, CorruptMessage, PyRuntimeError);
+
+/// Converts payload decryption failures into `DecryptionError`.
+impl From<CryptoError> for PyErr {
+    fn from(error: CryptoError) -> Self {
+        match error {
+            CryptoError::UnknownKey { key_id } => {
+                DecryptionError::new_err(format!("Unknown key id {}", key_id))
+            }
+            CryptoError::AuthenticationFailed { key_id } => DecryptionError::new_err(format!(
+                "Message authentication failed with key id {}",
+                key_id
+            )),
+        }
+    }
+}
 
 /// Implements automatic conversion from `MpmcQueueError` to `PyErr`,
 /// allowing Rust queue errors to be seamlessly translated into Python exceptions.
 impl From<MpmcQueueError> for PyErr {
     fn from(error: MpmcQueueError) -> Self {
         match error {
-            // Handle errors related to invalid input sizes.
-            MpmcQueueError::InvalidSourceLength { expected, actual } => {
-                PyValueError::new_err(format!(
-                    "Invalid source length: expected {}, got {}",
-                    expected, actual
-                ))
-            }
-            MpmcQueueError::InvalidDestinationLength { expected, actual } => {
-                PyValueError::new_err(format!(
-                    "Invalid destination length: expected {}, got {}",
-                    expected, actual
-                ))
-            }
+            MpmcQueueError::Layout(e) => e.into(),
+            MpmcQueueError::Capacity(e) => e.into(),
+            MpmcQueueError::Validation(e) => e.into(),
+            MpmcQueueError::SlotReclaimed { .. } => PyRuntimeError::new_err(error.to_string()),
+        }
+    }
+}
 
-            // Handle queue capacity errors.
-            MpmcQueueError::QueueFull => Full::new_err("Queue is full"),
-            MpmcQueueError::QueueEmpty => Empty::new_err("Queue is empty"),
+/// Converts buffer and capacity layout errors into `ValueError`, and corrupt
+/// headers into `OSError`.
+impl From<LayoutError> for PyErr {
+    fn from(error: LayoutError) -> Self {
+        match error {
+            LayoutError::HeaderChecksumMismatch { .. } => PyOSError::new_err(error.to_string()),
+            _ => PyValueError::new_err(error.to_string()),
+        }
+    }
+}
 
-            // Handle memory-related errors.
-            MpmcQueueError::BufferTooSmall { required, provided } => {
-                PyValueError::new_err(format!(
-                    "Buffer too small: required {}, provided {}",
-                    required, provided
-                ))
-            }
-            MpmcQueueError::BufferMisaligned { expected, actual } => {
-                PyValueError::new_err(format!(
-                    "Buffer misaligned: expected {}, actual {}",
-                    expected, actual
-                ))
-            }
-            MpmcQueueError::BufferSizeNotPowerOfTwo { actual } => PyValueError::new_err(format!(
-                "Buffer size must be a power of two, got {}",
-                actual
-            )),
+/// Converts full and empty outcomes into `Full` and `Empty`.
+impl From<CapacityError> for PyErr {
+    fn from(error: CapacityError) -> Self {
+        match error {
+            CapacityError::Full => Full::new_err(error.to_string()),
+            CapacityError::Empty => Empty::new_err(error.to_string()),
         }
     }
 }
+
+/// Converts argument validation errors into `ValueError`.
+impl From<ValidationError> for PyErr {
+    fn from(error: ValidationError) -> Self {
+        PyValueError::new_err(error.to_string())
+    }
+}
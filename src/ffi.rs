@@ -0,0 +1,37 @@
+//! C entry points for implementations outside Python.
+//!
+//! Declared in `include/zeroq.h`.
+
+use crate::conformance::{layout_json, verify_layout};
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::sync::OnceLock;
+
+/// Returns the layout of this build as a NUL-terminated flat JSON object.
+///
+/// The string is owned by the library and lives for the whole process.
+#[no_mangle]
+pub extern "C" fn zeroq_layout_descriptor() -> *const c_char {
+    static DESCRIPTOR: OnceLock<CString> = OnceLock::new();
+    DESCRIPTOR
+        .get_or_init(|| CString::new(layout_json()).expect("layout JSON has no NUL bytes"))
+        .as_ptr()
+}
+
+/// Compares a golden layout against this build.
+///
+/// # Returns
+/// The number of mismatching entries, zero when the layouts match, or -1
+/// if `golden` is null or not valid UTF-8.
+///
+/// # Safety
+/// `golden` must be null or point to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn zeroq_verify_layout(golden: *const c_char) -> c_int {
+    if golden.is_null() {
+        return -1;
+    }
+    match CStr::from_ptr(golden).to_str() {
+        Ok(golden) => verify_layout(golden).len().min(c_int::MAX as usize) as c_int,
+        Err(_) => -1,
+    }
+}
@@ -0,0 +1,58 @@
+//! Tracking of `fork()` so that handles inherited by a child process can tell
+//! that they were made by their parent.
+//!
+//! The child shares the mapping of the parent, but not its threads, and must
+//! neither remove the segment nor shut the queue down on its behalf.
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+
+/// Number of `os.fork()` calls between the start of the interpreter and the
+/// current process.
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Returns the fork generation of the current process, which differs from the
+/// one recorded by a handle made before the process was forked.
+pub fn generation() -> u64 {
+    GENERATION.load(Ordering::Relaxed)
+}
+
+/// Process id of the current process, or zero until first asked for.
+static PID: AtomicU32 = AtomicU32::new(0);
+
+/// Returns the id of the current process, without a system call once known.
+pub fn pid() -> u32 {
+    match PID.load(Ordering::Relaxed) {
+        0 => {
+            let pid = std::process::id();
+            PID.store(pid, Ordering::Relaxed);
+            pid
+        }
+        pid => pid,
+    }
+}
+
+/// Bumps the fork generation in a newly forked child, which has a process id of
+/// its own.
+#[pyfunction]
+fn after_fork_in_child() {
+    GENERATION.fetch_add(1, Ordering::Relaxed);
+    PID.store(0, Ordering::Relaxed);
+}
+
+/// Registers the fork handler through `os.register_at_fork()`, which only
+/// exists where the interpreter supports `fork()`.
+///
+/// # Errors
+/// Raises the error of importing `os` or of registering the handler.
+pub fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let os = m.py().import("os")?;
+    if !os.hasattr("register_at_fork")? {
+        return Ok(());
+    }
+    let kwargs = PyDict::new(m.py());
+    kwargs.set_item("after_in_child", wrap_pyfunction!(after_fork_in_child, m)?)?;
+    os.call_method("register_at_fork", (), Some(&kwargs))?;
+    Ok(())
+}
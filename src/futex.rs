@@ -0,0 +1,150 @@
+//! Cross-process wake-ups for blocked producers and consumers.
+//!
+//! Waiting handles sleep on a word in the queue header and are woken as soon
+//! as a peer makes progress: with futexes on Linux and with shared ulocks on
+//! macOS. Elsewhere [`SUPPORTED`] is false and handles keep sleeping for
+//! their wait interval.
+
+use std::sync::atomic::{fence, AtomicU32, Ordering};
+use std::time::Duration;
+
+/// Whether this platform can wake waiters in other processes.
+pub const SUPPORTED: bool = cfg!(any(target_os = "linux", target_os = "macos"));
+
+/// A wake-up word shared by every process attached to a queue.
+///
+/// `waiters` counts the handles currently blocked on the condition, so that
+/// peers only pay for a system call when someone is actually waiting.
+#[repr(C)]
+#[derive(Debug, Default)]
+pub struct WaitSignal {
+    epoch: AtomicU32,
+    waiters: AtomicU32,
+}
+
+impl WaitSignal {
+    /// Registers the caller as a waiter until the returned guard is dropped.
+    pub fn register(&self) -> Waiter<'_> {
+        self.waiters.fetch_add(1, Ordering::SeqCst);
+        fence(Ordering::SeqCst);
+        Waiter { signal: self }
+    }
+
+    /// Wakes every registered waiter; called after a slot was published or
+    /// released.
+    #[inline]
+    pub fn notify(&self) {
+        fence(Ordering::SeqCst);
+        if self.waiters.load(Ordering::Relaxed) != 0 {
+            self.epoch.fetch_add(1, Ordering::Release);
+            futex_wake(&self.epoch);
+        }
+    }
+}
+
+/// A registration on a [`WaitSignal`].
+pub struct Waiter<'a> {
+    signal: &'a WaitSignal,
+}
+
+impl Waiter<'_> {
+    /// Returns the current epoch; read it before checking the condition and
+    /// pass it to [`Waiter::wait`] so that wake-ups in between are not lost.
+    #[inline]
+    pub fn epoch(&self) -> u32 {
+        self.signal.epoch.load(Ordering::Acquire)
+    }
+
+    /// Blocks until a peer notifies the signal after `epoch` was read, or
+    /// `timeout` elapses.
+    pub fn wait(&self, epoch: u32, timeout: Duration) {
+        futex_wait(&self.signal.epoch, epoch, timeout);
+    }
+}
+
+impl Drop for Waiter<'_> {
+    fn drop(&mut self) {
+        self.signal.waiters.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn futex_wait(word: &AtomicU32, expected: u32, timeout: Duration) {
+    let timeout = libc::timespec {
+        tv_sec: timeout.as_secs().min(libc::time_t::MAX as u64) as libc::time_t,
+        tv_nsec: timeout.subsec_nanos() as libc::c_long,
+    };
+    // Spurious returns are harmless: callers re-check the queue.
+    unsafe {
+        libc::syscall(
+            libc::SYS_futex,
+            word.as_ptr(),
+            libc::FUTEX_WAIT,
+            expected,
+            &timeout as *const libc::timespec,
+        );
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn futex_wake(word: &AtomicU32) {
+    unsafe {
+        libc::syscall(libc::SYS_futex, word.as_ptr(), libc::FUTEX_WAKE, i32::MAX);
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod ulock {
+    use std::ffi::{c_int, c_void};
+
+    /// Compare-and-wait on a 32-bit value that may be mapped by other processes.
+    pub const UL_COMPARE_AND_WAIT_SHARED: u32 = 3;
+    /// Wakes every waiter instead of one.
+    pub const ULF_WAKE_ALL: u32 = 0x100;
+
+    extern "C" {
+        pub fn __ulock_wait(
+            operation: u32,
+            addr: *mut c_void,
+            value: u64,
+            timeout_us: u32,
+        ) -> c_int;
+        pub fn __ulock_wake(operation: u32, addr: *mut c_void, wake_value: u64) -> c_int;
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn futex_wait(word: &AtomicU32, expected: u32, timeout: Duration) {
+    // A zero timeout would wait forever.
+    let timeout_us = timeout.as_micros().clamp(1, u32::MAX as u128) as u32;
+    // Spurious returns are harmless: callers re-check the queue.
+    unsafe {
+        ulock::__ulock_wait(
+            ulock::UL_COMPARE_AND_WAIT_SHARED,
+            word.as_ptr().cast(),
+            expected as u64,
+            timeout_us,
+        );
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn futex_wake(word: &AtomicU32) {
+    unsafe {
+        ulock::__ulock_wake(
+            ulock::UL_COMPARE_AND_WAIT_SHARED | ulock::ULF_WAKE_ALL,
+            word.as_ptr().cast(),
+            0,
+        );
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn futex_wait(word: &AtomicU32, expected: u32, timeout: Duration) {
+    if word.load(Ordering::Acquire) == expected {
+        std::thread::sleep(timeout);
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn futex_wake(_word: &AtomicU32) {}
@@ -1,15 +1,80 @@
+mod array;
+mod bus;
+mod byte_buffer;
+mod clock;
+mod config;
+mod conflating_queue;
+mod conformance;
+mod copy;
+mod crypto;
+mod deque;
 mod errors;
+mod ffi;
+mod fork;
+mod futex;
+mod lvc_slot;
+#[cfg(unix)]
+mod mapping;
+mod message;
 mod mpmc_queue;
+mod out_of_band;
+mod poison;
+mod pool;
+mod py_layout;
 mod py_queue;
+#[cfg(unix)]
+mod readiness;
+mod region;
+mod registry;
+mod router;
+mod segment;
+mod serializer;
+mod sharded_queue;
 mod shmem_wrapper;
+mod slot_view;
+mod spsc_queue;
+mod stack;
+mod stats;
+mod stream;
+mod struct_format;
+mod tensor;
+mod wait;
 
-use crate::errors::{Empty, Full};
+use crate::errors::{
+    AlreadyExists, Cancelled, CorruptMessage, DecryptionError, Empty, Full, InvalidParameters,
+    QueueClosed,
+};
 use pyo3::prelude::*;
 
 #[pymodule]
 fn zeroq(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<py_queue::Queue>()?;
+    m.add_class::<message::Message>()?;
+    m.add_class::<clock::ManualClock>()?;
+    m.add_class::<slot_view::SlotView>()?;
+    m.add_class::<pool::Pool>()?;
+    m.add_class::<deque::Deque>()?;
+    m.add_class::<stack::Stack>()?;
+    m.add_class::<lvc_slot::LvcSlot>()?;
+    m.add_class::<conflating_queue::ConflatingQueue>()?;
+    m.add_class::<bus::Bus>()?;
+    m.add_class::<router::Router>()?;
+    m.add_class::<spsc_queue::SpscQueue>()?;
+    m.add_class::<sharded_queue::ShardedQueue>()?;
+    m.add_function(wrap_pyfunction!(py_layout::required_size, m)?)?;
+    m.add_function(wrap_pyfunction!(py_layout::plan, m)?)?;
+    m.add_function(wrap_pyfunction!(conformance::layout_descriptor, m)?)?;
+    m.add_function(wrap_pyfunction!(conformance::py_verify_layout, m)?)?;
+    m.add_function(wrap_pyfunction!(segment::exists, m)?)?;
+    m.add_function(wrap_pyfunction!(segment::unlink, m)?)?;
     m.add("Empty", m.py().get_type::<Empty>())?;
     m.add("Full", m.py().get_type::<Full>())?;
+    m.add("DecryptionError", m.py().get_type::<DecryptionError>())?;
+    m.add("AlreadyExists", m.py().get_type::<AlreadyExists>())?;
+    m.add("QueueClosed", m.py().get_type::<QueueClosed>())?;
+    m.add("Cancelled", m.py().get_type::<Cancelled>())?;
+    m.add("InvalidParameters", m.py().get_type::<InvalidParameters>())?;
+    m.add("CorruptMessage", m.py().get_type::<CorruptMessage>())?;
+    fork::register(m)?;
     Ok(())
 }
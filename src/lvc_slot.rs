@@ -0,0 +1,295 @@
+//! A single latest value in shared memory, for control loops that publish state at a
+//! high rate to readers that only ever want the most recent sample.
+//!
+//! The segment starts with an [`LvcHeader`], followed by a 4-byte length and
+//! `element_size` bytes. Writers take the spinlock of the header and publish under a
+//! seqlock: the sequence is odd while a value is being written and advances by two
+//! per value, so readers never block writers and retry a copy that overlapped one.
+//! After a writer exited mid-write, the sequence stays odd until the next writer
+//! takes the lock over and replaces the value.
+
+use crate::errors::{Empty, InvalidParameters};
+use crate::futex::WaitSignal;
+use crate::region::{self, Abandoned, SpinLock};
+use crate::shmem_wrapper::ShmemWrapper;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use std::borrow::Cow;
+use std::sync::atomic::{fence, AtomicU64, Ordering};
+
+/// Marks segments holding a latest-value slot.
+const MAGIC: u64 = u64::from_le_bytes(*b"ZQLVCS01");
+
+/// Size of the length in front of the value.
+const LEN: usize = size_of::<u32>();
+
+/// Number of times a read retries before leaving a value being written to a wait.
+const SPINS: u32 = 1024;
+
+/// Header of a latest-value slot segment.
+#[repr(C, align(64))]
+struct LvcHeader {
+    magic: AtomicU64,
+    element_size: u64,
+    lock: SpinLock,
+    /// Twice the number of values put, plus one while a value is being written.
+    seq: AtomicU64,
+    changed: WaitSignal,
+}
+
+/// A shared-memory cell holding the latest of the values put into it.
+///
+/// Each handle remembers the version it read last, for `get_changed()`. The handle
+/// that creates the slot removes its segment when garbage-collected.
+#[pyclass(frozen, module = "zeroq")]
+pub struct LvcSlot {
+    name: String,
+    shmem: ShmemWrapper,
+    element_size: usize,
+    /// Version returned by the last read through this handle.
+    seen: AtomicU64,
+}
+
+impl LvcSlot {
+    fn header(&self) -> &LvcHeader {
+        unsafe { &*(self.shmem.as_ptr() as *const LvcHeader) }
+    }
+
+    fn value_ptr(&self) -> *mut u8 {
+        unsafe { self.shmem.as_ptr().add(size_of::<LvcHeader>()) as *mut u8 }
+    }
+
+    /// Returns the number of values put so far.
+    fn current_version(&self) -> u64 {
+        self.header().seq.load(Ordering::Acquire) / 2
+    }
+
+    /// Copies out the latest value and its version, unless nothing was put yet, the
+    /// version is not newer than `after`, or the value is still being written after
+    /// `SPINS` retries.
+    fn try_read(&self, after: u64) -> Option<(u64, Vec<u8>)> {
+        let seq = &self.header().seq;
+        for _ in 0..SPINS {
+            let before = seq.load(Ordering::Acquire);
+            if before % 2 == 1 {
+                std::hint::spin_loop();
+                continue;
+            }
+            if before / 2 <= after {
+                return None;
+            }
+            let ptr = self.value_ptr();
+            let data = unsafe {
+                let size = u32::from_le_bytes(std::ptr::read_volatile(ptr as *const [u8; LEN]));
+                let mut data = vec![0u8; (size as usize).min(self.element_size)];
+                std::ptr::copy_nonoverlapping(ptr.add(LEN), data.as_mut_ptr(), data.len());
+                data
+            };
+            fence(Ordering::Acquire);
+            if seq.load(Ordering::Relaxed) == before {
+                return Some((before / 2, data));
+            }
+        }
+        None
+    }
+
+    /// Records `version` as read, keeping a newer one a concurrent read recorded.
+    fn saw(&self, version: u64) {
+        self.seen.fetch_max(version, Ordering::Relaxed);
+    }
+}
+
+#[pymethods]
+impl LvcSlot {
+    /// Creates or attaches to a latest-value slot in shared memory.
+    ///
+    /// # Arguments
+    /// - `name` (str): Shared memory segment name.
+    /// - `element_size` (int): Maximum size of a value in bytes (required if
+    ///   creating).
+    /// - `create` (bool, default=True): Whether to create a new slot.
+    ///
+    /// # Errors
+    /// Raises `ValueError` for invalid parameters, `AlreadyExists` if a slot is to be
+    /// created under a name that is taken, or `OSError` if the segment cannot be
+    /// created or opened or holds no slot.
+    #[new]
+    #[pyo3(signature = (name, element_size=None, create=true))]
+    fn new(
+        py: Python<'_>,
+        name: String,
+        element_size: Option<usize>,
+        create: bool,
+    ) -> PyResult<Self> {
+        if !create {
+            let shmem = region::attach(py, &name, MAGIC, size_of::<LvcHeader>(), "slot")?;
+            let header = unsafe { &*(shmem.as_ptr() as *const LvcHeader) };
+            let found = header.element_size as usize;
+            if shmem.len() < size_of::<LvcHeader>() + LEN + found {
+                return Err(region::holds_no(&name, "slot"));
+            }
+            if element_size.is_some_and(|size| size != found) {
+                return Err(InvalidParameters::new_err(format!(
+                    "Slot '{}' exists with element_size {}",
+                    name, found
+                )));
+            }
+            return Ok(Self {
+                name,
+                shmem,
+                element_size: found,
+                seen: AtomicU64::new(0),
+            });
+        }
+        let element_size = element_size
+            .filter(|size| (1..=u32::MAX as usize).contains(size))
+            .ok_or_else(|| {
+                PyValueError::new_err(format!(
+                    "element_size between 1 and {} required when create=true",
+                    u32::MAX
+                ))
+            })?;
+        let size = element_size
+            .checked_add(size_of::<LvcHeader>() + LEN)
+            .ok_or_else(|| PyValueError::new_err("The slot is too large"))?;
+        let shmem = crate::py_queue::create_shmem(&name, size)?;
+        let slot = Self {
+            name,
+            shmem: ShmemWrapper::new(shmem),
+            element_size,
+            seen: AtomicU64::new(0),
+        };
+        let header = unsafe { &mut *(slot.shmem.as_ptr() as *mut LvcHeader) };
+        header.element_size = element_size as u64;
+        header.magic.store(MAGIC, Ordering::Release);
+        Ok(slot)
+    }
+
+    /// Name of the shared memory segment.
+    #[getter]
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Maximum size of a value in bytes.
+    #[getter]
+    fn element_size(&self) -> usize {
+        self.element_size
+    }
+
+    /// Number of values put so far, across all handles.
+    #[getter]
+    fn version(&self) -> u64 {
+        self.current_version()
+    }
+
+    /// Replaces the value; never blocks on readers.
+    ///
+    /// # Arguments
+    /// - `item` (bytes): The new value, at most `element_size` bytes.
+    ///
+    /// # Errors
+    /// Raises `ValueError` if `item` is larger than `element_size`.
+    fn put(&self, py: Python<'_>, item: Cow<[u8]>) -> PyResult<()> {
+        if item.len() > self.element_size {
+            return Err(PyValueError::new_err(format!(
+                "Item of {} bytes does not fit in elements of {} bytes",
+                item.len(),
+                self.element_size
+            )));
+        }
+        py.allow_threads(|| {
+            let header = self.header();
+            let guard = header.lock.lock(Abandoned::TakeOver)?;
+            // The sequence stays odd if the previous owner exited mid-write.
+            if header.seq.load(Ordering::Relaxed).is_multiple_of(2) {
+                header.seq.fetch_add(1, Ordering::Relaxed);
+            }
+            fence(Ordering::Release);
+            let ptr = self.value_ptr();
+            unsafe {
+                std::ptr::copy_nonoverlapping((item.len() as u32).to_le_bytes().as_ptr(), ptr, LEN);
+                std::ptr::copy_nonoverlapping(item.as_ptr(), ptr.add(LEN), item.len());
+            }
+            header.seq.fetch_add(1, Ordering::Release);
+            drop(guard);
+            header.changed.notify();
+            Ok(())
+        })
+    }
+
+    /// Returns the latest value, waiting for a value being written.
+    ///
+    /// # Errors
+    /// Raises `Empty` if no value was put yet, or if the process writing the latest
+    /// value exited before finishing it.
+    fn get<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        let header = self.header();
+        let empty = || Empty::new_err(format!("Slot '{}' holds no value yet", self.name));
+        let (version, data) = region::wait_for(
+            py,
+            &header.changed,
+            None,
+            || {
+                if let Some(read) = self.try_read(0) {
+                    return Ok(Some(read));
+                }
+                let seq = header.seq.load(Ordering::Acquire);
+                if seq == 0 {
+                    return Err(empty());
+                }
+                if seq % 2 == 1
+                    && header.lock.abandoned()
+                    && header.seq.load(Ordering::Acquire) == seq
+                {
+                    return Err(Empty::new_err(format!(
+                        "Slot '{}' holds a value its writer left unfinished",
+                        self.name
+                    )));
+                }
+                Ok(None)
+            },
+            empty,
+        )?;
+        self.saw(version);
+        Ok(PyBytes::new(py, &data))
+    }
+
+    /// Returns the latest value once it is newer than the last one this handle read.
+    ///
+    /// Values put in between are skipped: only the most recent one is returned.
+    ///
+    /// # Arguments
+    /// - `timeout` (float, optional): Maximum time to wait for a new value in
+    ///   seconds; waits indefinitely if omitted, and does not wait if 0.
+    ///
+    /// # Errors
+    /// Raises `Empty` if no new value is put before the timeout.
+    #[pyo3(signature = (timeout=None))]
+    fn get_changed<'py>(
+        &self,
+        py: Python<'py>,
+        timeout: Option<f64>,
+    ) -> PyResult<Bound<'py, PyBytes>> {
+        let seen = self.seen.load(Ordering::Relaxed);
+        let (version, data) = region::wait_for(
+            py,
+            &self.header().changed,
+            timeout,
+            || Ok(self.try_read(seen)),
+            || Empty::new_err(format!("Slot '{}' holds no new value", self.name)),
+        )?;
+        self.saw(version);
+        Ok(PyBytes::new(py, &data))
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "LvcSlot(name='{}', element_size={}, version={})",
+            self.name,
+            self.element_size,
+            self.current_version()
+        )
+    }
+}
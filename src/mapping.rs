@@ -0,0 +1,221 @@
+//! Shared memory mapped from a file descriptor rather than opened by name.
+//!
+//! Anonymous `memfd` segments never appear in `/dev/shm`: other processes reach them
+//! through a descriptor passed over a Unix socket or inherited across `fork()`, and
+//! the kernel frees them once the last descriptor and mapping are gone, including
+//! when every process using them was killed.
+//!
+//! Segments can also be regular files at a path of the caller's choosing, e.g. on a
+//! tmpfs larger than `/dev/shm`. Their creator removes them when dropped, and files
+//! left behind by killed processes can be inspected afterwards.
+
+use crate::errors::AlreadyExists;
+use pyo3::exceptions::PyOSError;
+use pyo3::prelude::*;
+use std::fs::OpenOptions;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+
+/// A shared mapping of the whole file behind a descriptor, unmapped when dropped.
+pub struct Mapping {
+    ptr: *mut u8,
+    len: usize,
+    fd: OwnedFd,
+    owner: bool,
+    /// The file the segment lives in, removed on drop by its creator.
+    path: Option<PathBuf>,
+}
+
+impl Mapping {
+    /// Creates an anonymous segment of `size` bytes labelled `name`, in huge pages from
+    /// the hugetlb pool if `huge_pages`, and maps it.
+    ///
+    /// The segment is sealed against resizing, so that processes receiving its
+    /// descriptor can trust the size they map.
+    ///
+    /// # Errors
+    /// Raises `ValueError` if `name` contains NUL bytes, `OSError` if the segment
+    /// cannot be created or mapped, or `NotImplementedError` outside Linux.
+    pub fn create_memfd(name: &str, size: usize, huge_pages: bool) -> PyResult<Self> {
+        #[cfg(target_os = "linux")]
+        {
+            let label = std::ffi::CString::new(name).map_err(|_| {
+                pyo3::exceptions::PyValueError::new_err("name must not contain NUL bytes")
+            })?;
+            let mut flags = libc::MFD_CLOEXEC | libc::MFD_ALLOW_SEALING;
+            if huge_pages {
+                flags |= libc::MFD_HUGETLB | libc::MFD_HUGE_2MB;
+            }
+            let raw = unsafe { libc::memfd_create(label.as_ptr(), flags) };
+            if raw < 0 {
+                return Err(os_error("create", name));
+            }
+            let fd = unsafe { OwnedFd::from_raw_fd(raw) };
+            let seals = libc::F_SEAL_SHRINK | libc::F_SEAL_GROW | libc::F_SEAL_SEAL;
+            if unsafe { libc::ftruncate(raw, size as libc::off_t) } != 0
+                || unsafe { libc::fcntl(raw, libc::F_ADD_SEALS, seals) } != 0
+            {
+                return Err(os_error("size", name));
+            }
+            Self::map(name, fd, size, true, None)
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            let _ = (name, size, huge_pages);
+            Err(pyo3::exceptions::PyNotImplementedError::new_err(
+                "backing='memfd' is only supported on Linux",
+            ))
+        }
+    }
+
+    /// Maps the segment behind the descriptor `fd`, keeping a duplicate of it so the
+    /// caller may close its own.
+    ///
+    /// # Errors
+    /// Raises `OSError` if `fd` is not an open descriptor of a file that can be mapped.
+    pub fn from_fd(name: &str, fd: RawFd) -> PyResult<Self> {
+        let raw = unsafe { libc::fcntl(fd, libc::F_DUPFD_CLOEXEC, 0) };
+        if raw < 0 {
+            return Err(os_error("open", name));
+        }
+        let fd = unsafe { OwnedFd::from_raw_fd(raw) };
+        let mut stat = std::mem::MaybeUninit::<libc::stat>::uninit();
+        if unsafe { libc::fstat(raw, stat.as_mut_ptr()) } != 0 {
+            return Err(os_error("open", name));
+        }
+        let len = unsafe { stat.assume_init() }.st_size as usize;
+        Self::map(name, fd, len, false, None)
+    }
+
+    /// Creates the file `path` of `size` bytes, readable and writable by its owner
+    /// only, and maps it.
+    ///
+    /// # Errors
+    /// Raises `AlreadyExists` if the file exists, or `OSError` if it cannot be created
+    /// or mapped.
+    pub fn create_file(name: &str, path: &Path, size: usize) -> PyResult<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .mode(0o600)
+            .open(path)
+            .map_err(|e| match e.kind() {
+                std::io::ErrorKind::AlreadyExists => AlreadyExists::new_err(format!(
+                    "Failed to create queue file '{}': {}",
+                    path.display(),
+                    e
+                )),
+                _ => file_error("create", path, e),
+            })?;
+        let mapping = match file.set_len(size as u64) {
+            Ok(()) => Self::map(name, OwnedFd::from(file), size, true, Some(path.into())),
+            Err(e) => Err(file_error("size", path, e)),
+        };
+        if mapping.is_err() {
+            let _ = std::fs::remove_file(path);
+        }
+        mapping
+    }
+
+    /// Opens and maps the existing file `path`.
+    ///
+    /// # Errors
+    /// Raises `OSError` if the file cannot be opened or mapped.
+    pub fn open_file(name: &str, path: &Path) -> PyResult<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(path)
+            .map_err(|e| file_error("open", path, e))?;
+        let len = file
+            .metadata()
+            .map_err(|e| file_error("open", path, e))?
+            .len() as usize;
+        Self::map(name, OwnedFd::from(file), len, false, None)
+    }
+
+    fn map(
+        name: &str,
+        fd: OwnedFd,
+        len: usize,
+        owner: bool,
+        path: Option<PathBuf>,
+    ) -> PyResult<Self> {
+        let ptr = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED,
+                fd.as_raw_fd(),
+                0,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            return Err(os_error("map", name));
+        }
+        Ok(Self {
+            ptr: ptr as *mut u8,
+            len,
+            fd,
+            owner,
+            path,
+        })
+    }
+
+    /// Returns a pointer to the start of the mapping.
+    pub fn as_ptr(&self) -> *mut u8 {
+        self.ptr
+    }
+
+    /// Returns the size of the mapping in bytes.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns the descriptor of the segment, owned by this mapping.
+    pub fn fd(&self) -> RawFd {
+        self.fd.as_raw_fd()
+    }
+
+    /// Returns whether this mapping created the segment.
+    pub fn is_owner(&self) -> bool {
+        self.owner
+    }
+
+    /// Leaves the segment to its creator, e.g. in a child that inherited the mapping.
+    pub fn disown(&mut self) {
+        self.owner = false;
+    }
+}
+
+impl Drop for Mapping {
+    fn drop(&mut self) {
+        unsafe { libc::munmap(self.ptr as *mut libc::c_void, self.len) };
+        if let (true, Some(path)) = (self.owner, &self.path) {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// Converts the last OS error of `action` on segment `name` into an `OSError`.
+fn os_error(action: &str, name: &str) -> PyErr {
+    PyOSError::new_err(format!(
+        "Failed to {} shared memory '{}': {}",
+        action,
+        name,
+        std::io::Error::last_os_error()
+    ))
+}
+
+/// Converts the failure of `action` on the queue file `path` into an `OSError`.
+fn file_error(action: &str, path: &Path, error: std::io::Error) -> PyErr {
+    PyOSError::new_err(format!(
+        "Failed to {} queue file '{}': {}",
+        action,
+        path.display(),
+        error
+    ))
+}
@@ -0,0 +1,338 @@
+use crate::crypto::{CipherKind, ENVELOPE_SIZE};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Metadata field flags stored in the queue header.
+pub const META_SEQUENCE: u32 = 1 << 0;
+pub const META_TIMESTAMP: u32 = 1 << 1;
+pub const META_PRODUCER_ID: u32 = 1 << 2;
+pub const META_HEADERS: u32 = 1 << 3;
+pub const META_DEADLINE: u32 = 1 << 4;
+pub const META_CHECKSUM: u32 = 1 << 5;
+
+/// Size of the per-message checksum.
+pub const CHECKSUM_SIZE: usize = size_of::<u32>();
+
+/// Python-facing names of the optional metadata fields.
+const META_FIELDS: [(&str, u32); 4] = [
+    ("sequence", META_SEQUENCE),
+    ("timestamp", META_TIMESTAMP),
+    ("producer_id", META_PRODUCER_ID),
+    ("deadline", META_DEADLINE),
+];
+
+/// Describes which metadata fields precede the payload in every slot.
+///
+/// Fields are encoded little-endian in a fixed order: sequence (u64),
+/// enqueue timestamp in nanoseconds since the Unix epoch (u64),
+/// producer id (u32), deadline in nanoseconds since the Unix epoch (u64,
+/// zero when unset), headers length (u32) and the headers bytes.
+/// Encrypted queues append the cipher envelope (key id, nonce, tag) after
+/// the fields, which are authenticated as associated data. Checksummed queues
+/// end the prefix with the CRC-32 (u32) of every other byte of the slot, the
+/// payload as stored included.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MetaLayout {
+    pub flags: u32,
+    pub headers_size: usize,
+}
+
+impl MetaLayout {
+    /// Builds a layout from the field names and headers size given at creation.
+    pub fn from_options(
+        fields: Option<Vec<String>>,
+        headers_size: usize,
+        cipher: Option<CipherKind>,
+        checksum: bool,
+    ) -> PyResult<Self> {
+        let mut flags = 0;
+        for field in fields.unwrap_or_default() {
+            let flag = META_FIELDS
+                .iter()
+                .find(|(name, _)| *name == field)
+                .map(|(_, flag)| *flag)
+                .ok_or_else(|| {
+                    PyValueError::new_err(format!(
+                        "Unknown metadata field '{}': expected one of 'sequence', 'timestamp', 'producer_id', 'deadline'",
+                        field
+                    ))
+                })?;
+            flags |= flag;
+        }
+        if headers_size > 0 {
+            flags |= META_HEADERS;
+        }
+        if let Some(cipher) = cipher {
+            flags |= cipher.flag();
+        }
+        if checksum {
+            flags |= META_CHECKSUM;
+        }
+        Ok(Self {
+            flags,
+            headers_size,
+        })
+    }
+
+    /// Recovers the layout from the flags and metadata size stored in the header.
+    pub fn from_header(flags: u32, meta_size: usize) -> Self {
+        let empty = Self {
+            flags,
+            headers_size: 0,
+        };
+        Self {
+            flags,
+            headers_size: meta_size - empty.size(),
+        }
+    }
+
+    #[inline]
+    fn has(&self, flag: u32) -> bool {
+        self.flags & flag != 0
+    }
+
+    /// Size of the fixed-width fields.
+    fn fixed_size(&self) -> usize {
+        let mut size = 0;
+        if self.has(META_SEQUENCE) {
+            size += 8;
+        }
+        if self.has(META_TIMESTAMP) {
+            size += 8;
+        }
+        if self.has(META_PRODUCER_ID) {
+            size += 4;
+        }
+        if self.has(META_DEADLINE) {
+            size += 8;
+        }
+        if self.has(META_HEADERS) {
+            size += 4;
+        }
+        size
+    }
+
+    /// Number of bytes taken by the metadata fields, excluding the cipher envelope.
+    pub fn fields_size(&self) -> usize {
+        self.fixed_size() + self.headers_size
+    }
+
+    /// Total number of metadata bytes preceding the payload.
+    pub fn size(&self) -> usize {
+        let envelope = match self.cipher() {
+            Some(_) => ENVELOPE_SIZE,
+            None => 0,
+        };
+        let checksum = if self.has(META_CHECKSUM) {
+            CHECKSUM_SIZE
+        } else {
+            0
+        };
+        self.fields_size() + envelope + checksum
+    }
+
+    /// Returns whether every message carries a checksum.
+    pub fn has_checksum(&self) -> bool {
+        self.has(META_CHECKSUM)
+    }
+
+    /// Returns the CRC-32 of the slot made of `prefix` and `payload`, leaving out the
+    /// checksum itself.
+    fn compute_checksum(&self, prefix: &[u8], payload: &[u8]) -> u32 {
+        let mut hasher = crc32fast::Hasher::new();
+        hasher.update(&prefix[..self.size() - CHECKSUM_SIZE]);
+        hasher.update(payload);
+        hasher.finalize()
+    }
+
+    /// Stores the checksum of a filled slot at the end of `prefix`, if enabled.
+    pub fn write_checksum(&self, prefix: &mut [u8], payload: &[u8]) {
+        if self.has(META_CHECKSUM) {
+            let checksum = self.compute_checksum(prefix, payload);
+            prefix[self.size() - CHECKSUM_SIZE..self.size()]
+                .copy_from_slice(&checksum.to_le_bytes());
+        }
+    }
+
+    /// Checks the checksum stored in `prefix` against the slot, if enabled.
+    ///
+    /// # Errors
+    /// Returns the stored and the computed checksum if they differ.
+    pub fn verify_checksum(&self, prefix: &[u8], payload: &[u8]) -> Result<(), (u32, u32)> {
+        if !self.has(META_CHECKSUM) {
+            return Ok(());
+        }
+        let end = self.size();
+        let stored = u32::from_le_bytes(prefix[end - CHECKSUM_SIZE..end].try_into().unwrap());
+        let computed = self.compute_checksum(prefix, payload);
+        if stored != computed {
+            return Err((stored, computed));
+        }
+        Ok(())
+    }
+
+    /// Returns the cipher protecting the payloads, if any.
+    pub fn cipher(&self) -> Option<CipherKind> {
+        CipherKind::from_flags(self.flags)
+    }
+
+    /// Validates headers passed to `put` against this layout.
+    pub fn validate_headers(&self, headers: Option<&[u8]>) -> PyResult<()> {
+        match headers {
+            Some(headers) if !self.has(META_HEADERS) => Err(PyValueError::new_err(format!(
+                "Headers of {} bytes given but the queue was created without headers_size",
+                headers.len()
+            ))),
+            Some(headers) if headers.len() > self.headers_size => {
+                Err(PyValueError::new_err(format!(
+                    "Headers too large: expected at most {}, got {}",
+                    self.headers_size,
+                    headers.len()
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Validates a deadline passed to `put` and converts it to nanoseconds
+    /// since the Unix epoch, zero meaning no deadline.
+    pub fn validate_deadline(&self, deadline: Option<f64>) -> PyResult<u64> {
+        match deadline {
+            None => Ok(0),
+            Some(_) if !self.has(META_DEADLINE) => Err(PyValueError::new_err(
+                "Deadline given but the queue was created without the 'deadline' metadata field",
+            )),
+            Some(deadline) if deadline.is_finite() && deadline > 0.0 => Ok((deadline * 1e9) as u64),
+            Some(deadline) => Err(PyValueError::new_err(format!(
+                "Deadline must be a positive Unix timestamp, got {}",
+                deadline
+            ))),
+        }
+    }
+
+    /// Returns whether the message described by `meta` has a deadline before `now_ns`.
+    pub fn is_expired(&self, meta: &[u8], now_ns: u64) -> bool {
+        if !self.has(META_DEADLINE) {
+            return false;
+        }
+        let mut offset = 0;
+        if self.has(META_SEQUENCE) {
+            offset += 8;
+        }
+        if self.has(META_TIMESTAMP) {
+            offset += 8;
+        }
+        if self.has(META_PRODUCER_ID) {
+            offset += 4;
+        }
+        let deadline = u64::from_le_bytes(meta[offset..offset + 8].try_into().unwrap());
+        deadline != 0 && deadline < now_ns
+    }
+
+    /// Writes the enabled fields for the message at `pos` into `meta`.
+    pub fn write(&self, meta: &mut [u8], pos: usize, headers: Option<&[u8]>, deadline_ns: u64) {
+        let mut offset = 0;
+        let mut put = |bytes: &[u8]| {
+            meta[offset..offset + bytes.len()].copy_from_slice(bytes);
+            offset += bytes.len();
+        };
+        if self.has(META_SEQUENCE) {
+            put(&(pos as u64).to_le_bytes());
+        }
+        if self.has(META_TIMESTAMP) {
+            put(&unix_time_ns().to_le_bytes());
+        }
+        if self.has(META_PRODUCER_ID) {
+            put(&std::process::id().to_le_bytes());
+        }
+        if self.has(META_DEADLINE) {
+            put(&deadline_ns.to_le_bytes());
+        }
+        if self.has(META_HEADERS) {
+            let headers = headers.unwrap_or_default();
+            put(&(headers.len() as u32).to_le_bytes());
+            put(headers);
+        }
+    }
+
+    /// Decodes the enabled fields from `meta` into a `Message` carrying `payload`.
+    pub fn read(&self, meta: &[u8], payload: Vec<u8>) -> Message {
+        let mut offset = 0;
+        let mut take = |len: usize| {
+            let bytes = &meta[offset..offset + len];
+            offset += len;
+            bytes
+        };
+        let mut message = Message {
+            payload,
+            ..Message::default()
+        };
+        if self.has(META_SEQUENCE) {
+            message.sequence = Some(u64::from_le_bytes(take(8).try_into().unwrap()));
+        }
+        if self.has(META_TIMESTAMP) {
+            let enqueued = u64::from_le_bytes(take(8).try_into().unwrap());
+            message.timestamp = Some(enqueued as f64 / 1e9);
+            message.dequeued_at = Some(unix_time_ns() as f64 / 1e9);
+        }
+        if self.has(META_PRODUCER_ID) {
+            message.producer_id = Some(u32::from_le_bytes(take(4).try_into().unwrap()));
+        }
+        if self.has(META_DEADLINE) {
+            let deadline = u64::from_le_bytes(take(8).try_into().unwrap());
+            message.deadline = (deadline != 0).then(|| deadline as f64 / 1e9);
+        }
+        if self.has(META_HEADERS) {
+            let len = u32::from_le_bytes(take(4).try_into().unwrap()) as usize;
+            let headers = take(self.headers_size);
+            message.headers = Some(headers[..len.min(headers.len())].to_vec());
+        }
+        message
+    }
+}
+
+/// Returns the current wall-clock time in nanoseconds since the Unix epoch.
+pub fn unix_time_ns() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+/// A dequeued element together with the metadata enabled at queue creation.
+///
+/// Fields that were not enabled when the queue was created are `None`.
+#[pyclass(frozen, get_all)]
+#[derive(Clone, Debug, Default)]
+pub struct Message {
+    /// The element payload.
+    pub payload: Vec<u8>,
+    /// Monotonic position of the message in the queue.
+    pub sequence: Option<u64>,
+    /// Enqueue time in seconds since the Unix epoch.
+    pub timestamp: Option<f64>,
+    /// Dequeue time in seconds since the Unix epoch.
+    pub dequeued_at: Option<f64>,
+    /// Process id of the producer.
+    pub producer_id: Option<u32>,
+    /// Deadline in seconds since the Unix epoch, if the producer set one.
+    pub deadline: Option<f64>,
+    /// Application headers attached by the producer.
+    pub headers: Option<Vec<u8>>,
+}
+
+#[pymethods]
+impl Message {
+    fn __repr__(&self) -> String {
+        format!(
+            "Message(payload=<{} bytes>, sequence={:?}, timestamp={:?}, producer_id={:?}, deadline={:?}, headers={:?})",
+            self.payload.len(),
+            self.sequence,
+            self.timestamp,
+            self.producer_id,
+            self.deadline,
+            self.headers.as_ref().map(|h| h.len())
+        )
+    }
+}
@@ -1,50 +1,540 @@
+use crate::conformance::LAYOUT_VERSION;
+use crate::copy;
+use crate::fork;
+use crate::futex::WaitSignal;
+use crate::poison;
+use std::error::Error;
+use std::fmt;
 use std::marker::PhantomData;
 use std::mem::{align_of, size_of, MaybeUninit};
 use std::ptr::NonNull;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicU32, AtomicU64, AtomicUsize, Ordering};
 
 /// Computes the required buffer size for an `MpmcQueueOnBuffer`
-/// given the `element_size` and `capacity`.
-pub fn compute_required_size(element_size: usize, capacity: usize) -> usize {
-    use std::mem::{align_of, size_of};
+/// given the slot `layout` and `capacity`.
+pub fn compute_required_size(layout: &SlotLayout, capacity: usize) -> usize {
+    compute_buffer_layout(layout, capacity).required_size
+}
+
+/// Computes the offset, relative to the queue header, at which a secondary
+/// queue (lane) sharing the segment can be placed after the queue regions.
+pub fn compute_lane_offset(layout: &SlotLayout, capacity: usize) -> usize {
+    align_up(
+        compute_required_size(layout, capacity),
+        align_of::<MpmcQueueHeader>(),
+    )
+}
 
+/// Offsets and sizes of the regions of a queue buffer.
+#[derive(Clone, Copy, Debug)]
+pub struct BufferLayout {
+    pub header_size: usize,
+    pub cells_offset: usize,
+    pub cells_size: usize,
+    pub owners_offset: usize,
+    pub owners_size: usize,
+    pub data_offset: usize,
+    pub data_size: usize,
+    pub required_size: usize,
+}
+
+/// Computes where the header, cells and data regions of a queue with the
+/// given slot `layout` and `capacity` are placed in its buffer.
+pub fn compute_buffer_layout(layout: &SlotLayout, capacity: usize) -> BufferLayout {
     let header_size = size_of::<MpmcQueueHeader>();
 
-    let cells_offset = align_up(header_size, align_of::<Cell>());
-    let cells_size = capacity * size_of::<Cell>();
+    let cells_offset = align_up(header_size, CELL_ALIGN);
+    let cells_size = capacity * layout.cell_width.size();
 
-    let data_offset = align_up(cells_offset + cells_size, align_of::<u8>());
-    let data_size = capacity * element_size;
-    data_offset + data_size
+    let data_align = if layout.padded {
+        CACHE_LINE
+    } else {
+        align_of::<u8>()
+    };
+    let owners_offset = align_up(cells_offset + cells_size, OWNER_ALIGN);
+    let owners_size = if layout.owners {
+        capacity * OWNER_WORDS * size_of::<AtomicU64>()
+    } else {
+        0
+    };
+    let data_offset = align_up(owners_offset + owners_size, data_align);
+    let data_size = capacity * layout.stride();
+    BufferLayout {
+        header_size,
+        cells_offset,
+        cells_size,
+        owners_offset,
+        owners_size,
+        data_offset,
+        data_size,
+        required_size: data_offset + data_size,
+    }
 }
 
-/// Errors that can occur when using `MpmcQueueOnBuffer`.
-#[derive(Debug)]
-pub enum MpmcQueueError {
-    InvalidSourceLength { expected: usize, actual: usize },
-    InvalidDestinationLength { expected: usize, actual: usize },
-    QueueFull,
-    QueueEmpty,
+/// Checks that `capacity` is a valid number of slots for the slot `layout`.
+pub fn validate_capacity(layout: &SlotLayout, capacity: usize) -> Result<(), LayoutError> {
+    if capacity < 2 {
+        return Err(LayoutError::BufferTooSmall {
+            required: 2,
+            provided: capacity,
+        });
+    }
+    if !capacity.is_power_of_two() {
+        return Err(LayoutError::BufferSizeNotPowerOfTwo { actual: capacity });
+    }
+    let max = layout.cell_width.max_capacity();
+    if capacity > max {
+        return Err(LayoutError::CapacityTooLarge {
+            max,
+            actual: capacity,
+        });
+    }
+    Ok(())
+}
+
+/// Width of the sequence number kept in each slot's cell.
+///
+/// Sequences are compared with wrapping arithmetic, so narrow cells work
+/// for any number of messages as long as the capacity stays well below
+/// the sequence range.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CellWidth {
+    /// Native word-sized sequences.
+    #[default]
+    Wide,
+    /// 32-bit sequences, halving the cells array.
+    Narrow,
+}
+
+impl CellWidth {
+    /// Builds a cell width from a number of sequence bits.
+    pub fn from_bits(bits: u32) -> Option<Self> {
+        match bits {
+            32 => Some(CellWidth::Narrow),
+            64 => Some(CellWidth::Wide),
+            _ => None,
+        }
+    }
+
+    /// Reads the cell width stored in a header; zero denotes a header
+    /// written before the width was recorded.
+    fn from_header(cell_size: u32) -> Self {
+        if cell_size as usize == size_of::<AtomicU32>() {
+            CellWidth::Narrow
+        } else {
+            CellWidth::Wide
+        }
+    }
+
+    /// Returns the number of bytes occupied by a single cell.
+    #[inline]
+    pub fn size(self) -> usize {
+        match self {
+            CellWidth::Wide => size_of::<AtomicUsize>(),
+            CellWidth::Narrow => size_of::<AtomicU32>(),
+        }
+    }
+
+    /// Returns the largest capacity for which wrapping sequence comparisons
+    /// stay unambiguous.
+    fn max_capacity(self) -> usize {
+        match self {
+            CellWidth::Wide => usize::MAX / 4 + 1,
+            CellWidth::Narrow => 1 << 30,
+        }
+    }
+
+    /// Truncates `value` to the sequence width.
+    #[inline]
+    fn truncate(self, value: usize) -> usize {
+        match self {
+            CellWidth::Wide => value,
+            CellWidth::Narrow => value as u32 as usize,
+        }
+    }
+
+    /// Returns the signed distance from `pos` to the stored sequence `seq`.
+    #[inline]
+    fn diff(self, seq: usize, pos: usize) -> isize {
+        match self {
+            CellWidth::Wide => seq.wrapping_sub(pos) as isize,
+            CellWidth::Narrow => (seq as u32).wrapping_sub(pos as u32) as i32 as isize,
+        }
+    }
+}
+
+/// Size of a cache line, the unit padded slots are rounded up to.
+pub const CACHE_LINE: usize = 64;
+
+/// Header `meta_flags` bit recording that slots are padded to [`CACHE_LINE`].
+pub const SLOT_PADDED: u32 = 1 << 16;
+
+/// Header `meta_flags` bit recording that the queue keeps owner words per slot.
+pub const SLOT_OWNERS: u32 = 1 << 17;
+
+/// Number of owner words per slot of a queue with `owners`.
+pub const OWNER_WORDS: usize = 2;
+
+/// Alignment of the owners array.
+const OWNER_ALIGN: usize = align_of::<AtomicU64>();
+
+/// Layout of a single queue slot: an optional metadata prefix
+/// followed by the element payload, plus the width of its cell.
+///
+/// The queue itself never interprets the metadata; `meta_flags` is stored
+/// in the header so that attaching processes can decode it.
+///
+/// Padded slots start on a cache-line boundary and are spaced a whole
+/// number of cache lines apart, so no slot straddles two lines more than
+/// its size requires.
+///
+/// With `owners`, an array of [`OWNER_WORDS`] owner words per slot follows
+/// the cells: right after reserving a slot, producers store the inode of
+/// their PID namespace in the second word, then their process id in the
+/// upper 32 bits of the first and the low 32 bits of the position in the
+/// lower ones, so that a slot abandoned by a dead producer can be told from
+/// a slow one.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SlotLayout {
+    pub element_size: usize,
+    pub meta_size: usize,
+    pub meta_flags: u32,
+    pub cell_width: CellWidth,
+    pub padded: bool,
+    pub owners: bool,
+}
+
+impl SlotLayout {
+    /// Reads the layout stored in an initialized queue header.
+    pub fn from_header(header: &MpmcQueueHeader) -> Self {
+        Self {
+            element_size: header.element_size,
+            meta_size: header.meta_size,
+            meta_flags: header.meta_flags & !(SLOT_PADDED | SLOT_OWNERS),
+            cell_width: CellWidth::from_header(header.cell_size),
+            padded: header.meta_flags & SLOT_PADDED != 0,
+            owners: header.meta_flags & SLOT_OWNERS != 0,
+        }
+    }
+
+    /// Returns the number of bytes occupied by a single slot.
+    #[inline]
+    pub fn slot_size(&self) -> usize {
+        self.meta_size + self.element_size
+    }
+
+    /// Returns the distance in bytes between the starts of adjacent slots.
+    #[inline]
+    pub fn stride(&self) -> usize {
+        if self.padded {
+            align_up(self.slot_size(), CACHE_LINE)
+        } else {
+            self.slot_size()
+        }
+    }
+}
+
+/// Errors raised when a buffer or capacity cannot hold a queue.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LayoutError {
     BufferTooSmall { required: usize, provided: usize },
     BufferMisaligned { expected: usize, actual: usize },
     BufferSizeNotPowerOfTwo { actual: usize },
+    CapacityTooLarge { max: usize, actual: usize },
+    HeaderChecksumMismatch { stored: u32, computed: u32 },
+}
+
+impl fmt::Display for LayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LayoutError::BufferTooSmall { required, provided } => write!(
+                f,
+                "Buffer too small: required {}, provided {}",
+                required, provided
+            ),
+            LayoutError::BufferMisaligned { expected, actual } => write!(
+                f,
+                "Buffer misaligned: expected {}, actual {}",
+                expected, actual
+            ),
+            LayoutError::BufferSizeNotPowerOfTwo { actual } => {
+                write!(f, "Buffer size must be a power of two, got {}", actual)
+            }
+            LayoutError::CapacityTooLarge { max, actual } => write!(
+                f,
+                "Capacity too large for the sequence width: maximum {}, got {}",
+                max, actual
+            ),
+            LayoutError::HeaderChecksumMismatch { stored, computed } => write!(
+                f,
+                "Header checksum mismatch: stored {:#010x}, computed {:#010x}",
+                stored, computed
+            ),
+        }
+    }
+}
+
+impl Error for LayoutError {}
+
+/// Transient outcomes of an operation on a full or empty queue.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CapacityError {
+    Full,
+    Empty,
+}
+
+impl fmt::Display for CapacityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CapacityError::Full => f.write_str("Queue is full"),
+            CapacityError::Empty => f.write_str("Queue is empty"),
+        }
+    }
+}
+
+impl Error for CapacityError {}
+
+/// Errors raised when an argument does not match the queue layout.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValidationError {
+    InvalidSourceLength { expected: usize, actual: usize },
+    InvalidDestinationLength { expected: usize, actual: usize },
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::InvalidSourceLength { expected, actual } => write!(
+                f,
+                "Invalid source length: expected {}, got {}",
+                expected, actual
+            ),
+            ValidationError::InvalidDestinationLength { expected, actual } => write!(
+                f,
+                "Invalid destination length: expected {}, got {}",
+                expected, actual
+            ),
+        }
+    }
+}
+
+impl Error for ValidationError {}
+
+/// Errors that can occur when using `MpmcQueueOnBuffer`, grouped by category.
+///
+/// Operations that can only fail in one way return the category directly;
+/// this type covers operations that can fail in several.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MpmcQueueError {
+    Layout(LayoutError),
+    Capacity(CapacityError),
+    Validation(ValidationError),
+    /// The slot reserved at `pos` was reclaimed as abandoned before the producer
+    /// published it, so the element was dropped.
+    SlotReclaimed {
+        pos: usize,
+    },
+}
+
+impl fmt::Display for MpmcQueueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MpmcQueueError::Layout(e) => e.fmt(f),
+            MpmcQueueError::Capacity(e) => e.fmt(f),
+            MpmcQueueError::Validation(e) => e.fmt(f),
+            MpmcQueueError::SlotReclaimed { pos } => write!(
+                f,
+                "Slot at position {} was reclaimed as abandoned before it was published",
+                pos
+            ),
+        }
+    }
+}
+
+impl Error for MpmcQueueError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            MpmcQueueError::Layout(e) => Some(e),
+            MpmcQueueError::Capacity(e) => Some(e),
+            MpmcQueueError::Validation(e) => Some(e),
+            MpmcQueueError::SlotReclaimed { .. } => None,
+        }
+    }
+}
+
+impl From<LayoutError> for MpmcQueueError {
+    fn from(error: LayoutError) -> Self {
+        MpmcQueueError::Layout(error)
+    }
+}
+
+impl From<CapacityError> for MpmcQueueError {
+    fn from(error: CapacityError) -> Self {
+        MpmcQueueError::Capacity(error)
+    }
+}
+
+impl From<ValidationError> for MpmcQueueError {
+    fn from(error: ValidationError) -> Self {
+        MpmcQueueError::Validation(error)
+    }
+}
+
+/// A cell whose sequence number lies outside the window implied by the
+/// header positions.
+#[derive(Clone, Copy, Debug)]
+pub struct CellInconsistency {
+    pub index: usize,
+    pub sequence: usize,
+    pub expected: usize,
+}
+
+/// Result of a cell-consistency audit.
+#[derive(Clone, Debug, Default)]
+pub struct AuditReport {
+    pub head: usize,
+    pub tail: usize,
+    /// Whether `dequeue_pos <= enqueue_pos <= dequeue_pos + capacity` holds.
+    pub positions_valid: bool,
+    pub inconsistencies: Vec<CellInconsistency>,
+    pub fixed: usize,
+}
+
+impl AuditReport {
+    /// Returns whether the audit found no problem.
+    pub fn is_consistent(&self) -> bool {
+        self.positions_valid && self.inconsistencies.is_empty()
+    }
+}
+
+/// Marks buffers holding a queue: `ZQMQ` in little-endian byte order.
+pub const QUEUE_MAGIC: u32 = u32::from_le_bytes(*b"ZQMQ");
+
+impl MpmcQueueHeader {
+    /// Returns the CRC-32 of the header bytes in front of `checksum`, which its
+    /// creator writes once and nobody changes afterwards.
+    pub fn compute_checksum(&self) -> u32 {
+        let bytes = unsafe {
+            std::slice::from_raw_parts(
+                self as *const Self as *const u8,
+                std::mem::offset_of!(Self, checksum),
+            )
+        };
+        crc32fast::hash(bytes)
+    }
+
+    /// Checks the stored checksum against the immutable fields.
+    ///
+    /// # Errors
+    /// Returns [`LayoutError::HeaderChecksumMismatch`] if they disagree.
+    pub fn verify_checksum(&self) -> Result<(), LayoutError> {
+        let computed = self.compute_checksum();
+        if self.checksum != computed {
+            return Err(LayoutError::HeaderChecksumMismatch {
+                stored: self.checksum,
+                computed,
+            });
+        }
+        Ok(())
+    }
 }
 
 /// Header structure stored at the beginning of the queue buffer.
 /// Contains metadata required for queue operation.
 #[repr(C)]
 pub struct MpmcQueueHeader {
+    /// [`QUEUE_MAGIC`], so that attaching to a segment holding anything else fails
+    /// instead of reading garbage as a layout.
+    pub magic: AtomicU32,
+    /// The [`LAYOUT_VERSION`] of the build that created the queue.
+    pub layout_version: u32,
     pub element_size: usize,
     pub buffer_mask: usize,
+    pub meta_size: usize,
+    pub meta_flags: u32,
+    pub cell_size: u32,
+    /// CRC-32 of the immutable fields above it, so that a trampled header is
+    /// rejected on attach instead of being trusted as a layout.
+    pub checksum: u32,
     pub enqueue_pos: AtomicUsize,
     pub dequeue_pos: AtomicUsize,
+    /// Offset of a secondary queue sharing the segment, or zero if there is none.
+    pub lane_offset: AtomicUsize,
+    /// Handle configuration shared by every attached process; the queue itself
+    /// never interprets it.
+    pub config: [AtomicU64; CONFIG_WORDS],
+    /// Woken when a slot is released to producers.
+    pub not_full: WaitSignal,
+    /// Woken when a slot is published to consumers.
+    pub not_empty: WaitSignal,
+    /// Non-zero once the creator finished initializing the queue, so that
+    /// handles attaching concurrently never use a half-initialized one.
+    pub ready: AtomicU32,
+    /// Non-zero once the queue was closed for every handle: puts are refused,
+    /// and gets return the remaining messages before they are refused too.
+    pub closed: AtomicU32,
+    /// Messages enqueued but not yet marked done by a consumer; only the
+    /// Python bindings maintain it, for `task_done()` and `join()`.
+    pub unfinished: AtomicU64,
+    /// Woken when `unfinished` drops to zero.
+    pub all_done: WaitSignal,
+    /// Processes attached to the queue, one handle per non-zero word; only the
+    /// Python bindings maintain it, see `registry`.
+    pub processes: [AtomicU64; PROCESS_SLOTS],
 }
 
-/// Metadata structure for each queue slot.
-/// Holds a sequence number used for synchronization.
-#[repr(C)]
-struct Cell {
-    sequence: AtomicUsize,
+/// Number of words reserved for shared handle configuration in the header.
+pub const CONFIG_WORDS: usize = 8;
+
+/// Number of handles the header can register as attached at once.
+pub const PROCESS_SLOTS: usize = 16;
+
+/// Alignment of the cells array, shared by both cell widths.
+const CELL_ALIGN: usize = align_of::<AtomicUsize>();
+
+/// Metadata for each queue slot: a sequence number used for
+/// synchronization, stored with the queue's cell width.
+///
+/// Values are passed around as `usize`; narrow cells store and return
+/// them truncated to 32 bits.
+enum Cell<'a> {
+    Wide(&'a AtomicUsize),
+    Narrow(&'a AtomicU32),
+}
+
+impl Cell<'_> {
+    #[inline]
+    fn load(&self, order: Ordering) -> usize {
+        match self {
+            Cell::Wide(seq) => seq.load(order),
+            Cell::Narrow(seq) => seq.load(order) as usize,
+        }
+    }
+
+    #[inline]
+    fn store(&self, value: usize, order: Ordering) {
+        match self {
+            Cell::Wide(seq) => seq.store(value, order),
+            Cell::Narrow(seq) => seq.store(value as u32, order),
+        }
+    }
+
+    #[inline]
+    fn compare_exchange(
+        &self,
+        current: usize,
+        new: usize,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<usize, usize> {
+        match self {
+            Cell::Wide(seq) => seq.compare_exchange(current, new, success, failure),
+            Cell::Narrow(seq) => seq
+                .compare_exchange(current as u32, new as u32, success, failure)
+                .map(|v| v as usize)
+                .map_err(|v| v as usize),
+        }
+    }
 }
 
 /// Aligns an offset upwards to the nearest multiple of `align`.
@@ -70,37 +560,20 @@ impl<'a> MpmcQueueOnBuffer<'a> {
     /// Returns offsets and sizes for different queue components.
     fn validate_and_compute_layout(
         buffer: &[MaybeUninit<u8>],
-        element_size: usize,
+        layout: &SlotLayout,
         buffer_size: usize,
-    ) -> Result<(usize, usize, usize, usize), MpmcQueueError> {
-        if buffer_size < 2 {
-            return Err(MpmcQueueError::BufferTooSmall {
-                required: 2,
-                provided: buffer_size,
-            });
-        }
-        if !buffer_size.is_power_of_two() {
-            return Err(MpmcQueueError::BufferSizeNotPowerOfTwo {
-                actual: buffer_size,
-            });
-        }
+    ) -> Result<BufferLayout, LayoutError> {
+        validate_capacity(layout, buffer_size)?;
 
-        let header_size = size_of::<MpmcQueueHeader>();
-        let cells_offset = align_up(header_size, align_of::<Cell>());
-        let cells_size = buffer_size * size_of::<Cell>();
-
-        let data_offset = align_up(cells_offset + cells_size, align_of::<u8>());
-        let data_size = buffer_size * element_size;
-
-        let required_size = data_offset + data_size;
-        if buffer.len() < required_size {
-            return Err(MpmcQueueError::BufferTooSmall {
-                required: required_size,
+        let regions = compute_buffer_layout(layout, buffer_size);
+        if buffer.len() < regions.required_size {
+            return Err(LayoutError::BufferTooSmall {
+                required: regions.required_size,
                 provided: buffer.len(),
             });
         }
 
-        Ok((header_size, cells_offset, data_offset, required_size))
+        Ok(regions)
     }
 
     /// Initializes the queue in a pre-allocated buffer.
@@ -110,26 +583,33 @@ impl<'a> MpmcQueueOnBuffer<'a> {
     /// and has sufficient size to hold the queue.
     pub unsafe fn init_on_buffer(
         buffer: &'a mut [MaybeUninit<u8>],
-        element_size: usize,
+        layout: &SlotLayout,
         buffer_size: usize,
         new: bool,
-    ) -> Result<Self, MpmcQueueError> {
-        let (_header_size, cells_offset, _data_offset, _required_size) =
-            Self::validate_and_compute_layout(buffer, element_size, buffer_size)?;
+    ) -> Result<Self, LayoutError> {
+        let regions = Self::validate_and_compute_layout(buffer, layout, buffer_size)?;
 
         let buffer_ptr = buffer.as_mut_ptr() as *mut u8;
         let header_align = align_of::<MpmcQueueHeader>();
 
-        if (buffer_ptr as usize) % header_align != 0 {
-            return Err(MpmcQueueError::BufferMisaligned {
+        if !(buffer_ptr as usize).is_multiple_of(header_align) {
+            return Err(LayoutError::BufferMisaligned {
                 expected: header_align,
                 actual: buffer_ptr as usize % header_align,
             });
         }
 
         if new {
-            Self::init_header(buffer_ptr, element_size, buffer_size);
-            Self::init_cells(buffer_ptr.add(cells_offset) as *mut Cell, buffer_size);
+            Self::init_header(buffer_ptr, layout, buffer_size);
+            Self::init_cells(
+                buffer_ptr.add(regions.cells_offset),
+                layout.cell_width,
+                buffer_size,
+            );
+            let owners = buffer_ptr.add(regions.owners_offset) as *mut AtomicU64;
+            for i in 0..regions.owners_size / size_of::<AtomicU64>() {
+                std::ptr::write(owners.add(i), AtomicU64::new(0));
+            }
         }
 
         Ok(Self {
@@ -138,30 +618,80 @@ impl<'a> MpmcQueueOnBuffer<'a> {
         })
     }
 
+    /// Attaches to a queue already initialized in `buffer`, reading its
+    /// layout from the header.
+    ///
+    /// # Safety
+    /// The caller must ensure that the buffer holds an initialized queue.
+    pub unsafe fn attach_on_buffer(buffer: &'a mut [MaybeUninit<u8>]) -> Result<Self, LayoutError> {
+        let header_size = size_of::<MpmcQueueHeader>();
+        if buffer.len() < header_size {
+            return Err(LayoutError::BufferTooSmall {
+                required: header_size,
+                provided: buffer.len(),
+            });
+        }
+        let header_align = align_of::<MpmcQueueHeader>();
+        let buffer_ptr = buffer.as_ptr() as usize;
+        if !buffer_ptr.is_multiple_of(header_align) {
+            return Err(LayoutError::BufferMisaligned {
+                expected: header_align,
+                actual: buffer_ptr % header_align,
+            });
+        }
+        let header = &*(buffer_ptr as *const MpmcQueueHeader);
+        header.verify_checksum()?;
+        let layout = SlotLayout::from_header(header);
+        let capacity = header.buffer_mask.wrapping_add(1);
+        Self::init_on_buffer(buffer, &layout, capacity, false)
+    }
+
     /// Initializes the queue header at the given pointer.
     #[inline]
-    unsafe fn init_header(header_ptr: *mut u8, element_size: usize, buffer_size: usize) {
+    unsafe fn init_header(header_ptr: *mut u8, layout: &SlotLayout, buffer_size: usize) {
         std::ptr::write(
             header_ptr as *mut MpmcQueueHeader,
             MpmcQueueHeader {
-                element_size,
+                magic: AtomicU32::new(QUEUE_MAGIC),
+                layout_version: LAYOUT_VERSION as u32,
+                element_size: layout.element_size,
                 buffer_mask: buffer_size - 1,
+                meta_size: layout.meta_size,
+                meta_flags: layout.meta_flags
+                    | if layout.padded { SLOT_PADDED } else { 0 }
+                    | if layout.owners { SLOT_OWNERS } else { 0 },
+                cell_size: layout.cell_width.size() as u32,
+                checksum: 0,
                 enqueue_pos: AtomicUsize::new(0),
                 dequeue_pos: AtomicUsize::new(0),
+                lane_offset: AtomicUsize::new(0),
+                config: Default::default(),
+                not_full: WaitSignal::default(),
+                not_empty: WaitSignal::default(),
+                ready: AtomicU32::new(0),
+                closed: AtomicU32::new(0),
+                unfinished: AtomicU64::new(0),
+                all_done: WaitSignal::default(),
+                processes: Default::default(),
             },
         );
+        let header = &mut *(header_ptr as *mut MpmcQueueHeader);
+        header.checksum = header.compute_checksum();
     }
 
     /// Initializes the sequence numbers for each cell.
     #[inline]
-    unsafe fn init_cells(cells_ptr: *mut Cell, buffer_size: usize) {
+    unsafe fn init_cells(cells_ptr: *mut u8, width: CellWidth, buffer_size: usize) {
         for i in 0..buffer_size {
-            std::ptr::write(
-                cells_ptr.add(i),
-                Cell {
-                    sequence: AtomicUsize::new(i),
-                },
-            );
+            match width {
+                CellWidth::Wide => {
+                    std::ptr::write((cells_ptr as *mut AtomicUsize).add(i), AtomicUsize::new(i))
+                }
+                CellWidth::Narrow => std::ptr::write(
+                    (cells_ptr as *mut AtomicU32).add(i),
+                    AtomicU32::new(i as u32),
+                ),
+            }
         }
     }
 
@@ -171,20 +701,33 @@ impl<'a> MpmcQueueOnBuffer<'a> {
     }
 
     #[inline]
-    fn cells_ptr(&self) -> *mut Cell {
-        let header_size = size_of::<MpmcQueueHeader>();
-        let cells_offset = align_up(header_size, align_of::<Cell>());
-        unsafe { self.base.as_ptr().add(cells_offset) as *mut Cell }
+    fn cell_width(&self) -> CellWidth {
+        CellWidth::from_header(self.header().cell_size)
     }
 
     #[inline]
-    fn data_ptr(&self) -> *mut u8 {
+    fn cells_ptr(&self) -> *mut u8 {
         let header_size = size_of::<MpmcQueueHeader>();
-        let cells_offset = align_up(header_size, align_of::<Cell>());
-        let buffer_size = self.header().buffer_mask + 1;
-        let cells_size = buffer_size * size_of::<Cell>();
-        let data_offset = align_up(cells_offset + cells_size, align_of::<u8>());
-        unsafe { self.base.as_ptr().add(data_offset) }
+        let cells_offset = align_up(header_size, CELL_ALIGN);
+        unsafe { self.base.as_ptr().add(cells_offset) }
+    }
+
+    /// Returns the slot layout recorded in the header.
+    #[inline]
+    pub fn layout(&self) -> SlotLayout {
+        SlotLayout::from_header(self.header())
+    }
+
+    #[inline]
+    fn slot_ptr(&self, index: usize) -> *mut u8 {
+        let layout = self.layout();
+        let capacity = self.header().buffer_mask + 1;
+        let data_offset = compute_buffer_layout(&layout, capacity).data_offset;
+        unsafe {
+            self.base
+                .as_ptr()
+                .add(data_offset + index * layout.stride())
+        }
     }
 
     #[inline]
@@ -192,11 +735,12 @@ impl<'a> MpmcQueueOnBuffer<'a> {
         pos & self.header().buffer_mask
     }
 
+    /// Checks that `src` matches the element size of the queue.
     #[inline]
-    fn validate_enqueue_src(&self, src: &[u8]) -> Result<(), MpmcQueueError> {
+    pub fn validate_enqueue_src(&self, src: &[u8]) -> Result<(), ValidationError> {
         let header = self.header();
         if src.len() != header.element_size {
-            Err(MpmcQueueError::InvalidSourceLength {
+            Err(ValidationError::InvalidSourceLength {
                 expected: header.element_size,
                 actual: src.len(),
             })
@@ -206,10 +750,10 @@ impl<'a> MpmcQueueOnBuffer<'a> {
     }
 
     #[inline]
-    fn validate_dequeue_dst(&self, dst: &[u8]) -> Result<(), MpmcQueueError> {
+    fn validate_dequeue_dst(&self, dst: &[u8]) -> Result<(), ValidationError> {
         let header = self.header();
         if dst.len() != header.element_size {
-            Err(MpmcQueueError::InvalidDestinationLength {
+            Err(ValidationError::InvalidDestinationLength {
                 expected: header.element_size,
                 actual: dst.len(),
             })
@@ -227,8 +771,8 @@ impl<'a> MpmcQueueOnBuffer<'a> {
         loop {
             let index = pos & buffer_mask;
             let cell = self.cell(index);
-            let seq = cell.sequence.load(Ordering::Acquire);
-            let dif = seq as isize - pos as isize;
+            let seq = cell.load(Ordering::Acquire);
+            let dif = self.cell_width().diff(seq, pos);
             match dif.cmp(&0) {
                 std::cmp::Ordering::Equal => {
                     match header.enqueue_pos.compare_exchange_weak(
@@ -237,7 +781,10 @@ impl<'a> MpmcQueueOnBuffer<'a> {
                         Ordering::Relaxed,
                         Ordering::Relaxed,
                     ) {
-                        Ok(_) => return Some(pos),
+                        Ok(_) => {
+                            self.record_owner(pos);
+                            return Some(pos);
+                        }
                         Err(new_pos) => pos = new_pos,
                     }
                 }
@@ -249,22 +796,71 @@ impl<'a> MpmcQueueOnBuffer<'a> {
         }
     }
 
+    /// Attempts to reserve between `min` and `max` consecutive slots for enqueuing
+    /// with a single update of the enqueue position.
+    /// Returns `Some((first_position, count))` if at least `min` slots were reserved,
+    /// `None` if fewer are free.
+    fn try_reserve_enqueue_slots(&self, min: usize, max: usize) -> Option<(usize, usize)> {
+        let header = self.header();
+        let buffer_mask = header.buffer_mask;
+        let mut pos = header.enqueue_pos.load(Ordering::Relaxed);
+        loop {
+            let seq = self.cell(pos & buffer_mask).load(Ordering::Acquire);
+            match self.cell_width().diff(seq, pos).cmp(&0) {
+                std::cmp::Ordering::Equal => {
+                    // A free cell stays free until its position is claimed, so
+                    // the run checked here is still free if the exchange succeeds.
+                    let mut count = 1;
+                    while count < max {
+                        let next = pos.wrapping_add(count);
+                        let seq = self.cell(next & buffer_mask).load(Ordering::Acquire);
+                        if self.cell_width().diff(seq, next) != 0 {
+                            break;
+                        }
+                        count += 1;
+                    }
+                    if count < min {
+                        return None;
+                    }
+                    match header.enqueue_pos.compare_exchange_weak(
+                        pos,
+                        pos + count,
+                        Ordering::Relaxed,
+                        Ordering::Relaxed,
+                    ) {
+                        Ok(_) => {
+                            for i in 0..count {
+                                self.record_owner(pos.wrapping_add(i));
+                            }
+                            return Some((pos, count));
+                        }
+                        Err(new_pos) => pos = new_pos,
+                    }
+                }
+                std::cmp::Ordering::Less => return None,
+                std::cmp::Ordering::Greater => {
+                    pos = header.enqueue_pos.load(Ordering::Relaxed);
+                }
+            }
+        }
+    }
+
+    /// Hands the whole slot (metadata prefix and payload) at `pos` to `fill`
+    /// and publishes it to consumers.
+    ///
+    /// Publishing fails if the slot was reclaimed in the meantime.
     #[inline]
-    fn write_slot(&self, pos: usize, src: &[u8]) {
+    fn write_slot<F: FnOnce(&mut [u8])>(&self, pos: usize, fill: F) -> Result<(), MpmcQueueError> {
         let header = self.header();
         let index = self.cell_index(pos);
-        let data_offset = index * header.element_size;
-        let dst = unsafe { self.data_ptr().add(data_offset) };
-        unsafe {
-            std::ptr::copy_nonoverlapping(src.as_ptr(), dst, header.element_size);
-            std::sync::atomic::compiler_fence(Ordering::Release);
-            self.cells_ptr()
-                .add(index)
-                .as_mut()
-                .unwrap()
-                .sequence
-                .store(pos + 1, Ordering::Release);
-        }
+        let slot_size = header.meta_size + header.element_size;
+        let slot = unsafe { std::slice::from_raw_parts_mut(self.slot_ptr(index), slot_size) };
+        fill(slot);
+        // No fence here: a compiler fence only constrains the compiler, and weakly
+        // ordered CPUs (aarch64, POWER) may still make the sequence visible before
+        // the payload. The release exchange in `commit_enqueue` orders every write
+        // of `fill` before the sequence, and consumers load it with acquire.
+        self.commit_enqueue(pos)
     }
 
     fn try_reserve_dequeue_slot(&self) -> Option<usize> {
@@ -274,8 +870,8 @@ impl<'a> MpmcQueueOnBuffer<'a> {
         loop {
             let index = pos & buffer_mask;
             let cell = self.cell(index);
-            let seq = cell.sequence.load(Ordering::Acquire);
-            let dif = seq as isize - (pos as isize + 1);
+            let seq = cell.load(Ordering::Acquire);
+            let dif = self.cell_width().diff(seq, pos.wrapping_add(1));
             match dif.cmp(&0) {
                 std::cmp::Ordering::Equal => {
                     match header.dequeue_pos.compare_exchange_weak(
@@ -296,52 +892,463 @@ impl<'a> MpmcQueueOnBuffer<'a> {
         }
     }
 
-    #[inline]
-    fn read_slot(&self, pos: usize, dst: &mut [u8]) {
+    /// Attempts to reserve up to `max` consecutive published slots for dequeuing
+    /// with a single update of the dequeue position.
+    /// Returns `Some((first_position, count))` if at least one slot was reserved,
+    /// `None` if the queue is empty.
+    fn try_reserve_dequeue_slots(&self, max: usize) -> Option<(usize, usize)> {
         let header = self.header();
-        let index = self.cell_index(pos);
-        let data_offset = index * header.element_size;
-        let src = unsafe { self.data_ptr().add(data_offset) };
-        unsafe {
-            std::ptr::copy_nonoverlapping(src, dst.as_mut_ptr(), header.element_size);
+        let buffer_mask = header.buffer_mask;
+        let mut pos = header.dequeue_pos.load(Ordering::Relaxed);
+        loop {
+            let seq = self.cell(pos & buffer_mask).load(Ordering::Acquire);
+            match self.cell_width().diff(seq, pos.wrapping_add(1)).cmp(&0) {
+                std::cmp::Ordering::Equal => {
+                    // A published cell stays published until its position is
+                    // claimed, so the run checked here is intact if the exchange
+                    // succeeds.
+                    let mut count = 1;
+                    while count < max {
+                        let next = pos.wrapping_add(count);
+                        let seq = self.cell(next & buffer_mask).load(Ordering::Acquire);
+                        if self.cell_width().diff(seq, next.wrapping_add(1)) != 0 {
+                            break;
+                        }
+                        count += 1;
+                    }
+                    match header.dequeue_pos.compare_exchange_weak(
+                        pos,
+                        pos + count,
+                        Ordering::Relaxed,
+                        Ordering::Relaxed,
+                    ) {
+                        Ok(_) => return Some((pos, count)),
+                        Err(new_pos) => pos = new_pos,
+                    }
+                }
+                std::cmp::Ordering::Less => return None,
+                std::cmp::Ordering::Greater => {
+                    pos = header.dequeue_pos.load(Ordering::Relaxed);
+                }
+            }
         }
-        unsafe {
-            self.cells_ptr()
-                .add(index)
-                .as_mut()
-                .unwrap()
-                .sequence
-                .store(pos + header.buffer_mask + 1, Ordering::Release);
+    }
+
+    /// Attempts to reserve the run of consecutive published slots at the dequeue
+    /// position whose length `run_len` reads from its first slot, with a single update
+    /// of the dequeue position. Lengths of zero or beyond the capacity reserve the
+    /// first slot alone.
+    /// Returns `Some((first_position, count))` if the whole run was reserved, `None`
+    /// if the queue is empty or the run is not fully published yet.
+    fn try_reserve_dequeue_run(&self, run_len: impl Fn(&[u8]) -> usize) -> Option<(usize, usize)> {
+        let header = self.header();
+        let buffer_mask = header.buffer_mask;
+        let slot_size = header.meta_size + header.element_size;
+        let mut pos = header.dequeue_pos.load(Ordering::Relaxed);
+        loop {
+            let seq = self.cell(pos & buffer_mask).load(Ordering::Acquire);
+            match self.cell_width().diff(seq, pos.wrapping_add(1)).cmp(&0) {
+                std::cmp::Ordering::Equal => {
+                    // The slot is read before it is claimed; it cannot be reused
+                    // unless the position moves on, which fails the exchange.
+                    let slot = unsafe {
+                        std::slice::from_raw_parts(self.slot_ptr(self.cell_index(pos)), slot_size)
+                    };
+                    let count = match run_len(slot) {
+                        count @ 1.. if count <= buffer_mask + 1 => count,
+                        _ => 1,
+                    };
+                    for i in 1..count {
+                        let next = pos.wrapping_add(i);
+                        let seq = self.cell(next & buffer_mask).load(Ordering::Acquire);
+                        if self.cell_width().diff(seq, next.wrapping_add(1)) != 0 {
+                            return None;
+                        }
+                    }
+                    match header.dequeue_pos.compare_exchange_weak(
+                        pos,
+                        pos + count,
+                        Ordering::Relaxed,
+                        Ordering::Relaxed,
+                    ) {
+                        Ok(_) => return Some((pos, count)),
+                        Err(new_pos) => pos = new_pos,
+                    }
+                }
+                std::cmp::Ordering::Less => return None,
+                std::cmp::Ordering::Greater => {
+                    pos = header.dequeue_pos.load(Ordering::Relaxed);
+                }
+            }
         }
     }
 
+    /// Hands the whole slot (metadata prefix and payload) at `pos` to `consume`
+    /// and releases it back to producers.
+    #[inline]
+    fn read_slot<R, F: FnOnce(&[u8]) -> R>(&self, pos: usize, consume: F) -> R {
+        let header = self.header();
+        let slot_size = header.meta_size + header.element_size;
+        let slot =
+            unsafe { std::slice::from_raw_parts(self.slot_ptr(self.cell_index(pos)), slot_size) };
+        let result = consume(slot);
+        self.commit_dequeue(pos);
+        result
+    }
+
+    /// Reserves the next published slot for reading in place, without releasing it
+    /// to producers.
+    ///
+    /// Returns the dequeue position and a pointer to the whole slot (metadata
+    /// prefix and payload), or `CapacityError::Empty` if the queue is empty.
+    /// The slot stays valid until the position is passed to [`commit_dequeue`];
+    /// producers cannot reuse it before that, so callers must always commit.
+    ///
+    /// [`commit_dequeue`]: MpmcQueueOnBuffer::commit_dequeue
+    pub fn begin_dequeue(&self) -> Result<(usize, NonNull<u8>), CapacityError> {
+        let pos = self
+            .try_reserve_dequeue_slot()
+            .ok_or(CapacityError::Empty)?;
+        let slot = unsafe { NonNull::new_unchecked(self.slot_ptr(self.cell_index(pos))) };
+        Ok((pos, slot))
+    }
+
+    /// Releases a slot reserved by [`begin_dequeue`] back to producers.
+    ///
+    /// [`begin_dequeue`]: MpmcQueueOnBuffer::begin_dequeue
+    #[inline]
+    pub fn commit_dequeue(&self, pos: usize) {
+        let capacity = self.header().buffer_mask + 1;
+        self.cell(self.cell_index(pos))
+            .store(pos.wrapping_add(capacity), Ordering::Release);
+        self.header().not_full.notify();
+    }
+
+    /// Reserves a free slot for writing in place, without publishing it to consumers.
+    ///
+    /// Returns the enqueue position and a pointer to the whole slot (metadata
+    /// prefix and payload), or `CapacityError::Full` if the queue is full.
+    /// Consumers cannot get past the position until it is passed to
+    /// [`commit_enqueue`], so callers must always commit.
+    ///
+    /// [`commit_enqueue`]: MpmcQueueOnBuffer::commit_enqueue
+    pub fn begin_enqueue(&self) -> Result<(usize, NonNull<u8>), CapacityError> {
+        let pos = self.try_reserve_enqueue_slot().ok_or(CapacityError::Full)?;
+        let slot = unsafe { NonNull::new_unchecked(self.slot_ptr(self.cell_index(pos))) };
+        Ok((pos, slot))
+    }
+
+    /// Returns a pointer to the whole slot of position `pos`, e.g. one reserved by
+    /// [`begin_enqueue`].
+    ///
+    /// [`begin_enqueue`]: MpmcQueueOnBuffer::begin_enqueue
+    #[inline]
+    pub fn slot_at(&self, pos: usize) -> NonNull<u8> {
+        unsafe { NonNull::new_unchecked(self.slot_ptr(self.cell_index(pos))) }
+    }
+
+    /// Publishes a slot reserved by [`begin_enqueue`] to consumers.
+    ///
+    /// Fails with `SlotReclaimed` if the slot was reclaimed in the meantime.
+    /// The exchange has release ordering, so every write to the slot made before the
+    /// call is visible to the consumer that dequeues it, on any architecture.
+    ///
+    /// [`begin_enqueue`]: MpmcQueueOnBuffer::begin_enqueue
+    #[inline]
+    pub fn commit_enqueue(&self, pos: usize) -> Result<(), MpmcQueueError> {
+        self.cell(self.cell_index(pos))
+            .compare_exchange(
+                pos,
+                pos.wrapping_add(1),
+                Ordering::Release,
+                Ordering::Relaxed,
+            )
+            .map_err(|_| MpmcQueueError::SlotReclaimed { pos })?;
+        self.header().not_empty.notify();
+        Ok(())
+    }
+
     /// Attempts to enqueue an element into the queue.
-    /// Returns `Ok(())` if successful, or `QueueFull` if the queue is full.
+    /// Returns `Ok(())` if successful, or `CapacityError::Full` if the queue is full.
     pub fn enqueue(&self, src: &[u8]) -> Result<(), MpmcQueueError> {
         self.validate_enqueue_src(src)?;
+        let meta_size = self.header().meta_size;
+        self.enqueue_with(|_pos, slot| copy::write_payload(&mut slot[meta_size..], src))
+    }
+
+    /// Attempts to reserve a slot and fill it in place with `fill`, which
+    /// receives the enqueue position and the whole slot.
+    /// Returns `Ok(())` if successful, `CapacityError::Full` if the queue is full,
+    /// or `SlotReclaimed` if the slot was reclaimed before publishing.
+    pub fn enqueue_with<F: FnOnce(usize, &mut [u8])>(&self, fill: F) -> Result<(), MpmcQueueError> {
         if let Some(pos) = self.try_reserve_enqueue_slot() {
-            self.write_slot(pos, src);
-            Ok(())
+            self.write_slot(pos, |slot| fill(pos, slot))
         } else {
-            Err(MpmcQueueError::QueueFull)
+            Err(CapacityError::Full.into())
+        }
+    }
+
+    /// Attempts to reserve up to `max` slots at once, at least one, and fill each in place with
+    /// `fill`, which receives the slot's index within the batch, its enqueue
+    /// position and the whole slot.
+    /// Returns the number of slots published, `CapacityError::Full` if the queue
+    /// is full, or `SlotReclaimed` if a slot was reclaimed before publishing;
+    /// every reserved slot is published either way.
+    pub fn enqueue_batch_with<F: FnMut(usize, usize, &mut [u8])>(
+        &self,
+        max: usize,
+        mut fill: F,
+    ) -> Result<usize, MpmcQueueError> {
+        let (first, count) = self
+            .try_reserve_enqueue_slots(1, max)
+            .ok_or(CapacityError::Full)?;
+        let mut result = Ok(count);
+        for i in 0..count {
+            let pos = first.wrapping_add(i);
+            let published = self.write_slot(pos, |slot| fill(i, pos, slot));
+            if result.is_ok() {
+                result = published.map(|_| count);
+            }
+        }
+        result
+    }
+
+    /// Attempts to reserve exactly `count` consecutive slots and fill each in place
+    /// with `fill`, as in [`enqueue_batch_with`], so that consumers find them as one
+    /// run.
+    /// Returns `CapacityError::Full` if fewer slots are free, or `SlotReclaimed` if a
+    /// slot was reclaimed before publishing.
+    ///
+    /// [`enqueue_batch_with`]: MpmcQueueOnBuffer::enqueue_batch_with
+    pub fn enqueue_run_with<F: FnMut(usize, usize, &mut [u8])>(
+        &self,
+        count: usize,
+        mut fill: F,
+    ) -> Result<(), MpmcQueueError> {
+        let (first, count) = self
+            .try_reserve_enqueue_slots(count, count)
+            .ok_or(CapacityError::Full)?;
+        let mut result = Ok(());
+        for i in 0..count {
+            let pos = first.wrapping_add(i);
+            let published = self.write_slot(pos, |slot| fill(i, pos, slot));
+            if result.is_ok() {
+                result = published;
+            }
+        }
+        result
+    }
+
+    /// Returns the dequeue position if its slot has been reserved by a producer
+    /// but not yet published.
+    pub fn stalled_head(&self) -> Option<usize> {
+        let header = self.header();
+        let pos = header.dequeue_pos.load(Ordering::Acquire);
+        let tail = header.enqueue_pos.load(Ordering::Acquire);
+        if tail.wrapping_sub(pos) as isize <= 0 {
+            return None;
+        }
+        let seq = self.cell(self.cell_index(pos)).load(Ordering::Acquire);
+        (self.cell_width().diff(seq, pos) == 0).then_some(pos)
+    }
+
+    /// Records this process as the owner of the slot just reserved at `pos`, if the
+    /// queue keeps owners.
+    #[inline]
+    fn record_owner(&self, pos: usize) {
+        if let Some([word, namespace]) = self.owner(pos) {
+            namespace.store(poison::pid_namespace() as u64, Ordering::Relaxed);
+            word.store(
+                (fork::pid() as u64) << 32 | pos as u32 as u64,
+                Ordering::Release,
+            );
         }
     }
 
+    /// Returns the process id and PID namespace recorded as the owner of the slot
+    /// reserved at `pos`.
+    ///
+    /// Returns `None` if the queue keeps no owners, or if the owner words belong to
+    /// an earlier lap of the slot, e.g. because its producer died before recording
+    /// itself.
+    pub fn slot_owner(&self, pos: usize) -> Option<(u32, u32)> {
+        let [word, namespace] = self.owner(pos)?;
+        let word = word.load(Ordering::Acquire);
+        let pid = (word >> 32) as u32;
+        (word as u32 == pos as u32 && pid != 0)
+            .then(|| (pid, namespace.load(Ordering::Relaxed) as u32))
+    }
+
+    /// Returns the owner words of the slot for `pos`, if the queue keeps owners.
+    #[inline]
+    fn owner(&self, pos: usize) -> Option<&[AtomicU64; OWNER_WORDS]> {
+        let header = self.header();
+        if header.meta_flags & SLOT_OWNERS == 0 {
+            return None;
+        }
+        let regions = compute_buffer_layout(&self.layout(), header.buffer_mask + 1);
+        unsafe {
+            let owners =
+                self.base.as_ptr().add(regions.owners_offset) as *const [AtomicU64; OWNER_WORDS];
+            Some(&*owners.add(self.cell_index(pos)))
+        }
+    }
+
+    /// Skips the reserved but unpublished slot at `pos` without delivering it,
+    /// releasing it to producers of the next lap.
+    ///
+    /// Returns whether this call performed the skip. A producer that later
+    /// tries to publish the slot fails with `SlotReclaimed`; one still writing
+    /// the payload may race with the next producer of the slot, so only skip
+    /// slots whose producer is presumed dead.
+    pub fn skip_reserved(&self, pos: usize) -> bool {
+        let header = self.header();
+        let released = pos.wrapping_add(header.buffer_mask + 1);
+        let skipped = self
+            .cell(self.cell_index(pos))
+            .compare_exchange(pos, released, Ordering::AcqRel, Ordering::Relaxed)
+            .is_ok();
+        if skipped {
+            let _ = header.dequeue_pos.compare_exchange(
+                pos,
+                pos.wrapping_add(1),
+                Ordering::AcqRel,
+                Ordering::Relaxed,
+            );
+            header.not_full.notify();
+        }
+        skipped
+    }
+
     /// Attempts to dequeue an element from the queue.
-    /// Returns `Ok(())` if successful, or `QueueEmpty` if the queue is empty.
+    /// Returns `Ok(())` if successful, or `CapacityError::Empty` if the queue is empty.
     pub fn dequeue(&self, dst: &mut [u8]) -> Result<(), MpmcQueueError> {
         self.validate_dequeue_dst(dst)?;
+        let meta_size = self.header().meta_size;
+        Ok(self.dequeue_with(|_pos, slot| copy::read_payload(dst, &slot[meta_size..]))?)
+    }
+
+    /// Attempts to dequeue an element by handing the whole slot to `consume`,
+    /// which receives the dequeue position and the slot contents.
+    /// Returns the result of `consume`, or `CapacityError::Empty` if the queue is empty.
+    pub fn dequeue_with<R, F: FnOnce(usize, &[u8]) -> R>(
+        &self,
+        consume: F,
+    ) -> Result<R, CapacityError> {
         if let Some(pos) = self.try_reserve_dequeue_slot() {
-            self.read_slot(pos, dst);
-            Ok(())
+            Ok(self.read_slot(pos, |slot| consume(pos, slot)))
         } else {
-            Err(MpmcQueueError::QueueEmpty)
+            Err(CapacityError::Empty)
         }
     }
 
+    /// Attempts to dequeue up to `max` elements at once, at least one, by handing
+    /// each whole slot to `consume` with its index within the batch and its
+    /// dequeue position.
+    /// Returns the number of slots consumed, or `CapacityError::Empty` if the
+    /// queue is empty.
+    pub fn dequeue_batch_with<F: FnMut(usize, usize, &[u8])>(
+        &self,
+        max: usize,
+        mut consume: F,
+    ) -> Result<usize, CapacityError> {
+        let (first, count) = self
+            .try_reserve_dequeue_slots(max)
+            .ok_or(CapacityError::Empty)?;
+        for i in 0..count {
+            let pos = first.wrapping_add(i);
+            self.read_slot(pos, |slot| consume(i, pos, slot));
+        }
+        Ok(count)
+    }
+
+    /// Attempts to dequeue a run of elements enqueued by [`enqueue_run_with`], whose
+    /// length `run_len` reads from the whole first slot, handing each slot to
+    /// `consume` as in [`dequeue_batch_with`].
+    /// Returns the number of slots consumed, or `CapacityError::Empty` if the queue
+    /// is empty or the run is not fully published yet.
+    ///
+    /// [`enqueue_run_with`]: MpmcQueueOnBuffer::enqueue_run_with
+    /// [`dequeue_batch_with`]: MpmcQueueOnBuffer::dequeue_batch_with
+    pub fn dequeue_run_with<F: FnMut(usize, usize, &[u8])>(
+        &self,
+        run_len: impl Fn(&[u8]) -> usize,
+        mut consume: F,
+    ) -> Result<usize, CapacityError> {
+        let (first, count) = self
+            .try_reserve_dequeue_run(run_len)
+            .ok_or(CapacityError::Empty)?;
+        for i in 0..count {
+            let pos = first.wrapping_add(i);
+            self.read_slot(pos, |slot| consume(i, pos, slot));
+        }
+        Ok(count)
+    }
+
+    /// Verifies that every cell sequence falls within the window implied by
+    /// the header positions, optionally rewriting invalid cells.
+    ///
+    /// For a position `pos` in `[head, head + capacity)`, an occupied slot
+    /// (`pos < tail`) must hold `pos + 1` (published) or `pos` (being written),
+    /// and a free slot must hold `pos` (ready) or `pos - capacity + 1` (being read).
+    /// Invalid cells are reset to their quiescent value when `fix` is set.
+    /// Cells are left untouched if the positions themselves are invalid.
+    pub fn audit(&self, fix: bool) -> AuditReport {
+        let header = self.header();
+        let capacity = header.buffer_mask + 1;
+        let head = header.dequeue_pos.load(Ordering::Acquire);
+        let tail = header.enqueue_pos.load(Ordering::Acquire);
+        let mut report = AuditReport {
+            head,
+            tail,
+            positions_valid: head <= tail && tail - head <= capacity,
+            ..AuditReport::default()
+        };
+        if !report.positions_valid {
+            return report;
+        }
+
+        let width = self.cell_width();
+        for pos in head..head + capacity {
+            let cell = self.cell(self.cell_index(pos));
+            let seq = cell.load(Ordering::Acquire);
+            let ahead = width.diff(seq, pos);
+            let (valid, expected) = if pos < tail {
+                (ahead == 1 || ahead == 0, width.truncate(pos + 1))
+            } else {
+                (
+                    ahead == 0 || ahead == 1 - capacity as isize,
+                    width.truncate(pos),
+                )
+            };
+            if valid {
+                continue;
+            }
+            report.inconsistencies.push(CellInconsistency {
+                index: self.cell_index(pos),
+                sequence: seq,
+                expected,
+            });
+            if fix
+                && cell
+                    .compare_exchange(seq, expected, Ordering::AcqRel, Ordering::Relaxed)
+                    .is_ok()
+            {
+                report.fixed += 1;
+            }
+        }
+        report
+    }
+
     /// Retrieves a reference to a queue cell at the given index.
     #[inline]
-    fn cell(&self, index: usize) -> &Cell {
-        unsafe { &*self.cells_ptr().add(index) }
+    fn cell(&self, index: usize) -> Cell<'_> {
+        let cells_ptr = self.cells_ptr();
+        unsafe {
+            match self.cell_width() {
+                CellWidth::Wide => Cell::Wide(&*(cells_ptr as *const AtomicUsize).add(index)),
+                CellWidth::Narrow => Cell::Narrow(&*(cells_ptr as *const AtomicU32).add(index)),
+            }
+        }
     }
 }
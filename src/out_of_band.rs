@@ -0,0 +1,185 @@
+//! Out-of-band buffers of pickle protocol 5 for queues in object mode.
+//!
+//! Large contiguous buffers found while pickling, e.g. the data of numpy arrays,
+//! are copied into side segments of their own instead of the pickle stream, and
+//! only the names of the segments travel through the queue. The consumer maps the
+//! segments and hands them to `pickle.loads()`, so the rebuilt objects use the
+//! shared memory in place; each segment is removed once its last user is gone.
+
+use crate::byte_buffer::ByteBuffer;
+use crate::segment;
+use crate::shmem_wrapper::ShmemWrapper;
+use pyo3::exceptions::PyOSError;
+use pyo3::ffi;
+use pyo3::prelude::*;
+use pyo3::types::{PyCFunction, PyDict, PyTuple};
+use shared_memory::ShmemConf;
+use std::ffi::{c_int, c_void};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Number of side segments created by this process, which makes their names unique.
+static CREATED: AtomicU64 = AtomicU64::new(0);
+
+/// Where and from which size buffers go out of band.
+pub struct OutOfBand {
+    /// Name of the queue, which prefixes the names of its side segments.
+    pub queue: String,
+    /// Minimum size in bytes of a buffer sent out of band.
+    pub threshold: usize,
+}
+
+impl OutOfBand {
+    /// Returns a `buffer_callback` for `pickle.dumps()` moving the buffers of at least
+    /// `threshold` bytes into side segments, whose names and sizes are collected in
+    /// `buffers`.
+    ///
+    /// Buffers that are smaller, or cannot be viewed as contiguous bytes, stay in band.
+    pub fn callback<'py>(
+        &self,
+        py: Python<'py>,
+        buffers: Arc<Mutex<Vec<(String, usize)>>>,
+    ) -> PyResult<Bound<'py, PyCFunction>> {
+        let queue = self.queue.clone();
+        let threshold = self.threshold;
+        PyCFunction::new_closure(
+            py,
+            None,
+            None,
+            move |args: &Bound<'_, PyTuple>, _: Option<&Bound<'_, PyDict>>| -> PyResult<bool> {
+                let Ok(raw) = args.get_item(0)?.call_method0("raw") else {
+                    return Ok(true);
+                };
+                let data = ByteBuffer::get(&raw, false)?;
+                let data = data.as_slice();
+                if data.is_empty() || data.len() < threshold {
+                    return Ok(true);
+                }
+                let name = format!(
+                    "{}-oob-{}-{}",
+                    queue,
+                    std::process::id(),
+                    CREATED.fetch_add(1, Ordering::Relaxed)
+                );
+                let mut shmem = ShmemConf::new()
+                    .size(data.len())
+                    .os_id(&name)
+                    .create()
+                    .map_err(|e| {
+                        PyOSError::new_err(format!(
+                            "Failed to create shared memory '{}': {}",
+                            name, e
+                        ))
+                    })?;
+                unsafe { std::slice::from_raw_parts_mut(shmem.as_ptr(), data.len()) }
+                    .copy_from_slice(data);
+                // The consumer removes the segment once it is done with it.
+                shmem.set_owner(false);
+                buffers.lock().unwrap().push((name, data.len()));
+                Ok(false)
+            },
+        )
+    }
+}
+
+/// Side segments holding the out-of-band buffers of a message being enqueued.
+///
+/// Unless the message was sent, the segments are removed when this is dropped.
+#[derive(Default)]
+pub struct Segments {
+    buffers: Vec<(String, usize)>,
+    sent: AtomicBool,
+}
+
+impl Segments {
+    /// Takes ownership of the side segments of `buffers`, given by name and size.
+    pub fn new(buffers: Vec<(String, usize)>) -> Self {
+        Self {
+            buffers,
+            sent: AtomicBool::new(false),
+        }
+    }
+
+    /// Returns the names and sizes of the buffers.
+    pub fn buffers(&self) -> &[(String, usize)] {
+        &self.buffers
+    }
+
+    /// Records that the message was enqueued, handing the segments to its consumer.
+    pub fn mark_sent(&self) {
+        self.sent.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Drop for Segments {
+    fn drop(&mut self) {
+        if self.sent.load(Ordering::Relaxed) || self.buffers.is_empty() {
+            return;
+        }
+        Python::with_gil(|_| {
+            for (name, _) in &self.buffers {
+                let _ = segment::unlink(name, true);
+            }
+        });
+    }
+}
+
+/// A side segment mapped by the consumer, exposed through the buffer protocol.
+///
+/// The segment is removed when the object, and thus every object rebuilt on top of
+/// its memory, is garbage-collected.
+#[pyclass(frozen)]
+pub struct OutOfBandBuffer {
+    shmem: ShmemWrapper,
+    len: usize,
+}
+
+impl OutOfBandBuffer {
+    /// Maps the buffer of `len` bytes in the side segment `name`, taking over the
+    /// removal of the segment, whose mapping may be rounded up to whole pages.
+    ///
+    /// # Errors
+    /// Raises `OSError` if the segment cannot be opened, e.g. because the message was
+    /// already consumed through another handle.
+    pub fn open(name: &str, len: usize) -> PyResult<Self> {
+        let mut shmem = segment::open_segment(name)?.ok_or_else(|| {
+            PyOSError::new_err(format!("Out-of-band buffer '{}' does not exist", name))
+        })?;
+        shmem.set_owner(true);
+        if shmem.len() < len {
+            return Err(PyOSError::new_err(format!(
+                "Out-of-band buffer '{}' is smaller than {} bytes",
+                name, len
+            )));
+        }
+        Ok(Self {
+            shmem: ShmemWrapper::new(shmem),
+            len,
+        })
+    }
+}
+
+#[pymethods]
+impl OutOfBandBuffer {
+    unsafe fn __getbuffer__(
+        slf: Bound<'_, Self>,
+        view: *mut ffi::Py_buffer,
+        flags: c_int,
+    ) -> PyResult<()> {
+        let this = slf.get();
+        if ffi::PyBuffer_FillInfo(
+            view,
+            slf.as_ptr(),
+            this.shmem.as_ptr() as *mut c_void,
+            this.len as ffi::Py_ssize_t,
+            0,
+            flags,
+        ) == -1
+        {
+            return Err(PyErr::fetch(slf.py()));
+        }
+        Ok(())
+    }
+
+    unsafe fn __releasebuffer__(&self, _view: *mut ffi::Py_buffer) {}
+}
@@ -0,0 +1,123 @@
+use crate::mpmc_queue::MpmcQueueOnBuffer;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Tracks how long the head slot of one lane has stayed reserved but unpublished,
+/// as seen by this handle.
+#[derive(Default)]
+pub struct StallTracker {
+    observed: Mutex<Option<(usize, Duration)>>,
+}
+
+impl StallTracker {
+    /// Checks the head slot of `lane` at clock reading `now`, returning the
+    /// stalled position and how long it has been observed stalled.
+    pub fn observe(&self, lane: &MpmcQueueOnBuffer, now: Duration) -> Option<(usize, Duration)> {
+        let mut observed = self.observed.lock().unwrap();
+        match lane.stalled_head() {
+            None => {
+                *observed = None;
+                None
+            }
+            Some(pos) => {
+                let since = match *observed {
+                    Some((seen, since)) if seen == pos => since,
+                    _ => {
+                        *observed = Some((pos, now));
+                        now
+                    }
+                };
+                Some((pos, now.saturating_sub(since)))
+            }
+        }
+    }
+}
+
+/// Returns whether the process `pid` has exited, as far as this process can tell.
+///
+/// On Linux, zombies count as exited; elsewhere they count as running until reaped.
+/// Outside Unix, processes are never known to have exited.
+pub fn process_exited(pid: u32) -> bool {
+    #[cfg(unix)]
+    {
+        if unsafe { libc::kill(pid as libc::pid_t, 0) } != 0 {
+            return std::io::Error::last_os_error().raw_os_error() == Some(libc::ESRCH);
+        }
+        // The state follows the parenthesized command name, which may itself hold
+        // parentheses.
+        #[cfg(target_os = "linux")]
+        return std::fs::read_to_string(format!("/proc/{}/stat", pid))
+            .ok()
+            .and_then(|stat| {
+                let (_, rest) = stat.rsplit_once(')')?;
+                rest.trim_start().chars().next()
+            })
+            .is_some_and(|state| matches!(state, 'Z' | 'X'));
+        #[cfg(not(target_os = "linux"))]
+        false
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = pid;
+        false
+    }
+}
+
+/// Returns the inode of the PID namespace of the current process, or zero outside
+/// Linux or where `/proc` is not mounted.
+///
+/// Process ids only identify a process within its PID namespace, e.g. within one
+/// container, so shared memory recording a process id records this alongside.
+pub fn pid_namespace() -> u32 {
+    #[cfg(target_os = "linux")]
+    {
+        use std::os::unix::fs::MetadataExt;
+        static NAMESPACE: std::sync::OnceLock<u32> = std::sync::OnceLock::new();
+        *NAMESPACE.get_or_init(|| {
+            std::fs::metadata("/proc/self/ns/pid")
+                .map(|ns| ns.ino() as u32)
+                .unwrap_or(0)
+        })
+    }
+    #[cfg(not(target_os = "linux"))]
+    0
+}
+
+/// Returns whether the process `pid` of the PID namespace `namespace` has exited, as
+/// far as this process can tell.
+///
+/// Processes of other namespaces are never known to have exited: their ids name
+/// unrelated processes, or none at all, in this one.
+pub fn owner_exited(pid: u32, namespace: u32) -> bool {
+    namespace == pid_namespace() && process_exited(pid)
+}
+
+/// Detection of slots whose producer reserved them but never published them.
+///
+/// A slot is poisoned once this handle has observed it stalled at the head
+/// of a lane for at least `timeout`. Poisoned slots are only reported: a
+/// stalled producer may still be writing, so only slots whose recorded
+/// producer has exited are ever skipped.
+pub struct PoisonPolicy {
+    pub timeout: Duration,
+    pub main: StallTracker,
+    pub urgent: StallTracker,
+}
+
+impl PoisonPolicy {
+    /// Builds a policy from its Python-facing options.
+    pub fn from_options(timeout: Option<f64>) -> PyResult<Option<Self>> {
+        let Some(timeout) = timeout else {
+            return Ok(None);
+        };
+        let timeout = Duration::try_from_secs_f64(timeout)
+            .map_err(|_| PyValueError::new_err("poison_timeout must be a non-negative number"))?;
+        Ok(Some(Self {
+            timeout,
+            main: StallTracker::default(),
+            urgent: StallTracker::default(),
+        }))
+    }
+}
@@ -0,0 +1,409 @@
+//! A shared-memory allocator of fixed-size blocks for payloads too large for slots.
+//!
+//! Producers allocate a block, write the payload into it and enqueue only its index;
+//! consumers read the block and free it. Blocks are reference-counted, so a payload
+//! can be handed to several consumers, and return to a lock-free free list when the
+//! last reference is dropped.
+//!
+//! The segment starts with a [`PoolHeader`], followed by one [`BlockMeta`] per block
+//! and the blocks themselves, each aligned to a cache line.
+
+use crate::errors::{Full, InvalidParameters};
+use crate::region::{self, TaggedList, NIL};
+use crate::shmem_wrapper::ShmemWrapper;
+use pyo3::exceptions::{PyIndexError, PyValueError};
+use pyo3::ffi;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyMemoryView};
+use std::borrow::Cow;
+use std::ffi::{c_int, c_void};
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+
+/// Marks segments holding a pool.
+const MAGIC: u64 = u64::from_le_bytes(*b"ZQPOOL01");
+
+/// Alignment of the blocks.
+const ALIGN: usize = 64;
+
+/// Header of a pool segment.
+#[repr(C, align(64))]
+struct PoolHeader {
+    magic: AtomicU64,
+    block_size: u64,
+    blocks: u64,
+    free_list: TaggedList,
+    free_count: AtomicU64,
+}
+
+/// Bookkeeping of a block.
+#[repr(C)]
+struct BlockMeta {
+    /// References to the block, zero while it is free.
+    refs: AtomicU32,
+    /// Next block in the free list.
+    next: AtomicU32,
+}
+
+/// Returns the offset of the first block in a pool of `blocks` blocks.
+fn data_offset(blocks: usize) -> usize {
+    (size_of::<PoolHeader>() + blocks * size_of::<BlockMeta>()).next_multiple_of(ALIGN)
+}
+
+/// Returns the distance between consecutive blocks of `block_size` bytes.
+fn stride(block_size: usize) -> usize {
+    block_size.next_multiple_of(ALIGN)
+}
+
+/// A pool of fixed-size blocks in shared memory.
+///
+/// The handle that creates the pool removes its segment when garbage-collected.
+#[pyclass(frozen, module = "zeroq")]
+pub struct Pool {
+    name: String,
+    shmem: ShmemWrapper,
+    block_size: usize,
+    blocks: usize,
+}
+
+impl Pool {
+    fn header(&self) -> &PoolHeader {
+        unsafe { &*(self.shmem.as_ptr() as *const PoolHeader) }
+    }
+
+    fn meta(&self, block: usize) -> &BlockMeta {
+        unsafe {
+            &*(self
+                .shmem
+                .as_ptr()
+                .add(size_of::<PoolHeader>() + block * size_of::<BlockMeta>())
+                as *const BlockMeta)
+        }
+    }
+
+    /// Returns a pointer to the first byte of `block`.
+    fn block_ptr(&self, block: usize) -> *mut u8 {
+        unsafe {
+            self.shmem
+                .as_ptr()
+                .add(data_offset(self.blocks) + block * stride(self.block_size))
+                as *mut u8
+        }
+    }
+
+    /// Checks that `block` is an allocated block of the pool.
+    ///
+    /// # Errors
+    /// Raises `IndexError` for indices past the last block, or `ValueError` for free
+    /// blocks.
+    fn allocated(&self, block: usize) -> PyResult<&BlockMeta> {
+        if block >= self.blocks {
+            return Err(PyIndexError::new_err(format!(
+                "Block {} is out of range for a pool of {} blocks",
+                block, self.blocks
+            )));
+        }
+        let meta = self.meta(block);
+        if meta.refs.load(Ordering::Acquire) == 0 {
+            return Err(not_allocated(block));
+        }
+        Ok(meta)
+    }
+
+    /// Pushes `block` onto the free list.
+    fn push(&self, block: usize) {
+        let header = self.header();
+        header
+            .free_list
+            .push(block as u32, |block| &self.meta(block as usize).next);
+        header.free_count.fetch_add(1, Ordering::Release);
+    }
+
+    /// Pops a block off the free list, if any is free.
+    fn pop(&self) -> Option<usize> {
+        let header = self.header();
+        let block = header
+            .free_list
+            .pop(|block| &self.meta(block as usize).next)?;
+        header.free_count.fetch_sub(1, Ordering::Release);
+        Some(block as usize)
+    }
+}
+
+#[pymethods]
+impl Pool {
+    /// Creates or attaches to a pool of blocks in shared memory.
+    ///
+    /// # Arguments
+    /// - `name` (str): Shared memory segment name.
+    /// - `block_size` (int): Size of each block in bytes (required if creating).
+    /// - `blocks` (int): Number of blocks (required if creating).
+    /// - `create` (bool, default=True): Whether to create a new pool.
+    ///
+    /// # Errors
+    /// Raises `ValueError` for invalid parameters, `AlreadyExists` if a pool is to be
+    /// created under a name that is taken, or `OSError` if the segment cannot be
+    /// created or opened or holds no pool.
+    #[new]
+    #[pyo3(signature = (name, block_size=None, blocks=None, create=true))]
+    fn new(
+        py: Python<'_>,
+        name: String,
+        block_size: Option<usize>,
+        blocks: Option<usize>,
+        create: bool,
+    ) -> PyResult<Self> {
+        if !create {
+            let shmem = region::attach(py, &name, MAGIC, size_of::<PoolHeader>(), "pool")?;
+            let header = unsafe { &*(shmem.as_ptr() as *const PoolHeader) };
+            let found = (header.block_size as usize, header.blocks as usize);
+            if shmem.len() < data_offset(found.1) + found.1 * stride(found.0) {
+                return Err(region::holds_no(&name, "pool"));
+            }
+            if block_size.is_some_and(|size| size != found.0)
+                || blocks.is_some_and(|blocks| blocks != found.1)
+            {
+                return Err(InvalidParameters::new_err(format!(
+                    "Pool '{}' exists with block_size {} and {} blocks",
+                    name, found.0, found.1
+                )));
+            }
+            return Ok(Self {
+                name,
+                shmem,
+                block_size: found.0,
+                blocks: found.1,
+            });
+        }
+        let block_size = block_size
+            .filter(|size| *size > 0)
+            .ok_or_else(|| PyValueError::new_err("block_size > 0 required when create=true"))?;
+        let blocks = blocks
+            .filter(|blocks| (1..NIL as usize).contains(blocks))
+            .ok_or_else(|| {
+                PyValueError::new_err(format!(
+                    "blocks between 1 and {} required when create=true",
+                    NIL - 1
+                ))
+            })?;
+        let size = blocks
+            .checked_mul(stride(block_size))
+            .and_then(|size| size.checked_add(data_offset(blocks)))
+            .ok_or_else(|| PyValueError::new_err("The pool is too large"))?;
+        let shmem = crate::py_queue::create_shmem(&name, size)?;
+        let pool = Self {
+            name,
+            shmem: ShmemWrapper::new(shmem),
+            block_size,
+            blocks,
+        };
+        let header = unsafe { &mut *(pool.shmem.as_ptr() as *mut PoolHeader) };
+        header.block_size = block_size as u64;
+        header.blocks = blocks as u64;
+        for block in 0..blocks {
+            let next = if block + 1 < blocks {
+                block as u32 + 1
+            } else {
+                NIL
+            };
+            pool.meta(block).refs.store(0, Ordering::Relaxed);
+            pool.meta(block).next.store(next, Ordering::Relaxed);
+        }
+        header.free_list.init(0);
+        header.free_count.store(blocks as u64, Ordering::Relaxed);
+        header.magic.store(MAGIC, Ordering::Release);
+        Ok(pool)
+    }
+
+    /// Name of the shared memory segment.
+    #[getter]
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Size of each block in bytes.
+    #[getter]
+    fn block_size(&self) -> usize {
+        self.block_size
+    }
+
+    /// Number of blocks in the pool.
+    #[getter]
+    fn blocks(&self) -> usize {
+        self.blocks
+    }
+
+    /// Number of free blocks, a snapshot that concurrent handles may change.
+    #[getter]
+    fn available(&self) -> usize {
+        self.header().free_count.load(Ordering::Acquire) as usize
+    }
+
+    /// Allocates a block with one reference.
+    ///
+    /// # Returns
+    /// - (int): Index of the block, to enqueue instead of the payload.
+    ///
+    /// # Errors
+    /// Raises `Full` if every block is allocated.
+    fn alloc(&self) -> PyResult<usize> {
+        let block = self
+            .pop()
+            .ok_or_else(|| Full::new_err(format!("Pool '{}' has no free block", self.name)))?;
+        self.meta(block).refs.store(1, Ordering::Release);
+        Ok(block)
+    }
+
+    /// Allocates a block and copies `data` into its front.
+    ///
+    /// # Arguments
+    /// - `data` (bytes): The payload, at most `block_size` bytes.
+    ///
+    /// # Returns
+    /// - (int): Index of the block.
+    ///
+    /// # Errors
+    /// Raises `ValueError` if `data` is larger than a block, or `Full` if every block
+    /// is allocated.
+    fn write(&self, py: Python<'_>, data: Cow<[u8]>) -> PyResult<usize> {
+        if data.len() > self.block_size {
+            return Err(PyValueError::new_err(format!(
+                "Payload of {} bytes does not fit in blocks of {} bytes",
+                data.len(),
+                self.block_size
+            )));
+        }
+        let block = self.alloc()?;
+        let dst = self.block_ptr(block) as usize;
+        py.allow_threads(|| unsafe {
+            std::ptr::copy_nonoverlapping(data.as_ptr(), dst as *mut u8, data.len())
+        });
+        Ok(block)
+    }
+
+    /// Copies bytes out of an allocated block.
+    ///
+    /// # Arguments
+    /// - `block` (int): Index of the block.
+    /// - `size` (int, optional): Number of bytes to read from its front; defaults to
+    ///   the whole block.
+    ///
+    /// # Errors
+    /// Raises `IndexError` or `ValueError` if `block` is not an allocated block, or
+    /// `ValueError` if `size` exceeds the block.
+    #[pyo3(signature = (block, size=None))]
+    fn read<'py>(
+        &self,
+        py: Python<'py>,
+        block: usize,
+        size: Option<usize>,
+    ) -> PyResult<Bound<'py, PyBytes>> {
+        self.allocated(block)?;
+        let size = size.unwrap_or(self.block_size);
+        if size > self.block_size {
+            return Err(PyValueError::new_err(format!(
+                "Cannot read {} bytes from blocks of {} bytes",
+                size, self.block_size
+            )));
+        }
+        let src = unsafe { std::slice::from_raw_parts(self.block_ptr(block), size) };
+        Ok(PyBytes::new(py, src))
+    }
+
+    /// Returns a writable view over an allocated block, for writing or reading the
+    /// payload in place. The view keeps this handle alive.
+    ///
+    /// # Errors
+    /// Raises `IndexError` or `ValueError` if `block` is not an allocated block.
+    fn view<'py>(slf: &Bound<'py, Self>, block: usize) -> PyResult<Bound<'py, PyMemoryView>> {
+        slf.get().allocated(block)?;
+        let block = Bound::new(
+            slf.py(),
+            PoolBlock {
+                pool: slf.clone().unbind(),
+                block,
+            },
+        )?;
+        PyMemoryView::from(block.as_any())
+    }
+
+    /// Adds a reference to an allocated block, e.g. before handing it to a second
+    /// consumer, which frees it as well.
+    ///
+    /// # Errors
+    /// Raises `IndexError` or `ValueError` if `block` is not an allocated block.
+    fn retain(&self, block: usize) -> PyResult<()> {
+        self.allocated(block)?
+            .refs
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |refs| {
+                (refs > 0).then(|| refs + 1)
+            })
+            .map_err(|_| not_allocated(block))?;
+        Ok(())
+    }
+
+    /// Drops a reference to an allocated block, returning it to the pool once no
+    /// reference is left. Views of the block must no longer be used then.
+    ///
+    /// # Errors
+    /// Raises `IndexError` or `ValueError` if `block` is not an allocated block.
+    fn free(&self, block: usize) -> PyResult<()> {
+        let refs = self
+            .allocated(block)?
+            .refs
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |refs| {
+                (refs > 0).then(|| refs - 1)
+            })
+            .map_err(|_| not_allocated(block))?;
+        if refs == 1 {
+            self.push(block);
+        }
+        Ok(())
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Pool(name='{}', block_size={}, blocks={}, available={})",
+            self.name,
+            self.block_size,
+            self.blocks,
+            self.available()
+        )
+    }
+}
+
+/// A block exposed through the buffer protocol by `Pool.view()`.
+#[pyclass(frozen)]
+struct PoolBlock {
+    pool: Py<Pool>,
+    block: usize,
+}
+
+#[pymethods]
+impl PoolBlock {
+    unsafe fn __getbuffer__(
+        slf: Bound<'_, Self>,
+        view: *mut ffi::Py_buffer,
+        flags: c_int,
+    ) -> PyResult<()> {
+        let this = slf.get();
+        let pool = this.pool.get();
+        if ffi::PyBuffer_FillInfo(
+            view,
+            slf.as_ptr(),
+            pool.block_ptr(this.block) as *mut c_void,
+            pool.block_size as ffi::Py_ssize_t,
+            0,
+            flags,
+        ) == -1
+        {
+            return Err(PyErr::fetch(slf.py()));
+        }
+        Ok(())
+    }
+
+    unsafe fn __releasebuffer__(&self, _view: *mut ffi::Py_buffer) {}
+}
+
+fn not_allocated(block: usize) -> PyErr {
+    PyValueError::new_err(format!("Block {} is not allocated", block))
+}
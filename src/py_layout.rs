@@ -0,0 +1,181 @@
+use crate::crypto::CipherKind;
+use crate::message::MetaLayout;
+use crate::mpmc_queue::{
+    compute_buffer_layout, compute_lane_offset, compute_required_size, validate_capacity,
+    CellWidth, SlotLayout,
+};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+/// Builds the slot layout produced by the given queue creation options.
+#[allow(clippy::too_many_arguments)]
+pub fn slot_layout(
+    element_size: usize,
+    metadata: Option<Vec<String>>,
+    headers_size: usize,
+    sequence_bits: u32,
+    encryption: Option<&str>,
+    pad_slots: bool,
+    checksum: bool,
+    track_owners: bool,
+) -> PyResult<SlotLayout> {
+    let cell_width = CellWidth::from_bits(sequence_bits).ok_or_else(|| {
+        PyValueError::new_err(format!(
+            "Unsupported sequence_bits {}: expected 32 or 64",
+            sequence_bits
+        ))
+    })?;
+    let cipher = encryption.map(CipherKind::from_name).transpose()?;
+    let meta = MetaLayout::from_options(metadata, headers_size, cipher, checksum)?;
+    Ok(SlotLayout {
+        element_size,
+        meta_size: meta.size(),
+        meta_flags: meta.flags,
+        cell_width,
+        padded: pad_slots,
+        owners: track_owners,
+    })
+}
+
+/// Fraction of the main capacity given to the urgent lane.
+const URGENT_LANE_RATIO: usize = 16;
+
+/// Returns the capacity of the urgent lane of a queue with `capacity` slots.
+pub fn urgent_capacity(capacity: usize) -> usize {
+    (capacity / URGENT_LANE_RATIO).max(2)
+}
+
+/// Returns the size in bytes of a queue with the given slot `layout` and
+/// `capacity`, including its urgent lane if enabled.
+pub fn segment_size(layout: &SlotLayout, capacity: usize, urgent_lane: bool) -> usize {
+    if urgent_lane {
+        compute_lane_offset(layout, capacity)
+            + compute_required_size(layout, urgent_capacity(capacity))
+    } else {
+        compute_required_size(layout, capacity)
+    }
+}
+
+/// Returns the size in bytes of the shared-memory segment a queue would need.
+///
+/// Accepts the same layout options as the `Queue` constructor, so deployment
+/// tooling can check shared-memory budgets before any queue is created.
+///
+/// # Errors
+/// Raises `ValueError` if the capacity or an option is invalid.
+#[pyfunction]
+#[pyo3(signature = (
+    element_size,
+    capacity,
+    metadata=None,
+    headers_size=0,
+    sequence_bits=64,
+    encryption=None,
+    urgent_lane=false,
+    pad_slots=false,
+    checksum=false,
+    track_owners=false,
+))]
+#[allow(clippy::too_many_arguments)]
+pub fn required_size(
+    element_size: usize,
+    capacity: usize,
+    metadata: Option<Vec<String>>,
+    headers_size: usize,
+    sequence_bits: u32,
+    encryption: Option<&str>,
+    urgent_lane: bool,
+    pad_slots: bool,
+    checksum: bool,
+    track_owners: bool,
+) -> PyResult<usize> {
+    let layout = slot_layout(
+        element_size,
+        metadata,
+        headers_size,
+        sequence_bits,
+        encryption,
+        pad_slots,
+        checksum,
+        track_owners,
+    )?;
+    validate_capacity(&layout, capacity)?;
+    Ok(segment_size(&layout, capacity, urgent_lane))
+}
+
+/// Describes the segment layout a queue with the given options would produce.
+///
+/// # Returns
+/// - (dict): Offsets and sizes in bytes of the header, cells, owners and data regions,
+///   the per-slot metadata and total slot size, the slot stride, the offset and capacity of the
+///   urgent lane (zero if disabled), and the total segment size.
+///
+/// # Errors
+/// Raises `ValueError` if the capacity or an option is invalid.
+#[pyfunction]
+#[pyo3(signature = (
+    element_size,
+    capacity,
+    metadata=None,
+    headers_size=0,
+    sequence_bits=64,
+    encryption=None,
+    urgent_lane=false,
+    pad_slots=false,
+    checksum=false,
+    track_owners=false,
+))]
+#[allow(clippy::too_many_arguments)]
+pub fn plan<'py>(
+    py: Python<'py>,
+    element_size: usize,
+    capacity: usize,
+    metadata: Option<Vec<String>>,
+    headers_size: usize,
+    sequence_bits: u32,
+    encryption: Option<&str>,
+    urgent_lane: bool,
+    pad_slots: bool,
+    checksum: bool,
+    track_owners: bool,
+) -> PyResult<Bound<'py, PyDict>> {
+    let layout = slot_layout(
+        element_size,
+        metadata,
+        headers_size,
+        sequence_bits,
+        encryption,
+        pad_slots,
+        checksum,
+        track_owners,
+    )?;
+    validate_capacity(&layout, capacity)?;
+    let regions = compute_buffer_layout(&layout, capacity);
+
+    let dict = PyDict::new(py);
+    dict.set_item("element_size", element_size)?;
+    dict.set_item("capacity", capacity)?;
+    dict.set_item("meta_size", layout.meta_size)?;
+    dict.set_item("slot_size", layout.slot_size())?;
+    dict.set_item("stride", layout.stride())?;
+    dict.set_item("header_size", regions.header_size)?;
+    dict.set_item("cells_offset", regions.cells_offset)?;
+    dict.set_item("cells_size", regions.cells_size)?;
+    dict.set_item("owners_offset", regions.owners_offset)?;
+    dict.set_item("owners_size", regions.owners_size)?;
+    dict.set_item("data_offset", regions.data_offset)?;
+    dict.set_item("data_size", regions.data_size)?;
+    let (urgent_offset, urgent_capacity) = if urgent_lane {
+        (
+            compute_lane_offset(&layout, capacity),
+            urgent_capacity(capacity),
+        )
+    } else {
+        (0, 0)
+    };
+    dict.set_item("urgent_offset", urgent_offset)?;
+    dict.set_item("urgent_capacity", urgent_capacity)?;
+    dict.set_item("total_size", segment_size(&layout, capacity, urgent_lane))?;
+    Ok(dict)
+}
@@ -1,23 +1,127 @@
-use crate::errors::{Empty, Full};
-use crate::mpmc_queue::MpmcQueueOnBuffer;
+use crate::array;
+use crate::byte_buffer::ByteBuffer;
+use crate::clock::{Clock, ManualClock};
+use crate::config::{ConfigOptions, HandleConfig, SharedConfig};
+use crate::conformance::LAYOUT_VERSION;
+use crate::copy;
+use crate::crypto::{Keyring, ENVELOPE_SIZE};
+use crate::errors::{
+    AlreadyExists, Cancelled, CorruptMessage, Empty, Full, InvalidParameters, QueueClosed,
+};
+use crate::fork;
+use crate::futex::{self, WaitSignal, Waiter};
+use crate::message::{unix_time_ns, Message, MetaLayout};
+use crate::mpmc_queue::{
+    compute_buffer_layout, compute_lane_offset, AuditReport, CapacityError, MpmcQueueError,
+    MpmcQueueOnBuffer, SlotLayout, QUEUE_MAGIC,
+};
+use crate::out_of_band::{OutOfBand, Segments};
+use crate::poison::{owner_exited, PoisonPolicy, StallTracker};
+use crate::py_layout::{segment_size, slot_layout, urgent_capacity};
+#[cfg(unix)]
+use crate::readiness::{Condition, Readiness};
+use crate::region;
+use crate::registry::{self, Registration};
+use crate::segment;
+use crate::serializer::{self, Serializer};
 use crate::shmem_wrapper::ShmemWrapper;
-use pyo3::exceptions::{PyOSError, PyValueError};
+use crate::slot_view::SlotView;
+use crate::stats::{QueueStats, WaitOp};
+use crate::stream;
+use crate::struct_format::StructFormat;
+use crate::tensor;
+use crate::wait::{BackpressureCurve, RetryPolicy, WaitStrategy};
+#[cfg(not(unix))]
+use pyo3::exceptions::PyNotImplementedError;
+use pyo3::exceptions::{PyBufferError, PyOSError, PyRuntimeError, PyTypeError, PyValueError};
 use pyo3::prelude::*;
-use shared_memory::ShmemConf;
+use pyo3::types::{PyByteArray, PyBytes, PyDict, PyType};
+use shared_memory::{Shmem, ShmemConf, ShmemError};
 use std::borrow::Cow;
+use std::cell::Cell;
+use std::collections::HashMap;
 use std::mem::MaybeUninit;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
 use std::time::{Duration, Instant};
 
+/// Longest a parked wait sleeps on a wake-up signal before re-checking the queue,
+/// bounding the delay when a peer cannot wake it (e.g. it died mid-operation).
+pub(crate) const PARK_RECHECK: Duration = Duration::from_millis(100);
+
+/// Longest a blocked operation goes without checking for signals, so that Ctrl-C
+/// interrupts waits that would otherwise last until a peer makes progress.
+pub const SIGNAL_CHECK: Duration = Duration::from_millis(50);
+
+/// Message of `QueueClosed` raised after another handle shut the queue down.
+const SHUT_DOWN: &str = "Queue was shut down";
+
+/// Longest an attaching handle waits for a concurrent creator to finish initializing
+/// the queue.
+pub const INIT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How a handle obtains the shared memory segment of its queue.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Creation {
+    Create,
+    Open,
+    OpenOrCreate,
+}
+
+/// An item about to be enqueued.
+struct Item {
+    /// The payload, or the serialized object if `framed` is set.
+    data: ByteBuffer,
+    /// Whether `data` is written as a frame instead of filling the payload.
+    framed: bool,
+    /// Side segments of the buffers of the serialized object sent out of band.
+    segments: Segments,
+}
+
+impl Item {
+    /// Writes the item into the whole `payload`.
+    fn write(&self, payload: &mut [u8]) {
+        if self.framed {
+            serializer::write_frame(payload, self.data.as_slice(), self.segments.buffers());
+            self.segments.mark_sent();
+        } else {
+            copy::write_payload(payload, self.data.as_slice());
+        }
+    }
+}
+
 /// A Python-exposed shared-memory MPMC queue.
 ///
 /// This queue supports both blocking and non-blocking `put`/`get` operations.
+/// Handles inherited through `fork()` keep working in the child, acting as attached
+/// handles there.
 #[pyclass]
 pub struct Queue {
+    name: String,
     shared_mem: Option<ShmemWrapper>,
     queue: MpmcQueueOnBuffer<'static>,
+    urgent: Option<MpmcQueueOnBuffer<'static>>,
+    meta: MetaLayout,
+    keyring: Option<RwLock<Keyring>>,
     closed: Arc<AtomicBool>,
+    cancelled: AtomicBool,
+    config: RwLock<Arc<HandleConfig>>,
+    config_generation: AtomicU64,
+    open_views: AtomicUsize,
+    #[cfg(unix)]
+    readiness: [Mutex<Option<Readiness>>; 2],
+    poison: Option<PoisonPolicy>,
+    skip_poisoned: bool,
+    clock: Clock,
+    stats: QueueStats,
+    serializer: Option<Serializer>,
+    format: Option<StructFormat>,
+    roles: u32,
+    registration: Mutex<Option<Registration>>,
+    /// Fork generation that `registration` was claimed in; a child that inherited the
+    /// handle claims an entry of its own on first use.
+    registered_in: AtomicU64,
 }
 
 #[pymethods]
@@ -30,72 +134,650 @@ impl Queue {
     /// # Arguments
     /// - `name` (str): Identifier for the shared memory segment.
     /// - `element_size` (int, optional): Size of each element in bytes (required if creating).
+    ///   When attaching, the queue must have elements of this size.
     /// - `capacity` (int, optional): Number of slots (must be a power of two; required if creating).
-    /// - `create` (bool, default=True): Whether to create a new queue.
+    ///   When attaching, the queue must have this capacity.
+    /// - `create` (bool, default=True): Whether to create a new queue; `Queue.create()` and
+    ///   `Queue.open()` spell this out.
+    /// - `mode` (str, optional): `"create"`, `"open"`, or `"open_or_create"`, which attaches
+    ///   to the queue if it exists and creates it otherwise, so that symmetric processes
+    ///   can start in any order; the existing queue must match `element_size`, `capacity`
+    ///   and the other layout options. Takes precedence over `create`.
+    /// - `offset` (int, default=0): Byte offset of the queue within the shared memory segment,
+    ///   leaving the bytes before it to other users of the segment. Must be a multiple of 8.
+    /// - `adopt` (bool, default=False): Initialize a new queue at `offset` inside an existing
+    ///   segment owned by another tool (requires `create=False`, `element_size` and
+    ///   `capacity`). The segment is never unlinked by the queue.
+    /// - `wait` (str, optional): Wait policy of blocking operations: `"spin"` busy-spins,
+    ///   `"hybrid"` spins for `spin_limit` attempts, yields for `yield_limit`, then parks,
+    ///   and `"sleep"` parks between every attempt. A shorthand for `busy_spin` and
+    ///   `adaptive`, which must not be given with it; defaults to `"sleep"`.
+    /// - `busy_spin` (bool, default=False): Busy-spin in blocking operations instead of
+    ///   sleeping, trading a full CPU core for the lowest wakeup latency.
+    /// - `adaptive` (bool, default=False): Spin, then yield, then park in blocking operations,
+    ///   giving near-spin latency under load and near-zero CPU when idle.
+    /// - `spin_limit` (int, default=1000): Attempts spent busy-spinning before yielding.
+    /// - `yield_limit` (int, default=100): Attempts spent yielding before parking.
+    /// - `park_interval` (float, default=0.001): Sleep in seconds between parked attempts,
+    ///   the maximum sleep of the `"hybrid"` and `"sleep"` policies.
+    ///   On Linux and macOS, parked attempts instead wait on a word in the queue header
+    ///   and wake as soon as a peer makes progress, re-checking at least every 100 ms.
+    /// - `backpressure` (str, optional): Throttling curve applied by blocking puts after
+    ///   consecutive `Full` outcomes, either `"linear"` or `"exponential"`.
+    /// - `backpressure_base` (float, default=0.0001): Initial throttling delay in seconds.
+    /// - `backpressure_max` (float, default=0.01): Upper bound of the throttling delay in seconds.
+    /// - `metadata` (list[str], optional): Metadata fields stored with every message, any of
+    ///   `"sequence"`, `"timestamp"`, `"producer_id"` and `"deadline"` (used only if creating).
+    /// - `headers_size` (int, default=0): Maximum size of per-message headers in bytes;
+    ///   zero disables headers (used only if creating).
+    /// - `sequence_bits` (int, default=64): Width of the per-slot sequence counter, 64 or 32;
+    ///   32 halves the cells array and limits the capacity to 2**30 (used only if creating).
+    /// - `pad_slots` (bool, default=False): Round the slot stride up to a multiple of the
+    ///   64-byte cache line so small elements never straddle cache lines, trading memory for
+    ///   throughput; the effective stride is reported by `debug_info()` (used only if
+    ///   creating).
+    /// - `urgent_lane` (bool, default=False): Reserve a secondary lane of 1/16 of the capacity
+    ///   (at least 2 slots) for `put_urgent()`, which `get()` drains first (used only if
+    ///   creating).
+    /// - `encryption` (str, optional): Authenticated cipher protecting every payload, either
+    ///   `"chacha20-poly1305"` or `"aes-256-gcm"` (used only if creating).
+    /// - `keys` (dict[int, bytes], optional): 32-byte keys by key id; required for
+    ///   encrypted queues, both when creating and when attaching.
+    /// - `key_id` (int, optional): Key used to encrypt new messages; defaults to the
+    ///   highest key id.
+    /// - `audit` (str, optional): Cell-consistency audit run when attaching: `"check"` raises
+    ///   on inconsistencies, `"fix"` repairs them (see `audit()`).
+    /// - `slow_op_threshold` (float, optional): Blocking operations waiting at least this many
+    ///   seconds are logged as warnings on the `zeroq` logger.
+    /// - `poison_timeout` (float, optional): Seconds a slot may stay reserved but unpublished
+    ///   at the head of a lane before `health()` reports it as poisoned.
+    /// - `skip_poisoned` (bool, default=False): Let consumers skip slots whose producer
+    ///   died before publishing them instead of waiting for them forever; requires a queue
+    ///   created with `track_owners`. Slots of live producers are never skipped, however
+    ///   long they stall.
+    /// - `drop_expired` (bool, default=False): Discard messages past their deadline on
+    ///   dequeue instead of returning them; discarded messages are counted in `stats()`.
+    /// - `clock` (ManualClock, optional): Time source for timeouts and waiting, replacing
+    ///   real time so timeout behavior can be tested deterministically.
+    /// - `serializer` (str, optional): `"pickle"` makes `put()`, `get()` and their
+    ///   variants accept and return arbitrary Python objects, serialized into the slot
+    ///   behind a 4-byte length field. Applies to this handle only, so pass it to every
+    ///   handle of the queue; `get_with_meta()` and the zero-copy methods still see the
+    ///   raw payload.
+    /// - `codec` (str or object, optional): How `put()`, `get()` and their variants
+    ///   encode objects, framed like with `serializer`: `"raw"` (the default) for bytes
+    ///   as they are, `"pickle"`, `"json"`, `"msgpack"` or `"cbor"` for self-describing
+    ///   formats that consumers in other languages can decode (the latter two need the
+    ///   `msgpack` and `cbor2` packages), or an object whose `encode(obj)` returns
+    ///   `bytes` or `str` and whose `decode(data)` rebuilds the object. Excludes
+    ///   `serializer`.
+    /// - `fmt` (str, optional): A `struct` format such as `"<IdQ16s"` making `put()`,
+    ///   `get()` and their variants pack and unpack tuples as fixed-size records,
+    ///   in Rust. Defaults `element_size` to the size of a record, and must match the
+    ///   element size of an existing queue. Excludes `serializer` and `codec`.
+    /// - `out_of_band` (int, optional): Minimum size in bytes of the buffers that pickle
+    ///   protocol 5 exposes, e.g. the data of numpy arrays, to send out of band: each is
+    ///   copied into a side segment of its own, which the consumer maps instead of
+    ///   unpickling a copy, and which is removed once the rebuilt objects are garbage
+    ///   collected. Requires the pickle serializer. Side segments of messages that are never
+    ///   received are left behind.
+    /// - `huge_pages` (bool, default=False): Back the segment with 2 MiB huge pages to
+    ///   cut TLB misses on large queues (Linux only): transparent huge pages for
+    ///   segments in `/dev/shm`, which must be mounted with `huge=advise`, or pages
+    ///   from the hugetlb pool with `backing='memfd'`. The creator rounds the segment up
+    ///   to whole huge pages and faults them in; pass it on every handle, so that each
+    ///   maps them as huge pages.
+    /// - `numa_node` (int, optional): NUMA node to place the pages of the segment on,
+    ///   whichever process touches them first (Linux only, used only if creating). Pages
+    ///   otherwise follow the default first-touch policy: each lands on the node of the
+    ///   process that first writes to it.
+    /// - `numa_policy` (str, default='bind'): `'bind'` to allocate only on `numa_node`,
+    ///   or `'preferred'` to fall back to other nodes once it runs out of memory.
+    /// - `backing` (str, default='shm'): `'shm'` for a segment named `name`; `'memfd'`
+    ///   for an anonymous segment that never appears in `/dev/shm`, which the kernel
+    ///   frees once the last handle is gone, even if every process was killed (Linux
+    ///   only); or `'file'` for a regular file at `path`, e.g. on a tmpfs larger than
+    ///   `/dev/shm`, which its creator removes when closed and which crashed processes
+    ///   leave behind for inspection. `name` then only labels the queue; other
+    ///   processes attach through the descriptor returned by `segment_fd()`, e.g.
+    ///   passed over a Unix socket, or to the same `path`.
+    /// - `path` (str or PathLike, optional): File of a queue with `backing='file'`.
+    /// - `fd` (int, optional): Descriptor of the segment to attach to instead of opening
+    ///   `name`, e.g. one received with `socket.recv_fds()` (requires `create=False`).
+    ///   The handle keeps a duplicate, so the caller may close it.
+    /// - `checksum` (bool, default=False): Store a CRC-32 of every message in its slot,
+    ///   verified on every get, so that a process scribbling over the segment raises
+    ///   `CorruptMessage` instead of delivering garbage (used only if creating).
+    /// - `track_owners` (bool, default=False): Record the process id of the producer of
+    ///   every slot as it is reserved, so that slots whose producer died before publishing
+    ///   them can be reclaimed, by consumers with `skip_poisoned` or on demand by
+    ///   `repair()` (Unix only, used only if creating). Producers in another PID
+    ///   namespace are never known to have exited, so their slots are never reclaimed.
+    /// - `role` (str, optional): Role the handle registers with in the queue header,
+    ///   `"producer"`, `"consumer"` or `"both"`, as reported by `attached_processes()`;
+    ///   handles without one are registered without roles.
     ///
     /// # Errors
-    /// Raises `InvalidParameters`, `FailedCreateSharedMemory`, or `FailedOpenSharedMemory`
-    /// on failure.
+    /// Raises `ValueError` for invalid parameters, `InvalidParameters` if the queue attached
+    /// to does not have the expected layout, `AlreadyExists` if a queue is to be created
+    /// under a name that is taken, or `OSError` if the segment cannot be created or opened.
     #[new]
-    #[pyo3(signature = (name, element_size=None, capacity=None, create=true))]
+    #[pyo3(signature = (
+        name,
+        element_size=None,
+        capacity=None,
+        create=true,
+        mode=None,
+        offset=0,
+        adopt=false,
+        wait=None,
+        busy_spin=false,
+        adaptive=false,
+        spin_limit=1000,
+        yield_limit=100,
+        park_interval=0.001,
+        backpressure=None,
+        backpressure_base=0.0001,
+        backpressure_max=0.01,
+        metadata=None,
+        headers_size=0,
+        sequence_bits=64,
+        pad_slots=false,
+        urgent_lane=false,
+        encryption=None,
+        keys=None,
+        key_id=None,
+        audit=None,
+        slow_op_threshold=None,
+        poison_timeout=None,
+        skip_poisoned=false,
+        drop_expired=false,
+        clock=None,
+        serializer=None,
+        codec=None,
+        fmt=None,
+        out_of_band=None,
+        huge_pages=false,
+        numa_node=None,
+        numa_policy="bind",
+        backing="shm",
+        fd=None,
+        path=None,
+        checksum=false,
+        track_owners=false,
+        role=None,
+    ))]
+    #[allow(clippy::too_many_arguments)]
     fn new(
+        py: Python<'_>,
         name: String,
         element_size: Option<usize>,
         capacity: Option<usize>,
         create: bool,
+        mode: Option<&str>,
+        offset: usize,
+        adopt: bool,
+        wait: Option<&str>,
+        busy_spin: bool,
+        adaptive: bool,
+        spin_limit: u32,
+        yield_limit: u32,
+        park_interval: f64,
+        backpressure: Option<&str>,
+        backpressure_base: f64,
+        backpressure_max: f64,
+        metadata: Option<Vec<String>>,
+        headers_size: usize,
+        sequence_bits: u32,
+        pad_slots: bool,
+        urgent_lane: bool,
+        encryption: Option<&str>,
+        keys: Option<HashMap<u8, Vec<u8>>>,
+        key_id: Option<u8>,
+        audit: Option<&str>,
+        slow_op_threshold: Option<f64>,
+        poison_timeout: Option<f64>,
+        skip_poisoned: bool,
+        drop_expired: bool,
+        clock: Option<Py<ManualClock>>,
+        serializer: Option<&str>,
+        codec: Option<&Bound<'_, PyAny>>,
+        fmt: Option<&str>,
+        out_of_band: Option<usize>,
+        huge_pages: bool,
+        numa_node: Option<usize>,
+        numa_policy: &str,
+        backing: &str,
+        fd: Option<i32>,
+        path: Option<PathBuf>,
+        checksum: bool,
+        track_owners: bool,
+        role: Option<&str>,
     ) -> PyResult<Self> {
-        // Determine queue parameters.
-        let (elem_size, cap) = if create {
+        let out_of_band = out_of_band.map(|threshold| OutOfBand {
+            queue: name.clone(),
+            threshold,
+        });
+        let serializer = match (serializer, codec) {
+            (Some(_), Some(_)) => {
+                return Err(PyValueError::new_err(
+                    "serializer and codec are mutually exclusive",
+                ))
+            }
+            (Some(serializer), None) => Some(Serializer::from_name(py, serializer, out_of_band)?),
+            (None, Some(codec)) => Serializer::from_codec(py, codec, out_of_band)?,
+            (None, None) if out_of_band.is_some() => {
+                return Err(PyValueError::new_err("out_of_band requires a serializer"))
+            }
+            (None, None) => None,
+        };
+        let format = fmt.map(StructFormat::parse).transpose()?;
+        let element_size = match &format {
+            Some(_) if serializer.is_some() => {
+                return Err(PyValueError::new_err(
+                    "fmt cannot be combined with serializer or codec",
+                ))
+            }
+            Some(format) if element_size.is_some_and(|size| size != format.size()) => {
+                return Err(PyValueError::new_err(format!(
+                    "element_size {} does not match the {} bytes of fmt '{}'",
+                    element_size.unwrap(),
+                    format.size(),
+                    fmt.unwrap()
+                )))
+            }
+            Some(format) => Some(format.size()),
+            None => element_size,
+        };
+        let audit_fix = match audit {
+            None => None,
+            Some("check") => Some(false),
+            Some("fix") => Some(true),
+            Some(other) => {
+                return Err(PyValueError::new_err(format!(
+                    "Unknown audit mode '{}': expected 'check' or 'fix'",
+                    other
+                )))
+            }
+        };
+        let (busy_spin, adaptive) = match wait {
+            None => (busy_spin, adaptive),
+            Some(_) if busy_spin || adaptive => {
+                return Err(PyValueError::new_err(
+                    "wait cannot be combined with busy_spin or adaptive",
+                ))
+            }
+            Some(name) => WaitStrategy::flags_from_name(name)?,
+        };
+        let config = HandleConfig::new(ConfigOptions {
+            busy_spin,
+            adaptive,
+            spin_limit,
+            yield_limit,
+            park_interval,
+            backpressure: backpressure.map(BackpressureCurve::from_name).transpose()?,
+            backpressure_base,
+            backpressure_max,
+            slow_op_threshold,
+            drop_expired,
+        })?;
+        let poison = PoisonPolicy::from_options(poison_timeout)?;
+        let roles = registry::roles_from_name(role)?;
+        if track_owners && cfg!(not(unix)) {
+            return Err(pyo3::exceptions::PyNotImplementedError::new_err(
+                "track_owners is only supported on Unix, where process liveness can be checked",
+            ));
+        }
+
+        let creation = match mode {
+            None if create => Creation::Create,
+            None | Some("open") => Creation::Open,
+            Some("create") => Creation::Create,
+            Some("open_or_create") => Creation::OpenOrCreate,
+            Some(other) => {
+                return Err(PyValueError::new_err(format!(
+                    "Unknown mode '{}': expected 'create', 'open' or 'open_or_create'",
+                    other
+                )))
+            }
+        };
+        if adopt && creation != Creation::Open {
+            return Err(PyValueError::new_err("adopt=true requires create=false"));
+        }
+        let backing_name = backing;
+        let backing = segment::BackingKind::from_name(backing)?;
+        match (backing, creation, fd, &path) {
+            (_, Creation::Create | Creation::OpenOrCreate, Some(_), _) => {
+                return Err(PyValueError::new_err("fd requires create=false"))
+            }
+            (segment::BackingKind::Shm | segment::BackingKind::Memfd, _, _, Some(_)) => {
+                return Err(PyValueError::new_err("path requires backing='file'"))
+            }
+            (segment::BackingKind::File, _, _, None) => {
+                return Err(PyValueError::new_err("backing='file' requires path"))
+            }
+            (segment::BackingKind::File, _, Some(_), _) => {
+                return Err(PyValueError::new_err(
+                    "fd cannot be combined with backing='file'",
+                ))
+            }
             (
-                element_size.ok_or_else(|| {
-                    PyValueError::new_err("element_size required when create=true")
-                })?,
-                capacity
-                    .ok_or_else(|| PyValueError::new_err("capacity required when create=true"))?,
-            )
-        } else {
-            // Attach: read parameters from shared memory header.
-            let shmem_temp = ShmemConf::new().os_id(&name).open().map_err(|e| {
-                PyOSError::new_err(format!("Failed to open shared memory '{}': {}", name, e))
-            })?;
-            let base_ptr = shmem_temp.as_ptr() as usize;
-            let header_ptr = base_ptr as *const crate::mpmc_queue::MpmcQueueHeader;
-            let header = unsafe { &*header_ptr };
-            (header.element_size, header.buffer_mask + 1)
+                segment::BackingKind::Memfd | segment::BackingKind::File,
+                Creation::OpenOrCreate,
+                ..,
+            ) => {
+                return Err(PyValueError::new_err(format!(
+                    "backing='{}' cannot be combined with mode='open_or_create'",
+                    backing_name
+                )))
+            }
+            (segment::BackingKind::Memfd, Creation::Open, None, _) => {
+                return Err(PyValueError::new_err(
+                    "backing='memfd' requires fd when attaching",
+                ))
+            }
+            _ => {}
+        }
+        let label = match creation {
+            Creation::Create => "create=true",
+            Creation::OpenOrCreate => "mode='open_or_create'",
+            Creation::Open => "adopt=true",
         };
 
-        let required_size = crate::mpmc_queue::compute_required_size(elem_size, cap);
+        // Layout of a queue this handle may have to initialize.
+        let requested = if creation != Creation::Open || adopt {
+            let elem_size = element_size.ok_or_else(|| {
+                PyValueError::new_err(format!("element_size required when {}", label))
+            })?;
+            let cap = capacity.ok_or_else(|| {
+                PyValueError::new_err(format!("capacity required when {}", label))
+            })?;
+            let layout = slot_layout(
+                elem_size,
+                metadata,
+                headers_size,
+                sequence_bits,
+                encryption,
+                pad_slots,
+                checksum,
+                track_owners,
+            )?;
+            Some((layout, cap))
+        } else {
+            None
+        };
 
         // Create or open shared memory.
-        let shmem = if create {
-            ShmemConf::new()
-                .os_id(&name)
-                .size(required_size)
-                .create()
-                .map_err(|e| {
-                    PyOSError::new_err(format!("Failed to create shared memory '{}': {}", name, e))
-                })?
-        } else {
-            ShmemConf::new().os_id(&name).open().map_err(|e| {
-                PyOSError::new_err(format!("Failed to open shared memory '{}': {}", name, e))
-            })?
+        if huge_pages {
+            segment::check_huge_pages(backing, fd.is_some())?;
+        }
+        let numa_policy = segment::NumaPolicy::from_name(numa_policy)?;
+        if let Some(node) = numa_node {
+            segment::check_numa_node(node)?;
+        }
+        let size = |layout, cap| {
+            let size = offset + segment_size(layout, cap, urgent_lane);
+            if huge_pages {
+                size.next_multiple_of(segment::HUGE_PAGE)
+            } else {
+                size
+            }
+        };
+        let (shmem_wrapper, created) = match (creation, requested) {
+            (Creation::Create, Some((layout, cap))) if backing == segment::BackingKind::Memfd => (
+                segment::create_memfd(&name, size(&layout, cap), huge_pages)?,
+                true,
+            ),
+            (Creation::Create, Some((layout, cap))) if backing == segment::BackingKind::File => (
+                segment::create_file(&name, path.as_deref().unwrap(), size(&layout, cap))?,
+                true,
+            ),
+            (Creation::Create, Some((layout, cap))) => (
+                ShmemWrapper::new(create_shmem(&name, size(&layout, cap))?),
+                true,
+            ),
+            (Creation::OpenOrCreate, Some((layout, cap))) => {
+                let (shmem, created) = open_or_create_shmem(&name, size(&layout, cap))?;
+                (ShmemWrapper::new(shmem), created)
+            }
+            _ => match (fd, &path) {
+                (Some(fd), _) => (segment::map_fd(&name, fd)?, false),
+                (None, Some(path)) => (segment::open_file(&name, path)?, false),
+                (None, None) => (ShmemWrapper::new(open_shmem(&name)?), false),
+            },
+        };
+        if let (Some(node), true) = (numa_node, created) {
+            segment::bind_to_node(&name, &shmem_wrapper, node, numa_policy)?;
+        }
+        // memfd segments come from the hugetlb pool, whose pages need no advice.
+        if huge_pages && backing == segment::BackingKind::Shm && fd.is_none() {
+            segment::use_huge_pages(&name, &shmem_wrapper, created)?;
+        }
+        check_offset(&name, offset, shmem_wrapper.len())?;
+        let initialize = created || adopt;
+
+        // Determine queue parameters, from the header when attaching.
+        let (layout, cap) = match requested {
+            Some(requested) if initialize => requested,
+            _ => {
+                let header = unsafe {
+                    &*(shmem_wrapper.as_ptr().add(offset)
+                        as *const crate::mpmc_queue::MpmcQueueHeader)
+                };
+                py.allow_threads(|| wait_ready(&name, header))?;
+                let found = (SlotLayout::from_header(header), header.buffer_mask + 1);
+                let has_lane = header.lane_offset.load(Ordering::Acquire) != 0;
+                if let Some((layout, cap)) = requested {
+                    if (layout, cap) != found || urgent_lane != has_lane {
+                        return Err(InvalidParameters::new_err(format!(
+                            "Queue '{}' exists with element_size {} and capacity {}, which does \
+                             not match the requested element_size {}, capacity {} and layout \
+                             options",
+                            name, found.0.element_size, found.1, layout.element_size, cap
+                        )));
+                    }
+                }
+                // fmt derives element_size, and its mismatch is reported below.
+                let expected = [
+                    (
+                        "element_size",
+                        element_size.filter(|_| format.is_none()),
+                        found.0.element_size,
+                    ),
+                    ("capacity", capacity, found.1),
+                ];
+                let mismatches = expected
+                    .iter()
+                    .filter_map(|(field, expected, actual)| {
+                        expected
+                            .filter(|expected| expected != actual)
+                            .map(|expected| {
+                                format!("{} {} instead of the expected {}", field, actual, expected)
+                            })
+                    })
+                    .collect::<Vec<_>>();
+                if !mismatches.is_empty() {
+                    return Err(InvalidParameters::new_err(format!(
+                        "Queue '{}' has {}",
+                        name,
+                        mismatches.join(" and ")
+                    )));
+                }
+                found
+            }
+        };
+        if let Some(format) = format.as_ref().filter(|f| f.size() != layout.element_size) {
+            return Err(InvalidParameters::new_err(format!(
+                "Queue '{}' has elements of {} bytes, but records of fmt '{}' take {} bytes",
+                name,
+                layout.element_size,
+                fmt.unwrap(),
+                format.size()
+            )));
+        }
+
+        if skip_poisoned && !layout.owners {
+            return Err(PyValueError::new_err(
+                "skip_poisoned requires a queue created with track_owners",
+            ));
+        }
+
+        let meta = MetaLayout::from_header(layout.meta_flags, layout.meta_size);
+        let keyring = match (meta.cipher(), keys) {
+            (Some(cipher), Some(keys)) => Some(RwLock::new(Keyring::new(cipher, keys, key_id)?)),
+            (Some(_), None) => {
+                return Err(PyValueError::new_err(
+                    "keys required for an encrypted queue",
+                ))
+            }
+            (None, Some(_)) => {
+                return Err(PyValueError::new_err(
+                    "keys given but the queue is not encrypted",
+                ))
+            }
+            (None, None) => None,
         };
 
-        let shmem_wrapper = ShmemWrapper::new(shmem);
-        let buf_len = shmem_wrapper.len();
-        let buf_ptr = shmem_wrapper.as_ptr() as *mut MaybeUninit<u8>;
+        shmem_wrapper.touch();
+        let buf_len = shmem_wrapper.len() - offset;
+        let buf_ptr = unsafe { shmem_wrapper.as_ptr().add(offset) } as *mut MaybeUninit<u8>;
         let buf_slice = unsafe { std::slice::from_raw_parts_mut(buf_ptr, buf_len) };
 
         // Initialize (or attach to) the queue in the shared memory buffer.
         let queue =
-            unsafe { MpmcQueueOnBuffer::init_on_buffer(buf_slice, elem_size, cap, create)? };
+            unsafe { MpmcQueueOnBuffer::init_on_buffer(buf_slice, &layout, cap, initialize)? };
         let queue_static: MpmcQueueOnBuffer<'static> = unsafe { std::mem::transmute(queue) };
 
-        Ok(Self {
+        // Initialize (or attach to) the urgent lane placed after the main queue.
+        let lane_offset = if initialize && urgent_lane {
+            compute_lane_offset(&layout, cap)
+        } else {
+            queue_static.header().lane_offset.load(Ordering::Acquire)
+        };
+        let urgent = if lane_offset == 0 {
+            None
+        } else if lane_offset >= buf_len {
+            return Err(PyValueError::new_err(format!(
+                "urgent lane offset {} is out of bounds for shared memory '{}'",
+                lane_offset, name
+            )));
+        } else {
+            let lane_slice = unsafe {
+                std::slice::from_raw_parts_mut(buf_ptr.add(lane_offset), buf_len - lane_offset)
+            };
+            let lane = unsafe {
+                if initialize {
+                    MpmcQueueOnBuffer::init_on_buffer(
+                        lane_slice,
+                        &layout,
+                        urgent_capacity(cap),
+                        true,
+                    )?
+                } else {
+                    MpmcQueueOnBuffer::attach_on_buffer(lane_slice)?
+                }
+            };
+            if initialize {
+                queue_static
+                    .header()
+                    .lane_offset
+                    .store(lane_offset, Ordering::Release);
+            }
+            Some(unsafe {
+                std::mem::transmute::<MpmcQueueOnBuffer<'_>, MpmcQueueOnBuffer<'static>>(lane)
+            })
+        };
+
+        if initialize {
+            queue_static.header().ready.store(1, Ordering::Release);
+        }
+
+        if let (false, Some(fix)) = (initialize, audit_fix) {
+            let report = queue_static.audit(fix);
+            if !report.positions_valid || (!fix && !report.inconsistencies.is_empty()) {
+                return Err(PyRuntimeError::new_err(format!(
+                    "Queue '{}' failed the consistency audit: {}",
+                    name,
+                    describe_audit(&report)
+                )));
+            }
+        }
+
+        let registration = Registration::claim(queue_static.header(), roles);
+        let queue = Self {
+            name,
             shared_mem: Some(shmem_wrapper),
             queue: queue_static,
+            urgent,
+            meta,
+            keyring,
             closed: Arc::new(AtomicBool::new(false)),
-        })
+            cancelled: AtomicBool::new(false),
+            config: RwLock::new(Arc::new(config)),
+            config_generation: AtomicU64::new(0),
+            open_views: AtomicUsize::new(0),
+            #[cfg(unix)]
+            readiness: Default::default(),
+            poison,
+            skip_poisoned,
+            clock: clock.map_or(Clock::System, Clock::Manual),
+            stats: QueueStats::default(),
+            serializer,
+            format,
+            roles,
+            registration: Mutex::new(registration),
+            registered_in: AtomicU64::new(fork::generation()),
+        };
+        queue.handle_config()?;
+        Ok(queue)
+    }
+
+    /// Creates a new queue, failing if the name is taken.
+    ///
+    /// # Arguments
+    /// - `name` (str): Shared memory segment name.
+    /// - `element_size` (int): Size of each element in bytes.
+    /// - `capacity` (int): Number of slots, a power of two.
+    /// - `**options`: Any other constructor argument except `create` and `mode`.
+    ///
+    /// # Errors
+    /// Raises `AlreadyExists` if a segment named `name` exists, or the constructor's
+    /// errors otherwise.
+    #[classmethod]
+    #[pyo3(name = "create", signature = (name, element_size, capacity, **options))]
+    fn py_create<'py>(
+        cls: &Bound<'py, PyType>,
+        name: String,
+        element_size: usize,
+        capacity: usize,
+        options: Option<&Bound<'py, PyDict>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let options = mode_options(cls.py(), options, "create")?;
+        options.set_item("element_size", element_size)?;
+        options.set_item("capacity", capacity)?;
+        cls.call((name,), Some(&options))
+    }
+
+    /// Attaches to an existing queue, taking its layout from the header.
+    ///
+    /// # Arguments
+    /// - `name` (str): Shared memory segment name.
+    /// - `**options`: Any other constructor argument except `create` and `mode`; an
+    ///   `element_size` or `capacity` given asserts that the queue has it.
+    ///
+    /// # Errors
+    /// Raises `OSError` if no queue named `name` exists, `InvalidParameters` if it does
+    /// not have the expected `element_size` or `capacity`, or the constructor's errors
+    /// otherwise.
+    #[classmethod]
+    #[pyo3(name = "open", signature = (name, **options))]
+    fn py_open<'py>(
+        cls: &Bound<'py, PyType>,
+        name: String,
+        options: Option<&Bound<'py, PyDict>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let options = mode_options(cls.py(), options, "open")?;
+        cls.call((name,), Some(&options))
     }
 
     /// Checks whether the queue is active.
@@ -104,9 +786,21 @@ impl Queue {
     /// Raises `QueueClosed` if the queue has been marked closed.
     fn check_active(&self) -> PyResult<()> {
         if self.closed.load(Ordering::Relaxed) {
-            Err(PyOSError::new_err("Queue is closed"))
-        } else {
-            Ok(())
+            return Err(QueueClosed::new_err("Queue is closed"));
+        }
+        if self.registered_in.load(Ordering::Relaxed) != fork::generation() {
+            self.register_after_fork();
+        }
+        Ok(())
+    }
+
+    /// Registers a handle inherited through `fork()` for the current process, leaving
+    /// the entry of the parent alone.
+    fn register_after_fork(&self) {
+        let generation = fork::generation();
+        let mut registration = self.registration.lock().unwrap();
+        if self.registered_in.swap(generation, Ordering::Relaxed) != generation {
+            *registration = Registration::claim(self.queue.header(), self.roles);
         }
     }
 
@@ -116,32 +810,50 @@ impl Queue {
     /// becomes available or the optional `timeout` (in seconds) is exceeded.
     ///
     /// # Arguments
-    /// - `item` (bytes): The item to enqueue.
+    /// - `item` (Buffer): The item to enqueue: `bytes`, or any C-contiguous object
+    ///   supporting the buffer protocol, which is copied straight into the slot.
     /// - `timeout` (float, optional): Maximum time to wait.
+    /// - `headers` (bytes, optional): Headers stored with the message; requires `headers_size`.
+    /// - `deadline` (float, optional): Unix timestamp after which the message is stale;
+    ///   requires the `deadline` metadata field.
+    /// - `retries` (int, default=0): Times to wait for another `timeout` after the queue
+    ///   stayed full, sleeping a jittered backoff in between.
+    /// - `retry_backoff` (float, default=0.001): Base backoff in seconds, doubled on every
+    ///   retry.
     ///
     /// # Errors
-    /// Raises `QueueFull` if the queue remains full beyond the timeout.
-    #[pyo3(signature = (item, timeout=None))]
-    fn put(&self, item: Cow<[u8]>, timeout: Option<f64>) -> PyResult<()> {
+    /// Raises `QueueFull` if the queue remains full beyond the timeout and every retry, or
+    /// `QueueClosed` if it was shut down.
+    #[pyo3(signature = (
+        item,
+        timeout=None,
+        headers=None,
+        deadline=None,
+        retries=0,
+        retry_backoff=0.001,
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn put(
+        &self,
+        py: Python<'_>,
+        item: &Bound<'_, PyAny>,
+        timeout: Option<f64>,
+        headers: Option<Cow<[u8]>>,
+        deadline: Option<f64>,
+        retries: u32,
+        retry_backoff: f64,
+    ) -> PyResult<()> {
         self.check_active()?;
-        let start = Instant::now();
-
-        Python::with_gil(|py| {
-            py.allow_threads(|| loop {
-                match self.queue.enqueue(item.as_ref()) {
-                    Ok(_) => return Ok(()),
-                    Err(crate::mpmc_queue::MpmcQueueError::QueueFull) => {
-                        if let Some(t) = timeout {
-                            if start.elapsed().as_secs_f64() > t {
-                                return Err(Full::new_err("Queue is full"));
-                            }
-                        }
-                        std::thread::sleep(Duration::from_millis(1));
-                    }
-                    Err(e) => return Err(e.into()),
-                }
-            })
+        let headers = headers.as_deref();
+        self.meta.validate_headers(headers)?;
+        let deadline = self.meta.validate_deadline(deadline)?;
+        let retry = RetryPolicy::new(retries, retry_backoff)?;
+        let item = self.item(item)?;
+        self.add_tasks(1);
+        self.blocking(py, WaitOp::Put, &self.queue, timeout, retry, || {
+            self.try_put_item(&self.queue, &item, headers, deadline)
         })
+        .inspect_err(|_| self.finish_tasks(1))
     }
 
     /// Non-blocking put operation.
@@ -149,13 +861,157 @@ impl Queue {
     /// Attempts to enqueue `item` into the queue immediately.
     ///
     /// # Arguments
-    /// - `item` (bytes): The item to enqueue.
+    /// - `item` (Buffer): The item to enqueue: `bytes`, or any C-contiguous object
+    ///   supporting the buffer protocol, which is copied straight into the slot.
+    /// - `headers` (bytes, optional): Headers stored with the message; requires `headers_size`.
+    /// - `deadline` (float, optional): Unix timestamp after which the message is stale;
+    ///   requires the `deadline` metadata field.
+    ///
+    /// # Errors
+    /// Raises `QueueFull` if the queue is full, or `QueueClosed` if it was shut down.
+    #[pyo3(signature = (item, headers=None, deadline=None))]
+    fn put_nowait(
+        &self,
+        py: Python<'_>,
+        item: &Bound<'_, PyAny>,
+        headers: Option<Cow<[u8]>>,
+        deadline: Option<f64>,
+    ) -> PyResult<()> {
+        self.check_active()?;
+        if self.shut_down() {
+            return Err(QueueClosed::new_err(SHUT_DOWN));
+        }
+        let headers = headers.as_deref();
+        self.meta.validate_headers(headers)?;
+        let deadline = self.meta.validate_deadline(deadline)?;
+        let item = self.item(item)?;
+        self.add_tasks(1);
+        py.allow_threads(|| self.try_put_item(&self.queue, &item, headers, deadline))
+            .inspect_err(|_| self.finish_tasks(1))?;
+        Ok(())
+    }
+
+    /// Blocking batch put operation.
+    ///
+    /// Enqueues every item of `items` in order, reserving as many slots as are free
+    /// in one step and copying the items into them under a single release of the GIL,
+    /// which avoids the per-call overhead of `put` for small messages. Messages carry
+    /// no headers or deadline.
+    ///
+    /// If the timeout expires mid-batch, the items enqueued so far stay enqueued and
+    /// their count is returned instead of raising, so producers can resume from the
+    /// remainder:
+    ///
+    /// ```python
+    /// sent = 0
+    /// while sent < len(items):
+    ///     sent += queue.put_many(items[sent:], timeout=1.0)
+    /// ```
+    ///
+    /// # Arguments
+    /// - `items` (Sequence[bytes]): The items to enqueue.
+    /// - `timeout` (float, optional): Maximum time to wait for free slots.
+    /// - `retries` (int, default=0): Times to wait for another `timeout` after the queue
+    ///   stayed full, sleeping a jittered backoff in between.
+    /// - `retry_backoff` (float, default=0.001): Base backoff in seconds, doubled on every
+    ///   retry.
+    ///
+    /// # Returns
+    /// - (int): The number of leading items enqueued, less than `len(items)` only if
+    ///   the queue stayed full beyond the timeout and every retry.
+    ///
+    /// # Errors
+    /// Raises `ValueError` if any item has the wrong size, before any is enqueued.
+    #[pyo3(signature = (items, timeout=None, retries=0, retry_backoff=0.001))]
+    fn put_many(
+        &self,
+        py: Python<'_>,
+        items: Vec<Bound<'_, PyAny>>,
+        timeout: Option<f64>,
+        retries: u32,
+        retry_backoff: f64,
+    ) -> PyResult<usize> {
+        self.check_active()?;
+        let items = items
+            .iter()
+            .map(|item| self.item(item))
+            .collect::<PyResult<Vec<_>>>()?;
+        for item in items.iter().filter(|item| !item.framed) {
+            self.queue.validate_enqueue_src(item.data.as_slice())?;
+        }
+        let retry = RetryPolicy::new(retries, retry_backoff)?;
+        let mut done = 0;
+        self.add_tasks(items.len());
+        let result = self.blocking(py, WaitOp::Put, &self.queue, timeout, retry, || {
+            while done < items.len() {
+                done += self.try_put_many(&items[done..])?;
+            }
+            Ok(())
+        });
+        self.finish_tasks(items.len() - done);
+        match result {
+            Err(e) if e.is_instance_of::<Full>(py) => Ok(done),
+            result => result.map(|_| done),
+        }
+    }
+
+    /// Blocking put operation on the urgent lane.
+    ///
+    /// Enqueues `item` into the urgent lane, which `get()` drains before the main
+    /// lane, so control messages bypass a data backlog. Metadata sequences are
+    /// numbered per lane.
+    ///
+    /// # Arguments
+    /// - `item` (Buffer): The item to enqueue: `bytes`, or any C-contiguous object
+    ///   supporting the buffer protocol, which is copied straight into the slot.
+    /// - `timeout` (float, optional): Maximum time to wait.
+    /// - `headers` (bytes, optional): Headers stored with the message; requires `headers_size`.
+    /// - `deadline` (float, optional): Unix timestamp after which the message is stale;
+    ///   requires the `deadline` metadata field.
+    /// - `retries` (int, default=0): Times to wait for another `timeout` after the lane
+    ///   stayed full, sleeping a jittered backoff in between.
+    /// - `retry_backoff` (float, default=0.001): Base backoff in seconds, doubled on every
+    ///   retry.
     ///
     /// # Errors
-    /// Raises `QueueFull` if the queue is full.
-    fn put_nowait(&self, item: Cow<[u8]>) -> PyResult<()> {
+    /// Raises `ValueError` if the queue has no urgent lane, or `QueueFull` if the
+    /// lane remains full beyond the timeout and every retry.
+    #[pyo3(signature = (
+        item,
+        timeout=None,
+        headers=None,
+        deadline=None,
+        retries=0,
+        retry_backoff=0.001,
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn put_urgent(
+        &self,
+        py: Python<'_>,
+        item: &Bound<'_, PyAny>,
+        timeout: Option<f64>,
+        headers: Option<Cow<[u8]>>,
+        deadline: Option<f64>,
+        retries: u32,
+        retry_backoff: f64,
+    ) -> PyResult<()> {
         self.check_active()?;
-        Python::with_gil(|py| py.allow_threads(|| self.queue.enqueue(item.as_ref())))?;
+        let urgent = self
+            .urgent
+            .as_ref()
+            .ok_or_else(|| PyValueError::new_err("the queue has no urgent lane"))?;
+        let headers = headers.as_deref();
+        self.meta.validate_headers(headers)?;
+        let deadline = self.meta.validate_deadline(deadline)?;
+        let retry = RetryPolicy::new(retries, retry_backoff)?;
+        let item = self.item(item)?;
+        self.add_tasks(1);
+        self.blocking(py, WaitOp::Put, urgent, timeout, retry, || {
+            self.try_put_item(urgent, &item, headers, deadline)
+        })
+        .inspect_err(|_| self.finish_tasks(1))?;
+        // Blocked consumers wait on the main lane, which covers both lanes.
+        self.queue.header().not_empty.notify();
         Ok(())
     }
 
@@ -167,12 +1023,24 @@ impl Queue {
     /// - (bytes): The dequeued item.
     ///
     /// # Errors
-    /// Raises `QueueEmpty` if the queue is empty.
-    fn get_nowait(&self) -> PyResult<Vec<u8>> {
+    /// Raises `QueueEmpty` if the queue is empty, `QueueClosed` if it is empty and was
+    /// shut down, or `CorruptMessage` if the item fails its checksum.
+    fn get_nowait<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
         self.check_active()?;
         let mut buf = vec![0u8; self.queue.header().element_size];
-        Python::with_gil(|py| py.allow_threads(|| self.queue.dequeue(&mut buf)))?;
-        Ok(buf)
+        let drop_expired = self.handle_config()?.drop_expired;
+        // Read first, so that messages put before the shutdown are still seen.
+        let shut_down = self.shut_down();
+        py.allow_threads(|| {
+            let prefix = self.try_get(&mut buf, drop_expired).map_err(|e| match e {
+                MpmcQueueError::Capacity(CapacityError::Empty) if shut_down => {
+                    QueueClosed::new_err(SHUT_DOWN)
+                }
+                e => PyErr::from(e),
+            })?;
+            self.open(&prefix, &mut buf)
+        })?;
+        self.decode(py, &buf)
     }
 
     /// Blocking get operation.
@@ -182,89 +1050,2344 @@ impl Queue {
     ///
     /// # Arguments
     /// - `timeout` (float, optional): Maximum time to wait.
+    /// - `retries` (int, default=0): Times to wait for another `timeout` after the queue
+    ///   stayed empty, sleeping a jittered backoff in between.
+    /// - `retry_backoff` (float, default=0.001): Base backoff in seconds, doubled on every
+    ///   retry.
     ///
     /// # Returns
     /// - (bytes): The dequeued item.
     ///
     /// # Errors
-    /// Raises `QueueEmpty` if no item is available before the timeout.
-    #[pyo3(signature = (timeout=None))]
-    fn get(&self, timeout: Option<f64>) -> PyResult<Vec<u8>> {
+    /// Raises `QueueEmpty` if no item is available before the timeout and every retry,
+    /// `QueueClosed` once the queue was shut down and drained, or `CorruptMessage` if the
+    /// item fails its checksum.
+    #[pyo3(signature = (timeout=None, retries=0, retry_backoff=0.001))]
+    fn get<'py>(
+        &self,
+        py: Python<'py>,
+        timeout: Option<f64>,
+        retries: u32,
+        retry_backoff: f64,
+    ) -> PyResult<Bound<'py, PyAny>> {
         self.check_active()?;
-        let start = Instant::now();
+        let retry = RetryPolicy::new(retries, retry_backoff)?;
+        let drop_expired = self.handle_config()?.drop_expired;
         let mut buf = vec![0u8; self.queue.header().element_size];
+        let payload = self.blocking_then(
+            py,
+            WaitOp::Get,
+            &self.queue,
+            timeout,
+            retry,
+            || self.try_get_owned(&mut buf, drop_expired),
+            |(prefix, mut payload)| {
+                self.open(&prefix, &mut payload)?;
+                Ok(payload)
+            },
+        )?;
+        self.decode(py, &payload)
+    }
 
-        Python::with_gil(|py| {
-            py.allow_threads(|| loop {
-                match self.queue.dequeue(&mut buf) {
-                    Ok(_) => return Ok(buf),
-                    Err(crate::mpmc_queue::MpmcQueueError::QueueEmpty) => {
-                        if let Some(t) = timeout {
-                            if start.elapsed().as_secs_f64() > t {
-                                return Err(Empty::new_err("Queue is empty"));
-                            }
-                        }
-                        std::thread::sleep(Duration::from_millis(1));
-                    }
-                    Err(e) => return Err(e.into()),
-                }
-            })
-        })
+    /// Blocking get operation into a caller-provided buffer.
+    ///
+    /// Behaves like `get`, but writes the item into `buffer`, which can be any writable,
+    /// C-contiguous object supporting the buffer protocol, e.g. a `bytearray`, a numpy
+    /// array or an `mmap`, so consumers reuse one buffer instead of allocating a new
+    /// `bytes` object per message.
+    ///
+    /// # Arguments
+    /// - `buffer` (Buffer): Destination of the item, at least `element_size` bytes long.
+    /// - `timeout` (float, optional): Maximum time to wait.
+    /// - `retries` (int, default=0): Times to wait for another `timeout` after the queue
+    ///   stayed empty, sleeping a jittered backoff in between.
+    /// - `retry_backoff` (float, default=0.001): Base backoff in seconds, doubled on every
+    ///   retry.
+    ///
+    /// # Returns
+    /// - (int): The number of bytes written, always `element_size`.
+    ///
+    /// # Errors
+    /// Raises `ValueError` if `buffer` is too small, `BufferError` if it is read-only,
+    /// `QueueEmpty` if no item is available before the timeout and every retry, or
+    /// `QueueClosed` once the queue was shut down and drained.
+    #[pyo3(signature = (buffer, timeout=None, retries=0, retry_backoff=0.001))]
+    fn get_into(
+        &self,
+        py: Python<'_>,
+        buffer: &Bound<'_, PyAny>,
+        timeout: Option<f64>,
+        retries: u32,
+        retry_backoff: f64,
+    ) -> PyResult<usize> {
+        self.check_active()?;
+        let retry = RetryPolicy::new(retries, retry_backoff)?;
+        let drop_expired = self.handle_config()?.drop_expired;
+        let element_size = self.queue.header().element_size;
+        let mut buffer = ByteBuffer::get(buffer, true)?;
+        let len = buffer.as_slice().len();
+        let dst = buffer
+            .as_mut_slice()
+            .get_mut(..element_size)
+            .ok_or_else(|| {
+                PyValueError::new_err(format!(
+                    "Buffer of {} bytes is smaller than the element size of {} bytes",
+                    len, element_size
+                ))
+            })?;
+        let prefix = self.blocking(py, WaitOp::Get, &self.queue, timeout, retry, || {
+            self.try_get(dst, drop_expired)
+        })?;
+        py.allow_threads(|| self.open(&prefix, dst))?;
+        Ok(element_size)
     }
 
-    /// Returns the element size in bytes.
-    #[getter]
-    fn element_size(&self) -> PyResult<usize> {
+    /// Blocking get operation returning the payload together with its metadata.
+    ///
+    /// Behaves like `get`, but returns a `Message` whose metadata fields are populated
+    /// according to the `metadata` and `headers_size` options given at creation.
+    ///
+    /// # Arguments
+    /// - `timeout` (float, optional): Maximum time to wait.
+    /// - `retries` (int, default=0): Times to wait for another `timeout` after the queue
+    ///   stayed empty, sleeping a jittered backoff in between.
+    /// - `retry_backoff` (float, default=0.001): Base backoff in seconds, doubled on every
+    ///   retry.
+    ///
+    /// # Returns
+    /// - (Message): The dequeued item and its metadata.
+    ///
+    /// # Errors
+    /// Raises `QueueEmpty` if no item is available before the timeout and every retry, or
+    /// `QueueClosed` once the queue was shut down and drained.
+    #[pyo3(signature = (timeout=None, retries=0, retry_backoff=0.001))]
+    fn get_with_meta(
+        &self,
+        py: Python<'_>,
+        timeout: Option<f64>,
+        retries: u32,
+        retry_backoff: f64,
+    ) -> PyResult<Message> {
         self.check_active()?;
-        Ok(self.queue.header().element_size)
+        let retry = RetryPolicy::new(retries, retry_backoff)?;
+        let drop_expired = self.handle_config()?.drop_expired;
+        let mut buf = vec![0u8; self.queue.header().element_size];
+        self.blocking_then(
+            py,
+            WaitOp::Get,
+            &self.queue,
+            timeout,
+            retry,
+            || self.try_get_owned(&mut buf, drop_expired),
+            |(prefix, mut payload)| {
+                self.open(&prefix, &mut payload)?;
+                Ok(self.meta.read(&prefix, payload))
+            },
+        )
     }
 
-    /// Returns the maximum number of elements in the queue.
-    #[getter]
-    fn maxsize(&self) -> PyResult<usize> {
+    /// Blocking batch get operation.
+    ///
+    /// Waits like `get` until at least one item is available, then drains up to
+    /// `max_items` items in one call, reserving runs of published slots in one step
+    /// and copying them out under a single release of the GIL.
+    ///
+    /// # Arguments
+    /// - `max_items` (int): Maximum number of items to return.
+    /// - `timeout` (float, optional): Maximum time to wait for the first item.
+    /// - `retries` (int, default=0): Times to wait for another `timeout` after the queue
+    ///   stayed empty, sleeping a jittered backoff in between.
+    /// - `retry_backoff` (float, default=0.001): Base backoff in seconds, doubled on every
+    ///   retry.
+    ///
+    /// # Returns
+    /// - (list[bytes]): Between one and `max_items` items, urgent ones first.
+    ///
+    /// # Errors
+    /// Raises `ValueError` if `max_items` is zero, `QueueEmpty` if no item is available
+    /// before the timeout and every retry, or `DecryptionError` if an encrypted item
+    /// fails authentication or `CorruptMessage` if an item fails its checksum; the other
+    /// items of the batch are lost with it.
+    #[pyo3(signature = (max_items, timeout=None, retries=0, retry_backoff=0.001))]
+    fn get_many<'py>(
+        &self,
+        py: Python<'py>,
+        max_items: usize,
+        timeout: Option<f64>,
+        retries: u32,
+        retry_backoff: f64,
+    ) -> PyResult<Vec<Bound<'py, PyAny>>> {
         self.check_active()?;
-        Ok(self.queue.header().buffer_mask + 1)
+        if max_items == 0 {
+            return Err(PyValueError::new_err("max_items must be at least 1"));
+        }
+        let retry = RetryPolicy::new(retries, retry_backoff)?;
+        let drop_expired = self.handle_config()?.drop_expired;
+        let batch = self.blocking_then(
+            py,
+            WaitOp::Get,
+            &self.queue,
+            timeout,
+            retry,
+            || self.try_get_many(max_items, drop_expired),
+            |batch| self.open_batch(batch),
+        )?;
+        batch
+            .iter()
+            .map(|payload| self.decode(py, payload))
+            .collect()
     }
 
-    /// Returns the number of elements in the queue.
-    fn __len__(&self) -> PyResult<usize> {
+    /// Dequeues every item queued when the call starts, without waiting.
+    ///
+    /// Runs as one loop under a single release of the GIL; items enqueued while it runs
+    /// may be left for later, so that busy producers cannot keep it going forever.
+    ///
+    /// # Returns
+    /// - (list[bytes]): The items, urgent ones first; empty if the queue was empty.
+    ///
+    /// # Errors
+    /// Raises `DecryptionError` if an encrypted item fails authentication, or
+    /// `CorruptMessage` if an item fails its checksum; the other drained items are lost
+    /// with it.
+    fn drain<'py>(&self, py: Python<'py>) -> PyResult<Vec<Bound<'py, PyAny>>> {
         self.check_active()?;
-        let head = self.queue.header().dequeue_pos.load(Ordering::Acquire);
-        let tail = self.queue.header().enqueue_pos.load(Ordering::Acquire);
-        Ok(tail.saturating_sub(head))
+        let drop_expired = self.handle_config()?.drop_expired;
+        let limit = self.qsize()?;
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+        let batch = py.allow_threads(|| match self.try_get_many(limit, drop_expired) {
+            Ok(batch) => self.open_batch(batch),
+            Err(MpmcQueueError::Capacity(CapacityError::Empty)) => Ok(Vec::new()),
+            Err(e) => Err(e.into()),
+        })?;
+        batch
+            .iter()
+            .map(|payload| self.decode(py, payload))
+            .collect()
     }
 
-    /// Returns whether the queue is not empty.
-    fn __bool__(&self) -> PyResult<bool> {
-        Ok(self.__len__()? > 0)
+    /// Discards every item queued when the call starts, without waiting or copying them
+    /// out.
+    ///
+    /// Like `drain()`, runs under a single release of the GIL and may leave items enqueued
+    /// while it runs.
+    ///
+    /// # Returns
+    /// - (int): Number of items discarded.
+    fn clear(&self, py: Python<'_>) -> PyResult<usize> {
+        self.check_active()?;
+        let limit = self.qsize()?;
+        Ok(py.allow_threads(|| {
+            let mut discarded = 0;
+            for lane in self.urgent.iter().chain(std::iter::once(&self.queue)) {
+                while discarded < limit {
+                    match lane.dequeue_batch_with(limit - discarded, |_, _, _| {}) {
+                        Ok(consumed) => discarded += consumed,
+                        Err(_) => break,
+                    }
+                }
+            }
+            self.finish_tasks(discarded);
+            discarded
+        }))
     }
 
-    /// Returns whether the queue is full.
-    fn full(&self) -> PyResult<bool> {
-        Ok(self.__len__()? >= (self.queue.header().buffer_mask + 1))
+    /// Blocking put operation for numpy arrays.
+    ///
+    /// Copies the data of `array` straight into a slot, after a small header carrying
+    /// its dtype and shape, so that `get_array()` rebuilds the array without pickling
+    /// or an intermediate `bytes` object. Arrays that are not C-contiguous are made
+    /// contiguous first. The header and the data must fit in `element_size`; the header
+    /// takes 4 bytes, plus `len(array.dtype.str)`, plus 8 bytes per dimension, rounded
+    /// up to a multiple of 16.
+    ///
+    /// # Arguments
+    /// - `array` (numpy.ndarray): The array to enqueue.
+    /// - `timeout` (float, optional): Maximum time to wait.
+    /// - `headers` (bytes, optional): Headers stored with the message; requires `headers_size`.
+    /// - `deadline` (float, optional): Unix timestamp after which the message is stale;
+    ///   requires the `deadline` metadata field.
+    /// - `retries` (int, default=0): Times to wait for another `timeout` after the queue
+    ///   stayed full, sleeping a jittered backoff in between.
+    /// - `retry_backoff` (float, default=0.001): Base backoff in seconds, doubled on every
+    ///   retry.
+    ///
+    /// # Errors
+    /// Raises `ValueError` if the array does not fit in a slot or has a dtype of Python
+    /// objects or named fields, `QueueFull` if the queue remains full beyond the timeout
+    /// and every retry, or `QueueClosed` if it was shut down.
+    #[pyo3(signature = (
+        array,
+        timeout=None,
+        headers=None,
+        deadline=None,
+        retries=0,
+        retry_backoff=0.001,
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn put_array(
+        &self,
+        py: Python<'_>,
+        array: &Bound<'_, PyAny>,
+        timeout: Option<f64>,
+        headers: Option<Cow<[u8]>>,
+        deadline: Option<f64>,
+        retries: u32,
+        retry_backoff: f64,
+    ) -> PyResult<()> {
+        self.check_active()?;
+        let headers = headers.as_deref();
+        self.meta.validate_headers(headers)?;
+        let deadline = self.meta.validate_deadline(deadline)?;
+        let retry = RetryPolicy::new(retries, retry_backoff)?;
+        let (header, buffer) = array::export(array)?;
+        let offset = header.data_offset();
+        let element_size = self.queue.header().element_size;
+        if offset + buffer.len_bytes() > element_size {
+            return Err(PyValueError::new_err(format!(
+                "Array of {} bytes with a {}-byte header does not fit in elements of {} bytes",
+                buffer.len_bytes(),
+                offset,
+                element_size
+            )));
+        }
+        // The buffer is C-contiguous and stays exported until it is dropped.
+        let data = unsafe {
+            std::slice::from_raw_parts(buffer.buf_ptr() as *const u8, buffer.len_bytes())
+        };
+        self.add_tasks(1);
+        self.blocking(py, WaitOp::Put, &self.queue, timeout, retry, || {
+            self.try_put_with(&self.queue, headers, deadline, |payload| {
+                header.write(payload);
+                let (dst, padding) = payload[offset..].split_at_mut(data.len());
+                copy::write_payload(dst, data);
+                padding.fill(0);
+            })
+        })
+        .inspect_err(|_| self.finish_tasks(1))
     }
 
-    /// Returns whether the queue is empty.
-    fn empty(&self) -> PyResult<bool> {
-        Ok(self.__len__()? == 0)
+    /// Blocking get operation for messages put by `put_array()`.
+    ///
+    /// Dequeues straight into a buffer owned by the returned array, which is writable
+    /// and has the dtype and shape of the array that was put.
+    ///
+    /// # Arguments
+    /// - `timeout` (float, optional): Maximum time to wait.
+    /// - `retries` (int, default=0): Times to wait for another `timeout` after the queue
+    ///   stayed empty, sleeping a jittered backoff in between.
+    /// - `retry_backoff` (float, default=0.001): Base backoff in seconds, doubled on every
+    ///   retry.
+    ///
+    /// # Returns
+    /// - (numpy.ndarray): The dequeued array.
+    ///
+    /// # Errors
+    /// Raises `ValueError` if the dequeued message was not put by `put_array()`, in which
+    /// case it is lost, `QueueEmpty` if no item is available before the timeout and every
+    /// retry, or `QueueClosed` once the queue was shut down and drained.
+    #[pyo3(signature = (timeout=None, retries=0, retry_backoff=0.001))]
+    fn get_array<'py>(
+        &self,
+        py: Python<'py>,
+        timeout: Option<f64>,
+        retries: u32,
+        retry_backoff: f64,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        self.check_active()?;
+        let retry = RetryPolicy::new(retries, retry_backoff)?;
+        let drop_expired = self.handle_config()?.drop_expired;
+        let payload = PyByteArray::new_with(py, self.queue.header().element_size, |_| Ok(()))?;
+        // Nothing else references the new bytearray while its contents are borrowed.
+        let dst = unsafe { payload.as_bytes_mut() };
+        let prefix = self.blocking(py, WaitOp::Get, &self.queue, timeout, retry, || {
+            self.try_get(dst, drop_expired)
+        })?;
+        py.allow_threads(|| self.open(&prefix, dst))?;
+        array::import(&payload)
     }
 
-    /// Closes the queue, releasing the shared memory segment.
-    fn close(&mut self) {
-        if self.closed.load(Ordering::Relaxed) {
-            return;
+    /// Blocking put operation for payloads of any size up to the capacity.
+    ///
+    /// Splits `item` into chunks of `element_size - 8` bytes, each behind an 8-byte
+    /// header, and enqueues them in consecutive slots reserved in one step, so that
+    /// chunks of concurrent producers never interleave. Waits until enough slots are
+    /// free for the whole payload. Every consumer of a queue carrying streamed
+    /// payloads must use `get_stream()`, since other gets take single chunks.
+    ///
+    /// # Arguments
+    /// - `item` (bytes): The payload, any C-contiguous buffer.
+    /// - `timeout` (float, optional): Maximum time to wait.
+    /// - `retries` (int, default=0): Times to wait for another `timeout` after the queue
+    ///   stayed full, sleeping a jittered backoff in between.
+    /// - `retry_backoff` (float, default=0.001): Base backoff in seconds, doubled on every
+    ///   retry.
+    ///
+    /// # Errors
+    /// Raises `ValueError` if the payload needs more slots than the capacity, or if
+    /// elements are too small for the chunk header, `QueueFull` if not enough slots
+    /// are free beyond the timeout and every retry, or `QueueClosed` if the queue was
+    /// shut down.
+    #[pyo3(signature = (item, timeout=None, retries=0, retry_backoff=0.001))]
+    fn put_stream(
+        &self,
+        py: Python<'_>,
+        item: &Bound<'_, PyAny>,
+        timeout: Option<f64>,
+        retries: u32,
+        retry_backoff: f64,
+    ) -> PyResult<()> {
+        self.check_active()?;
+        let retry = RetryPolicy::new(retries, retry_backoff)?;
+        let data = ByteBuffer::get(item, false)?;
+        let data = data.as_slice();
+        let header = self.queue.header();
+        let chunk_size = header.element_size.saturating_sub(stream::HEADER);
+        if chunk_size == 0 {
+            return Err(PyValueError::new_err(format!(
+                "Streaming requires elements larger than the {}-byte chunk header",
+                stream::HEADER
+            )));
         }
-        self.closed.store(true, Ordering::Relaxed);
-        self.shared_mem.take();
+        let count = data.len().div_ceil(chunk_size).max(1);
+        if count > header.buffer_mask + 1 {
+            return Err(PyValueError::new_err(format!(
+                "Payload of {} bytes needs {} slots, more than the capacity of {}",
+                data.len(),
+                count,
+                header.buffer_mask + 1
+            )));
+        }
+        let chunks: Vec<&[u8]> = if data.is_empty() {
+            vec![data]
+        } else {
+            data.chunks(chunk_size).collect()
+        };
+        self.add_tasks(1);
+        self.blocking(py, WaitOp::Put, &self.queue, timeout, retry, || {
+            self.try_put_run(&chunks)
+        })
+        .inspect_err(|_| self.finish_tasks(1))
     }
-}
 
-impl Drop for Queue {
-    fn drop(&mut self) {
-        if self.closed.load(Ordering::Relaxed) {
-            return;
+    /// Blocking get operation for payloads put by `put_stream()`.
+    ///
+    /// Dequeues every chunk of the next payload in one step and reassembles it. Reads
+    /// the main lane only.
+    ///
+    /// # Arguments
+    /// - `timeout` (float, optional): Maximum time to wait.
+    /// - `retries` (int, default=0): Times to wait for another `timeout` after the queue
+    ///   stayed empty, sleeping a jittered backoff in between.
+    /// - `retry_backoff` (float, default=0.001): Base backoff in seconds, doubled on every
+    ///   retry.
+    ///
+    /// # Returns
+    /// - (bytes): The payload.
+    ///
+    /// # Errors
+    /// Raises `ValueError` if the dequeued message was not put by `put_stream()`, in
+    /// which case it is lost, `DecryptionError` if a chunk fails authentication,
+    /// `QueueEmpty` if no complete payload is available before the timeout and every
+    /// retry, or `QueueClosed` once the queue was shut down and drained.
+    #[pyo3(signature = (timeout=None, retries=0, retry_backoff=0.001))]
+    fn get_stream<'py>(
+        &self,
+        py: Python<'py>,
+        timeout: Option<f64>,
+        retries: u32,
+        retry_backoff: f64,
+    ) -> PyResult<Bound<'py, PyBytes>> {
+        self.check_active()?;
+        let retry = RetryPolicy::new(retries, retry_backoff)?;
+        let mut chunks = self.blocking(py, WaitOp::Get, &self.queue, timeout, retry, || {
+            self.try_get_run()
+        })?;
+        py.allow_threads(|| {
+            chunks
+                .iter_mut()
+                .try_for_each(|(prefix, payload)| self.open(prefix, payload))
+        })?;
+        let count = chunks.len();
+        let data = chunks
+            .iter()
+            .enumerate()
+            .map(|(i, (_, payload))| stream::read_chunk(payload, i, count))
+            .collect::<PyResult<Vec<_>>>()?;
+        PyBytes::new_with(py, data.iter().map(|chunk| chunk.len()).sum(), |dst| {
+            let mut offset = 0;
+            for chunk in &data {
+                dst[offset..offset + chunk.len()].copy_from_slice(chunk);
+                offset += chunk.len();
+            }
+            Ok(())
+        })
+    }
+
+    /// Blocking put operation for PyTorch tensors.
+    ///
+    /// Enqueues only a handle to the storage of `tensor`, so that `get_tensor()` rebuilds
+    /// a tensor sharing its memory instead of a copy. CPU tensors are moved into shared
+    /// memory first unless they already are; CUDA tensors travel as CUDA IPC handles, and
+    /// the producer must keep them alive until every consumer is done with them. Handles
+    /// take a few hundred bytes, plus 6 bytes of framing.
+    ///
+    /// Between processes that were not forked from each other, CPU tensors need the
+    /// `file_system` sharing strategy of `torch.multiprocessing`.
+    ///
+    /// # Arguments
+    /// - `tensor` (torch.Tensor): The tensor to enqueue.
+    /// - `timeout` (float, optional): Maximum time to wait.
+    /// - `headers` (bytes, optional): Headers stored with the message; requires `headers_size`.
+    /// - `deadline` (float, optional): Unix timestamp after which the message is stale;
+    ///   requires the `deadline` metadata field.
+    /// - `retries` (int, default=0): Times to wait for another `timeout` after the queue
+    ///   stayed full, sleeping a jittered backoff in between.
+    /// - `retry_backoff` (float, default=0.001): Base backoff in seconds, doubled on every
+    ///   retry.
+    ///
+    /// # Errors
+    /// Raises `ImportError` if PyTorch is not installed, `TypeError` if `tensor` is not a
+    /// tensor, `ValueError` if its handle does not fit in a slot, `QueueFull` if the queue
+    /// remains full beyond the timeout and every retry, or `QueueClosed` if it was shut
+    /// down.
+    #[pyo3(signature = (
+        tensor,
+        timeout=None,
+        headers=None,
+        deadline=None,
+        retries=0,
+        retry_backoff=0.001,
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn put_tensor(
+        &self,
+        py: Python<'_>,
+        tensor: &Bound<'_, PyAny>,
+        timeout: Option<f64>,
+        headers: Option<Cow<[u8]>>,
+        deadline: Option<f64>,
+        retries: u32,
+        retry_backoff: f64,
+    ) -> PyResult<()> {
+        self.check_active()?;
+        let headers = headers.as_deref();
+        self.meta.validate_headers(headers)?;
+        let deadline = self.meta.validate_deadline(deadline)?;
+        let retry = RetryPolicy::new(retries, retry_backoff)?;
+        let handle = tensor::export(tensor)?;
+        let handle = handle.as_slice();
+        let element_size = self.queue.header().element_size;
+        if tensor::HEADER + handle.len() > element_size {
+            return Err(PyValueError::new_err(format!(
+                "Tensor handle of {} bytes with {} bytes of framing does not fit in elements \
+                 of {} bytes",
+                handle.len(),
+                tensor::HEADER,
+                element_size
+            )));
+        }
+        self.add_tasks(1);
+        self.blocking(py, WaitOp::Put, &self.queue, timeout, retry, || {
+            self.try_put_with(&self.queue, headers, deadline, |payload| {
+                tensor::write(payload, handle)
+            })
+        })
+        .inspect_err(|_| self.finish_tasks(1))
+    }
+
+    /// Blocking get operation for messages put by `put_tensor()`.
+    ///
+    /// # Arguments
+    /// - `timeout` (float, optional): Maximum time to wait.
+    /// - `retries` (int, default=0): Times to wait for another `timeout` after the queue
+    ///   stayed empty, sleeping a jittered backoff in between.
+    /// - `retry_backoff` (float, default=0.001): Base backoff in seconds, doubled on every
+    ///   retry.
+    ///
+    /// # Returns
+    /// - (torch.Tensor): A tensor sharing the memory of the tensor that was put.
+    ///
+    /// # Errors
+    /// Raises `ValueError` if the dequeued message was not put by `put_tensor()`, in
+    /// which case it is lost, `ImportError` if PyTorch is not installed, `QueueEmpty` if
+    /// no item is available before the timeout and every retry, or `QueueClosed` once the
+    /// queue was shut down and drained.
+    #[pyo3(signature = (timeout=None, retries=0, retry_backoff=0.001))]
+    fn get_tensor<'py>(
+        &self,
+        py: Python<'py>,
+        timeout: Option<f64>,
+        retries: u32,
+        retry_backoff: f64,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        self.check_active()?;
+        let retry = RetryPolicy::new(retries, retry_backoff)?;
+        let drop_expired = self.handle_config()?.drop_expired;
+        let mut payload = vec![0u8; self.queue.header().element_size];
+        let prefix = self.blocking(py, WaitOp::Get, &self.queue, timeout, retry, || {
+            self.try_get(&mut payload, drop_expired)
+        })?;
+        py.allow_threads(|| self.open(&prefix, &mut payload))?;
+        tensor::import(py, &payload)
+    }
+
+    /// Blocking zero-copy put operation.
+    ///
+    /// Reserves a slot like `put`, but instead of copying an item in returns a writable
+    /// `SlotView` over its payload, so producers can serialize straight into shared
+    /// memory. The message reaches consumers once it is committed:
+    ///
+    /// ```python
+    /// buf = queue.reserve()
+    /// buf[:] = payload
+    /// queue.commit(buf)
+    /// ```
+    ///
+    /// Consumers cannot get past an uncommitted slot, so commit promptly. A view that is
+    /// garbage-collected uncommitted is published with whatever it holds.
+    ///
+    /// # Arguments
+    /// - `timeout` (float, optional): Maximum time to wait.
+    /// - `headers` (bytes, optional): Headers stored with the message; requires `headers_size`.
+    /// - `deadline` (float, optional): Unix timestamp after which the message is stale;
+    ///   requires the `deadline` metadata field.
+    /// - `retries` (int, default=0): Times to wait for another `timeout` after the queue
+    ///   stayed full, sleeping a jittered backoff in between.
+    /// - `retry_backoff` (float, default=0.001): Base backoff in seconds, doubled on every
+    ///   retry.
+    ///
+    /// # Returns
+    /// - (SlotView): The reserved payload.
+    ///
+    /// # Errors
+    /// Raises `ValueError` on encrypted queues, whose payloads are sealed before they
+    /// reach shared memory, or `QueueFull` if the queue remains full beyond the timeout
+    /// and every retry.
+    #[pyo3(signature = (
+        timeout=None,
+        headers=None,
+        deadline=None,
+        retries=0,
+        retry_backoff=0.001,
+    ))]
+    fn reserve(
+        slf: &Bound<'_, Self>,
+        timeout: Option<f64>,
+        headers: Option<Cow<[u8]>>,
+        deadline: Option<f64>,
+        retries: u32,
+        retry_backoff: f64,
+    ) -> PyResult<SlotView> {
+        let guard = slf.borrow();
+        let this: &Queue = &guard;
+        this.check_active()?;
+        if this.keyring.is_some() {
+            return Err(PyValueError::new_err(
+                "zero-copy puts are not available on encrypted queues",
+            ));
+        }
+        let headers = headers.as_deref();
+        this.meta.validate_headers(headers)?;
+        let deadline = this.meta.validate_deadline(deadline)?;
+        let retry = RetryPolicy::new(retries, retry_backoff)?;
+        this.add_tasks(1);
+        let (pos, slot) = this
+            .blocking(slf.py(), WaitOp::Put, &this.queue, timeout, retry, || {
+                this.try_reserve(headers, deadline)
+            })
+            .inspect_err(|_| this.finish_tasks(1))?;
+        this.open_views.fetch_add(1, Ordering::Relaxed);
+        Ok(SlotView::reserved(
+            slf.clone().unbind(),
+            pos,
+            slot + this.meta.size(),
+            this.queue.header().element_size,
+        ))
+    }
+
+    /// Publishes a slot reserved by `reserve()` to consumers.
+    ///
+    /// Equivalent to `view.release()`; committing a view twice has no effect.
+    ///
+    /// # Arguments
+    /// - `view` (SlotView): The view returned by `reserve()`.
+    ///
+    /// # Errors
+    /// Raises `ValueError` if `view` was not returned by `reserve()` on this handle,
+    /// `BufferError` if memoryviews of it are still alive, or `RuntimeError` if the slot
+    /// was reclaimed from this process before it was committed.
+    fn commit(slf: &Bound<'_, Self>, view: &Bound<'_, SlotView>) -> PyResult<()> {
+        if !view.get().is_reservation_of(slf) {
+            return Err(PyValueError::new_err(
+                "the view was not reserved by this queue handle",
+            ));
+        }
+        view.call_method0("release")?;
+        Ok(())
+    }
+
+    /// Blocking zero-copy get operation.
+    ///
+    /// Reserves the next message like `get`, but instead of copying the payload
+    /// returns a `SlotView` exposing it in place in shared memory. The slot is only
+    /// handed back to producers once the view is released, so release it promptly:
+    ///
+    /// ```python
+    /// with queue.get_buffer() as payload:  # a read-only memoryview
+    ///     process(payload)
+    /// ```
+    ///
+    /// # Arguments
+    /// - `timeout` (float, optional): Maximum time to wait.
+    /// - `retries` (int, default=0): Times to wait for another `timeout` after the queue
+    ///   stayed empty, sleeping a jittered backoff in between.
+    /// - `retry_backoff` (float, default=0.001): Base backoff in seconds, doubled on every
+    ///   retry.
+    ///
+    /// # Returns
+    /// - (SlotView): The reserved payload.
+    ///
+    /// # Errors
+    /// Raises `ValueError` on encrypted queues, whose payloads must be decrypted into
+    /// private memory, `QueueEmpty` if no item is available before the timeout and
+    /// every retry, or `CorruptMessage` if the payload fails its checksum, releasing its
+    /// slot.
+    #[pyo3(signature = (timeout=None, retries=0, retry_backoff=0.001))]
+    fn get_buffer(
+        slf: &Bound<'_, Self>,
+        timeout: Option<f64>,
+        retries: u32,
+        retry_backoff: f64,
+    ) -> PyResult<SlotView> {
+        let guard = slf.borrow();
+        let this: &Queue = &guard;
+        this.check_active()?;
+        if this.keyring.is_some() {
+            return Err(PyValueError::new_err(
+                "zero-copy gets are not available on encrypted queues",
+            ));
+        }
+        let retry = RetryPolicy::new(retries, retry_backoff)?;
+        let drop_expired = this.handle_config()?.drop_expired;
+        let (urgent, pos, slot) =
+            this.blocking(slf.py(), WaitOp::Get, &this.queue, timeout, retry, || {
+                this.try_begin_get(drop_expired)
+            })?;
+        if this.meta.has_checksum() {
+            let (prefix, payload) = unsafe {
+                std::slice::from_raw_parts(slot as *const u8, this.queue.layout().slot_size())
+            }
+            .split_at(this.meta.size());
+            if let Err(e) = this.check_message(prefix, payload) {
+                this.lane(urgent).commit_dequeue(pos);
+                return Err(e);
+            }
+        }
+        this.open_views.fetch_add(1, Ordering::Relaxed);
+        Ok(SlotView::dequeued(
+            slf.clone().unbind(),
+            urgent,
+            pos,
+            slot + this.meta.size(),
+            this.queue.header().element_size,
+        ))
+    }
+
+    /// Switches the key used to encrypt new messages, optionally adding it first.
+    ///
+    /// Consumers keep accepting every key id they know, so producers can rotate
+    /// while older messages are still queued.
+    ///
+    /// # Arguments
+    /// - `key_id` (int): Id of the key to activate.
+    /// - `key` (bytes, optional): 32-byte key to register under `key_id`.
+    ///
+    /// # Errors
+    /// Raises `ValueError` if the queue is not encrypted, the key is unknown or malformed.
+    #[pyo3(signature = (key_id, key=None))]
+    fn rotate_key(&self, key_id: u8, key: Option<Cow<[u8]>>) -> PyResult<()> {
+        self.check_active()?;
+        let keyring = self
+            .keyring
+            .as_ref()
+            .ok_or_else(|| PyValueError::new_err("the queue is not encrypted"))?;
+        keyring.write().unwrap().rotate(key_id, key.as_deref())
+    }
+
+    /// Audits the cell sequence numbers against the header positions.
+    ///
+    /// Every slot in the window `[head, head + capacity)` must carry a sequence
+    /// consistent with being occupied or free. This catches half-initialized or
+    /// trampled segments before they cause lost or duplicated messages. Run it
+    /// while the queue is quiescent: in-flight operations are tolerated, but a
+    /// concurrent `fix` may race with them.
+    ///
+    /// # Arguments
+    /// - `fix` (bool, default=False): Reset inconsistent cells to their expected value.
+    ///
+    /// # Returns
+    /// - (dict): `head`, `tail`, `positions_valid`, `inconsistencies` (list of
+    ///   `(index, sequence, expected)` tuples), `fixed` and `consistent`.
+    #[pyo3(signature = (fix=false))]
+    fn audit<'py>(&self, py: Python<'py>, fix: bool) -> PyResult<Bound<'py, PyDict>> {
+        self.check_active()?;
+        let report = py.allow_threads(|| self.queue.audit(fix));
+        let dict = PyDict::new(py);
+        dict.set_item("head", report.head)?;
+        dict.set_item("tail", report.tail)?;
+        dict.set_item("positions_valid", report.positions_valid)?;
+        dict.set_item(
+            "inconsistencies",
+            report
+                .inconsistencies
+                .iter()
+                .map(|c| (c.index, c.sequence, c.expected))
+                .collect::<Vec<_>>(),
+        )?;
+        dict.set_item("fixed", report.fixed)?;
+        dict.set_item("consistent", report.is_consistent())?;
+        Ok(dict)
+    }
+
+    /// Reports slots stalled by producers that reserved them but never published them.
+    ///
+    /// Stall durations are measured from the first time this handle observed the
+    /// stall, so call `health()` periodically.
+    ///
+    /// # Returns
+    /// - (dict): `healthy` is false once a slot has stalled for at least
+    ///   `poison_timeout`; `stalled` lists `(lane, position, seconds)` for every lane
+    ///   whose head slot is stalled; `poison_skipped` counts slots skipped by this handle.
+    ///
+    /// # Errors
+    /// Raises `ValueError` if the queue was created without `poison_timeout`.
+    fn health<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        self.check_active()?;
+        let policy = self
+            .poison
+            .as_ref()
+            .ok_or_else(|| PyValueError::new_err("health() requires poison_timeout"))?;
+        let now = self.clock.now();
+        let stalled = self
+            .tracked_lanes(policy)
+            .filter_map(|(name, lane, tracker)| {
+                tracker
+                    .observe(lane, now)
+                    .map(|(pos, stalled)| (name, pos, stalled))
+            })
+            .collect::<Vec<_>>();
+        let dict = PyDict::new(py);
+        dict.set_item(
+            "healthy",
+            stalled
+                .iter()
+                .all(|(_, _, stalled)| *stalled < policy.timeout),
+        )?;
+        dict.set_item(
+            "stalled",
+            stalled
+                .iter()
+                .map(|(name, pos, stalled)| (*name, *pos, stalled.as_secs_f64()))
+                .collect::<Vec<_>>(),
+        )?;
+        dict.set_item(
+            "poison_skipped",
+            self.stats.poison_skipped.load(Ordering::Relaxed),
+        )?;
+        Ok(dict)
+    }
+
+    /// Reclaims the slots that producers reserved but died before publishing.
+    ///
+    /// Consumers with `skip_poisoned` reclaim such slots on their own; `repair()` does
+    /// so on demand, e.g. from a supervisor that just reaped a crashed producer. A slot is
+    /// only reclaimed once it reaches the head of its lane and the process recorded as its
+    /// producer has exited, so slow producers are never robbed of their slots. A
+    /// producer that died before recording itself leaves its slot stalled, as reported
+    /// by `health()`.
+    ///
+    /// # Returns
+    /// - (list[tuple[str, int, int]]): The lane, position and producer process id of
+    ///   every slot reclaimed.
+    ///
+    /// # Errors
+    /// Raises `ValueError` if the queue was created without `track_owners`.
+    fn repair(&self, py: Python<'_>) -> PyResult<Vec<(&'static str, usize, u32)>> {
+        self.check_active()?;
+        if !self.queue.layout().owners {
+            return Err(PyValueError::new_err("repair() requires track_owners"));
+        }
+        let reclaimed: Vec<_> =
+            py.allow_threads(|| std::iter::from_fn(|| self.reclaim_abandoned()).collect());
+        self.stats
+            .reclaimed
+            .fetch_add(reclaimed.len() as u64, Ordering::Relaxed);
+        Ok(reclaimed)
+    }
+
+    /// Lists the processes with handles registered in the queue header.
+    ///
+    /// Every handle registers on attach and deregisters when it is closed, so a process
+    /// that exits without closing its handles stays listed, as not alive, until its
+    /// entry is reused. Handles inherited through `fork()` register for the child on
+    /// their first use there. The header holds up to 16 handles; further handles work, but
+    /// are not listed. Liveness is only checked on Unix and assumes every process
+    /// shares a PID namespace.
+    ///
+    /// # Returns
+    /// - (list[dict]): Per process, ordered by process id: its `pid`, the `roles` of its
+    ///   handles, the number of `handles` and whether it is `alive`.
+    fn attached_processes<'py>(&self, py: Python<'py>) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        self.check_active()?;
+        registry::attached_processes(self.queue.header())
+            .iter()
+            .map(|process| process.to_dict(py))
+            .collect()
+    }
+
+    /// Returns the shared memory segment name, also after the queue is closed.
+    #[getter]
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the element size in bytes.
+    #[getter]
+    fn element_size(&self) -> PyResult<usize> {
+        self.check_active()?;
+        Ok(self.queue.header().element_size)
+    }
+
+    /// Returns the maximum number of elements in the queue.
+    #[getter]
+    fn maxsize(&self) -> PyResult<usize> {
+        self.check_active()?;
+        Ok(self.queue.header().buffer_mask + 1)
+    }
+
+    /// Returns the counters collected by this queue handle.
+    ///
+    /// # Returns
+    /// - (dict): `spin_count` is the number of busy-spin iterations performed while waiting,
+    ///   `yield_count` and `park_count` the yields and parks of the adaptive strategy,
+    ///   `throttled` is the number of backpressure delays applied by blocking puts.
+    ///   For both `put` and `get`, `<op>_waits` counts blocking calls that had to wait,
+    ///   `<op>_timeouts` those that gave up, and `<op>_wait_time`/`<op>_max_wait` give the
+    ///   total and longest wait in seconds. `expired` counts messages discarded by
+    ///   `drop_expired`, `poison_skipped` the slots of dead producers skipped by
+    ///   `skip_poisoned`, `reclaimed` those reclaimed by `repair()` and `retries` the
+    ///   timed-out blocking calls that were retried.
+    fn stats<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        self.stats.to_dict(py)
+    }
+
+    /// Reports how this handle's wait loops spent their time, to quantify the CPU
+    /// cost of the wait strategy.
+    ///
+    /// Spinning and yielding keep a core busy; parking (sleeping, including
+    /// backpressure delays) does not.
+    ///
+    /// # Returns
+    /// - (dict): `spin_time`, `yield_time` and `park_time` give the seconds spent in
+    ///   each phase, as measured by the queue's clock, and `busy_fraction` the share of
+    ///   that time spent spinning or yielding, zero if the handle never waited.
+    fn handle_stats<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        self.stats.pauses_to_dict(py)
+    }
+
+    /// Changes runtime options for every process attached to the queue.
+    ///
+    /// The options are stored in the shared header; every handle picks them up at
+    /// its next operation, and they take precedence over the options given to the
+    /// constructor of handles attaching later. Options not given keep their current
+    /// values.
+    ///
+    /// # Arguments
+    /// - `**options`: Any of `wait`, `busy_spin`, `adaptive`, `spin_limit`, `yield_limit`,
+    ///   `park_interval`, `backpressure`, `backpressure_base`, `backpressure_max`,
+    ///   `slow_op_threshold` and `drop_expired`, with the constructor's meaning.
+    ///
+    /// # Returns
+    /// - (dict): The options in effect after the call; calling `configure()` without
+    ///   arguments only reads them.
+    ///
+    /// # Errors
+    /// Raises `ValueError` for unknown options or invalid values, in which case
+    /// nothing is changed.
+    #[pyo3(signature = (**options))]
+    fn configure<'py>(
+        &self,
+        py: Python<'py>,
+        options: Option<&Bound<'py, PyDict>>,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        self.check_active()?;
+        let current = self.handle_config()?.options;
+        let Some(options) = options.filter(|options| !options.is_empty()) else {
+            return current.to_dict(py);
+        };
+        let updated = current.with_overrides(options)?;
+        let config = HandleConfig::new(updated)?;
+        let generation = self.shared_config().store(&updated);
+        *self.config.write().unwrap() = Arc::new(config);
+        self.config_generation.store(generation, Ordering::Relaxed);
+        updated.to_dict(py)
+    }
+
+    /// Describes the layout of the attached queue.
+    ///
+    /// # Returns
+    /// - (dict): `name`, `element_size`, `capacity`, `meta_size`, the `slot_size` of
+    ///   metadata plus payload, the effective `stride` between slots, whether slots are
+    ///   `padded`, whether messages carry a `checksum`, whether the queue tracks the
+    ///   owners of slots (`track_owners`), the `cell_size`, the
+    ///   `cells_offset` and `data_offset` relative to the queue header, and the
+    ///   `urgent_capacity` (zero without an urgent lane).
+    fn debug_info<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        self.check_active()?;
+        let layout = self.queue.layout();
+        let capacity = self.queue.header().buffer_mask + 1;
+        let regions = compute_buffer_layout(&layout, capacity);
+        let dict = PyDict::new(py);
+        dict.set_item("name", &self.name)?;
+        dict.set_item("element_size", layout.element_size)?;
+        dict.set_item("capacity", capacity)?;
+        dict.set_item("meta_size", layout.meta_size)?;
+        dict.set_item("slot_size", layout.slot_size())?;
+        dict.set_item("stride", layout.stride())?;
+        dict.set_item("padded", layout.padded)?;
+        dict.set_item("checksum", self.meta.has_checksum())?;
+        dict.set_item("track_owners", layout.owners)?;
+        dict.set_item("cell_size", layout.cell_width.size())?;
+        dict.set_item("cells_offset", regions.cells_offset)?;
+        dict.set_item("data_offset", regions.data_offset)?;
+        dict.set_item(
+            "urgent_capacity",
+            self.urgent
+                .as_ref()
+                .map_or(0, |lane| lane.header().buffer_mask + 1),
+        )?;
+        Ok(dict)
+    }
+
+    /// Returns the approximate number of elements in the queue, including the urgent lane.
+    ///
+    /// The count is taken from the lane positions without locking, so concurrent producers
+    /// and consumers may change it before the caller acts on it.
+    fn qsize(&self) -> PyResult<usize> {
+        self.check_active()?;
+        Ok(lane_len(&self.queue) + self.urgent.as_ref().map_or(0, lane_len))
+    }
+
+    /// Returns the name, element size, capacity and state of the queue; a closed queue only
+    /// reports its name, as its header is no longer mapped.
+    fn __repr__(&self) -> String {
+        if self.closed.load(Ordering::Relaxed) {
+            return format!("Queue(name='{}', closed=True)", self.name);
+        }
+        let header = self.queue.header();
+        format!(
+            "Queue(name='{}', element_size={}, capacity={}, closed=False)",
+            self.name,
+            header.element_size,
+            header.buffer_mask + 1
+        )
+    }
+
+    /// Returns the number of elements in the queue, like `qsize()`.
+    fn __len__(&self) -> PyResult<usize> {
+        self.qsize()
+    }
+
+    /// Returns whether the queue is not empty.
+    fn __bool__(&self) -> PyResult<bool> {
+        Ok(self.__len__()? > 0)
+    }
+
+    /// Returns whether the main lane of the queue is full.
+    fn full(&self) -> PyResult<bool> {
+        self.check_active()?;
+        Ok(lane_len(&self.queue) > self.queue.header().buffer_mask)
+    }
+
+    /// Returns whether the queue is empty.
+    fn empty(&self) -> PyResult<bool> {
+        Ok(self.__len__()? == 0)
+    }
+
+    /// Waits until the queue holds a message, without dequeuing it.
+    ///
+    /// Other consumers may take the message first, so callers retry with
+    /// `get_nowait()` and wait again on `Empty`. The GIL is released while waiting.
+    ///
+    /// # Arguments
+    /// - `timeout` (float, optional): Maximum time to wait; waits indefinitely if omitted.
+    ///
+    /// # Returns
+    /// - (bool): Whether the queue held a message before the timeout.
+    #[pyo3(signature = (timeout=None))]
+    fn wait_readable(&self, py: Python<'_>, timeout: Option<f64>) -> PyResult<bool> {
+        self.check_active()?;
+        self.wait_ready(py, &self.queue.header().not_empty, timeout, || {
+            lane_len(&self.queue) > 0 || self.urgent.as_ref().is_some_and(|u| lane_len(u) > 0)
+        })
+    }
+
+    /// Waits until the main lane has a free slot, without enqueuing anything.
+    ///
+    /// Other producers may take the slot first, so callers retry with
+    /// `put_nowait()` and wait again on `Full`. The GIL is released while waiting.
+    ///
+    /// # Arguments
+    /// - `timeout` (float, optional): Maximum time to wait; waits indefinitely if omitted.
+    ///
+    /// # Returns
+    /// - (bool): Whether a slot was free before the timeout.
+    #[pyo3(signature = (timeout=None))]
+    fn wait_writable(&self, py: Python<'_>, timeout: Option<f64>) -> PyResult<bool> {
+        self.check_active()?;
+        self.wait_ready(py, &self.queue.header().not_full, timeout, || {
+            lane_len(&self.queue) <= self.queue.header().buffer_mask
+        })
+    }
+
+    /// Returns the file descriptor of a queue with `backing='memfd'` or `'file'`, or
+    /// attached through `fd`, for other processes to attach to.
+    ///
+    /// The descriptor belongs to this handle; send it with `socket.send_fds()` rather
+    /// than closing it. An anonymous segment lives on as long as any process holds a
+    /// descriptor or handle to it.
+    ///
+    /// # Returns
+    /// - (int): The file descriptor.
+    ///
+    /// # Errors
+    /// Raises `ValueError` if the queue is a named segment, which other processes open
+    /// by name instead.
+    fn segment_fd(&self) -> PyResult<i32> {
+        self.check_active()?;
+        self.shared_mem
+            .as_ref()
+            .and_then(ShmemWrapper::fd)
+            .ok_or_else(|| {
+                PyValueError::new_err(format!(
+                    "Queue '{}' is a named segment without a descriptor",
+                    self.name
+                ))
+            })
+    }
+
+    /// Returns a file descriptor that is readable while the queue holds messages.
+    ///
+    /// The descriptor belongs to this handle and can be registered with `selectors`,
+    /// `select`, `poll` or `epoll` next to sockets; it turns readable when the queue
+    /// goes from empty to non-empty, in any process, and stops being readable once it
+    /// is drained. A background thread maintains it from the first call until the
+    /// handle is closed. Do not read from or close the descriptor.
+    ///
+    /// # Returns
+    /// - (int): The file descriptor.
+    ///
+    /// # Errors
+    /// Raises `OSError` if the descriptor or its thread cannot be created, or
+    /// `NotImplementedError` on platforms without Unix sockets.
+    fn fileno(&self) -> PyResult<i32> {
+        #[cfg(unix)]
+        return self.readiness_fd(Condition::Messages);
+        #[cfg(not(unix))]
+        Err(PyNotImplementedError::new_err(
+            "fileno() requires Unix domain sockets",
+        ))
+    }
+
+    /// Returns a file descriptor that is readable while the main lane has a free slot.
+    ///
+    /// The writer-side counterpart of `fileno()`, with the same lifetime and rules.
+    ///
+    /// # Returns
+    /// - (int): The file descriptor.
+    ///
+    /// # Errors
+    /// Raises `OSError` if the descriptor or its thread cannot be created, or
+    /// `NotImplementedError` on platforms without Unix sockets.
+    fn write_fileno(&self) -> PyResult<i32> {
+        #[cfg(unix)]
+        return self.readiness_fd(Condition::Space);
+        #[cfg(not(unix))]
+        Err(PyNotImplementedError::new_err(
+            "write_fileno() requires Unix domain sockets",
+        ))
+    }
+
+    /// Returns how many messages went through the queue since it was created, counted
+    /// across every attached process and both lanes.
+    ///
+    /// # Returns
+    /// - (dict): `enqueued` and `dequeued` message counts. Slots claimed by producers or
+    ///   consumers that are still copying are already counted.
+    fn totals<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        self.check_active()?;
+        let (mut enqueued, mut dequeued) = (0, 0);
+        for lane in std::iter::once(&self.queue).chain(self.urgent.as_ref()) {
+            enqueued += lane.header().enqueue_pos.load(Ordering::Acquire);
+            dequeued += lane.header().dequeue_pos.load(Ordering::Acquire);
+        }
+        let dict = PyDict::new(py);
+        dict.set_item("enqueued", enqueued)?;
+        dict.set_item("dequeued", dequeued)?;
+        Ok(dict)
+    }
+
+    /// Marks a message returned by a get as processed, like `queue.Queue.task_done()`.
+    ///
+    /// The count of unfinished messages lives in the shared header, so `join()` in any
+    /// process waits for consumers in every process.
+    ///
+    /// # Errors
+    /// Raises `ValueError` if called more times than messages were enqueued.
+    fn task_done(&self) -> PyResult<()> {
+        self.check_active()?;
+        let unfinished = &self.queue.header().unfinished;
+        unfinished
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| n.checked_sub(1))
+            .map_err(|_| PyValueError::new_err("task_done() called too many times"))?;
+        self.notify_if_done();
+        Ok(())
+    }
+
+    /// Waits until every message enqueued so far was marked done with `task_done()`,
+    /// like `queue.Queue.join()`. The GIL is released while waiting.
+    ///
+    /// Messages discarded by `clear()`, as expired, or as abandoned count as done.
+    ///
+    /// # Arguments
+    /// - `timeout` (float, optional): Maximum time to wait; waits indefinitely if omitted.
+    ///
+    /// # Returns
+    /// - (bool): Whether every message was done before the timeout.
+    #[pyo3(signature = (timeout=None))]
+    fn join(&self, py: Python<'_>, timeout: Option<f64>) -> PyResult<bool> {
+        self.check_active()?;
+        let header = self.queue.header();
+        self.wait_ready(py, &header.all_done, timeout, || {
+            header.unfinished.load(Ordering::Acquire) == 0
+        })
+    }
+
+    /// Cancels the blocking operations of this handle, so that another thread can stop a
+    /// worker waiting in `put()` or `get()` without a timeout, which `close()` cannot do
+    /// while the handle is in use.
+    ///
+    /// Operations waiting now and any started later raise `Cancelled`, as do
+    /// `wait_readable()` and `wait_writable()`; non-blocking operations are unaffected, and
+    /// other handles on the same queue keep working.
+    fn cancel(&self) -> PyResult<()> {
+        self.check_active()?;
+        self.cancelled.store(true, Ordering::Release);
+        self.wake_all();
+        Ok(())
+    }
+
+    /// Returns the queue itself, so that `with Queue(...) as queue:` closes it on exit.
+    fn __enter__(slf: PyRef<'_, Self>) -> PyResult<PyRef<'_, Self>> {
+        slf.check_active()?;
+        Ok(slf)
+    }
+
+    /// Closes the queue like `close()` when leaving a `with` block; exceptions are not
+    /// suppressed.
+    ///
+    /// # Errors
+    /// Raises `BufferError` if views returned by `get_buffer()` or `reserve()` are still
+    /// open.
+    fn __exit__(
+        &mut self,
+        _exc_type: PyObject,
+        _exc_value: PyObject,
+        _traceback: PyObject,
+    ) -> PyResult<bool> {
+        self.close(None)?;
+        Ok(false)
+    }
+
+    /// Closes the queue, releasing the shared memory segment.
+    ///
+    /// A shutdown also closes the queue for every other handle, in any process: their puts
+    /// raise `QueueClosed`, and their gets return the remaining messages, then raise
+    /// `QueueClosed` instead of waiting for more.
+    ///
+    /// # Arguments
+    /// - `shutdown` (bool, optional): Whether to shut the queue down; defaults to whether
+    ///   this handle created the queue.
+    ///
+    /// # Errors
+    /// Raises `BufferError` if views returned by `get_buffer()` or `reserve()` are still
+    /// open.
+    #[pyo3(signature = (shutdown=None))]
+    fn close(&mut self, shutdown: Option<bool>) -> PyResult<()> {
+        if self.closed.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+        let open_views = self.open_views.load(Ordering::Relaxed);
+        if open_views > 0 {
+            return Err(PyBufferError::new_err(format!(
+                "{} view(s) returned by get_buffer() or reserve() are still open",
+                open_views
+            )));
+        }
+        let owner = self.shared_mem.as_ref().is_some_and(ShmemWrapper::is_owner);
+        if shutdown.unwrap_or(owner) {
+            self.shut_down_all();
+        }
+        self.closed.store(true, Ordering::Relaxed);
+        self.stop_readiness();
+        if let Some(registration) = self.registration.get_mut().unwrap().take() {
+            registration.release(self.queue.header());
+        }
+        if let Some(shmem) = self.shared_mem.take() {
+            shmem.touch();
+        }
+        Ok(())
+    }
+}
+
+impl Queue {
+    /// Returns whether the queue was shut down for every handle.
+    fn shut_down(&self) -> bool {
+        self.queue.header().closed.load(Ordering::Acquire) != 0
+    }
+
+    /// Shuts the queue down for every handle and wakes their blocked operations.
+    fn shut_down_all(&self) {
+        self.queue.header().closed.store(1, Ordering::Release);
+        self.wake_all();
+    }
+
+    /// Wakes every operation parked on a lane of the queue, in any process, so that it
+    /// re-checks why it waits.
+    fn wake_all(&self) {
+        for lane in std::iter::once(&self.queue).chain(self.urgent.as_ref()) {
+            lane.header().not_empty.notify();
+            lane.header().not_full.notify();
+        }
+    }
+
+    /// Counts `count` messages about to be enqueued as unfinished. Counting them before
+    /// they become visible keeps `task_done()` from running ahead of the count.
+    fn add_tasks(&self, count: usize) {
+        let unfinished = &self.queue.header().unfinished;
+        unfinished.fetch_add(count as u64, Ordering::AcqRel);
+    }
+
+    /// Marks `count` messages as done without a consumer: ones that failed to enqueue,
+    /// or were discarded instead of being handed out.
+    fn finish_tasks(&self, count: usize) {
+        if count > 0 {
+            let unfinished = &self.queue.header().unfinished;
+            unfinished.fetch_sub(count as u64, Ordering::AcqRel);
+            self.notify_if_done();
+        }
+    }
+
+    /// Wakes `join()` callers once no message is unfinished.
+    fn notify_if_done(&self) {
+        let header = self.queue.header();
+        if header.unfinished.load(Ordering::Acquire) == 0 {
+            header.all_done.notify();
+        }
+    }
+
+    /// Prepares `obj` for enqueuing: packed with the `fmt` of the handle, serialized with
+    /// its serializer, or otherwise taken as a buffer.
+    ///
+    /// # Errors
+    /// Raises the error of the serializer, `ValueError` if the serialized object does not
+    /// fit in a slot or the record does not match `fmt`, or `TypeError` if `obj` is not
+    /// a buffer and there is neither `fmt` nor a serializer.
+    fn item(&self, obj: &Bound<'_, PyAny>) -> PyResult<Item> {
+        if let Some(format) = &self.format {
+            return Ok(Item {
+                data: ByteBuffer::get(format.pack(obj)?.as_any(), false)?,
+                framed: false,
+                segments: Segments::default(),
+            });
+        }
+        match &self.serializer {
+            Some(serializer) => {
+                let (data, segments) = serializer.encode(obj, self.queue.header().element_size)?;
+                Ok(Item {
+                    data: ByteBuffer::get(&data, false)?,
+                    framed: true,
+                    segments,
+                })
+            }
+            None => Ok(Item {
+                data: ByteBuffer::get(obj, false)?,
+                framed: false,
+                segments: Segments::default(),
+            }),
+        }
+    }
+
+    /// Turns a dequeued payload into the item returned to Python: the unpacked record,
+    /// the deserialized object, or the payload as `bytes` if the handle has neither
+    /// `fmt` nor a serializer.
+    ///
+    /// # Errors
+    /// Raises `ValueError` if the payload holds no serialized object, or the error of the
+    /// serializer.
+    fn decode<'py>(&self, py: Python<'py>, payload: &[u8]) -> PyResult<Bound<'py, PyAny>> {
+        if let Some(format) = &self.format {
+            return Ok(format.unpack(py, payload)?.into_any());
+        }
+        match &self.serializer {
+            Some(serializer) => serializer.decode(py, payload),
+            None => Ok(PyBytes::new(py, payload).into_any()),
+        }
+    }
+
+    /// Raises `Cancelled` once `cancel()` was called on this handle.
+    fn check_cancelled(&self) -> PyResult<()> {
+        if self.cancelled.load(Ordering::Acquire) {
+            Err(Cancelled::new_err("Operation was cancelled"))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns the descriptor readable while `condition` holds, starting its
+    /// watcher on first use.
+    #[cfg(unix)]
+    fn readiness_fd(&self, condition: Condition) -> PyResult<i32> {
+        self.check_active()?;
+        let mut readiness = self.readiness[condition as usize].lock().unwrap();
+        if readiness.as_ref().is_none_or(Readiness::inherited) {
+            let interval = Duration::from_secs_f64(self.handle_config()?.options.park_interval);
+            let lanes = std::iter::once(&self.queue)
+                .chain(self.urgent.as_ref())
+                .map(|lane| unsafe {
+                    &*(lane.header() as *const crate::mpmc_queue::MpmcQueueHeader)
+                })
+                .collect();
+            // The watcher is stopped before the segment is unmapped.
+            let spawned = unsafe { Readiness::spawn(lanes, condition, interval) }.map_err(|e| {
+                PyOSError::new_err(format!("Failed to create readiness descriptor: {}", e))
+            })?;
+            *readiness = Some(spawned);
+        }
+        Ok(readiness.as_ref().map_or(-1, Readiness::fileno))
+    }
+
+    /// Stops the watchers behind `fileno()` and `write_fileno()`, if they were started.
+    fn stop_readiness(&self) {
+        #[cfg(unix)]
+        for readiness in &self.readiness {
+            readiness.lock().unwrap().take();
+        }
+    }
+
+    /// Returns the configuration words shared through the queue header.
+    fn shared_config(&self) -> SharedConfig<'_> {
+        SharedConfig::new(&self.queue.header().config)
+    }
+
+    /// Returns the current handle configuration, first reloading it if another
+    /// handle published new options through `configure()`.
+    fn handle_config(&self) -> PyResult<Arc<HandleConfig>> {
+        let shared = self.shared_config();
+        if shared.generation() != self.config_generation.load(Ordering::Relaxed) {
+            if let Some((generation, options)) = shared.load() {
+                let config = HandleConfig::new(options)?;
+                *self.config.write().unwrap() = Arc::new(config);
+                self.config_generation.store(generation, Ordering::Relaxed);
+            }
+        }
+        Ok(self.config.read().unwrap().clone())
+    }
+
+    /// Retries `attempt` until it succeeds, fails, or `timeout` seconds elapse,
+    /// pausing with the configured wait strategy whenever the queue is full
+    /// (for puts) or empty (for gets). Once the timeout elapses, `retry` may
+    /// start a fresh timeout window after a backoff. The GIL is released while
+    /// waiting.
+    ///
+    /// Parked waits sleep on the wake-up signal of `lane` matching `op`, so a peer
+    /// publishing or releasing a slot there wakes them immediately.
+    fn blocking<T: Send>(
+        &self,
+        py: Python<'_>,
+        op: WaitOp,
+        lane: &MpmcQueueOnBuffer,
+        timeout: Option<f64>,
+        retry: RetryPolicy,
+        attempt: impl FnMut() -> Result<T, MpmcQueueError> + Send,
+    ) -> PyResult<T> {
+        self.blocking_then(py, op, lane, timeout, retry, attempt, Ok)
+    }
+
+    /// Like `blocking`, but passes the result of the successful attempt through `then`
+    /// before taking the GIL back, so that the whole operation, e.g. decrypting a
+    /// dequeued payload, runs under a single release of the GIL.
+    #[allow(clippy::too_many_arguments)]
+    fn blocking_then<T, U: Send>(
+        &self,
+        py: Python<'_>,
+        op: WaitOp,
+        lane: &MpmcQueueOnBuffer,
+        timeout: Option<f64>,
+        retry: RetryPolicy,
+        mut attempt: impl FnMut() -> Result<T, MpmcQueueError> + Send,
+        then: impl FnOnce(T) -> PyResult<U> + Send,
+    ) -> PyResult<U> {
+        let config = self.handle_config()?;
+        let start = self.clock.now();
+        let mut window_start = start;
+        let mut retried = 0u32;
+        let mut attempts = 0u32;
+        let mut timed_out = false;
+
+        let signal = match op {
+            WaitOp::Put => &lane.header().not_full,
+            WaitOp::Get => &lane.header().not_empty,
+        };
+
+        let result = py.allow_threads(|| {
+            let mut wait = || {
+                // A manual clock must advance instead of blocking in the kernel.
+                let waiter = (futex::SUPPORTED
+                    && config.wait.parks()
+                    && matches!(self.clock, Clock::System))
+                .then(|| signal.register());
+                let mut signals_checked = Instant::now();
+                loop {
+                    let epoch = waiter.as_ref().map(Waiter::epoch);
+                    self.check_cancelled()?;
+                    check_signals(&mut signals_checked)?;
+                    // Read after the epoch, so that a shutdown cuts the next park short,
+                    // and before the attempt, so that gets drain what was put before it.
+                    let shut_down = self.shut_down();
+                    if shut_down && op == WaitOp::Put {
+                        return Err(QueueClosed::new_err(SHUT_DOWN));
+                    }
+                    match attempt() {
+                        Ok(value) => {
+                            if let (WaitOp::Put, Some(backpressure)) = (op, &config.backpressure) {
+                                backpressure.reset();
+                            }
+                            return Ok(value);
+                        }
+                        Err(MpmcQueueError::Capacity(CapacityError::Full)) if op == WaitOp::Put => {
+                        }
+                        Err(MpmcQueueError::Capacity(CapacityError::Empty))
+                            if op == WaitOp::Get =>
+                        {
+                            if shut_down {
+                                return Err(QueueClosed::new_err(SHUT_DOWN));
+                            }
+                        }
+                        Err(e) => return Err(PyErr::from(e)),
+                    }
+                    if let Some(t) = timeout {
+                        if (self.clock.now() - window_start).as_secs_f64() > t {
+                            if retried < retry.retries {
+                                self.clock.sleep(retry.delay(retried));
+                                retried += 1;
+                                self.stats.retries.fetch_add(1, Ordering::Relaxed);
+                                window_start = self.clock.now();
+                                continue;
+                            }
+                            timed_out = true;
+                            return Err(match op {
+                                WaitOp::Put => Full::new_err("Queue is full"),
+                                WaitOp::Get => Empty::new_err("Queue is empty"),
+                            });
+                        }
+                    }
+                    let attempt = attempts;
+                    attempts = attempts.saturating_add(1);
+                    match (op, &config.backpressure) {
+                        (WaitOp::Put, Some(backpressure)) => {
+                            backpressure.throttle(&self.clock, &self.stats)
+                        }
+                        _ => config
+                            .wait
+                            .pause(attempt, &self.clock, &self.stats, |interval| {
+                                let window = timeout.map(|t| (t, window_start));
+                                self.park(waiter.as_ref().zip(epoch), interval, window)
+                            }),
+                    }
+                }
+            };
+            wait().and_then(then)
+        });
+
+        if attempts > 0 {
+            let elapsed = self.clock.now() - start;
+            self.stats.record_wait(op, elapsed, timed_out);
+            if config.slow_op_threshold.is_some_and(|t| elapsed >= t) {
+                self.log_slow_op(py, op, elapsed)?;
+            }
+        }
+        result
+    }
+
+    /// Parks a blocked operation for `interval`, or, with a registered `waiter`,
+    /// until a peer signals progress after `epoch` was read, the timeout window
+    /// `(timeout, window_start)` ends, or `PARK_RECHECK` elapses.
+    fn park(
+        &self,
+        waiter: Option<(&Waiter, u32)>,
+        interval: Duration,
+        window: Option<(f64, Duration)>,
+    ) {
+        match waiter {
+            Some((waiter, epoch)) => {
+                let remaining = window.map_or(PARK_RECHECK, |(timeout, start)| {
+                    Duration::try_from_secs_f64(timeout)
+                        .map_or(PARK_RECHECK, |t| t.saturating_sub(self.clock.now() - start))
+                });
+                waiter.wait(epoch, remaining.min(PARK_RECHECK));
+            }
+            None => self.clock.sleep(interval),
+        }
+    }
+
+    /// Waits until `ready` holds or `timeout` seconds elapse, parking on `signal` of the
+    /// main lane between checks, or sleeping `park_interval` where wake-ups are
+    /// unsupported. The GIL is released while waiting.
+    fn wait_ready(
+        &self,
+        py: Python<'_>,
+        signal: &WaitSignal,
+        timeout: Option<f64>,
+        ready: impl Fn() -> bool + Sync,
+    ) -> PyResult<bool> {
+        let config = self.handle_config()?;
+        let interval = Duration::from_secs_f64(config.options.park_interval);
+        // Negative timeouts only check once, as with the blocking operations.
+        let timeout = timeout.map(|t| Duration::try_from_secs_f64(t).unwrap_or_default());
+        let start = self.clock.now();
+        py.allow_threads(|| {
+            let waiter = (futex::SUPPORTED && matches!(self.clock, Clock::System))
+                .then(|| signal.register());
+            let mut signals_checked = Instant::now();
+            loop {
+                let epoch = waiter.as_ref().map(Waiter::epoch);
+                self.check_cancelled()?;
+                check_signals(&mut signals_checked)?;
+                if ready() {
+                    return Ok(true);
+                }
+                let remaining = timeout.map(|t| t.saturating_sub(self.clock.now() - start));
+                if remaining.is_some_and(|r| r.is_zero()) {
+                    return Ok(false);
+                }
+                match waiter.as_ref().zip(epoch) {
+                    Some((waiter, epoch)) => waiter.wait(
+                        epoch,
+                        remaining.map_or(PARK_RECHECK, |r| r.min(PARK_RECHECK)),
+                    ),
+                    None => self
+                        .clock
+                        .sleep(remaining.map_or(interval, |r| r.min(interval))),
+                }
+            }
+        })
+    }
+
+    /// Logs a blocking operation that exceeded `slow_op_threshold`.
+    fn log_slow_op(&self, py: Python<'_>, op: WaitOp, elapsed: Duration) -> PyResult<()> {
+        let header = self.queue.header();
+        let head = header.dequeue_pos.load(Ordering::Acquire);
+        let tail = header.enqueue_pos.load(Ordering::Acquire);
+        py.import("logging")?
+            .call_method1("getLogger", ("zeroq",))?
+            .call_method1(
+                "warning",
+                (
+                    "Slow %s on queue %r: waited %.6fs (occupancy %d/%d)",
+                    op.name(),
+                    &self.name,
+                    elapsed.as_secs_f64(),
+                    tail.saturating_sub(head),
+                    header.buffer_mask + 1,
+                ),
+            )?;
+        Ok(())
+    }
+
+    /// Attempts to enqueue `item` into `lane`, writing the enabled metadata fields in front of it.
+    ///
+    /// Encrypted payloads are sealed in a private buffer so that plaintext
+    /// never reaches shared memory.
+    fn try_put(
+        &self,
+        lane: &MpmcQueueOnBuffer,
+        item: &[u8],
+        headers: Option<&[u8]>,
+        deadline_ns: u64,
+    ) -> Result<(), MpmcQueueError> {
+        if self.meta.size() == 0 {
+            return lane.enqueue(item);
+        }
+        lane.validate_enqueue_src(item)?;
+        self.try_put_with(lane, headers, deadline_ns, |payload| {
+            copy::write_payload(payload, item)
+        })
+    }
+
+    /// Like `try_put`, but has `write` fill the payload in place of copying an item.
+    fn try_put_with(
+        &self,
+        lane: &MpmcQueueOnBuffer,
+        headers: Option<&[u8]>,
+        deadline_ns: u64,
+        write: impl FnOnce(&mut [u8]),
+    ) -> Result<(), MpmcQueueError> {
+        let keyring = self.keyring.as_ref().map(|k| k.read().unwrap());
+        let mut sealed = keyring
+            .as_ref()
+            .map(|_| vec![0u8; lane.header().element_size]);
+        lane.enqueue_with(|pos, slot| {
+            let sealing = keyring.as_deref().zip(sealed.as_deref_mut());
+            self.fill_slot(sealing, slot, pos, write, headers, deadline_ns)
+        })
+    }
+
+    /// Like `try_put`, but for an item prepared by `item()`.
+    fn try_put_item(
+        &self,
+        lane: &MpmcQueueOnBuffer,
+        item: &Item,
+        headers: Option<&[u8]>,
+        deadline_ns: u64,
+    ) -> Result<(), MpmcQueueError> {
+        if item.framed {
+            self.try_put_with(lane, headers, deadline_ns, |payload| item.write(payload))
+        } else {
+            self.try_put(lane, item.data.as_slice(), headers, deadline_ns)
+        }
+    }
+
+    /// Attempts to enqueue as many of `items` as fit into the main lane, reserving
+    /// their slots in one step, and returns how many were enqueued.
+    ///
+    /// Items must already have been validated against the element size.
+    fn try_put_many(&self, items: &[Item]) -> Result<usize, MpmcQueueError> {
+        let keyring = self.keyring.as_ref().map(|k| k.read().unwrap());
+        let mut sealed = keyring
+            .as_ref()
+            .map(|_| vec![0u8; self.queue.header().element_size]);
+        self.queue.enqueue_batch_with(items.len(), |i, pos, slot| {
+            let sealing = keyring.as_deref().zip(sealed.as_deref_mut());
+            self.fill_slot(
+                sealing,
+                slot,
+                pos,
+                |payload| items[i].write(payload),
+                None,
+                0,
+            )
+        })
+    }
+
+    /// Attempts to enqueue the chunks of a streamed payload into consecutive slots of
+    /// the main lane, all or none.
+    fn try_put_run(&self, chunks: &[&[u8]]) -> Result<(), MpmcQueueError> {
+        let keyring = self.keyring.as_ref().map(|k| k.read().unwrap());
+        let mut sealed = keyring
+            .as_ref()
+            .map(|_| vec![0u8; self.queue.header().element_size]);
+        self.queue.enqueue_run_with(chunks.len(), |i, pos, slot| {
+            let sealing = keyring.as_deref().zip(sealed.as_deref_mut());
+            let count = if i == 0 { chunks.len() as u32 } else { 0 };
+            self.fill_slot(
+                sealing,
+                slot,
+                pos,
+                |payload| stream::write_chunk(payload, count, chunks[i]),
+                None,
+                0,
+            )
+        })
+    }
+
+    /// Attempts to dequeue every chunk of the next streamed payload from the main lane
+    /// and returns the metadata prefix and sealed payload of each.
+    ///
+    /// A first slot that does not hold a streamed payload is dequeued alone.
+    #[allow(clippy::type_complexity)]
+    fn try_get_run(&self) -> Result<Vec<(Vec<u8>, Vec<u8>)>, MpmcQueueError> {
+        let meta_size = self.meta.size();
+        let mut chunks = Vec::new();
+        self.queue.dequeue_run_with(
+            |slot| {
+                let (prefix, payload) = slot.split_at(meta_size);
+                if self.keyring.is_none() {
+                    return stream::chunk_count(payload);
+                }
+                let mut payload = payload.to_vec();
+                match self.open(prefix, &mut payload) {
+                    Ok(()) => stream::chunk_count(&payload),
+                    Err(_) => 1,
+                }
+            },
+            |_, _, slot| {
+                let (prefix, payload) = slot.split_at(meta_size);
+                chunks.push((prefix.to_vec(), payload.to_vec()));
+            },
+        )?;
+        Ok(chunks)
+    }
+
+    /// Fills the whole slot at `pos`: the enabled metadata fields, then the payload,
+    /// written by `write`, then the checksum if enabled.
+    ///
+    /// With `sealing`, the payload is written to and encrypted in the given private
+    /// buffer first so that plaintext never reaches shared memory.
+    fn fill_slot(
+        &self,
+        sealing: Option<(&Keyring, &mut [u8])>,
+        slot: &mut [u8],
+        pos: usize,
+        write: impl FnOnce(&mut [u8]),
+        headers: Option<&[u8]>,
+        deadline_ns: u64,
+    ) {
+        let (prefix, payload) = slot.split_at_mut(self.meta.size());
+        self.meta.write(prefix, pos, headers, deadline_ns);
+        match sealing {
+            Some((keyring, sealed)) => {
+                write(sealed);
+                let (fields, envelope) = prefix.split_at_mut(self.meta.fields_size());
+                keyring.seal(&mut envelope[..ENVELOPE_SIZE], fields, sealed);
+                copy::write_payload(payload, sealed);
+            }
+            None => write(payload),
+        }
+        self.meta.write_checksum(prefix, payload);
+    }
+
+    /// Attempts to dequeue an element into `dst`, checking the urgent lane first,
+    /// and returns a copy of its metadata prefix.
+    ///
+    /// With `drop_expired`, messages past their deadline are discarded and
+    /// counted instead of being returned.
+    fn try_get(&self, dst: &mut [u8], drop_expired: bool) -> Result<Vec<u8>, MpmcQueueError> {
+        loop {
+            let prefix = match self.try_get_any(dst) {
+                Err(MpmcQueueError::Capacity(CapacityError::Empty)) if self.skip_poisoned() => {
+                    continue
+                }
+                result => result?,
+            };
+            if drop_expired && self.meta.is_expired(&prefix, unix_time_ns()) {
+                self.stats.expired.fetch_add(1, Ordering::Relaxed);
+                self.finish_tasks(1);
+                continue;
+            }
+            return Ok(prefix);
+        }
+    }
+
+    /// Like `try_get`, but on success moves the payload out of `dst`, which then needs
+    /// refilling before the next call, and returns it with its metadata prefix.
+    fn try_get_owned(
+        &self,
+        dst: &mut Vec<u8>,
+        drop_expired: bool,
+    ) -> Result<(Vec<u8>, Vec<u8>), MpmcQueueError> {
+        let prefix = self.try_get(dst, drop_expired)?;
+        Ok((prefix, std::mem::take(dst)))
+    }
+
+    /// Decrypts the payloads of a batch returned by `try_get_many`.
+    ///
+    /// # Errors
+    /// Returns `DecryptionError` if any payload fails authentication.
+    fn open_batch(&self, batch: Vec<(Vec<u8>, Vec<u8>)>) -> PyResult<Vec<Vec<u8>>> {
+        batch
+            .into_iter()
+            .map(|(prefix, mut payload)| {
+                self.open(&prefix, &mut payload)?;
+                Ok(payload)
+            })
+            .collect()
+    }
+
+    /// Attempts to dequeue up to `max` elements, draining the urgent lane first, and
+    /// returns each element's metadata prefix and payload.
+    ///
+    /// Poisoned slots and, with `drop_expired`, expired messages are skipped as in
+    /// `try_get`.
+    #[allow(clippy::type_complexity)]
+    fn try_get_many(
+        &self,
+        max: usize,
+        drop_expired: bool,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, MpmcQueueError> {
+        let meta_size = self.meta.size();
+        let mut batch = Vec::new();
+        loop {
+            for lane in self.urgent.iter().chain(std::iter::once(&self.queue)) {
+                while batch.len() < max {
+                    let consumed = lane.dequeue_batch_with(max - batch.len(), |_, _, slot| {
+                        let (prefix, payload) = slot.split_at(meta_size);
+                        batch.push((prefix.to_vec(), payload.to_vec()));
+                    });
+                    if consumed.is_err() {
+                        break;
+                    }
+                }
+            }
+            if drop_expired {
+                let now = unix_time_ns();
+                let before = batch.len();
+                batch.retain(|(prefix, _)| !self.meta.is_expired(prefix, now));
+                let expired = (before - batch.len()) as u64;
+                self.stats.expired.fetch_add(expired, Ordering::Relaxed);
+                self.finish_tasks(expired as usize);
+                if expired > 0 && batch.len() < max {
+                    continue;
+                }
+            }
+            if !batch.is_empty() {
+                return Ok(batch);
+            }
+            if !self.skip_poisoned() {
+                return Err(CapacityError::Empty.into());
+            }
+        }
+    }
+
+    /// Reserves the next message for a zero-copy read, checking the urgent lane first,
+    /// and returns its lane (`true` for urgent), position and slot address.
+    ///
+    /// Poisoned slots and, with `drop_expired`, expired messages are skipped as in
+    /// `try_get`.
+    fn try_begin_get(&self, drop_expired: bool) -> Result<(bool, usize, usize), MpmcQueueError> {
+        loop {
+            let (urgent, pos, slot) = match self.begin_get_any() {
+                Err(CapacityError::Empty) if self.skip_poisoned() => continue,
+                result => result?,
+            };
+            if drop_expired {
+                let prefix = unsafe { std::slice::from_raw_parts(slot, self.meta.size()) };
+                if self.meta.is_expired(prefix, unix_time_ns()) {
+                    self.lane(urgent).commit_dequeue(pos);
+                    self.stats.expired.fetch_add(1, Ordering::Relaxed);
+                    self.finish_tasks(1);
+                    continue;
+                }
+            }
+            return Ok((urgent, pos, slot as usize));
+        }
+    }
+
+    /// Reserves the next published slot, checking the urgent lane first.
+    fn begin_get_any(&self) -> Result<(bool, usize, *const u8), CapacityError> {
+        if let Some(urgent) = &self.urgent {
+            if let Ok((pos, slot)) = urgent.begin_dequeue() {
+                return Ok((true, pos, slot.as_ptr()));
+            }
+        }
+        let (pos, slot) = self.queue.begin_dequeue()?;
+        Ok((false, pos, slot.as_ptr()))
+    }
+
+    /// Returns the urgent lane if `urgent` is set, otherwise the main lane.
+    fn lane(&self, urgent: bool) -> &MpmcQueueOnBuffer<'static> {
+        match (&self.urgent, urgent) {
+            (Some(lane), true) => lane,
+            _ => &self.queue,
+        }
+    }
+
+    /// Reserves a slot in the main lane for a zero-copy put, writing the enabled
+    /// metadata fields in front of it, and returns its position and slot address.
+    fn try_reserve(
+        &self,
+        headers: Option<&[u8]>,
+        deadline_ns: u64,
+    ) -> Result<(usize, usize), MpmcQueueError> {
+        let (pos, slot) = self.queue.begin_enqueue()?;
+        let meta_size = self.meta.size();
+        if meta_size > 0 {
+            let prefix = unsafe { std::slice::from_raw_parts_mut(slot.as_ptr(), meta_size) };
+            self.meta.write(prefix, pos, headers, deadline_ns);
+        }
+        Ok((pos, slot.as_ptr() as usize))
+    }
+
+    /// Publishes a slot reserved by `reserve()` to consumers, once its checksum covers
+    /// the payload written in place.
+    pub(crate) fn commit_reservation(&self, pos: usize) -> PyResult<()> {
+        self.open_views.fetch_sub(1, Ordering::Relaxed);
+        if self.meta.has_checksum() {
+            let slot = self.queue.slot_at(pos).as_ptr();
+            let (prefix, payload) =
+                unsafe { std::slice::from_raw_parts_mut(slot, self.queue.layout().slot_size()) }
+                    .split_at_mut(self.meta.size());
+            self.meta.write_checksum(prefix, payload);
+        }
+        self.queue.commit_enqueue(pos)?;
+        Ok(())
+    }
+
+    /// Releases a slot reserved by `get_buffer()` back to producers.
+    pub(crate) fn commit_view(&self, urgent: bool, pos: usize) {
+        self.lane(urgent).commit_dequeue(pos);
+        self.open_views.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    /// Returns the lanes of the queue paired with their stall trackers.
+    fn tracked_lanes<'a>(
+        &'a self,
+        policy: &'a PoisonPolicy,
+    ) -> impl Iterator<
+        Item = (
+            &'static str,
+            &'a MpmcQueueOnBuffer<'static>,
+            &'a StallTracker,
+        ),
+    > {
+        let urgent = self
+            .urgent
+            .as_ref()
+            .map(|lane| ("urgent", lane, &policy.urgent));
+        urgent
+            .into_iter()
+            .chain(std::iter::once(("main", &self.queue, &policy.main)))
+    }
+
+    /// Skips the first slot found abandoned by a dead producer when the handle has
+    /// `skip_poisoned`, returning whether one was skipped.
+    fn skip_poisoned(&self) -> bool {
+        if !self.skip_poisoned || self.reclaim_abandoned().is_none() {
+            return false;
+        }
+        self.stats.poison_skipped.fetch_add(1, Ordering::Relaxed);
+        true
+    }
+
+    /// Reclaims the first slot found stalled at the head of a lane whose recorded
+    /// producer has exited, returning its lane, position and producer.
+    fn reclaim_abandoned(&self) -> Option<(&'static str, usize, u32)> {
+        if !self.queue.layout().owners {
+            return None;
+        }
+        let urgent = self.urgent.as_ref().map(|lane| ("urgent", lane));
+        for (name, lane) in urgent
+            .into_iter()
+            .chain(std::iter::once(("main", &self.queue)))
+        {
+            let Some(pos) = lane.stalled_head() else {
+                continue;
+            };
+            let Some((pid, namespace)) = lane.slot_owner(pos) else {
+                continue;
+            };
+            if owner_exited(pid, namespace) && lane.skip_reserved(pos) {
+                self.finish_tasks(1);
+                return Some((name, pos, pid));
+            }
+        }
+        None
+    }
+
+    /// Attempts to dequeue an element from the urgent lane, then from the main lane.
+    fn try_get_any(&self, dst: &mut [u8]) -> Result<Vec<u8>, MpmcQueueError> {
+        if let Some(urgent) = &self.urgent {
+            match self.try_get_from(urgent, dst) {
+                Err(MpmcQueueError::Capacity(CapacityError::Empty)) => {}
+                result => return result,
+            }
+        }
+        self.try_get_from(&self.queue, dst)
+    }
+
+    /// Attempts to dequeue an element from `lane` into `dst`, returning a copy of its
+    /// metadata prefix.
+    fn try_get_from(
+        &self,
+        lane: &MpmcQueueOnBuffer,
+        dst: &mut [u8],
+    ) -> Result<Vec<u8>, MpmcQueueError> {
+        let meta_size = self.meta.size();
+        if meta_size == 0 {
+            return lane.dequeue(dst).map(|_| Vec::new());
+        }
+        Ok(lane.dequeue_with(|_pos, slot| {
+            let (prefix, payload) = slot.split_at(meta_size);
+            copy::read_payload(dst, payload);
+            prefix.to_vec()
+        })?)
+    }
+
+    /// Verifies the checksum of a dequeued message when the queue has them, then
+    /// decrypts its payload in place when the queue is encrypted.
+    ///
+    /// # Errors
+    /// Raises `CorruptMessage` if the checksum does not match, or `DecryptionError` if
+    /// the payload fails authentication.
+    fn open(&self, prefix: &[u8], payload: &mut [u8]) -> PyResult<()> {
+        self.check_message(prefix, payload)?;
+        match &self.keyring {
+            Some(keyring) => {
+                let (fields, envelope) = prefix.split_at(self.meta.fields_size());
+                Ok(keyring
+                    .read()
+                    .unwrap()
+                    .open(&envelope[..ENVELOPE_SIZE], fields, payload)?)
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Verifies the checksum of a dequeued message when the queue has them.
+    ///
+    /// # Errors
+    /// Raises `CorruptMessage` if the checksum does not match.
+    fn check_message(&self, prefix: &[u8], payload: &[u8]) -> PyResult<()> {
+        self.meta
+            .verify_checksum(prefix, payload)
+            .map_err(|(stored, computed)| {
+                CorruptMessage::new_err(format!(
+                    "Message in queue '{}' is corrupt: stored checksum {:#010x}, computed \
+                     {:#010x}",
+                    self.name, stored, computed
+                ))
+            })
+    }
+}
+
+/// Returns the number of elements in `lane`.
+pub fn lane_len(lane: &MpmcQueueOnBuffer) -> usize {
+    let head = lane.header().dequeue_pos.load(Ordering::Acquire);
+    let tail = lane.header().enqueue_pos.load(Ordering::Acquire);
+    tail.saturating_sub(head)
+}
+
+thread_local! {
+    /// Whether this thread is the main Python thread, with the process it was found in.
+    static MAIN_THREAD: Cell<Option<(u32, bool)>> = const { Cell::new(None) };
+}
+
+/// Runs the Python signal handlers if `SIGNAL_CHECK` passed since `last`, briefly
+/// taking the GIL, so that a pending `KeyboardInterrupt` ends a wait.
+///
+/// Only the main thread runs signal handlers, so other threads never take the GIL
+/// here once they know they are not it, which keeps their spinning waits from
+/// stalling behind it.
+///
+/// # Errors
+/// Returns the exception raised by a signal handler.
+pub fn check_signals(last: &mut Instant) -> PyResult<()> {
+    if last.elapsed() < SIGNAL_CHECK {
+        return Ok(());
+    }
+    *last = Instant::now();
+    let pid = fork::pid();
+    if MAIN_THREAD.get() == Some((pid, false)) {
+        return Ok(());
+    }
+    Python::with_gil(|py| {
+        if MAIN_THREAD
+            .get()
+            .is_none_or(|(found_in, _)| found_in != pid)
+        {
+            let threading = py.import("threading")?;
+            let main = threading.call_method0("main_thread")?;
+            MAIN_THREAD.set(Some((
+                pid,
+                main.is(&threading.call_method0("current_thread")?),
+            )));
+        }
+        py.check_signals()
+    })
+}
+
+/// Copies the keyword arguments of `Queue.create()` or `Queue.open()` and adds `mode`.
+///
+/// # Errors
+/// Raises `TypeError` if `options` already chooses between creating and opening.
+fn mode_options<'py>(
+    py: Python<'py>,
+    options: Option<&Bound<'py, PyDict>>,
+    mode: &str,
+) -> PyResult<Bound<'py, PyDict>> {
+    let options = options.map_or_else(|| Ok(PyDict::new(py)), |options| options.copy())?;
+    for key in ["create", "mode"] {
+        if options.contains(key)? {
+            return Err(PyTypeError::new_err(format!(
+                "Queue.{}() does not accept '{}'",
+                mode, key
+            )));
+        }
+    }
+    options.set_item("mode", mode)?;
+    Ok(options)
+}
+
+/// Creates the shared memory segment `name` of `size` bytes.
+///
+/// # Errors
+/// Raises `AlreadyExists` if the name is taken, or `OSError` if the segment does not
+/// fit in the shared memory left or cannot be created.
+pub fn create_shmem(name: &str, size: usize) -> PyResult<Shmem> {
+    if !segment::exists(name)? {
+        segment::check_space(name, size)?;
+    }
+    ShmemConf::new()
+        .os_id(name)
+        .size(size)
+        .create()
+        .map_err(|e| create_error(name, size, e))
+}
+
+/// Converts a failure to create the segment `name` of `size` bytes into a Python
+/// exception.
+fn create_error(name: &str, size: usize, error: ShmemError) -> PyErr {
+    match error {
+        e @ ShmemError::MappingIdExists => {
+            AlreadyExists::new_err(format!("Failed to create shared memory '{}': {}", name, e))
+        }
+        e => PyOSError::new_err(format!(
+            "Failed to create shared memory '{}' of {} bytes: {}",
+            name, size, e
+        )),
+    }
+}
+
+/// Opens the existing shared memory segment `name`.
+pub fn open_shmem(name: &str) -> PyResult<Shmem> {
+    ShmemConf::new()
+        .os_id(name)
+        .open()
+        .map_err(|e| PyOSError::new_err(format!("Failed to open shared memory '{}': {}", name, e)))
+}
+
+/// Creates the shared memory segment `name` of `size` bytes, or opens it if another
+/// process created it first.
+///
+/// # Returns
+/// The segment and whether this call created it.
+fn open_or_create_shmem(name: &str, size: usize) -> PyResult<(Shmem, bool)> {
+    let start = Instant::now();
+    if !segment::exists(name)? {
+        segment::check_space(name, size)?;
+    }
+    loop {
+        match ShmemConf::new().os_id(name).size(size).create() {
+            Ok(shmem) => return Ok((shmem, true)),
+            Err(ShmemError::MappingIdExists) => {}
+            Err(e) => return Err(create_error(name, size, e)),
+        }
+        // Opening fails while the creator has not sized the segment yet, or after
+        // it removed the segment again; both resolve on a later attempt.
+        match open_shmem(name) {
+            Ok(shmem) => return Ok((shmem, false)),
+            Err(e) if start.elapsed() >= INIT_TIMEOUT => return Err(e),
+            Err(_) => std::thread::sleep(Duration::from_millis(1)),
+        }
+    }
+}
+
+/// Waits until the creator of the queue `name` finished initializing `header`, and
+/// checks that it holds an intact queue of this layout version.
+///
+/// # Errors
+/// Raises `OSError` if the segment holds no queue, was created by a zeroq build with
+/// another layout version, has a header that fails its checksum, or is still not
+/// ready after `INIT_TIMEOUT`.
+fn wait_ready(name: &str, header: &crate::mpmc_queue::MpmcQueueHeader) -> PyResult<()> {
+    let start = Instant::now();
+    while header.ready.load(Ordering::Acquire) == 0 {
+        // The creator zeroes the segment first, so any other magic is not a queue.
+        if !matches!(header.magic.load(Ordering::Relaxed), 0 | QUEUE_MAGIC) {
+            return Err(region::holds_no(name, "zeroq queue"));
+        }
+        if start.elapsed() >= INIT_TIMEOUT {
+            return Err(PyOSError::new_err(format!(
+                "Queue '{}' was not initialized within {} s; its creator may have crashed",
+                name,
+                INIT_TIMEOUT.as_secs()
+            )));
+        }
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    if header.magic.load(Ordering::Relaxed) != QUEUE_MAGIC {
+        return Err(region::holds_no(name, "zeroq queue"));
+    }
+    if header.layout_version as usize != LAYOUT_VERSION {
+        return Err(PyOSError::new_err(format!(
+            "Queue '{}' has layout version {}, but this build of zeroq reads version {}; \
+             attach with the zeroq version that created it",
+            name, header.layout_version, LAYOUT_VERSION
+        )));
+    }
+    header.verify_checksum().map_err(|e| {
+        PyOSError::new_err(format!(
+            "Queue '{}' has a corrupt header ({}); the segment was overwritten or \
+             truncated",
+            name, e
+        ))
+    })
+}
+
+/// Checks that a queue header fits at `offset` within a segment of `len` bytes.
+fn check_offset(name: &str, offset: usize, len: usize) -> PyResult<()> {
+    let header_size = std::mem::size_of::<crate::mpmc_queue::MpmcQueueHeader>();
+    if offset.saturating_add(header_size) > len {
+        return Err(PyValueError::new_err(format!(
+            "offset {} is out of bounds for shared memory '{}' of {} bytes",
+            offset, name, len
+        )));
+    }
+    Ok(())
+}
+
+/// Summarizes an audit report for error messages.
+fn describe_audit(report: &AuditReport) -> String {
+    if !report.positions_valid {
+        return format!(
+            "invalid positions (head={}, tail={})",
+            report.head, report.tail
+        );
+    }
+    let cells = report
+        .inconsistencies
+        .iter()
+        .take(8)
+        .map(|c| {
+            format!(
+                "cell {}: sequence {} (expected {})",
+                c.index, c.sequence, c.expected
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!(
+        "{} inconsistent cells ({})",
+        report.inconsistencies.len(),
+        cells
+    )
+}
+
+impl Drop for Queue {
+    fn drop(&mut self) {
+        if self.closed.load(Ordering::Relaxed) {
+            return;
+        }
+        self.closed.store(true, Ordering::Relaxed);
+        self.stop_readiness();
+        if let Some(registration) = self.registration.get_mut().unwrap().take() {
+            registration.release(self.queue.header());
+        }
+        if let Some(shmem) = self.shared_mem.take() {
+            shmem.touch();
         }
-        self.closed.store(true, Ordering::Relaxed);
-        self.shared_mem.take();
     }
 }
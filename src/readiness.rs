@@ -0,0 +1,171 @@
+//! Readiness file descriptors for plugging queues into event loops.
+//!
+//! File descriptors cannot be shared through the queue header, so each
+//! handle gets its own socket pair, kept readable by a watcher thread while
+//! the queue holds messages, or has free slots. The thread follows the
+//! wake-up signals of the header, so it reacts to producers and consumers in
+//! every process.
+
+use crate::fork;
+use crate::futex::{self, WaitSignal, Waiter};
+use crate::mpmc_queue::MpmcQueueHeader;
+use crate::py_queue::PARK_RECHECK;
+use std::io::{self, Read, Write};
+use std::os::fd::{AsRawFd, RawFd};
+use std::os::unix::net::UnixStream;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// Queue state that makes a readiness descriptor readable.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Condition {
+    /// Any lane holds a message.
+    Messages,
+    /// The main lane has a free slot.
+    Space,
+}
+
+/// A file descriptor that is readable while a queue meets a [`Condition`].
+pub struct Readiness {
+    reader: UnixStream,
+    main: &'static MpmcQueueHeader,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+    generation: u64,
+}
+
+impl Readiness {
+    /// Starts watching `lanes`, main lane first.
+    ///
+    /// # Arguments
+    /// - `lanes`: Headers of the lanes whose messages make the descriptor readable;
+    ///   only the main lane counts for [`Condition::Space`].
+    /// - `condition`: State in which the descriptor is readable.
+    /// - `interval`: Polling interval where cross-process wake-ups are unsupported.
+    ///
+    /// # Safety
+    /// The headers must stay mapped until the returned value is dropped.
+    ///
+    /// # Errors
+    /// Returns the I/O error raised while creating the socket pair or the thread.
+    pub unsafe fn spawn(
+        lanes: Vec<&'static MpmcQueueHeader>,
+        condition: Condition,
+        interval: Duration,
+    ) -> io::Result<Self> {
+        let (reader, writer) = UnixStream::pair()?;
+        reader.set_nonblocking(true)?;
+        writer.set_nonblocking(true)?;
+        let drain = reader.try_clone()?;
+        let stop = Arc::new(AtomicBool::new(false));
+        let main = lanes[0];
+        let thread = std::thread::Builder::new()
+            .name("zeroq-readiness".into())
+            .spawn({
+                let stop = stop.clone();
+                move || watch(&lanes, condition, writer, drain, &stop, interval)
+            })?;
+        Ok(Self {
+            reader,
+            main,
+            stop,
+            thread: Some(thread),
+            generation: fork::generation(),
+        })
+    }
+
+    /// Returns whether the watcher was started by the parent process, before
+    /// `fork()`. The thread did not survive the fork, so the descriptor no longer
+    /// follows the queue.
+    pub fn inherited(&self) -> bool {
+        self.generation != fork::generation()
+    }
+
+    /// Returns the descriptor to register with `select`, `poll` or `epoll`.
+    pub fn fileno(&self) -> RawFd {
+        self.reader.as_raw_fd()
+    }
+}
+
+impl Drop for Readiness {
+    fn drop(&mut self) {
+        if self.inherited() {
+            // The handle refers to a thread of the parent, which must not be joined.
+            std::mem::forget(self.thread.take());
+            return;
+        }
+        self.stop.store(true, Ordering::Release);
+        // Waking every waiter is harmless: they all re-check their condition.
+        self.main.not_empty.notify();
+        self.main.not_full.notify();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Writes a byte to `writer` whenever `condition` starts to hold and reads it
+/// back through `drain` once it no longer does, until `stop` is set.
+fn watch(
+    lanes: &[&MpmcQueueHeader],
+    condition: Condition,
+    mut writer: UnixStream,
+    mut drain: UnixStream,
+    stop: &AtomicBool,
+    interval: Duration,
+) {
+    let main = lanes[0];
+    let holds = || match condition {
+        Condition::Messages => lanes.iter().any(|header| occupancy(header) > 0),
+        Condition::Space => occupancy(main) <= main.buffer_mask,
+    };
+    let (rising, falling) = match condition {
+        Condition::Messages => (&main.not_empty, &main.not_full),
+        Condition::Space => (&main.not_full, &main.not_empty),
+    };
+    while wait_until(rising, stop, interval, holds) {
+        let _ = writer.write(&[1]);
+        // Urgent-lane gets only signal their own lane, so those are picked up
+        // by the periodic re-check.
+        if !wait_until(falling, stop, interval, || !holds()) {
+            break;
+        }
+        let mut buf = [0u8; 16];
+        while matches!(drain.read(&mut buf), Ok(n) if n > 0) {}
+    }
+}
+
+/// Returns the number of messages in the lane of `header`.
+fn occupancy(header: &MpmcQueueHeader) -> usize {
+    let head = header.dequeue_pos.load(Ordering::Acquire);
+    let tail = header.enqueue_pos.load(Ordering::Acquire);
+    tail.saturating_sub(head)
+}
+
+/// Waits on `signal` until `ready` holds.
+///
+/// # Returns
+/// `true` once `ready` holds, `false` if `stop` was set first.
+fn wait_until(
+    signal: &WaitSignal,
+    stop: &AtomicBool,
+    interval: Duration,
+    ready: impl Fn() -> bool,
+) -> bool {
+    let waiter = futex::SUPPORTED.then(|| signal.register());
+    loop {
+        let epoch = waiter.as_ref().map(Waiter::epoch);
+        if stop.load(Ordering::Acquire) {
+            return false;
+        }
+        if ready() {
+            return true;
+        }
+        match waiter.as_ref().zip(epoch) {
+            Some((waiter, epoch)) => waiter.wait(epoch, PARK_RECHECK),
+            None => std::thread::sleep(interval),
+        }
+    }
+}
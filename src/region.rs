@@ -0,0 +1,260 @@
+//! Building blocks shared by the structures that live in a segment of their own next
+//! to queues: `Pool`, `Deque`, `Stack`, `LvcSlot`, `ConflatingQueue`, `Bus`,
+//! `Router`, `SpscQueue` and `ShardedQueue`.
+//!
+//! Each such segment starts with a header whose first word is a magic number, stored
+//! last by the creator, so that handles attaching concurrently wait for it.
+
+use crate::fork;
+use crate::futex::{self, WaitSignal, Waiter};
+use crate::poison;
+use crate::py_queue::{check_signals, open_shmem, INIT_TIMEOUT, SIGNAL_CHECK};
+use crate::shmem_wrapper::ShmemWrapper;
+use pyo3::exceptions::{PyOSError, PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Opens the segment `name` and waits until its creator stored `magic`.
+///
+/// # Errors
+/// Raises `OSError` if the segment cannot be opened, is smaller than `min_len`, or
+/// holds something other than `what`.
+pub fn attach(
+    py: Python<'_>,
+    name: &str,
+    magic: u64,
+    min_len: usize,
+    what: &str,
+) -> PyResult<ShmemWrapper> {
+    let shmem = ShmemWrapper::new(open_shmem(name)?);
+    if shmem.len() < min_len.max(size_of::<AtomicU64>()) {
+        return Err(holds_no(name, what));
+    }
+    let word = unsafe { &*(shmem.as_ptr() as *const AtomicU64) };
+    let start = Instant::now();
+    let found = py.allow_threads(|| loop {
+        let found = word.load(Ordering::Acquire);
+        if found != 0 || start.elapsed() >= INIT_TIMEOUT {
+            return found;
+        }
+        std::thread::sleep(Duration::from_millis(1));
+    });
+    if found != magic {
+        return Err(holds_no(name, what));
+    }
+    Ok(shmem)
+}
+
+/// Returns the error for a segment `name` that does not hold `what`.
+pub fn holds_no(name: &str, what: &str) -> PyErr {
+    PyOSError::new_err(format!("Shared memory '{}' holds no {}", name, what))
+}
+
+/// Retries `attempt` until it returns a value or fails, parking on `signal` in
+/// between.
+///
+/// `timeout` is in seconds: `None` waits indefinitely and `0` tries once.
+///
+/// # Errors
+/// Raises `ValueError` for a negative timeout, `timed_out()` once the timeout
+/// elapsed, the error of `attempt`, or the exception of a signal handler that ran
+/// while waiting.
+pub fn wait_for<T: Send>(
+    py: Python<'_>,
+    signal: &WaitSignal,
+    timeout: Option<f64>,
+    mut attempt: impl FnMut() -> PyResult<Option<T>> + Send,
+    timed_out: impl FnOnce() -> PyErr + Send,
+) -> PyResult<T> {
+    let timeout = timeout
+        .map(|t| {
+            Duration::try_from_secs_f64(t)
+                .map_err(|_| PyValueError::new_err("timeout must be a non-negative number"))
+        })
+        .transpose()?;
+    py.allow_threads(|| {
+        let waiter = futex::SUPPORTED.then(|| signal.register());
+        let start = Instant::now();
+        let mut signals_checked = Instant::now();
+        loop {
+            let epoch = waiter.as_ref().map(Waiter::epoch);
+            if let Some(value) = attempt()? {
+                return Ok(value);
+            }
+            let park = match timeout {
+                Some(timeout) => match timeout.checked_sub(start.elapsed()) {
+                    Some(left) if !left.is_zero() => left.min(SIGNAL_CHECK),
+                    _ => return Err(timed_out()),
+                },
+                None => SIGNAL_CHECK,
+            };
+            check_signals(&mut signals_checked)?;
+            match (&waiter, epoch) {
+                (Some(waiter), Some(epoch)) => waiter.wait(epoch, park),
+                _ => std::thread::sleep(park.min(Duration::from_millis(1))),
+            }
+        }
+    })
+}
+
+/// Index terminating a [`TaggedList`].
+pub const NIL: u32 = u32::MAX;
+
+/// A lock-free singly linked list of indices in shared memory, a Treiber stack.
+///
+/// The head holds a tag in its high half, bumped on every change so that a stale
+/// compare-and-swap fails, and the index of the first entry in its low half. The links
+/// live with the entries and are passed in as `next`.
+#[repr(C)]
+pub struct TaggedList {
+    head: AtomicU64,
+}
+
+impl TaggedList {
+    /// Makes `first` the first entry, or empties the list with [`NIL`]; only for a
+    /// list no other handle uses yet.
+    pub fn init(&self, first: u32) {
+        self.head.store(first as u64, Ordering::Relaxed);
+    }
+
+    /// Pushes `index` onto the list.
+    pub fn push<'a>(&self, index: u32, next: impl Fn(u32) -> &'a AtomicU32) {
+        let mut head = self.head.load(Ordering::Acquire);
+        loop {
+            next(index).store(head as u32, Ordering::Relaxed);
+            let new = ((head >> 32).wrapping_add(1) << 32) | index as u64;
+            match self
+                .head
+                .compare_exchange_weak(head, new, Ordering::AcqRel, Ordering::Acquire)
+            {
+                Ok(_) => return,
+                Err(current) => head = current,
+            }
+        }
+    }
+
+    /// Pops the first entry, if the list is not empty.
+    pub fn pop<'a>(&self, next: impl Fn(u32) -> &'a AtomicU32) -> Option<u32> {
+        let mut head = self.head.load(Ordering::Acquire);
+        loop {
+            let index = head as u32;
+            if index == NIL {
+                return None;
+            }
+            let new =
+                ((head >> 32).wrapping_add(1) << 32) | next(index).load(Ordering::Relaxed) as u64;
+            match self
+                .head
+                .compare_exchange_weak(head, new, Ordering::AcqRel, Ordering::Acquire)
+            {
+                Ok(_) => return Some(index),
+                Err(current) => head = current,
+            }
+        }
+    }
+}
+
+/// Lock word of a [`SpinLock`] whose owner exited mid-section, for good.
+const POISONED: u64 = u64::MAX;
+
+/// A spinlock in shared memory, for critical sections that only copy an element.
+///
+/// The lock word holds the process id of the owner in its low half and the inode of
+/// its PID namespace in its high half, or zero while the lock is free, so that
+/// waiters can tell when the owner exited while holding it.
+#[repr(C)]
+pub struct SpinLock {
+    owner: AtomicU64,
+}
+
+/// What waiters do with a [`SpinLock`] whose owner exited while holding it.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Abandoned {
+    /// Take the lock over, for critical sections that the next one completes.
+    TakeOver,
+    /// Poison the lock, for critical sections that may leave the structure
+    /// inconsistent when cut short.
+    Poison,
+}
+
+impl SpinLock {
+    /// Spins, then yields, until the lock is taken; it is released when the guard is
+    /// dropped.
+    ///
+    /// Must be called with the GIL released. While waiting, checks for signals every
+    /// `SIGNAL_CHECK`, and whether the owner exited; owners in another PID namespace
+    /// are never known to have exited.
+    ///
+    /// # Arguments
+    /// * `abandoned` - What to do once the owner exited while holding the lock.
+    ///
+    /// # Errors
+    /// Raises `RuntimeError` if the lock is, or becomes, poisoned, or returns the
+    /// exception of a signal handler that ran while waiting.
+    pub fn lock(&self, abandoned: Abandoned) -> PyResult<SpinGuard<'_>> {
+        let word = (u64::from(poison::pid_namespace()) << 32) | u64::from(fork::pid());
+        let mut spins = 0u32;
+        let mut checked = Instant::now();
+        let mut signals_checked = Instant::now();
+        let mut owner = 0;
+        while let Err(current) =
+            self.owner
+                .compare_exchange(owner, word, Ordering::Acquire, Ordering::Relaxed)
+        {
+            owner = 0;
+            if current == POISONED {
+                return Err(PyRuntimeError::new_err(
+                    "A process exited while holding the lock of this structure, which \
+                     may be inconsistent; remove and recreate it",
+                ));
+            }
+            if spins < 64 {
+                std::hint::spin_loop();
+                spins += 1;
+                continue;
+            }
+            std::thread::yield_now();
+            if checked.elapsed() >= SIGNAL_CHECK {
+                checked = Instant::now();
+                if owner_exited(current) {
+                    match abandoned {
+                        Abandoned::TakeOver => owner = current,
+                        Abandoned::Poison => {
+                            let _ = self.owner.compare_exchange(
+                                current,
+                                POISONED,
+                                Ordering::Relaxed,
+                                Ordering::Relaxed,
+                            );
+                            continue;
+                        }
+                    }
+                }
+            }
+            check_signals(&mut signals_checked)?;
+        }
+        Ok(SpinGuard { lock: self })
+    }
+
+    /// Returns whether the lock is held by a process that exited.
+    pub fn abandoned(&self) -> bool {
+        owner_exited(self.owner.load(Ordering::Acquire))
+    }
+}
+
+/// Returns whether the owner in a [`SpinLock`] word is known to have exited.
+fn owner_exited(word: u64) -> bool {
+    word != 0 && word != POISONED && poison::owner_exited(word as u32, (word >> 32) as u32)
+}
+
+/// A held [`SpinLock`].
+pub struct SpinGuard<'a> {
+    lock: &'a SpinLock,
+}
+
+impl Drop for SpinGuard<'_> {
+    fn drop(&mut self) {
+        self.lock.owner.store(0, Ordering::Release);
+    }
+}
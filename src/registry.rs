@@ -0,0 +1,135 @@
+//! Registry of the handles attached to a queue, kept in its header so that
+//! operators can tell which processes use the queue and whether they still run.
+//!
+//! Every handle claims one word of [`MpmcQueueHeader::processes`], holding its
+//! process id in the upper half and its role bits in the lower half; a zero
+//! word is free. Words left behind by processes that exited without closing
+//! their handles are reclaimed once the table is otherwise full.
+
+use crate::fork;
+use crate::mpmc_queue::MpmcQueueHeader;
+use crate::poison::process_exited;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::sync::atomic::Ordering;
+
+/// Role bit of handles that put messages.
+pub const ROLE_PRODUCER: u32 = 1;
+/// Role bit of handles that get messages.
+pub const ROLE_CONSUMER: u32 = 2;
+
+/// Returns the role bits of a handle declaring `role`.
+///
+/// # Errors
+/// Raises `ValueError` for an unknown role.
+pub fn roles_from_name(role: Option<&str>) -> PyResult<u32> {
+    match role {
+        None => Ok(0),
+        Some("producer") => Ok(ROLE_PRODUCER),
+        Some("consumer") => Ok(ROLE_CONSUMER),
+        Some("both") => Ok(ROLE_PRODUCER | ROLE_CONSUMER),
+        Some(other) => Err(PyValueError::new_err(format!(
+            "Unknown role '{}': expected 'producer', 'consumer' or 'both'",
+            other
+        ))),
+    }
+}
+
+/// Entry claimed by a handle in the registry of its queue.
+pub struct Registration {
+    index: usize,
+    word: u64,
+}
+
+impl Registration {
+    /// Claims an entry for a handle of the current process with `roles`, taking over
+    /// entries of exited processes once no entry is free.
+    ///
+    /// # Returns
+    /// `None` if every entry belongs to a live handle.
+    pub fn claim(header: &MpmcQueueHeader, roles: u32) -> Option<Self> {
+        let word = (fork::pid() as u64) << 32 | roles as u64;
+        let claim = |index: usize, current: u64| {
+            header.processes[index]
+                .compare_exchange(current, word, Ordering::AcqRel, Ordering::Relaxed)
+                .is_ok()
+        };
+        let free = (0..header.processes.len()).find(|&index| claim(index, 0));
+        let index = free.or_else(|| {
+            (0..header.processes.len()).find(|&index| {
+                let current = header.processes[index].load(Ordering::Acquire);
+                process_exited((current >> 32) as u32) && claim(index, current)
+            })
+        })?;
+        Some(Self { index, word })
+    }
+
+    /// Releases the entry, unless the handle was inherited through `fork()`, as the
+    /// entry then belongs to the parent.
+    pub fn release(self, header: &MpmcQueueHeader) {
+        if (self.word >> 32) as u32 == fork::pid() {
+            let _ = header.processes[self.index].compare_exchange(
+                self.word,
+                0,
+                Ordering::AcqRel,
+                Ordering::Relaxed,
+            );
+        }
+    }
+}
+
+/// Handles that one process has registered with a queue.
+pub struct AttachedProcess {
+    pub pid: u32,
+    pub roles: u32,
+    pub handles: usize,
+    pub alive: bool,
+}
+
+impl AttachedProcess {
+    /// Converts the entry into a Python dictionary.
+    ///
+    /// # Errors
+    /// Returns an error if a dictionary item cannot be set.
+    pub fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let roles = [(ROLE_PRODUCER, "producer"), (ROLE_CONSUMER, "consumer")]
+            .into_iter()
+            .filter(|(bit, _)| self.roles & bit != 0)
+            .map(|(_, name)| name)
+            .collect::<Vec<_>>();
+        let dict = PyDict::new(py);
+        dict.set_item("pid", self.pid)?;
+        dict.set_item("roles", roles)?;
+        dict.set_item("handles", self.handles)?;
+        dict.set_item("alive", self.alive)?;
+        Ok(dict)
+    }
+}
+
+/// Lists the processes registered with the queue of `header`, ordered by process
+/// id, with the roles of all their handles combined.
+pub fn attached_processes(header: &MpmcQueueHeader) -> Vec<AttachedProcess> {
+    let mut processes: Vec<AttachedProcess> = Vec::new();
+    for entry in &header.processes {
+        let word = entry.load(Ordering::Acquire);
+        let (pid, roles) = ((word >> 32) as u32, word as u32);
+        if pid == 0 {
+            continue;
+        }
+        match processes.iter_mut().find(|process| process.pid == pid) {
+            Some(process) => {
+                process.roles |= roles;
+                process.handles += 1;
+            }
+            None => processes.push(AttachedProcess {
+                pid,
+                roles,
+                handles: 1,
+                alive: !process_exited(pid),
+            }),
+        }
+    }
+    processes.sort_by_key(|process| process.pid);
+    processes
+}
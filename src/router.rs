@@ -0,0 +1,256 @@
+//! Several named queues, topics, inside one shared-memory segment, so that a
+//! deployment manages one segment name instead of one per queue.
+//!
+//! The segment starts with a [`RouterHeader`] and a directory of one [`TopicEntry`]
+//! per topic, followed by the queues themselves, each at a cache-line aligned offset.
+//! The creator lays out and initializes every topic; handles to a topic are plain
+//! `Queue` handles opened at its offset.
+
+use crate::mpmc_queue::MpmcQueueHeader;
+use crate::py_queue::Queue;
+use crate::region;
+use crate::shmem_wrapper::ShmemWrapper;
+use pyo3::exceptions::{PyKeyError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Marks segments holding a router.
+const MAGIC: u64 = u64::from_le_bytes(*b"ZQROUT01");
+
+/// Longest name of a topic in bytes.
+const TOPIC_NAME: usize = 48;
+
+/// Options of a topic that determine the size of its queue.
+const LAYOUT_OPTIONS: [&str; 8] = [
+    "element_size",
+    "capacity",
+    "metadata",
+    "headers_size",
+    "sequence_bits",
+    "encryption",
+    "urgent_lane",
+    "pad_slots",
+];
+
+/// Header of a router segment.
+#[repr(C, align(64))]
+struct RouterHeader {
+    magic: AtomicU64,
+    topics: u64,
+}
+
+/// A topic in the directory.
+#[repr(C)]
+struct TopicEntry {
+    offset: u64,
+    name_len: u32,
+    _reserved: u32,
+    name: [u8; TOPIC_NAME],
+}
+
+/// Returns `offset` rounded up to where a queue may start.
+fn align(offset: usize) -> usize {
+    offset.next_multiple_of(align_of::<MpmcQueueHeader>().max(64))
+}
+
+/// Named queues sharing one shared-memory segment.
+///
+/// The handle that creates the router removes its segment when garbage-collected.
+#[pyclass(frozen, module = "zeroq")]
+pub struct Router {
+    name: String,
+    /// Keeps the segment alive while the creating handle exists.
+    _shmem: ShmemWrapper,
+    /// Name and offset of each topic, in directory order.
+    topics: Vec<(String, usize)>,
+}
+
+#[pymethods]
+impl Router {
+    /// Creates or attaches to a router.
+    ///
+    /// # Arguments
+    /// - `name` (str): Shared memory segment name.
+    /// - `topics` (dict[str, dict], optional): Options of each topic's queue, passed
+    ///   to the `Queue` constructor; `element_size` and `capacity` are required, and
+    ///   the layout options fix its size (required if creating).
+    /// - `create` (bool, default=True): Whether to create a new router.
+    ///
+    /// # Errors
+    /// Raises `ValueError` for invalid topics or options, `AlreadyExists` if a router
+    /// is to be created under a name that is taken, or `OSError` if the segment cannot
+    /// be created or opened or holds no router.
+    #[new]
+    #[pyo3(signature = (name, topics=None, create=true))]
+    fn new(
+        py: Python<'_>,
+        name: String,
+        topics: Option<&Bound<'_, PyDict>>,
+        create: bool,
+    ) -> PyResult<Self> {
+        if !create {
+            let shmem = region::attach(py, &name, MAGIC, size_of::<RouterHeader>(), "router")?;
+            let header = unsafe { &*(shmem.as_ptr() as *const RouterHeader) };
+            let count = header.topics as usize;
+            let directory = count
+                .checked_mul(size_of::<TopicEntry>())
+                .and_then(|size| size.checked_add(size_of::<RouterHeader>()));
+            if directory.is_none_or(|size| size > shmem.len()) {
+                return Err(region::holds_no(&name, "router"));
+            }
+            let topics = (0..count)
+                .map(|index| {
+                    let entry = unsafe {
+                        &*(shmem
+                            .as_ptr()
+                            .add(size_of::<RouterHeader>() + index * size_of::<TopicEntry>())
+                            as *const TopicEntry)
+                    };
+                    let len = (entry.name_len as usize).min(TOPIC_NAME);
+                    let topic = String::from_utf8_lossy(&entry.name[..len]).into_owned();
+                    (topic, entry.offset as usize)
+                })
+                .collect();
+            return Ok(Self {
+                name,
+                _shmem: shmem,
+                topics,
+            });
+        }
+        let topics = topics
+            .filter(|topics| !topics.is_empty())
+            .ok_or_else(|| PyValueError::new_err("topics required when create=true"))?;
+        let required_size = wrap_pyfunction!(crate::py_layout::required_size, py)?;
+        let mut offset = align(size_of::<RouterHeader>() + topics.len() * size_of::<TopicEntry>());
+        let mut layout = Vec::with_capacity(topics.len());
+        for (topic, options) in topics {
+            let topic: String = topic.extract()?;
+            if topic.is_empty() || topic.len() > TOPIC_NAME {
+                return Err(PyValueError::new_err(format!(
+                    "Topic names take 1 to {} bytes, got '{}'",
+                    TOPIC_NAME, topic
+                )));
+            }
+            let options = options.downcast::<PyDict>().map_err(|_| {
+                PyValueError::new_err(format!(
+                    "Options of topic '{}' must be a dict of Queue arguments",
+                    topic
+                ))
+            })?;
+            let sizing = PyDict::new(py);
+            for key in LAYOUT_OPTIONS {
+                if let Some(value) = options.get_item(key)? {
+                    sizing.set_item(key, value)?;
+                }
+            }
+            for key in ["element_size", "capacity"] {
+                if !sizing.contains(key)? {
+                    return Err(PyValueError::new_err(format!(
+                        "Topic '{}' requires {}",
+                        topic, key
+                    )));
+                }
+            }
+            let size: usize = required_size.call((), Some(&sizing))?.extract()?;
+            layout.push((topic, offset, options.clone()));
+            offset = align(offset + size);
+        }
+        let shmem = ShmemWrapper::new(crate::py_queue::create_shmem(&name, offset)?);
+        let header = unsafe { &mut *(shmem.as_ptr() as *mut RouterHeader) };
+        header.topics = layout.len() as u64;
+        for (index, (topic, offset, _)) in layout.iter().enumerate() {
+            let entry = unsafe {
+                &mut *(shmem
+                    .as_ptr()
+                    .add(size_of::<RouterHeader>() + index * size_of::<TopicEntry>())
+                    as *mut TopicEntry)
+            };
+            entry.offset = *offset as u64;
+            entry.name_len = topic.len() as u32;
+            entry.name[..topic.len()].copy_from_slice(topic.as_bytes());
+        }
+        let queue = py.get_type::<Queue>();
+        for (_, offset, options) in &layout {
+            let options = options.copy()?;
+            options.set_item("create", false)?;
+            options.set_item("offset", offset)?;
+            options.set_item("adopt", true)?;
+            queue
+                .call((&name,), Some(&options))?
+                .call_method1("close", (false,))?;
+        }
+        header.magic.store(MAGIC, Ordering::Release);
+        Ok(Self {
+            name,
+            _shmem: shmem,
+            topics: layout
+                .into_iter()
+                .map(|(topic, offset, _)| (topic, offset))
+                .collect(),
+        })
+    }
+
+    /// Name of the shared memory segment.
+    #[getter]
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Names of the topics, in the order they were created.
+    #[getter]
+    fn topics<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        PyList::new(py, self.topics.iter().map(|(topic, _)| topic))
+    }
+
+    fn __contains__(&self, topic: &str) -> bool {
+        self.topics.iter().any(|(name, _)| name == topic)
+    }
+
+    /// Opens a handle to the queue of a topic.
+    ///
+    /// # Arguments
+    /// - `topic` (str): Name of the topic.
+    /// - `**options`: Handle options passed to the `Queue` constructor, e.g.
+    ///   `serializer` or `keys`.
+    ///
+    /// # Returns
+    /// - (Queue): A handle to the topic's queue.
+    ///
+    /// # Errors
+    /// Raises `KeyError` if the router has no such topic, or the errors of the `Queue`
+    /// constructor.
+    #[pyo3(signature = (topic, **options))]
+    fn queue<'py>(
+        &self,
+        py: Python<'py>,
+        topic: &str,
+        options: Option<&Bound<'py, PyDict>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let offset = self
+            .topics
+            .iter()
+            .find(|(name, _)| name == topic)
+            .map(|(_, offset)| *offset)
+            .ok_or_else(|| {
+                PyKeyError::new_err(format!("Router '{}' has no topic '{}'", self.name, topic))
+            })?;
+        let options = options.map_or_else(|| Ok(PyDict::new(py)), |options| options.copy())?;
+        options.set_item("create", false)?;
+        options.set_item("offset", offset)?;
+        py.get_type::<Queue>().call((&self.name,), Some(&options))
+    }
+
+    fn __repr__(&self) -> String {
+        let topics: Vec<String> = self
+            .topics
+            .iter()
+            .map(|(topic, _)| format!("'{}'", topic))
+            .collect();
+        format!(
+            "Router(name='{}', topics=[{}])",
+            self.name,
+            topics.join(", ")
+        )
+    }
+}
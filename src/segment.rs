@@ -0,0 +1,516 @@
+//! Helpers for shared-memory segments that work without attaching a queue.
+
+use crate::shmem_wrapper::ShmemWrapper;
+use pyo3::exceptions::{PyFileNotFoundError, PyOSError, PyValueError};
+use pyo3::prelude::*;
+use shared_memory::{Shmem, ShmemConf, ShmemError};
+
+/// OS error code of a missing segment: `ENOENT` on Unix and
+/// `ERROR_FILE_NOT_FOUND` on Windows.
+const NOT_FOUND: u32 = 2;
+
+/// Opens the segment `name`, or returns `None` if it does not exist.
+pub fn open_segment(name: &str) -> PyResult<Option<Shmem>> {
+    match ShmemConf::new().os_id(name).open() {
+        Ok(shmem) => Ok(Some(shmem)),
+        Err(ShmemError::MapOpenFailed(NOT_FOUND)) => Ok(None),
+        Err(e) => Err(PyOSError::new_err(format!(
+            "Failed to open shared memory '{}': {}",
+            name, e
+        ))),
+    }
+}
+
+/// Checks that a new segment `name` of `size` bytes fits in the shared memory left
+/// on the platform.
+///
+/// On Linux, segments are files in the `/dev/shm` tmpfs, which accepts segments of
+/// any size but kills the process with `SIGBUS` once pages beyond its free space are
+/// touched. Elsewhere the OS refuses oversized segments itself.
+///
+/// # Errors
+/// Raises `OSError` if `/dev/shm` has less than `size` bytes free.
+pub fn check_space(name: &str, size: usize) -> PyResult<()> {
+    #[cfg(target_os = "linux")]
+    {
+        let mut stat = std::mem::MaybeUninit::<libc::statvfs>::uninit();
+        if unsafe { libc::statvfs(c"/dev/shm".as_ptr(), stat.as_mut_ptr()) } != 0 {
+            return Ok(());
+        }
+        let stat = unsafe { stat.assume_init() };
+        let free = stat.f_bavail.saturating_mul(stat.f_frsize);
+        if (size as u64) > free {
+            return Err(PyOSError::new_err(format!(
+                "Shared memory '{}' of {} bytes exceeds the {} bytes free in /dev/shm; \
+                 enlarge /dev/shm (e.g. docker run --shm-size) or lower capacity or \
+                 element_size",
+                name, size, free
+            )));
+        }
+    }
+    #[cfg(not(target_os = "linux"))]
+    let _ = (name, size);
+    Ok(())
+}
+
+/// Size of the huge pages segments are rounded up to with `huge_pages`.
+pub const HUGE_PAGE: usize = 2 << 20;
+
+/// Checks that the platform can back the segment of a queue with `backing` with huge
+/// pages, or that of a queue attached to through a descriptor if `fd`.
+///
+/// On Linux, `memfd` segments come from the hugetlb pool, checked when created, and
+/// segments attached through a descriptor come as their creator made them. Segments
+/// in `/dev/shm` get transparent huge pages as the `huge=` mount option of that tmpfs
+/// allows, unless `/sys/kernel/mm/transparent_hugepage/shmem_enabled` is `force` or
+/// `deny`; with `huge=advise`, mappings opt in with `madvise()`.
+///
+/// # Errors
+/// Raises `ValueError` for `backing='file'`, `OSError` if the kernel or the mount
+/// options of `/dev/shm` deny huge pages, or `NotImplementedError` on other
+/// platforms.
+pub fn check_huge_pages(backing: BackingKind, fd: bool) -> PyResult<()> {
+    #[cfg(target_os = "linux")]
+    {
+        match backing {
+            BackingKind::File => {
+                return Err(PyValueError::new_err(
+                    "huge_pages requires backing='shm' or 'memfd'",
+                ))
+            }
+            BackingKind::Memfd => return Ok(()),
+            BackingKind::Shm if fd => return Ok(()),
+            BackingKind::Shm => {}
+        }
+        const SETTING: &str = "/sys/kernel/mm/transparent_hugepage/shmem_enabled";
+        let setting = std::fs::read_to_string(SETTING).map_err(|e| {
+            PyOSError::new_err(format!(
+                "The kernel has no transparent huge pages for shared memory ({}: {})",
+                SETTING, e
+            ))
+        })?;
+        let current = setting
+            .split_whitespace()
+            .find(|mode| mode.starts_with('['))
+            .unwrap_or_default()
+            .trim_matches(['[', ']']);
+        match current {
+            "force" => return Ok(()),
+            "deny" => {
+                return Err(PyOSError::new_err(format!(
+                    "Huge pages are denied for shared memory: {} is 'deny'; use \
+                     backing='memfd' or create the queue without huge_pages",
+                    SETTING
+                )))
+            }
+            _ => {}
+        }
+        // The last mount on /dev/shm hides those before it.
+        let mounts = std::fs::read_to_string("/proc/self/mounts").unwrap_or_default();
+        let huge = mounts
+            .lines()
+            .rev()
+            .find_map(|line| {
+                let mut fields = line.split_whitespace();
+                let target = fields.nth(1)?;
+                let options = fields.nth(1)?;
+                (target == "/dev/shm").then_some(options)
+            })
+            .and_then(|options| options.split(',').find_map(|o| o.strip_prefix("huge=")))
+            .unwrap_or("never");
+        if !matches!(huge, "always" | "within_size" | "advise") {
+            return Err(PyOSError::new_err(format!(
+                "Huge pages are disabled for /dev/shm, which is mounted with huge={}; \
+                 remount it with huge=advise (e.g. mount -o remount,huge=advise /dev/shm), \
+                 use backing='memfd' or create the queue without huge_pages",
+                huge
+            )));
+        }
+        Ok(())
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = (backing, fd);
+        Err(pyo3::exceptions::PyNotImplementedError::new_err(
+            "huge_pages is only supported on Linux",
+        ))
+    }
+}
+
+/// Checks that the hugetlb pool has room for a `memfd` segment of `size` bytes.
+///
+/// # Errors
+/// Raises `OSError` if fewer 2 MiB huge pages are free than the segment needs.
+#[cfg(target_os = "linux")]
+fn check_free_huge_pages(size: usize) -> PyResult<()> {
+    const POOL: &str = "/sys/kernel/mm/hugepages/hugepages-2048kB";
+    let read = |file: &str| {
+        std::fs::read_to_string(format!("{}/{}", POOL, file))
+            .ok()
+            .and_then(|count| count.trim().parse::<usize>().ok())
+            .unwrap_or(0)
+    };
+    let free = read("free_hugepages").saturating_sub(read("resv_hugepages"));
+    let needed = size.div_ceil(HUGE_PAGE);
+    if needed > free {
+        return Err(PyOSError::new_err(format!(
+            "A memfd segment of {} bytes needs {} free 2 MiB huge pages, but {} are; \
+             reserve more (e.g. sysctl vm.nr_hugepages=N) or create the queue without \
+             huge_pages",
+            size, needed, free
+        )));
+    }
+    Ok(())
+}
+
+/// Asks the kernel to back the mapping of `shmem` with huge pages, and faults them
+/// all in if `prefault`, so that a new segment is allocated in huge pages up front.
+///
+/// # Errors
+/// Raises `OSError` if the kernel refuses the advice.
+pub fn use_huge_pages(name: &str, shmem: &ShmemWrapper, prefault: bool) -> PyResult<()> {
+    #[cfg(target_os = "linux")]
+    {
+        let ptr = shmem.as_ptr() as *mut u8;
+        if unsafe { libc::madvise(ptr as *mut libc::c_void, shmem.len(), libc::MADV_HUGEPAGE) } != 0
+        {
+            return Err(PyOSError::new_err(format!(
+                "Failed to back shared memory '{}' with huge pages: {}",
+                name,
+                std::io::Error::last_os_error()
+            )));
+        }
+        if prefault {
+            // Nothing else uses a new segment yet, and it reads as zeros.
+            for offset in (0..shmem.len()).step_by(HUGE_PAGE) {
+                unsafe { std::ptr::write_volatile(ptr.add(offset), 0) };
+            }
+        }
+        Ok(())
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = (name, shmem, prefault);
+        check_huge_pages(BackingKind::Shm, false)
+    }
+}
+
+/// Where the memory of a new queue comes from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BackingKind {
+    /// A segment named in `/dev/shm` or its platform equivalent.
+    Shm,
+    /// An anonymous `memfd` segment, reached through its file descriptor.
+    Memfd,
+    /// A regular file mapped from a path.
+    File,
+}
+
+impl BackingKind {
+    /// Parses the name of a backing as accepted by the `Queue` constructor.
+    ///
+    /// # Errors
+    /// Raises `ValueError` for unknown names.
+    pub fn from_name(name: &str) -> PyResult<Self> {
+        match name {
+            "shm" => Ok(Self::Shm),
+            "memfd" => Ok(Self::Memfd),
+            "file" => Ok(Self::File),
+            other => Err(PyValueError::new_err(format!(
+                "Unknown backing '{}': expected 'shm', 'memfd' or 'file'",
+                other
+            ))),
+        }
+    }
+}
+
+/// Creates and maps an anonymous `memfd` segment of `size` bytes labelled `name`,
+/// allocated from the hugetlb pool if `huge_pages`.
+///
+/// # Errors
+/// Raises `OSError` if the segment cannot be created or the pool has too few free
+/// huge pages, or `NotImplementedError` outside Linux.
+pub fn create_memfd(name: &str, size: usize, huge_pages: bool) -> PyResult<ShmemWrapper> {
+    #[cfg(target_os = "linux")]
+    if huge_pages {
+        check_free_huge_pages(size)?;
+    }
+    #[cfg(unix)]
+    return Ok(ShmemWrapper::from_mapping(
+        crate::mapping::Mapping::create_memfd(name, size, huge_pages)?,
+    ));
+    #[cfg(not(unix))]
+    {
+        let _ = (name, size, huge_pages);
+        Err(pyo3::exceptions::PyNotImplementedError::new_err(
+            "backing='memfd' is only supported on Linux",
+        ))
+    }
+}
+
+/// Creates the queue file `path` of `size` bytes and maps it.
+///
+/// Like segments in `/dev/shm`, the file is sparse, so the space left on its file
+/// system is checked up front.
+///
+/// # Errors
+/// Raises `AlreadyExists` if the file exists, `OSError` if it does not fit or
+/// cannot be created, or `NotImplementedError` on platforms without `mmap()`.
+pub fn create_file(name: &str, path: &std::path::Path, size: usize) -> PyResult<ShmemWrapper> {
+    check_file_space(path, size)?;
+    #[cfg(unix)]
+    return Ok(ShmemWrapper::from_mapping(
+        crate::mapping::Mapping::create_file(name, path, size)?,
+    ));
+    #[cfg(not(unix))]
+    {
+        let _ = name;
+        Err(pyo3::exceptions::PyNotImplementedError::new_err(
+            "backing='file' requires a platform with mmap()",
+        ))
+    }
+}
+
+/// Opens and maps the existing queue file `path`.
+///
+/// # Errors
+/// Raises `OSError` if the file cannot be opened, or `NotImplementedError` on
+/// platforms without `mmap()`.
+pub fn open_file(name: &str, path: &std::path::Path) -> PyResult<ShmemWrapper> {
+    #[cfg(unix)]
+    return Ok(ShmemWrapper::from_mapping(
+        crate::mapping::Mapping::open_file(name, path)?,
+    ));
+    #[cfg(not(unix))]
+    {
+        let _ = (name, path);
+        Err(pyo3::exceptions::PyNotImplementedError::new_err(
+            "backing='file' requires a platform with mmap()",
+        ))
+    }
+}
+
+/// Checks that a new queue file `path` of `size` bytes fits in the space left on its
+/// file system, like `check_space()` does for `/dev/shm`.
+///
+/// # Errors
+/// Raises `OSError` if the file system has less than `size` bytes free.
+fn check_file_space(path: &std::path::Path, size: usize) -> PyResult<()> {
+    #[cfg(target_os = "linux")]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        let directory = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => std::path::Path::new("."),
+        };
+        let Ok(directory_c) = std::ffi::CString::new(directory.as_os_str().as_bytes()) else {
+            return Ok(());
+        };
+        let mut stat = std::mem::MaybeUninit::<libc::statvfs>::uninit();
+        if unsafe { libc::statvfs(directory_c.as_ptr(), stat.as_mut_ptr()) } != 0 {
+            return Ok(());
+        }
+        let stat = unsafe { stat.assume_init() };
+        let free = stat.f_bavail.saturating_mul(stat.f_frsize);
+        if (size as u64) > free {
+            return Err(PyOSError::new_err(format!(
+                "Queue file '{}' of {} bytes exceeds the {} bytes free in {}",
+                path.display(),
+                size,
+                free,
+                directory.display()
+            )));
+        }
+    }
+    #[cfg(not(target_os = "linux"))]
+    let _ = (path, size);
+    Ok(())
+}
+
+/// Maps the segment behind the file descriptor `fd`, e.g. one received over a Unix
+/// socket.
+///
+/// # Errors
+/// Raises `OSError` if `fd` cannot be mapped, or `NotImplementedError` on platforms
+/// without file descriptors.
+pub fn map_fd(name: &str, fd: i32) -> PyResult<ShmemWrapper> {
+    #[cfg(unix)]
+    return Ok(ShmemWrapper::from_mapping(
+        crate::mapping::Mapping::from_fd(name, fd)?,
+    ));
+    #[cfg(not(unix))]
+    {
+        let _ = (name, fd);
+        Err(pyo3::exceptions::PyNotImplementedError::new_err(
+            "fd requires a platform with file descriptors",
+        ))
+    }
+}
+
+/// Largest NUMA node number a segment can be bound to.
+#[cfg(target_os = "linux")]
+const MAX_NUMA_NODE: usize = 1023;
+
+/// How strictly the pages of a segment stay on its NUMA node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NumaPolicy {
+    /// Allocate only on the node, failing when it runs out of memory.
+    Bind,
+    /// Allocate on the node while it has free memory, elsewhere after that.
+    Preferred,
+}
+
+impl NumaPolicy {
+    /// Parses the name of a policy as accepted by the `Queue` constructor.
+    ///
+    /// # Errors
+    /// Raises `ValueError` for unknown names.
+    pub fn from_name(name: &str) -> PyResult<Self> {
+        match name {
+            "bind" => Ok(Self::Bind),
+            "preferred" => Ok(Self::Preferred),
+            other => Err(PyValueError::new_err(format!(
+                "Unknown numa_policy '{}': expected 'bind' or 'preferred'",
+                other
+            ))),
+        }
+    }
+}
+
+/// Checks that `node` is a NUMA node with memory that segments can be bound to.
+///
+/// # Errors
+/// Raises `ValueError` if the node does not exist, or `NotImplementedError` outside
+/// Linux.
+pub fn check_numa_node(node: usize) -> PyResult<()> {
+    #[cfg(target_os = "linux")]
+    {
+        let online = std::fs::read_to_string("/sys/devices/system/node/has_memory")
+            .unwrap_or_else(|_| "0".to_string());
+        let found = node <= MAX_NUMA_NODE
+            && online.trim().split(',').any(|range| {
+                let mut bounds = range.split('-').map(|bound| bound.parse::<usize>());
+                match (bounds.next(), bounds.next()) {
+                    (Some(Ok(first)), None) => node == first,
+                    (Some(Ok(first)), Some(Ok(last))) => (first..=last).contains(&node),
+                    _ => false,
+                }
+            });
+        if !found {
+            return Err(PyValueError::new_err(format!(
+                "NUMA node {} does not exist or has no memory; nodes with memory: {}",
+                node,
+                online.trim()
+            )));
+        }
+        Ok(())
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = node;
+        Err(pyo3::exceptions::PyNotImplementedError::new_err(
+            "numa_node is only supported on Linux",
+        ))
+    }
+}
+
+/// Binds the pages of the segment mapped by `shmem` to NUMA node `node`, moving those
+/// already allocated.
+///
+/// The policy belongs to the segment rather than the mapping, so it applies to pages
+/// first touched by any process.
+///
+/// # Errors
+/// Raises `OSError` if the kernel refuses the policy, e.g. in a container without
+/// the `CAP_SYS_NICE` capability needed to move pages.
+pub fn bind_to_node(
+    name: &str,
+    shmem: &ShmemWrapper,
+    node: usize,
+    policy: NumaPolicy,
+) -> PyResult<()> {
+    #[cfg(target_os = "linux")]
+    {
+        /// `MPOL_MF_MOVE` of `<linux/mempolicy.h>`, which the libc crate lacks.
+        const MPOL_MF_MOVE: libc::c_ulong = 1 << 1;
+        const BITS: usize = u64::BITS as usize;
+        let mut mask = [0u64; (MAX_NUMA_NODE + 1) / BITS];
+        mask[node / BITS] |= 1 << (node % BITS);
+        let mode = match policy {
+            NumaPolicy::Bind => libc::MPOL_BIND,
+            NumaPolicy::Preferred => libc::MPOL_PREFERRED,
+        };
+        // The kernel reads one bit less than `maxnode`.
+        let result = unsafe {
+            libc::syscall(
+                libc::SYS_mbind,
+                shmem.as_ptr(),
+                shmem.len(),
+                mode,
+                mask.as_ptr(),
+                mask.len() * BITS + 1,
+                MPOL_MF_MOVE,
+            )
+        };
+        if result != 0 {
+            return Err(PyOSError::new_err(format!(
+                "Failed to bind shared memory '{}' to NUMA node {}: {}",
+                name,
+                node,
+                std::io::Error::last_os_error()
+            )));
+        }
+        Ok(())
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = (name, shmem, policy);
+        check_numa_node(node)
+    }
+}
+
+/// Returns whether a shared-memory segment named `name` exists.
+///
+/// # Errors
+/// Raises `OSError` if the segment exists but cannot be opened, e.g. for lack of
+/// permissions.
+#[pyfunction]
+pub fn exists(name: &str) -> PyResult<bool> {
+    Ok(open_segment(name)?.is_some())
+}
+
+/// Removes the shared-memory segment named `name`, e.g. one left behind by a crashed
+/// creator, so that a queue of that name can be created again.
+///
+/// Processes still attached keep their mapping, but no new handle can attach to it.
+///
+/// # Arguments
+/// - `name` (str): Name of the segment, as given to the `Queue` constructor.
+/// - `missing_ok` (bool, default=False): Do nothing if the segment does not exist.
+///
+/// # Errors
+/// Raises `FileNotFoundError` if the segment does not exist and `missing_ok` is false,
+/// or `OSError` if it cannot be removed.
+#[pyfunction]
+#[pyo3(signature = (name, missing_ok=false))]
+pub fn unlink(name: &str, missing_ok: bool) -> PyResult<()> {
+    let Some(mut shmem) = open_segment(name)? else {
+        if missing_ok {
+            return Ok(());
+        }
+        return Err(PyFileNotFoundError::new_err(format!(
+            "Shared memory '{}' does not exist",
+            name
+        )));
+    };
+    // An owning mapping unlinks the segment when dropped, but ignores failures.
+    shmem.set_owner(true);
+    drop(shmem);
+    if open_segment(name)?.is_some() {
+        return Err(PyOSError::new_err(format!(
+            "Failed to remove shared memory '{}'",
+            name
+        )));
+    }
+    Ok(())
+}
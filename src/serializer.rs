@@ -0,0 +1,292 @@
+//! Serialization of Python objects for queues in object mode.
+//!
+//! Objects are encoded by a codec: pickle, which only Python reads back, or a
+//! self-describing format such as JSON, MessagePack or CBOR that consumers in
+//! other languages can decode as well, or any pair of callables.
+//!
+//! Objects travel in the payload as a frame: their length as a little-endian
+//! u32 and the serialized bytes. With out-of-band buffers, a table of their
+//! side segments follows: their count as a u8, then for each the length of
+//! its name as a u8, the name and the size of the buffer as a little-endian
+//! u64. Zero padding fills the rest of the payload, and reads as an empty table.
+
+use crate::out_of_band::{OutOfBand, OutOfBandBuffer, Segments};
+use pyo3::exceptions::{PyImportError, PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict, PyList, PyMemoryView, PyString};
+use std::sync::{Arc, Mutex};
+
+/// Size of the length field in front of every frame.
+pub const FRAME_HEADER: usize = 4;
+
+/// A serializer turning objects into payloads and back.
+pub enum Serializer {
+    /// The `pickle` module, with its highest protocol.
+    Pickle {
+        dumps: PyObject,
+        loads: PyObject,
+        protocol: PyObject,
+        out_of_band: Option<OutOfBand>,
+    },
+    /// A pair of callables turning objects into `bytes` or `str` and back.
+    Codec { encode: PyObject, decode: PyObject },
+}
+
+impl Serializer {
+    /// Looks up the serializer named `name`.
+    ///
+    /// # Arguments
+    /// - `out_of_band`: Where to send large buffers out of band, if at all.
+    ///
+    /// # Errors
+    /// Raises `ValueError` for unknown names.
+    pub fn from_name(py: Python<'_>, name: &str, out_of_band: Option<OutOfBand>) -> PyResult<Self> {
+        match name {
+            "pickle" => {
+                let pickle = py.import("pickle")?;
+                Ok(Self::Pickle {
+                    dumps: pickle.getattr("dumps")?.unbind(),
+                    loads: pickle.getattr("loads")?.unbind(),
+                    protocol: pickle.getattr("HIGHEST_PROTOCOL")?.unbind(),
+                    out_of_band,
+                })
+            }
+            other => Err(PyValueError::new_err(format!(
+                "Unknown serializer '{}': expected 'pickle'",
+                other
+            ))),
+        }
+    }
+
+    /// Builds the serializer of `codec`: the name of a built-in codec, or an object
+    /// with `encode()` and `decode()` methods.
+    ///
+    /// # Arguments
+    /// - `out_of_band`: Where to send large buffers out of band, if at all.
+    ///
+    /// # Returns
+    /// The serializer, or `None` for the `"raw"` codec.
+    ///
+    /// # Errors
+    /// Raises `ValueError` for unknown names or if `out_of_band` is given for another
+    /// codec than pickle, `ImportError` if the package of a built-in codec is missing,
+    /// or `TypeError` if `codec` is neither a name nor a codec object.
+    pub fn from_codec(
+        py: Python<'_>,
+        codec: &Bound<'_, PyAny>,
+        out_of_band: Option<OutOfBand>,
+    ) -> PyResult<Option<Self>> {
+        let (encode, decode) = match codec.downcast::<PyString>() {
+            Ok(name) => {
+                let (module, encode, decode) = match name.to_str()? {
+                    "raw" if out_of_band.is_some() => {
+                        return Err(PyValueError::new_err("out_of_band requires a serializer"))
+                    }
+                    "raw" => return Ok(None),
+                    "pickle" => return Self::from_name(py, "pickle", out_of_band).map(Some),
+                    "json" => ("json", "dumps", "loads"),
+                    "msgpack" => ("msgpack", "packb", "unpackb"),
+                    "cbor" => ("cbor2", "dumps", "loads"),
+                    other => {
+                        return Err(PyValueError::new_err(format!(
+                            "Unknown codec '{}': expected 'raw', 'pickle', 'json', \
+                             'msgpack' or 'cbor'",
+                            other
+                        )))
+                    }
+                };
+                let module = py.import(module).map_err(|_| {
+                    PyImportError::new_err(format!(
+                        "Codec '{}' requires the '{}' package",
+                        name, module
+                    ))
+                })?;
+                (module.getattr(encode)?, module.getattr(decode)?)
+            }
+            Err(_) => match (codec.getattr("encode"), codec.getattr("decode")) {
+                (Ok(encode), Ok(decode)) if encode.is_callable() && decode.is_callable() => {
+                    (encode, decode)
+                }
+                _ => {
+                    return Err(PyTypeError::new_err(
+                        "codec must be a codec name or an object with encode() and \
+                         decode() methods",
+                    ))
+                }
+            },
+        };
+        if out_of_band.is_some() {
+            return Err(PyValueError::new_err(
+                "out_of_band requires the pickle codec",
+            ));
+        }
+        Ok(Some(Self::Codec {
+            encode: encode.unbind(),
+            decode: decode.unbind(),
+        }))
+    }
+
+    /// Serializes `obj`, checking that its frame fits in `element_size` bytes.
+    ///
+    /// # Returns
+    /// The serialized bytes and the side segments of the buffers sent out of band.
+    ///
+    /// # Errors
+    /// Raises the error of the serializer, `ValueError` if the frame is too large, or
+    /// `OSError` if a side segment cannot be created.
+    pub fn encode<'py>(
+        &self,
+        obj: &Bound<'py, PyAny>,
+        element_size: usize,
+    ) -> PyResult<(Bound<'py, PyBytes>, Segments)> {
+        let py = obj.py();
+        let (data, segments) = match self {
+            Self::Pickle {
+                dumps,
+                protocol,
+                out_of_band,
+                ..
+            } => {
+                let buffers = Arc::new(Mutex::new(Vec::new()));
+                let kwargs = PyDict::new(py);
+                if let Some(out_of_band) = out_of_band {
+                    kwargs.set_item(
+                        "buffer_callback",
+                        out_of_band.callback(py, buffers.clone())?,
+                    )?;
+                }
+                let data = dumps.bind(py).call((obj, protocol), Some(&kwargs));
+                // Taken first, so that the segments are removed if pickling failed.
+                let segments = Segments::new(std::mem::take(&mut *buffers.lock().unwrap()));
+                (data?, segments)
+            }
+            Self::Codec { encode, .. } => (encode.bind(py).call1((obj,))?, Segments::default()),
+        };
+        let data = match data.downcast_into::<PyString>() {
+            Ok(text) => PyBytes::new(py, text.to_str()?.as_bytes()),
+            Err(e) => e.into_inner().downcast_into::<PyBytes>()?,
+        };
+        if segments.buffers().len() > u8::MAX as usize {
+            return Err(PyValueError::new_err(format!(
+                "Objects may hold at most {} out-of-band buffers",
+                u8::MAX
+            )));
+        }
+        let len = data.as_bytes().len();
+        let frame_len = FRAME_HEADER + len + table_len(segments.buffers());
+        if frame_len > element_size {
+            return Err(PyValueError::new_err(format!(
+                "Serialized object of {} bytes with {} bytes of framing does not fit in \
+                 elements of {} bytes",
+                len,
+                frame_len - len,
+                element_size
+            )));
+        }
+        Ok((data, segments))
+    }
+
+    /// Deserializes the object framed in `payload`, mapping its out-of-band buffers.
+    ///
+    /// # Errors
+    /// Raises `ValueError` if the payload holds no valid frame, `OSError` if a side
+    /// segment cannot be opened, or the error of the serializer.
+    pub fn decode<'py>(&self, py: Python<'py>, payload: &[u8]) -> PyResult<Bound<'py, PyAny>> {
+        let (data, buffers) = read_frame(payload)?;
+        let data = PyBytes::new(py, data);
+        match self {
+            Self::Pickle { loads, .. } => {
+                let loads = loads.bind(py);
+                if buffers.is_empty() {
+                    return loads.call1((data,));
+                }
+                let buffers = buffers
+                    .iter()
+                    .map(|(name, len)| {
+                        let buffer = Bound::new(py, OutOfBandBuffer::open(name, *len)?)?;
+                        PyMemoryView::from(buffer.as_any())
+                    })
+                    .collect::<PyResult<Vec<_>>>()?;
+                let kwargs = PyDict::new(py);
+                kwargs.set_item("buffers", PyList::new(py, buffers)?)?;
+                loads.call((data,), Some(&kwargs))
+            }
+            Self::Codec { decode, .. } => decode.bind(py).call1((data,)),
+        }
+    }
+}
+
+/// Returns the size of the table describing `buffers`, none if there are none.
+fn table_len(buffers: &[(String, usize)]) -> usize {
+    if buffers.is_empty() {
+        return 0;
+    }
+    1 + buffers
+        .iter()
+        .map(|(name, _)| 1 + name.len() + 8)
+        .sum::<usize>()
+}
+
+/// Writes `data` and the table of its out-of-band `buffers` as a frame filling the
+/// whole `payload`.
+pub fn write_frame(payload: &mut [u8], data: &[u8], buffers: &[(String, usize)]) {
+    let (header, rest) = payload.split_at_mut(FRAME_HEADER);
+    header.copy_from_slice(&(data.len() as u32).to_le_bytes());
+    let (dst, mut rest) = rest.split_at_mut(data.len());
+    dst.copy_from_slice(data);
+    let mut put = |bytes: &[u8]| {
+        let (dst, tail) = std::mem::take(&mut rest).split_at_mut(bytes.len());
+        dst.copy_from_slice(bytes);
+        rest = tail;
+    };
+    if !buffers.is_empty() {
+        put(&[buffers.len() as u8]);
+        for (name, len) in buffers {
+            put(&[name.len() as u8]);
+            put(name.as_bytes());
+            put(&(*len as u64).to_le_bytes());
+        }
+    }
+    rest.fill(0);
+}
+
+/// Returns the data framed in `payload` and the names and sizes of its out-of-band
+/// buffers.
+///
+/// # Errors
+/// Raises `ValueError` if the frame exceeds the payload, e.g. for a message put by a
+/// handle without a serializer.
+#[allow(clippy::type_complexity)]
+fn read_frame(payload: &[u8]) -> PyResult<(&[u8], Vec<(String, usize)>)> {
+    let invalid = || PyValueError::new_err("The message holds no serialized object");
+    let header = payload.get(..FRAME_HEADER).ok_or_else(invalid)?;
+    let len = u32::from_le_bytes(header.try_into().unwrap()) as usize;
+    let data = payload[FRAME_HEADER..].get(..len).ok_or_else(invalid)?;
+    let mut rest = &payload[FRAME_HEADER + len..];
+    let count = match rest.split_first() {
+        Some((&count, tail)) => {
+            rest = tail;
+            count
+        }
+        None => 0,
+    };
+    let mut buffers = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        let name_len = take(&mut rest, 1).ok_or_else(invalid)?[0] as usize;
+        let name = take(&mut rest, name_len).ok_or_else(invalid)?;
+        let name = std::str::from_utf8(name).map_err(|_| invalid())?;
+        let len = take(&mut rest, 8).ok_or_else(invalid)?;
+        buffers.push((
+            name.to_owned(),
+            u64::from_le_bytes(len.try_into().unwrap()) as usize,
+        ));
+    }
+    Ok((data, buffers))
+}
+
+/// Splits the first `n` bytes off `rest`, if it holds that many.
+fn take<'a>(rest: &mut &'a [u8], n: usize) -> Option<&'a [u8]> {
+    let (head, tail) = rest.split_at_checked(n)?;
+    *rest = tail;
+    Some(head)
+}
@@ -0,0 +1,394 @@
+//! An MPMC queue split into shards, for many producers and consumers that would
+//! otherwise all contend on the positions of a single queue.
+//!
+//! The segment starts with a [`ShardedHeader`], followed by `shards` independent MPMC
+//! queues at cache-line aligned offsets. Each thread has a home shard, picked per
+//! process and thread unless the handle fixes one: producers put to it and move on to
+//! the next shards only while it is full, and consumers get from it and steal from
+//! the next shards only while it is empty. Elements are stored with a 4-byte length,
+//! so items may be shorter than `element_size`.
+//!
+//! Order is kept within a shard only. Blocked producers and consumers park on the
+//! signals of the header, which every put and get notify whatever shard it used.
+
+use crate::errors::{Empty, Full, InvalidParameters};
+use crate::futex::WaitSignal;
+use crate::mpmc_queue::{
+    compute_lane_offset, validate_capacity, MpmcQueueOnBuffer, SlotLayout, CACHE_LINE,
+};
+use crate::py_queue::lane_len;
+use crate::region;
+use crate::shmem_wrapper::ShmemWrapper;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use std::borrow::Cow;
+use std::mem::MaybeUninit;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+/// Marks segments holding a sharded queue.
+const MAGIC: u64 = u64::from_le_bytes(*b"ZQSHRD01");
+
+/// Size of the length in front of each element.
+const LEN: usize = size_of::<u32>();
+
+/// Largest number of shards.
+const MAX_SHARDS: usize = 256;
+
+/// Header of a sharded queue segment.
+#[repr(C, align(64))]
+struct ShardedHeader {
+    magic: AtomicU64,
+    element_size: u64,
+    shards: u64,
+    capacity: u64,
+    not_empty: WaitSignal,
+    not_full: WaitSignal,
+}
+
+/// Number of threads that asked for a home shard in this process.
+static THREADS: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    /// Index of the calling thread among those of its process that use shards.
+    static THREAD: usize = THREADS.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Returns a number spreading threads of every process across shards.
+fn thread_token() -> usize {
+    let thread = THREAD.with(|thread| *thread);
+    (std::process::id() as usize).wrapping_mul(0x9E37_79B9) ^ thread
+}
+
+/// Returns the slot layout of the shards, holding the length and the element.
+fn shard_layout(element_size: usize) -> SlotLayout {
+    SlotLayout {
+        element_size: LEN + element_size,
+        ..Default::default()
+    }
+}
+
+/// Returns the distance between consecutive shards.
+fn shard_stride(element_size: usize, capacity: usize) -> usize {
+    compute_lane_offset(&shard_layout(element_size), capacity).next_multiple_of(CACHE_LINE)
+}
+
+/// A bounded MPMC queue of byte strings split into shards, each a queue of its own.
+///
+/// The handle that creates the queue removes its segment when garbage-collected.
+#[pyclass(frozen, module = "zeroq")]
+pub struct ShardedQueue {
+    name: String,
+    shmem: ShmemWrapper,
+    element_size: usize,
+    capacity: usize,
+    shards: Vec<MpmcQueueOnBuffer<'static>>,
+    /// Home shard of every thread using this handle, or `None` to pick one per thread.
+    shard: Option<usize>,
+}
+
+impl ShardedQueue {
+    fn header(&self) -> &ShardedHeader {
+        unsafe { &*(self.shmem.as_ptr() as *const ShardedHeader) }
+    }
+
+    /// Returns the home shard of the calling thread.
+    fn home(&self) -> usize {
+        self.shard
+            .unwrap_or_else(|| thread_token() % self.shards.len())
+    }
+
+    /// Returns the shards starting at the home shard of the calling thread.
+    fn in_home_order(&self) -> impl Iterator<Item = &MpmcQueueOnBuffer<'static>> {
+        let home = self.home();
+        self.shards[home..].iter().chain(&self.shards[..home])
+    }
+
+    /// Copies `data` into the first shard with room, unless every shard is full.
+    fn try_put(&self, data: &[u8]) -> Option<()> {
+        let put = self.in_home_order().any(|shard| {
+            shard
+                .enqueue_with(|_, slot| {
+                    slot[..LEN].copy_from_slice(&(data.len() as u32).to_le_bytes());
+                    slot[LEN..LEN + data.len()].copy_from_slice(data);
+                })
+                .is_ok()
+        });
+        if put {
+            self.header().not_empty.notify();
+        }
+        put.then_some(())
+    }
+
+    /// Copies out and removes the oldest element of the first shard holding one,
+    /// unless every shard is empty.
+    fn try_get(&self) -> Option<Vec<u8>> {
+        let data = self.in_home_order().find_map(|shard| {
+            shard
+                .dequeue_with(|_, slot| {
+                    let size = u32::from_le_bytes(slot[..LEN].try_into().unwrap()) as usize;
+                    slot[LEN..LEN + size.min(self.element_size)].to_vec()
+                })
+                .ok()
+        })?;
+        self.header().not_full.notify();
+        Some(data)
+    }
+}
+
+#[pymethods]
+impl ShardedQueue {
+    /// Creates or attaches to a sharded queue in shared memory.
+    ///
+    /// # Arguments
+    /// - `name` (str): Shared memory segment name.
+    /// - `element_size` (int): Maximum size of an element in bytes (required if
+    ///   creating).
+    /// - `capacity` (int): Number of slots of each shard, a power of two (required if
+    ///   creating).
+    /// - `shards` (int): Number of shards (required if creating).
+    /// - `shard` (int, optional): Home shard of this handle; each thread picks one of
+    ///   its own if omitted.
+    /// - `create` (bool, default=True): Whether to create a new queue.
+    ///
+    /// # Errors
+    /// Raises `ValueError` for invalid parameters, `AlreadyExists` if a queue is to be
+    /// created under a name that is taken, or `OSError` if the segment cannot be
+    /// created or opened or holds no sharded queue.
+    #[new]
+    #[pyo3(signature = (name, element_size=None, capacity=None, shards=None, shard=None, create=true))]
+    fn new(
+        py: Python<'_>,
+        name: String,
+        element_size: Option<usize>,
+        capacity: Option<usize>,
+        shards: Option<usize>,
+        shard: Option<usize>,
+        create: bool,
+    ) -> PyResult<Self> {
+        let shmem = if create {
+            let element_size = element_size
+                .filter(|size| (1..=u32::MAX as usize - LEN).contains(size))
+                .ok_or_else(|| {
+                    PyValueError::new_err(format!(
+                        "element_size between 1 and {} required when create=true",
+                        u32::MAX as usize - LEN
+                    ))
+                })?;
+            let capacity = capacity
+                .ok_or_else(|| PyValueError::new_err("capacity required when create=true"))?;
+            validate_capacity(&shard_layout(element_size), capacity)?;
+            let shards = shards
+                .filter(|shards| (1..=MAX_SHARDS).contains(shards))
+                .ok_or_else(|| {
+                    PyValueError::new_err(format!(
+                        "shards between 1 and {} required when create=true",
+                        MAX_SHARDS
+                    ))
+                })?;
+            let size = shard_stride(element_size, capacity)
+                .checked_mul(shards)
+                .and_then(|size| size.checked_add(size_of::<ShardedHeader>()))
+                .ok_or_else(|| PyValueError::new_err("The queue is too large"))?;
+            let shmem = ShmemWrapper::new(crate::py_queue::create_shmem(&name, size)?);
+            let header = unsafe { &mut *(shmem.as_ptr() as *mut ShardedHeader) };
+            header.element_size = element_size as u64;
+            header.shards = shards as u64;
+            header.capacity = capacity as u64;
+            for index in 0..shards {
+                unsafe {
+                    MpmcQueueOnBuffer::init_on_buffer(
+                        shard_buffer(
+                            shmem.as_ptr() as *mut u8,
+                            index,
+                            shard_stride(element_size, capacity),
+                        ),
+                        &shard_layout(element_size),
+                        capacity,
+                        true,
+                    )?
+                };
+            }
+            header.magic.store(MAGIC, Ordering::Release);
+            shmem
+        } else {
+            let shmem = region::attach(
+                py,
+                &name,
+                MAGIC,
+                size_of::<ShardedHeader>(),
+                "sharded queue",
+            )?;
+            let header = unsafe { &*(shmem.as_ptr() as *const ShardedHeader) };
+            let found = (
+                header.element_size as usize,
+                header.capacity as usize,
+                header.shards as usize,
+            );
+            let valid = found.1.is_power_of_two()
+                && (1..=MAX_SHARDS).contains(&found.2)
+                && shard_stride(found.0, found.1)
+                    .checked_mul(found.2)
+                    .is_some_and(|size| size_of::<ShardedHeader>() + size <= shmem.len());
+            if !valid {
+                return Err(region::holds_no(&name, "sharded queue"));
+            }
+            if element_size.is_some_and(|size| size != found.0)
+                || capacity.is_some_and(|capacity| capacity != found.1)
+                || shards.is_some_and(|shards| shards != found.2)
+            {
+                return Err(InvalidParameters::new_err(format!(
+                    "Queue '{}' exists with element_size {}, capacity {} and {} shards",
+                    name, found.0, found.1, found.2
+                )));
+            }
+            shmem
+        };
+        let header = unsafe { &*(shmem.as_ptr() as *const ShardedHeader) };
+        let (element_size, capacity) = (header.element_size as usize, header.capacity as usize);
+        let count = header.shards as usize;
+        if shard.is_some_and(|shard| shard >= count) {
+            return Err(PyValueError::new_err(format!(
+                "shard must be below the {} shards of queue '{}'",
+                count, name
+            )));
+        }
+        let shards = (0..count)
+            .map(|index| {
+                let stride = shard_stride(element_size, capacity);
+                Ok(unsafe {
+                    MpmcQueueOnBuffer::attach_on_buffer(shard_buffer(
+                        shmem.as_ptr() as *mut u8,
+                        index,
+                        stride,
+                    ))?
+                })
+            })
+            .collect::<PyResult<_>>()?;
+        Ok(Self {
+            name,
+            shmem,
+            element_size,
+            capacity,
+            shards,
+            shard,
+        })
+    }
+
+    /// Name of the shared memory segment.
+    #[getter]
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Maximum size of an element in bytes.
+    #[getter]
+    fn element_size(&self) -> usize {
+        self.element_size
+    }
+
+    /// Number of slots of each shard.
+    #[getter]
+    fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of shards.
+    #[getter]
+    fn shards(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Home shard of the calling thread.
+    #[getter]
+    fn shard(&self) -> usize {
+        self.home()
+    }
+
+    /// Number of elements in each shard, a snapshot that concurrent handles may change.
+    fn shard_lengths(&self) -> Vec<usize> {
+        self.shards.iter().map(lane_len).collect()
+    }
+
+    /// Number of elements, a snapshot that concurrent handles may change.
+    fn __len__(&self) -> usize {
+        self.shards.iter().map(lane_len).sum()
+    }
+
+    /// Adds an element to the home shard, or to the next shard with room while it is
+    /// full.
+    ///
+    /// # Arguments
+    /// - `item` (bytes): The element, at most `element_size` bytes.
+    /// - `timeout` (float, optional): Maximum time to wait for room in seconds; waits
+    ///   indefinitely if omitted, and does not wait if 0.
+    ///
+    /// # Errors
+    /// Raises `ValueError` if `item` is larger than an element, or `Full` if every
+    /// shard stays full beyond the timeout.
+    #[pyo3(signature = (item, timeout=None))]
+    fn put(&self, py: Python<'_>, item: Cow<[u8]>, timeout: Option<f64>) -> PyResult<()> {
+        if item.len() > self.element_size {
+            return Err(PyValueError::new_err(format!(
+                "Item of {} bytes does not fit in elements of {} bytes",
+                item.len(),
+                self.element_size
+            )));
+        }
+        region::wait_for(
+            py,
+            &self.header().not_full,
+            timeout,
+            || Ok(self.try_put(&item)),
+            || Full::new_err(format!("Queue '{}' is full", self.name)),
+        )
+    }
+
+    /// Removes and returns the oldest element of the home shard, or steals one from
+    /// the next shard holding any while it is empty.
+    ///
+    /// # Arguments
+    /// - `timeout` (float, optional): Maximum time to wait for an element in seconds;
+    ///   waits indefinitely if omitted, and does not wait if 0.
+    ///
+    /// # Errors
+    /// Raises `Empty` if every shard stays empty beyond the timeout.
+    #[pyo3(signature = (timeout=None))]
+    fn get<'py>(&self, py: Python<'py>, timeout: Option<f64>) -> PyResult<Bound<'py, PyBytes>> {
+        let data = region::wait_for(
+            py,
+            &self.header().not_empty,
+            timeout,
+            || Ok(self.try_get()),
+            || Empty::new_err(format!("Queue '{}' is empty", self.name)),
+        )?;
+        Ok(PyBytes::new(py, &data))
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ShardedQueue(name='{}', element_size={}, capacity={}, shards={}, len={})",
+            self.name,
+            self.element_size,
+            self.capacity,
+            self.shards.len(),
+            self.__len__()
+        )
+    }
+}
+
+/// Returns the buffer of shard `index` in the segment starting at `base`, `stride`
+/// bytes long.
+///
+/// # Safety
+/// The segment must hold the shard and outlive every use of the buffer.
+unsafe fn shard_buffer(
+    base: *mut u8,
+    index: usize,
+    stride: usize,
+) -> &'static mut [MaybeUninit<u8>] {
+    std::slice::from_raw_parts_mut(
+        base.add(size_of::<ShardedHeader>() + index * stride) as *mut MaybeUninit<u8>,
+        stride,
+    )
+}
@@ -1,9 +1,22 @@
+use crate::fork;
+#[cfg(unix)]
+use crate::mapping::Mapping;
 use shared_memory::Shmem;
 
+/// Where the memory of a segment comes from.
+enum Backing {
+    /// A segment opened by name, under `/dev/shm` on Linux.
+    Named(Shmem),
+    /// A segment mapped from a file descriptor, anonymous or a regular file.
+    #[cfg(unix)]
+    Mapped(Mapping),
+}
+
 /// A wrapper around `Shmem` to safely enable `Send` and `Sync` traits,
 /// allowing shared memory to be safely used across threads.
 pub struct ShmemWrapper {
-    shmem: Shmem,
+    backing: Backing,
+    generation: u64,
 }
 
 // Manually implementing `Send` and `Sync` because `Shmem` is not marked as such by default.
@@ -14,16 +27,95 @@ unsafe impl Sync for ShmemWrapper {}
 impl ShmemWrapper {
     /// Creates a new `ShmemWrapper` from an existing `Shmem` instance.
     pub fn new(shmem: Shmem) -> Self {
-        Self { shmem }
+        Self {
+            backing: Backing::Named(shmem),
+            generation: fork::generation(),
+        }
+    }
+
+    /// Creates a new `ShmemWrapper` from a segment mapped from a file descriptor.
+    #[cfg(unix)]
+    pub fn from_mapping(mapping: Mapping) -> Self {
+        Self {
+            backing: Backing::Mapped(mapping),
+            generation: fork::generation(),
+        }
+    }
+
+    /// Returns whether this mapping was inherited from the parent process through
+    /// `fork()`.
+    pub fn inherited(&self) -> bool {
+        self.generation != fork::generation()
     }
 
     /// Returns a raw pointer to the beginning of the shared memory region.
     pub fn as_ptr(&self) -> *const u8 {
-        self.shmem.as_ptr()
+        match &self.backing {
+            Backing::Named(shmem) => shmem.as_ptr(),
+            #[cfg(unix)]
+            Backing::Mapped(mapping) => mapping.as_ptr(),
+        }
     }
 
     /// Returns the total size of the shared memory region in bytes.
     pub fn len(&self) -> usize {
-        self.shmem.len()
+        match &self.backing {
+            Backing::Named(shmem) => shmem.len(),
+            #[cfg(unix)]
+            Backing::Mapped(mapping) => mapping.len(),
+        }
+    }
+
+    /// Returns the file descriptor of a segment mapped from one, or `None` for named
+    /// segments.
+    pub fn fd(&self) -> Option<i32> {
+        match &self.backing {
+            Backing::Named(_) => None,
+            #[cfg(unix)]
+            Backing::Mapped(mapping) => Some(mapping.fd()),
+        }
+    }
+
+    /// Returns whether this mapping created the segment and removes it when dropped.
+    ///
+    /// A mapping inherited through `fork()` never owns the segment: the parent does.
+    pub fn is_owner(&self) -> bool {
+        let owner = match &self.backing {
+            Backing::Named(shmem) => shmem.is_owner(),
+            #[cfg(unix)]
+            Backing::Mapped(mapping) => mapping.is_owner(),
+        };
+        owner && !self.inherited()
+    }
+
+    /// Records an attach or detach in the modification time of the segment,
+    /// which `python -m zeroq prune` reads as a heartbeat.
+    ///
+    /// Only Linux exposes named segments as files, under `/dev/shm`; failures are
+    /// ignored since the heartbeat is advisory.
+    pub fn touch(&self) {
+        #[cfg(target_os = "linux")]
+        if let Backing::Named(shmem) = &self.backing {
+            let path =
+                std::path::Path::new("/dev/shm").join(shmem.get_os_id().trim_start_matches('/'));
+            if let Ok(file) = std::fs::File::options().write(true).open(path) {
+                let _ = file.set_modified(std::time::SystemTime::now());
+            }
+        }
+    }
+}
+
+impl Drop for ShmemWrapper {
+    fn drop(&mut self) {
+        // Only unmap the copy of a forked child, leaving the segment to the parent.
+        if self.inherited() {
+            match &mut self.backing {
+                Backing::Named(shmem) => {
+                    shmem.set_owner(false);
+                }
+                #[cfg(unix)]
+                Backing::Mapped(mapping) => mapping.disown(),
+            }
+        }
     }
 }
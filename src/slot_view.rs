@@ -0,0 +1,187 @@
+use crate::py_queue::Queue;
+use pyo3::exceptions::PyBufferError;
+use pyo3::ffi;
+use pyo3::prelude::*;
+use pyo3::types::PyMemoryView;
+use std::ffi::{c_int, c_void};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// A payload accessed in place in shared memory.
+///
+/// Returned by `Queue.get_buffer()` for a dequeued message, and by
+/// `Queue.reserve()` for a message being written. The object exposes the
+/// payload through the buffer protocol, read-only for dequeued messages; the
+/// slot stays reserved until `release()` is called, which hands a dequeued
+/// slot back to producers and publishes a written one to consumers. Used as
+/// a context manager it yields a `memoryview` and releases the slot on exit.
+#[pyclass(frozen)]
+pub struct SlotView {
+    queue: Py<Queue>,
+    writable: bool,
+    urgent: bool,
+    pos: usize,
+    ptr: usize,
+    len: usize,
+    exports: AtomicUsize,
+    released: AtomicBool,
+    entered: Mutex<Option<Py<PyMemoryView>>>,
+}
+
+impl SlotView {
+    /// Wraps the payload of the slot dequeued at `pos` in the given lane.
+    pub fn dequeued(queue: Py<Queue>, urgent: bool, pos: usize, ptr: usize, len: usize) -> Self {
+        Self::new(queue, false, urgent, pos, ptr, len)
+    }
+
+    /// Wraps the payload of the slot reserved for enqueuing at `pos` in the main lane.
+    pub fn reserved(queue: Py<Queue>, pos: usize, ptr: usize, len: usize) -> Self {
+        Self::new(queue, true, false, pos, ptr, len)
+    }
+
+    fn new(
+        queue: Py<Queue>,
+        writable: bool,
+        urgent: bool,
+        pos: usize,
+        ptr: usize,
+        len: usize,
+    ) -> Self {
+        Self {
+            queue,
+            writable,
+            urgent,
+            pos,
+            ptr,
+            len,
+            exports: AtomicUsize::new(0),
+            released: AtomicBool::new(false),
+            entered: Mutex::new(None),
+        }
+    }
+
+    /// Returns whether the view was reserved by `queue` for enqueuing.
+    pub fn is_reservation_of(&self, queue: &Bound<'_, Queue>) -> bool {
+        self.writable && self.queue.is(queue)
+    }
+
+    /// Hands the slot over unless it was already released: a dequeued slot
+    /// goes back to producers, a reserved one is published to consumers.
+    fn commit(&self, py: Python<'_>) -> PyResult<()> {
+        if self.released.swap(true, Ordering::AcqRel) {
+            return Ok(());
+        }
+        let queue = self.queue.borrow(py);
+        if self.writable {
+            queue.commit_reservation(self.pos)
+        } else {
+            queue.commit_view(self.urgent, self.pos);
+            Ok(())
+        }
+    }
+}
+
+#[pymethods]
+impl SlotView {
+    /// Releases the slot, returning it to producers or, for a reservation,
+    /// publishing it to consumers.
+    ///
+    /// Calling it more than once has no effect.
+    ///
+    /// # Errors
+    /// Raises `BufferError` if memoryviews of the payload are still alive, or
+    /// `RuntimeError` if a reserved slot was reclaimed from this process before it
+    /// was published.
+    fn release(&self, py: Python<'_>) -> PyResult<()> {
+        let exports = self.exports.load(Ordering::Acquire);
+        if exports > 0 {
+            return Err(PyBufferError::new_err(format!(
+                "{} memoryview(s) of the slot are still alive; release them first",
+                exports
+            )));
+        }
+        self.commit(py)
+    }
+
+    /// Returns whether the slot was released.
+    #[getter]
+    fn released(&self) -> bool {
+        self.released.load(Ordering::Acquire)
+    }
+
+    fn __len__(&self) -> usize {
+        self.len
+    }
+
+    /// Writes into a reserved payload, as in `view[:] = data`.
+    fn __setitem__(
+        slf: &Bound<'_, Self>,
+        key: &Bound<'_, PyAny>,
+        value: &Bound<'_, PyAny>,
+    ) -> PyResult<()> {
+        let view = PyMemoryView::from(slf.as_any())?;
+        let result = view.set_item(key, value);
+        view.call_method0("release")?;
+        result
+    }
+
+    fn __enter__<'py>(slf: &Bound<'py, Self>) -> PyResult<Bound<'py, PyMemoryView>> {
+        let view = PyMemoryView::from(slf.as_any())?;
+        *slf.get().entered.lock().unwrap() = Some(view.clone().unbind());
+        Ok(view)
+    }
+
+    fn __exit__(
+        &self,
+        py: Python<'_>,
+        _exc_type: PyObject,
+        _exc_value: PyObject,
+        _traceback: PyObject,
+    ) -> PyResult<bool> {
+        let entered = self.entered.lock().unwrap().take();
+        if let Some(view) = entered {
+            view.bind(py).call_method0("release")?;
+        }
+        self.release(py)?;
+        Ok(false)
+    }
+
+    unsafe fn __getbuffer__(
+        slf: Bound<'_, Self>,
+        view: *mut ffi::Py_buffer,
+        flags: c_int,
+    ) -> PyResult<()> {
+        let this = slf.get();
+        if this.released.load(Ordering::Acquire) {
+            return Err(PyBufferError::new_err("the slot was already released"));
+        }
+        if ffi::PyBuffer_FillInfo(
+            view,
+            slf.as_ptr(),
+            this.ptr as *mut c_void,
+            this.len as ffi::Py_ssize_t,
+            c_int::from(!this.writable),
+            flags,
+        ) == -1
+        {
+            return Err(PyErr::fetch(slf.py()));
+        }
+        this.exports.fetch_add(1, Ordering::AcqRel);
+        Ok(())
+    }
+
+    unsafe fn __releasebuffer__(&self, _view: *mut ffi::Py_buffer) {
+        self.exports.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+impl Drop for SlotView {
+    fn drop(&mut self) {
+        // Memoryviews keep this object alive, so none can remain at this point.
+        // A reservation dropped uncommitted is published as it stands, since
+        // consumers cannot get past an unpublished slot.
+        Python::with_gil(|py| {
+            let _ = self.commit(py);
+        });
+    }
+}
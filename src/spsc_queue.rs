@@ -0,0 +1,294 @@
+//! A single-producer single-consumer queue in shared memory, for 1-to-1 pipelines
+//! that do not need the compare-and-swap loops and per-cell sequences of `Queue`.
+//!
+//! The segment starts with an [`SpscHeader`], followed by `capacity` slots of a
+//! 4-byte length and `element_size` bytes each. Each side owns one [`Index`] on a
+//! cache line of its own: the producer advances `tail` with a release store once an
+//! element is copied in, and the consumer advances `head` once it is copied out. Each
+//! side also caches the other's position, and only reloads it when the ring looks
+//! full or empty.
+
+use crate::errors::{Empty, Full, InvalidParameters};
+use crate::futex::WaitSignal;
+use crate::region;
+use crate::shmem_wrapper::ShmemWrapper;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use std::borrow::Cow;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Marks segments holding a single-producer single-consumer queue.
+const MAGIC: u64 = u64::from_le_bytes(*b"ZQSPSC01");
+
+/// Size of the length in front of each element.
+const LEN: usize = size_of::<u32>();
+
+/// The position of one side of the queue, written by that side only.
+#[repr(C, align(64))]
+struct Index {
+    /// Number of elements this side moved.
+    pos: AtomicU64,
+    /// Position of the other side as last loaded by this side.
+    peer: AtomicU64,
+}
+
+/// Header of a single-producer single-consumer queue segment.
+#[repr(C, align(64))]
+struct SpscHeader {
+    magic: AtomicU64,
+    element_size: u64,
+    capacity: u64,
+    not_empty: WaitSignal,
+    not_full: WaitSignal,
+    tail: Index,
+    head: Index,
+}
+
+/// Returns the distance between consecutive slots holding `element_size` bytes.
+fn stride(element_size: usize) -> usize {
+    (LEN + element_size).next_multiple_of(8)
+}
+
+/// A bounded FIFO queue of byte strings in shared memory, for exactly one producer
+/// and one consumer at a time.
+///
+/// Puts from several handles at once, or gets from several handles at once, corrupt
+/// the queue; use `Queue` for those. The handle that creates the queue removes its
+/// segment when garbage-collected.
+#[pyclass(frozen, module = "zeroq")]
+pub struct SpscQueue {
+    name: String,
+    shmem: ShmemWrapper,
+    element_size: usize,
+    capacity: usize,
+}
+
+impl SpscQueue {
+    fn header(&self) -> &SpscHeader {
+        unsafe { &*(self.shmem.as_ptr() as *const SpscHeader) }
+    }
+
+    /// Returns a pointer to the slot holding the element at position `pos`.
+    fn slot_ptr(&self, pos: u64) -> *mut u8 {
+        let index = (pos % self.capacity as u64) as usize;
+        unsafe {
+            self.shmem
+                .as_ptr()
+                .add(size_of::<SpscHeader>() + index * stride(self.element_size))
+                as *mut u8
+        }
+    }
+
+    /// Copies `data` into the next slot, unless the queue is full.
+    fn try_put(&self, data: &[u8]) -> Option<()> {
+        let header = self.header();
+        let tail = header.tail.pos.load(Ordering::Relaxed);
+        if tail - header.tail.peer.load(Ordering::Relaxed) >= self.capacity as u64 {
+            let head = header.head.pos.load(Ordering::Acquire);
+            header.tail.peer.store(head, Ordering::Relaxed);
+            if tail - head >= self.capacity as u64 {
+                return None;
+            }
+        }
+        let slot = self.slot_ptr(tail);
+        unsafe {
+            std::ptr::copy_nonoverlapping((data.len() as u32).to_le_bytes().as_ptr(), slot, LEN);
+            std::ptr::copy_nonoverlapping(data.as_ptr(), slot.add(LEN), data.len());
+        }
+        header.tail.pos.store(tail + 1, Ordering::Release);
+        header.not_empty.notify();
+        Some(())
+    }
+
+    /// Copies out and removes the oldest element, unless the queue is empty.
+    fn try_get(&self) -> Option<Vec<u8>> {
+        let header = self.header();
+        let head = header.head.pos.load(Ordering::Relaxed);
+        if header.head.peer.load(Ordering::Relaxed) == head {
+            let tail = header.tail.pos.load(Ordering::Acquire);
+            header.head.peer.store(tail, Ordering::Relaxed);
+            if tail == head {
+                return None;
+            }
+        }
+        let slot = self.slot_ptr(head);
+        let data = unsafe {
+            let size = u32::from_le_bytes(*(slot as *const [u8; LEN])) as usize;
+            std::slice::from_raw_parts(slot.add(LEN), size.min(self.element_size)).to_vec()
+        };
+        header.head.pos.store(head + 1, Ordering::Release);
+        header.not_full.notify();
+        Some(data)
+    }
+}
+
+#[pymethods]
+impl SpscQueue {
+    /// Creates or attaches to a single-producer single-consumer queue in shared
+    /// memory.
+    ///
+    /// # Arguments
+    /// - `name` (str): Shared memory segment name.
+    /// - `element_size` (int): Maximum size of an element in bytes (required if
+    ///   creating).
+    /// - `capacity` (int): Maximum number of elements (required if creating).
+    /// - `create` (bool, default=True): Whether to create a new queue.
+    ///
+    /// # Errors
+    /// Raises `ValueError` for invalid parameters, `AlreadyExists` if a queue is to be
+    /// created under a name that is taken, or `OSError` if the segment cannot be
+    /// created or opened or holds no such queue.
+    #[new]
+    #[pyo3(signature = (name, element_size=None, capacity=None, create=true))]
+    fn new(
+        py: Python<'_>,
+        name: String,
+        element_size: Option<usize>,
+        capacity: Option<usize>,
+        create: bool,
+    ) -> PyResult<Self> {
+        if !create {
+            let shmem = region::attach(
+                py,
+                &name,
+                MAGIC,
+                size_of::<SpscHeader>(),
+                "single-producer single-consumer queue",
+            )?;
+            let header = unsafe { &*(shmem.as_ptr() as *const SpscHeader) };
+            let found = (header.element_size as usize, header.capacity as usize);
+            if shmem.len() < size_of::<SpscHeader>() + found.1 * stride(found.0) {
+                return Err(region::holds_no(
+                    &name,
+                    "single-producer single-consumer queue",
+                ));
+            }
+            if element_size.is_some_and(|size| size != found.0)
+                || capacity.is_some_and(|capacity| capacity != found.1)
+            {
+                return Err(InvalidParameters::new_err(format!(
+                    "Queue '{}' exists with element_size {} and capacity {}",
+                    name, found.0, found.1
+                )));
+            }
+            return Ok(Self {
+                name,
+                shmem,
+                element_size: found.0,
+                capacity: found.1,
+            });
+        }
+        let element_size = element_size
+            .filter(|size| (1..=u32::MAX as usize).contains(size))
+            .ok_or_else(|| {
+                PyValueError::new_err(format!(
+                    "element_size between 1 and {} required when create=true",
+                    u32::MAX
+                ))
+            })?;
+        let capacity = capacity
+            .filter(|capacity| *capacity > 0)
+            .ok_or_else(|| PyValueError::new_err("capacity > 0 required when create=true"))?;
+        let size = capacity
+            .checked_mul(stride(element_size))
+            .and_then(|size| size.checked_add(size_of::<SpscHeader>()))
+            .ok_or_else(|| PyValueError::new_err("The queue is too large"))?;
+        let shmem = crate::py_queue::create_shmem(&name, size)?;
+        let queue = Self {
+            name,
+            shmem: ShmemWrapper::new(shmem),
+            element_size,
+            capacity,
+        };
+        let header = unsafe { &mut *(queue.shmem.as_ptr() as *mut SpscHeader) };
+        header.element_size = element_size as u64;
+        header.capacity = capacity as u64;
+        header.magic.store(MAGIC, Ordering::Release);
+        Ok(queue)
+    }
+
+    /// Name of the shared memory segment.
+    #[getter]
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Maximum size of an element in bytes.
+    #[getter]
+    fn element_size(&self) -> usize {
+        self.element_size
+    }
+
+    /// Maximum number of elements.
+    #[getter]
+    fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of elements, a snapshot that the other side may change.
+    fn __len__(&self) -> usize {
+        let header = self.header();
+        let head = header.head.pos.load(Ordering::Acquire);
+        let tail = header.tail.pos.load(Ordering::Acquire);
+        tail.saturating_sub(head) as usize
+    }
+
+    /// Adds an element; only one handle may put at a time.
+    ///
+    /// # Arguments
+    /// - `item` (bytes): The element, at most `element_size` bytes.
+    /// - `timeout` (float, optional): Maximum time to wait for room in seconds; waits
+    ///   indefinitely if omitted, and does not wait if 0.
+    ///
+    /// # Errors
+    /// Raises `ValueError` if `item` is larger than an element, or `Full` if the queue
+    /// stays full beyond the timeout.
+    #[pyo3(signature = (item, timeout=None))]
+    fn put(&self, py: Python<'_>, item: Cow<[u8]>, timeout: Option<f64>) -> PyResult<()> {
+        if item.len() > self.element_size {
+            return Err(PyValueError::new_err(format!(
+                "Item of {} bytes does not fit in elements of {} bytes",
+                item.len(),
+                self.element_size
+            )));
+        }
+        region::wait_for(
+            py,
+            &self.header().not_full,
+            timeout,
+            || Ok(self.try_put(&item)),
+            || Full::new_err(format!("Queue '{}' is full", self.name)),
+        )
+    }
+
+    /// Removes and returns the oldest element; only one handle may get at a time.
+    ///
+    /// # Arguments
+    /// - `timeout` (float, optional): Maximum time to wait for an element in seconds;
+    ///   waits indefinitely if omitted, and does not wait if 0.
+    ///
+    /// # Errors
+    /// Raises `Empty` if the queue stays empty beyond the timeout.
+    #[pyo3(signature = (timeout=None))]
+    fn get<'py>(&self, py: Python<'py>, timeout: Option<f64>) -> PyResult<Bound<'py, PyBytes>> {
+        let data = region::wait_for(
+            py,
+            &self.header().not_empty,
+            timeout,
+            || Ok(self.try_get()),
+            || Empty::new_err(format!("Queue '{}' is empty", self.name)),
+        )?;
+        Ok(PyBytes::new(py, &data))
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "SpscQueue(name='{}', element_size={}, capacity={}, len={})",
+            self.name,
+            self.element_size,
+            self.capacity,
+            self.__len__()
+        )
+    }
+}
@@ -0,0 +1,282 @@
+//! A lock-free LIFO stack in shared memory, e.g. for free lists or undo buffers shared
+//! between processes.
+//!
+//! The segment starts with a [`StackHeader`], followed by `capacity` nodes of a link,
+//! a 4-byte length and `element_size` bytes each. Every node is on one of two
+//! [`TaggedList`]s: the stack itself or the free nodes. A push takes a free node,
+//! fills it and links it on top; a pop unlinks the top node, copies it out and returns
+//! it to the free nodes.
+
+use crate::errors::{Empty, Full, InvalidParameters};
+use crate::futex::WaitSignal;
+use crate::region::{self, TaggedList, NIL};
+use crate::shmem_wrapper::ShmemWrapper;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use std::borrow::Cow;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+
+/// Marks segments holding a stack.
+const MAGIC: u64 = u64::from_le_bytes(*b"ZQSTCK01");
+
+/// Size of the link and the length in front of each element.
+const NODE_HEADER: usize = 2 * size_of::<u32>();
+
+/// Header of a stack segment.
+#[repr(C, align(64))]
+struct StackHeader {
+    magic: AtomicU64,
+    element_size: u64,
+    capacity: u64,
+    top: TaggedList,
+    free: TaggedList,
+    /// Number of elements, updated after each push and pop.
+    len: AtomicU64,
+    not_empty: WaitSignal,
+    not_full: WaitSignal,
+}
+
+/// Returns the distance between consecutive nodes holding `element_size` bytes.
+fn stride(element_size: usize) -> usize {
+    (NODE_HEADER + element_size).next_multiple_of(8)
+}
+
+/// A bounded LIFO stack of byte strings in shared memory.
+///
+/// The handle that creates the stack removes its segment when garbage-collected.
+#[pyclass(frozen, module = "zeroq")]
+pub struct Stack {
+    name: String,
+    shmem: ShmemWrapper,
+    element_size: usize,
+    capacity: usize,
+}
+
+impl Stack {
+    fn header(&self) -> &StackHeader {
+        unsafe { &*(self.shmem.as_ptr() as *const StackHeader) }
+    }
+
+    /// Returns a pointer to `node`, which starts with its link.
+    fn node_ptr(&self, node: u32) -> *mut u8 {
+        unsafe {
+            self.shmem
+                .as_ptr()
+                .add(size_of::<StackHeader>() + node as usize * stride(self.element_size))
+                as *mut u8
+        }
+    }
+
+    fn next(&self, node: u32) -> &AtomicU32 {
+        unsafe { &*(self.node_ptr(node) as *const AtomicU32) }
+    }
+
+    /// Copies `data` into a free node and links it on top, unless no node is free.
+    fn try_push(&self, data: &[u8]) -> Option<()> {
+        let header = self.header();
+        let node = header.free.pop(|node| self.next(node))?;
+        let ptr = self.node_ptr(node);
+        unsafe {
+            std::ptr::copy_nonoverlapping(
+                (data.len() as u32).to_le_bytes().as_ptr(),
+                ptr.add(size_of::<u32>()),
+                size_of::<u32>(),
+            );
+            std::ptr::copy_nonoverlapping(data.as_ptr(), ptr.add(NODE_HEADER), data.len());
+        }
+        header.top.push(node, |node| self.next(node));
+        header.len.fetch_add(1, Ordering::Relaxed);
+        header.not_empty.notify();
+        Some(())
+    }
+
+    /// Unlinks the top node and copies it out, unless the stack is empty.
+    fn try_pop(&self) -> Option<Vec<u8>> {
+        let header = self.header();
+        let node = header.top.pop(|node| self.next(node))?;
+        let ptr = self.node_ptr(node);
+        let data = unsafe {
+            let size = u32::from_le_bytes(*(ptr.add(size_of::<u32>()) as *const [u8; 4]));
+            std::slice::from_raw_parts(ptr.add(NODE_HEADER), (size as usize).min(self.element_size))
+                .to_vec()
+        };
+        header.free.push(node, |node| self.next(node));
+        header.len.fetch_sub(1, Ordering::Relaxed);
+        header.not_full.notify();
+        Some(data)
+    }
+}
+
+#[pymethods]
+impl Stack {
+    /// Creates or attaches to a stack in shared memory.
+    ///
+    /// # Arguments
+    /// - `name` (str): Shared memory segment name.
+    /// - `element_size` (int): Maximum size of an element in bytes (required if
+    ///   creating).
+    /// - `capacity` (int): Maximum number of elements (required if creating).
+    /// - `create` (bool, default=True): Whether to create a new stack.
+    ///
+    /// # Errors
+    /// Raises `ValueError` for invalid parameters, `AlreadyExists` if a stack is to be
+    /// created under a name that is taken, or `OSError` if the segment cannot be
+    /// created or opened or holds no stack.
+    #[new]
+    #[pyo3(signature = (name, element_size=None, capacity=None, create=true))]
+    fn new(
+        py: Python<'_>,
+        name: String,
+        element_size: Option<usize>,
+        capacity: Option<usize>,
+        create: bool,
+    ) -> PyResult<Self> {
+        if !create {
+            let shmem = region::attach(py, &name, MAGIC, size_of::<StackHeader>(), "stack")?;
+            let header = unsafe { &*(shmem.as_ptr() as *const StackHeader) };
+            let found = (header.element_size as usize, header.capacity as usize);
+            if shmem.len() < size_of::<StackHeader>() + found.1 * stride(found.0) {
+                return Err(region::holds_no(&name, "stack"));
+            }
+            if element_size.is_some_and(|size| size != found.0)
+                || capacity.is_some_and(|capacity| capacity != found.1)
+            {
+                return Err(InvalidParameters::new_err(format!(
+                    "Stack '{}' exists with element_size {} and capacity {}",
+                    name, found.0, found.1
+                )));
+            }
+            return Ok(Self {
+                name,
+                shmem,
+                element_size: found.0,
+                capacity: found.1,
+            });
+        }
+        let element_size = element_size
+            .filter(|size| (1..=u32::MAX as usize).contains(size))
+            .ok_or_else(|| {
+                PyValueError::new_err(format!(
+                    "element_size between 1 and {} required when create=true",
+                    u32::MAX
+                ))
+            })?;
+        let capacity = capacity
+            .filter(|capacity| (1..NIL as usize).contains(capacity))
+            .ok_or_else(|| {
+                PyValueError::new_err(format!(
+                    "capacity between 1 and {} required when create=true",
+                    NIL - 1
+                ))
+            })?;
+        let size = capacity
+            .checked_mul(stride(element_size))
+            .and_then(|size| size.checked_add(size_of::<StackHeader>()))
+            .ok_or_else(|| PyValueError::new_err("The stack is too large"))?;
+        let shmem = crate::py_queue::create_shmem(&name, size)?;
+        let stack = Self {
+            name,
+            shmem: ShmemWrapper::new(shmem),
+            element_size,
+            capacity,
+        };
+        let header = unsafe { &mut *(stack.shmem.as_ptr() as *mut StackHeader) };
+        header.element_size = element_size as u64;
+        header.capacity = capacity as u64;
+        for node in 0..capacity as u32 {
+            let next = if node as usize + 1 < capacity {
+                node + 1
+            } else {
+                NIL
+            };
+            stack.next(node).store(next, Ordering::Relaxed);
+        }
+        header.top.init(NIL);
+        header.free.init(0);
+        header.magic.store(MAGIC, Ordering::Release);
+        Ok(stack)
+    }
+
+    /// Name of the shared memory segment.
+    #[getter]
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Maximum size of an element in bytes.
+    #[getter]
+    fn element_size(&self) -> usize {
+        self.element_size
+    }
+
+    /// Maximum number of elements.
+    #[getter]
+    fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of elements, a snapshot that concurrent handles may change.
+    fn __len__(&self) -> usize {
+        // A pop may be counted before the push it undoes.
+        (self.header().len.load(Ordering::Relaxed) as i64).max(0) as usize
+    }
+
+    /// Pushes an element on top.
+    ///
+    /// # Arguments
+    /// - `item` (bytes): The element, at most `element_size` bytes.
+    /// - `timeout` (float, optional): Maximum time to wait for room in seconds; waits
+    ///   indefinitely if omitted, and does not wait if 0.
+    ///
+    /// # Errors
+    /// Raises `ValueError` if `item` is larger than an element, or `Full` if the stack
+    /// stays full beyond the timeout.
+    #[pyo3(signature = (item, timeout=None))]
+    fn push(&self, py: Python<'_>, item: Cow<[u8]>, timeout: Option<f64>) -> PyResult<()> {
+        if item.len() > self.element_size {
+            return Err(PyValueError::new_err(format!(
+                "Item of {} bytes does not fit in elements of {} bytes",
+                item.len(),
+                self.element_size
+            )));
+        }
+        region::wait_for(
+            py,
+            &self.header().not_full,
+            timeout,
+            || Ok(self.try_push(&item)),
+            || Full::new_err(format!("Stack '{}' is full", self.name)),
+        )
+    }
+
+    /// Removes and returns the element on top, the one pushed last.
+    ///
+    /// # Arguments
+    /// - `timeout` (float, optional): Maximum time to wait for an element in seconds;
+    ///   waits indefinitely if omitted, and does not wait if 0.
+    ///
+    /// # Errors
+    /// Raises `Empty` if the stack stays empty beyond the timeout.
+    #[pyo3(signature = (timeout=None))]
+    fn pop<'py>(&self, py: Python<'py>, timeout: Option<f64>) -> PyResult<Bound<'py, PyBytes>> {
+        let data = region::wait_for(
+            py,
+            &self.header().not_empty,
+            timeout,
+            || Ok(self.try_pop()),
+            || Empty::new_err(format!("Stack '{}' is empty", self.name)),
+        )?;
+        Ok(PyBytes::new(py, &data))
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Stack(name='{}', element_size={}, capacity={}, len={})",
+            self.name,
+            self.element_size,
+            self.capacity,
+            self.__len__()
+        )
+    }
+}
@@ -0,0 +1,163 @@
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Blocking operation kinds tracked separately in the statistics.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WaitOp {
+    Put,
+    Get,
+}
+
+impl WaitOp {
+    /// Returns the Python-facing name of the operation.
+    pub fn name(self) -> &'static str {
+        match self {
+            WaitOp::Put => "put",
+            WaitOp::Get => "get",
+        }
+    }
+}
+
+/// Ways a wait loop pauses between attempts, accounted separately because
+/// spinning and yielding keep a core busy while parking does not.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WaitPhase {
+    Spin,
+    Yield,
+    Park,
+}
+
+/// Wait-time counters for one kind of blocking operation.
+#[derive(Default)]
+pub struct WaitStats {
+    /// Number of blocking calls that had to wait at least once.
+    pub waits: AtomicU64,
+    /// Number of blocking calls that gave up after their timeout.
+    pub timeouts: AtomicU64,
+    /// Total time spent waiting, in nanoseconds.
+    pub wait_ns: AtomicU64,
+    /// Longest single wait, in nanoseconds.
+    pub max_wait_ns: AtomicU64,
+}
+
+impl WaitStats {
+    fn record(&self, waited: Duration, timed_out: bool) {
+        let ns = waited.as_nanos().min(u64::MAX as u128) as u64;
+        self.waits.fetch_add(1, Ordering::Relaxed);
+        if timed_out {
+            self.timeouts.fetch_add(1, Ordering::Relaxed);
+        }
+        self.wait_ns.fetch_add(ns, Ordering::Relaxed);
+        self.max_wait_ns.fetch_max(ns, Ordering::Relaxed);
+    }
+
+    fn to_dict(&self, dict: &Bound<'_, PyDict>, prefix: &str) -> PyResult<()> {
+        let secs = |counter: &AtomicU64| counter.load(Ordering::Relaxed) as f64 / 1e9;
+        dict.set_item(
+            format!("{}_waits", prefix),
+            self.waits.load(Ordering::Relaxed),
+        )?;
+        dict.set_item(
+            format!("{}_timeouts", prefix),
+            self.timeouts.load(Ordering::Relaxed),
+        )?;
+        dict.set_item(format!("{}_wait_time", prefix), secs(&self.wait_ns))?;
+        dict.set_item(format!("{}_max_wait", prefix), secs(&self.max_wait_ns))?;
+        Ok(())
+    }
+}
+
+/// Per-handle counters describing how blocking operations behaved.
+#[derive(Default)]
+pub struct QueueStats {
+    /// Number of busy-spin iterations performed while waiting.
+    pub spin_count: AtomicU64,
+    /// Number of scheduler yields performed by the adaptive wait strategy.
+    pub yield_count: AtomicU64,
+    /// Number of parks (sleeps) performed by the adaptive wait strategy.
+    pub park_count: AtomicU64,
+    /// Number of backpressure delays applied by blocking puts.
+    pub throttled: AtomicU64,
+    /// Number of messages discarded on dequeue because their deadline passed.
+    pub expired: AtomicU64,
+    /// Number of slots of dead producers skipped by consumers with `skip_poisoned`.
+    pub poison_skipped: AtomicU64,
+    /// Number of slots of dead producers reclaimed by `repair()`.
+    pub reclaimed: AtomicU64,
+    /// Number of times a timed-out blocking operation was retried.
+    pub retries: AtomicU64,
+    /// Time spent busy-spinning while waiting, in nanoseconds.
+    pub spin_ns: AtomicU64,
+    /// Time spent yielding to the scheduler while waiting, in nanoseconds.
+    pub yield_ns: AtomicU64,
+    /// Time spent sleeping while waiting or throttled, in nanoseconds.
+    pub park_ns: AtomicU64,
+    /// Wait times of blocking puts.
+    pub put: WaitStats,
+    /// Wait times of blocking gets.
+    pub get: WaitStats,
+}
+
+impl QueueStats {
+    /// Records how long a blocking operation waited before completing or timing out.
+    pub fn record_wait(&self, op: WaitOp, waited: Duration, timed_out: bool) {
+        match op {
+            WaitOp::Put => self.put.record(waited, timed_out),
+            WaitOp::Get => self.get.record(waited, timed_out),
+        }
+    }
+
+    /// Records how long a wait loop paused in `phase`.
+    #[inline]
+    pub fn record_pause(&self, phase: WaitPhase, paused: Duration) {
+        let ns = paused.as_nanos().min(u64::MAX as u128) as u64;
+        let counter = match phase {
+            WaitPhase::Spin => &self.spin_ns,
+            WaitPhase::Yield => &self.yield_ns,
+            WaitPhase::Park => &self.park_ns,
+        };
+        counter.fetch_add(ns, Ordering::Relaxed);
+    }
+
+    /// Returns the time spent in each wait phase as a Python dictionary.
+    pub fn pauses_to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        let spin = self.spin_ns.load(Ordering::Relaxed);
+        let yielded = self.yield_ns.load(Ordering::Relaxed);
+        let park = self.park_ns.load(Ordering::Relaxed);
+        let total = spin + yielded + park;
+        dict.set_item("spin_time", spin as f64 / 1e9)?;
+        dict.set_item("yield_time", yielded as f64 / 1e9)?;
+        dict.set_item("park_time", park as f64 / 1e9)?;
+        dict.set_item(
+            "busy_fraction",
+            if total == 0 {
+                0.0
+            } else {
+                (spin + yielded) as f64 / total as f64
+            },
+        )?;
+        Ok(dict)
+    }
+
+    /// Returns the counters as a Python dictionary.
+    pub fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        dict.set_item("spin_count", self.spin_count.load(Ordering::Relaxed))?;
+        dict.set_item("yield_count", self.yield_count.load(Ordering::Relaxed))?;
+        dict.set_item("park_count", self.park_count.load(Ordering::Relaxed))?;
+        dict.set_item("throttled", self.throttled.load(Ordering::Relaxed))?;
+        dict.set_item("expired", self.expired.load(Ordering::Relaxed))?;
+        dict.set_item(
+            "poison_skipped",
+            self.poison_skipped.load(Ordering::Relaxed),
+        )?;
+        dict.set_item("reclaimed", self.reclaimed.load(Ordering::Relaxed))?;
+        dict.set_item("retries", self.retries.load(Ordering::Relaxed))?;
+        self.put.to_dict(&dict, "put")?;
+        self.get.to_dict(&dict, "get")?;
+        Ok(dict)
+    }
+}
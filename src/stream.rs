@@ -0,0 +1,54 @@
+//! Chunking of payloads larger than a slot for `put_stream()` and `get_stream()`.
+//!
+//! A streamed payload is split into chunks that fill consecutive slots. Each chunk
+//! starts with a little-endian u32 holding the number of chunks of the payload in
+//! the first chunk and zero in the others, then the length of its data as a
+//! little-endian u32; zero padding follows the data.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+/// Size of the header in front of every chunk.
+pub const HEADER: usize = 8;
+
+/// Error message for runs of slots that do not hold a streamed payload.
+pub const NOT_STREAMED: &str = "The message was not put by put_stream()";
+
+/// Writes the chunk `data` into the whole `payload`, with the number of chunks of
+/// the payload if it is the first chunk and zero otherwise.
+pub fn write_chunk(payload: &mut [u8], count: u32, data: &[u8]) {
+    let (header, rest) = payload.split_at_mut(HEADER);
+    header[..4].copy_from_slice(&count.to_le_bytes());
+    header[4..].copy_from_slice(&(data.len() as u32).to_le_bytes());
+    let (dst, padding) = rest.split_at_mut(data.len());
+    dst.copy_from_slice(data);
+    padding.fill(0);
+}
+
+/// Returns the number of chunks recorded in the first chunk of a payload, or zero
+/// if the payload holds no valid chunk header.
+pub fn chunk_count(payload: &[u8]) -> usize {
+    let Some(header) = payload.get(..HEADER) else {
+        return 0;
+    };
+    let len = u32::from_le_bytes(header[4..].try_into().unwrap()) as usize;
+    if len > payload.len() - HEADER {
+        return 0;
+    }
+    u32::from_le_bytes(header[..4].try_into().unwrap()) as usize
+}
+
+/// Returns the data of the `index`-th chunk of a payload.
+///
+/// # Errors
+/// Raises `ValueError` if the chunk was not written by `put_stream()` at that index.
+pub fn read_chunk(payload: &[u8], index: usize, count: usize) -> PyResult<&[u8]> {
+    let invalid = || PyValueError::new_err(NOT_STREAMED);
+    let header = payload.get(..HEADER).ok_or_else(invalid)?;
+    let expected = if index == 0 { count } else { 0 };
+    if chunk_count(payload) != expected {
+        return Err(invalid());
+    }
+    let len = u32::from_le_bytes(header[4..].try_into().unwrap()) as usize;
+    payload[HEADER..].get(..len).ok_or_else(invalid)
+}
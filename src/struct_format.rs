@@ -0,0 +1,295 @@
+//! Packing of tuples into fixed-size records for queues with a `fmt`.
+//!
+//! Formats follow the `struct` module: an optional byte order character, then
+//! format characters, each optionally preceded by a repeat count. With `@`, the
+//! default, fields use native sizes and alignment; the other byte orders use
+//! standard sizes and no padding. Half floats (`e`) are not supported.
+
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::pybacked::PyBackedBytes;
+use pyo3::types::{PyBool, PyBytes, PyTuple};
+use std::ffi::c_long;
+
+/// How a field is packed.
+#[derive(Clone, Copy)]
+enum Kind {
+    /// `c`: a `bytes` object of length 1.
+    Char,
+    /// `?`: a bool stored as one byte.
+    Bool,
+    /// An integer of `size` bytes.
+    Int { size: usize, signed: bool },
+    /// `f`: an IEEE 754 binary32.
+    Float,
+    /// `d`: an IEEE 754 binary64.
+    Double,
+    /// `s`: `bytes` truncated or zero-padded to the given length.
+    Bytes(usize),
+    /// `p`: `bytes` behind a length byte, in the given total length.
+    Pascal(usize),
+}
+
+/// A field and its offset within the record.
+struct Field {
+    offset: usize,
+    kind: Kind,
+}
+
+/// A parsed `struct` format.
+pub struct StructFormat {
+    /// The format string, for error messages.
+    format: String,
+    fields: Vec<Field>,
+    little: bool,
+    size: usize,
+}
+
+impl StructFormat {
+    /// Parses `format`.
+    ///
+    /// # Errors
+    /// Raises `ValueError` for malformed or unsupported formats.
+    pub fn parse(format: &str) -> PyResult<Self> {
+        let invalid = |reason: String| {
+            PyValueError::new_err(format!("Invalid struct format '{}': {}", format, reason))
+        };
+        let (native, little, rest) = match format.chars().next() {
+            Some('@') => (true, cfg!(target_endian = "little"), &format[1..]),
+            Some('=') => (false, cfg!(target_endian = "little"), &format[1..]),
+            Some('<') => (false, true, &format[1..]),
+            Some('>' | '!') => (false, false, &format[1..]),
+            _ => (true, cfg!(target_endian = "little"), format),
+        };
+        let mut fields = Vec::new();
+        let mut size = 0;
+        let mut chars = rest.chars();
+        while let Some(c) = chars.next() {
+            if c.is_whitespace() {
+                continue;
+            }
+            let mut count = None;
+            let mut c = c;
+            while let Some(digit) = c.to_digit(10) {
+                count = Some(count.unwrap_or(0usize) * 10 + digit as usize);
+                c = chars
+                    .next()
+                    .ok_or_else(|| invalid("repeat count given without format character".into()))?;
+            }
+            let count = count.unwrap_or(1);
+            let native_only = |width: usize| {
+                if native {
+                    Ok(width)
+                } else {
+                    Err(invalid(format!("'{}' requires native byte order", c)))
+                }
+            };
+            let (kind, width) = match c {
+                'x' => {
+                    size += count;
+                    continue;
+                }
+                's' => (Kind::Bytes(count), 1),
+                'p' => (Kind::Pascal(count), 1),
+                'c' => (Kind::Char, 1),
+                '?' => (Kind::Bool, 1),
+                'b' | 'B' => (int(1, c == 'b'), 1),
+                'h' | 'H' => (int(2, c == 'h'), 2),
+                'i' | 'I' => (int(4, c == 'i'), 4),
+                'l' | 'L' => {
+                    let width = if native { size_of::<c_long>() } else { 4 };
+                    (int(width, c == 'l'), width)
+                }
+                'q' | 'Q' => (int(8, c == 'q'), 8),
+                'n' | 'N' => {
+                    let width = native_only(size_of::<isize>())?;
+                    (int(width, c == 'n'), width)
+                }
+                'P' => {
+                    let width = native_only(size_of::<usize>())?;
+                    (int(width, false), width)
+                }
+                'f' => (Kind::Float, 4),
+                'd' => (Kind::Double, 8),
+                other => return Err(invalid(format!("unsupported character '{}'", other))),
+            };
+            if native {
+                size = size.next_multiple_of(width);
+            }
+            match kind {
+                Kind::Bytes(len) | Kind::Pascal(len) => {
+                    fields.push(Field { offset: size, kind });
+                    size += len;
+                }
+                _ => {
+                    for _ in 0..count {
+                        fields.push(Field { offset: size, kind });
+                        size += width;
+                    }
+                }
+            }
+        }
+        if size == 0 {
+            return Err(invalid("records must not be empty".into()));
+        }
+        Ok(Self {
+            format: format.to_owned(),
+            fields,
+            little,
+            size,
+        })
+    }
+
+    /// Returns the size of a record in bytes.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Packs the sequence `values` into a record.
+    ///
+    /// # Errors
+    /// Raises `ValueError` if the number of values does not match the format or an
+    /// integer is out of range, and `TypeError` for values of the wrong type.
+    pub fn pack<'py>(&self, values: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyBytes>> {
+        let py = values.py();
+        let values: Vec<Bound<'py, PyAny>> = values.extract().map_err(|_| {
+            PyTypeError::new_err(format!(
+                "Records of format '{}' are put as tuples",
+                self.format
+            ))
+        })?;
+        if values.len() != self.fields.len() {
+            return Err(PyValueError::new_err(format!(
+                "Format '{}' packs {} values, got {}",
+                self.format,
+                self.fields.len(),
+                values.len()
+            )));
+        }
+        let mut record = vec![0u8; self.size];
+        for (field, value) in self.fields.iter().zip(&values) {
+            let dst = &mut record[field.offset..];
+            match field.kind {
+                Kind::Char => {
+                    let byte: PyBackedBytes = value.extract()?;
+                    if byte.len() != 1 {
+                        return Err(PyTypeError::new_err(
+                            "Format 'c' requires a bytes object of length 1",
+                        ));
+                    }
+                    dst[0] = byte[0];
+                }
+                Kind::Bool => dst[0] = value.is_truthy()? as u8,
+                Kind::Int { size, signed } => {
+                    let bits = size as u32 * 8;
+                    let fits = if signed {
+                        let v: i64 = value.extract()?;
+                        (bits == 64 || (-(1i64 << (bits - 1))..1i64 << (bits - 1)).contains(&v))
+                            .then_some(v as u64)
+                    } else {
+                        let v: u64 = value.extract()?;
+                        (bits == 64 || v < 1u64 << bits).then_some(v)
+                    };
+                    let v = fits.ok_or_else(|| {
+                        PyValueError::new_err(format!(
+                            "Value {} does not fit in {} bytes",
+                            value, size
+                        ))
+                    })?;
+                    self.put(&mut dst[..size], &v.to_le_bytes()[..size]);
+                }
+                Kind::Float => self.put(
+                    &mut dst[..4],
+                    &(value.extract::<f64>()? as f32).to_le_bytes(),
+                ),
+                Kind::Double => self.put(&mut dst[..8], &value.extract::<f64>()?.to_le_bytes()),
+                Kind::Bytes(len) => {
+                    let data: PyBackedBytes = value.extract()?;
+                    let n = data.len().min(len);
+                    dst[..n].copy_from_slice(&data[..n]);
+                }
+                Kind::Pascal(len) => {
+                    if len == 0 {
+                        continue;
+                    }
+                    let data: PyBackedBytes = value.extract()?;
+                    let n = data.len().min(len - 1).min(u8::MAX as usize);
+                    dst[0] = n as u8;
+                    dst[1..1 + n].copy_from_slice(&data[..n]);
+                }
+            }
+        }
+        Ok(PyBytes::new(py, &record))
+    }
+
+    /// Unpacks the record at the front of `payload` into a tuple.
+    ///
+    /// # Errors
+    /// Raises `ValueError` if `payload` is shorter than a record.
+    pub fn unpack<'py>(&self, py: Python<'py>, payload: &[u8]) -> PyResult<Bound<'py, PyTuple>> {
+        if payload.len() < self.size {
+            return Err(PyValueError::new_err(format!(
+                "Payload of {} bytes is shorter than records of format '{}'",
+                payload.len(),
+                self.format
+            )));
+        }
+        let values = self
+            .fields
+            .iter()
+            .map(|field| {
+                let src = &payload[field.offset..];
+                Ok(match field.kind {
+                    Kind::Char => PyBytes::new(py, &src[..1]).into_any(),
+                    Kind::Bool => PyBool::new(py, src[0] != 0).to_owned().into_any(),
+                    Kind::Int { size, signed } => {
+                        let mut bytes = [0u8; 8];
+                        self.put(&mut bytes[..size], &src[..size]);
+                        let v = u64::from_le_bytes(bytes);
+                        if signed {
+                            let shift = 64 - size as u32 * 8;
+                            (((v << shift) as i64) >> shift)
+                                .into_pyobject(py)?
+                                .into_any()
+                        } else {
+                            v.into_pyobject(py)?.into_any()
+                        }
+                    }
+                    Kind::Float => {
+                        let mut bytes = [0u8; 4];
+                        self.put(&mut bytes, &src[..4]);
+                        (f32::from_le_bytes(bytes) as f64)
+                            .into_pyobject(py)?
+                            .into_any()
+                    }
+                    Kind::Double => {
+                        let mut bytes = [0u8; 8];
+                        self.put(&mut bytes, &src[..8]);
+                        f64::from_le_bytes(bytes).into_pyobject(py)?.into_any()
+                    }
+                    Kind::Bytes(len) => PyBytes::new(py, &src[..len]).into_any(),
+                    Kind::Pascal(0) => PyBytes::new(py, b"").into_any(),
+                    Kind::Pascal(len) => {
+                        let n = (src[0] as usize).min(len - 1);
+                        PyBytes::new(py, &src[1..1 + n]).into_any()
+                    }
+                })
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+        PyTuple::new(py, values)
+    }
+
+    /// Copies the little-endian `bytes` into `dst` in the byte order of the format,
+    /// or back.
+    fn put(&self, dst: &mut [u8], bytes: &[u8]) {
+        dst.copy_from_slice(bytes);
+        if !self.little {
+            dst.reverse();
+        }
+    }
+}
+
+/// Returns the kind of an integer field.
+fn int(size: usize, signed: bool) -> Kind {
+    Kind::Int { size, signed }
+}
@@ -0,0 +1,71 @@
+//! Handles of PyTorch tensors for `put_tensor()` and `get_tensor()`.
+//!
+//! Only a handle to the storage of a tensor travels through the queue: the
+//! pickle that `torch.multiprocessing` produces for it, which names the shared
+//! memory of a CPU tensor or carries the CUDA IPC handle of a GPU tensor. The
+//! consumer rebuilds a tensor on top of the same memory, without copying data.
+
+use crate::byte_buffer::ByteBuffer;
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+
+/// Marks payloads written by `put_tensor()`.
+const MAGIC: &[u8; 2] = b"ZT";
+
+/// Size of the magic and the length of the handle in front of it.
+pub const HEADER: usize = MAGIC.len() + 4;
+
+/// Error message for payloads that do not hold a tensor handle.
+const NOT_A_TENSOR: &str = "The message was not put by put_tensor()";
+
+/// Returns the handle of `tensor`, moving its storage into shared memory first if it
+/// is a CPU tensor that is not shared yet.
+///
+/// # Errors
+/// Raises `ImportError` if PyTorch is not installed, `TypeError` if `tensor` is not a
+/// tensor, or the error of `torch.multiprocessing` if it cannot be shared.
+pub fn export(tensor: &Bound<'_, PyAny>) -> PyResult<ByteBuffer> {
+    let py = tensor.py();
+    if !tensor.is_instance(&py.import("torch")?.getattr("Tensor")?)? {
+        return Err(PyTypeError::new_err(format!(
+            "put_tensor() requires a torch.Tensor, not {}",
+            tensor.get_type().name()?
+        )));
+    }
+    let handle = pickler(py)?.call_method1("dumps", (tensor,))?;
+    ByteBuffer::get(&handle, false)
+}
+
+/// Writes `handle` behind its magic and length, zero-filling the rest of `payload`.
+pub fn write(payload: &mut [u8], handle: &[u8]) {
+    let (header, rest) = payload.split_at_mut(HEADER);
+    header[..MAGIC.len()].copy_from_slice(MAGIC);
+    header[MAGIC.len()..].copy_from_slice(&(handle.len() as u32).to_le_bytes());
+    let (dst, padding) = rest.split_at_mut(handle.len());
+    dst.copy_from_slice(handle);
+    padding.fill(0);
+}
+
+/// Rebuilds the tensor whose handle `payload` carries.
+///
+/// # Errors
+/// Raises `ValueError` if the payload was not written by `put_tensor()`, `ImportError`
+/// if PyTorch is not installed, or the error of `torch.multiprocessing` if the storage
+/// cannot be opened, e.g. because the producer already released it.
+pub fn import<'py>(py: Python<'py>, payload: &[u8]) -> PyResult<Bound<'py, PyAny>> {
+    let invalid = || PyValueError::new_err(NOT_A_TENSOR);
+    if payload.len() < HEADER || &payload[..MAGIC.len()] != MAGIC {
+        return Err(invalid());
+    }
+    let len = u32::from_le_bytes(payload[MAGIC.len()..HEADER].try_into().unwrap()) as usize;
+    let handle = payload[HEADER..].get(..len).ok_or_else(invalid)?;
+    pickler(py)?.call_method1("loads", (PyBytes::new(py, handle),))
+}
+
+/// Returns `ForkingPickler` with the reductions of `torch.multiprocessing` registered.
+fn pickler(py: Python<'_>) -> PyResult<Bound<'_, PyAny>> {
+    py.import("torch.multiprocessing")?;
+    py.import("multiprocessing.reduction")?
+        .getattr("ForkingPickler")
+}
@@ -0,0 +1,277 @@
+use crate::clock::Clock;
+use crate::stats::{QueueStats, WaitPhase};
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::OsRng;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+/// Strategy used by blocking operations while the queue is full or empty.
+#[derive(Clone, Copy, Debug)]
+pub enum WaitStrategy {
+    /// Sleeps for a fixed interval between attempts.
+    Sleep(Duration),
+    /// Busy-spins with `std::hint::spin_loop`, never sleeping or yielding
+    /// to the scheduler.
+    BusySpin,
+    /// Spins for the first `spin_limit` attempts, yields to the scheduler for
+    /// the next `yield_limit`, then parks by sleeping `park` between attempts.
+    Adaptive {
+        spin_limit: u32,
+        yield_limit: u32,
+        park: Duration,
+    },
+}
+
+impl WaitStrategy {
+    /// Builds a wait strategy from its Python-facing options.
+    pub fn from_options(
+        busy_spin: bool,
+        adaptive: bool,
+        spin_limit: u32,
+        yield_limit: u32,
+        park_interval: f64,
+    ) -> PyResult<Self> {
+        if busy_spin && adaptive {
+            return Err(PyValueError::new_err(
+                "busy_spin and adaptive are mutually exclusive",
+            ));
+        }
+        if busy_spin {
+            return Ok(WaitStrategy::BusySpin);
+        }
+        let park = Duration::try_from_secs_f64(park_interval)
+            .ok()
+            .filter(|park| !park.is_zero())
+            .ok_or_else(|| PyValueError::new_err("park_interval must be positive"))?;
+        if adaptive {
+            Ok(WaitStrategy::Adaptive {
+                spin_limit,
+                yield_limit,
+                park,
+            })
+        } else {
+            Ok(WaitStrategy::Sleep(park))
+        }
+    }
+
+    /// Parses a Python-facing wait policy name into the `busy_spin` and
+    /// `adaptive` flags it stands for.
+    pub fn flags_from_name(name: &str) -> PyResult<(bool, bool)> {
+        match name {
+            "sleep" => Ok((false, false)),
+            "spin" => Ok((true, false)),
+            "hybrid" => Ok((false, true)),
+            other => Err(PyValueError::new_err(format!(
+                "Unknown wait policy '{}': expected 'spin', 'hybrid' or 'sleep'",
+                other
+            ))),
+        }
+    }
+
+    /// Returns the Python-facing name of the wait policy selected by the
+    /// `busy_spin` and `adaptive` flags.
+    pub fn name_of(busy_spin: bool, adaptive: bool) -> &'static str {
+        if busy_spin {
+            "spin"
+        } else if adaptive {
+            "hybrid"
+        } else {
+            "sleep"
+        }
+    }
+
+    /// Returns whether the strategy ever parks, so that waiting handles should
+    /// register for wake-ups.
+    pub fn parks(&self) -> bool {
+        !matches!(self, WaitStrategy::BusySpin)
+    }
+
+    /// Pauses the calling thread before the next attempt; `attempt` counts the
+    /// failed attempts of the current operation, starting at zero. Parking is
+    /// delegated to `park`, which receives the configured interval.
+    #[inline]
+    pub fn pause(
+        &self,
+        attempt: u32,
+        clock: &Clock,
+        stats: &QueueStats,
+        park: impl FnOnce(Duration),
+    ) {
+        let started = clock.now();
+        let phase = match self {
+            WaitStrategy::Sleep(interval) => {
+                park(*interval);
+                WaitPhase::Park
+            }
+            WaitStrategy::BusySpin => spin(stats),
+            WaitStrategy::Adaptive {
+                spin_limit,
+                yield_limit,
+                park: interval,
+            } => {
+                if attempt < *spin_limit {
+                    spin(stats)
+                } else if attempt - spin_limit < *yield_limit {
+                    stats.yield_count.fetch_add(1, Ordering::Relaxed);
+                    std::thread::yield_now();
+                    WaitPhase::Yield
+                } else {
+                    stats.park_count.fetch_add(1, Ordering::Relaxed);
+                    park(*interval);
+                    WaitPhase::Park
+                }
+            }
+        };
+        stats.record_pause(phase, clock.now() - started);
+    }
+}
+
+/// Performs one busy-spin iteration.
+#[inline]
+fn spin(stats: &QueueStats) -> WaitPhase {
+    stats.spin_count.fetch_add(1, Ordering::Relaxed);
+    std::hint::spin_loop();
+    WaitPhase::Spin
+}
+
+/// Retries of blocking operations that timed out on a full or empty queue.
+///
+/// Each retry waits for a fresh `timeout` after a jittered, exponentially
+/// growing backoff, so producers that all hit a full queue at once do not
+/// retry in lockstep.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RetryPolicy {
+    pub retries: u32,
+    backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// Builds a retry policy from its Python-facing parameters.
+    pub fn new(retries: u32, backoff: f64) -> PyResult<Self> {
+        let backoff = Duration::try_from_secs_f64(backoff)
+            .map_err(|_| PyValueError::new_err("retry_backoff must be non-negative"))?;
+        Ok(Self { retries, backoff })
+    }
+
+    /// Returns the delay before retry number `retry`, starting at zero: a random
+    /// duration between half and all of `backoff * 2**retry`.
+    pub fn delay(&self, retry: u32) -> Duration {
+        let ceiling = self
+            .backoff
+            .saturating_mul(1u32.checked_shl(retry).unwrap_or(u32::MAX));
+        let jitter = 0.5 + OsRng.next_u32() as f64 / (2.0 * u32::MAX as f64);
+        ceiling.mul_f64(jitter)
+    }
+}
+
+/// Shape of the delay curve applied by [`Backpressure`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BackpressureCurve {
+    /// The delay grows by `base` with every consecutive `Full` outcome.
+    Linear,
+    /// The delay doubles with every consecutive `Full` outcome.
+    Exponential,
+}
+
+impl BackpressureCurve {
+    /// Parses the Python-facing curve name.
+    pub fn from_name(name: &str) -> PyResult<Self> {
+        match name {
+            "linear" => Ok(BackpressureCurve::Linear),
+            "exponential" => Ok(BackpressureCurve::Exponential),
+            other => Err(PyValueError::new_err(format!(
+                "Unknown backpressure curve '{}': expected 'linear' or 'exponential'",
+                other
+            ))),
+        }
+    }
+
+    /// Returns the Python-facing curve name.
+    pub fn name(self) -> &'static str {
+        match self {
+            BackpressureCurve::Linear => "linear",
+            BackpressureCurve::Exponential => "exponential",
+        }
+    }
+
+    /// Encodes an optional curve as a byte, zero meaning no backpressure.
+    pub fn code(curve: Option<Self>) -> u8 {
+        match curve {
+            None => 0,
+            Some(BackpressureCurve::Linear) => 1,
+            Some(BackpressureCurve::Exponential) => 2,
+        }
+    }
+
+    /// Decodes a byte written by [`BackpressureCurve::code`].
+    pub fn from_code(code: u8) -> Option<Self> {
+        match code {
+            1 => Some(BackpressureCurve::Linear),
+            2 => Some(BackpressureCurve::Exponential),
+            _ => None,
+        }
+    }
+}
+
+/// Adaptive producer throttling applied by blocking puts.
+///
+/// Every consecutive `Full` outcome observed by a handle increases the delay
+/// before the next attempt, up to `max`. A successful put resets the streak.
+#[derive(Debug)]
+pub struct Backpressure {
+    curve: BackpressureCurve,
+    base: Duration,
+    max: Duration,
+    full_streak: AtomicU32,
+}
+
+impl Backpressure {
+    /// Builds a backpressure policy from its Python-facing parameters.
+    pub fn new(curve: BackpressureCurve, base: f64, max: f64) -> PyResult<Self> {
+        if !(base > 0.0 && max >= base) {
+            return Err(PyValueError::new_err(
+                "backpressure delays must satisfy 0 < backpressure_base <= backpressure_max",
+            ));
+        }
+        Ok(Self {
+            curve,
+            base: Duration::from_secs_f64(base),
+            max: Duration::from_secs_f64(max),
+            full_streak: AtomicU32::new(0),
+        })
+    }
+
+    /// Returns the delay to apply after `streak` consecutive `Full` outcomes.
+    fn delay(&self, streak: u32) -> Duration {
+        let delay = match self.curve {
+            BackpressureCurve::Linear => self.base.saturating_mul(streak),
+            BackpressureCurve::Exponential => self.base.saturating_mul(
+                1u32.checked_shl(streak.saturating_sub(1))
+                    .unwrap_or(u32::MAX),
+            ),
+        };
+        delay.min(self.max)
+    }
+
+    /// Records a `Full` outcome and sleeps for the resulting delay.
+    pub fn throttle(&self, clock: &Clock, stats: &QueueStats) {
+        let streak = self
+            .full_streak
+            .fetch_add(1, Ordering::Relaxed)
+            .saturating_add(1);
+        stats.throttled.fetch_add(1, Ordering::Relaxed);
+        let started = clock.now();
+        clock.sleep(self.delay(streak));
+        stats.record_pause(WaitPhase::Park, clock.now() - started);
+    }
+
+    /// Resets the streak after a successful put.
+    #[inline]
+    pub fn reset(&self) {
+        if self.full_streak.load(Ordering::Relaxed) != 0 {
+            self.full_streak.store(0, Ordering::Relaxed);
+        }
+    }
+}
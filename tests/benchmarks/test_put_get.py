@@ -22,7 +22,7 @@ def put_item_get_item(queue: IQueue, item: bytes) -> None:
     queue.get()
 
 
-queue_types = ['multiprocessing', 'zeroq']
+queue_types = ['multiprocessing', 'zeroq', 'zeroq-spsc']
 
 item_sizes = [2**p for p in range(1, 28, 4)]
 
@@ -38,6 +38,12 @@ def test_put_item_get_item(
     queue: IQueue
     if queue_type == 'multiprocessing':
         queue = multiprocessing.Queue()
+    elif queue_type == 'zeroq-spsc':
+        queue = zeroq.SpscQueue(
+            name='benchmark',
+            element_size=item_size,
+            capacity=8,
+        )
     else:
         queue = zeroq.Queue(
             name='benchmark',
@@ -0,0 +1,154 @@
+import json
+import mmap
+import os
+import struct
+import subprocess
+import sys
+import time
+from pathlib import Path
+
+import pytest
+
+import zeroq
+from zeroq import Queue
+
+pytestmark = pytest.mark.skipif(
+    not sys.platform.startswith('linux'),
+    reason='process liveness is only checked on Linux',
+)
+
+
+def _abandon_slot(name: str) -> subprocess.Popen[bytes]:
+    """Starts a producer that reserves a slot of name and dies mid-write."""
+    code = (
+        'import os\n'
+        'from zeroq import Queue\n'
+        f'queue = Queue(name={name!r}, create=False)\n'
+        'view = queue.reserve()\n'
+        'print("reserved", flush=True)\n'
+        'os._exit(0)\n'
+    )
+    env = {**os.environ, 'PYTHONPATH': os.pathsep.join(sys.path)}
+    producer = subprocess.Popen(
+        [sys.executable, '-c', code], env=env, stdout=subprocess.PIPE
+    )
+    assert producer.stdout is not None
+    assert producer.stdout.readline() == b'reserved\n'
+    return producer
+
+
+def test_consumers_skip_slots_of_dead_producers() -> None:
+    """Tests that skip_poisoned skips slots whose producer died."""
+    queue = Queue(
+        name='test-abandoned-get',
+        element_size=1,
+        capacity=4,
+        track_owners=True,
+    )
+    producer = _abandon_slot('test-abandoned-get')
+    producer.wait(timeout=10)
+    queue.put(b'b')
+    with pytest.raises(zeroq.Empty):
+        queue.get_nowait()
+
+    consumer = Queue(
+        name='test-abandoned-get', create=False, skip_poisoned=True
+    )
+    assert consumer.get(timeout=5.0) == b'b'
+    assert consumer.stats()['poison_skipped'] == 1
+    assert len(queue) == 0
+
+
+def test_repair_reports_reclaimed_slots() -> None:
+    """Tests that repair() reclaims slots of exited, unreaped producers."""
+    queue = Queue(
+        name='test-abandoned-repair',
+        element_size=1,
+        capacity=4,
+        track_owners=True,
+    )
+    producer = _abandon_slot('test-abandoned-repair')
+    deadline = time.monotonic() + 10.0
+    reclaimed = queue.repair()
+    while not reclaimed and time.monotonic() < deadline:
+        time.sleep(0.01)
+        reclaimed = queue.repair()
+    producer.wait(timeout=10)
+
+    assert reclaimed == [('main', 0, producer.pid)]
+    assert queue.stats()['reclaimed'] == 1
+    queue.put(b'b')
+    assert queue.get_nowait() == b'b'
+
+
+def test_live_producers_keep_their_slots() -> None:
+    """Tests that slots reserved by a live process are not reclaimed."""
+    queue = Queue(
+        name='test-abandoned-live',
+        element_size=1,
+        capacity=4,
+        track_owners=True,
+    )
+    consumer = Queue(
+        name='test-abandoned-live', create=False, skip_poisoned=True
+    )
+    view = queue.reserve()
+    queue.put(b'b')
+
+    assert queue.repair() == []
+    with pytest.raises(zeroq.Empty):
+        consumer.get(timeout=0.1)
+    assert consumer.stats()['poison_skipped'] == 0
+    view[0] = ord('a')
+    view.release()
+    assert queue.get_many(2) == [b'a', b'b']
+
+
+def _reserve_as(name: str, pid: int, namespace: int) -> None:
+    """Reserves the first slot of name as process pid of namespace would."""
+    with Path(f'/dev/shm/{name}').open('r+b') as segment:
+        view = mmap.mmap(segment.fileno(), 0)
+        offset = json.loads(zeroq.layout_descriptor())[
+            'header.enqueue_pos.offset'
+        ]
+        struct.pack_into('=Q', view, offset, 1)
+        owners = zeroq.plan(1, 4, track_owners=True)['owners_offset']
+        struct.pack_into('=QQ', view, owners, pid << 32, namespace)
+        view.close()
+
+
+def test_owners_in_other_pid_namespaces_are_not_reclaimed() -> None:
+    """Tests that pids recorded in another PID namespace are never judged."""
+    queue = Queue(
+        name='test-abandoned-namespace',
+        element_size=1,
+        capacity=4,
+        track_owners=True,
+    )
+    if not Path('/dev/shm/test-abandoned-namespace').exists():
+        pytest.skip('shared memory is not exposed under /dev/shm')
+    namespace = os.stat('/proc/self/ns/pid').st_ino
+    # No process ever has the id pid_max.
+    pid = int(Path('/proc/sys/kernel/pid_max').read_text())
+    _reserve_as('test-abandoned-namespace', pid, namespace + 1)
+
+    assert queue.repair() == []
+    _reserve_as('test-abandoned-namespace', pid, namespace)
+    assert queue.repair() == [('main', 0, pid)]
+
+
+def test_repair_requires_track_owners() -> None:
+    """Tests that repair() refuses queues that keep no owners."""
+    queue = Queue(name='test-abandoned-untracked', element_size=1, capacity=4)
+    with pytest.raises(ValueError, match='track_owners'):
+        queue.repair()
+    assert queue.debug_info()['track_owners'] is False
+
+
+def test_owners_take_a_word_per_slot() -> None:
+    """Tests that tracking owners adds two 8-byte words per slot."""
+    plain = zeroq.plan(8, 16)
+    tracked = zeroq.plan(8, 16, track_owners=True)
+
+    assert tracked['owners_size'] == 16 * 16
+    assert tracked['total_size'] == plain['total_size'] + 16 * 16
@@ -0,0 +1,72 @@
+from multiprocessing import shared_memory
+
+import pytest
+
+from zeroq import Queue, required_size
+
+OFFSET = 4096
+
+
+@pytest.fixture
+def segment():
+    """Yields a segment owned by another tool, with a legacy prefix."""
+    size = OFFSET + required_size(element_size=8, capacity=4)
+    shm = shared_memory.SharedMemory(name='test-adopt-segment', create=True, size=size)
+    shm.buf[:OFFSET] = b'\xab' * OFFSET
+    yield shm
+    shm.close()
+    shm.unlink()
+
+
+def test_adopt_initializes_queue_at_offset(segment) -> None:
+    """Tests that an adopted queue works and leaves the prefix untouched."""
+    queue = Queue(
+        name='test-adopt-segment',
+        element_size=8,
+        capacity=4,
+        create=False,
+        offset=OFFSET,
+        adopt=True,
+    )
+    queue.put(b'12345678')
+
+    consumer = Queue(name='test-adopt-segment', create=False, offset=OFFSET)
+    assert consumer.element_size == 8
+    assert consumer.maxsize == 4
+    assert consumer.get() == b'12345678'
+    assert bytes(segment.buf[:OFFSET]) == b'\xab' * OFFSET
+
+
+def test_adopt_requires_dimensions(segment) -> None:
+    """Tests that adopting a segment needs explicit element_size and capacity."""
+    with pytest.raises(ValueError, match='element_size required when adopt=true'):
+        Queue(name='test-adopt-segment', create=False, adopt=True, capacity=4)
+
+
+def test_adopt_rejects_create() -> None:
+    """Tests that adopt cannot be combined with create."""
+    with pytest.raises(ValueError, match='requires create=false'):
+        Queue(name='test-adopt-invalid', element_size=8, capacity=4, adopt=True)
+
+
+@pytest.mark.parametrize('offset', [3, 1 << 20])
+def test_adopt_rejects_bad_offset(segment, offset: int) -> None:
+    """Tests that misaligned or out-of-bounds offsets are rejected."""
+    with pytest.raises(ValueError):
+        Queue(
+            name='test-adopt-segment',
+            element_size=8,
+            capacity=4,
+            create=False,
+            offset=offset,
+            adopt=True,
+        )
+
+
+def test_create_at_offset() -> None:
+    """Tests that a created queue can reserve a prefix for other users."""
+    queue = Queue(name='test-create-offset', element_size=8, capacity=2, offset=64)
+    queue.put(b'abcdefgh')
+
+    consumer = Queue(name='test-create-offset', create=False, offset=64)
+    assert consumer.get() == b'abcdefgh'
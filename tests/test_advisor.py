@@ -0,0 +1,108 @@
+import pytest
+
+import zeroq
+from zeroq import Observation, Queue
+from zeroq.__main__ import main
+
+
+def _observation(
+    occupancy: list[int], enqueued: int = 0, capacity: int = 1024
+) -> Observation:
+    return Observation(
+        element_size=4096,
+        capacity=capacity,
+        duration=1.0,
+        occupancy=occupancy,
+        enqueued=enqueued,
+        dequeued=enqueued,
+    )
+
+
+def test_totals_count_messages_across_handles() -> None:
+    """Tests that totals() counts every handle's puts and gets."""
+    queue = Queue(
+        name='test-totals', element_size=1, capacity=4, urgent_lane=True
+    )
+    attached = Queue(name='test-totals', create=False)
+
+    queue.put(b'a')
+    attached.put(b'b')
+    attached.put_urgent(b'c')
+    queue.get()
+
+    assert attached.totals() == {'enqueued': 3, 'dequeued': 1}
+    attached.close()
+    queue.close()
+
+
+def test_observe_samples_occupancy_and_throughput() -> None:
+    """Tests that observe() reports what went through the queue."""
+    queue = Queue(name='test-observe', element_size=1, capacity=8)
+    queue.put(b'a')
+    queue.put(b'b')
+
+    observation = zeroq.observe(queue, duration=0.02, interval=0.005)
+
+    assert observation.capacity == 8
+    assert observation.element_size == 1
+    assert observation.high_water == 2
+    assert observation.enqueued == observation.dequeued == 0
+    assert len(observation.occupancy) >= 2
+    queue.close()
+
+
+def test_advise_shrinks_overprovisioned_capacity() -> None:
+    """Tests that capacity follows the high-water mark with headroom."""
+    advice = zeroq.advise(_observation([0, 3, 12, 5]))
+
+    assert advice.capacity == 32
+    assert advice.recommended_slot_bytes == 4096 * 32
+    assert advice.slot_bytes == 4096 * 1024
+
+
+def test_advise_grows_saturated_queue() -> None:
+    """Tests that a queue seen full is recommended at least twice as large."""
+    advice = zeroq.advise(_observation([4, 8, 8], capacity=8))
+
+    assert advice.capacity == 16
+    assert any('full in 67%' in reason for reason in advice.reasons)
+
+
+def test_advise_sizes_elements_from_max_payload() -> None:
+    """Tests that the element size is rounded up from the largest payload."""
+    advice = zeroq.advise(_observation([1]), max_payload=1000)
+
+    assert advice.element_size == 1000
+    larger = zeroq.advise(_observation([1]), max_payload=1001)
+    assert larger.element_size == 1008
+    assert zeroq.advise(_observation([1])).element_size == 4096
+
+
+@pytest.mark.parametrize(
+    ('enqueued', 'wait'),
+    [(10, 'sleep'), (50_000, 'hybrid'), (2_000_000, 'spin')],
+)
+def test_advise_picks_wait_policy_from_rate(enqueued: int, wait: str) -> None:
+    """Tests that busier queues are advised to wait more eagerly."""
+    assert zeroq.advise(_observation([1], enqueued=enqueued)).wait == wait
+
+
+def test_advise_rejects_headroom_below_one() -> None:
+    """Tests that advice never sizes a queue below what was observed."""
+    with pytest.raises(ValueError):
+        zeroq.advise(_observation([1]), headroom=0.5)
+
+
+def test_cli_advise_prints_recommendation(
+    capsys: pytest.CaptureFixture[str],
+) -> None:
+    """Tests that the advise subcommand observes a queue by name."""
+    queue = Queue(name='test-cli-advise', element_size=1, capacity=256)
+    queue.put(b'a')
+
+    args = ['advise', 'test-cli-advise', '--duration', '0.01']
+    assert main([*args, '--max-payload', '20']) == 0
+    out = capsys.readouterr().out
+    assert 'element_size=24 capacity=2' in out
+    assert "wait='sleep'" in out
+    queue.close()
@@ -0,0 +1,71 @@
+import pytest
+
+from zeroq import Queue
+
+np = pytest.importorskip('numpy')
+
+
+@pytest.mark.parametrize(
+    'array',
+    [
+        np.arange(12, dtype=np.float32).reshape(3, 4),
+        np.arange(5, dtype='>i8'),
+        np.array(3.5),
+        np.zeros((2, 0, 3), dtype=np.uint8),
+        np.array(['2024-01-01', '2024-06-30'], dtype='datetime64[ns]'),
+    ],
+)
+def test_array_roundtrip(array: 'np.ndarray') -> None:
+    """Tests that dtype, shape and data survive the queue."""
+    queue = Queue(name='test-array-roundtrip', element_size=128, capacity=2)
+
+    queue.put_array(array)
+    received = queue.get_array()
+
+    assert received.dtype == array.dtype
+    assert received.shape == array.shape
+    np.testing.assert_array_equal(received, array)
+    assert received.flags.writeable
+    queue.close()
+
+
+def test_non_contiguous_array() -> None:
+    """Tests that strided arrays arrive in C order."""
+    queue = Queue(name='test-array-strided', element_size=128, capacity=2)
+    array = np.arange(16, dtype=np.int16).reshape(4, 4).T[::2]
+
+    queue.put_array(array)
+
+    np.testing.assert_array_equal(queue.get_array(), array)
+    queue.close()
+
+
+def test_array_too_large() -> None:
+    """Tests that arrays exceeding the element size are rejected."""
+    queue = Queue(name='test-array-large', element_size=32, capacity=2)
+
+    with pytest.raises(ValueError, match='does not fit'):
+        queue.put_array(np.zeros(4, dtype=np.float64))
+    assert queue.empty()
+    queue.close()
+
+
+def test_unsupported_dtypes() -> None:
+    """Tests that object and structured dtypes are rejected."""
+    queue = Queue(name='test-array-dtypes', element_size=64, capacity=2)
+
+    with pytest.raises(ValueError, match='Python objects'):
+        queue.put_array(np.array([None, 1], dtype=object))
+    with pytest.raises(ValueError, match='structured'):
+        queue.put_array(np.zeros(2, dtype=[('x', 'i4'), ('y', 'f4')]))
+    queue.close()
+
+
+def test_get_array_rejects_plain_messages() -> None:
+    """Tests that messages from put() are not mistaken for arrays."""
+    queue = Queue(name='test-array-plain', element_size=16, capacity=2)
+    queue.put(bytes(16))
+
+    with pytest.raises(ValueError, match='put_array'):
+        queue.get_array()
+    queue.close()
@@ -0,0 +1,99 @@
+import asyncio
+import threading
+import time
+
+import pytest
+
+from zeroq import Empty, Full, Queue
+from zeroq.aio import AsyncQueue
+
+
+def test_wait_readable_times_out_without_dequeuing() -> None:
+    """Tests that readiness waits report the queue state and consume nothing."""
+    queue = Queue(name='test-wait-readable', element_size=1, capacity=2)
+
+    assert queue.wait_readable(timeout=0.01) is False
+    queue.put(b'a')
+    assert queue.wait_readable(timeout=0.01) is True
+    assert queue.wait_readable() is True
+    assert len(queue) == 1
+    queue.close()
+
+
+def test_wait_writable_wakes_on_get() -> None:
+    """Tests that a writability wait returns once a consumer frees a slot."""
+    queue = Queue(name='test-wait-writable', element_size=1, capacity=2)
+    queue.put(b'a')
+    queue.put(b'b')
+    assert queue.wait_writable(timeout=0.01) is False
+
+    consumer = threading.Timer(0.05, queue.get)
+    consumer.start()
+    started = time.monotonic()
+    assert queue.wait_writable(timeout=5.0) is True
+    assert time.monotonic() - started < 2.0
+    consumer.join()
+    queue.close()
+
+
+def test_async_get_returns_pending_message_immediately() -> None:
+    """Tests that an awaitable get takes queued messages on the loop."""
+    queue = Queue(name='test-async-get', element_size=1, capacity=2)
+    queue.put(b'a')
+
+    assert asyncio.run(AsyncQueue(queue).get(timeout=0.01)) == b'a'
+    queue.close()
+
+
+def test_async_get_waits_for_producer() -> None:
+    """Tests that a waiting get lets other coroutines run until a put."""
+    queue = Queue(name='test-async-wait', element_size=1, capacity=2)
+    async_queue = AsyncQueue(queue)
+    ticks = []
+
+    async def ticker() -> None:
+        for tick in range(3):
+            ticks.append(tick)
+            await asyncio.sleep(0.01)
+        await async_queue.put(b'z')
+
+    async def main() -> bytes:
+        consumer = asyncio.ensure_future(async_queue.get(timeout=5.0))
+        await ticker()
+        return await consumer
+
+    assert asyncio.run(main()) == b'z'
+    assert ticks == [0, 1, 2]
+    queue.close()
+
+
+def test_async_timeouts_raise_queue_errors() -> None:
+    """Tests that awaitable operations time out like their blocking peers."""
+    queue = Queue(name='test-async-timeout', element_size=1, capacity=2)
+    async_queue = AsyncQueue(queue)
+
+    with pytest.raises(Empty):
+        asyncio.run(async_queue.get(timeout=0.02))
+    queue.put(b'a')
+    queue.put(b'b')
+    with pytest.raises(Full):
+        asyncio.run(async_queue.put(b'c', timeout=0.02))
+    queue.close()
+
+
+def test_cancelled_async_get_loses_no_message() -> None:
+    """Tests that a cancelled get leaves later messages in the queue."""
+    queue = Queue(name='test-async-cancel', element_size=1, capacity=2)
+    async_queue = AsyncQueue(queue)
+
+    async def main() -> None:
+        waiting = asyncio.ensure_future(async_queue.get())
+        await asyncio.sleep(0.01)
+        waiting.cancel()
+        with pytest.raises(asyncio.CancelledError):
+            await waiting
+
+    asyncio.run(main())
+    queue.put(b'a')
+    assert queue.get_nowait() == b'a'
+    queue.close()
@@ -0,0 +1,101 @@
+import os
+import subprocess
+import sys
+
+import pytest
+
+from zeroq import Queue
+
+
+def _attach_and_die(name: str) -> int:
+    """Runs a consumer that attaches to name and exits without closing."""
+    code = (
+        'import os\n'
+        'from zeroq import Queue\n'
+        f'queue = Queue(name={name!r}, create=False, role="consumer")\n'
+        'os._exit(0)\n'
+    )
+    env = {**os.environ, 'PYTHONPATH': os.pathsep.join(sys.path)}
+    consumer = subprocess.Popen([sys.executable, '-c', code], env=env)
+    assert consumer.wait(timeout=10) == 0
+    return consumer.pid
+
+
+def test_handles_register_with_their_roles() -> None:
+    """Tests that attached handles are listed by process with their roles."""
+    producer = Queue(
+        name='test-attached-roles',
+        element_size=8,
+        capacity=4,
+        role='producer',
+    )
+    consumer = Queue(name='test-attached-roles', create=False, role='consumer')
+
+    assert producer.attached_processes() == [
+        {
+            'pid': os.getpid(),
+            'roles': ['producer', 'consumer'],
+            'handles': 2,
+            'alive': True,
+        }
+    ]
+    consumer.close()
+    (process,) = producer.attached_processes()
+    assert (process['roles'], process['handles']) == (['producer'], 1)
+
+
+def test_handles_without_role_are_listed() -> None:
+    """Tests that handles without a role are registered without roles."""
+    queue = Queue(name='test-attached-no-role', element_size=8, capacity=4)
+    both = Queue(name='test-attached-no-role', create=False, role='both')
+    both.close()
+
+    (process,) = queue.attached_processes()
+    assert (process['roles'], process['handles']) == ([], 1)
+
+
+@pytest.mark.skipif(
+    sys.platform == 'win32', reason='process liveness is only checked on Unix'
+)
+def test_exited_processes_are_not_alive() -> None:
+    """Tests that processes that exited without closing are not alive."""
+    queue = Queue(name='test-attached-exited', element_size=8, capacity=4)
+    pid = _attach_and_die('test-attached-exited')
+
+    processes = {p['pid']: p for p in queue.attached_processes()}
+    assert processes[os.getpid()]['alive'] is True
+    assert processes[pid]['alive'] is False
+    assert processes[pid]['roles'] == ['consumer']
+
+
+@pytest.mark.skipif(
+    sys.platform == 'win32', reason='process liveness is only checked on Unix'
+)
+def test_full_registry_reuses_entries_of_exited_processes() -> None:
+    """Tests that a full registry hands entries of exited processes on."""
+    queue = Queue(name='test-attached-full', element_size=8, capacity=4)
+    handles = [
+        Queue(name='test-attached-full', create=False) for _ in range(14)
+    ]
+    pid = _attach_and_die('test-attached-full')
+    handles.append(Queue(name='test-attached-full', create=False))
+
+    (process,) = queue.attached_processes()
+    assert process['pid'] == os.getpid() != pid
+    assert process['handles'] == 16
+
+    handles.append(Queue(name='test-attached-full', create=False))
+    handles[-1].put(b'unlisted')
+    assert queue.get_nowait() == b'unlisted'
+    assert queue.attached_processes()[0]['handles'] == 16
+
+
+def test_unknown_role_is_rejected() -> None:
+    """Tests that roles other than producer, consumer and both are refused."""
+    with pytest.raises(ValueError, match='role'):
+        Queue(
+            name='test-attached-unknown-role',
+            element_size=8,
+            capacity=4,
+            role='observer',
+        )
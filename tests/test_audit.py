@@ -0,0 +1,83 @@
+import mmap
+import struct
+from collections.abc import Iterator
+from pathlib import Path
+
+import pytest
+
+import zeroq
+from zeroq import Queue
+
+NAME = 'test-audit'
+
+
+@pytest.fixture
+def queue() -> Iterator[Queue]:
+    """Returns a queue with two published items."""
+    queue = Queue(name=NAME, element_size=4, capacity=4, create=True)
+    queue.put(b'aaaa')
+    queue.put(b'bbbb')
+    if not Path(f'/dev/shm/{NAME}').exists():
+        pytest.skip('shared memory is not exposed under /dev/shm')
+    yield queue
+    queue.close()
+
+
+def corrupt_cell(index: int, sequence: int) -> None:
+    """Overwrites the sequence number of a cell in the segment."""
+    offset = zeroq.plan(4, 4)['cells_offset'] + index * 8
+    with Path(f'/dev/shm/{NAME}').open('r+b') as segment:
+        view = mmap.mmap(segment.fileno(), 0)
+        view[offset : offset + 8] = struct.pack('=Q', sequence)
+        view.close()
+
+
+def test_audit_consistent_queue(queue: Queue) -> None:
+    """Tests that a healthy queue passes the audit."""
+    report = queue.audit()
+
+    assert report['consistent']
+    assert report['positions_valid']
+    assert (report['head'], report['tail']) == (0, 2)
+    assert report['inconsistencies'] == []
+
+
+def test_audit_reports_and_fixes(queue: Queue) -> None:
+    """Tests that a trampled cell is reported and repaired."""
+    corrupt_cell(3, 12345)
+
+    report = queue.audit()
+    assert not report['consistent']
+    assert report['inconsistencies'] == [(3, 12345, 3)]
+    assert report['fixed'] == 0
+
+    report = queue.audit(fix=True)
+    assert report['fixed'] == 1
+    assert queue.audit()['consistent']
+
+    queue.put(b'cccc')
+    queue.put(b'dddd')
+    assert [queue.get() for _ in range(4)] == [
+        b'aaaa',
+        b'bbbb',
+        b'cccc',
+        b'dddd',
+    ]
+
+
+def test_attach_audit_modes(queue: Queue) -> None:
+    """Tests that attach-time audits raise or repair."""
+    corrupt_cell(0, 7)
+
+    with pytest.raises(RuntimeError, match='failed the consistency audit'):
+        Queue(name=NAME, create=False, audit='check')
+
+    attached = Queue(name=NAME, create=False, audit='fix')
+    assert attached.audit()['consistent']
+    assert attached.get() == b'aaaa'
+
+
+def test_attach_audit_invalid_mode(queue: Queue) -> None:
+    """Tests that unknown audit modes are rejected."""
+    with pytest.raises(ValueError, match='Unknown audit mode'):
+        Queue(name=NAME, create=False, audit='strict')
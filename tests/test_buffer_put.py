@@ -0,0 +1,55 @@
+import array
+import mmap
+
+import pytest
+
+from zeroq import Queue
+
+
+def test_put_accepts_buffers() -> None:
+    """Tests that put copies from any C-contiguous buffer."""
+    queue = Queue(name='test-buffer-put', element_size=4, capacity=8)
+    region = mmap.mmap(-1, 8)
+    region[:4] = b'mmap'
+    shorts = array.array('h')
+    shorts.frombytes(b'abcd')
+
+    queue.put(bytearray(b'barr'))
+    queue.put(memoryview(b'..view..')[2:6])
+    queue.put_nowait(memoryview(region)[:4])
+    queue.put(shorts)
+
+    received = [queue.get() for _ in range(4)]
+
+    assert received == [b'barr', b'view', b'mmap', b'abcd']
+    region.close()
+    queue.close()
+
+
+def test_put_urgent_accepts_buffers() -> None:
+    """Tests that the urgent lane takes buffers as well."""
+    queue = Queue(
+        name='test-buffer-put-urgent',
+        element_size=2,
+        capacity=4,
+        urgent_lane=True,
+    )
+
+    queue.put_urgent(memoryview(bytearray(b'up')))
+
+    assert queue.get() == b'up'
+    queue.close()
+
+
+def test_put_rejects_unusable_objects() -> None:
+    """Tests that non-contiguous views and non-buffers are rejected."""
+    queue = Queue(name='test-buffer-put-invalid', element_size=2, capacity=4)
+
+    with pytest.raises(BufferError):
+        queue.put(memoryview(b'abcd')[::2])
+    with pytest.raises(TypeError):
+        queue.put('ab')
+    with pytest.raises(ValueError):
+        queue.put(bytearray(3))
+    assert queue.empty()
+    queue.close()
@@ -0,0 +1,178 @@
+import threading
+
+import pytest
+
+from zeroq import Bus, Empty
+
+
+def test_every_handle_receives_every_message() -> None:
+    """Tests that messages are duplicated across handles."""
+    bus = Bus(name='test-bus', element_size=8, capacity=4)
+    first = Bus(name='test-bus', create=False)
+    second = Bus(name='test-bus', create=False)
+
+    assert bus.publish(b'a') == 0
+    assert bus.publish(b'bb') == 1
+
+    assert [first.receive(), first.receive()] == [b'a', b'bb']
+    assert [second.receive(), second.receive()] == [b'a', b'bb']
+    with pytest.raises(Empty):
+        first.receive(timeout=0)
+    assert len(second) == 0
+    assert bus.published == 2
+
+
+def test_late_handles_see_new_messages_only() -> None:
+    """Tests that a handle starts at the messages published after it."""
+    bus = Bus(name='test-bus-late', element_size=8, capacity=4)
+    bus.publish(b'old')
+    late = Bus(name='test-bus-late', create=False)
+
+    bus.publish(b'new')
+
+    assert late.cursor == 1
+    assert late.receive(timeout=0) == b'new'
+
+
+def test_slow_reader_skips_overwritten_messages() -> None:
+    """Tests that a lagging reader resumes at the oldest retained message."""
+    bus = Bus(name='test-bus-lag', element_size=8, capacity=4)
+    reader = Bus(name='test-bus-lag', create=False)
+
+    for i in range(10):
+        bus.publish(bytes([i]))
+
+    assert reader.receive(timeout=0) == bytes([6])
+    assert reader.missed == 6
+    assert [reader.receive(timeout=0) for _ in range(3)] == [
+        bytes([7]),
+        bytes([8]),
+        bytes([9]),
+    ]
+
+
+def test_receive_waits_for_publish() -> None:
+    """Tests that a blocked receive returns once a message is published."""
+    bus = Bus(name='test-bus-wait', element_size=8, capacity=2)
+    reader = Bus(name='test-bus-wait', create=False)
+    timer = threading.Timer(0.05, bus.publish, args=(b'late',))
+    timer.start()
+
+    assert reader.receive(timeout=5) == b'late'
+    timer.join()
+
+
+def test_invalid_parameters() -> None:
+    """Tests the element size limit and attaching with a mismatch."""
+    bus = Bus(name='test-bus-errors', element_size=4, capacity=2)
+
+    with pytest.raises(ValueError, match='does not fit'):
+        bus.publish(b'12345')
+    with pytest.raises(ValueError, match='exists with element_size 4'):
+        Bus(name='test-bus-errors', capacity=3, create=False)
+    with pytest.raises(ValueError, match='capacity > 0'):
+        Bus(name='test-bus-zero', element_size=4, capacity=0)
+
+
+def test_groups_share_messages() -> None:
+    """Tests load balancing within a group and duplication across groups."""
+    bus = Bus(name='test-bus-groups', element_size=8, capacity=16)
+    first = Bus(name='test-bus-groups', create=False, group='workers')
+    second = Bus(name='test-bus-groups', create=False, group='workers')
+    audit = Bus(name='test-bus-groups', create=False, group='audit')
+
+    for i in range(4):
+        bus.publish(bytes([i]))
+
+    assert first.receive(timeout=0) == bytes([0])
+    assert second.receive(timeout=0) == bytes([1])
+    assert first.receive(timeout=0) == bytes([2])
+    assert second.cursor == 3
+    assert [audit.receive(timeout=0) for _ in range(4)] == [
+        bytes([i]) for i in range(4)
+    ]
+    assert bus.groups() == {'workers': 3, 'audit': 4}
+    assert first.group == 'workers'
+    assert bus.group is None
+
+
+def test_group_members_receive_each_message_once() -> None:
+    """Tests that concurrent members of a group never duplicate messages."""
+    bus = Bus(name='test-bus-group-threads', element_size=8, capacity=4096)
+    members = [
+        Bus(name='test-bus-group-threads', create=False, group='g')
+        for _ in range(4)
+    ]
+    for i in range(2000):
+        bus.publish(i.to_bytes(8, 'little'))
+    received = [[] for _ in members]
+
+    def drain(index: int) -> None:
+        while True:
+            try:
+                received[index].append(members[index].receive(timeout=0))
+            except Empty:
+                return
+
+    threads = [
+        threading.Thread(target=drain, args=(i,)) for i in range(len(members))
+    ]
+    for thread in threads:
+        thread.start()
+    for thread in threads:
+        thread.join()
+
+    values = sorted(
+        int.from_bytes(item, 'little') for items in received for item in items
+    )
+    assert values == list(range(2000))
+
+
+def test_group_limits() -> None:
+    """Tests invalid group names and a full group table."""
+    bus = Bus(
+        name='test-bus-group-limits', element_size=8, capacity=2, max_groups=1
+    )
+    member = Bus(name='test-bus-group-limits', create=False, group='one')
+
+    with pytest.raises(ValueError, match='no room for another group'):
+        Bus(name='test-bus-group-limits', create=False, group='two')
+    with pytest.raises(ValueError, match='Group names take 1 to 48 bytes'):
+        Bus(name='test-bus-group-limits', create=False, group='x' * 49)
+    with pytest.raises(ValueError, match='max_groups 1'):
+        Bus(name='test-bus-group-limits', create=False, max_groups=2)
+    assert bus.groups() == {member.group: 0}
+
+
+def test_seek_replays_retained_messages() -> None:
+    """Tests seeking to the oldest, a given and the newest message."""
+    bus = Bus(name='test-bus-seek', element_size=8, capacity=4)
+    for i in range(6):
+        bus.publish(bytes([i]))
+    tool = Bus(name='test-bus-seek', create=False)
+
+    assert tool.seek('oldest') == 2
+    assert [tool.receive(timeout=0) for _ in range(4)] == [
+        bytes([i]) for i in range(2, 6)
+    ]
+    assert tool.seek(4) == 4
+    assert tool.receive(timeout=0) == bytes([4])
+    assert tool.seek('newest') == 6
+    with pytest.raises(Empty):
+        tool.receive(timeout=0)
+    assert tool.missed == 0
+
+
+def test_seek_errors() -> None:
+    """Tests seeking outside the retained messages or to unknown positions."""
+    bus = Bus(name='test-bus-seek-errors', element_size=8, capacity=2)
+    for i in range(4):
+        bus.publish(bytes([i]))
+
+    with pytest.raises(ValueError, match='retains messages 2 to 3'):
+        bus.seek(1)
+    with pytest.raises(ValueError, match='Cannot seek to message 5'):
+        bus.seek(5)
+    with pytest.raises(TypeError, match="'oldest', 'newest'"):
+        bus.seek('latest')
+    assert bus.oldest == 2
@@ -0,0 +1,77 @@
+import threading
+import time
+
+import pytest
+
+from zeroq import Cancelled, Queue
+
+
+def _blocked(target: object) -> tuple[threading.Thread, list[BaseException]]:
+    """Starts target in a thread, collecting what it raises."""
+    errors = []
+
+    def run() -> None:
+        try:
+            target()
+        except Exception as exc:  # noqa: BLE001
+            errors.append(exc)
+
+    thread = threading.Thread(target=run)
+    thread.start()
+    time.sleep(0.05)
+    return thread, errors
+
+
+def test_cancel_wakes_blocked_get() -> None:
+    """Tests that a get without timeout raises Cancelled once cancelled."""
+    queue = Queue(name='test-cancel-get', element_size=1, capacity=2)
+    thread, errors = _blocked(queue.get)
+
+    queue.cancel()
+    thread.join(timeout=2.0)
+
+    assert not thread.is_alive()
+    assert isinstance(errors[0], Cancelled)
+    queue.close()
+
+
+def test_cancel_wakes_blocked_put() -> None:
+    """Tests that a put on a full queue raises Cancelled once cancelled."""
+    queue = Queue(name='test-cancel-put', element_size=1, capacity=2)
+    queue.put(b'a')
+    queue.put(b'b')
+    thread, errors = _blocked(lambda: queue.put(b'c'))
+
+    queue.cancel()
+    thread.join(timeout=2.0)
+
+    assert isinstance(errors[0], Cancelled)
+    queue.close()
+
+
+def test_cancel_is_sticky_for_blocking_calls_only() -> None:
+    """Tests that later blocking calls fail while non-blocking ones work."""
+    queue = Queue(name='test-cancel-sticky', element_size=1, capacity=2)
+    queue.cancel()
+
+    with pytest.raises(Cancelled):
+        queue.get(timeout=1.0)
+    with pytest.raises(Cancelled):
+        queue.wait_readable()
+
+    queue.put_nowait(b'a')
+    assert queue.get_nowait() == b'a'
+    queue.close()
+
+
+def test_cancel_leaves_other_handles_alone() -> None:
+    """Tests that cancelling one handle does not affect another."""
+    queue = Queue(name='test-cancel-other', element_size=1, capacity=2)
+    other = Queue(name='test-cancel-other', create=False)
+
+    other.cancel()
+
+    queue.put(b'a', timeout=1.0)
+    assert queue.get(timeout=1.0) == b'a'
+    other.close()
+    queue.close()
@@ -0,0 +1,131 @@
+from pathlib import Path
+
+import pytest
+
+import zeroq
+from zeroq import CorruptMessage, Queue
+
+KEY = bytes(range(32))
+
+
+def _scribble(queue: Queue, index: int) -> None:
+    """Flips the first payload byte of slot index behind the queue's back."""
+    segment = Path('/dev/shm') / queue.name
+    if not segment.exists():
+        pytest.skip('shared memory is not exposed under /dev/shm')
+    info = queue.debug_info()
+    offset = info['data_offset'] + index * info['stride'] + info['meta_size']
+    with segment.open('r+b') as file:
+        file.seek(offset)
+        byte = file.read(1)
+        file.seek(offset)
+        file.write(bytes([byte[0] ^ 0xFF]))
+
+
+def test_checksummed_messages_round_trip() -> None:
+    """Tests that checksummed queues deliver messages unchanged."""
+    queue = Queue(
+        name='test-checksum-round-trip',
+        element_size=8,
+        capacity=8,
+        metadata=['sequence'],
+        headers_size=4,
+        checksum=True,
+    )
+    queue.put(b'abcdefgh', headers=b'hd')
+    queue.put_many([b'12345678', b'ABCDEFGH'])
+
+    message = queue.get_with_meta()
+    assert (message.payload, message.headers) == (b'abcdefgh', b'hd')
+    assert queue.get_many(2) == [b'12345678', b'ABCDEFGH']
+    assert queue.debug_info()['checksum'] is True
+
+
+def test_checksum_takes_four_bytes_per_slot() -> None:
+    """Tests that the checksum adds four bytes of metadata to each slot."""
+    plain = zeroq.plan(8, 16)
+    checked = zeroq.plan(8, 16, checksum=True)
+
+    assert checked['meta_size'] == plain['meta_size'] + 4
+    assert zeroq.required_size(8, 16, checksum=True) == checked['total_size']
+
+
+def test_scribbled_message_raises_corrupt_message() -> None:
+    """Tests that a payload changed in shared memory fails its checksum."""
+    queue = Queue(
+        name='test-checksum-scribbled',
+        element_size=8,
+        capacity=4,
+        checksum=True,
+    )
+    queue.put(b'abcdefgh')
+    queue.put(b'12345678')
+    _scribble(queue, 0)
+
+    with pytest.raises(CorruptMessage, match='checksum'):
+        queue.get_nowait()
+    assert queue.get_nowait() == b'12345678'
+
+
+def test_corrupt_message_is_runtime_error() -> None:
+    """Tests that CorruptMessage can be caught as RuntimeError."""
+    assert issubclass(CorruptMessage, RuntimeError)
+
+
+def test_attached_handles_verify_checksums() -> None:
+    """Tests that handles attaching by name verify checksums as well."""
+    producer = Queue(
+        name='test-checksum-attached',
+        element_size=8,
+        capacity=4,
+        checksum=True,
+    )
+    consumer = Queue(name='test-checksum-attached', create=False)
+    producer.put(b'abcdefgh')
+    _scribble(producer, 0)
+
+    with pytest.raises(CorruptMessage):
+        consumer.get(timeout=1.0)
+    assert consumer.debug_info()['checksum'] is True
+
+
+def test_zero_copy_paths_use_checksums() -> None:
+    """Tests that reserved slots are checksummed and views verified."""
+    queue = Queue(
+        name='test-checksum-zero-copy',
+        element_size=8,
+        capacity=4,
+        checksum=True,
+    )
+    with queue.reserve() as payload:
+        payload[:] = b'reserved'
+    assert queue.get_nowait() == b'reserved'
+
+    queue.put(b'abcdefgh')
+    with queue.get_buffer() as payload:
+        assert bytes(payload) == b'abcdefgh'
+
+    queue.put(b'abcdefgh')
+    _scribble(queue, 2)
+    with pytest.raises(CorruptMessage):
+        queue.get_buffer(timeout=0)
+    assert len(queue) == 0
+
+
+def test_encrypted_queues_checksum_ciphertext() -> None:
+    """Tests that checksums cover encrypted payloads as stored."""
+    queue = Queue(
+        name='test-checksum-encrypted',
+        element_size=8,
+        capacity=4,
+        encryption='chacha20-poly1305',
+        keys={1: KEY},
+        checksum=True,
+    )
+    queue.put(b'abcdefgh')
+    queue.put(b'12345678')
+    assert queue.get_nowait() == b'abcdefgh'
+    _scribble(queue, 1)
+
+    with pytest.raises(CorruptMessage):
+        queue.get_nowait()
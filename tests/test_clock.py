@@ -0,0 +1,54 @@
+import time
+
+import pytest
+
+from zeroq import Empty, Full, ManualClock, Queue
+
+
+def test_manual_clock_advances() -> None:
+    """Tests that a manual clock only moves when advanced."""
+    clock = ManualClock(start=1.5)
+    assert clock.time() == 1.5
+
+    clock.advance(2.0)
+    assert clock.time() == 3.5
+
+    with pytest.raises(ValueError):
+        clock.advance(-1.0)
+
+
+def test_get_timeout_uses_virtual_time() -> None:
+    """Tests that a long get timeout expires without sleeping in real time."""
+    clock = ManualClock()
+    queue = Queue(name='test-clock-get', element_size=8, capacity=2, create=True, clock=clock)
+
+    started = time.monotonic()
+    with pytest.raises(Empty):
+        queue.get(timeout=60.0)
+
+    assert time.monotonic() - started < 30.0
+    assert clock.time() > 60.0
+    assert queue.stats()['get_wait_time'] > 60.0
+
+
+def test_put_timeout_uses_virtual_time() -> None:
+    """Tests that backpressure delays advance the manual clock."""
+    clock = ManualClock()
+    queue = Queue(
+        name='test-clock-put',
+        element_size=8,
+        capacity=2,
+        create=True,
+        backpressure='exponential',
+        backpressure_base=0.5,
+        backpressure_max=60.0,
+        clock=clock,
+    )
+    queue.put(b'a' * 8)
+    queue.put(b'b' * 8)
+
+    with pytest.raises(Full):
+        queue.put(b'c' * 8, timeout=600.0)
+
+    assert 600.0 < clock.time() <= 660.0
+    assert queue.stats()['put_timeouts'] == 1
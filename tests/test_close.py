@@ -0,0 +1,48 @@
+import pytest
+
+from zeroq import Queue
+
+
+def test_context_manager_closes_queue() -> None:
+    """Tests that leaving a with block detaches the queue."""
+    with Queue(name='test-with', element_size=1, capacity=2) as queue:
+        queue.put(b'a')
+        assert queue.get() == b'a'
+
+    with pytest.raises(OSError, match='closed'):
+        queue.put(b'b')
+
+
+def test_context_manager_closes_on_error() -> None:
+    """Tests that exceptions propagate and still close the queue."""
+    with pytest.raises(KeyError):  # noqa: PT012
+        with Queue(name='test-with-error', element_size=1, capacity=2) as q:
+            raise KeyError
+
+    with pytest.raises(OSError, match='closed'):
+        len(q)
+
+
+def test_close_is_idempotent() -> None:
+    """Tests that closing twice, or after a with block, is harmless."""
+    with Queue(name='test-close-twice', element_size=1, capacity=2) as queue:
+        queue.close()
+    queue.close()
+
+
+def test_enter_rejects_closed_queue() -> None:
+    """Tests that a closed queue cannot be used as a context manager."""
+    queue = Queue(name='test-enter-closed', element_size=1, capacity=2)
+    queue.close()
+
+    with pytest.raises(OSError, match='closed'), queue:
+        pass
+
+
+def test_close_releases_segment_for_recreation() -> None:
+    """Tests that a closed creator's segment can be created again."""
+    with Queue(name='test-close-recreate', element_size=1, capacity=2):
+        pass
+
+    with Queue(name='test-close-recreate', element_size=1, capacity=4) as q:
+        assert q.maxsize == 4
@@ -0,0 +1,103 @@
+import json
+
+import pytest
+
+from zeroq import Queue
+
+
+class Upper:
+    """Custom codec storing strings upper-cased as UTF-8."""
+
+    def encode(self, obj: str) -> bytes:
+        """Encodes a string.
+
+        Args:
+            obj: The string to encode.
+
+        Returns:
+            The upper-cased string as UTF-8.
+        """
+        return obj.upper().encode()
+
+    def decode(self, data: bytes) -> str:
+        """Decodes a string.
+
+        Args:
+            data: The encoded string.
+
+        Returns:
+            The decoded string.
+        """
+        return data.decode()
+
+
+def test_json_codec() -> None:
+    """Tests that the json codec frames plain JSON readable by any language."""
+    queue = Queue(
+        name='test-codec-json', element_size=64, capacity=4, codec='json'
+    )
+    raw = Queue(name='test-codec-json', create=False)
+    item = {'id': 1, 'tags': ['a', 'b'], 'ok': True}
+
+    queue.put(item)
+    payload = raw.get()
+    length = int.from_bytes(payload[:4], 'little')
+
+    assert json.loads(payload[4 : 4 + length]) == item
+    queue.put_many([item, [1, 2]])
+    assert queue.get_many(2) == [item, [1, 2]]
+    raw.close()
+    queue.close()
+
+
+def test_custom_codec() -> None:
+    """Tests that objects with encode() and decode() act as codecs."""
+    queue = Queue(
+        name='test-codec-custom', element_size=32, capacity=4, codec=Upper()
+    )
+
+    queue.put('hello')
+
+    assert queue.get() == 'HELLO'
+    queue.close()
+
+
+@pytest.mark.parametrize('codec', ['msgpack', 'cbor'])
+def test_binary_codecs(codec: str) -> None:
+    """Tests the codecs backed by optional packages, if they are installed."""
+    pytest.importorskip({'msgpack': 'msgpack', 'cbor': 'cbor2'}[codec])
+    queue = Queue(
+        name='test-codec-bin', element_size=64, capacity=4, codec=codec
+    )
+    item = {'event': 'click', 'x': 10, 'data': b'\x00\x01'}
+
+    queue.put(item)
+
+    assert queue.get() == item
+    queue.close()
+
+
+def test_raw_codec_and_invalid_codecs() -> None:
+    """Tests the raw codec and the rejection of invalid codec arguments."""
+    queue = Queue(
+        name='test-codec-raw', element_size=4, capacity=2, codec='raw'
+    )
+    queue.put(b'abcd')
+    assert queue.get() == b'abcd'
+
+    with pytest.raises(ValueError, match="Unknown codec 'xml'"):
+        Queue(name='test-codec-raw', create=False, codec='xml')
+    with pytest.raises(TypeError, match='encode'):
+        Queue(name='test-codec-raw', create=False, codec=42)
+    with pytest.raises(ValueError, match='mutually exclusive'):
+        Queue(
+            name='test-codec-raw',
+            create=False,
+            serializer='pickle',
+            codec='json',
+        )
+    with pytest.raises(ValueError, match='requires the pickle codec'):
+        Queue(
+            name='test-codec-raw', create=False, codec='json', out_of_band=64
+        )
+    queue.close()
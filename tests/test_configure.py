@@ -0,0 +1,96 @@
+import logging
+
+import pytest
+
+from zeroq import Empty, ManualClock, Queue
+
+
+def test_configure_returns_current_options() -> None:
+    """Tests that configure() without arguments reports the constructor options."""
+    queue = Queue(
+        name='test-configure-read',
+        element_size=8,
+        capacity=2,
+        create=True,
+        adaptive=True,
+        spin_limit=5,
+    )
+
+    options = queue.configure()
+    assert options['adaptive'] is True
+    assert options['spin_limit'] == 5
+    assert options['backpressure'] is None
+    assert options['slow_op_threshold'] is None
+    queue.close()
+
+
+def test_configure_propagates_to_attached_handles() -> None:
+    """Tests that options set by one handle reach every attached handle."""
+    owner = Queue(name='test-configure-share', element_size=8, capacity=2, create=True)
+    attached = Queue(name='test-configure-share', create=False)
+
+    updated = owner.configure(busy_spin=True, drop_expired=True)
+    assert updated['busy_spin'] is True
+
+    options = attached.configure()
+    assert options['busy_spin'] is True
+    assert options['drop_expired'] is True
+
+    late = Queue(name='test-configure-share', create=False, adaptive=True)
+    assert late.configure()['busy_spin'] is True
+    assert late.configure()['adaptive'] is False
+
+    late.close()
+    attached.close()
+    owner.close()
+
+
+def test_configure_changes_wait_strategy_at_runtime() -> None:
+    """Tests that a reconfigured wait strategy is used by the next wait."""
+    queue = Queue(name='test-configure-wait', element_size=8, capacity=2, create=True)
+    attached = Queue(name='test-configure-wait', create=False)
+
+    queue.configure(busy_spin=True)
+    with pytest.raises(Empty):
+        attached.get(timeout=0.01)
+
+    assert attached.stats()['spin_count'] > 0
+    attached.close()
+    queue.close()
+
+
+def test_configure_slow_op_threshold(caplog: pytest.LogCaptureFixture) -> None:
+    """Tests that slow-operation logging can be enabled on a live queue."""
+    clock = ManualClock()
+    queue = Queue(
+        name='test-configure-slow', element_size=8, capacity=2, create=True, clock=clock
+    )
+    queue.configure(slow_op_threshold=0.5)
+
+    with caplog.at_level(logging.WARNING, logger='zeroq'), pytest.raises(Empty):
+        queue.get(timeout=1.0)
+
+    assert any('Slow get' in r.getMessage() for r in caplog.records)
+    queue.close()
+
+
+@pytest.mark.parametrize(
+    'options',
+    [
+        {'unknown': 1},
+        {'busy_spin': True, 'adaptive': True},
+        {'backpressure': 'quadratic'},
+        {'park_interval': 0.0, 'adaptive': True},
+        {'slow_op_threshold': -1.0},
+    ],
+)
+def test_configure_rejects_invalid_options(options: dict[str, object]) -> None:
+    """Tests that invalid options are rejected without changing anything."""
+    queue = Queue(name='test-configure-invalid', element_size=8, capacity=2, create=True)
+    before = queue.configure()
+
+    with pytest.raises(ValueError):
+        queue.configure(**options)
+
+    assert queue.configure() == before
+    queue.close()
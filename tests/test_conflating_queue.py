@@ -0,0 +1,86 @@
+import random
+import threading
+
+import pytest
+
+from zeroq import ConflatingQueue, Empty, Full
+
+
+def test_put_replaces_pending_value() -> None:
+    """Tests that a pending key keeps its position and takes the new value."""
+    queue = ConflatingQueue(
+        name='test-conflate', key_size=8, element_size=8, capacity=4
+    )
+    consumer = ConflatingQueue(name='test-conflate', create=False)
+
+    assert not queue.put(b'AAPL', b'1')
+    assert not queue.put(b'MSFT', b'2')
+    assert queue.put(b'AAPL', b'3')
+
+    assert len(consumer) == 2
+    assert consumer.get() == (b'AAPL', b'3')
+    assert consumer.get() == (b'MSFT', b'2')
+    assert not queue.put(b'AAPL', b'4')
+    assert consumer.get() == (b'AAPL', b'4')
+
+
+def test_full_and_empty() -> None:
+    """Tests that only new keys wait for room."""
+    queue = ConflatingQueue(
+        name='test-conflate-bounds', key_size=2, element_size=4, capacity=2
+    )
+
+    with pytest.raises(Empty):
+        queue.get(timeout=0)
+    queue.put(b'a', b'1')
+    queue.put(b'b', b'1')
+    with pytest.raises(Full):
+        queue.put(b'c', b'1', timeout=0.01)
+    assert queue.put(b'b', b'2', timeout=0)
+    with pytest.raises(ValueError, match='longer than key_size'):
+        queue.put(b'abc', b'1')
+    with pytest.raises(ValueError, match='does not fit'):
+        queue.put(b'a', b'12345')
+
+
+def test_matches_a_model() -> None:
+    """Tests random puts and gets against a dict-based model."""
+    queue = ConflatingQueue(
+        name='test-conflate-model', key_size=4, element_size=8, capacity=16
+    )
+    model = {}
+    rng = random.Random(7)
+
+    for i in range(5000):
+        if rng.random() < 0.6 and len(model) < 16:
+            key = rng.randrange(24).to_bytes(4, 'little')
+            replaced = queue.put(key, i.to_bytes(8, 'little'), timeout=0)
+            assert replaced == (key in model)
+            model[key] = i.to_bytes(8, 'little')
+        elif model:
+            key = next(iter(model))
+            assert queue.get(timeout=0) == (key, model.pop(key))
+    assert len(queue) == len(model)
+
+
+def test_concurrent_producers() -> None:
+    """Tests that the consumer ends with the last value of every key."""
+    queue = ConflatingQueue(
+        name='test-conflate-threads', key_size=1, element_size=4, capacity=8
+    )
+    latest = {}
+
+    def producer(key: int) -> None:
+        for i in range(1000):
+            queue.put(bytes([key]), i.to_bytes(4, 'little'), timeout=5)
+
+    threads = [threading.Thread(target=producer, args=(k,)) for k in range(8)]
+    for thread in threads:
+        thread.start()
+    for thread in threads:
+        thread.join()
+    while len(queue):
+        key, value = queue.get(timeout=0)
+        latest[key] = int.from_bytes(value, 'little')
+
+    assert latest == {bytes([k]): 999 for k in range(8)}
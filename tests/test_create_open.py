@@ -0,0 +1,88 @@
+import os
+import sys
+
+import pytest
+
+import zeroq
+from zeroq import Queue
+
+
+def test_create_then_open() -> None:
+    """Tests that open() attaches to a queue made by create()."""
+    created = Queue.create('test-create-open', element_size=1, capacity=4)
+    opened = Queue.open('test-create-open')
+
+    created.put(b'x')
+
+    assert opened.get() == b'x'
+    assert opened.maxsize == 4
+    opened.close()
+    created.close()
+
+
+def test_create_raises_already_exists() -> None:
+    """Tests that a taken name raises AlreadyExists, a FileExistsError."""
+    queue = Queue.create('test-create-taken', element_size=1, capacity=2)
+
+    with pytest.raises(zeroq.AlreadyExists, match='already exists'):
+        Queue.create('test-create-taken', element_size=1, capacity=2)
+    with pytest.raises(FileExistsError):
+        Queue(name='test-create-taken', element_size=1, capacity=2)
+    queue.close()
+
+
+def test_open_checks_expected_parameters() -> None:
+    """Tests that open() asserts the element_size and capacity it is given."""
+    queue = Queue.create('test-open-expect', element_size=64, capacity=8)
+
+    opened = Queue.open('test-open-expect', element_size=64, capacity=8)
+    opened.close()
+    with pytest.raises(
+        zeroq.InvalidParameters,
+        match='element_size 64 instead of the expected 4096',
+    ):
+        Queue.open('test-open-expect', element_size=4096)
+    with pytest.raises(ValueError, match='capacity 8 instead of the'):
+        Queue(name='test-open-expect', capacity=4, create=False)
+    with pytest.raises(zeroq.InvalidParameters, match=' and capacity'):
+        Queue.open('test-open-expect', element_size=32, capacity=16)
+    queue.close()
+
+
+def test_open_missing_queue() -> None:
+    """Tests that opening a queue nobody created raises OSError."""
+    with pytest.raises(OSError, match='Failed to open'):
+        Queue.open('test-open-missing')
+
+
+@pytest.mark.skipif(
+    not sys.platform.startswith('linux'), reason='/dev/shm is Linux-only'
+)
+def test_create_beyond_free_shared_memory() -> None:
+    """Tests that a queue larger than /dev/shm raises before mapping it."""
+    stat = os.statvfs('/dev/shm')
+    capacity = stat.f_bavail * stat.f_frsize // 4096 + 16
+
+    with pytest.raises(OSError, match='free in /dev/shm'):
+        Queue.create('test-create-huge', element_size=4096, capacity=capacity)
+    assert not zeroq.exists('test-create-huge')
+
+
+def test_options_are_forwarded() -> None:
+    """Tests that other constructor arguments pass through."""
+    queue = Queue.create(
+        'test-create-options', element_size=1, capacity=4, urgent_lane=True
+    )
+
+    queue.put_urgent(b'u')
+
+    assert queue.get() == b'u'
+    queue.close()
+
+
+def test_conflicting_mode_is_rejected() -> None:
+    """Tests that create and mode cannot be passed to the classmethods."""
+    with pytest.raises(TypeError, match="'create'"):
+        Queue.create('test-create-conflict', 1, 2, create=False)
+    with pytest.raises(TypeError, match="'mode'"):
+        Queue.open('test-create-conflict', mode='create')
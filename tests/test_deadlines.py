@@ -0,0 +1,58 @@
+import time
+
+import pytest
+
+from zeroq import Queue
+
+
+def test_deadline_is_propagated() -> None:
+    """Tests that a deadline set by the producer reaches the consumer."""
+    queue = Queue(name='test-deadline', element_size=4, capacity=4, metadata=['deadline'])
+    deadline = time.time() + 60.0
+    queue.put(b'abcd', deadline=deadline)
+    queue.put_nowait(b'efgh')
+
+    message = queue.get_with_meta()
+    assert message.deadline == pytest.approx(deadline, abs=1e-6)
+    assert queue.get_with_meta().deadline is None
+
+
+def test_deadline_requires_metadata_field() -> None:
+    """Tests that deadlines need the deadline metadata field."""
+    queue = Queue(name='test-deadline-missing', element_size=4, capacity=4)
+    with pytest.raises(ValueError, match="'deadline' metadata field"):
+        queue.put(b'abcd', deadline=time.time() + 1.0)
+
+
+@pytest.mark.parametrize('deadline', [-1.0, 0.0, float('nan')])
+def test_invalid_deadline(deadline: float) -> None:
+    """Tests that deadlines must be positive finite timestamps."""
+    queue = Queue(name='test-deadline-invalid', element_size=4, capacity=4, metadata=['deadline'])
+    with pytest.raises(ValueError, match='positive Unix timestamp'):
+        queue.put_nowait(b'abcd', deadline=deadline)
+
+
+def test_expired_messages_are_dropped() -> None:
+    """Tests that drop_expired skips stale messages and counts them."""
+    producer = Queue(
+        name='test-deadline-drop', element_size=4, capacity=4, metadata=['deadline']
+    )
+    consumer = Queue(name='test-deadline-drop', create=False, drop_expired=True)
+    producer.put(b'old1', deadline=time.time() - 1.0)
+    producer.put(b'keep', deadline=time.time() + 60.0)
+    producer.put(b'old2', deadline=time.time() - 1.0)
+    producer.put(b'none')
+
+    assert consumer.get_nowait() == b'keep'
+    assert consumer.get_nowait() == b'none'
+    assert consumer.empty()
+    assert consumer.stats()['expired'] == 2
+
+
+def test_expired_messages_are_kept_by_default() -> None:
+    """Tests that stale messages are delivered unless drop_expired is set."""
+    queue = Queue(name='test-deadline-keep', element_size=4, capacity=4, metadata=['deadline'])
+    queue.put(b'old1', deadline=time.time() - 1.0)
+
+    assert queue.get() == b'old1'
+    assert queue.stats()['expired'] == 0
@@ -0,0 +1,203 @@
+import mmap
+import os
+import signal
+import struct
+import subprocess
+import sys
+import threading
+import time
+from pathlib import Path
+from typing import Callable
+
+import pytest
+
+from zeroq import Deque, Empty, Full
+
+
+def test_both_ends() -> None:
+    """Tests that elements leave from the end they are popped at."""
+    deque = Deque(name='test-deque', element_size=8, capacity=4)
+
+    deque.push_back(b'b')
+    deque.push_back(b'c')
+    deque.push_front(b'a')
+
+    assert len(deque) == 3
+    assert deque.pop_back() == b'c'
+    assert deque.pop_front() == b'a'
+    assert deque.pop_front() == b'b'
+    assert len(deque) == 0
+
+
+def test_handles_share_the_deque() -> None:
+    """Tests that an attached handle steals what the creator pushed."""
+    owner = Deque(name='test-deque-steal', element_size=16, capacity=8)
+    thief = Deque(name='test-deque-steal', create=False)
+
+    for task in (b'oldest', b'middle', b'newest'):
+        owner.push_back(task)
+
+    assert thief.pop_front() == b'oldest'
+    assert owner.pop_back() == b'newest'
+    assert thief.capacity == 8
+    assert thief.element_size == 16
+
+
+def test_full_and_empty() -> None:
+    """Tests the timeouts and the element size limit."""
+    deque = Deque(name='test-deque-bounds', element_size=4, capacity=2)
+
+    with pytest.raises(Empty):
+        deque.pop_front(timeout=0)
+    deque.push_back(b'1')
+    deque.push_front(b'0')
+    with pytest.raises(Full):
+        deque.push_back(b'2', timeout=0.01)
+    assert deque.pop_back() == b'1'
+    with pytest.raises(ValueError, match='does not fit'):
+        deque.push_back(b'12345')
+
+
+def test_pop_waits_for_a_push() -> None:
+    """Tests that a blocked pop returns once another thread pushes."""
+    deque = Deque(name='test-deque-wait', element_size=8, capacity=2)
+    timer = threading.Timer(0.05, deque.push_front, args=(b'late',))
+    timer.start()
+
+    assert deque.pop_back(timeout=5) == b'late'
+    timer.join()
+
+
+def test_concurrent_push_and_steal() -> None:
+    """Tests that every element is popped exactly once across both ends."""
+    deque = Deque(name='test-deque-threads', element_size=8, capacity=16)
+    popped = []
+    done = threading.Event()
+
+    def owner() -> None:
+        for i in range(2000):
+            deque.push_back(struct.pack('<Q', i), timeout=5)
+            if i % 3 == 0:
+                try:
+                    popped.append(deque.pop_back(timeout=0))
+                except Empty:
+                    pass
+        done.set()
+
+    def thief() -> None:
+        while not done.is_set() or len(deque):
+            try:
+                popped.append(deque.pop_front(timeout=0.01))
+            except Empty:
+                pass
+
+    threads = [threading.Thread(target=owner)]
+    threads += [threading.Thread(target=thief) for _ in range(3)]
+    for thread in threads:
+        thread.start()
+    for thread in threads:
+        thread.join()
+
+    values = sorted(struct.unpack('<Q', item)[0] for item in popped)
+    assert values == list(range(2000))
+
+
+def test_attach_errors() -> None:
+    """Tests attaching with mismatched parameters or to a missing deque."""
+    deque = Deque(name='test-deque-attach', element_size=8, capacity=2)
+
+    with pytest.raises(ValueError, match='exists with element_size 8'):
+        Deque(name='test-deque-attach', capacity=3, create=False)
+    with pytest.raises(ValueError, match='capacity > 0'):
+        Deque(name='test-deque-zero', element_size=8, capacity=0)
+    with pytest.raises(OSError):
+        Deque(name='test-deque-missing', create=False)
+    del deque
+
+
+def _pid_namespace() -> int:
+    """Returns the inode of the PID namespace of this process, if any."""
+    path = Path('/proc/self/ns/pid')
+    return path.stat().st_ino if path.exists() else 0
+
+
+def _hold_lock(name: str, pid: int, namespace: int | None = None) -> None:
+    """Marks the spinlock of deque name as held by process pid."""
+    path = Path(f'/dev/shm/{name}')
+    if not path.exists():
+        pytest.skip('shared memory is not exposed under /dev/shm')
+    if namespace is None:
+        namespace = _pid_namespace()
+    with path.open('r+b') as segment:
+        view = mmap.mmap(segment.fileno(), 0)
+        struct.pack_into('=Q', view, 24, namespace << 32 | pid)
+        view.close()
+
+
+def _exited_pid() -> int:
+    """Returns the pid of a process that has exited."""
+    owner = subprocess.Popen([sys.executable, '-c', 'pass'])
+    owner.wait(timeout=10)
+    return owner.pid
+
+
+def _interrupt(call: Callable[[], object]) -> None:
+    """Runs call, which waits for a lock, until SIGUSR1 interrupts it."""
+
+    def handler(signum: int, frame: object) -> None:
+        raise RuntimeError('interrupted')
+
+    previous = signal.signal(signal.SIGUSR1, handler)
+    timer = threading.Timer(0.3, os.kill, (os.getpid(), signal.SIGUSR1))
+    timer.start()
+    started = time.monotonic()
+    try:
+        with pytest.raises(RuntimeError, match='interrupted'):
+            call()
+        assert time.monotonic() - started < 2.0
+    finally:
+        timer.join()
+        signal.signal(signal.SIGUSR1, previous)
+
+
+@pytest.mark.skipif(
+    sys.platform == 'win32',
+    reason='process liveness is only checked on Unix',
+)
+def test_lock_of_exited_owner_poisons_the_deque() -> None:
+    """Tests that a lock held by an exited process poisons the deque."""
+    deque = Deque(name='test-deque-dead-owner', element_size=1, capacity=2)
+    other = Deque(name='test-deque-dead-owner', create=False)
+    _hold_lock('test-deque-dead-owner', _exited_pid())
+
+    with pytest.raises(RuntimeError, match='recreate'):
+        deque.push_back(b'a')
+    with pytest.raises(RuntimeError, match='recreate'):
+        other.pop_front(timeout=0)
+
+
+@pytest.mark.skipif(os.name != 'posix', reason='needs SIGUSR1')
+def test_lock_of_other_pid_namespace_is_kept() -> None:
+    """Tests that owners in other PID namespaces are never seen to exit."""
+    deque = Deque(name='test-deque-foreign', element_size=1, capacity=2)
+    _hold_lock('test-deque-foreign', _exited_pid(), _pid_namespace() + 1)
+
+    _interrupt(lambda: deque.push_back(b'a'))
+    _hold_lock('test-deque-foreign', 0, 0)
+    deque.push_back(b'a')
+    assert deque.pop_front(timeout=0) == b'a'
+
+
+@pytest.mark.skipif(os.name != 'posix', reason='needs SIGUSR1')
+def test_signals_end_waits_for_the_lock() -> None:
+    """Tests that a raising handler ends a wait for a lock held elsewhere."""
+    deque = Deque(name='test-deque-live-owner', element_size=1, capacity=2)
+    owner = subprocess.Popen(
+        [sys.executable, '-c', 'input()'], stdin=subprocess.PIPE
+    )
+    _hold_lock('test-deque-live-owner', owner.pid)
+
+    try:
+        _interrupt(lambda: deque.push_back(b'a'))
+    finally:
+        owner.communicate(b'\n', timeout=10)
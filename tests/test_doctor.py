@@ -0,0 +1,142 @@
+import os
+import struct
+import time
+import zlib
+from pathlib import Path
+
+import pytest
+
+import zeroq
+from zeroq.__main__ import main
+
+
+def _fake_segment(path: Path, element_size: int, capacity: int) -> None:
+    fields = struct.pack(
+        '=IIQQQII',
+        int.from_bytes(b'ZQMQ', 'little'),
+        8,
+        element_size,
+        capacity - 1,
+        0,
+        0,
+        8,
+    )
+    header = struct.pack(
+        '=40sI4xQQQ8Q4IIIQ2I16Q',
+        fields,
+        zlib.crc32(fields),
+        *[0] * 36,
+    )
+    size = len(header) + capacity * (8 + element_size)
+    path.write_bytes(header.ljust(size, b'\0'))
+
+
+def test_diagnose_reports_every_check(tmp_path: Path) -> None:
+    """Tests that diagnose runs every check on an existing directory."""
+    findings = zeroq.diagnose(tmp_path)
+
+    checks = {finding.check for finding in findings}
+    assert {
+        'mount',
+        'capacity',
+        'hugepages',
+        'name_limit',
+        'permissions',
+        'orphans',
+    } <= checks
+    assert all(
+        f.severity in {'ok', 'info', 'warning', 'error'} for f in findings
+    )
+
+
+def test_diagnose_missing_directory(tmp_path: Path) -> None:
+    """Tests that a missing shared memory directory is reported."""
+    findings = zeroq.diagnose(tmp_path / 'missing')
+
+    assert findings[0].check == 'shm'
+    assert 'does not exist' in findings[0].message
+    assert findings[0].hint
+
+
+def test_diagnose_finds_orphaned_segment(tmp_path: Path) -> None:
+    """Tests that unmapped queue segments are reported as orphans."""
+    _fake_segment(tmp_path / 'stale-queue', element_size=16, capacity=4)
+    (tmp_path / 'not-a-queue').write_bytes(b'hello')
+
+    orphans = [f for f in zeroq.diagnose(tmp_path) if f.check == 'orphans']
+
+    assert len(orphans) == 1
+    assert orphans[0].severity == 'warning'
+    assert 'stale-queue' in orphans[0].message
+    assert 'rm ' in orphans[0].hint
+
+
+def test_diagnose_ignores_live_queue() -> None:
+    """Tests that a segment mapped by this process is not an orphan."""
+    if not Path('/proc/self/maps').exists():
+        pytest.skip('process mappings are not exposed under /proc')
+    queue = zeroq.Queue(
+        name='test-doctor-live', element_size=8, capacity=4, create=True
+    )
+
+    orphans = [f for f in zeroq.diagnose() if f.check == 'orphans']
+
+    assert not any('test-doctor-live' in f.message for f in orphans)
+    queue.close()
+
+
+def test_doctor_command_exit_code(tmp_path: Path) -> None:
+    """Tests that the doctor command fails only on errors."""
+    assert main(['doctor', '--shm-dir', str(tmp_path)]) == 0
+    assert main(['doctor', '--shm-dir', str(tmp_path / 'missing')]) == 1
+
+
+def test_find_abandoned_respects_idle_period(tmp_path: Path) -> None:
+    """Tests that only segments idle for the whole period are abandoned."""
+    old = tmp_path / 'old-queue'
+    fresh = tmp_path / 'fresh-queue'
+    _fake_segment(old, element_size=16, capacity=4)
+    _fake_segment(fresh, element_size=16, capacity=4)
+    (tmp_path / 'not-a-queue').write_bytes(b'hello')
+    hour_ago = time.time() - 3600
+    os.utime(old, (hour_ago, hour_ago))
+
+    assert zeroq.find_abandoned(tmp_path, idle=600) == [old]
+    assert zeroq.find_abandoned(tmp_path, idle=7200) == []
+
+
+def test_prune_command_lists_then_unlinks(
+    tmp_path: Path, capsys: pytest.CaptureFixture[str]
+) -> None:
+    """Tests that prune only removes segments when asked to."""
+    old = tmp_path / 'old-queue'
+    _fake_segment(old, element_size=16, capacity=4)
+    os.utime(old, (0, 0))
+    args = ['prune', '--shm-dir', str(tmp_path), '--idle', '60']
+
+    assert main(args) == 0
+    assert str(old) in capsys.readouterr().out
+    assert old.exists()
+
+    assert main([*args, '--unlink']) == 0
+    assert f'removed {old}' in capsys.readouterr().out
+    assert not old.exists()
+
+
+def test_queue_handles_refresh_heartbeat() -> None:
+    """Tests that attaching and closing a queue count as activity."""
+    segment = Path('/dev/shm/test-doctor-heartbeat')
+    queue = zeroq.Queue(
+        name='test-doctor-heartbeat', element_size=8, capacity=4, create=True
+    )
+    if not segment.exists():
+        queue.close()
+        pytest.skip('shared memory is not exposed under /dev/shm')
+    os.utime(segment, (0, 0))
+
+    attached = zeroq.Queue(name='test-doctor-heartbeat', create=False)
+    assert segment.stat().st_mtime > time.time() - 60
+    os.utime(segment, (0, 0))
+    attached.close()
+    assert segment.stat().st_mtime > time.time() - 60
+    queue.close()
@@ -0,0 +1,44 @@
+from zeroq import Queue
+
+
+def test_drain_returns_everything_queued() -> None:
+    """Tests that drain() returns all items in order, urgent ones first."""
+    queue = Queue(
+        name='test-drain', element_size=1, capacity=8, urgent_lane=True
+    )
+    for item in (b'a', b'b', b'c'):
+        queue.put(item)
+    queue.put_urgent(b'u')
+
+    assert queue.drain() == [b'u', b'a', b'b', b'c']
+    assert queue.empty()
+    assert queue.drain() == []
+    queue.close()
+
+
+def test_clear_discards_and_counts() -> None:
+    """Tests that clear() empties the queue and reports how much it dropped."""
+    queue = Queue(name='test-clear', element_size=1, capacity=4)
+    for item in (b'a', b'b', b'c', b'd'):
+        queue.put(item)
+
+    assert queue.clear() == 4
+    assert queue.empty()
+    assert queue.clear() == 0
+
+    queue.put(b'e')
+    assert queue.get() == b'e'
+    queue.close()
+
+
+def test_clear_frees_slots_after_wraparound() -> None:
+    """Tests that clearing a full queue leaves it fully usable again."""
+    queue = Queue(name='test-clear-wrap', element_size=1, capacity=2)
+    for _ in range(3):
+        queue.put(b'x')
+        queue.put(b'y')
+        assert queue.full()
+        assert queue.clear() == 2
+
+    assert not queue.full()
+    queue.close()
@@ -0,0 +1,119 @@
+from pathlib import Path
+
+import pytest
+
+from zeroq import DecryptionError, Queue
+
+KEY_1 = bytes(range(32))
+KEY_2 = bytes(range(32, 64))
+
+
+@pytest.mark.parametrize('cipher', ['chacha20-poly1305', 'aes-256-gcm'])
+def test_encrypted_roundtrip(cipher: str) -> None:
+    """Tests that encrypted payloads round-trip between handles."""
+    producer = Queue(
+        name='test-encrypted',
+        element_size=16,
+        capacity=4,
+        create=True,
+        encryption=cipher,
+        keys={1: KEY_1},
+    )
+    consumer = Queue(name='test-encrypted', create=False, keys={1: KEY_1})
+
+    producer.put(b'secret-payload!!')
+
+    assert consumer.get() == b'secret-payload!!'
+
+
+def test_plaintext_never_reaches_shared_memory() -> None:
+    """Tests that the segment only holds ciphertext."""
+    queue = Queue(
+        name='test-encrypted',
+        element_size=16,
+        capacity=4,
+        create=True,
+        encryption='chacha20-poly1305',
+        keys={1: KEY_1},
+    )
+    queue.put(b'secret-payload!!')
+
+    segment = Path('/dev/shm/test-encrypted')
+    if not segment.exists():
+        pytest.skip('shared memory is not exposed under /dev/shm')
+    assert b'secret-payload!!' not in segment.read_bytes()
+    assert queue.get() == b'secret-payload!!'
+
+
+def test_key_rotation() -> None:
+    """Tests that consumers accept old and new keys during rotation."""
+    producer = Queue(
+        name='test-encrypted',
+        element_size=4,
+        capacity=4,
+        create=True,
+        encryption='aes-256-gcm',
+        metadata=['sequence'],
+        keys={1: KEY_1},
+    )
+    consumer = Queue(
+        name='test-encrypted', create=False, keys={1: KEY_1, 2: KEY_2}
+    )
+
+    producer.put(b'old1')
+    producer.rotate_key(2, KEY_2)
+    producer.put(b'new2')
+
+    assert consumer.get_with_meta().payload == b'old1'
+    assert consumer.get_with_meta().payload == b'new2'
+
+
+def test_wrong_key_raises() -> None:
+    """Tests that a message sealed with an unknown key is rejected."""
+    producer = Queue(
+        name='test-encrypted',
+        element_size=4,
+        capacity=4,
+        create=True,
+        encryption='chacha20-poly1305',
+        keys={1: KEY_1},
+    )
+    wrong = Queue(name='test-encrypted', create=False, keys={1: KEY_2})
+    unknown = Queue(name='test-encrypted', create=False, keys={2: KEY_2})
+
+    producer.put(b'data')
+    producer.put(b'data')
+
+    with pytest.raises(DecryptionError, match='authentication failed'):
+        wrong.get_nowait()
+    with pytest.raises(DecryptionError, match='Unknown key id 1'):
+        unknown.get_nowait()
+
+
+def test_encryption_requires_keys() -> None:
+    """Tests that keys are required exactly for encrypted queues."""
+    with pytest.raises(ValueError, match='keys required'):
+        Queue(
+            name='test-encrypted',
+            element_size=4,
+            capacity=4,
+            create=True,
+            encryption='chacha20-poly1305',
+        )
+    with pytest.raises(ValueError, match='not encrypted'):
+        Queue(
+            name='test-encrypted',
+            element_size=4,
+            capacity=4,
+            create=True,
+            keys={1: KEY_1},
+        )
+    with pytest.raises(ValueError, match='Invalid key length'):
+        Queue(
+            name='test-encrypted',
+            element_size=4,
+            capacity=4,
+            create=True,
+            encryption='aes-256-gcm',
+            keys={1: b'short'},
+        )
@@ -0,0 +1,21 @@
+import queue
+
+import pytest
+
+import zeroq
+from zeroq import Queue
+
+
+def test_empty_and_full_extend_stdlib_exceptions() -> None:
+    """Tests that handlers for queue.Empty and queue.Full catch ours."""
+    assert issubclass(zeroq.Empty, queue.Empty)
+    assert issubclass(zeroq.Full, queue.Full)
+
+    q = Queue(name='test-stdlib-exceptions', element_size=1, capacity=2)
+    with pytest.raises(queue.Empty):
+        q.get_nowait()
+    q.put(b'a')
+    q.put(b'b')
+    with pytest.raises(queue.Full):
+        q.put(b'c', timeout=0)
+    q.close()
@@ -0,0 +1,79 @@
+import sys
+from pathlib import Path
+
+import pytest
+
+from zeroq import AlreadyExists, Queue, exists
+
+pytestmark = pytest.mark.skipif(
+    sys.platform == 'win32', reason='file backing needs mmap()'
+)
+
+
+def test_file_roundtrip(tmp_path: Path) -> None:
+    """Tests that a queue in a file works and is removed by its creator."""
+    path = tmp_path / 'queue.zq'
+    queue = Queue(
+        name='test-file',
+        element_size=16,
+        capacity=4,
+        backing='file',
+        path=path,
+    )
+    other = Queue(name='test-file', create=False, backing='file', path=path)
+
+    queue.put(b'x' * 16)
+
+    assert other.get() == b'x' * 16
+    assert path.stat().st_size > 0
+    assert path.stat().st_mode & 0o777 == 0o600
+    assert not exists('test-file')
+    other.close()
+    assert path.exists()
+    queue.close()
+    del queue
+    assert not path.exists()
+
+
+def test_file_survives_crashed_creator(tmp_path: Path) -> None:
+    """Tests that the file of a creator that never closed stays readable."""
+    path = tmp_path / 'crashed.zq'
+    queue = Queue(
+        name='test-file-crash',
+        element_size=8,
+        capacity=2,
+        backing='file',
+        path=str(path),
+    )
+    queue.put(b'survivor')
+    with open(path, 'rb') as file:
+        data = file.read()
+
+    assert b'survivor' in data
+    queue.close()
+
+
+def test_file_errors(tmp_path: Path) -> None:
+    """Tests that file options are validated."""
+    path = tmp_path / 'taken.zq'
+    path.write_bytes(b'')
+    with pytest.raises(AlreadyExists):
+        Queue(
+            name='test-file-err',
+            element_size=8,
+            capacity=2,
+            backing='file',
+            path=path,
+        )
+    assert path.exists()
+    with pytest.raises(ValueError, match='requires path'):
+        Queue(name='test-file-err', element_size=8, capacity=2, backing='file')
+    with pytest.raises(ValueError, match="requires backing='file'"):
+        Queue(name='test-file-err', element_size=8, capacity=2, path=path)
+    with pytest.raises(OSError, match='missing.zq'):
+        Queue(
+            name='test-file-err',
+            create=False,
+            backing='file',
+            path=tmp_path / 'missing.zq',
+        )
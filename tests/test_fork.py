@@ -0,0 +1,109 @@
+import os
+import select
+import time
+from typing import Callable
+
+import pytest
+
+import zeroq
+from zeroq import Queue
+
+pytestmark = pytest.mark.skipif(
+    not hasattr(os, 'fork'), reason='os.fork() is unavailable'
+)
+
+
+def run_in_child(target: Callable[[], None]) -> int:
+    """Runs target in a forked child and returns its exit code.
+
+    Args:
+        target: Function to run in the child; an exception fails the child.
+
+    Returns:
+        The exit code of the child, or -1 if it did not exit in time.
+    """
+    pid = os.fork()
+    if pid == 0:
+        code = 0
+        try:
+            target()
+        except BaseException:  # noqa: BLE001
+            code = 1
+        os._exit(code)
+    deadline = time.monotonic() + 10.0
+    while time.monotonic() < deadline:
+        done, status = os.waitpid(pid, os.WNOHANG)
+        if done:
+            return os.waitstatus_to_exitcode(status)
+        time.sleep(0.01)
+    os.kill(pid, 9)
+    os.waitpid(pid, 0)
+    return -1
+
+
+def test_child_uses_inherited_handle() -> None:
+    """Tests that a forked child can put through the handle of its parent."""
+    queue = Queue(name='test-fork-put', element_size=1, capacity=4)
+
+    def child() -> None:
+        queue.put(b'c')
+        queue.close()
+
+    assert run_in_child(child) == 0
+    assert queue.get(timeout=1.0) == b'c'
+    queue.close()
+
+
+def test_child_registers_inherited_handle() -> None:
+    """Tests that a forked consumer lists itself on first use."""
+    queue = Queue(name='test-fork-registry', element_size=1, capacity=4)
+    consumer = Queue(name='test-fork-registry', create=False, role='consumer')
+    queue.put(b'a')
+
+    def child() -> None:
+        assert consumer.get(timeout=1.0) == b'a'
+        processes = {p['pid']: p for p in queue.attached_processes()}
+        assert processes[os.getpid()]['roles'] == ['consumer']
+
+    assert run_in_child(child) == 0
+    parent, child_process = sorted(
+        queue.attached_processes(), key=lambda p: p['pid'] != os.getpid()
+    )
+    assert (parent['pid'], parent['handles']) == (os.getpid(), 2)
+    assert child_process['roles'] == ['consumer']
+    consumer.close()
+    queue.close()
+
+
+def test_child_neither_removes_nor_shuts_down() -> None:
+    """Tests that the child leaves the queue of its parent in place."""
+    queue = Queue(name='test-fork-owner', element_size=1, capacity=4)
+
+    def child() -> None:
+        queue.close()
+
+    assert run_in_child(child) == 0
+    assert run_in_child(lambda: None) == 0
+    assert zeroq.exists('test-fork-owner')
+    queue.put(b'p')
+    assert queue.get() == b'p'
+    queue.close()
+    assert not zeroq.exists('test-fork-owner')
+
+
+def test_child_restarts_readiness_watcher() -> None:
+    """Tests that a child gets a working fileno() instead of the parent's."""
+    queue = Queue(name='test-fork-fileno', element_size=1, capacity=4)
+    parent_fd = queue.fileno()
+
+    def child() -> None:
+        fd = queue.fileno()
+        assert fd != parent_fd
+        queue.put(b'x')
+        readable, _, _ = select.select([fd], [], [], 5.0)
+        assert readable == [fd]
+        queue.close()
+
+    assert run_in_child(child) == 0
+    assert queue.get(timeout=1.0) == b'x'
+    queue.close()
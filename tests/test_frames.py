@@ -0,0 +1,56 @@
+import pytest
+
+from zeroq import Queue
+from zeroq.frames import HEADER, FrameQueue, frame_size
+
+
+def test_frame_roundtrip() -> None:
+    """Tests that pixels, geometry and timestamp survive the queue."""
+    size = frame_size(4, 2, 'RGB24')
+    frames = FrameQueue(
+        Queue(name='test-frames', element_size=size, capacity=2)
+    )
+    pixels = bytes(range(24))
+
+    frames.put(pixels, width=4, height=2, pixel_format='RGB24', timestamp=123)
+    frame = frames.get()
+
+    assert size == HEADER.size + 24
+    assert bytes(frame.data) == pixels
+    assert (frame.width, frame.height, frame.stride) == (4, 2, 12)
+    assert (frame.pixel_format, frame.timestamp) == ('RGB24', 123)
+    frames.queue.close()
+
+
+def test_planar_and_padded_frames() -> None:
+    """Tests planar formats, explicit strides and smaller frames than slots."""
+    frames = FrameQueue(
+        Queue(name='test-frames-nv12', element_size=256, capacity=2)
+    )
+
+    frames.put(bytes(24), width=4, height=4, pixel_format='NV12')
+    frames.put(bytes(16), width=2, height=2, pixel_format='MJPG', stride=8)
+
+    nv12 = frames.get()
+    assert (len(nv12.data), nv12.stride) == (24, 4)
+    custom = frames.get()
+    assert (len(custom.data), custom.pixel_format) == (16, 'MJPG')
+    assert custom.timestamp > 0
+    frames.queue.close()
+
+
+def test_invalid_frames() -> None:
+    """Tests the errors for mismatched geometry and foreign messages."""
+    queue = Queue(name='test-frames-invalid', element_size=64, capacity=2)
+    frames = FrameQueue(queue)
+
+    with pytest.raises(ValueError, match='does not match'):
+        frames.put(bytes(5), width=2, height=2, pixel_format='GRAY8')
+    with pytest.raises(ValueError, match='does not fit'):
+        frames.put(bytes(64), width=8, height=8, pixel_format='GRAY8')
+    with pytest.raises(ValueError, match='pass the stride'):
+        frames.put(bytes(4), width=2, height=2, pixel_format='MJPG')
+    queue.put(bytes(64))
+    with pytest.raises(ValueError, match='not put by FrameQueue'):
+        frames.get()
+    queue.close()
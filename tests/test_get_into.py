@@ -0,0 +1,62 @@
+import mmap
+
+import pytest
+
+from zeroq import Empty, Queue
+
+
+def test_get_into_bytearray() -> None:
+    """Tests that the item is written into a reused bytearray."""
+    queue = Queue(name='test-get-into', element_size=4, capacity=4)
+    queue.put(b'abcd')
+    queue.put(b'efgh')
+    buffer = bytearray(4)
+
+    assert queue.get_into(buffer) == 4
+    assert buffer == b'abcd'
+    assert queue.get_into(buffer) == 4
+    assert buffer == b'efgh'
+    queue.close()
+
+
+def test_get_into_larger_buffers() -> None:
+    """Tests memoryview slices and mmaps larger than the element."""
+    queue = Queue(name='test-get-into-views', element_size=2, capacity=4)
+    queue.put(b'xy')
+    queue.put(b'zw')
+    backing = bytearray(b'....')
+    region = mmap.mmap(-1, 8)
+
+    queue.get_into(memoryview(backing)[1:])
+    queue.get_into(region)
+
+    assert backing == b'.xy.'
+    assert region[:3] == b'zw\x00'
+    region.close()
+    queue.close()
+
+
+def test_get_into_rejects_unusable_buffers() -> None:
+    """Tests that nothing is dequeued into read-only or short buffers."""
+    queue = Queue(name='test-get-into-invalid', element_size=4, capacity=4)
+    queue.put(b'keep')
+
+    with pytest.raises(BufferError):
+        queue.get_into(b'\x00' * 4)
+    with pytest.raises(ValueError, match='smaller than the element size'):
+        queue.get_into(bytearray(3))
+    with pytest.raises(TypeError):
+        queue.get_into(4)
+    assert queue.get() == b'keep'
+    queue.close()
+
+
+def test_get_into_timeout() -> None:
+    """Tests that an empty queue times out without touching the buffer."""
+    queue = Queue(name='test-get-into-empty', element_size=2, capacity=2)
+    buffer = bytearray(b'..')
+
+    with pytest.raises(Empty):
+        queue.get_into(buffer, timeout=0.01)
+    assert buffer == b'..'
+    queue.close()
@@ -0,0 +1,83 @@
+import pytest
+
+from zeroq import Empty, Queue
+
+KEY = bytes(range(32))
+
+
+def test_get_many_drains_up_to_max_items() -> None:
+    """Tests that a batch holds at most max_items items, in order."""
+    queue = Queue(name='test-get-many', element_size=4, capacity=16)
+    items = [i.to_bytes(4, 'little') for i in range(10)]
+    queue.put_many(items)
+
+    assert queue.get_many(4) == items[:4]
+    assert queue.get_many(100) == items[4:]
+    assert queue.empty()
+    queue.close()
+
+
+def test_get_many_wraps_around_the_ring() -> None:
+    """Tests that batches spanning the end of the ring stay in order."""
+    queue = Queue(name='test-get-many-wrap', element_size=4, capacity=4)
+    queue.put_many([b'aaaa', b'bbbb', b'cccc'])
+    assert queue.get_many(2) == [b'aaaa', b'bbbb']
+    queue.put_many([b'dddd', b'eeee', b'ffff'])
+
+    assert queue.get_many(8) == [b'cccc', b'dddd', b'eeee', b'ffff']
+    queue.close()
+
+
+def test_get_many_drains_urgent_lane_first() -> None:
+    """Tests that urgent messages lead the batch."""
+    queue = Queue(
+        name='test-get-many-urgent', element_size=4, capacity=8, urgent_lane=True
+    )
+    queue.put(b'data')
+    queue.put_urgent(b'ctrl')
+
+    assert queue.get_many(8) == [b'ctrl', b'data']
+    queue.close()
+
+
+def test_get_many_times_out_on_empty_queue() -> None:
+    """Tests that Empty is raised when no item arrives in time."""
+    queue = Queue(name='test-get-many-empty', element_size=4, capacity=4)
+
+    with pytest.raises(Empty):
+        queue.get_many(4, timeout=0.01)
+    with pytest.raises(ValueError, match='max_items'):
+        queue.get_many(0)
+    queue.close()
+
+
+def test_get_many_drops_expired_messages() -> None:
+    """Tests that expired messages are discarded from batches."""
+    queue = Queue(
+        name='test-get-many-expired',
+        element_size=4,
+        capacity=8,
+        metadata=['deadline'],
+        drop_expired=True,
+    )
+    queue.put(b'old!', deadline=1.0)
+    queue.put(b'new!')
+
+    assert queue.get_many(8) == [b'new!']
+    assert queue.stats()['expired'] == 1
+    queue.close()
+
+
+def test_get_many_decrypts_items() -> None:
+    """Tests that encrypted batches are opened."""
+    queue = Queue(
+        name='test-get-many-sealed',
+        element_size=8,
+        capacity=4,
+        encryption='aes-256-gcm',
+        keys={1: KEY},
+    )
+    queue.put_many([b'secret-1', b'secret-2'])
+
+    assert queue.get_many(4) == [b'secret-1', b'secret-2']
+    queue.close()
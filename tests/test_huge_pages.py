@@ -0,0 +1,229 @@
+import functools
+import os
+import shutil
+import subprocess
+import sys
+from collections.abc import Iterator
+from pathlib import Path
+
+import pytest
+
+from zeroq import Queue, exists
+
+SETTING = Path('/sys/kernel/mm/transparent_hugepage/shmem_enabled')
+
+POOL = Path('/sys/kernel/mm/hugepages/hugepages-2048kB')
+
+NR_HUGEPAGES = Path('/proc/sys/vm/nr_hugepages')
+
+HUGE_PAGE = 2 << 20
+
+PRIVATE_SHM = 'mount -t tmpfs -o huge=advise tmpfs /dev/shm && exec "$@"'
+
+
+def shm_huge_pages() -> bool:
+    """Returns whether files in /dev/shm get transparent huge pages."""
+    if not SETTING.exists():
+        return False
+    setting = SETTING.read_text()
+    if '[force]' in setting or '[deny]' in setting:
+        return '[force]' in setting
+    options = [
+        line.split()[3]
+        for line in Path('/proc/self/mounts').read_text().splitlines()
+        if line.split()[1] == '/dev/shm'
+    ]
+    huge = [
+        option.removeprefix('huge=')
+        for option in (options[-1].split(',') if options else [])
+        if option.startswith('huge=')
+    ]
+    return bool(huge) and huge[0] in ('always', 'within_size', 'advise')
+
+
+def free_huge_pages() -> int:
+    """Returns the number of 2 MiB pages free in the hugetlb pool."""
+    if not POOL.exists():
+        return 0
+    free = int((POOL / 'free_hugepages').read_text())
+    return free - int((POOL / 'resv_hugepages').read_text())
+
+
+@functools.cache
+def private_shm() -> list[str] | None:
+    """Returns a command prefix that runs its arguments with a /dev/shm of
+    their own mounted with huge=advise, if this process may mount one."""
+    unshare = shutil.which('unshare')
+    if (
+        sys.platform != 'linux'
+        or unshare is None
+        or os.geteuid() != 0
+        or '[deny]' in SETTING.read_text()
+    ):
+        return None
+    command = [unshare, '--mount', '--propagation', 'private']
+    command += ['sh', '-c', PRIVATE_SHM, 'sh']
+    probe = subprocess.run([*command, 'true'], capture_output=True)
+    return command if probe.returncode == 0 else None
+
+
+@pytest.fixture
+def hugetlb_page() -> Iterator[None]:
+    """Makes sure the hugetlb pool has a free page, reserving one for the
+    test if this process may."""
+    if free_huge_pages() >= 1:
+        yield
+        return
+    if not os.access(NR_HUGEPAGES, os.W_OK):
+        pytest.skip('no free pages in the hugetlb pool')
+    reserved = NR_HUGEPAGES.read_text()
+    NR_HUGEPAGES.write_text(str(int(reserved) + 1))
+    try:
+        if free_huge_pages() < 1:
+            pytest.skip('no memory for a huge page')
+        yield
+    finally:
+        NR_HUGEPAGES.write_text(reserved)
+
+
+def huge_kb(path: str) -> int:
+    """Returns the kB of huge pages this process maps from path."""
+    total = 0
+    mapped = False
+    for line in Path('/proc/self/smaps').read_text().splitlines():
+        fields = line.split()
+        if '-' in fields[0] and not fields[0].endswith(':'):
+            mapped = len(fields) > 5 and fields[5].startswith(path)
+        elif mapped and fields[0] in (
+            'ShmemPmdMapped:',
+            'Shared_Hugetlb:',
+            'Private_Hugetlb:',
+        ):
+            total += int(fields[1])
+    return total
+
+
+def roundtrip() -> None:
+    """Checks that huge-page queues in /dev/shm are mapped in huge pages."""
+    queue = Queue(
+        name='test-huge-pages', element_size=64, capacity=4, huge_pages=True
+    )
+    other = Queue(name='test-huge-pages', create=False, huge_pages=True)
+
+    queue.put(b'x' * 64)
+
+    assert other.get() == b'x' * 64
+    size = Path('/dev/shm/test-huge-pages').stat().st_size
+    assert size % HUGE_PAGE == 0
+    assert huge_kb('/dev/shm/test-huge-pages') >= 2 * HUGE_PAGE // 1024
+    other.close()
+    queue.close()
+
+
+def test_huge_pages_roundtrip() -> None:
+    """Tests that huge-page queues in /dev/shm are mapped in huge pages,
+    mounting a /dev/shm of its own with huge=advise if need be."""
+    if shm_huge_pages():
+        roundtrip()
+        return
+    command = private_shm()
+    if command is None:
+        pytest.skip('no huge pages for /dev/shm')
+    code = (
+        'import sys\n'
+        f'sys.path.insert(0, {str(Path(__file__).parent)!r})\n'
+        'from test_huge_pages import roundtrip\n'
+        'roundtrip()\n'
+    )
+    env = {**os.environ, 'PYTHONPATH': os.pathsep.join(sys.path)}
+    child = subprocess.run(
+        [*command, sys.executable, '-c', code],
+        env=env,
+        capture_output=True,
+        text=True,
+        timeout=60,
+    )
+    assert child.returncode == 0, child.stderr
+
+
+def test_huge_pages_memfd(hugetlb_page: None) -> None:
+    """Tests that memfd queues with huge pages come from the hugetlb pool."""
+    queue = Queue(
+        name='test-huge-memfd',
+        element_size=64,
+        capacity=4,
+        backing='memfd',
+        huge_pages=True,
+    )
+    other = Queue(
+        name='test-huge-memfd',
+        create=False,
+        fd=queue.segment_fd(),
+        huge_pages=True,
+    )
+
+    queue.put(b'x' * 64)
+
+    assert other.get() == b'x' * 64
+    assert huge_kb('/memfd:test-huge-memfd') >= 2 * HUGE_PAGE // 1024
+    other.close()
+    queue.close()
+
+
+@pytest.mark.skipif(
+    sys.platform != 'linux' or shm_huge_pages(),
+    reason='huge pages are available',
+)
+def test_huge_pages_disabled() -> None:
+    """Tests that /dev/shm without huge pages is reported up front."""
+    with pytest.raises(OSError, match='huge_pages'):
+        Queue(
+            name='test-huge-pages-off',
+            element_size=64,
+            capacity=4,
+            huge_pages=True,
+        )
+
+    assert not exists('test-huge-pages-off')
+
+
+@pytest.mark.skipif(
+    sys.platform != 'linux' or free_huge_pages() > 64,
+    reason='the hugetlb pool is too large to exhaust',
+)
+def test_huge_pages_pool_exhausted() -> None:
+    """Tests that a hugetlb pool without room is reported up front."""
+    with pytest.raises(OSError, match='nr_hugepages'):
+        Queue(
+            name='test-huge-memfd-full',
+            element_size=(free_huge_pages() + 1) * HUGE_PAGE,
+            capacity=2,
+            backing='memfd',
+            huge_pages=True,
+        )
+
+
+@pytest.mark.skipif(sys.platform != 'linux', reason='huge pages on Linux')
+def test_huge_pages_need_shared_memory(tmp_path: Path) -> None:
+    """Tests that huge pages are refused for queue files."""
+    with pytest.raises(ValueError, match='backing'):
+        Queue(
+            name='test-huge-pages-file',
+            element_size=64,
+            capacity=4,
+            backing='file',
+            path=tmp_path / 'queue',
+            huge_pages=True,
+        )
+
+
+@pytest.mark.skipif(sys.platform == 'linux', reason='huge pages on Linux')
+def test_huge_pages_unsupported() -> None:
+    """Tests that huge pages are refused outside Linux."""
+    with pytest.raises(NotImplementedError):
+        Queue(
+            name='test-huge-pages-os',
+            element_size=64,
+            capacity=4,
+            huge_pages=True,
+        )
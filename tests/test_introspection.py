@@ -0,0 +1,24 @@
+from zeroq import Queue
+
+
+def test_repr_shows_layout_and_state() -> None:
+    """Tests that repr() names the queue, its layout and whether it is closed."""
+    queue = Queue(name='test-repr', element_size=8, capacity=4)
+
+    assert repr(queue) == (
+        "Queue(name='test-repr', element_size=8, capacity=4, closed=False)"
+    )
+
+    queue.close()
+    assert repr(queue) == "Queue(name='test-repr', closed=True)"
+
+
+def test_name_outlives_close() -> None:
+    """Tests that the name getter still works on a closed queue."""
+    queue = Queue(name='test-name', element_size=1, capacity=2)
+    attached = Queue(name='test-name', create=False)
+
+    assert attached.name == 'test-name'
+    attached.close()
+    assert attached.name == 'test-name'
+    queue.close()
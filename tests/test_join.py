@@ -0,0 +1,87 @@
+import os
+import subprocess
+import sys
+import threading
+import time
+
+import pytest
+
+from zeroq import Full, Queue
+
+
+def test_join_waits_for_task_done() -> None:
+    """Tests that join() returns once every message was marked done."""
+    queue = Queue(name='test-join', element_size=1, capacity=4)
+    queue.put(b'a')
+    queue.put_many([b'b', b'c'])
+
+    assert not queue.join(timeout=0.05)
+
+    for _ in range(3):
+        queue.get()
+        queue.task_done()
+
+    assert queue.join(timeout=1.0)
+    queue.close()
+
+
+def test_task_done_too_many_times() -> None:
+    """Tests that task_done() without a pending message raises ValueError."""
+    queue = Queue(name='test-join-extra', element_size=1, capacity=2)
+
+    with pytest.raises(ValueError, match='too many times'):
+        queue.task_done()
+    queue.close()
+
+
+def test_failed_puts_and_clear_are_not_pending() -> None:
+    """Tests that refused and discarded messages do not block join()."""
+    queue = Queue(name='test-join-clear', element_size=1, capacity=2)
+    queue.put(b'a')
+    queue.put(b'b')
+    with pytest.raises(Full):
+        queue.put_nowait(b'c')
+
+    assert queue.clear() == 2
+    assert queue.join(timeout=0)
+    queue.close()
+
+
+def test_join_wakes_when_consumer_finishes() -> None:
+    """Tests that a blocked join() returns right after the last task_done()."""
+    queue = Queue(name='test-join-wake', element_size=1, capacity=2)
+    queue.put(b'a')
+
+    def consume() -> None:
+        queue.get()
+        time.sleep(0.05)
+        queue.task_done()
+
+    thread = threading.Thread(target=consume)
+    thread.start()
+
+    assert queue.join(timeout=2.0)
+    thread.join()
+    queue.close()
+
+
+def test_join_across_processes() -> None:
+    """Tests that a producer joins on work done by a consumer process."""
+    name = 'test-join-process'
+    queue = Queue(name=name, element_size=1, capacity=8)
+    for item in (b'a', b'b', b'c'):
+        queue.put(item)
+    code = (
+        'from zeroq import Queue\n'
+        f'queue = Queue(name={name!r}, create=False)\n'
+        'for _ in range(3):\n'
+        '    queue.get()\n'
+        '    queue.task_done()\n'
+        'queue.close()\n'
+    )
+    env = {**os.environ, 'PYTHONPATH': os.pathsep.join(sys.path)}
+    consumer = subprocess.Popen([sys.executable, '-c', code], env=env)
+
+    assert queue.join(timeout=10.0)
+    assert consumer.wait(timeout=10) == 0
+    queue.close()
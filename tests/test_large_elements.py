@@ -0,0 +1,41 @@
+import os
+
+import pytest
+
+from zeroq import Queue
+from zeroq.testing import run_exchange
+
+# Above the size from which payloads are copied with non-temporal stores,
+# and odd, so the copies have unaligned heads and tails.
+SIZE = 64 * 1024 + 3
+
+
+@pytest.mark.parametrize('metadata', [None, ['sequence', 'timestamp']])
+def test_large_payloads_roundtrip(metadata: list[str] | None) -> None:
+    """Tests that large payloads survive the streaming copies intact."""
+    queue = Queue(
+        name='test-large',
+        element_size=SIZE,
+        capacity=2,
+        metadata=metadata,
+    )
+    payloads = [os.urandom(SIZE) for _ in range(3)]
+
+    queue.put(payloads[0])
+    assert queue.get() == payloads[0]
+    queue.put(payloads[1])
+    buffer = bytearray(SIZE)
+    assert queue.get_into(buffer) == SIZE
+    assert buffer == payloads[1]
+    queue.put(memoryview(payloads[2]))
+    assert queue.get() == payloads[2]
+    queue.close()
+
+
+def test_large_payloads_across_processes() -> None:
+    """Tests that no process reads a large payload before all of it lands."""
+    report = run_exchange(
+        producers=2, consumers=2, messages=300, element_size=SIZE, capacity=2
+    )
+
+    report.verify()
@@ -0,0 +1,138 @@
+import ctypes
+import json
+import struct
+from multiprocessing import shared_memory
+from pathlib import Path
+
+import pytest
+
+import zeroq
+from zeroq import zeroq as native
+
+GOLDEN_DIR = Path(__file__).parent / 'golden'
+
+
+def _golden() -> str:
+    layout = json.loads(zeroq.layout_descriptor())
+    path = GOLDEN_DIR / (
+        f'layout-{layout["pointer_width"]}-v{layout["layout_version"]}.json'
+    )
+    if not path.exists():
+        pytest.skip(f'no golden layout for this platform: {path.name}')
+    return path.read_text()
+
+
+def test_layout_matches_golden() -> None:
+    """Tests that this build reproduces the checked-in golden layout."""
+    golden = _golden()
+
+    zeroq.verify_layout(golden)
+    assert json.loads(zeroq.layout_descriptor()) == json.loads(golden)
+
+
+def test_verify_layout_reports_mismatches() -> None:
+    """Tests that differing, unknown and missing entries are all reported."""
+    layout = json.loads(zeroq.layout_descriptor())
+    layout['header.size'] += 8
+    layout['header.bogus.offset'] = 0
+    del layout['envelope.size']
+
+    with pytest.raises(ValueError, match='header.size') as exc:
+        zeroq.verify_layout(json.dumps(layout))
+    assert 'header.bogus.offset: unknown entry' in str(exc.value)
+    assert 'envelope.size: missing' in str(exc.value)
+
+
+def test_verify_layout_rejects_malformed_input() -> None:
+    """Tests that non-flat JSON is rejected."""
+    with pytest.raises(ValueError, match='flat JSON'):
+        zeroq.verify_layout('{"header": {"size": 56}}')
+
+
+def test_layout_matches_live_queue() -> None:
+    """Tests that the described header offsets match a real segment."""
+    layout = json.loads(zeroq.layout_descriptor())
+    queue = zeroq.Queue(
+        name='test-conformance', element_size=24, capacity=16, create=True
+    )
+    segment = Path('/dev/shm/test-conformance')
+    if not segment.exists():
+        queue.close()
+        pytest.skip('shared memory is not exposed under /dev/shm')
+    data = segment.read_bytes()
+
+    def field(name: str) -> int:
+        offset = layout[f'header.{name}.offset']
+        return struct.unpack_from('=Q', data, offset)[0]
+
+    assert field('magic') & 0xFFFFFFFF == layout['header.magic']
+    assert field('element_size') == 24
+    assert field('buffer_mask') == 15
+    assert len(data) == (
+        layout['sample.plain.data_offset'] + layout['sample.plain.data_size']
+    )
+    queue.close()
+
+
+def test_c_api_verifies_layout() -> None:
+    """Tests the C entry points exported by the shared library."""
+    library = ctypes.CDLL(native.__file__)
+    library.zeroq_layout_descriptor.restype = ctypes.c_char_p
+    library.zeroq_verify_layout.argtypes = [ctypes.c_char_p]
+    library.zeroq_verify_layout.restype = ctypes.c_int
+
+    descriptor = library.zeroq_layout_descriptor()
+    assert descriptor.decode() == zeroq.layout_descriptor()
+    assert library.zeroq_verify_layout(descriptor) == 0
+    assert library.zeroq_verify_layout(b'{"header.size": 0}') > 0
+    assert library.zeroq_verify_layout(None) == -1
+
+
+def test_attach_rejects_foreign_segment() -> None:
+    """Tests that attaching to a segment without a queue header fails."""
+    foreign = shared_memory.SharedMemory(
+        name='test-foreign-segment', create=True, size=4096
+    )
+    foreign.buf[:8] = b'garbage!'
+    try:
+        with pytest.raises(OSError, match='holds no zeroq queue'):
+            zeroq.Queue(name='test-foreign-segment', create=False)
+    finally:
+        foreign.close()
+        foreign.unlink()
+
+
+def test_attach_rejects_other_layout_version() -> None:
+    """Tests that a queue of another layout version is refused by name."""
+    layout = json.loads(zeroq.layout_descriptor())
+    queue = zeroq.Queue(name='test-layout-version', element_size=8, capacity=2)
+    segment = Path('/dev/shm/test-layout-version')
+    if not segment.exists():
+        queue.close()
+        pytest.skip('shared memory is not exposed under /dev/shm')
+    with segment.open('r+b') as file:
+        file.seek(layout['header.layout_version.offset'])
+        file.write(struct.pack('=I', layout['layout_version'] + 1))
+
+    with pytest.raises(OSError, match='layout version'):
+        zeroq.Queue(name='test-layout-version', create=False)
+    queue.close()
+
+
+def test_attach_rejects_trampled_header() -> None:
+    """Tests that a header overwritten after creation fails its checksum."""
+    layout = json.loads(zeroq.layout_descriptor())
+    queue = zeroq.Queue(
+        name='test-trampled-header', element_size=8, capacity=2
+    )
+    segment = Path('/dev/shm/test-trampled-header')
+    if not segment.exists():
+        queue.close()
+        pytest.skip('shared memory is not exposed under /dev/shm')
+    with segment.open('r+b') as file:
+        file.seek(layout['header.element_size.offset'])
+        file.write(struct.pack('=Q', 1 << 20))
+
+    with pytest.raises(OSError, match='corrupt header'):
+        zeroq.Queue(name='test-trampled-header', create=False)
+    queue.close()
@@ -0,0 +1,55 @@
+from pathlib import Path
+
+import pytest
+
+import zeroq
+
+
+def test_required_size_matches_segment() -> None:
+    """Tests that required_size matches the segment created for a queue."""
+    size = zeroq.required_size(64, 8, metadata=['sequence'], headers_size=8)
+    queue = zeroq.Queue(
+        name='test-plan',
+        element_size=64,
+        capacity=8,
+        create=True,
+        metadata=['sequence'],
+        headers_size=8,
+    )
+
+    segment = Path('/dev/shm/test-plan')
+    if not segment.exists():
+        pytest.skip('shared memory is not exposed under /dev/shm')
+    assert segment.stat().st_size == size
+    queue.close()
+
+
+def test_plan_regions_are_consistent() -> None:
+    """Tests that the planned regions tile the segment."""
+    layout = zeroq.plan(24, 16, encryption='chacha20-poly1305')
+
+    assert layout['capacity'] == 16
+    assert layout['element_size'] == 24
+    assert layout['slot_size'] == layout['element_size'] + layout['meta_size']
+    assert layout['cells_offset'] >= layout['header_size']
+    assert layout['data_offset'] >= layout['cells_offset'] + layout['cells_size']
+    assert layout['data_size'] == layout['slot_size'] * layout['capacity']
+    assert layout['total_size'] == layout['data_offset'] + layout['data_size']
+    assert layout['total_size'] == zeroq.required_size(
+        24, 16, encryption='chacha20-poly1305'
+    )
+
+
+def test_plan_without_options_has_no_metadata() -> None:
+    """Tests that plain queues carry no per-slot metadata."""
+    layout = zeroq.plan(8, 2)
+
+    assert layout['meta_size'] == 0
+    assert layout['slot_size'] == 8
+
+
+@pytest.mark.parametrize('capacity', [0, 1, 3, 12])
+def test_required_size_rejects_invalid_capacity(capacity: int) -> None:
+    """Tests that invalid capacities are rejected before creating anything."""
+    with pytest.raises(ValueError, match='Buffer'):
+        zeroq.required_size(8, capacity)
@@ -0,0 +1,131 @@
+import mmap
+import os
+import struct
+import subprocess
+import sys
+import threading
+from pathlib import Path
+
+import pytest
+
+from zeroq import Empty, LvcSlot
+
+
+def test_put_replaces_value() -> None:
+    """Tests that get() returns the latest value only."""
+    slot = LvcSlot(name='test-lvc', element_size=8)
+    reader = LvcSlot(name='test-lvc', create=False)
+
+    with pytest.raises(Empty):
+        reader.get()
+    slot.put(b'first')
+    slot.put(b'second')
+
+    assert reader.get() == b'second'
+    assert reader.get() == b'second'
+    assert reader.version == 2
+
+
+def test_get_changed_skips_to_newest() -> None:
+    """Tests that get_changed() returns each newer value once."""
+    slot = LvcSlot(name='test-lvc-changed', element_size=8)
+    reader = LvcSlot(name='test-lvc-changed', create=False)
+
+    slot.put(b'1')
+    slot.put(b'2')
+    assert reader.get_changed(timeout=0) == b'2'
+    with pytest.raises(Empty):
+        reader.get_changed(timeout=0.01)
+
+    timer = threading.Timer(0.05, slot.put, args=(b'3',))
+    timer.start()
+    assert reader.get_changed(timeout=5) == b'3'
+    timer.join()
+
+
+def test_readers_never_see_torn_values() -> None:
+    """Tests that concurrent writes never leave a half-written value."""
+    slot = LvcSlot(name='test-lvc-torn', element_size=4096)
+    stop = threading.Event()
+    torn = []
+
+    def writer(tag: int) -> None:
+        i = 0
+        while not stop.is_set():
+            i += 1
+            slot.put(struct.pack('<I', tag * 1000000 + i) * 1024)
+
+    def reader() -> None:
+        for _ in range(2000):
+            value = slot.get()
+            if value != value[:4] * 1024:
+                torn.append(value[:8])
+
+    slot.put(bytes(4096))
+    writers = [threading.Thread(target=writer, args=(i,)) for i in range(2)]
+    for thread in writers:
+        thread.start()
+    reader()
+    stop.set()
+    for thread in writers:
+        thread.join()
+
+    assert not torn
+
+
+def test_invalid_parameters() -> None:
+    """Tests the element size limit and attaching with a mismatch."""
+    slot = LvcSlot(name='test-lvc-errors', element_size=4)
+
+    with pytest.raises(ValueError, match='does not fit'):
+        slot.put(b'12345')
+    with pytest.raises(ValueError, match='exists with element_size 4'):
+        LvcSlot(name='test-lvc-errors', element_size=8, create=False)
+    with pytest.raises(ValueError, match='element_size between'):
+        LvcSlot(name='test-lvc-none')
+
+
+def _interrupt_put(name: str, owner: int) -> None:
+    """Leaves the value of slot name mid-write, under a lock held by owner."""
+    path = Path(f'/dev/shm/{name}')
+    if not path.exists():
+        pytest.skip('shared memory is not exposed under /dev/shm')
+    pid_ns = Path('/proc/self/ns/pid')
+    namespace = pid_ns.stat().st_ino if pid_ns.exists() else 0
+    with path.open('r+b') as segment:
+        view = mmap.mmap(segment.fileno(), 0)
+        struct.pack_into('=Q', view, 16, namespace << 32 | owner)
+        (seq,) = struct.unpack_from('=Q', view, 24)
+        struct.pack_into('=Q', view, 24, seq | 1)
+        view.close()
+
+
+def test_value_being_written_is_waited_for() -> None:
+    """Tests that readers park instead of spinning on a value being written."""
+    slot = LvcSlot(name='test-lvc-writing', element_size=4)
+    slot.put(b'a')
+    _interrupt_put('test-lvc-writing', os.getpid())
+
+    with pytest.raises(Empty):
+        slot.get_changed(timeout=0.1)
+
+
+@pytest.mark.skipif(
+    sys.platform == 'win32',
+    reason='process liveness is only checked on Unix',
+)
+def test_value_of_exited_writer_is_replaced() -> None:
+    """Tests that a value left unfinished is never read and gets replaced."""
+    slot = LvcSlot(name='test-lvc-exited', element_size=4)
+    slot.put(b'a')
+    writer = subprocess.Popen([sys.executable, '-c', 'pass'])
+    writer.wait(timeout=10)
+    _interrupt_put('test-lvc-exited', writer.pid)
+
+    with pytest.raises(Empty, match='unfinished'):
+        slot.get()
+    with pytest.raises(Empty):
+        slot.get_changed(timeout=0.1)
+    slot.put(b'b')
+    assert slot.get() == b'b'
+    assert slot.version == 2
@@ -0,0 +1,91 @@
+import os
+import socket
+import subprocess
+import sys
+from pathlib import Path
+
+import pytest
+
+from zeroq import Queue, exists
+
+pytestmark = pytest.mark.skipif(
+    sys.platform != 'linux', reason='memfd is Linux only'
+)
+
+
+def test_memfd_roundtrip() -> None:
+    """Tests that anonymous queues work and never appear by name."""
+    queue = Queue(
+        name='test-memfd', element_size=16, capacity=4, backing='memfd'
+    )
+    other = Queue(name='test-memfd', create=False, fd=queue.segment_fd())
+
+    queue.put(b'x' * 16)
+
+    assert other.get() == b'x' * 16
+    assert not exists('test-memfd')
+    assert os.readlink(f'/proc/self/fd/{queue.segment_fd()}').startswith(
+        '/memfd:test-memfd'
+    )
+    other.close()
+    queue.close()
+
+
+def test_memfd_pass_over_socket() -> None:
+    """Tests that another process attaches through a passed descriptor."""
+    queue = Queue(
+        name='test-memfd-child', element_size=8, capacity=2, backing='memfd'
+    )
+    parent, child = socket.socketpair()
+    code = (
+        'import os, socket\n'
+        'from zeroq import Queue\n'
+        f'conn = socket.socket(fileno={child.fileno()})\n'
+        '_, fds, _, _ = socket.recv_fds(conn, 16, 1)\n'
+        "queue = Queue('test-memfd-child', create=False, fd=fds[0])\n"
+        'os.close(fds[0])\n'
+        'conn.sendall(queue.get(timeout=5.0))\n'
+        'queue.close()\n'
+    )
+    env = {**os.environ, 'PYTHONPATH': os.pathsep.join(sys.path)}
+    consumer = subprocess.Popen(
+        [sys.executable, '-c', code], env=env, pass_fds=[child.fileno()]
+    )
+
+    socket.send_fds(parent, [b'queue'], [queue.segment_fd()])
+    queue.put(b'12345678')
+
+    assert parent.recv(8) == b'12345678'
+    assert consumer.wait(timeout=10) == 0
+    parent.close()
+    child.close()
+    queue.close()
+
+
+def test_memfd_freed_with_last_handle() -> None:
+    """Tests that the segment goes away once no handle holds it."""
+    queue = Queue(
+        name='test-memfd-free', element_size=8, capacity=2, backing='memfd'
+    )
+    fd = queue.segment_fd()
+    link = Path(f'/proc/self/fd/{fd}')
+    assert link.exists()
+
+    queue.close()
+    del queue
+
+    assert not link.exists() or 'test-memfd-free' not in os.readlink(link)
+
+
+def test_memfd_errors() -> None:
+    """Tests that descriptor options are validated."""
+    with pytest.raises(ValueError, match='requires fd'):
+        Queue(name='test-memfd-err', create=False, backing='memfd')
+    with pytest.raises(ValueError, match='create=false'):
+        Queue(name='test-memfd-err', element_size=8, capacity=2, fd=0)
+    with pytest.raises(ValueError, match='Unknown backing'):
+        Queue(name='test-memfd-err', element_size=8, capacity=2, backing='x')
+    named = Queue(name='test-memfd-named', element_size=8, capacity=2)
+    with pytest.raises(ValueError, match='named segment'):
+        named.segment_fd()
+    named.close()
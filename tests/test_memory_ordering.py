@@ -0,0 +1,65 @@
+"""Stress tests for the publication of payloads between cores.
+
+On weakly ordered CPUs (aarch64, POWER) a consumer may observe a slot's
+sequence before the payload unless the two are ordered explicitly. These
+tests make producers and consumers on different cores reuse a few large
+slots as fast as possible and check every byte of every message, so a
+missing barrier shows up as torn messages on those platforms.
+"""
+
+import threading
+
+import pytest
+
+from zeroq import SpscQueue
+from zeroq.testing import ExchangeReport, run_exchange
+
+
+@pytest.mark.parametrize(
+    ('producers', 'consumers'), [(1, 1), (2, 2), (4, 4)]
+)
+def test_payloads_are_whole_across_processes(
+    producers: int, consumers: int
+) -> None:
+    """Tests that no process reads a payload before all of it is written."""
+    report = run_exchange(
+        producers=producers,
+        consumers=consumers,
+        messages=2000,
+        element_size=4096,
+        capacity=4,
+    )
+
+    report.verify()
+    assert report.torn == []
+
+
+def test_report_detects_torn_messages() -> None:
+    """Tests that verify() flags torn messages."""
+    report = ExchangeReport(
+        producers=1, messages=1, received=[[(0, 0)]], torn=[(0, 0)]
+    )
+
+    with pytest.raises(AssertionError, match='1 torn'):
+        report.verify()
+
+
+def test_spsc_payloads_are_whole() -> None:
+    """Tests that the consumer of an SpscQueue never reads a partial payload."""
+    queue = SpscQueue(name='test-ordering-spsc', element_size=4096, capacity=2)
+    torn = []
+
+    def consume() -> None:
+        consumer = SpscQueue(name='test-ordering-spsc', create=False)
+        for seq in range(5000):
+            payload = consumer.get(timeout=10)
+            if payload != bytes([seq & 0xFF]) * len(payload):
+                torn.append(seq)
+
+    thread = threading.Thread(target=consume)
+    thread.start()
+    for seq in range(5000):
+        queue.put(bytes([seq & 0xFF]) * 4096, timeout=10)
+    thread.join()
+
+    assert torn == []
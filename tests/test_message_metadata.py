@@ -0,0 +1,95 @@
+import os
+import time
+
+import pytest
+
+from zeroq import Queue
+
+
+def test_get_with_meta_without_metadata() -> None:
+    """Tests that only the payload is populated when no metadata is enabled."""
+    queue = Queue(name='test-meta', element_size=4, capacity=4, create=True)
+    queue.put(b'data')
+
+    message = queue.get_with_meta()
+
+    assert message.payload == b'data'
+    assert message.sequence is None
+    assert message.timestamp is None
+    assert message.dequeued_at is None
+    assert message.producer_id is None
+    assert message.headers is None
+
+
+def test_get_with_meta_all_fields() -> None:
+    """Tests that enabled metadata fields are recorded and decoded."""
+    queue = Queue(
+        name='test-meta',
+        element_size=4,
+        capacity=4,
+        create=True,
+        metadata=['sequence', 'timestamp', 'producer_id'],
+        headers_size=16,
+    )
+    before = time.time()
+    queue.put(b'aaaa', headers=b'trace-id')
+    queue.put_nowait(b'bbbb')
+
+    first = queue.get_with_meta()
+    second = queue.get_with_meta()
+
+    assert (first.payload, second.payload) == (b'aaaa', b'bbbb')
+    assert (first.sequence, second.sequence) == (0, 1)
+    assert first.timestamp is not None
+    assert first.dequeued_at is not None
+    assert before <= first.timestamp <= first.dequeued_at
+    assert first.producer_id == os.getpid()
+    assert first.headers == b'trace-id'
+    assert second.headers == b''
+
+
+def test_metadata_is_transparent_to_get() -> None:
+    """Tests that plain get returns only the payload of metadata queues."""
+    queue = Queue(
+        name='test-meta',
+        element_size=4,
+        capacity=4,
+        create=True,
+        metadata=['sequence'],
+        headers_size=4,
+    )
+    queue.put(b'data', headers=b'h')
+
+    attached = Queue(name='test-meta', create=False)
+
+    assert attached.get() == b'data'
+
+
+def test_headers_validation() -> None:
+    """Tests that oversized or unexpected headers are rejected."""
+    plain = Queue(name='test-meta', element_size=4, capacity=4, create=True)
+    with pytest.raises(ValueError, match='without headers_size'):
+        plain.put(b'data', headers=b'h')
+    plain.close()
+
+    queue = Queue(
+        name='test-meta',
+        element_size=4,
+        capacity=4,
+        create=True,
+        headers_size=2,
+    )
+    with pytest.raises(ValueError, match='Headers too large'):
+        queue.put_nowait(b'data', headers=b'abc')
+
+
+def test_unknown_metadata_field() -> None:
+    """Tests that unknown metadata field names are rejected."""
+    with pytest.raises(ValueError, match='Unknown metadata field'):
+        Queue(
+            name='test-meta',
+            element_size=4,
+            capacity=4,
+            create=True,
+            metadata=['checksum'],
+        )
@@ -0,0 +1,40 @@
+import pytest
+
+from zeroq.testing import ExchangeReport, run_exchange, temp_queue_name
+
+
+@pytest.mark.parametrize(
+    ('producers', 'consumers'), [(1, 1), (2, 1), (1, 2), (3, 3)]
+)
+def test_exchange_across_processes(producers: int, consumers: int) -> None:
+    """Tests that messages cross process boundaries exactly once, in order."""
+    report = run_exchange(
+        producers=producers, consumers=consumers, messages=500, capacity=16
+    )
+
+    report.verify()
+    assert sum(len(pairs) for pairs in report.received) == producers * 500
+
+
+def test_exchange_rejects_small_elements() -> None:
+    """Tests that elements too small for a record are rejected."""
+    with pytest.raises(ValueError, match='element_size'):
+        run_exchange(element_size=4)
+
+
+def test_report_detects_violations() -> None:
+    """Tests that verify() flags lost, duplicated and reordered messages."""
+    report = ExchangeReport(
+        producers=1, messages=4, received=[[(0, 0), (0, 2), (0, 1), (0, 2)]]
+    )
+
+    assert report.missing() == {(0, 3)}
+    assert report.duplicates() == {(0, 2)}
+    assert report.reordered() == [(0, 1)]
+    with pytest.raises(AssertionError, match='missing'):
+        report.verify()
+
+
+def test_temp_queue_names_are_unique() -> None:
+    """Tests that temporary queue names do not repeat."""
+    assert len({temp_queue_name() for _ in range(100)}) == 100
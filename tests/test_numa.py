@@ -0,0 +1,62 @@
+import sys
+from pathlib import Path
+
+import pytest
+
+from zeroq import Queue, exists
+
+NODES = Path('/sys/devices/system/node')
+
+
+@pytest.mark.skipif(
+    not (NODES / 'node0').exists(), reason='no NUMA topology exposed'
+)
+def test_numa_node_roundtrip() -> None:
+    """Tests that a queue bound to a node works as usual."""
+    for policy in ('bind', 'preferred'):
+        name = f'test-numa-{policy}'
+        try:
+            queue = Queue(
+                name=name,
+                element_size=64,
+                capacity=4,
+                numa_node=0,
+                numa_policy=policy,
+            )
+        except OSError as exc:
+            pytest.skip(f'mbind refused: {exc}')
+        other = Queue(name=name, create=False)
+
+        queue.put(b'x' * 64)
+
+        assert other.get() == b'x' * 64
+        other.close()
+        queue.close()
+
+
+@pytest.mark.skipif(sys.platform != 'linux', reason='NUMA on Linux only')
+def test_numa_node_missing() -> None:
+    """Tests that nodes without memory are refused before creating."""
+    with pytest.raises(ValueError, match='NUMA node 4095'):
+        Queue(
+            name='test-numa-missing',
+            element_size=64,
+            capacity=4,
+            numa_node=4095,
+        )
+
+    assert not exists('test-numa-missing')
+
+
+def test_numa_policy_unknown() -> None:
+    """Tests that unknown policies are refused."""
+    with pytest.raises(ValueError, match='numa_policy'):
+        Queue(
+            name='test-numa-policy',
+            element_size=64,
+            capacity=4,
+            numa_node=0,
+            numa_policy='interleave',
+        )
+
+    assert not exists('test-numa-policy')
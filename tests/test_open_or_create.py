@@ -0,0 +1,100 @@
+import threading
+
+import pytest
+
+from zeroq import Queue
+
+
+def test_first_handle_creates_and_second_attaches() -> None:
+    """Tests that open_or_create handles share one queue in either role."""
+    first = Queue(
+        name='test-ooc-share', element_size=1, capacity=4, mode='open_or_create'
+    )
+    second = Queue(
+        name='test-ooc-share', element_size=1, capacity=4, mode='open_or_create'
+    )
+
+    first.put(b'a')
+
+    assert second.get() == b'a'
+    second.close()
+    first.close()
+
+
+def test_mismatched_layout_is_rejected() -> None:
+    """Tests that attaching with other sizes raises instead of reinterpreting."""
+    queue = Queue(name='test-ooc-mismatch', element_size=8, capacity=4)
+
+    with pytest.raises(ValueError, match='does not match'):
+        Queue(
+            name='test-ooc-mismatch',
+            element_size=8,
+            capacity=8,
+            mode='open_or_create',
+        )
+    with pytest.raises(ValueError, match='does not match'):
+        Queue(
+            name='test-ooc-mismatch',
+            element_size=4,
+            capacity=4,
+            mode='open_or_create',
+        )
+    queue.close()
+
+
+def test_concurrent_starts_agree_on_one_queue() -> None:
+    """Tests that racing handles end up attached to the same queue."""
+    handles = []
+    errors = []
+    barrier = threading.Barrier(8)
+
+    def start() -> None:
+        barrier.wait()
+        try:
+            handles.append(
+                Queue(
+                    name='test-ooc-race',
+                    element_size=1,
+                    capacity=16,
+                    mode='open_or_create',
+                )
+            )
+        except Exception as exc:  # noqa: BLE001
+            errors.append(exc)
+
+    threads = [threading.Thread(target=start) for _ in range(8)]
+    for thread in threads:
+        thread.start()
+    for thread in threads:
+        thread.join()
+
+    assert not errors
+    for i, handle in enumerate(handles):
+        handle.put(bytes([i]))
+    assert sorted(handles[0].get_nowait()[0] for _ in handles) == list(
+        range(len(handles))
+    )
+    for handle in handles:
+        handle.close()
+
+
+def test_explicit_modes() -> None:
+    """Tests that mode='create' and mode='open' override create."""
+    created = Queue(
+        name='test-ooc-modes',
+        element_size=1,
+        capacity=2,
+        create=False,
+        mode='create',
+    )
+    opened = Queue(name='test-ooc-modes', mode='open')
+
+    assert opened.maxsize == created.maxsize
+    opened.close()
+    created.close()
+
+
+def test_unknown_mode_is_rejected() -> None:
+    """Tests that a misspelled mode raises ValueError."""
+    with pytest.raises(ValueError, match='Unknown mode'):
+        Queue(name='test-ooc-unknown', element_size=1, capacity=2, mode='new')
@@ -0,0 +1,128 @@
+import pickle
+from pathlib import Path
+
+import pytest
+
+from zeroq import Full, Queue
+
+
+class Blob:
+    """Holds a buffer pickled out of band with protocol 5."""
+
+    def __init__(self, data: bytearray | memoryview) -> None:
+        """Initializes the blob.
+
+        Args:
+            data: The buffer held by the blob.
+        """
+        self.data = data
+
+    def __reduce_ex__(self, protocol: int) -> tuple:
+        """Pickles the buffer through a PickleBuffer.
+
+        Args:
+            protocol: The pickle protocol in use.
+
+        Returns:
+            The reconstructor and its arguments.
+        """
+        return Blob, (pickle.PickleBuffer(self.data),)
+
+
+def side_segments(name: str) -> list[str]:
+    """Returns the side segments of the queue name, where /dev/shm exists.
+
+    Args:
+        name: Name of the queue.
+
+    Returns:
+        The names of the side segments.
+    """
+    shm = Path('/dev/shm')
+    if not shm.is_dir():
+        pytest.skip('segments are not listed under /dev/shm')
+    return sorted(p.name for p in shm.glob(f'{name}-oob-*'))
+
+
+def test_large_buffers_travel_out_of_band() -> None:
+    """Tests that large buffers are mapped from side segments, then removed."""
+    name = 'test-oob'
+    queue = Queue(
+        name=name,
+        element_size=128,
+        capacity=4,
+        serializer='pickle',
+        out_of_band=64,
+    )
+
+    queue.put(Blob(bytearray(b'x' * 4096)))
+    assert len(side_segments(name)) == 1
+    received = queue.get()
+
+    assert isinstance(received.data, memoryview)
+    assert received.data == b'x' * 4096
+    del received
+    assert not side_segments(name)
+    queue.close()
+
+
+def test_small_buffers_stay_in_band() -> None:
+    """Tests that buffers below the threshold are pickled in the slot."""
+    name = 'test-oob-small'
+    queue = Queue(
+        name=name,
+        element_size=128,
+        capacity=4,
+        serializer='pickle',
+        out_of_band=64,
+    )
+
+    queue.put(Blob(bytearray(b'small')))
+
+    assert not side_segments(name)
+    assert bytes(queue.get().data) == b'small'
+    queue.close()
+
+
+def test_failed_put_removes_side_segments() -> None:
+    """Tests that the side segments of a message that was not sent go away."""
+    name = 'test-oob-full'
+    queue = Queue(
+        name=name,
+        element_size=128,
+        capacity=2,
+        serializer='pickle',
+        out_of_band=64,
+    )
+    queue.put(1)
+    queue.put(2)
+
+    with pytest.raises(Full):
+        queue.put_nowait(Blob(bytearray(4096)))
+
+    assert not side_segments(name)
+    queue.close()
+
+
+def test_out_of_band_requires_serializer() -> None:
+    """Tests that out_of_band is rejected without a serializer."""
+    with pytest.raises(ValueError, match='requires a serializer'):
+        Queue(name='test-oob-raw', element_size=8, capacity=2, out_of_band=64)
+
+
+def test_numpy_arrays_out_of_band() -> None:
+    """Tests that numpy arrays are rebuilt on top of their side segment."""
+    np = pytest.importorskip('numpy')
+    queue = Queue(
+        name='test-oob-numpy',
+        element_size=512,
+        capacity=2,
+        serializer='pickle',
+        out_of_band=1024,
+    )
+    array = np.arange(10_000, dtype=np.float64).reshape(100, 100)
+
+    queue.put({'frame': array})
+
+    np.testing.assert_array_equal(queue.get()['frame'], array)
+    queue.close()
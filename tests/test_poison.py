@@ -0,0 +1,88 @@
+import json
+import mmap
+import struct
+from collections.abc import Iterator
+from pathlib import Path
+
+import pytest
+
+from zeroq import Empty, ManualClock, Queue, layout_descriptor
+
+NAME = 'test-poison'
+
+
+def reserve_without_publishing() -> None:
+    """Simulates a producer that reserved the next slot and died mid-write."""
+    with Path(f'/dev/shm/{NAME}').open('r+b') as segment:
+        view = mmap.mmap(segment.fileno(), 0)
+        offset = json.loads(layout_descriptor())['header.enqueue_pos.offset']
+        (tail,) = struct.unpack_from('=Q', view, offset)
+        struct.pack_into('=Q', view, offset, tail + 1)
+        view.close()
+
+
+@pytest.fixture
+def owner() -> Iterator[Queue]:
+    """Returns the queue owning the segment, with a wedged head slot and one item behind it."""
+    queue = Queue(
+        name=NAME, element_size=4, capacity=4, create=True, track_owners=True
+    )
+    if not Path(f'/dev/shm/{NAME}').exists():
+        pytest.skip('shared memory is not exposed under /dev/shm')
+    reserve_without_publishing()
+    queue.put(b'next')
+    yield queue
+    queue.close()
+
+
+def test_health_reports_stalled_slot(owner: Queue) -> None:
+    """Tests that a slot stalled past poison_timeout makes the queue unhealthy."""
+    clock = ManualClock()
+    consumer = Queue(name=NAME, create=False, poison_timeout=5.0, clock=clock)
+
+    health = consumer.health()
+    assert health['healthy']
+    assert health['stalled'] == [('main', 0, 0.0)]
+
+    clock.advance(5.0)
+    health = consumer.health()
+    assert not health['healthy']
+    assert health['stalled'] == [('main', 0, 5.0)]
+    assert health['poison_skipped'] == 0
+
+
+def test_poisoned_slot_without_owner_is_not_skipped(owner: Queue) -> None:
+    """Tests that skip_poisoned never skips a slot of an unknown producer."""
+    clock = ManualClock()
+    consumer = Queue(
+        name=NAME,
+        create=False,
+        poison_timeout=1.0,
+        skip_poisoned=True,
+        clock=clock,
+    )
+
+    with pytest.raises(Empty):
+        consumer.get(timeout=10.0)
+    assert consumer.stats()['poison_skipped'] == 0
+    assert consumer.health()['healthy']
+    clock.advance(1.0)
+    assert not consumer.health()['healthy']
+
+
+def test_stalled_slot_blocks_without_skipping(owner: Queue) -> None:
+    """Tests that consumers keep waiting on a stalled slot by default."""
+    consumer = Queue(name=NAME, create=False, poison_timeout=1.0, clock=ManualClock())
+
+    with pytest.raises(Empty):
+        consumer.get(timeout=2.0)
+    assert consumer.stats()['poison_skipped'] == 0
+
+
+def test_poison_options() -> None:
+    """Tests that health() needs poison_timeout and skip_poisoned owners."""
+    queue = Queue(name='test-poison-options', element_size=4, capacity=2)
+    with pytest.raises(ValueError, match='poison_timeout'):
+        queue.health()
+    with pytest.raises(ValueError, match='track_owners'):
+        Queue(name='test-poison-options', create=False, skip_poisoned=True)
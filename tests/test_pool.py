@@ -0,0 +1,93 @@
+import struct
+import threading
+
+import pytest
+
+from zeroq import Full, Pool, Queue
+
+
+def test_blocks_travel_by_index() -> None:
+    """Tests that consumers read payloads through the enqueued block index."""
+    pool = Pool(name='test-pool', block_size=1 << 20, blocks=4)
+    queue = Queue(name='test-pool-queue', capacity=4, fmt='<IQ')
+    payload = bytes(range(256)) * 4096
+
+    queue.put((pool.write(payload), len(payload)))
+    consumer = Pool(name='test-pool', create=False)
+    block, size = queue.get()
+
+    assert consumer.read(block, size) == payload
+    consumer.free(block)
+    assert pool.available == 4
+    queue.close()
+
+
+def test_views_write_in_place() -> None:
+    """Tests that views expose the block memory to every handle."""
+    pool = Pool(name='test-pool-view', block_size=64, blocks=2)
+    other = Pool(name='test-pool-view', create=False)
+    block = pool.alloc()
+
+    pool.view(block)[:5] = b'hello'
+
+    assert bytes(other.view(block)[:5]) == b'hello'
+    pool.free(block)
+
+
+def test_reference_counting() -> None:
+    """Tests that blocks return to the pool once every reference is freed."""
+    pool = Pool(name='test-pool-refs', block_size=8, blocks=1)
+    block = pool.alloc()
+    pool.retain(block)
+
+    pool.free(block)
+    with pytest.raises(Full):
+        pool.alloc()
+    pool.free(block)
+
+    assert pool.available == 1
+    with pytest.raises(ValueError, match='not allocated'):
+        pool.free(block)
+    with pytest.raises(IndexError):
+        pool.read(5)
+    with pytest.raises(ValueError, match='does not fit'):
+        pool.write(b'x' * 9)
+
+
+def test_concurrent_alloc_and_free() -> None:
+    """Tests that threads never receive the same block twice."""
+    pool = Pool(name='test-pool-threads', block_size=8, blocks=8)
+    errors = []
+
+    def worker(tag: int) -> None:
+        for _ in range(2000):
+            try:
+                block = pool.alloc()
+            except Full:
+                continue
+            pool.view(block)[:8] = struct.pack('<Q', tag)
+            if pool.read(block, 8) != struct.pack('<Q', tag):
+                errors.append(block)
+            pool.free(block)
+
+    threads = [threading.Thread(target=worker, args=(i,)) for i in range(8)]
+    for thread in threads:
+        thread.start()
+    for thread in threads:
+        thread.join()
+
+    assert not errors
+    assert pool.available == 8
+
+
+def test_attach_errors() -> None:
+    """Tests attaching with mismatched parameters or to a plain queue."""
+    pool = Pool(name='test-pool-attach', block_size=16, blocks=2)
+    queue = Queue(name='test-pool-not', element_size=8, capacity=2)
+
+    with pytest.raises(ValueError, match='exists with block_size 16'):
+        Pool(name='test-pool-attach', block_size=32, create=False)
+    with pytest.raises(OSError, match='holds no pool'):
+        Pool(name='test-pool-not', create=False)
+    queue.close()
+    del pool
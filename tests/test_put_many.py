@@ -0,0 +1,105 @@
+import threading
+
+import pytest
+
+from zeroq import Queue
+
+KEY = bytes(range(32))
+
+
+def test_put_many_enqueues_in_order() -> None:
+    """Tests that a batch is delivered in order and counted."""
+    queue = Queue(name='test-put-many', element_size=4, capacity=16)
+    items = [i.to_bytes(4, 'little') for i in range(10)]
+
+    assert queue.put_many(items) == 10
+    assert len(queue) == 10
+    assert [queue.get() for _ in range(10)] == items
+    queue.close()
+
+
+def test_put_many_accepts_empty_batch() -> None:
+    """Tests that an empty batch returns immediately."""
+    queue = Queue(name='test-put-many-empty', element_size=4, capacity=4)
+
+    assert queue.put_many([]) == 0
+    assert queue.empty()
+    queue.close()
+
+
+def test_put_many_waits_for_consumers() -> None:
+    """Tests that batches larger than the free space wrap around the ring."""
+    queue = Queue(name='test-put-many-wrap', element_size=4, capacity=4)
+    queue.put(b'head')
+    queue.get()
+    items = [bytes([i]) * 4 for i in range(4)]
+
+    assert queue.put_many(items) == 4
+    assert queue.full()
+    assert [queue.get() for _ in range(4)] == items
+    queue.close()
+
+
+def test_put_many_validates_before_enqueuing() -> None:
+    """Tests that a malformed item rejects the whole batch."""
+    queue = Queue(name='test-put-many-invalid', element_size=4, capacity=4)
+
+    with pytest.raises(ValueError):
+        queue.put_many([b'good', b'bad'])
+    assert queue.empty()
+    queue.close()
+
+
+def test_put_many_reports_partial_progress_on_timeout() -> None:
+    """Tests that a timed-out batch returns how many items were enqueued."""
+    queue = Queue(name='test-put-many-full', element_size=4, capacity=2)
+    items = [b'aaaa', b'bbbb', b'cccc']
+
+    assert queue.put_many(items, timeout=0.01) == 2
+    assert queue.stats()['put_timeouts'] == 1
+    assert queue.put_many(items[2:], timeout=0.01) == 0
+
+    assert queue.get() == b'aaaa'
+    assert queue.put_many(items[2:], timeout=0.01) == 1
+    assert [queue.get(), queue.get()] == [b'bbbb', b'cccc']
+    queue.close()
+
+
+def test_put_many_resumes_while_consumer_drains() -> None:
+    """Tests that a producer can resume a batch from the remainder."""
+    queue = Queue(name='test-put-many-resume', element_size=4, capacity=4)
+    items = [i.to_bytes(4, 'little') for i in range(64)]
+    received = []
+
+    def consume() -> None:
+        while len(received) < len(items):
+            received.append(queue.get(timeout=5.0))
+
+    consumer = threading.Thread(target=consume)
+    consumer.start()
+    sent = 0
+    while sent < len(items):
+        sent += queue.put_many(items[sent:], timeout=0.001)
+    consumer.join()
+
+    assert received == items
+    queue.close()
+
+
+def test_put_many_writes_metadata_and_encrypts() -> None:
+    """Tests that batched messages carry metadata and are sealed."""
+    queue = Queue(
+        name='test-put-many-sealed',
+        element_size=8,
+        capacity=4,
+        metadata=['sequence'],
+        encryption='chacha20-poly1305',
+        keys={1: KEY},
+    )
+
+    assert queue.put_many([b'secret-1', b'secret-2']) == 2
+    first = queue.get_with_meta()
+    second = queue.get_with_meta()
+    assert (first.payload, first.sequence) == (b'secret-1', 0)
+    assert (second.payload, second.sequence) == (b'secret-2', 1)
+    queue.close()
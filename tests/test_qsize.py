@@ -0,0 +1,45 @@
+import pytest
+
+from zeroq import Queue
+
+
+def test_qsize_tracks_puts_and_gets() -> None:
+    """Tests that qsize(), empty() and full() follow the queue contents."""
+    queue = Queue(name='test-qsize', element_size=1, capacity=2)
+    assert queue.qsize() == 0
+    assert queue.empty()
+
+    queue.put(b'a')
+    assert queue.qsize() == len(queue) == 1
+    assert not queue.empty()
+    assert not queue.full()
+
+    queue.put(b'b')
+    assert queue.qsize() == 2
+    assert queue.full()
+
+    queue.get()
+    assert queue.qsize() == 1
+    queue.close()
+
+
+def test_qsize_counts_urgent_lane() -> None:
+    """Tests that urgent messages count towards qsize() but not full()."""
+    queue = Queue(
+        name='test-qsize-urgent', element_size=1, capacity=2, urgent_lane=True
+    )
+
+    queue.put_urgent(b'u')
+
+    assert queue.qsize() == 1
+    assert not queue.full()
+    queue.close()
+
+
+def test_qsize_on_closed_queue() -> None:
+    """Tests that qsize() raises once the handle is closed."""
+    queue = Queue(name='test-qsize-closed', element_size=1, capacity=2)
+    queue.close()
+
+    with pytest.raises(OSError, match='closed'):
+        queue.qsize()
@@ -0,0 +1,153 @@
+import select
+import selectors
+import sys
+import threading
+import time
+
+import pytest
+
+import zeroq
+from zeroq import Queue
+
+pytestmark = pytest.mark.skipif(
+    sys.platform == 'win32', reason='fileno() needs Unix domain sockets'
+)
+
+
+def _readable(fd: int, timeout: float = 1.0) -> bool:
+    return bool(select.select([fd], [], [], timeout)[0])
+
+
+def _drained(fd: int, timeout: float = 1.0) -> bool:
+    deadline = time.monotonic() + timeout
+    while _readable(fd, 0):
+        if time.monotonic() > deadline:
+            return False
+        time.sleep(0.01)
+    return True
+
+
+def test_fileno_follows_queue_occupancy() -> None:
+    """Tests that the descriptor is readable exactly while messages wait."""
+    queue = Queue(name='test-fileno', element_size=1, capacity=4)
+    fd = queue.fileno()
+    assert queue.fileno() == fd
+    assert not _readable(fd, 0.05)
+
+    queue.put(b'a')
+    queue.put(b'b')
+    assert _readable(fd)
+
+    queue.get()
+    assert _readable(fd)
+    queue.get()
+    assert _drained(fd)
+    queue.close()
+
+
+def test_fileno_wakes_selector_on_put_by_another_handle() -> None:
+    """Tests that a put through another handle wakes a selector."""
+    queue = Queue(name='test-fileno-selector', element_size=1, capacity=2)
+    producer = Queue(name='test-fileno-selector', create=False)
+    selector = selectors.DefaultSelector()
+    selector.register(queue.fileno(), selectors.EVENT_READ)
+
+    timer = threading.Timer(0.05, producer.put, args=(b'x',))
+    timer.start()
+    events = selector.select(timeout=5.0)
+    timer.join()
+
+    assert events
+    assert queue.get_nowait() == b'x'
+    selector.close()
+    producer.close()
+    queue.close()
+
+
+def test_fileno_counts_urgent_lane() -> None:
+    """Tests that urgent messages make the descriptor readable."""
+    queue = Queue(
+        name='test-fileno-urgent',
+        element_size=1,
+        capacity=16,
+        urgent_lane=True,
+    )
+    fd = queue.fileno()
+
+    queue.put_urgent(b'u')
+    assert _readable(fd)
+    queue.get()
+    assert _drained(fd)
+    queue.close()
+
+
+def test_fileno_requires_open_queue() -> None:
+    """Tests that a closed handle has no descriptor."""
+    queue = Queue(name='test-fileno-closed', element_size=1, capacity=2)
+    queue.fileno()
+    queue.close()
+
+    with pytest.raises(OSError):
+        queue.fileno()
+
+
+def test_write_fileno_follows_free_slots() -> None:
+    """Tests that the writer descriptor is readable while a slot is free."""
+    queue = Queue(name='test-write-fileno', element_size=1, capacity=2)
+    fd = queue.write_fileno()
+    assert _readable(fd)
+
+    queue.put(b'a')
+    queue.put(b'b')
+    assert _drained(fd)
+
+    queue.get()
+    assert _readable(fd)
+    queue.close()
+
+
+def test_select_returns_queues_with_messages() -> None:
+    """Tests that select() reports only the queues holding messages."""
+    high = Queue(name='test-select-high', element_size=1, capacity=2)
+    low = Queue(name='test-select-low', element_size=1, capacity=2)
+
+    assert zeroq.select([high, low], timeout=0.02) == ([], [])
+    low.put(b'l')
+    assert zeroq.select([high, low], timeout=0) == ([low], [])
+    high.put(b'h')
+    assert zeroq.select([high, low, high]) == ([high, low, high], [])
+    low.close()
+    high.close()
+
+
+def test_select_wakes_on_put_from_another_handle() -> None:
+    """Tests that select() blocks until a producer makes a queue ready."""
+    first = Queue(name='test-select-first', element_size=1, capacity=2)
+    second = Queue(name='test-select-second', element_size=1, capacity=2)
+    producer = Queue(name='test-select-second', create=False)
+
+    timer = threading.Timer(0.05, producer.put, args=(b'x',))
+    timer.start()
+    started = time.monotonic()
+    readable, _ = zeroq.select([first, second], timeout=5.0)
+    timer.join()
+
+    assert readable == [second]
+    assert time.monotonic() - started < 2.0
+    producer.close()
+    second.close()
+    first.close()
+
+
+def test_select_reports_writers_with_free_slots() -> None:
+    """Tests that select() waits for space on writer queues."""
+    queue = Queue(name='test-select-writer', element_size=1, capacity=2)
+    queue.put(b'a')
+    queue.put(b'b')
+
+    assert zeroq.select([], timeout=0.02, writers=[queue]) == ([], [])
+    timer = threading.Timer(0.05, queue.get)
+    timer.start()
+    assert zeroq.select([], timeout=5.0, writers=[queue]) == ([], [queue])
+    timer.join()
+    queue.close()
@@ -0,0 +1,80 @@
+import threading
+import time
+
+import pytest
+
+from zeroq import Empty, Full, ManualClock, Queue
+
+
+def test_get_retries_after_timeout() -> None:
+    """Tests that a timed-out get waits for a fresh timeout on every retry."""
+    clock = ManualClock()
+    queue = Queue(
+        name='test-retries-get', element_size=8, capacity=2, create=True, clock=clock
+    )
+
+    with pytest.raises(Empty):
+        queue.get(timeout=1.0, retries=3, retry_backoff=0.5)
+
+    stats = queue.stats()
+    assert stats['retries'] == 3
+    assert stats['get_timeouts'] == 1
+    # Four timeout windows plus backoffs of at least 0.25, 0.5 and 1.0.
+    assert clock.time() >= 4.0 + 1.75
+    assert clock.time() <= 4.5 + 3.5 + 1.0
+    queue.close()
+
+
+def test_put_retries_after_timeout() -> None:
+    """Tests that a timed-out put is retried before raising Full."""
+    clock = ManualClock()
+    queue = Queue(
+        name='test-retries-put', element_size=8, capacity=2, create=True, clock=clock
+    )
+    queue.put(b'x' * 8)
+    queue.put(b'y' * 8)
+
+    with pytest.raises(Full):
+        queue.put(b'z' * 8, timeout=0.1, retries=2)
+
+    assert queue.stats()['retries'] == 2
+    assert queue.stats()['put_timeouts'] == 1
+    queue.close()
+
+
+def test_retry_succeeds_once_space_frees() -> None:
+    """Tests that a retried put succeeds when a consumer catches up."""
+    queue = Queue(name='test-retries-ok', element_size=8, capacity=2, create=True)
+    queue.put(b'x' * 8)
+    queue.put(b'y' * 8)
+
+    consumer = threading.Timer(0.05, queue.get_nowait)
+    consumer.start()
+    started = time.monotonic()
+    queue.put(b'z' * 8, timeout=0.01, retries=50, retry_backoff=0.002)
+    consumer.join()
+
+    assert time.monotonic() - started < 5.0
+    assert queue.stats()['retries'] >= 1
+    assert len(queue) == 2
+    queue.close()
+
+
+def test_no_retries_by_default() -> None:
+    """Tests that blocking operations do not retry unless asked to."""
+    queue = Queue(name='test-retries-default', element_size=8, capacity=2, create=True)
+
+    with pytest.raises(Empty):
+        queue.get_with_meta(timeout=0.001)
+
+    assert queue.stats()['retries'] == 0
+    queue.close()
+
+
+def test_negative_backoff_is_rejected() -> None:
+    """Tests that a negative backoff is rejected."""
+    queue = Queue(name='test-retries-invalid', element_size=8, capacity=2, create=True)
+
+    with pytest.raises(ValueError, match='retry_backoff'):
+        queue.get(timeout=0.001, retries=1, retry_backoff=-1.0)
+    queue.close()
@@ -0,0 +1,78 @@
+import pytest
+
+import zeroq
+from zeroq import Router
+
+
+def test_topics_are_independent_queues() -> None:
+    """Tests that each topic routes to its own queue in one segment."""
+    router = Router(
+        name='test-router',
+        topics={
+            'control': {'element_size': 16, 'capacity': 4},
+            'frames': {
+                'element_size': 1024,
+                'capacity': 2,
+                'urgent_lane': True,
+            },
+        },
+    )
+    remote = Router(name='test-router', create=False)
+    control = router.queue('control')
+    frames = remote.queue('frames')
+
+    control.put(b'stop'.ljust(16, b'\0'))
+    frames.put(b'x' * 1024)
+
+    assert remote.queue('control').get()[:4] == b'stop'
+    assert router.queue('frames').get() == b'x' * 1024
+    assert frames.element_size == 1024
+    assert remote.topics == ['control', 'frames']
+    assert 'frames' in remote
+    assert not zeroq.exists('control')
+    control.close()
+    frames.close()
+
+
+def test_handle_options_pass_through() -> None:
+    """Tests that topic options and handle options reach the Queue."""
+    router = Router(
+        name='test-router-options',
+        topics={
+            'jobs': {
+                'element_size': 64,
+                'capacity': 4,
+                'metadata': ['sequence'],
+            }
+        },
+    )
+    producer = router.queue('jobs', serializer='pickle')
+    consumer = Router(name='test-router-options', create=False).queue(
+        'jobs', serializer='pickle'
+    )
+
+    producer.put({'job': 1})
+
+    message = consumer.get_with_meta()
+    assert message.sequence == 0
+    producer.close()
+    consumer.close()
+
+
+def test_invalid_topics() -> None:
+    """Tests missing topics, incomplete options and unknown topic names."""
+    with pytest.raises(ValueError, match='topics required'):
+        Router(name='test-router-empty', topics={})
+    with pytest.raises(ValueError, match="Topic 'a' requires capacity"):
+        Router(name='test-router-bad', topics={'a': {'element_size': 8}})
+    with pytest.raises(ValueError, match='Topic names take 1 to 48 bytes'):
+        Router(
+            name='test-router-long',
+            topics={'t' * 49: {'element_size': 8, 'capacity': 2}},
+        )
+    router = Router(
+        name='test-router-missing',
+        topics={'a': {'element_size': 8, 'capacity': 2}},
+    )
+    with pytest.raises(KeyError, match="no topic 'b'"):
+        router.queue('b')
@@ -0,0 +1,94 @@
+import threading
+
+import pytest
+
+from zeroq import Empty, Queue
+from zeroq.rpc import Channel, RemoteError
+
+
+def test_call_and_serve() -> None:
+    """Tests that replies reach the caller that sent each request."""
+    requests = Queue(name='test-rpc', element_size=128, capacity=8)
+    replies = [
+        Queue(name=f'test-rpc-replies-{i}', element_size=128, capacity=2)
+        for i in range(2)
+    ]
+    worker = threading.Thread(
+        target=Channel(requests).serve, args=(bytes.upper, 1.0)
+    )
+    worker.start()
+    results: dict[int, list[bytes]] = {0: [], 1: []}
+
+    def client(index: int) -> None:
+        channel = Channel(Queue.open('test-rpc'), replies[index])
+        for i in range(20):
+            results[index].append(channel.call(b'c%d-%d' % (index, i), 5.0))
+
+    clients = [threading.Thread(target=client, args=(i,)) for i in range(2)]
+    for thread in clients:
+        thread.start()
+    for thread in clients:
+        thread.join()
+    worker.join()
+
+    for index in range(2):
+        assert results[index] == [b'C%d-%d' % (index, i) for i in range(20)]
+    requests.close()
+    for queue in replies:
+        queue.close()
+
+
+def test_handler_errors() -> None:
+    """Tests that handler exceptions reach the caller and serving goes on."""
+    requests = Queue(name='test-rpc-errors', element_size=64, capacity=4)
+    replies = Queue(name='test-rpc-errors-replies', element_size=64, capacity=2)
+    client = Channel(requests, replies)
+
+    def handler(payload: bytes) -> bytes:
+        if payload == b'bad':
+            raise KeyError(payload)
+        return payload[::-1]
+
+    worker = threading.Thread(
+        target=Channel(requests).serve, args=(handler, 1.0)
+    )
+    worker.start()
+
+    with pytest.raises(RemoteError, match="KeyError: b'bad'"):
+        client.call(b'bad', timeout=5.0)
+    assert client.call(b'ok', timeout=5.0) == b'ko'
+    worker.join()
+    requests.close()
+    replies.close()
+
+
+def test_call_timeout_discards_late_reply() -> None:
+    """Tests that a reply arriving after its call timed out is skipped."""
+    requests = Queue(name='test-rpc-late', element_size=64, capacity=4)
+    replies = Queue(name='test-rpc-late-replies', element_size=64, capacity=4)
+    client = Channel(requests, replies)
+    worker = Channel(requests)
+
+    with pytest.raises(Empty):
+        client.call(b'first', timeout=0.05)
+    assert worker.serve(lambda payload: payload + b'!', timeout=0) == 1
+    thread = threading.Thread(target=worker.serve, args=(bytes.upper, 1.0))
+    thread.start()
+
+    assert client.call(b'second', timeout=5.0) == b'SECOND'
+    thread.join()
+    requests.close()
+    replies.close()
+
+
+def test_request_too_large() -> None:
+    """Tests that requests must fit with their header in a slot."""
+    requests = Queue(name='test-rpc-large', element_size=32, capacity=2)
+    replies = Queue(name='test-rpc-large-replies', element_size=32, capacity=2)
+
+    with pytest.raises(ValueError, match='does not fit'):
+        Channel(requests, replies).call(bytes(32))
+    with pytest.raises(ValueError, match='reply queue'):
+        Channel(requests).call(b'')
+    requests.close()
+    replies.close()
@@ -0,0 +1,66 @@
+import os
+import subprocess
+import sys
+
+import pytest
+
+import zeroq
+from zeroq import Queue
+
+
+def _crash_creator(name: str) -> None:
+    """Creates a queue in a child process that exits without cleaning up."""
+    code = (
+        'import os\n'
+        'from zeroq import Queue\n'
+        f'queue = Queue(name={name!r}, element_size=1, capacity=2)\n'
+        'os._exit(0)\n'
+    )
+    env = {**os.environ, 'PYTHONPATH': os.pathsep.join(sys.path)}
+    subprocess.run([sys.executable, '-c', code], env=env, check=True)
+
+
+def test_exists_tracks_queue_lifetime() -> None:
+    """Tests that exists() sees a segment only while its creator lives."""
+    assert not zeroq.exists('test-exists')
+
+    queue = Queue(name='test-exists', element_size=1, capacity=2)
+    assert zeroq.exists('test-exists')
+
+    queue.close()
+    assert not zeroq.exists('test-exists')
+
+
+def test_unlink_recovers_from_crashed_creator() -> None:
+    """Tests that a stale segment can be removed and the queue recreated."""
+    _crash_creator('test-unlink-stale')
+    assert zeroq.exists('test-unlink-stale')
+    with pytest.raises(OSError):
+        Queue(name='test-unlink-stale', element_size=1, capacity=2)
+
+    zeroq.unlink('test-unlink-stale')
+
+    assert not zeroq.exists('test-unlink-stale')
+    queue = Queue(name='test-unlink-stale', element_size=1, capacity=2)
+    queue.close()
+
+
+def test_unlink_missing_segment() -> None:
+    """Tests that unlinking a missing segment fails unless allowed."""
+    with pytest.raises(FileNotFoundError):
+        zeroq.unlink('test-unlink-missing')
+
+    zeroq.unlink('test-unlink-missing', missing_ok=True)
+
+
+def test_unlink_keeps_attached_handles_working() -> None:
+    """Tests that handles attached before an unlink keep their mapping."""
+    queue = Queue(name='test-unlink-attached', element_size=1, capacity=2)
+
+    zeroq.unlink('test-unlink-attached')
+
+    queue.put(b'a')
+    assert queue.get() == b'a'
+    with pytest.raises(OSError):
+        Queue(name='test-unlink-attached', create=False)
+    queue.close()
@@ -0,0 +1,67 @@
+import json
+import mmap
+import struct
+from pathlib import Path
+
+import pytest
+
+import zeroq
+from zeroq import Queue
+
+
+def test_narrow_sequences_halve_cells() -> None:
+    """Tests that 32-bit sequences halve the cells array."""
+    wide = zeroq.plan(8, 1024)
+    narrow = zeroq.plan(8, 1024, sequence_bits=32)
+
+    assert narrow['cells_size'] * 2 == wide['cells_size']
+    assert narrow['total_size'] == zeroq.required_size(8, 1024, sequence_bits=32)
+
+
+@pytest.mark.parametrize('sequence_bits', [0, 16, 128])
+def test_unsupported_sequence_bits(sequence_bits: int) -> None:
+    """Tests that only 32 and 64 bit sequences are accepted."""
+    with pytest.raises(ValueError, match='Unsupported sequence_bits'):
+        zeroq.plan(8, 4, sequence_bits=sequence_bits)
+
+
+def test_narrow_capacity_limit() -> None:
+    """Tests that narrow sequences bound the capacity."""
+    with pytest.raises(ValueError, match='Capacity too large'):
+        zeroq.required_size(8, 1 << 31, sequence_bits=32)
+
+
+def test_narrow_queue_round_trip() -> None:
+    """Tests that a narrow queue works and attaching peers pick up the width."""
+    queue = Queue(name='test-seq-narrow', element_size=8, capacity=4, sequence_bits=32)
+    peer = Queue(name='test-seq-narrow', create=False)
+
+    for round_ in range(10):
+        items = [bytes([round_, i]) * 4 for i in range(4)]
+        for item in items:
+            queue.put_nowait(item)
+        assert [peer.get_nowait() for _ in items] == items
+
+
+def test_narrow_sequences_wrap_around() -> None:
+    """Tests that a narrow queue keeps working past 2**32 positions."""
+    name = 'test-seq-wrap'
+    queue = Queue(name=name, element_size=8, capacity=4, sequence_bits=32)
+    segment = Path(f'/dev/shm/{name}')
+    if not segment.exists():
+        pytest.skip('shared memory is not exposed under /dev/shm')
+
+    start = (1 << 32) - 3
+    with segment.open('r+b') as file:
+        view = mmap.mmap(file.fileno(), 0)
+        offset = json.loads(zeroq.layout_descriptor())['header.enqueue_pos.offset']
+        view[offset : offset + 16] = struct.pack('=QQ', start, start)
+        view.close()
+    assert queue.audit(fix=True)['fixed'] == 3
+
+    for i in range(12):
+        queue.put_nowait(bytes([i]) * 8)
+        assert queue.get_nowait() == bytes([i]) * 8
+    report = queue.audit()
+    assert report['consistent']
+    assert report['head'] == start + 12
@@ -0,0 +1,98 @@
+import threading
+
+import pytest
+
+from zeroq import Queue
+
+
+def test_objects_roundtrip() -> None:
+    """Tests that put and get exchange arbitrary picklable objects."""
+    producer = Queue(
+        name='test-serializer',
+        element_size=256,
+        capacity=8,
+        serializer='pickle',
+    )
+    consumer = Queue(name='test-serializer', create=False, serializer='pickle')
+    items = [{'id': 1, 'tags': ['a', 'b']}, (1, 2.5, None), 'text', b'raw']
+
+    for item in items:
+        producer.put(item)
+
+    assert [consumer.get() for _ in items] == items
+    consumer.close()
+    producer.close()
+
+
+def test_batches_and_nowait() -> None:
+    """Tests that the batch and non-blocking variants serialize as well."""
+    queue = Queue(
+        name='test-serializer-batch',
+        element_size=64,
+        capacity=8,
+        serializer='pickle',
+    )
+
+    assert queue.put_many([1, 'two', [3]]) == 3
+    queue.put_nowait({'four': 4})
+
+    assert queue.get_many(2) == [1, 'two']
+    assert queue.get_nowait() == [3]
+    assert queue.drain() == [{'four': 4}]
+    queue.close()
+
+
+def test_serializer_across_threads() -> None:
+    """Tests that a consumer thread receives the objects of a producer."""
+    queue = Queue(
+        name='test-serializer-threads',
+        element_size=64,
+        capacity=4,
+        serializer='pickle',
+    )
+    received = []
+    consumer = threading.Thread(
+        target=lambda: received.extend(queue.get() for _ in range(100))
+    )
+    consumer.start()
+
+    for i in range(100):
+        queue.put({'i': i})
+    consumer.join()
+
+    assert received == [{'i': i} for i in range(100)]
+    queue.close()
+
+
+def test_oversized_object_is_rejected() -> None:
+    """Tests that objects pickling to more than a slot raise ValueError."""
+    queue = Queue(
+        name='test-serializer-large',
+        element_size=32,
+        capacity=2,
+        serializer='pickle',
+    )
+
+    with pytest.raises(ValueError, match='does not fit'):
+        queue.put('x' * 100)
+    assert queue.empty()
+    queue.close()
+
+
+def test_raw_handle_and_unknown_serializer() -> None:
+    """Tests raw payloads on plain handles and unknown serializer names."""
+    queue = Queue(
+        name='test-serializer-raw',
+        element_size=8,
+        capacity=2,
+        serializer='pickle',
+    )
+    raw = Queue(name='test-serializer-raw', create=False)
+    raw.put(b'\xff' * 8)
+
+    with pytest.raises(ValueError, match='no serialized object'):
+        queue.get()
+    with pytest.raises(ValueError, match="Unknown serializer 'json'"):
+        Queue(name='test-serializer-raw', create=False, serializer='json')
+    raw.close()
+    queue.close()
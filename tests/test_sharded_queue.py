@@ -0,0 +1,88 @@
+import struct
+import threading
+
+import pytest
+
+from zeroq import Empty, Full, ShardedQueue, Stack
+
+
+def test_home_shard_and_stealing() -> None:
+    """Tests that puts go to the home shard and gets steal from the others."""
+    producer = ShardedQueue(
+        name='test-sharded', element_size=8, capacity=2, shards=3, shard=1
+    )
+    consumer = ShardedQueue(name='test-sharded', shard=2, create=False)
+
+    for item in (b'a', b'bb', b'ccc'):
+        producer.put(item)
+
+    assert producer.shard_lengths() == [0, 2, 1]
+    assert len(consumer) == 3
+    assert sorted(consumer.get() for _ in range(3)) == [b'a', b'bb', b'ccc']
+    assert consumer.shard_lengths() == [0, 0, 0]
+
+
+def test_full_and_empty() -> None:
+    """Tests the timeouts across shards and the element size limit."""
+    queue = ShardedQueue(
+        name='test-sharded-bounds', element_size=4, capacity=2, shards=2
+    )
+
+    with pytest.raises(Empty):
+        queue.get(timeout=0)
+    for i in range(4):
+        queue.put(b'%d' % i)
+    with pytest.raises(Full):
+        queue.put(b'4', timeout=0.01)
+    with pytest.raises(ValueError, match='does not fit'):
+        queue.put(b'12345')
+    assert sorted(queue.get() for _ in range(4)) == [b'0', b'1', b'2', b'3']
+
+
+def test_concurrent_producers_and_consumers() -> None:
+    """Tests that no element is lost or duplicated under contention."""
+    queue = ShardedQueue(
+        name='test-sharded-threads', element_size=8, capacity=8, shards=4
+    )
+    received = [[] for _ in range(4)]
+
+    def produce(tag: int) -> None:
+        for i in range(1000):
+            queue.put(struct.pack('<HxxI', tag, i), timeout=5)
+
+    def consume(index: int) -> None:
+        for _ in range(1000):
+            received[index].append(queue.get(timeout=5))
+
+    threads = [threading.Thread(target=produce, args=(i,)) for i in range(4)]
+    threads += [threading.Thread(target=consume, args=(i,)) for i in range(4)]
+    for thread in threads:
+        thread.start()
+    for thread in threads:
+        thread.join()
+
+    items = sorted(item for items in received for item in items)
+    expected = sorted(
+        struct.pack('<HxxI', tag, i) for tag in range(4) for i in range(1000)
+    )
+    assert items == expected
+    assert len(queue) == 0
+
+
+def test_attach_errors() -> None:
+    """Tests invalid parameters and attaching to another structure."""
+    queue = ShardedQueue(
+        name='test-sharded-attach', element_size=8, capacity=4, shards=2
+    )
+
+    with pytest.raises(ValueError, match='and 2 shards'):
+        ShardedQueue(name='test-sharded-attach', shards=4, create=False)
+    with pytest.raises(ValueError, match='shard must be below'):
+        ShardedQueue(name='test-sharded-attach', shard=2, create=False)
+    with pytest.raises(ValueError, match='power of two'):
+        ShardedQueue(
+            name='test-sharded-3', element_size=8, capacity=3, shards=2
+        )
+    with pytest.raises(OSError, match='holds no stack'):
+        Stack(name='test-sharded-attach', create=False)
+    del queue
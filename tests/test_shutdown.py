@@ -0,0 +1,131 @@
+import os
+import subprocess
+import sys
+import threading
+import time
+
+import pytest
+
+from zeroq import Queue, QueueClosed
+
+
+def test_consumer_drains_then_sees_shutdown() -> None:
+    """Tests that gets return queued items before raising QueueClosed."""
+    producer = Queue(name='test-shutdown-drain', element_size=1, capacity=4)
+    consumer = Queue(name='test-shutdown-drain', create=False)
+    producer.put(b'a')
+    producer.put(b'b')
+
+    producer.close()
+
+    assert consumer.get() == b'a'
+    assert consumer.get_nowait() == b'b'
+    with pytest.raises(QueueClosed, match='shut down'):
+        consumer.get(timeout=1.0)
+    with pytest.raises(QueueClosed, match='shut down'):
+        consumer.get_nowait()
+    consumer.close()
+
+
+def test_shutdown_wakes_blocked_consumer() -> None:
+    """Tests that a consumer blocked without timeout is released."""
+    producer = Queue(name='test-shutdown-wake', element_size=1, capacity=2)
+    consumer = Queue(name='test-shutdown-wake', create=False)
+    errors = []
+
+    def consume() -> None:
+        try:
+            consumer.get()
+        except QueueClosed as exc:
+            errors.append(exc)
+
+    thread = threading.Thread(target=consume)
+    thread.start()
+    time.sleep(0.05)
+    producer.close()
+    thread.join(timeout=2.0)
+
+    assert not thread.is_alive()
+    assert len(errors) == 1
+    consumer.close()
+
+
+def test_puts_are_refused_after_shutdown() -> None:
+    """Tests that producers cannot enqueue into a shut down queue."""
+    creator = Queue(name='test-shutdown-put', element_size=1, capacity=2)
+    producer = Queue(name='test-shutdown-put', create=False)
+
+    creator.close()
+
+    with pytest.raises(QueueClosed):
+        producer.put_nowait(b'x')
+    with pytest.raises(QueueClosed):
+        producer.put(b'x', timeout=1.0)
+    producer.close()
+
+
+def test_attached_handles_choose_whether_to_shut_down() -> None:
+    """Tests that attached handles only detach unless asked to shut down."""
+    creator = Queue(name='test-shutdown-attached', element_size=1, capacity=2)
+    first = Queue(name='test-shutdown-attached', create=False)
+    second = Queue(name='test-shutdown-attached', create=False)
+
+    first.close()
+    creator.put(b'a')
+    assert creator.get() == b'a'
+
+    second.close(shutdown=True)
+    with pytest.raises(QueueClosed):
+        creator.put_nowait(b'b')
+    creator.close()
+
+
+def test_creator_can_detach_without_shutdown() -> None:
+    """Tests that close(shutdown=False) leaves attached handles working."""
+    creator = Queue(name='test-shutdown-detach', element_size=1, capacity=2)
+    attached = Queue(name='test-shutdown-detach', create=False)
+
+    creator.close(shutdown=False)
+
+    attached.put(b'a')
+    assert attached.get() == b'a'
+    attached.close()
+
+
+def test_closed_handle_raises_queue_closed() -> None:
+    """Tests that a closed handle raises QueueClosed, an OSError."""
+    queue = Queue(name='test-shutdown-local', element_size=1, capacity=2)
+    queue.close()
+
+    with pytest.raises(QueueClosed, match='Queue is closed'):
+        queue.put(b'x')
+    assert issubclass(QueueClosed, OSError)
+
+
+def test_shutdown_reaches_other_processes() -> None:
+    """Tests that a consumer process stops once the producer closes."""
+    name = 'test-shutdown-process'
+    producer = Queue(name=name, element_size=1, capacity=4)
+    code = (
+        'from zeroq import Queue, QueueClosed\n'
+        f'queue = Queue(name={name!r}, create=False)\n'
+        "print('attached', flush=True)\n"
+        'items = []\n'
+        'try:\n'
+        '    while True:\n'
+        '        items.append(queue.get())\n'
+        'except QueueClosed:\n'
+        '    print(b"".join(items).decode())\n'
+    )
+    env = {**os.environ, 'PYTHONPATH': os.pathsep.join(sys.path)}
+    consumer = subprocess.Popen(
+        [sys.executable, '-c', code], env=env, stdout=subprocess.PIPE
+    )
+    assert consumer.stdout.readline() == b'attached\n'
+    for item in (b'a', b'b', b'c'):
+        producer.put(item)
+    producer.close()
+
+    out, _ = consumer.communicate(timeout=10)
+
+    assert out.decode().strip() == 'abc'
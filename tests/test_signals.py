@@ -0,0 +1,81 @@
+import os
+import signal
+import threading
+import time
+
+import pytest
+
+from zeroq import Queue
+
+
+def _signal_soon(signum: int) -> threading.Timer:
+    """Sends signum to this process shortly, from another thread."""
+    timer = threading.Timer(0.1, os.kill, (os.getpid(), signum))
+    timer.start()
+    return timer
+
+
+@pytest.mark.skipif(os.name != 'posix', reason='needs os.kill with SIGINT')
+def test_ctrl_c_interrupts_blocked_get() -> None:
+    """Tests that SIGINT ends a get without timeout with KeyboardInterrupt."""
+    queue = Queue(name='test-signal-get', element_size=1, capacity=2)
+    timer = _signal_soon(signal.SIGINT)
+    started = time.monotonic()
+
+    with pytest.raises(KeyboardInterrupt):
+        queue.get()
+
+    assert time.monotonic() - started < 2.0
+    timer.join()
+    queue.close()
+
+
+@pytest.mark.skipif(os.name != 'posix', reason='needs SIGUSR1')
+def test_signal_handler_errors_propagate_from_put() -> None:
+    """Tests that an exception raised by a handler ends a blocked put."""
+    queue = Queue(name='test-signal-put', element_size=1, capacity=2)
+    queue.put(b'a')
+    queue.put(b'b')
+
+    def handler(signum: int, frame: object) -> None:
+        raise TimeoutError
+
+    previous = signal.signal(signal.SIGUSR1, handler)
+    timer = _signal_soon(signal.SIGUSR1)
+    try:
+        with pytest.raises(TimeoutError):
+            queue.put(b'c')
+    finally:
+        timer.join()
+        signal.signal(signal.SIGUSR1, previous)
+    queue.close()
+
+
+@pytest.mark.skipif(os.name != 'posix', reason='needs os.kill with SIGINT')
+def test_ctrl_c_interrupts_wait_readable() -> None:
+    """Tests that SIGINT also ends a readiness wait."""
+    queue = Queue(name='test-signal-wait', element_size=1, capacity=2)
+    timer = _signal_soon(signal.SIGINT)
+
+    with pytest.raises(KeyboardInterrupt):
+        queue.wait_readable()
+
+    timer.join()
+    queue.close()
+
+
+@pytest.mark.skipif(os.name != 'posix', reason='needs os.kill with SIGINT')
+def test_ctrl_c_interrupts_busy_spinning_get() -> None:
+    """Tests that SIGINT ends a busy-spinning get without timeout."""
+    queue = Queue(
+        name='test-signal-spin', element_size=1, capacity=2, wait='spin'
+    )
+    timer = _signal_soon(signal.SIGINT)
+    started = time.monotonic()
+
+    with pytest.raises(KeyboardInterrupt):
+        queue.get()
+
+    assert time.monotonic() - started < 2.0
+    timer.join()
+    queue.close()
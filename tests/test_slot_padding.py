@@ -0,0 +1,70 @@
+import pytest
+
+import zeroq
+
+
+def test_padding_rounds_stride_to_cache_line() -> None:
+    """Tests that padded slots are spaced a whole cache line apart."""
+    plain = zeroq.plan(24, 16)
+    padded = zeroq.plan(24, 16, pad_slots=True)
+
+    assert plain['stride'] == plain['slot_size'] == 24
+    assert padded['stride'] == 64
+    assert padded['data_offset'] % 64 == 0
+    assert padded['data_size'] == 64 * 16
+    assert padded['total_size'] == zeroq.required_size(24, 16, pad_slots=True)
+
+
+def test_padding_keeps_exact_multiples() -> None:
+    """Tests that slots already a cache-line multiple are not padded further."""
+    layout = zeroq.plan(120, 8, metadata=['sequence'], pad_slots=True)
+
+    assert layout['slot_size'] == 128
+    assert layout['stride'] == 128
+
+
+def test_debug_info_reports_stride() -> None:
+    """Tests that debug_info() reports the effective stride to attachers."""
+    queue = zeroq.Queue(
+        name='test-padding-info',
+        element_size=24,
+        capacity=8,
+        create=True,
+        pad_slots=True,
+    )
+    attached = zeroq.Queue(name='test-padding-info', create=False)
+
+    info = attached.debug_info()
+    assert info['padded'] is True
+    assert info['slot_size'] == 24
+    assert info['stride'] == 64
+    assert info['data_offset'] % 64 == 0
+    assert info['capacity'] == 8
+    assert queue.debug_info() == info
+
+    attached.close()
+    queue.close()
+
+
+@pytest.mark.parametrize('pad_slots', [False, True])
+def test_padded_queue_round_trips(pad_slots: bool) -> None:
+    """Tests that messages survive padded and unpadded slots alike."""
+    queue = zeroq.Queue(
+        name='test-padding-roundtrip',
+        element_size=24,
+        capacity=4,
+        create=True,
+        pad_slots=pad_slots,
+        metadata=['sequence'],
+        urgent_lane=True,
+    )
+    items = [bytes([i]) * 24 for i in range(10)]
+
+    for item in items:
+        queue.put(item)
+        assert queue.get_with_meta().payload == item
+    queue.put_urgent(items[0])
+    assert queue.get() == items[0]
+    assert queue.debug_info()['urgent_capacity'] == 2
+
+    queue.close()
@@ -0,0 +1,94 @@
+import threading
+from pathlib import Path
+
+import pytest
+
+from zeroq import Empty, Queue
+from zeroq.spill import HEADER, SpillQueue
+
+
+def test_spill_when_full(tmp_path: Path) -> None:
+    """Tests that puts spill once the ring is full and order is kept."""
+    path = tmp_path / 'queue.spill'
+    queue = Queue(name='test-spill', element_size=4, capacity=2)
+    spill = SpillQueue(queue, path)
+
+    spilled = [spill.put(b'%04d' % i) for i in range(6)]
+
+    assert spilled == [False, False, True, True, True, True]
+    assert spill.spilled == 4
+    assert len(spill) == 6
+    assert [spill.get(timeout=0) for _ in range(6)] == [
+        b'%04d' % i for i in range(6)
+    ]
+    assert path.stat().st_size == HEADER.size
+    with pytest.raises(Empty):
+        spill.get_nowait()
+
+    assert spill.put(b'next') is False
+    spill.close()
+    queue.close()
+
+
+def test_spill_shared_between_handles(tmp_path: Path) -> None:
+    """Tests that a consumer handle drains what a producer handle spilled."""
+    path = tmp_path / 'shared.spill'
+    queue = Queue(name='test-spill-shared', element_size=8, capacity=4)
+    producer = SpillQueue(queue, path)
+    consumer = SpillQueue(Queue.open('test-spill-shared'), path)
+    received: list[bytes] = []
+
+    def consume() -> None:
+        for _ in range(200):
+            received.append(consumer.get(timeout=5.0))
+
+    thread = threading.Thread(target=consume)
+    thread.start()
+    for i in range(200):
+        producer.put(b'%08d' % i)
+    thread.join()
+
+    assert received == [b'%08d' % i for i in range(200)]
+    assert consumer.spilled == 0
+    consumer.queue.close()
+    consumer.close()
+    producer.close()
+    queue.close()
+
+
+def test_spill_torn_record(tmp_path: Path) -> None:
+    """Tests that a record cut short by a crashed producer is dropped."""
+    path = tmp_path / 'torn.spill'
+    queue = Queue(name='test-spill-torn', element_size=4, capacity=2)
+    spill = SpillQueue(queue, path)
+    for i in range(3):
+        spill.put(b'%04d' % i)
+    with open(path, 'ab') as file:
+        file.write(b'\x04\x00\x00\x00ab')
+
+    assert [spill.get(timeout=0) for _ in range(3)] == [
+        b'%04d' % i for i in range(3)
+    ]
+    with pytest.raises(Empty):
+        spill.get(timeout=0.02)
+    assert path.stat().st_size == HEADER.size
+    spill.close()
+    queue.close()
+
+
+def test_spill_errors(tmp_path: Path) -> None:
+    """Tests that foreign files and mis-sized items are refused."""
+    path = tmp_path / 'foreign'
+    path.write_bytes(b'not a spill file at all!')
+    queue = Queue(name='test-spill-errors', element_size=4, capacity=2)
+
+    with pytest.raises(ValueError, match='not a spill file'):
+        SpillQueue(queue, path)
+    spill = SpillQueue(queue, tmp_path / 'errors.spill')
+    for item in (b'1234', b'5678', b'9012'):
+        spill.put(item)
+    with pytest.raises(ValueError, match='Invalid source length'):
+        spill.put(b'12')
+    assert spill.spilled == 1
+    spill.close()
+    queue.close()
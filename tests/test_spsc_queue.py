@@ -0,0 +1,66 @@
+import struct
+import threading
+
+import pytest
+
+from zeroq import Deque, Empty, Full, SpscQueue
+
+
+def test_first_in_first_out() -> None:
+    """Tests that get() returns elements in the order of put()."""
+    queue = SpscQueue(name='test-spsc', element_size=8, capacity=4)
+    consumer = SpscQueue(name='test-spsc', create=False)
+
+    for item in (b'a', b'bb', b'ccc'):
+        queue.put(item)
+
+    assert len(consumer) == 3
+    assert [consumer.get() for _ in range(3)] == [b'a', b'bb', b'ccc']
+    assert len(queue) == 0
+
+
+def test_full_and_empty() -> None:
+    """Tests the timeouts, wrap-around and the element size limit."""
+    queue = SpscQueue(name='test-spsc-bounds', element_size=4, capacity=2)
+
+    with pytest.raises(Empty):
+        queue.get(timeout=0)
+    for i in range(5):
+        queue.put(b'%d' % i)
+        queue.put(b'x')
+        with pytest.raises(Full):
+            queue.put(b'y', timeout=0.01)
+        assert queue.get() == b'%d' % i
+        assert queue.get() == b'x'
+    with pytest.raises(ValueError, match='does not fit'):
+        queue.put(b'12345')
+
+
+def test_producer_and_consumer_threads() -> None:
+    """Tests that elements cross between two threads in order."""
+    queue = SpscQueue(name='test-spsc-threads', element_size=8, capacity=16)
+    received = []
+
+    def consume() -> None:
+        consumer = SpscQueue(name='test-spsc-threads', create=False)
+        for _ in range(10000):
+            received.append(consumer.get(timeout=5))
+
+    thread = threading.Thread(target=consume)
+    thread.start()
+    for i in range(10000):
+        queue.put(struct.pack('<Q', i), timeout=5)
+    thread.join()
+
+    assert received == [struct.pack('<Q', i) for i in range(10000)]
+
+
+def test_attach_errors() -> None:
+    """Tests attaching with mismatched parameters or to another structure."""
+    queue = SpscQueue(name='test-spsc-attach', element_size=8, capacity=2)
+
+    with pytest.raises(ValueError, match='exists with element_size 8'):
+        SpscQueue(name='test-spsc-attach', capacity=4, create=False)
+    with pytest.raises(OSError, match='holds no deque'):
+        Deque(name='test-spsc-attach', create=False)
+    del queue
@@ -0,0 +1,69 @@
+import struct
+import threading
+
+import pytest
+
+from zeroq import Deque, Empty, Full, Stack
+
+
+def test_last_in_first_out() -> None:
+    """Tests that pop() returns elements in reverse order of push()."""
+    stack = Stack(name='test-stack', element_size=8, capacity=4)
+    other = Stack(name='test-stack', create=False)
+
+    for item in (b'a', b'bb', b'ccc'):
+        stack.push(item)
+
+    assert len(other) == 3
+    assert [other.pop() for _ in range(3)] == [b'ccc', b'bb', b'a']
+    assert len(stack) == 0
+
+
+def test_full_and_empty() -> None:
+    """Tests the timeouts and the element size limit."""
+    stack = Stack(name='test-stack-bounds', element_size=4, capacity=2)
+
+    with pytest.raises(Empty):
+        stack.pop(timeout=0)
+    stack.push(b'1')
+    stack.push(b'2')
+    with pytest.raises(Full):
+        stack.push(b'3', timeout=0.01)
+    with pytest.raises(ValueError, match='does not fit'):
+        stack.push(b'12345')
+    assert stack.pop() == b'2'
+
+
+def test_concurrent_push_and_pop() -> None:
+    """Tests that no element is lost or duplicated under contention."""
+    stack = Stack(name='test-stack-threads', element_size=8, capacity=8)
+    popped = [[] for _ in range(4)]
+
+    def worker(tag: int) -> None:
+        for i in range(1000):
+            stack.push(struct.pack('<HxxI', tag, i), timeout=5)
+            popped[tag].append(stack.pop(timeout=5))
+
+    threads = [threading.Thread(target=worker, args=(i,)) for i in range(4)]
+    for thread in threads:
+        thread.start()
+    for thread in threads:
+        thread.join()
+
+    items = sorted(item for items in popped for item in items)
+    expected = sorted(
+        struct.pack('<HxxI', tag, i) for tag in range(4) for i in range(1000)
+    )
+    assert items == expected
+    assert len(stack) == 0
+
+
+def test_attach_errors() -> None:
+    """Tests attaching with mismatched parameters or to another structure."""
+    stack = Stack(name='test-stack-attach', element_size=8, capacity=2)
+
+    with pytest.raises(ValueError, match='exists with element_size 8'):
+        Stack(name='test-stack-attach', element_size=4, create=False)
+    with pytest.raises(OSError, match='holds no deque'):
+        Deque(name='test-stack-attach', create=False)
+    del stack
@@ -0,0 +1,76 @@
+import os
+import threading
+
+import pytest
+
+from zeroq import Full, Queue
+
+
+def test_payloads_span_slots() -> None:
+    """Tests that payloads of any size up to the capacity are reassembled."""
+    queue = Queue(name='test-stream', element_size=24, capacity=16)
+    payloads = [b'', b'abc', bytes(range(16)), bytes(range(100))]
+
+    for payload in payloads:
+        queue.put_stream(payload)
+
+    assert [queue.get_stream() for _ in payloads] == payloads
+    assert queue.empty()
+    queue.close()
+
+
+def test_concurrent_streams_do_not_interleave() -> None:
+    """Tests that chunks of concurrent producers reach one consumer intact."""
+    queue = Queue(name='test-stream-threads', element_size=32, capacity=16)
+    payloads = [os.urandom(i * 7 % 150) for i in range(400)]
+    received = []
+
+    def produce(part: list[bytes]) -> None:
+        for payload in part:
+            queue.put_stream(payload)
+
+    def consume(count: int) -> None:
+        received.extend(queue.get_stream() for _ in range(count))
+
+    threads = [
+        threading.Thread(target=produce, args=(payloads[i::4],))
+        for i in range(4)
+    ] + [threading.Thread(target=consume, args=(100,)) for _ in range(4)]
+    for thread in threads:
+        thread.start()
+    for thread in threads:
+        thread.join()
+
+    assert sorted(received) == sorted(payloads)
+    queue.close()
+
+
+def test_encrypted_stream() -> None:
+    """Tests that every chunk of an encrypted stream is sealed and opened."""
+    queue = Queue(
+        name='test-stream-encrypted',
+        element_size=32,
+        capacity=8,
+        encryption='chacha20-poly1305',
+        keys={1: b'k' * 32},
+    )
+
+    queue.put_stream(b'secret' * 20)
+
+    assert queue.get_stream() == b'secret' * 20
+    queue.close()
+
+
+def test_stream_errors() -> None:
+    """Tests oversized payloads, full queues and foreign messages."""
+    queue = Queue(name='test-stream-errors', element_size=16, capacity=4)
+
+    with pytest.raises(ValueError, match='more than the capacity'):
+        queue.put_stream(b'x' * 100)
+    queue.put(b'y' * 16)
+    with pytest.raises(Full):
+        queue.put_stream(b'x' * 30, timeout=0.01)
+    with pytest.raises(ValueError, match='not put by put_stream'):
+        queue.get_stream()
+    assert queue.empty()
+    queue.close()
@@ -0,0 +1,79 @@
+import struct
+
+import pytest
+
+from zeroq import Queue
+
+
+def test_records_roundtrip() -> None:
+    """Tests that tuples are packed exactly like struct.pack() and back."""
+    fmt = '<IdQ16s'
+    queue = Queue(name='test-fmt', capacity=4, fmt=fmt)
+    raw = Queue(name='test-fmt', create=False)
+    record = (7, 2.5, 2**40, b'sensor-1'.ljust(16, b'\0'))
+
+    assert queue.element_size == struct.calcsize(fmt)
+    queue.put(record)
+    payload = raw.get()
+    assert payload == struct.pack(fmt, *record)
+
+    raw.put(payload)
+    assert queue.get() == record
+    raw.close()
+    queue.close()
+
+
+@pytest.mark.parametrize(
+    ('fmt', 'record'),
+    [
+        ('@bhiqcx?', (-1, -300, 70000, -5, b'z', True)),
+        ('>3h5p', (1, 2, 3, b'abcd')),
+        ('=2If', (1, 2, 0.5)),
+    ],
+)
+def test_formats_match_struct(fmt: str, record: tuple) -> None:
+    """Tests sizes, alignment and byte orders against the struct module."""
+    queue = Queue(name='test-fmt-struct', capacity=2, fmt=fmt)
+    raw = Queue(name='test-fmt-struct', create=False)
+
+    queue.put(record)
+
+    assert raw.get() == struct.pack(fmt, *record)
+    raw.put(struct.pack(fmt, *record))
+    assert queue.get() == record
+    raw.close()
+    queue.close()
+
+
+def test_batches() -> None:
+    """Tests that the batch variants pack and unpack records as well."""
+    queue = Queue(name='test-fmt-batch', capacity=8, fmt='<Hh')
+
+    assert queue.put_many([(1, -1), (2, -2)]) == 2
+    queue.put_nowait((3, -3))
+
+    assert queue.get_many(2) == [(1, -1), (2, -2)]
+    assert queue.drain() == [(3, -3)]
+    queue.close()
+
+
+def test_invalid_records_and_formats() -> None:
+    """Tests the errors for bad records, formats and element sizes."""
+    queue = Queue(name='test-fmt-invalid', capacity=2, fmt='<Bh')
+
+    with pytest.raises(ValueError, match='packs 2 values, got 1'):
+        queue.put((1,))
+    with pytest.raises(ValueError, match='does not fit'):
+        queue.put((256, 0))
+    with pytest.raises(TypeError):
+        queue.put(('one', 0))
+    with pytest.raises(ValueError, match='records of fmt'):
+        Queue(name='test-fmt-invalid', create=False, fmt='<I')
+    with pytest.raises(ValueError, match='does not match'):
+        Queue(name='test-fmt-size', element_size=8, capacity=2, fmt='<I')
+    with pytest.raises(ValueError, match='Invalid struct format'):
+        Queue(name='test-fmt-bad', capacity=2, fmt='<Iz')
+    with pytest.raises(ValueError, match='cannot be combined'):
+        Queue(name='test-fmt-bad', capacity=2, fmt='<I', codec='json')
+    assert queue.empty()
+    queue.close()
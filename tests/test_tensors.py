@@ -0,0 +1,29 @@
+import pytest
+
+from zeroq import Queue
+
+
+def test_tensor_shares_memory() -> None:
+    """Tests that the rebuilt tensor shares the storage of the original."""
+    torch = pytest.importorskip('torch')
+    queue = Queue(name='test-tensor', element_size=1024, capacity=2)
+    tensor = torch.arange(6, dtype=torch.float32).reshape(2, 3)
+
+    queue.put_tensor(tensor)
+    received = queue.get_tensor()
+
+    assert torch.equal(received, tensor)
+    received[0, 0] = 42
+    assert tensor[0, 0] == 42
+    queue.close()
+
+
+def test_tensor_errors() -> None:
+    """Tests that get_tensor() rejects messages not put by put_tensor()."""
+    queue = Queue(name='test-tensor-errors', element_size=64, capacity=2)
+    queue.put(b'\0' * 64)
+
+    with pytest.raises(ValueError, match='not put by put_tensor'):
+        queue.get_tensor()
+    assert queue.empty()
+    queue.close()
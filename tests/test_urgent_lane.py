@@ -0,0 +1,77 @@
+import pytest
+
+import zeroq
+from zeroq import Full, Queue
+
+
+def test_urgent_messages_bypass_backlog() -> None:
+    """Tests that get() returns urgent messages before the data backlog."""
+    queue = Queue(name='test-urgent', element_size=4, capacity=32, urgent_lane=True)
+    for i in range(8):
+        queue.put(bytes([i]) * 4)
+    queue.put_urgent(b'stop')
+
+    assert len(queue) == 9
+    assert queue.get() == b'stop'
+    assert [queue.get() for _ in range(8)] == [bytes([i]) * 4 for i in range(8)]
+    assert queue.empty()
+
+
+def test_urgent_lane_is_shared_with_peers() -> None:
+    """Tests that attaching handles discover the urgent lane from the header."""
+    producer = Queue(name='test-urgent-peer', element_size=4, capacity=16, urgent_lane=True)
+    consumer = Queue(name='test-urgent-peer', create=False)
+
+    producer.put(b'data')
+    producer.put_urgent(b'ctrl')
+    assert consumer.get_nowait() == b'ctrl'
+    assert consumer.get_nowait() == b'data'
+
+
+def test_urgent_lane_capacity() -> None:
+    """Tests that the urgent lane holds 1/16 of the capacity, at least two slots."""
+    queue = Queue(name='test-urgent-full', element_size=4, capacity=4, urgent_lane=True)
+    queue.put_urgent(b'aaaa')
+    queue.put_urgent(b'bbbb')
+    with pytest.raises(Full):
+        queue.put_urgent(b'cccc', timeout=0.01)
+    assert not queue.full()
+
+    assert zeroq.plan(4, 64, urgent_lane=True)['urgent_capacity'] == 4
+    assert zeroq.plan(4, 4, urgent_lane=True)['urgent_capacity'] == 2
+
+
+def test_put_urgent_without_lane() -> None:
+    """Tests that put_urgent() requires a queue created with an urgent lane."""
+    queue = Queue(name='test-urgent-none', element_size=4, capacity=4)
+    with pytest.raises(ValueError, match='no urgent lane'):
+        queue.put_urgent(b'stop')
+
+
+def test_plan_includes_urgent_lane() -> None:
+    """Tests that the planned segment reserves room for the urgent lane."""
+    layout = zeroq.plan(8, 64, metadata=['sequence'], urgent_lane=True)
+
+    assert layout['urgent_offset'] >= layout['data_offset'] + layout['data_size']
+    assert layout['urgent_offset'] % 8 == 0
+    assert layout['total_size'] == zeroq.required_size(
+        8, 64, metadata=['sequence'], urgent_lane=True
+    )
+    assert layout['total_size'] > zeroq.required_size(8, 64, metadata=['sequence'])
+
+
+def test_urgent_lane_with_metadata() -> None:
+    """Tests that urgent messages carry the same metadata as regular ones."""
+    queue = Queue(
+        name='test-urgent-meta',
+        element_size=4,
+        capacity=16,
+        metadata=['sequence'],
+        headers_size=4,
+        urgent_lane=True,
+    )
+    queue.put(b'data')
+    queue.put_urgent(b'ctrl', headers=b'hdr')
+
+    message = queue.get_with_meta()
+    assert (message.payload, message.sequence, message.headers) == (b'ctrl', 0, b'hdr')
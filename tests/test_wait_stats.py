@@ -0,0 +1,73 @@
+import logging
+import threading
+import time
+
+import pytest
+
+from zeroq import Empty, Full, Queue
+
+
+def test_get_timeout_is_recorded() -> None:
+    """Tests that a timed-out get is counted with its wait time."""
+    queue = Queue(name='test-wait-stats-get', element_size=8, capacity=2, create=True)
+
+    with pytest.raises(Empty):
+        queue.get(timeout=0.02)
+
+    stats = queue.stats()
+    assert stats['get_waits'] == 1
+    assert stats['get_timeouts'] == 1
+    assert stats['get_wait_time'] >= 0.02
+    assert stats['get_max_wait'] == stats['get_wait_time']
+    assert stats['put_waits'] == 0
+
+
+def test_put_timeout_is_recorded() -> None:
+    """Tests that a timed-out put on a full queue is counted."""
+    queue = Queue(name='test-wait-stats-put', element_size=8, capacity=2, create=True)
+    queue.put(b'a' * 8)
+    queue.put(b'b' * 8)
+
+    with pytest.raises(Full):
+        queue.put(b'c' * 8, timeout=0.01)
+
+    stats = queue.stats()
+    assert stats['put_waits'] == 1
+    assert stats['put_timeouts'] == 1
+    assert stats['put_wait_time'] > 0
+
+
+def test_immediate_operations_are_not_recorded() -> None:
+    """Tests that blocking calls that never wait leave the counters untouched."""
+    queue = Queue(name='test-wait-stats-fast', element_size=8, capacity=2, create=True)
+    queue.put(b'a' * 8)
+    queue.get()
+
+    stats = queue.stats()
+    assert stats['put_waits'] == 0
+    assert stats['get_waits'] == 0
+    assert stats['get_wait_time'] == 0.0
+
+
+def test_slow_operation_is_logged(caplog: pytest.LogCaptureFixture) -> None:
+    """Tests that a wait exceeding slow_op_threshold is logged as a warning."""
+    queue = Queue(
+        name='test-wait-stats-slow',
+        element_size=8,
+        capacity=2,
+        create=True,
+        slow_op_threshold=0.01,
+    )
+
+    def produce() -> None:
+        time.sleep(0.05)
+        queue.put(b'x' * 8)
+
+    producer = threading.Thread(target=produce)
+    producer.start()
+    with caplog.at_level(logging.WARNING, logger='zeroq'):
+        assert queue.get(timeout=1.0) == b'x' * 8
+    producer.join()
+
+    assert queue.stats()['get_timeouts'] == 0
+    assert any('Slow get' in record.getMessage() for record in caplog.records)
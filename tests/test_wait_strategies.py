@@ -0,0 +1,335 @@
+import sys
+import threading
+import time
+
+import pytest
+
+from zeroq import Empty, Full, ManualClock, Queue
+
+wakeups_only = pytest.mark.skipif(
+    sys.platform not in {'linux', 'darwin'},
+    reason='wake-ups need futexes or ulocks',
+)
+
+
+def test_busy_spin_get_timeout_counts_spins() -> None:
+    """Tests that a busy-spinning get times out and reports spin iterations."""
+    queue = Queue(
+        name='test-busy-spin',
+        element_size=8,
+        capacity=2,
+        create=True,
+        busy_spin=True,
+    )
+
+    with pytest.raises(Empty):
+        queue.get(timeout=0.01)
+
+    assert queue.stats()['spin_count'] > 0
+
+
+def test_busy_spin_put_timeout_counts_spins() -> None:
+    """Tests that a busy-spinning put times out on a full queue."""
+    queue = Queue(
+        name='test-busy-spin',
+        element_size=1,
+        capacity=2,
+        create=True,
+        busy_spin=True,
+    )
+    queue.put(b'1')
+    queue.put(b'2')
+
+    with pytest.raises(Full):
+        queue.put(b'3', timeout=0.01)
+
+    assert queue.stats()['spin_count'] > 0
+
+
+def test_sleeping_wait_does_not_spin() -> None:
+    """Tests that the default wait strategy never busy-spins."""
+    queue = Queue(
+        name='test-sleep-wait',
+        element_size=8,
+        capacity=2,
+        create=True,
+    )
+
+    with pytest.raises(Empty):
+        queue.get(timeout=0.01)
+
+    assert queue.stats()['spin_count'] == 0
+
+
+@pytest.mark.parametrize('curve', ['linear', 'exponential'])
+def test_backpressure_throttles_blocking_put(curve: str) -> None:
+    """Tests that blocking puts on a full queue are throttled and counted."""
+    queue = Queue(
+        name='test-backpressure',
+        element_size=1,
+        capacity=2,
+        create=True,
+        backpressure=curve,
+        backpressure_base=0.001,
+        backpressure_max=0.004,
+    )
+    queue.put(b'1')
+    queue.put(b'2')
+
+    with pytest.raises(Full):
+        queue.put(b'3', timeout=0.02)
+
+    throttled = queue.stats()['throttled']
+    assert 0 < throttled < 20
+
+    queue.get()
+    queue.put(b'3')
+    assert queue.stats()['throttled'] == throttled
+
+
+@pytest.mark.parametrize(
+    ('curve', 'base', 'maximum'),
+    [('quadratic', 0.001, 0.01), ('linear', 0.0, 0.01), ('linear', 0.1, 0.01)],
+)
+def test_backpressure_invalid_parameters(
+    curve: str, base: float, maximum: float
+) -> None:
+    """Tests that invalid backpressure settings are rejected."""
+    with pytest.raises(ValueError, match='backpressure'):
+        Queue(
+            name='test-backpressure',
+            element_size=1,
+            capacity=2,
+            create=True,
+            backpressure=curve,
+            backpressure_base=base,
+            backpressure_max=maximum,
+        )
+
+
+def test_adaptive_wait_spins_yields_then_parks() -> None:
+    """Tests that the adaptive strategy escalates from spinning to parking."""
+    queue = Queue(
+        name='test-adaptive-wait',
+        element_size=8,
+        capacity=2,
+        create=True,
+        adaptive=True,
+        spin_limit=50,
+        yield_limit=20,
+        park_interval=0.001,
+    )
+
+    with pytest.raises(Empty):
+        queue.get(timeout=0.05)
+
+    stats = queue.stats()
+    assert stats['spin_count'] == 50
+    assert stats['yield_count'] == 20
+    assert 0 < stats['park_count'] <= 60
+
+
+def test_adaptive_wait_restarts_per_operation() -> None:
+    """Tests that every blocking call starts spinning again."""
+    queue = Queue(
+        name='test-adaptive-restart',
+        element_size=1,
+        capacity=2,
+        create=True,
+        adaptive=True,
+        spin_limit=10,
+        yield_limit=0,
+    )
+
+    for _ in range(2):
+        with pytest.raises(Empty):
+            queue.get(timeout=0.01)
+
+    assert queue.stats()['spin_count'] == 20
+
+
+@pytest.mark.parametrize(
+    'options',
+    [{'busy_spin': True, 'adaptive': True}, {'adaptive': True, 'park_interval': 0.0}],
+)
+def test_adaptive_wait_invalid_options(options: dict) -> None:
+    """Tests that conflicting or invalid adaptive options are rejected."""
+    with pytest.raises(ValueError):
+        Queue(name='test-adaptive-invalid', element_size=1, capacity=2, **options)
+
+
+def test_handle_stats_splits_busy_and_parked_time() -> None:
+    """Tests that wait time is attributed to the phase that spent it."""
+    queue = Queue(
+        name='test-handle-stats',
+        element_size=8,
+        capacity=2,
+        create=True,
+        adaptive=True,
+        spin_limit=1000,
+        yield_limit=10,
+        park_interval=0.002,
+    )
+    assert queue.handle_stats()['busy_fraction'] == 0.0
+
+    with pytest.raises(Empty):
+        queue.get(timeout=0.05)
+
+    stats = queue.handle_stats()
+    assert stats['spin_time'] > 0
+    assert stats['yield_time'] > 0
+    assert stats['park_time'] >= 0.002
+    total = stats['spin_time'] + stats['yield_time'] + stats['park_time']
+    assert total <= 0.1
+    assert 0 < stats['busy_fraction'] < 1
+
+
+def test_handle_stats_counts_sleeps_as_parked() -> None:
+    """Tests that the default sleeping strategy never reports busy time."""
+    clock = ManualClock()
+    queue = Queue(
+        name='test-handle-stats-sleep',
+        element_size=8,
+        capacity=2,
+        create=True,
+        clock=clock,
+    )
+
+    with pytest.raises(Empty):
+        queue.get(timeout=0.01)
+
+    stats = queue.handle_stats()
+    assert stats['park_time'] == pytest.approx(clock.time())
+    assert stats['spin_time'] == stats['yield_time'] == 0
+    assert stats['busy_fraction'] == 0.0
+
+
+@wakeups_only
+def test_parked_get_wakes_on_put() -> None:
+    """Tests that a parked consumer is woken by a put, not its interval."""
+    queue = Queue(
+        name='test-futex-wake',
+        element_size=4,
+        capacity=2,
+        create=True,
+        adaptive=True,
+        spin_limit=0,
+        yield_limit=0,
+        park_interval=2.0,
+    )
+    producer = Queue(name='test-futex-wake', create=False)
+    received = []
+
+    consumer = threading.Thread(
+        target=lambda: received.append(queue.get(timeout=5.0))
+    )
+    consumer.start()
+    time.sleep(0.05)
+    started = time.monotonic()
+    producer.put(b'wake')
+    consumer.join()
+
+    assert received == [b'wake']
+    assert time.monotonic() - started < 0.5
+    producer.close()
+    queue.close()
+
+
+@wakeups_only
+def test_parked_put_wakes_on_get() -> None:
+    """Tests that a parked producer is woken when a slot is released."""
+    queue = Queue(
+        name='test-futex-wake-put', element_size=4, capacity=2, create=True
+    )
+    queue.put(b'aaaa')
+    queue.put(b'bbbb')
+
+    producer = threading.Thread(
+        target=lambda: queue.put(b'cccc', timeout=5.0)
+    )
+    producer.start()
+    time.sleep(0.05)
+    assert queue.get() == b'aaaa'
+    producer.join()
+
+    assert [queue.get(), queue.get()] == [b'bbbb', b'cccc']
+    queue.close()
+
+
+@pytest.mark.parametrize(
+    ('wait', 'busy_spin', 'adaptive'),
+    [('spin', True, False), ('hybrid', False, True), ('sleep', False, False)],
+)
+def test_wait_policy_selects_strategy(
+    wait: str, busy_spin: bool, adaptive: bool
+) -> None:
+    """Tests that each wait policy maps onto the matching strategy flags."""
+    queue = Queue(
+        name='test-wait-policy', element_size=1, capacity=2, wait=wait
+    )
+
+    options = queue.configure()
+    assert options['wait'] == wait
+    assert options['busy_spin'] is busy_spin
+    assert options['adaptive'] is adaptive
+    queue.close()
+
+
+def test_wait_policy_defaults_to_sleep() -> None:
+    """Tests that handles without a wait policy report sleeping."""
+    queue = Queue(name='test-wait-default', element_size=1, capacity=2)
+
+    assert queue.configure()['wait'] == 'sleep'
+    queue.close()
+
+
+@pytest.mark.parametrize(
+    'options',
+    [
+        {'wait': 'sleep', 'busy_spin': True},
+        {'wait': 'spin', 'adaptive': True},
+        {'wait': 'poll'},
+        {'wait': 'sleep', 'park_interval': 0.0},
+    ],
+)
+def test_wait_policy_invalid_options(options: dict) -> None:
+    """Tests that unknown or conflicting wait policies are rejected."""
+    with pytest.raises(ValueError):
+        Queue(name='test-wait-invalid', element_size=1, capacity=2, **options)
+
+
+def test_sleep_policy_honors_park_interval() -> None:
+    """Tests that the sleeping policy sleeps park_interval between attempts."""
+    clock = ManualClock()
+    queue = Queue(
+        name='test-wait-sleep-interval',
+        element_size=1,
+        capacity=2,
+        wait='sleep',
+        park_interval=0.004,
+        clock=clock,
+    )
+
+    with pytest.raises(Empty):
+        queue.get(timeout=0.01)
+
+    assert clock.time() == pytest.approx(0.012)
+    queue.close()
+
+
+def test_configure_switches_wait_policy() -> None:
+    """Tests that configure() accepts a wait policy for every handle."""
+    queue = Queue(name='test-wait-configure', element_size=1, capacity=2)
+    attached = Queue(name='test-wait-configure', create=False)
+
+    updated = queue.configure(wait='spin')
+    assert updated['busy_spin'] is True
+    with pytest.raises(Empty):
+        attached.get(timeout=0.01)
+    assert attached.stats()['spin_count'] > 0
+
+    with pytest.raises(ValueError):
+        queue.configure(wait='hybrid', busy_spin=False)
+    assert queue.configure()['wait'] == 'spin'
+    attached.close()
+    queue.close()
@@ -0,0 +1,192 @@
+import pytest
+
+from zeroq import Empty, Full, Queue
+
+
+def test_get_buffer_reads_payload_in_place() -> None:
+    """Tests that the context manager yields the payload as a memoryview."""
+    queue = Queue(name='test-zero-copy', element_size=8, capacity=4)
+    queue.put(b'payload!')
+
+    with queue.get_buffer() as payload:
+        assert isinstance(payload, memoryview)
+        assert payload.readonly
+        assert payload.tobytes() == b'payload!'
+
+    assert queue.empty()
+    queue.close()
+
+
+def test_slot_stays_reserved_until_release() -> None:
+    """Tests that producers cannot reuse a slot before the view is released."""
+    queue = Queue(name='test-zero-copy-reserve', element_size=4, capacity=2)
+    queue.put(b'aaaa')
+    queue.put(b'bbbb')
+
+    view = queue.get_buffer()
+    assert len(view) == 4
+    with pytest.raises(Full):
+        queue.put_nowait(b'cccc')
+
+    view.release()
+    assert view.released
+    queue.put_nowait(b'cccc')
+    assert queue.get() == b'bbbb'
+    assert queue.get() == b'cccc'
+    queue.close()
+
+
+def test_release_with_live_memoryview_fails() -> None:
+    """Tests that a slot cannot be released while its memory is exported."""
+    queue = Queue(name='test-zero-copy-export', element_size=4, capacity=2)
+    queue.put(b'data')
+
+    view = queue.get_buffer()
+    payload = memoryview(view)
+    with pytest.raises(BufferError):
+        view.release()
+
+    assert bytes(payload) == b'data'
+    payload.release()
+    view.release()
+    view.release()
+    with pytest.raises(BufferError):
+        memoryview(view)
+    queue.close()
+
+
+def test_close_with_open_view_fails() -> None:
+    """Tests that the segment is not unmapped under an open view."""
+    queue = Queue(name='test-zero-copy-close', element_size=4, capacity=2)
+    queue.put(b'data')
+
+    view = queue.get_buffer()
+    with pytest.raises(BufferError):
+        queue.close()
+
+    view.release()
+    queue.close()
+
+
+def test_get_buffer_prefers_urgent_lane() -> None:
+    """Tests that zero-copy gets drain the urgent lane first."""
+    queue = Queue(
+        name='test-zero-copy-urgent', element_size=4, capacity=4, urgent_lane=True
+    )
+    queue.put(b'data')
+    queue.put_urgent(b'ctrl')
+
+    with queue.get_buffer() as payload:
+        assert payload.tobytes() == b'ctrl'
+    with queue.get_buffer() as payload:
+        assert payload.tobytes() == b'data'
+    with pytest.raises(Empty):
+        queue.get_buffer(timeout=0.01)
+    queue.close()
+
+
+def test_get_buffer_rejects_encrypted_queues() -> None:
+    """Tests that encrypted payloads are never exposed in place."""
+    queue = Queue(
+        name='test-zero-copy-encrypted',
+        element_size=8,
+        capacity=2,
+        encryption='chacha20-poly1305',
+        keys={1: bytes(32)},
+    )
+    queue.put(b'secret!!')
+
+    with pytest.raises(ValueError, match='encrypted'):
+        queue.get_buffer()
+    queue.close()
+
+
+def test_reserve_commit_roundtrip() -> None:
+    """Tests that a payload written into a reserved slot is delivered."""
+    queue = Queue(name='test-zero-copy-reserve-put', element_size=8, capacity=4)
+
+    buf = queue.reserve()
+    assert len(buf) == 8
+    buf[:] = b'inplace!'
+    with pytest.raises(Empty):
+        queue.get_nowait()
+
+    queue.commit(buf)
+    assert buf.released
+    assert queue.get() == b'inplace!'
+    queue.close()
+
+
+def test_uncommitted_reservation_blocks_consumers() -> None:
+    """Tests that consumers cannot get past a slot before it is committed."""
+    queue = Queue(name='test-zero-copy-uncommitted', element_size=4, capacity=4)
+
+    buf = queue.reserve()
+    queue.put(b'next')
+    with pytest.raises(Empty):
+        queue.get_nowait()
+
+    with memoryview(buf) as payload:
+        payload[:] = b'head'
+    queue.commit(buf)
+    assert queue.get() == b'head'
+    assert queue.get() == b'next'
+    queue.close()
+
+
+def test_reserve_as_context_manager_commits_on_exit() -> None:
+    """Tests that leaving the with block publishes the reservation."""
+    queue = Queue(name='test-zero-copy-reserve-with', element_size=4, capacity=4)
+
+    with queue.reserve() as payload:
+        assert not payload.readonly
+        payload[:] = b'with'
+
+    assert queue.get() == b'with'
+    queue.close()
+
+
+def test_commit_rejects_foreign_views() -> None:
+    """Tests that only reservations made by the handle can be committed."""
+    queue = Queue(name='test-zero-copy-foreign', element_size=4, capacity=4)
+    queue.put(b'data')
+
+    view = queue.get_buffer()
+    with pytest.raises(ValueError, match='reserved'):
+        queue.commit(view)
+    with pytest.raises(TypeError):
+        view[:] = b'nope'
+
+    view.release()
+    queue.close()
+
+
+def test_reserve_full_queue_times_out() -> None:
+    """Tests that reserving on a full queue raises Full after the timeout."""
+    queue = Queue(name='test-zero-copy-reserve-full', element_size=4, capacity=2)
+    queue.put(b'aaaa')
+    queue.put(b'bbbb')
+
+    with pytest.raises(Full):
+        queue.reserve(timeout=0.01)
+    queue.close()
+
+
+def test_reserve_writes_metadata() -> None:
+    """Tests that reserved messages carry the enabled metadata fields."""
+    queue = Queue(
+        name='test-zero-copy-meta',
+        element_size=4,
+        capacity=4,
+        metadata=['sequence'],
+        headers_size=4,
+    )
+
+    with queue.reserve(headers=b'hdr!') as payload:
+        payload[:] = b'body'
+
+    message = queue.get_with_meta()
+    assert message.payload == b'body'
+    assert message.sequence == 0
+    assert message.headers == b'hdr!'
+    queue.close()
@@ -1,7 +1,71 @@
-from .zeroq import Empty, Full, Queue
+from .advisor import Advice, Observation, advise, observe
+from .doctor import Finding, diagnose, find_abandoned, prune
+from .multiplex import select
+from .zeroq import (
+    AlreadyExists,
+    Bus,
+    Cancelled,
+    ConflatingQueue,
+    CorruptMessage,
+    DecryptionError,
+    Deque,
+    Empty,
+    Full,
+    InvalidParameters,
+    LvcSlot,
+    ManualClock,
+    Message,
+    Pool,
+    Queue,
+    QueueClosed,
+    Router,
+    ShardedQueue,
+    SlotView,
+    SpscQueue,
+    Stack,
+    exists,
+    layout_descriptor,
+    plan,
+    required_size,
+    unlink,
+    verify_layout,
+)
 
 __all__ = [
+    'Advice',
+    'AlreadyExists',
+    'Bus',
+    'Cancelled',
+    'ConflatingQueue',
+    'CorruptMessage',
+    'DecryptionError',
+    'Deque',
     'Empty',
+    'Finding',
     'Full',
+    'InvalidParameters',
+    'LvcSlot',
+    'ManualClock',
+    'Message',
+    'Observation',
+    'Pool',
     'Queue',
+    'QueueClosed',
+    'Router',
+    'ShardedQueue',
+    'SlotView',
+    'SpscQueue',
+    'Stack',
+    'advise',
+    'diagnose',
+    'exists',
+    'find_abandoned',
+    'layout_descriptor',
+    'observe',
+    'plan',
+    'prune',
+    'required_size',
+    'select',
+    'unlink',
+    'verify_layout',
 ]
@@ -0,0 +1,100 @@
+"""Command-line entry point: ``python -m zeroq <command>``."""
+
+from __future__ import annotations
+
+import argparse
+import sys
+
+from .advisor import advise, observe
+from .doctor import diagnose, find_abandoned, prune
+from .zeroq import Queue
+
+
+def main(argv: list[str] | None = None) -> int:
+    """Runs the zeroq command line.
+
+    Args:
+        argv: Arguments without the program name, defaults to sys.argv.
+
+    Returns:
+        The process exit code.
+    """
+    parser = argparse.ArgumentParser(prog='zeroq')
+    commands = parser.add_subparsers(dest='command', required=True)
+    doctor = commands.add_parser(
+        'doctor', help='diagnose the shared-memory environment'
+    )
+    doctor.add_argument(
+        '--shm-dir',
+        default='/dev/shm',
+        help='directory backing POSIX shared memory',
+    )
+    pruner = commands.add_parser(
+        'prune', help='list or remove abandoned queue segments'
+    )
+    pruner.add_argument(
+        '--shm-dir',
+        default='/dev/shm',
+        help='directory backing POSIX shared memory',
+    )
+    pruner.add_argument(
+        '--idle',
+        type=float,
+        default=3600.0,
+        help='seconds without attach or detach before an unmapped segment '
+        'is abandoned (default: %(default)s)',
+    )
+    pruner.add_argument(
+        '--unlink',
+        action='store_true',
+        help='remove the abandoned segments instead of listing them',
+    )
+    advisor = commands.add_parser(
+        'advise', help='recommend settings for the traffic of a queue'
+    )
+    advisor.add_argument('name', help='name of the queue to observe')
+    advisor.add_argument(
+        '--duration',
+        type=float,
+        default=10.0,
+        help='seconds to observe the queue (default: %(default)s)',
+    )
+    advisor.add_argument(
+        '--interval',
+        type=float,
+        default=0.001,
+        help='seconds between occupancy samples (default: %(default)s)',
+    )
+    advisor.add_argument(
+        '--max-payload',
+        type=int,
+        help='largest payload the producers send, in bytes',
+    )
+    args = parser.parse_args(argv)
+
+    if args.command == 'advise':
+        queue = Queue(args.name, create=False)
+        try:
+            observation = observe(queue, args.duration, args.interval)
+        finally:
+            queue.close()
+        print(advise(observation, args.max_payload))  # noqa: T201
+        return 0
+
+    if args.command == 'prune':
+        if args.unlink:
+            for path in prune(args.shm_dir, args.idle):
+                print(f'removed {path}')  # noqa: T201
+        else:
+            for path in find_abandoned(args.shm_dir, args.idle):
+                print(path)  # noqa: T201
+        return 0
+
+    findings = diagnose(args.shm_dir)
+    for finding in findings:
+        print(finding)  # noqa: T201
+    return 1 if any(f.severity == 'error' for f in findings) else 0
+
+
+if __name__ == '__main__':
+    sys.exit(main())
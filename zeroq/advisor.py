@@ -0,0 +1,237 @@
+"""Sizing and wait-policy recommendations from observed traffic.
+
+Sample a live queue for a while, then turn what was seen into settings::
+
+    from zeroq.advisor import advise, observe
+
+    print(advise(observe(queue, duration=30.0)))
+"""
+
+from __future__ import annotations
+
+import math
+import time
+from dataclasses import dataclass, field
+from typing import Literal
+
+from .zeroq import Queue
+
+#: Arrival rate in messages per second from which waits are short enough
+#: that spinning before parking pays off.
+HYBRID_RATE = 1_000.0
+
+#: Arrival rate in messages per second from which dedicating a core to
+#: spinning is worth the lowest latency.
+SPIN_RATE = 1_000_000.0
+
+#: Alignment of recommended element sizes, in bytes.
+ELEMENT_ALIGN = 8
+
+Wait = Literal['spin', 'hybrid', 'sleep']
+
+
+@dataclass(frozen=True)
+class Observation:
+    """Traffic seen on a queue over a sampling window.
+
+    Attributes:
+        element_size: Element size of the queue in bytes.
+        capacity: Capacity of the main lane.
+        duration: Length of the window in seconds.
+        occupancy: Number of queued messages at each sample.
+        enqueued: Messages enqueued during the window.
+        dequeued: Messages dequeued during the window.
+    """
+
+    element_size: int
+    capacity: int
+    duration: float
+    occupancy: list[int]
+    enqueued: int
+    dequeued: int
+
+    @property
+    def arrival_rate(self) -> float:
+        """Messages enqueued per second."""
+        return self.enqueued / self.duration if self.duration > 0 else 0.0
+
+    @property
+    def high_water(self) -> int:
+        """Highest occupancy sampled."""
+        return max(self.occupancy, default=0)
+
+    @property
+    def full_fraction(self) -> float:
+        """Share of samples that found the queue full."""
+        if not self.occupancy:
+            return 0.0
+        full = sum(1 for n in self.occupancy if n >= self.capacity)
+        return full / len(self.occupancy)
+
+    def percentile(self, q: float) -> int:
+        """Returns the occupancy not exceeded by a share q of the samples.
+
+        Args:
+            q: Share of samples between 0 and 1.
+        """
+        if not self.occupancy:
+            return 0
+        ordered = sorted(self.occupancy)
+        index = min(len(ordered) - 1, math.ceil(q * len(ordered)) - 1)
+        return ordered[max(index, 0)]
+
+
+@dataclass(frozen=True)
+class Advice:
+    """Settings recommended for observed traffic.
+
+    Attributes:
+        element_size: Recommended element size in bytes.
+        capacity: Recommended capacity, a power of two.
+        wait: Recommended wait policy.
+        slot_bytes: Payload memory of the current settings.
+        recommended_slot_bytes: Payload memory of the recommended settings.
+        reasons: Why each setting was chosen, one line each.
+    """
+
+    element_size: int
+    capacity: int
+    wait: Wait
+    slot_bytes: int
+    recommended_slot_bytes: int
+    reasons: list[str] = field(default_factory=list)
+
+    def __str__(self) -> str:
+        """Formats the advice as a short report."""
+        lines = [
+            f'element_size={self.element_size} capacity={self.capacity} '
+            f'wait={self.wait!r}',
+            f'payload memory: {_mib(self.slot_bytes)} -> '
+            f'{_mib(self.recommended_slot_bytes)}',
+        ]
+        lines.extend(f'  - {reason}' for reason in self.reasons)
+        return '\n'.join(lines)
+
+
+def observe(
+    queue: Queue, duration: float = 10.0, interval: float = 0.001
+) -> Observation:
+    """Samples the occupancy and throughput of a queue.
+
+    The queue is only read; producers and consumers in other processes keep
+    running undisturbed.
+
+    Args:
+        queue: Handle of the queue to observe.
+        duration: Length of the sampling window in seconds.
+        interval: Seconds between occupancy samples.
+
+    Returns:
+        What was seen during the window.
+    """
+    before = queue.totals()
+    started = time.monotonic()
+    occupancy = [len(queue)]
+    while time.monotonic() - started < duration:
+        time.sleep(interval)
+        occupancy.append(len(queue))
+    elapsed = time.monotonic() - started
+    after = queue.totals()
+    return Observation(
+        element_size=queue.element_size,
+        capacity=queue.maxsize,
+        duration=elapsed,
+        occupancy=occupancy,
+        enqueued=after['enqueued'] - before['enqueued'],
+        dequeued=after['dequeued'] - before['dequeued'],
+    )
+
+
+def advise(
+    observation: Observation,
+    max_payload: int | None = None,
+    headroom: float = 2.0,
+) -> Advice:
+    """Recommends settings for the traffic of an observation.
+
+    Args:
+        observation: Traffic returned by observe().
+        max_payload: Largest payload the producers send, in bytes; the
+            element size is kept when unknown.
+        headroom: Factor applied to the high-water mark to absorb bursts
+            larger than the ones observed.
+
+    Returns:
+        The recommended settings and the reasons for them.
+
+    Raises:
+        ValueError: If headroom is below 1.
+    """
+    if headroom < 1:
+        msg = 'headroom must be at least 1'
+        raise ValueError(msg)
+    reasons = []
+
+    element_size = observation.element_size
+    if max_payload is not None:
+        element_size = max(
+            ELEMENT_ALIGN, -(-max_payload // ELEMENT_ALIGN) * ELEMENT_ALIGN
+        )
+        reasons.append(
+            f'payloads of up to {max_payload} bytes fit in '
+            f'{element_size}-byte elements'
+        )
+
+    high_water = observation.high_water
+    if observation.full_fraction > 0:
+        capacity = observation.capacity * 2
+        reasons.append(
+            f'the queue was full in {observation.full_fraction:.0%} of '
+            'samples, so producers blocked; the real peak is unknown, '
+            'observe again after growing it'
+        )
+    else:
+        capacity = _next_power_of_two(math.ceil(max(high_water, 1) * headroom))
+        reasons.append(
+            f'occupancy peaked at {high_water} of {observation.capacity} '
+            f'(p99 {observation.percentile(0.99)}); '
+            f'{headroom:g}x headroom needs {capacity} slots'
+        )
+
+    rate = observation.arrival_rate
+    wait: Wait
+    if rate >= SPIN_RATE:
+        wait = 'spin'
+        reasons.append(
+            f'{rate:,.0f} msg/s keeps waits shorter than a park; '
+            'spinning trades a core for the lowest latency'
+        )
+    elif rate >= HYBRID_RATE:
+        wait = 'hybrid'
+        reasons.append(
+            f'{rate:,.0f} msg/s makes most waits short; spin briefly, '
+            'then park'
+        )
+    else:
+        wait = 'sleep'
+        reasons.append(
+            f'{rate:,.0f} msg/s leaves the queue idle most of the time; '
+            'sleeping gives the CPU back'
+        )
+
+    return Advice(
+        element_size=element_size,
+        capacity=capacity,
+        wait=wait,
+        slot_bytes=observation.element_size * observation.capacity,
+        recommended_slot_bytes=element_size * capacity,
+        reasons=reasons,
+    )
+
+
+def _next_power_of_two(n: int) -> int:
+    return max(2, 1 << (n - 1).bit_length())
+
+
+def _mib(size: int) -> str:
+    return f'{size / (1024 * 1024):.1f} MiB'
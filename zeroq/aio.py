@@ -0,0 +1,124 @@
+"""Awaitable puts and gets for asyncio applications.
+
+Wrap a queue handle to use it from coroutines without blocking the event
+loop::
+
+    from zeroq.aio import AsyncQueue
+
+    async def consume(queue: Queue) -> None:
+        async_queue = AsyncQueue(queue)
+        while True:
+            handle(await async_queue.get())
+
+Operations first try the non-blocking variant on the event loop thread, so
+a queue with work pending costs no thread hop. Only when they have to wait
+do they hand a readiness wait to an executor, which never dequeues or
+enqueues anything itself; cancelling an awaiting operation therefore cannot
+lose or duplicate a message.
+"""
+
+from __future__ import annotations
+
+import asyncio
+from collections.abc import Callable
+from concurrent.futures import Executor
+from typing import TypeVar
+
+from .zeroq import Empty, Full, Queue
+
+T = TypeVar('T')
+
+#: Longest readiness wait handed to the executor at once, in seconds, which
+#: bounds how long a cancelled operation keeps an executor thread busy.
+WAIT_SLICE = 0.1
+
+
+class AsyncQueue:
+    """Awaitable view of a queue handle.
+
+    Attributes:
+        queue: The wrapped handle, still usable for synchronous calls.
+    """
+
+    def __init__(self, queue: Queue, executor: Executor | None = None) -> None:
+        """Wraps a queue handle.
+
+        Args:
+            queue: Handle to wrap; closing it stays up to the caller.
+            executor: Executor running readiness waits, defaults to the
+                event loop's default executor.
+        """
+        self.queue = queue
+        self._executor = executor
+
+    async def put(
+        self,
+        item: bytes,
+        timeout: float | None = None,
+        headers: bytes | None = None,
+        deadline: float | None = None,
+    ) -> None:
+        """Enqueues an item, waiting without blocking the loop while full.
+
+        Args:
+            item: The item to enqueue.
+            timeout: Maximum time to wait in seconds; waits indefinitely if
+                omitted.
+            headers: Headers stored with the message.
+            deadline: Unix timestamp after which the message is stale.
+
+        Raises:
+            Full: If the queue stays full beyond the timeout.
+        """
+        await self._retry(
+            lambda: self.queue.put_nowait(item, headers, deadline),
+            Full,
+            'Queue is full',
+            self.queue.wait_writable,
+            timeout,
+        )
+
+    async def get(self, timeout: float | None = None) -> bytes:
+        """Dequeues an item, waiting without blocking the loop while empty.
+
+        Args:
+            timeout: Maximum time to wait in seconds; waits indefinitely if
+                omitted.
+
+        Returns:
+            The dequeued item.
+
+        Raises:
+            Empty: If no item arrives before the timeout.
+        """
+        return await self._retry(
+            self.queue.get_nowait,
+            Empty,
+            'Queue is empty',
+            self.queue.wait_readable,
+            timeout,
+        )
+
+    async def _retry(  # noqa: PLR0913
+        self,
+        attempt: Callable[[], T],
+        unavailable: type[Exception],
+        message: str,
+        wait: Callable[[float], bool],
+        timeout: float | None,
+    ) -> T:
+        """Calls attempt until it stops raising unavailable, waiting for
+        readiness in the executor in between."""
+        loop = asyncio.get_running_loop()
+        deadline = None if timeout is None else loop.time() + timeout
+        while True:
+            try:
+                return attempt()
+            except unavailable:
+                pass
+            remaining = WAIT_SLICE
+            if deadline is not None:
+                remaining = min(remaining, deadline - loop.time())
+                if remaining <= 0:
+                    raise unavailable(message)
+            await loop.run_in_executor(self._executor, wait, remaining)
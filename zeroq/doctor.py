@@ -0,0 +1,360 @@
+"""Environment diagnostics for shared-memory queues."""
+
+from __future__ import annotations
+
+import os
+import stat
+import struct
+import sys
+import time
+import zlib
+from dataclasses import dataclass
+from pathlib import Path
+from typing import Literal
+
+Severity = Literal['ok', 'info', 'warning', 'error']
+
+#: Directory backing POSIX shared memory on Linux.
+SHM_DIR = Path('/dev/shm')
+
+#: Free space below which /dev/shm is reported as nearly full.
+LOW_SPACE_BYTES = 64 * 1024 * 1024
+
+#: Maximum shared memory name length on macOS (PSHMNAMLEN).
+MACOS_NAME_MAX = 31
+
+# Queue header fields: magic, layout_version, element_size, buffer_mask,
+# meta_size, meta_flags, cell_size, checksum, enqueue_pos, dequeue_pos,
+# lane_offset, the shared config, the not_full and not_empty wake-up signals,
+# the ready and closed flags, the unfinished task count, its all_done
+# wake-up signal and the registry of attached processes.
+_HEADER = struct.Struct('=IIQQQIII4xQQQ8Q4IIIQ2I16Q')
+
+#: Number of header bytes covered by the checksum.
+_CHECKSUM_COVERS = 40
+
+#: Magic number at the start of every queue header.
+_MAGIC = int.from_bytes(b'ZQMQ', 'little')
+
+
+@dataclass(frozen=True)
+class Finding:
+    """A single diagnostic result.
+
+    Attributes:
+        check: Name of the check that produced the finding.
+        severity: 'ok', 'info', 'warning' or 'error'.
+        message: What was observed.
+        hint: Suggested action, empty when none is needed.
+    """
+
+    check: str
+    severity: Severity
+    message: str
+    hint: str = ''
+
+    def __str__(self) -> str:
+        """Formats the finding as a single report line."""
+        line = f'[{self.severity}] {self.check}: {self.message}'
+        return f'{line}\n    -> {self.hint}' if self.hint else line
+
+
+def diagnose(shm_dir: str | os.PathLike[str] = SHM_DIR) -> list[Finding]:
+    """Checks the environment for conditions that break shared-memory queues.
+
+    Covers /dev/shm capacity and mount options, hugepage availability,
+    shared memory name limits, permissions and orphaned queue segments.
+
+    Args:
+        shm_dir: Directory backing POSIX shared memory.
+
+    Returns:
+        The findings, one or more per check.
+    """
+    shm_dir = Path(shm_dir)
+    if not shm_dir.is_dir():
+        return [
+            Finding(
+                'shm',
+                'info' if sys.platform == 'darwin' else 'error',
+                f'{shm_dir} does not exist',
+                _missing_shm_hint(),
+            ),
+            _check_name_limit(shm_dir),
+        ]
+    return [
+        _check_mount(shm_dir),
+        _check_capacity(shm_dir),
+        _check_hugepages(),
+        _check_name_limit(shm_dir),
+        _check_permissions(shm_dir),
+        *_check_orphans(shm_dir),
+    ]
+
+
+def find_abandoned(
+    shm_dir: str | os.PathLike[str] = SHM_DIR, idle: float = 3600.0
+) -> list[Path]:
+    """Finds queue segments that look abandoned.
+
+    A segment is abandoned when no process visible to this user maps it and
+    no queue handle attached to or detached from it for ``idle`` seconds.
+    Handles record both in the segment's modification time, so a queue that
+    was merely restarted is not considered idle.
+
+    Args:
+        shm_dir: Directory backing POSIX shared memory.
+        idle: Seconds without attach or detach after which an unmapped
+            segment is abandoned.
+
+    Returns:
+        The abandoned segments, oldest first.
+    """
+    shm_dir = Path(shm_dir)
+    cutoff = time.time() - idle
+    mapped = _mapped_paths()
+    abandoned = []
+    for path in _entries(shm_dir):
+        if not _is_queue_segment(path) or str(path.resolve()) in mapped:
+            continue
+        try:
+            modified = path.stat().st_mtime
+        except OSError:
+            continue
+        if modified <= cutoff:
+            abandoned.append((modified, path))
+    return [path for _, path in sorted(abandoned)]
+
+
+def prune(
+    shm_dir: str | os.PathLike[str] = SHM_DIR, idle: float = 3600.0
+) -> list[Path]:
+    """Unlinks the segments returned by find_abandoned().
+
+    Args:
+        shm_dir: Directory backing POSIX shared memory.
+        idle: Seconds without attach or detach after which an unmapped
+            segment is abandoned.
+
+    Returns:
+        The segments that were removed; ones that vanished or could not be
+        removed are left out.
+    """
+    removed = []
+    for path in find_abandoned(shm_dir, idle):
+        try:
+            path.unlink()
+        except OSError:
+            continue
+        removed.append(path)
+    return removed
+
+
+def _missing_shm_hint() -> str:
+    if sys.platform == 'darwin':
+        return (
+            'macOS keeps shared memory outside the filesystem; '
+            'segment checks are skipped'
+        )
+    return (
+        'mount a tmpfs on /dev/shm, e.g. '
+        '`mount -t tmpfs -o size=1g tmpfs /dev/shm`'
+    )
+
+
+def _mount_options(path: Path) -> list[str] | None:
+    """Returns the options of the mount holding path, if /proc/mounts exists."""
+    try:
+        mounts = Path('/proc/mounts').read_text().splitlines()
+    except OSError:
+        return None
+    best: tuple[int, list[str]] | None = None
+    resolved = str(path.resolve())
+    for line in mounts:
+        fields = line.split()
+        if len(fields) < 4:
+            continue
+        point = fields[1]
+        if resolved == point or resolved.startswith(point.rstrip('/') + '/'):
+            if best is None or len(point) > best[0]:
+                best = (len(point), [fields[2], *fields[3].split(',')])
+    return best[1] if best else None
+
+
+def _check_mount(shm_dir: Path) -> Finding:
+    options = _mount_options(shm_dir)
+    if options is None:
+        return Finding('mount', 'info', 'mount table is not available')
+    fstype, *flags = options
+    if 'ro' in flags:
+        return Finding(
+            'mount',
+            'error',
+            f'{shm_dir} is mounted read-only',
+            f'remount it read-write: `mount -o remount,rw {shm_dir}`',
+        )
+    if fstype != 'tmpfs':
+        return Finding(
+            'mount',
+            'warning',
+            f'{shm_dir} is a {fstype} filesystem, not tmpfs',
+            'queues will be backed by disk; mount a tmpfs instead',
+        )
+    return Finding('mount', 'ok', f'{shm_dir} is tmpfs ({",".join(flags)})')
+
+
+def _check_capacity(shm_dir: Path) -> Finding:
+    usage = os.statvfs(shm_dir)
+    total = usage.f_blocks * usage.f_frsize
+    free = usage.f_bavail * usage.f_frsize
+    message = f'{_mib(free)} free of {_mib(total)}'
+    if free < LOW_SPACE_BYTES:
+        return Finding(
+            'capacity',
+            'warning',
+            message,
+            'creating large queues will fail with SIGBUS or OSError; '
+            'remove stale segments or remount with a larger size=',
+        )
+    return Finding('capacity', 'ok', message)
+
+
+def _check_hugepages() -> Finding:
+    try:
+        lines = Path('/proc/meminfo').read_text().splitlines()
+    except OSError:
+        return Finding('hugepages', 'info', 'hugepage information unavailable')
+    info = {}
+    for line in lines:
+        key, _, value = line.partition(':')
+        info[key] = value.split()[0] if value.split() else ''
+    total = int(info.get('HugePages_Total', 0))
+    if total == 0:
+        return Finding(
+            'hugepages',
+            'info',
+            'no hugepages are reserved',
+            'reserve some with `sysctl vm.nr_hugepages=N` to back very '
+            'large segments with hugepages',
+        )
+    free = int(info.get('HugePages_Free', 0))
+    size = info.get('Hugepagesize', '?')
+    return Finding(
+        'hugepages', 'ok', f'{free} of {total} hugepages free ({size} kB each)'
+    )
+
+
+def _check_name_limit(shm_dir: Path) -> Finding:
+    if sys.platform == 'darwin':
+        return Finding(
+            'name_limit',
+            'info',
+            f'shared memory names are limited to {MACOS_NAME_MAX} characters',
+            'keep queue names short when targeting macOS',
+        )
+    try:
+        limit = os.pathconf(shm_dir, 'PC_NAME_MAX')
+    except (OSError, ValueError):
+        return Finding('name_limit', 'info', 'name limit unavailable')
+    return Finding(
+        'name_limit',
+        'ok',
+        f'shared memory names are limited to {limit} characters',
+    )
+
+
+def _check_permissions(shm_dir: Path) -> Finding:
+    if not os.access(shm_dir, os.W_OK | os.X_OK):
+        return Finding(
+            'permissions',
+            'error',
+            f'{shm_dir} is not writable by this user',
+            f'fix the mode with `chmod 1777 {shm_dir}`',
+        )
+    mode = shm_dir.stat().st_mode
+    if mode & stat.S_IWOTH and not mode & stat.S_ISVTX:
+        return Finding(
+            'permissions',
+            'warning',
+            f'{shm_dir} is world-writable without the sticky bit',
+            'other users can remove queue segments; '
+            f'run `chmod +t {shm_dir}`',
+        )
+    return Finding('permissions', 'ok', f'{shm_dir} is writable')
+
+
+def _check_orphans(shm_dir: Path) -> list[Finding]:
+    segments = [path for path in _entries(shm_dir) if _is_queue_segment(path)]
+    mapped = _mapped_paths()
+    orphans = [p for p in segments if str(p.resolve()) not in mapped]
+    if not orphans:
+        return [
+            Finding(
+                'orphans',
+                'ok',
+                f'{len(segments)} queue segment(s), none orphaned',
+            )
+        ]
+    return [
+        Finding(
+            'orphans',
+            'warning',
+            f'{path.name} ({_mib(path.stat().st_size)}) is not mapped by '
+            'any process visible to this user',
+            f'remove it with `rm {path}` once no process uses it',
+        )
+        for path in orphans
+    ]
+
+
+def _entries(shm_dir: Path) -> list[Path]:
+    try:
+        return [path for path in shm_dir.iterdir() if path.is_file()]
+    except OSError:
+        return []
+
+
+def _is_queue_segment(path: Path) -> bool:
+    """Returns whether path holds a queue header at offset 0."""
+    try:
+        with path.open('rb') as segment:
+            header = segment.read(_HEADER.size)
+        size = path.stat().st_size
+    except OSError:
+        return False
+    if len(header) < _HEADER.size:
+        return False
+    magic, _, element_size, mask, meta_size, _, cell_size, checksum, *_ = (
+        _HEADER.unpack(header)
+    )
+    if magic != _MAGIC:
+        return False
+    if checksum != zlib.crc32(header[:_CHECKSUM_COVERS]):
+        return False
+    capacity = mask + 1
+    cell_size = cell_size or 8
+    if element_size == 0 or capacity < 2 or capacity & mask:
+        return False
+    if cell_size not in {4, 8}:
+        return False
+    slots = capacity * (cell_size + element_size + meta_size)
+    return size >= _HEADER.size + slots
+
+
+def _mapped_paths() -> set[str]:
+    """Returns the files mapped by processes whose maps are readable."""
+    mapped: set[str] = set()
+    for maps in Path('/proc').glob('[0-9]*/maps'):
+        try:
+            lines = maps.read_text().splitlines()
+        except OSError:
+            continue
+        for line in lines:
+            fields = line.split(maxsplit=5)
+            if len(fields) == 6:
+                mapped.add(fields[5].removesuffix(' (deleted)'))
+    return mapped
+
+
+def _mib(size: int) -> str:
+    return f'{size / (1024 * 1024):.1f} MiB'
@@ -0,0 +1,256 @@
+"""Video frames with their geometry and pixel format.
+
+Wrap a queue handle so that capture and inference processes agree on the
+layout of every frame without inventing a header of their own::
+
+    from zeroq.frames import FrameQueue, frame_size
+
+    size = frame_size(1920, 1080, 'BGR24')
+    frames = FrameQueue(Queue('camera', element_size=size, capacity=8))
+    frames.put(pixels, width=1920, height=1080, pixel_format='BGR24')
+
+    frame = frames.get()
+    image = np.frombuffer(frame.data, np.uint8).reshape(
+        frame.height, frame.width, 3
+    )
+
+Each payload starts with a 32-byte little-endian header: the magic ``ZF``,
+a version byte, a reserved byte, the pixel format as 8 ASCII bytes padded
+with zeros, then width, height and stride as u32 and the capture timestamp
+in nanoseconds since the Unix epoch as u64. The pixels follow, ``height``
+rows of ``stride`` bytes, or more for planar formats, and zeros fill the
+rest of the slot.
+"""
+
+from __future__ import annotations
+
+import struct
+import time
+from dataclasses import dataclass
+
+from .zeroq import Queue
+
+#: Layout of the frame header.
+HEADER = struct.Struct('<2sBx8sIIIQ')
+
+#: Marks payloads written by FrameQueue.put().
+MAGIC = b'ZF'
+
+#: Version of the frame header.
+VERSION = 1
+
+#: Bytes per pixel of the packed pixel formats.
+PACKED_FORMATS = {
+    'GRAY8': 1,
+    'GRAY16': 2,
+    'YUYV': 2,
+    'UYVY': 2,
+    'RGB24': 3,
+    'BGR24': 3,
+    'RGBA32': 4,
+    'BGRA32': 4,
+}
+
+#: Size of the chroma planes of the planar pixel formats, relative to the
+#: luma plane.
+PLANAR_FORMATS = {'NV12': 0.5, 'NV21': 0.5, 'I420': 0.5}
+
+
+@dataclass(frozen=True)
+class Frame:
+    """A frame received from a FrameQueue.
+
+    Attributes:
+        data: The pixels, a view into the dequeued payload.
+        width: Width in pixels.
+        height: Height in pixels.
+        stride: Bytes per row of the first plane.
+        pixel_format: Name of the pixel format, e.g. 'BGR24'.
+        timestamp: Capture time in nanoseconds since the Unix epoch.
+    """
+
+    data: memoryview
+    width: int
+    height: int
+    stride: int
+    pixel_format: str
+    timestamp: int
+
+
+def default_stride(width: int, pixel_format: str) -> int:
+    """Returns the stride of rows without padding.
+
+    Args:
+        width: Width in pixels.
+        pixel_format: Name of the pixel format.
+
+    Returns:
+        The stride in bytes.
+
+    Raises:
+        ValueError: If the pixel format is unknown, so the stride must be
+            given.
+    """
+    if pixel_format in PACKED_FORMATS:
+        return width * PACKED_FORMATS[pixel_format]
+    if pixel_format in PLANAR_FORMATS:
+        return width
+    raise ValueError(
+        f"Unknown pixel format '{pixel_format}': pass the stride explicitly"
+    )
+
+
+def pixels_size(height: int, stride: int, pixel_format: str) -> int:
+    """Returns the size of the pixels of a frame.
+
+    Args:
+        height: Height in pixels.
+        stride: Bytes per row of the first plane.
+        pixel_format: Name of the pixel format.
+
+    Returns:
+        The size in bytes, with the chroma planes of planar formats.
+    """
+    size = height * stride
+    return size + int(size * PLANAR_FORMATS.get(pixel_format, 0))
+
+
+def frame_size(
+    width: int, height: int, pixel_format: str, stride: int | None = None
+) -> int:
+    """Returns the element size holding frames of the given geometry.
+
+    Args:
+        width: Width in pixels.
+        height: Height in pixels.
+        pixel_format: Name of the pixel format.
+        stride: Bytes per row, defaults to rows without padding.
+
+    Returns:
+        The header size plus the size of the pixels.
+    """
+    if stride is None:
+        stride = default_stride(width, pixel_format)
+    return HEADER.size + pixels_size(height, stride, pixel_format)
+
+
+class FrameQueue:
+    """Frame view of a queue handle.
+
+    Attributes:
+        queue: The wrapped handle, which must have no serializer, codec or
+            fmt.
+    """
+
+    def __init__(self, queue: Queue) -> None:
+        """Wraps a queue handle.
+
+        Args:
+            queue: Handle to wrap; closing it stays up to the caller.
+        """
+        self.queue = queue
+
+    def put(
+        self,
+        data: bytes | bytearray | memoryview,
+        width: int,
+        height: int,
+        pixel_format: str,
+        stride: int | None = None,
+        timestamp: int | None = None,
+        timeout: float | None = None,
+    ) -> None:
+        """Enqueues a frame.
+
+        Args:
+            data: The pixels, any C-contiguous buffer.
+            width: Width in pixels.
+            height: Height in pixels.
+            pixel_format: Name of the pixel format, at most 8 ASCII
+                characters.
+            stride: Bytes per row, defaults to rows without padding; required
+                for pixel formats not listed in PACKED_FORMATS or
+                PLANAR_FORMATS.
+            timestamp: Capture time in nanoseconds since the Unix epoch,
+                defaults to now.
+            timeout: Maximum time to wait in seconds; waits indefinitely if
+                omitted.
+
+        Raises:
+            ValueError: If the pixel format is invalid, data does not match
+                the geometry, or the frame does not fit in a slot.
+            Full: If the queue stays full beyond the timeout.
+        """
+        encoded = pixel_format.encode('ascii')
+        if len(encoded) > 8:
+            raise ValueError(
+                f"Pixel format '{pixel_format}' is longer than 8 characters"
+            )
+        if stride is None:
+            stride = default_stride(width, pixel_format)
+        pixels = memoryview(data).cast('B')
+        expected = pixels_size(height, stride, pixel_format)
+        if len(pixels) != expected:
+            raise ValueError(
+                f'Frame of {len(pixels)} bytes does not match {width}x{height}'
+                f' {pixel_format} with stride {stride}, which takes'
+                f' {expected} bytes'
+            )
+        element_size = self.queue.element_size
+        if HEADER.size + len(pixels) > element_size:
+            raise ValueError(
+                f'Frame of {len(pixels)} bytes with a {HEADER.size}-byte header'
+                f' does not fit in elements of {element_size} bytes'
+            )
+        if timestamp is None:
+            timestamp = time.time_ns()
+        payload = bytearray(element_size)
+        HEADER.pack_into(
+            payload,
+            0,
+            MAGIC,
+            VERSION,
+            encoded,
+            width,
+            height,
+            stride,
+            timestamp,
+        )
+        payload[HEADER.size : HEADER.size + len(pixels)] = pixels
+        self.queue.put(payload, timeout)
+
+    def get(self, timeout: float | None = None) -> Frame:
+        """Dequeues a frame.
+
+        Args:
+            timeout: Maximum time to wait in seconds; waits indefinitely if
+                omitted.
+
+        Returns:
+            The frame.
+
+        Raises:
+            ValueError: If the message was not put by FrameQueue.put(); it is
+                consumed regardless.
+            Empty: If the queue stays empty beyond the timeout.
+        """
+        payload = self.queue.get(timeout)
+        if len(payload) < HEADER.size:
+            raise ValueError('The message was not put by FrameQueue.put()')
+        magic, version, encoded, width, height, stride, timestamp = (
+            HEADER.unpack_from(payload)
+        )
+        if magic != MAGIC or version != VERSION:
+            raise ValueError('The message was not put by FrameQueue.put()')
+        pixel_format = encoded.rstrip(b'\0').decode('ascii')
+        size = pixels_size(height, stride, pixel_format)
+        if HEADER.size + size > len(payload):
+            raise ValueError('The message was not put by FrameQueue.put()')
+        return Frame(
+            data=memoryview(payload)[HEADER.size : HEADER.size + size],
+            width=width,
+            height=height,
+            stride=stride,
+            pixel_format=pixel_format,
+            timestamp=timestamp,
+        )
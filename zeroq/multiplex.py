@@ -0,0 +1,67 @@
+"""Waiting on several queues at once."""
+
+from __future__ import annotations
+
+import selectors
+import time
+from collections.abc import Iterable
+
+from .zeroq import Queue
+
+#: Pause in seconds after a descriptor woke the selector for a queue that is
+#: no longer ready.
+STALE_BACKOFF = 0.001
+
+
+def select(
+    queues: Iterable[Queue],
+    timeout: float | None = None,
+    writers: Iterable[Queue] = (),
+) -> tuple[list[Queue], list[Queue]]:
+    """Waits until one of several queues has a message or a free slot.
+
+    Blocks on the readiness descriptors of the queues (see Queue.fileno()
+    and Queue.write_fileno()), so it wakes as soon as a producer or consumer
+    in any process makes one of them ready, without polling.
+
+    Readiness is only a snapshot: another consumer or producer may still win
+    the message or slot, so follow up with get_nowait() or put_nowait() and
+    select again on Empty or Full.
+
+    Args:
+        queues: Queues to wait on for a message.
+        timeout: Maximum time to wait in seconds; waits indefinitely if
+            omitted, and only checks once if zero.
+        writers: Queues to wait on for a free slot in the main lane.
+
+    Returns:
+        The queues with a message and the writers with a free slot, each in
+        the order given; both are empty if the timeout elapsed first.
+
+    Raises:
+        NotImplementedError: On platforms without Unix sockets.
+    """
+    queues = list(queues)
+    writers = list(writers)
+    fds = {queue.fileno() for queue in queues}
+    fds.update(queue.write_fileno() for queue in writers)
+    deadline = None if timeout is None else time.monotonic() + timeout
+    with selectors.DefaultSelector() as selector:
+        for fd in fds:
+            selector.register(fd, selectors.EVENT_READ)
+        while True:
+            # The descriptors trail the queues by a wake-up, so the queues
+            # themselves decide what is ready.
+            readable = [queue for queue in queues if not queue.empty()]
+            writable = [queue for queue in writers if not queue.full()]
+            if readable or writable:
+                return readable, writable
+            remaining = None
+            if deadline is not None:
+                remaining = deadline - time.monotonic()
+                if remaining <= 0:
+                    return [], []
+            if selector.select(remaining):
+                # A descriptor still reports a state its queue already left;
+                # give its watcher a moment instead of spinning.
+                time.sleep(STALE_BACKOFF)
@@ -0,0 +1,261 @@
+"""Request-reply calls over a pair of queues.
+
+A client sends each request on a shared request queue together with the
+name of its own reply queue and a correlation ID; a worker answers on that
+reply queue, and the client matches the reply by its ID::
+
+    from zeroq.rpc import Channel
+
+    requests = Queue('resize', element_size=4096, capacity=64)
+
+    # Worker process.
+    Channel(requests).serve(lambda payload: payload.upper())
+
+    # Client process, one reply queue per caller.
+    replies = Queue(
+        f'resize-replies-{os.getpid()}', element_size=4096, capacity=4
+    )
+    client = Channel(Queue.open('resize'), replies)
+    reply = client.call(b'image', timeout=1.0)
+
+Each request starts with a 16-byte little-endian header: the magic ``ZR``,
+a version byte, the length of the reply queue name as a byte, the length of
+the payload as u32 and the correlation ID as u64. The reply queue name and
+the payload follow, and zeros fill the rest of the slot. A reply starts with
+the same header, where a status byte replaces the name length, followed by
+the payload or, if the handler raised, the error message.
+"""
+
+from __future__ import annotations
+
+import itertools
+import struct
+import time
+from collections.abc import Callable
+
+from .zeroq import Empty, Full, Queue, QueueClosed
+
+#: Layout of the request and reply headers.
+HEADER = struct.Struct('<2sBBIQ')
+
+#: Marks payloads written by Channel.
+MAGIC = b'ZR'
+
+#: Version of the headers.
+VERSION = 1
+
+#: Status of a reply carrying the handler's result.
+OK = 0
+
+#: Status of a reply carrying the error the handler raised.
+ERROR = 1
+
+#: Type of the handlers that serve requests.
+Handler = Callable[[bytes], 'bytes | bytearray | memoryview | None']
+
+
+class RemoteError(Exception):
+    """Raised by Channel.call() when the worker's handler raised.
+
+    The message names the exception type and repeats its message.
+    """
+
+
+class Channel:
+    """Request-reply view of a request queue and, for clients, a reply queue.
+
+    The queues must have no serializer, codec or fmt. Calls through one
+    channel must not overlap, so give each thread that calls its own reply
+    queue.
+
+    Attributes:
+        requests: The shared request queue.
+        replies: The reply queue of this client, None for workers.
+    """
+
+    def __init__(self, requests: Queue, replies: Queue | None = None) -> None:
+        """Wraps queue handles.
+
+        Args:
+            requests: Handle to the request queue.
+            replies: Handle to the reply queue, required to call; closing
+                both stays up to the caller.
+
+        Raises:
+            ValueError: If the name of the reply queue is longer than 255
+                bytes.
+        """
+        self.requests = requests
+        self.replies = replies
+        self._reply_name = b''
+        if replies is not None:
+            self._reply_name = replies.name.encode()
+            if len(self._reply_name) > 255:
+                raise ValueError(
+                    f"Reply queue name '{replies.name}' is longer than 255"
+                    ' bytes'
+                )
+        self._ids = itertools.count(1)
+        # Handles to the reply queues of the clients a worker answered.
+        self._clients: dict[str, Queue] = {}
+
+    def call(
+        self,
+        payload: bytes | bytearray | memoryview,
+        timeout: float | None = None,
+    ) -> bytes:
+        """Sends a request and waits for its reply.
+
+        Replies to earlier calls that timed out are discarded.
+
+        Args:
+            payload: The request, any C-contiguous buffer.
+            timeout: Maximum time to wait for both room in the request queue
+                and the reply, in seconds; waits indefinitely if omitted.
+
+        Returns:
+            The reply returned by the handler.
+
+        Raises:
+            ValueError: If the channel has no reply queue or the request does
+                not fit in a slot.
+            Full: If the request queue stays full beyond the timeout.
+            Empty: If no reply arrives before the timeout.
+            RemoteError: If the handler raised.
+        """
+        if self.replies is None:
+            raise ValueError('Calls need a channel with a reply queue')
+        data = memoryview(payload).cast('B')
+        size = HEADER.size + len(self._reply_name) + len(data)
+        element_size = self.requests.element_size
+        if size > element_size:
+            raise ValueError(
+                f'Request of {len(data)} bytes with its header and reply'
+                f' queue name does not fit in elements of {element_size}'
+                ' bytes'
+            )
+        deadline = None if timeout is None else time.monotonic() + timeout
+        request_id = next(self._ids)
+        request = bytearray(element_size)
+        HEADER.pack_into(
+            request,
+            0,
+            MAGIC,
+            VERSION,
+            len(self._reply_name),
+            len(data),
+            request_id,
+        )
+        offset = HEADER.size + len(self._reply_name)
+        request[HEADER.size : offset] = self._reply_name
+        request[offset:size] = data
+        self.requests.put(request, timeout)
+        while True:
+            remaining = None
+            if deadline is not None:
+                remaining = max(deadline - time.monotonic(), 0)
+            try:
+                reply = self.replies.get(remaining)
+            except Empty:
+                raise Empty(
+                    f'No reply to request {request_id} on'
+                    f" '{self.replies.name}'"
+                ) from None
+            if len(reply) < HEADER.size:
+                continue
+            magic, version, status, body_len, reply_id = HEADER.unpack_from(
+                reply
+            )
+            if (
+                magic != MAGIC
+                or version != VERSION
+                or reply_id != request_id
+                or HEADER.size + body_len > len(reply)
+            ):
+                continue
+            body = bytes(reply[HEADER.size : HEADER.size + body_len])
+            if status == ERROR:
+                raise RemoteError(body.decode(errors='replace'))
+            return body
+
+    def serve(self, handler: Handler, timeout: float | None = None) -> int:
+        """Answers requests with a handler until none arrive.
+
+        Exceptions raised by the handler are sent back to the caller as
+        RemoteError, and serving continues. Replies to clients whose reply
+        queue is gone or full are dropped.
+
+        Args:
+            handler: Returns the reply to a request payload, None for an
+                empty reply.
+            timeout: Maximum time to wait for each request in seconds;
+                serves until the request queue is shut down if omitted.
+
+        Returns:
+            The number of requests answered.
+        """
+        served = 0
+        while True:
+            try:
+                request = self.requests.get(timeout)
+            except (Empty, QueueClosed):
+                return served
+            if len(request) < HEADER.size:
+                continue
+            magic, version, name_len, body_len, request_id = (
+                HEADER.unpack_from(request)
+            )
+            offset = HEADER.size + name_len
+            if (
+                magic != MAGIC
+                or version != VERSION
+                or offset + body_len > len(request)
+            ):
+                continue
+            client = bytes(request[HEADER.size : offset]).decode(
+                errors='replace'
+            )
+            status = OK
+            try:
+                body = handler(bytes(request[offset : offset + body_len]))
+                body = b'' if body is None else memoryview(body).cast('B')
+            except Exception as exc:
+                status = ERROR
+                body = f'{type(exc).__name__}: {exc}'.encode()
+            self._reply(client, request_id, status, body)
+            served += 1
+
+    def _reply(
+        self,
+        client: str,
+        request_id: int,
+        status: int,
+        body: bytes | memoryview,
+    ) -> None:
+        """Puts a reply on a client's reply queue, dropping it on failure."""
+        replies = self._clients.get(client)
+        if replies is None:
+            try:
+                replies = Queue.open(client)
+            except OSError:
+                return
+            self._clients[client] = replies
+        if HEADER.size + len(body) > replies.element_size:
+            status = ERROR
+            body = (
+                f'Reply of {len(body)} bytes does not fit in elements of'
+                f' {replies.element_size} bytes'
+            ).encode()
+        # Error messages are cut to fit, so the caller still learns of them.
+        body = body[: replies.element_size - HEADER.size]
+        reply = bytearray(replies.element_size)
+        HEADER.pack_into(
+            reply, 0, MAGIC, VERSION, status, len(body), request_id
+        )
+        reply[HEADER.size : HEADER.size + len(body)] = body
+        try:
+            replies.put_nowait(reply)
+        except Full:
+            pass
+        except QueueClosed:
+            del self._clients[client]
@@ -0,0 +1,241 @@
+"""Queues that spill to disk instead of blocking or dropping when full.
+
+Wrap a queue handle so that puts never wait: once the ring is full,
+messages are appended to a spill file, and consumers drain the file once
+the ring is exhausted::
+
+    from zeroq.spill import SpillQueue
+
+    jobs = SpillQueue(
+        Queue('jobs', element_size=4096, capacity=1024), '/var/tmp/jobs.spill'
+    )
+    jobs.put(record)
+
+    # Consumer process, on the same spill file.
+    jobs = SpillQueue(Queue.open('jobs'), '/var/tmp/jobs.spill')
+    record = jobs.get(timeout=1.0)
+
+While the file holds messages, producers append to it rather than to the
+ring, so every producer's messages arrive in the order it put them. The
+file starts with a 24-byte little-endian header: the magic ``ZQSPILL1``,
+the offset of the next record to read as u64 and the number of records
+left as u64. Records follow, each a u32 length and the payload. Every
+handle locks the file with ``flock()`` around each access, and consumers
+truncate it back to its header once drained. Spilled messages survive
+crashed processes, but are not synced to disk.
+"""
+
+from __future__ import annotations
+
+import fcntl
+import os
+import struct
+import threading
+import time
+from collections.abc import Iterator
+from contextlib import contextmanager
+
+from .zeroq import Empty, Full, Queue
+
+#: Layout of the spill file header.
+HEADER = struct.Struct('<8sQQ')
+
+#: Layout of the length in front of each record.
+LENGTH = struct.Struct('<I')
+
+#: Marks spill files written by SpillQueue.
+MAGIC = b'ZQSPILL1'
+
+
+class SpillQueue:
+    """Queue handle that spills to an append-only file when the ring is full.
+
+    The queue must have no serializer, codec or fmt. Every handle of the
+    queue, in any process, must go through a SpillQueue on the same file,
+    or its consumers miss spilled messages.
+
+    Attributes:
+        queue: The wrapped queue handle.
+        path: The spill file.
+    """
+
+    def __init__(
+        self,
+        queue: Queue,
+        path: str | os.PathLike[str],
+        poll_interval: float = 0.01,
+    ) -> None:
+        """Wraps a queue handle, creating the spill file if needed.
+
+        Args:
+            queue: Handle to the queue; closing it stays up to the caller.
+            path: Spill file shared by every handle of the queue.
+            poll_interval: Maximum time in seconds that a blocked get() waits
+                on the ring before checking the spill file again.
+
+        Raises:
+            ValueError: If the file exists but is not a spill file.
+            OSError: If the file cannot be opened or created.
+        """
+        self.queue = queue
+        self.path = os.fspath(path)
+        self._poll_interval = poll_interval
+        # flock() excludes other open files only, so threads sharing this
+        # handle take a lock of their own as well.
+        self._thread_lock = threading.Lock()
+        # Set first, so that __del__ finds no descriptor if opening fails.
+        self._fd = -1
+        self._fd = os.open(
+            self.path, os.O_RDWR | os.O_CREAT | os.O_CLOEXEC, 0o600
+        )
+        try:
+            with self._locked():
+                size = os.fstat(self._fd).st_size
+                if size == 0:
+                    self._write_header(HEADER.size, 0)
+                elif size < HEADER.size or self._read_header()[0] != MAGIC:
+                    raise ValueError(f"'{self.path}' is not a spill file")
+        except BaseException:
+            self.close()
+            raise
+
+    @property
+    def spilled(self) -> int:
+        """Number of messages waiting in the spill file."""
+        with self._locked():
+            return self._read_header()[2]
+
+    def __len__(self) -> int:
+        """Returns the number of messages in the ring and the spill file."""
+        return len(self.queue) + self.spilled
+
+    def put(self, item: bytes | bytearray | memoryview) -> bool:
+        """Adds a message, spilling it to disk if the ring is full.
+
+        Args:
+            item: The message, exactly element_size bytes.
+
+        Returns:
+            Whether the message was spilled.
+
+        Raises:
+            ValueError: If the item does not fill an element.
+            QueueClosed: If the queue was shut down.
+        """
+        data = memoryview(item).cast('B')
+        if not self._pending():
+            try:
+                self.queue.put_nowait(data)
+                return False
+            except Full:
+                pass
+        if len(data) != self.queue.element_size:
+            raise ValueError(
+                'Invalid source length: expected'
+                f' {self.queue.element_size}, got {len(data)}'
+            )
+        with self._locked():
+            _, offset, count = self._read_header()
+            end = os.fstat(self._fd).st_size
+            os.pwrite(self._fd, LENGTH.pack(len(data)) + data, end)
+            self._write_header(offset, count + 1)
+        return True
+
+    def get(self, timeout: float | None = None) -> bytes:
+        """Removes and returns the oldest message, from the ring first.
+
+        Args:
+            timeout: Maximum time to wait in seconds; waits indefinitely if
+                omitted, and does not wait if 0.
+
+        Returns:
+            The message.
+
+        Raises:
+            Empty: If neither the ring nor the spill file holds a message
+                before the timeout.
+        """
+        deadline = None if timeout is None else time.monotonic() + timeout
+        while True:
+            try:
+                return self.queue.get_nowait()
+            except Empty:
+                pass
+            item = self._unspill()
+            if item is not None:
+                return item
+            wait = self._poll_interval
+            if deadline is not None:
+                wait = min(wait, deadline - time.monotonic())
+                if wait <= 0:
+                    raise Empty(
+                        f"Queue '{self.queue.name}' and its spill file are"
+                        ' empty'
+                    )
+            try:
+                return self.queue.get(wait)
+            except Empty:
+                pass
+
+    def get_nowait(self) -> bytes:
+        """Removes and returns the oldest message without waiting.
+
+        Raises:
+            Empty: If neither the ring nor the spill file holds a message.
+        """
+        return self.get(0)
+
+    def close(self) -> None:
+        """Closes the spill file, leaving the queue handle open."""
+        if self._fd >= 0:
+            os.close(self._fd)
+            self._fd = -1
+
+    def __del__(self) -> None:
+        """Closes the spill file."""
+        self.close()
+
+    def _pending(self) -> bool:
+        """Returns whether the spill file holds records."""
+        return os.fstat(self._fd).st_size > HEADER.size
+
+    def _unspill(self) -> bytes | None:
+        """Removes and returns the oldest spilled record, if any."""
+        if not self._pending():
+            return None
+        with self._locked():
+            _, offset, count = self._read_header()
+            end = os.fstat(self._fd).st_size
+            prefix = os.pread(self._fd, LENGTH.size, offset)
+            item = None
+            if len(prefix) == LENGTH.size:
+                (length,) = LENGTH.unpack(prefix)
+                start = offset + LENGTH.size
+                if start + length <= end:
+                    item = os.pread(self._fd, length, start)
+                    offset = start + length
+                    count -= 1
+            if item is None or offset >= end:
+                # Drained, or the rest was torn by a crashed producer.
+                os.ftruncate(self._fd, HEADER.size)
+                offset, count = HEADER.size, 0
+            self._write_header(offset, count)
+            return item
+
+    def _read_header(self) -> tuple[bytes, int, int]:
+        """Returns the magic, read offset and record count of the file."""
+        return HEADER.unpack(os.pread(self._fd, HEADER.size, 0))
+
+    def _write_header(self, offset: int, count: int) -> None:
+        """Stores the read offset and record count in the file."""
+        os.pwrite(self._fd, HEADER.pack(MAGIC, offset, count), 0)
+
+    @contextmanager
+    def _locked(self) -> Iterator[None]:
+        """Holds the file lock against other handles and threads."""
+        with self._thread_lock:
+            fcntl.flock(self._fd, fcntl.LOCK_EX)
+            try:
+                yield
+            finally:
+                fcntl.flock(self._fd, fcntl.LOCK_UN)
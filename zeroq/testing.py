@@ -0,0 +1,217 @@
+"""Helpers for validating queues across real processes.
+
+Producers and consumers run in separate processes started with the
+``spawn`` method, exchange sequence-numbered messages through a temporary
+queue and report what they received, so the exchange can be checked for
+lost, duplicated, reordered and torn messages::
+
+    from zeroq.testing import run_exchange
+
+    def test_cross_process() -> None:
+        run_exchange(producers=2, consumers=2, messages=1000).verify()
+"""
+
+from __future__ import annotations
+
+import multiprocessing
+import struct
+import uuid
+from dataclasses import dataclass, field
+from multiprocessing.connection import Connection
+
+from .zeroq import Empty, Queue
+
+# Payload prefix: producer index and per-producer sequence number.
+_RECORD = struct.Struct('=IQ')
+
+#: Producer index marking the end-of-stream message sent to consumers.
+_SENTINEL = 0xFFFFFFFF
+
+
+def temp_queue_name(prefix: str = 'zeroq-test') -> str:
+    """Returns a queue name that does not collide with concurrent runs."""
+    return f'{prefix}-{uuid.uuid4().hex[:12]}'
+
+
+@dataclass
+class ExchangeReport:
+    """Messages received by each consumer of an exchange.
+
+    Attributes:
+        producers: Number of producer processes.
+        messages: Messages sent by each producer.
+        received: Per consumer, the (producer, sequence) pairs in the order
+            they were dequeued.
+        errors: Exceptions raised in consumer processes, as strings.
+        torn: The (producer, sequence) pairs of messages whose padding did
+            not match their record, i.e. read before all of it was visible.
+    """
+
+    producers: int
+    messages: int
+    received: list[list[tuple[int, int]]] = field(default_factory=list)
+    errors: list[str] = field(default_factory=list)
+    torn: list[tuple[int, int]] = field(default_factory=list)
+
+    def missing(self) -> set[tuple[int, int]]:
+        """Returns the messages that no consumer received."""
+        expected = {
+            (producer, seq)
+            for producer in range(self.producers)
+            for seq in range(self.messages)
+        }
+        return expected - {pair for pairs in self.received for pair in pairs}
+
+    def duplicates(self) -> set[tuple[int, int]]:
+        """Returns the messages received more than once."""
+        seen: set[tuple[int, int]] = set()
+        duplicates = set()
+        for pairs in self.received:
+            for pair in pairs:
+                if pair in seen:
+                    duplicates.add(pair)
+                seen.add(pair)
+        return duplicates
+
+    def reordered(self) -> list[tuple[int, int]]:
+        """Returns messages a consumer saw before an earlier one of the same
+        producer, which FIFO delivery rules out."""
+        reordered = []
+        for pairs in self.received:
+            last: dict[int, int] = {}
+            for producer, seq in pairs:
+                if seq <= last.get(producer, -1):
+                    reordered.append((producer, seq))
+                last[producer] = seq
+        return reordered
+
+    def verify(self) -> None:
+        """Checks that every message was delivered exactly once, whole and in
+        order.
+
+        Raises:
+            AssertionError: Describing the first violations found.
+        """
+        problems = []
+        if self.errors:
+            problems.append(f'consumer errors: {self.errors}')
+        for label, found in (
+            ('missing', sorted(self.missing())),
+            ('duplicated', sorted(self.duplicates())),
+            ('reordered', self.reordered()),
+            ('torn', self.torn),
+        ):
+            if found:
+                problems.append(f'{len(found)} {label}, e.g. {found[:5]}')
+        if problems:
+            raise AssertionError('; '.join(problems))
+
+
+def run_exchange(  # noqa: PLR0913
+    producers: int = 2,
+    consumers: int = 2,
+    messages: int = 1000,
+    element_size: int = 64,
+    capacity: int = 64,
+    timeout: float = 10.0,
+) -> ExchangeReport:
+    """Exchanges messages between producer and consumer processes.
+
+    Args:
+        producers: Number of producer processes.
+        consumers: Number of consumer processes.
+        messages: Messages sent by each producer.
+        element_size: Element size of the temporary queue, at least 12.
+        capacity: Capacity of the temporary queue, a power of two.
+        timeout: Seconds any single put or get may block before the
+            exchange is abandoned.
+
+    Returns:
+        What each consumer received; call verify() to check it.
+
+    Raises:
+        ValueError: If element_size is too small to hold a record.
+    """
+    if element_size < _RECORD.size:
+        msg = f'element_size must be at least {_RECORD.size}'
+        raise ValueError(msg)
+    name = temp_queue_name()
+    queue = Queue(name, element_size=element_size, capacity=capacity)
+    context = multiprocessing.get_context('spawn')
+    report = ExchangeReport(producers, messages)
+    processes: list[multiprocessing.process.BaseProcess] = []
+    try:
+        pipes = [context.Pipe(duplex=False) for _ in range(consumers)]
+        readers = [
+            context.Process(target=_consume, args=(name, timeout, sender))
+            for _, sender in pipes
+        ]
+        writers = [
+            context.Process(
+                target=_produce, args=(name, index, messages, timeout)
+            )
+            for index in range(producers)
+        ]
+        processes = readers + writers
+        for process in processes:
+            process.start()
+        for process in writers:
+            process.join()
+        for _ in readers:
+            queue.put(_record(queue, _SENTINEL, 0), timeout=timeout)
+        for (receiver, _), process in zip(pipes, readers):
+            received, torn, error = receiver.recv()
+            report.received.append(received)
+            report.torn.extend(torn)
+            if error:
+                report.errors.append(error)
+            process.join()
+    finally:
+        for process in processes:
+            if process.is_alive():
+                process.terminate()
+        queue.close()
+    return report
+
+
+def _padding(producer: int, seq: int, size: int) -> bytes:
+    # Differs between consecutive messages, so a payload mixing the writes
+    # of two laps of a slot does not match its record.
+    return bytes([(producer * 31 + seq) & 0xFF]) * (size - _RECORD.size)
+
+
+def _record(queue: Queue, producer: int, seq: int) -> bytes:
+    return _RECORD.pack(producer, seq) + _padding(
+        producer, seq, queue.element_size
+    )
+
+
+def _produce(name: str, index: int, messages: int, timeout: float) -> None:
+    queue = Queue(name, create=False)
+    try:
+        for seq in range(messages):
+            queue.put(_record(queue, index, seq), timeout=timeout)
+    finally:
+        queue.close()
+
+
+def _consume(name: str, timeout: float, results: Connection) -> None:
+    received: list[tuple[int, int]] = []
+    torn: list[tuple[int, int]] = []
+    error = None
+    queue = Queue(name, create=False)
+    try:
+        while True:
+            payload = queue.get(timeout=timeout)
+            producer, seq = _RECORD.unpack_from(payload)
+            if producer == _SENTINEL:
+                break
+            received.append((producer, seq))
+            padding = _padding(producer, seq, len(payload))
+            if payload[_RECORD.size :] != padding:
+                torn.append((producer, seq))
+    except Empty as exc:
+        error = repr(exc)
+    finally:
+        queue.close()
+    results.send((received, torn, error))
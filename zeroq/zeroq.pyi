@@ -1,11 +1,1018 @@
-class Empty(Exception):  # noqa: N818
-    """Raised when the queue is empty."""
+import os
+import queue
+from collections.abc import Sequence
+from typing import Any, Literal, Protocol, TypedDict
 
-class Full(Exception):  # noqa: N818
-    """Raised when the queue is full."""
+from typing_extensions import Buffer
+
+class Codec(Protocol):
+    """Encodes objects for put() and decodes them for get()."""
+
+    def encode(self, obj: Any) -> bytes | str:
+        """Encodes an object into the payload of a message.
+
+        :param obj: The object to encode.
+        :return: The encoded object; str is stored as UTF-8.
+        """
+
+    def decode(self, data: bytes) -> Any:
+        """Decodes the payload of a message.
+
+        :param data: The encoded object.
+        :return: The decoded object.
+        """
+
+class LayoutPlan(TypedDict):
+    """Segment layout produced by a queue configuration, in bytes."""
+
+    element_size: int
+    capacity: int
+    meta_size: int
+    slot_size: int
+    stride: int
+    header_size: int
+    cells_offset: int
+    cells_size: int
+    data_offset: int
+    data_size: int
+    urgent_offset: int
+    urgent_capacity: int
+    owners_offset: int
+    owners_size: int
+    total_size: int
+
+def required_size(
+    element_size: int,
+    capacity: int,
+    metadata: list[
+        Literal['sequence', 'timestamp', 'producer_id', 'deadline']
+    ]
+    | None = None,
+    headers_size: int = 0,
+    sequence_bits: Literal[32, 64] = 64,
+    encryption: Literal['chacha20-poly1305', 'aes-256-gcm'] | None = None,
+    urgent_lane: bool = False,
+    pad_slots: bool = False,
+    checksum: bool = False,
+    track_owners: bool = False,
+) -> int:
+    """Returns the shared-memory size a queue with these options needs.
+
+    :raises ValueError: If the capacity or an option is invalid.
+    """
+
+def plan(
+    element_size: int,
+    capacity: int,
+    metadata: list[
+        Literal['sequence', 'timestamp', 'producer_id', 'deadline']
+    ]
+    | None = None,
+    headers_size: int = 0,
+    sequence_bits: Literal[32, 64] = 64,
+    encryption: Literal['chacha20-poly1305', 'aes-256-gcm'] | None = None,
+    urgent_lane: bool = False,
+    pad_slots: bool = False,
+    checksum: bool = False,
+    track_owners: bool = False,
+) -> LayoutPlan:
+    """Describes the segment layout a queue with these options produces.
+
+    :raises ValueError: If the capacity or an option is invalid.
+    """
+
+def exists(name: str) -> bool:
+    """Returns whether a shared-memory segment named name exists.
+
+    :raises OSError: If the segment exists but cannot be opened.
+    """
+
+def unlink(name: str, missing_ok: bool = False) -> None:
+    """Removes a shared-memory segment, e.g. one left by a crashed creator.
+
+    Attached processes keep their mapping, but no new handle can attach.
+
+    :param name: Name of the segment, as given to Queue.
+    :param missing_ok: Do nothing if the segment does not exist.
+    :raises FileNotFoundError: If the segment does not exist.
+    :raises OSError: If the segment cannot be removed.
+    """
+
+def layout_descriptor() -> str:
+    """Returns the shared-memory layout of this build as a flat JSON object.
+
+    Golden copies are checked in per pointer width and layout version so
+    implementations in other languages can prove byte-level compatibility.
+    """
+
+def verify_layout(golden: str) -> None:
+    """Checks a golden layout, as returned by layout_descriptor().
+
+    :raises ValueError: Listing every mismatching entry.
+    """
+
+class AuditReport(TypedDict):
+    """Result of a cell-consistency audit."""
+
+    head: int
+    tail: int
+    positions_valid: bool
+    inconsistencies: list[tuple[int, int, int]]
+    fixed: int
+    consistent: bool
+
+class Empty(queue.Empty):
+    """Raised when the queue is empty; a subclass of queue.Empty."""
+
+class Full(queue.Full):
+    """Raised when the queue is full; a subclass of queue.Full."""
+
+class Message:
+    """A dequeued item with the metadata enabled at queue creation."""
+
+    @property
+    def payload(self) -> bytes:
+        """The item payload."""
+
+    @property
+    def sequence(self) -> int | None:
+        """Position of the message in the queue."""
+
+    @property
+    def timestamp(self) -> float | None:
+        """Enqueue time in seconds since the Unix epoch."""
+
+    @property
+    def dequeued_at(self) -> float | None:
+        """Dequeue time in seconds since the Unix epoch."""
+
+    @property
+    def producer_id(self) -> int | None:
+        """Process id of the producer."""
+
+    @property
+    def deadline(self) -> float | None:
+        """Deadline in seconds since the Unix epoch, if one was set."""
+
+    @property
+    def headers(self) -> bytes | None:
+        """Headers attached by the producer."""
+
+class DecryptionError(Exception):
+    """Raised when an encrypted message cannot be authenticated."""
+
+class AlreadyExists(FileExistsError):  # noqa: N818
+    """Raised when creating a queue whose segment already exists."""
+
+class QueueClosed(OSError):  # noqa: N818
+    """Raised when using a closed handle, or a queue that was shut down."""
+
+class Cancelled(Exception):  # noqa: N818
+    """Raised by blocking operations of a handle after Queue.cancel()."""
+
+class InvalidParameters(ValueError):  # noqa: N818
+    """Raised when the queue attached to does not have the expected layout."""
+
+class CorruptMessage(RuntimeError):  # noqa: N818
+    """Raised when a dequeued message does not match its checksum."""
+
+class SlotView:
+    """A payload accessed in place in shared memory.
+
+    Returned by get_buffer() for a dequeued message, read-only, and by
+    reserve() for a message being written. Supports the buffer protocol;
+    the slot stays reserved until release() is called. Used as a context
+    manager it yields a memoryview and releases the slot on exit.
+    """
+
+    @property
+    def released(self) -> bool:
+        """Whether the slot was handed over."""
+
+    def release(self) -> None:
+        """Hands a dequeued slot back to producers, or publishes a reserved
+        one to consumers; later calls have no effect.
+
+        :raises BufferError: If memoryviews of the payload are still alive.
+        :raises RuntimeError: If a reserved slot was reclaimed.
+        """
+
+    def __len__(self) -> int:
+        """Returns the payload size in bytes."""
+
+    def __setitem__(
+        self, key: int | slice, value: bytes | bytearray | memoryview | int
+    ) -> None:
+        """Writes into a reserved payload.
+
+        :raises TypeError: If the view was returned by get_buffer().
+        """
+
+    def __buffer__(self, flags: int, /) -> memoryview: ...
+    def __enter__(self) -> memoryview: ...
+    def __exit__(self, *args: object) -> bool: ...
+
+class ManualClock:
+    """A manually driven clock for deterministic timeout testing.
+
+    Waiting on a queue using this clock advances it instead of sleeping;
+    busy-spinning waits do not advance it.
+    """
+
+    def __init__(self, start: float = 0.0) -> None:
+        """Creates a clock reading start seconds.
+
+        :raises ValueError: If start is negative or not finite.
+        """
+
+    def time(self) -> float:
+        """Returns the current reading in seconds."""
+
+    def advance(self, seconds: float) -> None:
+        """Moves the clock forward.
+
+        :raises ValueError: If seconds is negative or not finite.
+        """
+
+class Pool:
+    """Fixed-size blocks in shared memory for payloads too large for slots.
+
+    Producers allocate a block, write the payload and enqueue only its
+    index; consumers read the block and free it. Blocks are reference
+    counted and return to the pool when the last reference is freed. The
+    handle that creates the pool removes its segment when garbage-collected.
+    """
+
+    def __init__(
+        self,
+        name: str,
+        block_size: int | None = None,
+        blocks: int | None = None,
+        create: bool = True,
+    ) -> None:
+        """Creates or attaches to a pool.
+
+        :param name: Shared memory segment name.
+        :param block_size: Size of each block in bytes (required if
+            creating).
+        :param blocks: Number of blocks (required if creating).
+        :param create: Whether to create a new pool (default=True).
+
+        :raises ValueError: If parameters are invalid or do not match the
+            existing pool.
+        :raises AlreadyExists: If the segment to create already exists.
+        :raises OSError: If the segment cannot be created or opened, or
+            holds no pool.
+        """
+
+    @property
+    def name(self) -> str:
+        """Name of the shared memory segment."""
+
+    @property
+    def block_size(self) -> int:
+        """Size of each block in bytes."""
+
+    @property
+    def blocks(self) -> int:
+        """Number of blocks in the pool."""
+
+    @property
+    def available(self) -> int:
+        """Number of free blocks, a snapshot."""
+
+    def alloc(self) -> int:
+        """Allocates a block with one reference.
+
+        :return: Index of the block, to enqueue instead of the payload.
+        :raises Full: If every block is allocated.
+        """
+
+    def write(self, data: Buffer) -> int:
+        """Allocates a block and copies data into its front.
+
+        :return: Index of the block.
+        :raises ValueError: If data is larger than a block.
+        :raises Full: If every block is allocated.
+        """
+
+    def read(self, block: int, size: int | None = None) -> bytes:
+        """Copies size bytes, by default the whole block, out of a block.
+
+        :raises IndexError: If block is out of range.
+        :raises ValueError: If block is free or size exceeds it.
+        """
+
+    def view(self, block: int) -> memoryview:
+        """Returns a writable view over an allocated block.
+
+        :raises IndexError: If block is out of range.
+        :raises ValueError: If block is free.
+        """
+
+    def retain(self, block: int) -> None:
+        """Adds a reference to an allocated block.
+
+        :raises IndexError: If block is out of range.
+        :raises ValueError: If block is free.
+        """
+
+    def free(self, block: int) -> None:
+        """Drops a reference, returning the block once none is left.
+
+        :raises IndexError: If block is out of range.
+        :raises ValueError: If block is free.
+        """
+
+class Deque:
+    """A bounded double-ended queue of byte strings in shared memory.
+
+    Workers push and pop at one end while idle peers steal from the other,
+    in any process. The handle that creates the deque removes its segment
+    when garbage-collected.
+    """
+
+    def __init__(
+        self,
+        name: str,
+        element_size: int | None = None,
+        capacity: int | None = None,
+        create: bool = True,
+    ) -> None:
+        """Creates or attaches to a deque.
+
+        :param name: Shared memory segment name.
+        :param element_size: Maximum size of an element in bytes (required
+            if creating).
+        :param capacity: Maximum number of elements (required if creating).
+        :param create: Whether to create a new deque (default=True).
+
+        :raises ValueError: If parameters are invalid or do not match the
+            existing deque.
+        :raises AlreadyExists: If the segment to create already exists.
+        :raises OSError: If the segment cannot be created or opened, or
+            holds no deque.
+        """
+
+    @property
+    def name(self) -> str:
+        """Name of the shared memory segment."""
+
+    @property
+    def element_size(self) -> int:
+        """Maximum size of an element in bytes."""
+
+    @property
+    def capacity(self) -> int:
+        """Maximum number of elements."""
+
+    def __len__(self) -> int:
+        """Number of elements, a snapshot."""
+
+    def push_front(self, item: Buffer, timeout: float | None = None) -> None:
+        """Adds an element at the front.
+
+        :param item: The element, at most element_size bytes.
+        :param timeout: Maximum time to wait for room in seconds; waits
+            indefinitely if omitted, and does not wait if 0.
+
+        :raises ValueError: If item is larger than an element.
+        :raises Full: If the deque stays full beyond the timeout.
+        :raises RuntimeError: If the deque is poisoned.
+        """
+
+    def push_back(self, item: Buffer, timeout: float | None = None) -> None:
+        """Adds an element at the back.
+
+        :param item: The element, at most element_size bytes.
+        :param timeout: Maximum time to wait for room in seconds; waits
+            indefinitely if omitted, and does not wait if 0.
+
+        :raises ValueError: If item is larger than an element.
+        :raises Full: If the deque stays full beyond the timeout.
+        :raises RuntimeError: If the deque is poisoned.
+        """
+
+    def pop_front(self, timeout: float | None = None) -> bytes:
+        """Removes and returns the element at the front.
+
+        :param timeout: Maximum time to wait for an element in seconds;
+            waits indefinitely if omitted, and does not wait if 0.
+
+        :raises Empty: If the deque stays empty beyond the timeout.
+        :raises RuntimeError: If the deque is poisoned.
+        """
+
+    def pop_back(self, timeout: float | None = None) -> bytes:
+        """Removes and returns the element at the back.
+
+        :param timeout: Maximum time to wait for an element in seconds;
+            waits indefinitely if omitted, and does not wait if 0.
+
+        :raises Empty: If the deque stays empty beyond the timeout.
+        :raises RuntimeError: If the deque is poisoned.
+        """
+
+class Stack:
+    """A bounded lock-free LIFO stack of byte strings in shared memory.
+
+    Suited to free lists and undo buffers shared between processes. The
+    handle that creates the stack removes its segment when
+    garbage-collected.
+    """
+
+    def __init__(
+        self,
+        name: str,
+        element_size: int | None = None,
+        capacity: int | None = None,
+        create: bool = True,
+    ) -> None:
+        """Creates or attaches to a stack.
+
+        :param name: Shared memory segment name.
+        :param element_size: Maximum size of an element in bytes (required
+            if creating).
+        :param capacity: Maximum number of elements (required if creating).
+        :param create: Whether to create a new stack (default=True).
+
+        :raises ValueError: If parameters are invalid or do not match the
+            existing stack.
+        :raises AlreadyExists: If the segment to create already exists.
+        :raises OSError: If the segment cannot be created or opened, or
+            holds no stack.
+        """
+
+    @property
+    def name(self) -> str:
+        """Name of the shared memory segment."""
+
+    @property
+    def element_size(self) -> int:
+        """Maximum size of an element in bytes."""
+
+    @property
+    def capacity(self) -> int:
+        """Maximum number of elements."""
+
+    def __len__(self) -> int:
+        """Number of elements, a snapshot."""
+
+    def push(self, item: Buffer, timeout: float | None = None) -> None:
+        """Pushes an element on top.
+
+        :param item: The element, at most element_size bytes.
+        :param timeout: Maximum time to wait for room in seconds; waits
+            indefinitely if omitted, and does not wait if 0.
+
+        :raises ValueError: If item is larger than an element.
+        :raises Full: If the stack stays full beyond the timeout.
+        """
+
+    def pop(self, timeout: float | None = None) -> bytes:
+        """Removes and returns the element pushed last.
+
+        :param timeout: Maximum time to wait for an element in seconds;
+            waits indefinitely if omitted, and does not wait if 0.
+
+        :raises Empty: If the stack stays empty beyond the timeout.
+        """
+
+class SpscQueue:
+    """A bounded FIFO queue of byte strings for one producer and one consumer.
+
+    Skips the compare-and-swap loops and per-slot sequences of Queue: each
+    side owns one index and publishes it with a release store. Puts from
+    several handles at once, or gets from several handles at once, corrupt
+    the queue. The handle that creates the queue removes its segment when
+    garbage-collected.
+    """
+
+    def __init__(
+        self,
+        name: str,
+        element_size: int | None = None,
+        capacity: int | None = None,
+        create: bool = True,
+    ) -> None:
+        """Creates or attaches to a single-producer single-consumer queue.
+
+        :param name: Shared memory segment name.
+        :param element_size: Maximum size of an element in bytes (required
+            if creating).
+        :param capacity: Maximum number of elements (required if creating).
+        :param create: Whether to create a new queue (default=True).
+
+        :raises ValueError: If parameters are invalid or do not match the
+            existing queue.
+        :raises AlreadyExists: If the segment to create already exists.
+        :raises OSError: If the segment cannot be created or opened, or
+            holds no such queue.
+        """
+
+    @property
+    def name(self) -> str:
+        """Name of the shared memory segment."""
+
+    @property
+    def element_size(self) -> int:
+        """Maximum size of an element in bytes."""
+
+    @property
+    def capacity(self) -> int:
+        """Maximum number of elements."""
+
+    def __len__(self) -> int:
+        """Number of elements, a snapshot."""
+
+    def put(self, item: Buffer, timeout: float | None = None) -> None:
+        """Adds an element; only one handle may put at a time.
+
+        :param item: The element, at most element_size bytes.
+        :param timeout: Maximum time to wait for room in seconds; waits
+            indefinitely if omitted, and does not wait if 0.
+
+        :raises ValueError: If item is larger than an element.
+        :raises Full: If the queue stays full beyond the timeout.
+        """
+
+    def get(self, timeout: float | None = None) -> bytes:
+        """Removes and returns the oldest element; only one handle may get
+        at a time.
+
+        :param timeout: Maximum time to wait for an element in seconds;
+            waits indefinitely if omitted, and does not wait if 0.
+
+        :raises Empty: If the queue stays empty beyond the timeout.
+        """
+
+class ShardedQueue:
+    """A bounded MPMC queue of byte strings split into shards.
+
+    Each shard is a queue of its own, so many producers and consumers do
+    not all contend on the same positions. Each thread has a home shard:
+    puts go to it and move on to the next shards only while it is full, and
+    gets take from it and steal from the next shards only while it is empty.
+    Order is kept within a shard only. The handle that creates the queue
+    removes its segment when garbage-collected.
+    """
+
+    def __init__(
+        self,
+        name: str,
+        element_size: int | None = None,
+        capacity: int | None = None,
+        shards: int | None = None,
+        shard: int | None = None,
+        create: bool = True,
+    ) -> None:
+        """Creates or attaches to a sharded queue.
+
+        :param name: Shared memory segment name.
+        :param element_size: Maximum size of an element in bytes (required
+            if creating).
+        :param capacity: Number of slots of each shard, a power of two
+            (required if creating).
+        :param shards: Number of shards, at most 256 (required if creating).
+        :param shard: Home shard of this handle; each thread picks one of
+            its own if omitted.
+        :param create: Whether to create a new queue (default=True).
+
+        :raises ValueError: If parameters are invalid or do not match the
+            existing queue.
+        :raises AlreadyExists: If the segment to create already exists.
+        :raises OSError: If the segment cannot be created or opened, or
+            holds no sharded queue.
+        """
+
+    @property
+    def name(self) -> str:
+        """Name of the shared memory segment."""
+
+    @property
+    def element_size(self) -> int:
+        """Maximum size of an element in bytes."""
+
+    @property
+    def capacity(self) -> int:
+        """Number of slots of each shard."""
+
+    @property
+    def shards(self) -> int:
+        """Number of shards."""
+
+    @property
+    def shard(self) -> int:
+        """Home shard of the calling thread."""
+
+    def shard_lengths(self) -> list[int]:
+        """Number of elements in each shard, a snapshot."""
+
+    def __len__(self) -> int:
+        """Number of elements, a snapshot."""
+
+    def put(self, item: Buffer, timeout: float | None = None) -> None:
+        """Adds an element to the home shard, or the next one with room.
+
+        :param item: The element, at most element_size bytes.
+        :param timeout: Maximum time to wait for room in seconds; waits
+            indefinitely if omitted, and does not wait if 0.
+
+        :raises ValueError: If item is larger than an element.
+        :raises Full: If every shard stays full beyond the timeout.
+        """
+
+    def get(self, timeout: float | None = None) -> bytes:
+        """Removes and returns the oldest element of the home shard, or
+        steals one from the next shard holding any.
+
+        :param timeout: Maximum time to wait for an element in seconds;
+            waits indefinitely if omitted, and does not wait if 0.
+
+        :raises Empty: If every shard stays empty beyond the timeout.
+        """
+
+class LvcSlot:
+    """A shared-memory cell holding the latest of the values put into it.
+
+    Writers never wait for readers, and readers only ever see the most
+    recent value. Each handle remembers the version it read last, for
+    get_changed(). The handle that creates the slot removes its segment
+    when garbage-collected.
+    """
+
+    def __init__(
+        self,
+        name: str,
+        element_size: int | None = None,
+        create: bool = True,
+    ) -> None:
+        """Creates or attaches to a latest-value slot.
+
+        :param name: Shared memory segment name.
+        :param element_size: Maximum size of a value in bytes (required if
+            creating).
+        :param create: Whether to create a new slot (default=True).
+
+        :raises ValueError: If parameters are invalid or do not match the
+            existing slot.
+        :raises AlreadyExists: If the segment to create already exists.
+        :raises OSError: If the segment cannot be created or opened, or
+            holds no slot.
+        """
+
+    @property
+    def name(self) -> str:
+        """Name of the shared memory segment."""
+
+    @property
+    def element_size(self) -> int:
+        """Maximum size of a value in bytes."""
+
+    @property
+    def version(self) -> int:
+        """Number of values put so far, across all handles."""
+
+    def put(self, item: Buffer) -> None:
+        """Replaces the value; never blocks on readers.
+
+        :param item: The new value, at most element_size bytes.
+
+        :raises ValueError: If item is larger than element_size.
+        """
+
+    def get(self) -> bytes:
+        """Returns the latest value, waiting for a value being written.
+
+        :raises Empty: If no value was put yet, or if the process writing
+            the latest value exited before finishing it.
+        """
+
+    def get_changed(self, timeout: float | None = None) -> bytes:
+        """Returns the latest value once it is newer than the last one read.
+
+        :param timeout: Maximum time to wait for a new value in seconds;
+            waits indefinitely if omitted, and does not wait if 0.
+
+        :raises Empty: If no new value is put before the timeout.
+        """
+
+class ConflatingQueue:
+    """A FIFO queue keeping at most one pending value per key.
+
+    A put for a key that is still pending replaces its value, which keeps
+    its position in the queue, so the queue never grows beyond the number of
+    distinct keys. The handle that creates the queue removes its segment
+    when garbage-collected.
+    """
+
+    def __init__(
+        self,
+        name: str,
+        key_size: int | None = None,
+        element_size: int | None = None,
+        capacity: int | None = None,
+        create: bool = True,
+    ) -> None:
+        """Creates or attaches to a conflating queue.
+
+        :param name: Shared memory segment name.
+        :param key_size: Maximum size of a key in bytes (required if
+            creating).
+        :param element_size: Maximum size of a value in bytes (required if
+            creating).
+        :param capacity: Maximum number of pending keys (required if
+            creating).
+        :param create: Whether to create a new queue (default=True).
+
+        :raises ValueError: If parameters are invalid or do not match the
+            existing queue.
+        :raises AlreadyExists: If the segment to create already exists.
+        :raises OSError: If the segment cannot be created or opened, or
+            holds no conflating queue.
+        """
+
+    @property
+    def name(self) -> str:
+        """Name of the shared memory segment."""
+
+    @property
+    def key_size(self) -> int:
+        """Maximum size of a key in bytes."""
+
+    @property
+    def element_size(self) -> int:
+        """Maximum size of a value in bytes."""
+
+    @property
+    def capacity(self) -> int:
+        """Maximum number of pending keys."""
+
+    def __len__(self) -> int:
+        """Number of pending keys, a snapshot."""
+
+    def put(
+        self, key: Buffer, value: Buffer, timeout: float | None = None
+    ) -> bool:
+        """Enqueues value for key, replacing the value still pending for it.
+
+        :param key: The key, at most key_size bytes.
+        :param value: The value, at most element_size bytes.
+        :param timeout: Maximum time to wait for room for a new key in
+            seconds; waits indefinitely if omitted, and does not wait if 0.
+        :return: Whether a pending value was replaced.
+
+        :raises ValueError: If the key or value is too large.
+        :raises Full: If the queue stays full of other keys beyond the
+            timeout.
+        :raises RuntimeError: If the queue is poisoned.
+        """
+
+    def get(self, timeout: float | None = None) -> tuple[bytes, bytes]:
+        """Dequeues the oldest pending key with its latest value.
+
+        :param timeout: Maximum time to wait for a key in seconds; waits
+            indefinitely if omitted, and does not wait if 0.
+        :return: The key and its value.
+
+        :raises Empty: If the queue stays empty beyond the timeout.
+        :raises RuntimeError: If the queue is poisoned.
+        """
+
+class Bus:
+    """A broadcast ring where every handle receives every message.
+
+    Publishers never wait for readers: once the ring is full, a message
+    overwrites the oldest one. Each handle keeps its own cursor, starting
+    at the messages published after it attached, unless it joins a
+    consumer group: members of a group share its offset, so each message
+    goes to one member while every group receives all of them. A reader
+    that falls more than capacity messages behind skips ahead to the oldest
+    retained message. The handle that creates the bus removes its segment
+    when garbage-collected.
+    """
+
+    def __init__(
+        self,
+        name: str,
+        element_size: int | None = None,
+        capacity: int | None = None,
+        create: bool = True,
+        group: str | None = None,
+        max_groups: int | None = None,
+    ) -> None:
+        """Creates or attaches to a bus.
+
+        :param name: Shared memory segment name.
+        :param element_size: Maximum size of a message in bytes (required
+            if creating).
+        :param capacity: Number of most recent messages retained (required
+            if creating).
+        :param create: Whether to create a new bus (default=True).
+        :param group: Consumer group to join, created at the newest message
+            if it does not exist; its members share the messages.
+        :param max_groups: Number of consumer groups the bus has room for
+            (default=16).
+
+        :raises ValueError: If parameters are invalid or do not match the
+            existing bus, or there is no room for the group.
+        :raises AlreadyExists: If the segment to create already exists.
+        :raises OSError: If the segment cannot be created or opened, or
+            holds no bus.
+        """
+
+    @property
+    def name(self) -> str:
+        """Name of the shared memory segment."""
+
+    @property
+    def element_size(self) -> int:
+        """Maximum size of a message in bytes."""
+
+    @property
+    def capacity(self) -> int:
+        """Number of most recent messages retained."""
+
+    @property
+    def published(self) -> int:
+        """Number of messages published so far, the next sequence."""
+
+    @property
+    def cursor(self) -> int:
+        """Sequence of the next message this handle, or its group, receives."""
+
+    @property
+    def group(self) -> str | None:
+        """Name of the consumer group of this handle."""
+
+    @property
+    def max_groups(self) -> int:
+        """Number of consumer groups the bus has room for."""
+
+    def groups(self) -> dict[str, int]:
+        """Returns the offset of each consumer group of the bus."""
+
+    @property
+    def oldest(self) -> int:
+        """Sequence of the oldest message still retained, a snapshot."""
+
+    def seek(self, position: Literal['oldest', 'newest'] | int) -> int:
+        """Moves the cursor of this handle, or of its group.
+
+        :param position: 'oldest' for the oldest retained message, 'newest'
+            for the next message to be published, or the sequence of a
+            retained message.
+        :return: The new cursor.
+
+        :raises ValueError: If the sequence is not retained or not
+            published yet.
+        :raises TypeError: If position is neither a sequence nor a known
+            position.
+        """
+
+    @property
+    def missed(self) -> int:
+        """Number of messages this handle skipped as overwritten."""
+
+    def __len__(self) -> int:
+        """Number of published messages this handle has yet to receive."""
+
+    def publish(self, item: Buffer) -> int:
+        """Publishes a message to every handle; never waits for readers.
+
+        :param item: The message, at most element_size bytes.
+        :return: Sequence of the message.
+
+        :raises ValueError: If item is larger than element_size.
+        """
+
+    def receive(self, timeout: float | None = None) -> bytes:
+        """Receives the next message for this handle or its group.
+
+        :param timeout: Maximum time to wait for a message in seconds;
+            waits indefinitely if omitted, and does not wait if 0.
+
+        :raises Empty: If no message is published before the timeout.
+        """
+
+class Router:
+    """Named queues, topics, sharing one shared-memory segment.
+
+    A directory at the start of the segment maps each topic to the offset
+    of its queue, so a deployment manages one segment name instead of one
+    per queue. The handle that creates the router removes its segment when
+    garbage-collected.
+    """
+
+    def __init__(
+        self,
+        name: str,
+        topics: dict[str, dict[str, Any]] | None = None,
+        create: bool = True,
+    ) -> None:
+        """Creates or attaches to a router.
+
+        :param name: Shared memory segment name.
+        :param topics: Options of each topic's queue, passed to the Queue
+            constructor; element_size and capacity are required, and the
+            layout options fix its size (required if creating).
+        :param create: Whether to create a new router (default=True).
+
+        :raises ValueError: If topics or options are invalid.
+        :raises AlreadyExists: If the segment to create already exists.
+        :raises OSError: If the segment cannot be created or opened, or
+            holds no router.
+        """
+
+    @property
+    def name(self) -> str:
+        """Name of the shared memory segment."""
+
+    @property
+    def topics(self) -> list[str]:
+        """Names of the topics, in the order they were created."""
+
+    def __contains__(self, topic: str) -> bool:
+        """Whether the router has the topic."""
+
+    def queue(self, topic: str, **options: Any) -> Queue:
+        """Opens a handle to the queue of a topic.
+
+        :param topic: Name of the topic.
+        :param options: Handle options passed to the Queue constructor,
+            e.g. serializer or keys.
+        :return: A handle to the topic's queue.
+
+        :raises KeyError: If the router has no such topic.
+        """
+
+class DebugInfo(TypedDict):
+    """Layout of an attached queue returned by Queue.debug_info()."""
+
+    name: str
+    element_size: int
+    capacity: int
+    meta_size: int
+    slot_size: int
+    stride: int
+    padded: bool
+    checksum: bool
+    track_owners: bool
+    cell_size: int
+    cells_offset: int
+    data_offset: int
+    urgent_capacity: int
+
+class RuntimeOptions(TypedDict):
+    """Runtime options shared through Queue.configure()."""
+
+    wait: Literal['spin', 'hybrid', 'sleep']
+    busy_spin: bool
+    adaptive: bool
+    spin_limit: int
+    yield_limit: int
+    park_interval: float
+    backpressure: Literal['linear', 'exponential'] | None
+    backpressure_base: float
+    backpressure_max: float
+    slow_op_threshold: float | None
+    drop_expired: bool
+
+class HandleStats(TypedDict):
+    """Time the wait loops of a handle spent in each phase, in seconds."""
+
+    spin_time: float
+    yield_time: float
+    park_time: float
+    busy_fraction: float
+
+class Totals(TypedDict):
+    """Message counts returned by Queue.totals()."""
+
+    enqueued: int
+    dequeued: int
+
+class Health(TypedDict):
+    """Stalled-slot report returned by Queue.health()."""
+
+    healthy: bool
+    stalled: list[tuple[Literal['main', 'urgent'], int, float]]
+    poison_skipped: int
+
+class AttachedProcess(TypedDict):
+    """Process returned by Queue.attached_processes()."""
+
+    pid: int
+    roles: list[Literal['producer', 'consumer']]
+    handles: int
+    alive: bool
 
 class Queue:
-    """A shared-memory MPMC queue."""
+    """A shared-memory MPMC queue.
+
+    Handles inherited through ``os.fork()`` keep working in the child, which
+    never removes the segment or shuts the queue down on behalf of the parent.
+    """
 
     def __init__(
         self,
@@ -13,59 +1020,705 @@ class Queue:
         element_size: int | None = None,
         capacity: int | None = None,
         create: bool = True,
+        mode: Literal['create', 'open', 'open_or_create'] | None = None,
+        offset: int = 0,
+        adopt: bool = False,
+        wait: Literal['spin', 'hybrid', 'sleep'] | None = None,
+        busy_spin: bool = False,
+        adaptive: bool = False,
+        spin_limit: int = 1000,
+        yield_limit: int = 100,
+        park_interval: float = 0.001,
+        backpressure: Literal['linear', 'exponential'] | None = None,
+        backpressure_base: float = 0.0001,
+        backpressure_max: float = 0.01,
+        metadata: list[
+            Literal['sequence', 'timestamp', 'producer_id', 'deadline']
+        ] | None = None,
+        headers_size: int = 0,
+        sequence_bits: Literal[32, 64] = 64,
+        pad_slots: bool = False,
+        urgent_lane: bool = False,
+        encryption: Literal['chacha20-poly1305', 'aes-256-gcm'] | None = None,
+        keys: dict[int, bytes] | None = None,
+        key_id: int | None = None,
+        audit: Literal['check', 'fix'] | None = None,
+        slow_op_threshold: float | None = None,
+        poison_timeout: float | None = None,
+        skip_poisoned: bool = False,
+        drop_expired: bool = False,
+        clock: ManualClock | None = None,
+        serializer: Literal['pickle'] | None = None,
+        codec: Literal['raw', 'pickle', 'json', 'msgpack', 'cbor']
+        | Codec
+        | None = None,
+        fmt: str | None = None,
+        out_of_band: int | None = None,
+        huge_pages: bool = False,
+        numa_node: int | None = None,
+        numa_policy: Literal['bind', 'preferred'] = 'bind',
+        backing: Literal['shm', 'memfd', 'file'] = 'shm',
+        fd: int | None = None,
+        path: str | os.PathLike[str] | None = None,
+        checksum: bool = False,
+        track_owners: bool = False,
+        role: Literal['producer', 'consumer', 'both'] | None = None,
     ) -> None:
         """Creates or attaches to a shared-memory queue.
 
         :param name: Shared memory segment name.
-        :param element_size: Element size in bytes (required if creating).
-        :param capacity: Number of slots (power of two, required if creating).
-        :param create: Whether to create a new queue (default=True).
+        :param element_size: Element size in bytes (required if creating);
+            when attaching, the queue must have elements of this size.
+        :param capacity: Number of slots (power of two, required if creating);
+            when attaching, the queue must have this capacity.
+        :param create: Whether to create a new queue (default=True);
+            Queue.create() and Queue.open() spell this out.
+        :param mode: 'create', 'open', or 'open_or_create', which attaches
+            to the queue if it exists and creates it otherwise, so processes
+            can start in any order; the existing queue must match
+            element_size, capacity and the other layout options. Takes
+            precedence over create.
+        :param offset: Byte offset of the queue within the segment, a multiple
+            of 8 (default=0).
+        :param adopt: Initialize a new queue at offset inside an existing
+            segment owned by another tool; requires create=False.
+        :param wait: Wait policy of blocking operations: 'spin' busy-spins,
+            'hybrid' spins, then yields, then parks, and 'sleep' parks between
+            every attempt. Shorthand for busy_spin and adaptive, which must
+            not be given with it (default='sleep').
+        :param busy_spin: Busy-spin in blocking operations instead of sleeping
+            (default=False).
+        :param adaptive: Spin, then yield, then park in blocking operations
+            (default=False).
+        :param spin_limit: Attempts spent spinning before yielding.
+        :param yield_limit: Attempts spent yielding before parking.
+        :param park_interval: Sleep in seconds between parked attempts, the
+            maximum sleep of the 'hybrid' and 'sleep' policies. On Linux and
+            macOS, parked attempts wait on the queue header instead and wake
+            as soon as a peer makes progress.
+        :param backpressure: Delay curve applied by blocking puts after
+            consecutive Full outcomes, 'linear' or 'exponential'.
+        :param backpressure_base: Initial throttling delay in seconds.
+        :param backpressure_max: Maximum throttling delay in seconds.
+        :param metadata: Metadata fields stored with every message
+            (used only if creating).
+        :param headers_size: Maximum size of per-message headers in bytes,
+            0 disables headers (used only if creating).
+        :param sequence_bits: Width of the per-slot sequence counter; 32
+            halves the cells array and limits capacity to 2**30
+            (used only if creating).
+        :param pad_slots: Round the slot stride up to a multiple of the
+            64-byte cache line, trading memory for throughput with small
+            elements (used only if creating).
+        :param urgent_lane: Reserve a lane of 1/16 of the capacity for
+            put_urgent(), drained first by get() (used only if creating).
+        :param encryption: Authenticated cipher protecting every payload
+            (used only if creating).
+        :param keys: 32-byte keys by key id, required for encrypted queues
+            when creating and when attaching.
+        :param key_id: Key used to encrypt new messages, defaults to the
+            highest key id.
+        :param audit: Cell-consistency audit run when attaching, 'check'
+            raises on inconsistencies and 'fix' repairs them.
+        :param slow_op_threshold: Blocking operations waiting at least this many
+            seconds are logged as warnings on the 'zeroq' logger.
+        :param poison_timeout: Seconds a slot may stay reserved but
+            unpublished before health() reports it as poisoned.
+        :param skip_poisoned: Let consumers skip slots whose producer died
+            before publishing them; requires a queue created with
+            track_owners. Slots of live producers are never skipped.
+        :param drop_expired: Discard messages past their deadline on dequeue
+            instead of returning them (default=False).
+        :param clock: Time source replacing real time for timeouts and waiting.
+        :param serializer: 'pickle' makes put()/get() and their variants take
+            and return arbitrary objects, framed behind a 4-byte length field;
+            applies to this handle only, so pass it to every handle.
+        :param codec: Encoding of put()/get() objects, framed like with
+            serializer: 'raw' (default) for bytes as they are, 'pickle',
+            'json', 'msgpack' or 'cbor' for formats other languages decode
+            too (the latter two need the msgpack and cbor2 packages), or an
+            object with encode() and decode() methods; excludes serializer.
+        :param fmt: struct format, e.g. '<IdQ16s', making put()/get() and
+            their variants pack and unpack tuples as fixed-size records;
+            defaults element_size to the record size and excludes serializer
+            and codec.
+        :param out_of_band: Minimum size in bytes of the pickle protocol 5
+            buffers, e.g. numpy array data, copied into side segments that
+            consumers map instead of unpickling a copy; requires the pickle
+            serializer.
+        :param huge_pages: Back the segment with 2 MiB huge pages (Linux
+            only): transparent ones in /dev/shm, which must be mounted with
+            huge=advise, or ones from the hugetlb pool with backing='memfd';
+            the creator rounds the segment up to whole huge pages, and every
+            handle should pass it to map them as such.
+        :param numa_node: NUMA node to place the pages of the segment on
+            (Linux only, used only if creating); pages otherwise land on the
+            node of the process that first writes to them.
+        :param numa_policy: 'bind' to allocate only on numa_node, or
+            'preferred' to fall back to other nodes once it is full.
+        :param backing: 'shm' for a segment named name; 'memfd' for an
+            anonymous segment that the kernel frees with the last handle, even
+            after SIGKILL (Linux only); or 'file' for a regular file at path,
+            which its creator removes on close and crashes leave behind. name
+            then only labels the queue, and other processes attach through
+            segment_fd() or the same path.
+        :param fd: Descriptor of the segment to attach to instead of opening
+            name, e.g. one received with socket.recv_fds(); requires
+            create=False, and the handle keeps a duplicate.
+        :param path: File of a queue with backing='file'.
+        :param checksum: Store a CRC-32 of every message, verified on every
+            get, so that a process scribbling over the segment raises
+            CorruptMessage instead of delivering garbage (used only if
+            creating).
+        :param track_owners: Record the process id of the producer of every
+            slot as it is reserved, so that slots whose producer died before
+            publishing them can be reclaimed by repair() or skip_poisoned
+            (Unix only, used only if creating); producers in another PID
+            namespace are never known to have exited.
+        :param role: Role the handle registers with in the queue header, as
+            reported by attached_processes().
+
+        :raises ValueError: If element_size/capacity is missing when creating,
+            numa_node is not a node with memory, or skip_poisoned is given
+            for a queue without track_owners.
+        :raises InvalidParameters: If the queue attached to does not have the
+            expected element_size, capacity, fmt or layout options.
+        :raises ImportError: If the package of a built-in codec is missing.
+        :raises AlreadyExists: If the segment to create already exists.
+        :raises OSError: If shared memory creation/opening fails, /dev/shm
+            is mounted without huge pages, the hugetlb pool has too few free
+            pages for a memfd segment, or the kernel refuses to bind the
+            segment to numa_node.
+        :raises NotImplementedError: If huge_pages or numa_node is used
+            outside Linux, or track_owners outside Unix.
+        :raises RuntimeError: If the attach-time audit finds inconsistencies.
+        """
+
+    @classmethod
+    def create(
+        cls, name: str, element_size: int, capacity: int, **options: Any
+    ) -> Queue:
+        """Creates a new queue, failing if the name is taken.
+
+        :param options: Any other constructor argument except create and
+            mode.
+        :raises AlreadyExists: If a segment named name exists.
+        :raises TypeError: If options contain create or mode.
+        """
 
-        :raises ValueError: If element_size/capacity is missing when creating.
-        :raises OSError: If shared memory creation/opening fails.
+    @classmethod
+    def open(cls, name: str, **options: Any) -> Queue:
+        """Attaches to an existing queue, taking its layout from the header.
+
+        :param options: Any other constructor argument except create and
+            mode; element_size or capacity assert that the queue has them.
+        :raises OSError: If no queue named name exists.
+        :raises InvalidParameters: If the queue does not have the expected
+            element_size or capacity.
+        :raises TypeError: If options contain create or mode.
         """
 
     def put(
-        self, item: bytes | bytearray, timeout: float | None = None
+        self,
+        item: Buffer | Any,
+        timeout: float | None = None,
+        headers: bytes | None = None,
+        deadline: float | None = None,
+        retries: int = 0,
+        retry_backoff: float = 0.001,
     ) -> None:
         """Blocking enqueue operation.
 
         Blocks until space is available or the timeout expires.
 
-        :param item: Item to enqueue.
+        :param item: Item to enqueue: bytes, or any C-contiguous buffer such
+            as a memoryview or numpy array, copied straight into the slot; any
+            object with a serializer.
         :param timeout: Max wait time (seconds), None for indefinite.
+        :param headers: Headers stored with the message (needs headers_size).
+        :param deadline: Unix timestamp after which the message is stale
+            (needs the 'deadline' metadata field).
+        :param retries: Times to wait for another timeout after the queue
+            stayed full, sleeping a jittered backoff in between.
+        :param retry_backoff: Base backoff in seconds, doubled every retry.
 
-        :raises FullError: If queue remains full beyond timeout.
+        :raises FullError: If queue remains full beyond timeout and retries.
+        :raises QueueClosed: If the queue was shut down.
         """
 
-    def put_nowait(self, item: bytes | bytearray) -> None:
+    def put_nowait(
+        self,
+        item: Buffer | Any,
+        headers: bytes | None = None,
+        deadline: float | None = None,
+    ) -> None:
         """Non-blocking enqueue operation.
 
-        :param item: Item to enqueue.
+        :param item: Item to enqueue: bytes, or any C-contiguous buffer such
+            as a memoryview or numpy array, copied straight into the slot; any
+            object with a serializer.
+        :param headers: Headers stored with the message (needs headers_size).
+        :param deadline: Unix timestamp after which the message is stale
+            (needs the 'deadline' metadata field).
 
         :raises FullError: If the queue is full.
+        :raises QueueClosed: If the queue was shut down.
         """
 
-    def get(self, timeout: float | None = None) -> bytes:
+    def put_many(
+        self,
+        items: Sequence[Buffer | Any],
+        timeout: float | None = None,
+        retries: int = 0,
+        retry_backoff: float = 0.001,
+    ) -> int:
+        """Blocking batch enqueue operation.
+
+        Reserves as many slots as are free in one step and copies the items
+        into them without reacquiring the GIL in between. Messages carry no
+        headers or deadline.
+
+        If the timeout expires mid-batch, the items enqueued so far stay
+        enqueued and their count is returned, so producers can resume with
+        items[count:].
+
+        :param items: Items of exactly element_size bytes, or objects with a
+            serializer, enqueued in order.
+        :param timeout: Max wait time (seconds), None for indefinite.
+        :param retries: Times to wait for another timeout after the queue
+            stayed full, sleeping a jittered backoff in between.
+        :param retry_backoff: Base backoff in seconds, doubled every retry.
+
+        :return: The number of leading items enqueued, fewer than len(items)
+            only if the queue stayed full beyond timeout and every retry.
+
+        :raises ValueError: If any item has the wrong size or serializes to
+            more than a slot; nothing is enqueued.
+        """
+
+    def put_urgent(
+        self,
+        item: Buffer | Any,
+        timeout: float | None = None,
+        headers: bytes | None = None,
+        deadline: float | None = None,
+        retries: int = 0,
+        retry_backoff: float = 0.001,
+    ) -> None:
+        """Blocking enqueue onto the urgent lane, which get() drains first.
+
+        :param item: Item to enqueue: bytes, or any C-contiguous buffer such
+            as a memoryview or numpy array, copied straight into the slot; any
+            object with a serializer.
+        :param timeout: Maximum time to wait in seconds.
+        :param headers: Headers stored with the message (needs headers_size).
+        :param deadline: Unix timestamp after which the message is stale
+            (needs the 'deadline' metadata field).
+        :param retries: Times to wait for another timeout after the lane
+            stayed full, sleeping a jittered backoff in between.
+        :param retry_backoff: Base backoff in seconds, doubled every retry.
+
+        :raises ValueError: If the queue has no urgent lane.
+        :raises FullError: If the lane remains full beyond the timeout and
+            retries.
+        """
+
+    def get(
+        self,
+        timeout: float | None = None,
+        retries: int = 0,
+        retry_backoff: float = 0.001,
+    ) -> Any:
         """Blocking dequeue operation.
 
         Blocks until an item is available or timeout expires.
 
         :param timeout: Max wait time (seconds), None for indefinite.
+        :param retries: Times to wait for another timeout after the queue
+            stayed empty, sleeping a jittered backoff in between.
+        :param retry_backoff: Base backoff in seconds, doubled every retry.
 
-        :return: The dequeued item as bytes.
+        :return: The dequeued item as bytes, or the deserialized object with
+            a serializer.
 
         :raises Empty: If queue remains empty beyond timeout.
+        :raises QueueClosed: If the queue was shut down and is drained.
+        :raises CorruptMessage: If the item fails its checksum.
         """
 
-    def get_nowait(self) -> bytes:
+    def get_nowait(self) -> Any:
         """Non-blocking dequeue operation.
 
-        :return: The dequeued item as bytes.
+        :return: The dequeued item as bytes, or the deserialized object with
+            a serializer.
 
         :raises Empty: If the queue is empty.
+        :raises QueueClosed: If the queue was shut down and is drained.
+        :raises DecryptionError: If an encrypted item fails authentication.
+        :raises CorruptMessage: If the item fails its checksum.
+        """
+
+    def get_many(
+        self,
+        max_items: int,
+        timeout: float | None = None,
+        retries: int = 0,
+        retry_backoff: float = 0.001,
+    ) -> list[Any]:
+        """Blocking batch dequeue operation.
+
+        Waits until at least one item is available, then drains up to
+        max_items items without reacquiring the GIL in between.
+
+        :param max_items: Maximum number of items to return.
+        :param timeout: Max wait time for the first item (seconds), None for
+            indefinite.
+        :param retries: Times to wait for another timeout after the queue
+            stayed empty, sleeping a jittered backoff in between.
+        :param retry_backoff: Base backoff in seconds, doubled every retry.
+
+        :return: Between one and max_items items, urgent ones first.
+
+        :raises ValueError: If max_items is zero.
+        :raises Empty: If queue remains empty beyond timeout.
+        :raises DecryptionError: If an encrypted item fails authentication;
+            the rest of the batch is lost with it.
+        :raises CorruptMessage: If an item fails its checksum; the rest of
+            the batch is lost with it.
+        """
+
+    def drain(self) -> list[Any]:
+        """Dequeues every item queued when the call starts, without waiting.
+
+        Items enqueued while it runs may be left for later.
+
+        :return: The items, urgent ones first; empty if the queue was empty.
+
+        :raises DecryptionError: If an encrypted item fails authentication;
+            the other drained items are lost with it.
+        :raises CorruptMessage: If an item fails its checksum; the other
+            drained items are lost with it.
+        """
+
+    def clear(self) -> int:
+        """Discards every item queued when the call starts, without waiting.
+
+        :return: Number of items discarded.
+        """
+
+    def get_into(
+        self,
+        buffer: Buffer,
+        timeout: float | None = None,
+        retries: int = 0,
+        retry_backoff: float = 0.001,
+    ) -> int:
+        """Blocking dequeue operation into a caller-provided buffer.
+
+        :param buffer: Writable, C-contiguous buffer such as a bytearray, an
+            mmap or a numpy array, at least element_size bytes long.
+        :param timeout: Max wait time (seconds), None for indefinite.
+        :param retries: Times to wait for another timeout after the queue
+            stayed empty, sleeping a jittered backoff in between.
+        :param retry_backoff: Base backoff in seconds, doubled every retry.
+
+        :return: Number of bytes written, always element_size.
+
+        :raises ValueError: If the buffer is smaller than element_size.
+        :raises BufferError: If the buffer is read-only.
+        :raises Empty: If queue remains empty beyond timeout.
+        :raises QueueClosed: If the queue was shut down and drained.
+        """
+
+    def get_with_meta(
+        self,
+        timeout: float | None = None,
+        retries: int = 0,
+        retry_backoff: float = 0.001,
+    ) -> Message:
+        """Blocking dequeue operation returning the item with its metadata.
+
+        :param timeout: Max wait time (seconds), None for indefinite.
+        :param retries: Times to wait for another timeout after the queue
+            stayed empty, sleeping a jittered backoff in between.
+        :param retry_backoff: Base backoff in seconds, doubled every retry.
+
+        :return: The dequeued item and the metadata enabled at creation.
+
+        :raises Empty: If queue remains empty beyond timeout.
+        """
+
+    def put_array(
+        self,
+        array: Any,
+        timeout: float | None = None,
+        headers: bytes | None = None,
+        deadline: float | None = None,
+        retries: int = 0,
+        retry_backoff: float = 0.001,
+    ) -> None:
+        """Blocking enqueue of a numpy array, with its dtype and shape.
+
+        Copies the array data straight into a slot, after a header of 4
+        bytes, plus len(array.dtype.str), plus 8 bytes per dimension, rounded
+        up to a multiple of 16; both must fit in element_size.
+
+        :param array: Array to enqueue; made C-contiguous if needed.
+        :param timeout: Max wait time (seconds), None for indefinite.
+        :param headers: Headers stored with the message (needs headers_size).
+        :param deadline: Unix timestamp after which the message is stale
+            (needs the 'deadline' metadata field).
+        :param retries: Times to wait for another timeout after the queue
+            stayed full, sleeping a jittered backoff in between.
+        :param retry_backoff: Base backoff in seconds, doubled every retry.
+
+        :raises ValueError: If the array does not fit in a slot, or its dtype
+            holds Python objects or named fields.
+        :raises FullError: If queue remains full beyond timeout and retries.
+        :raises QueueClosed: If the queue was shut down.
+        """
+
+    def get_array(
+        self,
+        timeout: float | None = None,
+        retries: int = 0,
+        retry_backoff: float = 0.001,
+    ) -> Any:
+        """Blocking dequeue of an array enqueued by put_array().
+
+        :param timeout: Max wait time (seconds), None for indefinite.
+        :param retries: Times to wait for another timeout after the queue
+            stayed empty, sleeping a jittered backoff in between.
+        :param retry_backoff: Base backoff in seconds, doubled every retry.
+
+        :return: A writable numpy array with the dtype and shape that were put.
+
+        :raises ValueError: If the message was not put by put_array(); it is
+            consumed regardless.
+        :raises Empty: If queue remains empty beyond timeout.
+        :raises QueueClosed: If the queue was shut down and drained.
+        """
+
+    def put_stream(
+        self,
+        item: Buffer,
+        timeout: float | None = None,
+        retries: int = 0,
+        retry_backoff: float = 0.001,
+    ) -> None:
+        """Blocking enqueue of a payload of any size up to the capacity.
+
+        The payload is split into chunks of element_size - 8 bytes that
+        fill consecutive slots, reserved in one step so that chunks of
+        concurrent producers never interleave. Every consumer of the queue
+        must use get_stream().
+
+        :param item: The payload, any C-contiguous buffer.
+        :param timeout: Max wait time (seconds), None for indefinite.
+        :param retries: Times to wait for another timeout after the queue
+            stayed full, sleeping a jittered backoff in between.
+        :param retry_backoff: Base backoff in seconds, doubled every retry.
+
+        :raises ValueError: If the payload needs more slots than the
+            capacity.
+        :raises Full: If not enough slots free up before the timeout.
+        :raises QueueClosed: If the queue was shut down.
+        """
+
+    def get_stream(
+        self,
+        timeout: float | None = None,
+        retries: int = 0,
+        retry_backoff: float = 0.001,
+    ) -> bytes:
+        """Blocking dequeue of a payload enqueued by put_stream().
+
+        Reads the main lane only.
+
+        :param timeout: Max wait time (seconds), None for indefinite.
+        :param retries: Times to wait for another timeout after the queue
+            stayed empty, sleeping a jittered backoff in between.
+        :param retry_backoff: Base backoff in seconds, doubled every retry.
+
+        :return: The reassembled payload.
+
+        :raises ValueError: If the message was not put by put_stream(); it
+            is consumed regardless.
+        :raises Empty: If no complete payload arrives before the timeout.
+        :raises QueueClosed: If the queue was shut down and drained.
+        """
+
+    def put_tensor(
+        self,
+        tensor: Any,
+        timeout: float | None = None,
+        headers: bytes | None = None,
+        deadline: float | None = None,
+        retries: int = 0,
+        retry_backoff: float = 0.001,
+    ) -> None:
+        """Blocking enqueue of a handle to the storage of a PyTorch tensor.
+
+        CPU tensors are moved into shared memory unless they already are;
+        CUDA tensors travel as CUDA IPC handles and must be kept alive by the
+        producer while consumers use them. Unrelated processes need the
+        'file_system' sharing strategy for CPU tensors.
+
+        :param tensor: The torch.Tensor to share.
+        :param timeout: Max wait time (seconds), None for indefinite.
+        :param headers: Headers stored with the message; requires
+            headers_size.
+        :param deadline: Unix timestamp after which the message is stale;
+            requires the 'deadline' metadata field.
+        :param retries: Times to wait for another timeout after the queue
+            stayed full, sleeping a jittered backoff in between.
+        :param retry_backoff: Base backoff in seconds, doubled every retry.
+
+        :raises ImportError: If PyTorch is not installed.
+        :raises TypeError: If tensor is not a torch.Tensor.
+        :raises ValueError: If the handle does not fit in a slot.
+        :raises Full: If queue remains full beyond timeout.
+        :raises QueueClosed: If the queue was shut down.
+        """
+
+    def get_tensor(
+        self,
+        timeout: float | None = None,
+        retries: int = 0,
+        retry_backoff: float = 0.001,
+    ) -> Any:
+        """Blocking dequeue of a tensor enqueued by put_tensor().
+
+        :param timeout: Max wait time (seconds), None for indefinite.
+        :param retries: Times to wait for another timeout after the queue
+            stayed empty, sleeping a jittered backoff in between.
+        :param retry_backoff: Base backoff in seconds, doubled every retry.
+
+        :return: A torch.Tensor sharing the memory of the tensor that was put.
+
+        :raises ValueError: If the message was not put by put_tensor(); it is
+            consumed regardless.
+        :raises ImportError: If PyTorch is not installed.
+        :raises Empty: If queue remains empty beyond timeout.
+        :raises QueueClosed: If the queue was shut down and drained.
+        """
+
+    def reserve(
+        self,
+        timeout: float | None = None,
+        headers: bytes | None = None,
+        deadline: float | None = None,
+        retries: int = 0,
+        retry_backoff: float = 0.001,
+    ) -> SlotView:
+        """Blocking zero-copy enqueue operation.
+
+        Returns a writable view of a reserved slot. Consumers cannot get past
+        it until it is committed; an uncommitted view that is garbage
+        collected is published as it stands.
+
+        :param timeout: Max wait time (seconds), None for indefinite.
+        :param headers: Headers stored with the message; requires
+            headers_size.
+        :param deadline: Unix timestamp after which the message is stale.
+        :param retries: Times to wait for another timeout after the queue
+            stayed full, sleeping a jittered backoff in between.
+        :param retry_backoff: Base backoff in seconds, doubled every retry.
+
+        :return: A writable view of the reserved payload.
+
+        :raises ValueError: If the queue is encrypted.
+        :raises Full: If queue remains full beyond timeout.
+        """
+
+    def commit(self, view: SlotView) -> None:
+        """Publishes a slot reserved by reserve() to consumers.
+
+        :raises ValueError: If view was not reserved by this handle.
+        :raises BufferError: If memoryviews of the payload are still alive.
+        :raises RuntimeError: If the slot was reclaimed.
+        """
+
+    def get_buffer(
+        self,
+        timeout: float | None = None,
+        retries: int = 0,
+        retry_backoff: float = 0.001,
+    ) -> SlotView:
+        """Blocking zero-copy dequeue operation.
+
+        The payload stays in shared memory, and its slot reserved, until the
+        returned view is released.
+
+        :param timeout: Max wait time (seconds), None for indefinite.
+        :param retries: Times to wait for another timeout after the queue
+            stayed empty, sleeping a jittered backoff in between.
+        :param retry_backoff: Base backoff in seconds, doubled every retry.
+
+        :return: A view of the dequeued payload.
+
+        :raises ValueError: If the queue is encrypted.
+        :raises Empty: If queue remains empty beyond timeout.
+        :raises CorruptMessage: If the payload fails its checksum; its slot
+            is released.
+        """
+
+    def rotate_key(self, key_id: int, key: bytes | None = None) -> None:
+        """Switches the key used to encrypt new messages.
+
+        :param key_id: Id of the key to activate.
+        :param key: 32-byte key to register under key_id first.
+
+        :raises ValueError: If the queue is not encrypted or the key is
+            unknown or malformed.
+        """
+
+    def health(self) -> Health:
+        """Reports slots reserved by producers but never published.
+
+        Stall durations are measured from the first time this handle observed
+        the stall, so call it periodically.
+
+        :raises ValueError: If the queue was created without poison_timeout.
+        """
+
+    def repair(self) -> list[tuple[Literal['main', 'urgent'], int, int]]:
+        """Reclaims the slots that producers reserved but died before
+        publishing.
+
+        Consumers with skip_poisoned reclaim such slots on their own. A slot
+        is only reclaimed at the head of its lane once its producer has
+        exited.
+
+        :return: The lane, position and producer process id of every slot
+            reclaimed.
+
+        :raises ValueError: If the queue was created without track_owners.
+        """
+
+    def attached_processes(self) -> list[AttachedProcess]:
+        """Lists the processes with handles registered in the queue header.
+
+        A process that exits without closing its handles stays listed, as not
+        alive, until its entry is reused. Handles inherited through fork()
+        register for the child on their first use there. Up to 16 handles are
+        listed, and liveness is only checked on Unix.
+
+        :return: The processes, ordered by process id.
+        """
+
+    def audit(self, fix: bool = False) -> AuditReport:
+        """Audits the cell sequence numbers against the header positions.
+
+        :param fix: Reset inconsistent cells to their expected value.
+
+        :return: The audit report.
         """
 
+    @property
+    def name(self) -> str:
+        """Shared memory segment name, also available after close()."""
+
     @property
     def element_size(self) -> int:
         """Size of a single element in bytes."""
@@ -74,6 +1727,64 @@ class Queue:
     def maxsize(self) -> int:
         """Maximum number of elements the queue can hold."""
 
+    def stats(self) -> dict[str, int | float]:
+        """Returns the counters collected by this queue handle.
+
+        Besides spin_count, yield_count, park_count, throttled, expired,
+        poison_skipped, reclaimed and retries, reports put_/get_ waits,
+        timeouts, wait_time and max_wait (in seconds) for blocking calls that
+        waited.
+        """
+
+    def handle_stats(self) -> HandleStats:
+        """Reports how this handle's wait loops spent their time.
+
+        Spinning and yielding keep a core busy; parking, which includes
+        sleeps and backpressure delays, does not. busy_fraction is the share
+        of wait time spent spinning or yielding.
+        """
+
+    def configure(
+        self,
+        *,
+        wait: Literal['spin', 'hybrid', 'sleep'] = ...,
+        busy_spin: bool = ...,
+        adaptive: bool = ...,
+        spin_limit: int = ...,
+        yield_limit: int = ...,
+        park_interval: float = ...,
+        backpressure: Literal['linear', 'exponential'] | None = ...,
+        backpressure_base: float = ...,
+        backpressure_max: float = ...,
+        slow_op_threshold: float | None = ...,
+        drop_expired: bool = ...,
+    ) -> RuntimeOptions:
+        """Changes runtime options for every process attached to the queue.
+
+        Options are stored in the shared header and picked up by every handle
+        at its next operation, taking precedence over constructor options.
+        Options not given keep their current values; without arguments the
+        current options are only returned.
+
+        :return: The options in effect after the call.
+
+        :raises ValueError: For unknown options or invalid values.
+        """
+
+    def debug_info(self) -> DebugInfo:
+        """Describes the layout of the attached queue, including the
+        effective slot stride."""
+
+    def __repr__(self) -> str:
+        """Shows the name, element size, capacity and closed state."""
+
+    def qsize(self) -> int:
+        """Returns the approximate number of elements in the queue.
+
+        The count includes the urgent lane and may be outdated by the time
+        it is used, as other processes keep enqueuing and dequeuing.
+        """
+
     def full(self) -> bool:
         """Returns True if the queue is full."""
 
@@ -86,5 +1797,106 @@ class Queue:
     def __bool__(self) -> bool:
         """Returns True if the queue is not empty."""
 
-    def close(self) -> None:
-        """Closes the queue and releases the shared memory segment."""
+    def wait_readable(self, timeout: float | None = None) -> bool:
+        """Waits until the queue holds a message, without dequeuing it.
+
+        Other consumers may take the message first, so retry get_nowait()
+        and wait again on Empty.
+
+        :param timeout: Maximum time to wait in seconds.
+        :return: Whether the queue held a message before the timeout.
+        """
+
+    def wait_writable(self, timeout: float | None = None) -> bool:
+        """Waits until the main lane has a free slot, without enqueuing.
+
+        Other producers may take the slot first, so retry put_nowait() and
+        wait again on Full.
+
+        :param timeout: Maximum time to wait in seconds.
+        :return: Whether a slot was free before the timeout.
+        """
+
+    def segment_fd(self) -> int:
+        """Returns the descriptor of a memfd or file queue, or one attached
+        through fd, for other processes to attach to.
+
+        It belongs to this handle: send it with socket.send_fds() instead of
+        closing it.
+
+        :raises ValueError: If the queue is a named segment.
+        """
+
+    def fileno(self) -> int:
+        """Returns a file descriptor readable while the queue holds messages.
+
+        Register it with selectors, select, poll or epoll next to sockets. It
+        belongs to this handle and is maintained by a background thread until
+        the handle is closed; do not read from or close it.
+
+        :raises OSError: If the descriptor cannot be created.
+        :raises NotImplementedError: On platforms without Unix sockets.
+        """
+
+    def write_fileno(self) -> int:
+        """Returns a file descriptor readable while the main lane has a free
+        slot; the writer-side counterpart of fileno().
+
+        :raises OSError: If the descriptor cannot be created.
+        :raises NotImplementedError: On platforms without Unix sockets.
+        """
+
+    def totals(self) -> Totals:
+        """Returns how many messages went through the queue since it was
+        created, counted across every attached process and both lanes.
+
+        Slots claimed by producers or consumers that are still copying are
+        already counted.
+        """
+
+    def __enter__(self) -> Queue:
+        """Returns the queue, which is closed when the with block exits."""
+
+    def __exit__(self, *args: object) -> bool:
+        """Closes the queue; exceptions are not suppressed."""
+
+    def task_done(self) -> None:
+        """Marks a message returned by a get as processed.
+
+        Works like queue.Queue.task_done(), with the count of unfinished
+        messages shared by every process.
+
+        :raises ValueError: If called more times than messages were enqueued.
+        """
+
+    def join(self, timeout: float | None = None) -> bool:
+        """Waits until every message enqueued so far was marked done.
+
+        Messages discarded by clear(), as expired, or as abandoned count as
+        done.
+
+        :param timeout: Max wait time (seconds), None for indefinite.
+        :return: Whether every message was done before the timeout.
+        """
+
+    def cancel(self) -> None:
+        """Cancels the blocking operations of this handle.
+
+        Lets another thread stop a worker waiting in put() or get() without
+        a timeout. Operations waiting now and any started later raise
+        Cancelled, as do wait_readable() and wait_writable(); non-blocking
+        operations and other handles on the queue are unaffected.
+        """
+
+    def close(self, shutdown: bool | None = None) -> None:
+        """Closes the queue and releases the shared memory segment.
+
+        A shutdown also closes the queue for every other handle, in any
+        process: their puts raise QueueClosed, and their gets return the
+        remaining messages, then raise QueueClosed instead of waiting.
+
+        :param shutdown: Whether to shut the queue down; defaults to whether
+            this handle created the queue.
+        :raises BufferError: If views returned by get_buffer() or reserve()
+            are still open.
+        """